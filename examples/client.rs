@@ -115,6 +115,18 @@ fn main() {
             EngineEvent::ResendRequested(connection_id,range) => {
                 println!("({})Received ResendRequest for messages where {} <= MsgSeqNum < {}",connection_id,range.start,range.end);
             },
+            //Response passed to send_resend_response() was queued to be sent.
+            EngineEvent::ResendCompleted(connection_id,summary) => {
+                println!("({})Resent {} messages and {} gap fills for ResendRequest {:?}",connection_id,summary.messages_resent,summary.gap_fills_sent,summary.requested);
+            },
+            //Remote included a SessionStatus in a Logon or Logout message.
+            EngineEvent::SessionStatusReceived(data) => {
+                println!("({})Session status: {:?} {}",data.connection,data.session_status,String::from_utf8_lossy(&data.text));
+            },
+            //Queued messages were dropped while responding to a Logout.
+            EngineEvent::MessagesNotSent(connection_id,messages) => {
+                println!("({}){} messages were not sent",connection_id,messages.len());
+            },
             //Connection received a SequenceReset-Reset message where NewSeqNo is set to the same
             //number as the expected MsgSeqNum.
             EngineEvent::SequenceResetResetHasNoEffect(connection_id) => {
@@ -132,10 +144,12 @@ fn main() {
             },
             //The following events are not used for client connections.
             EngineEvent::ConnectionDropped(_,_) |
-            EngineEvent::ConnectionAccepted(_,_,_) |
-            EngineEvent::ConnectionLoggingOn(_,_,_) |
+            EngineEvent::ConnectionAccepted(_) |
+            EngineEvent::ConnectionLoggingOn(_) |
             EngineEvent::ListenerFailed(_,_) |
-            EngineEvent::ListenerAcceptFailed(_,_) => {}
+            EngineEvent::ListenerAcceptFailed(_,_) => {},
+            //Events added in the future.
+            _ => {},
         }
     }
 }
@@ -46,8 +46,8 @@ fn main() {
     while let Some(event) = server.poll(timeout_duration) {
         match event {
             //Listener accepted a new connection and is awaiting a Logon message.
-            EngineEvent::ConnectionAccepted(listener_id,connection_id,addr) => {
-                println!("({},{})New connection accepted: {}",listener_id,connection_id,addr);
+            EngineEvent::ConnectionAccepted(data) => {
+                println!("({},{})New connection accepted: {}",data.listener,data.connection,data.address);
 
                 //The connection can be rejected here if the addr is blacklisted/not whitelisted or
                 //if over capacity.
@@ -59,7 +59,9 @@ fn main() {
                 println!("({})New connection was dropped: {}",listener_id,addr);
             },
             //Connection sent a Logon message and is awaiting approval or rejection.
-            EngineEvent::ConnectionLoggingOn(listener_id,connection_id,logon) => {
+            EngineEvent::ConnectionLoggingOn(data) => {
+                let connection_id = data.connection;
+                let logon = data.message;
                 if logon.username == b"some_user" &&
                    logon.password == b"some_password" {
                     let mut response_logon = Logon::new();
@@ -130,6 +132,18 @@ fn main() {
                 response.push(ResendResponse::Gap(range));
                 server.send_resend_response(connection_id,response);
             },
+            //Response passed to send_resend_response() was queued to be sent.
+            EngineEvent::ResendCompleted(connection_id,summary) => {
+                println!("({})Resent {} messages and {} gap fills for ResendRequest {:?}",connection_id,summary.messages_resent,summary.gap_fills_sent,summary.requested);
+            },
+            //Remote included a SessionStatus in a Logon or Logout message.
+            EngineEvent::SessionStatusReceived(data) => {
+                println!("({})Session status: {:?} {}",data.connection,data.session_status,String::from_utf8_lossy(&data.text));
+            },
+            //Queued messages were dropped while responding to a Logout.
+            EngineEvent::MessagesNotSent(connection_id,messages) => {
+                println!("({}){} messages were not sent",connection_id,messages.len());
+            },
             //Connection received a SequenceReset-Reset message where NewSeqNo is set to the same
             //number as the expected MsgSeqNum.
             EngineEvent::SequenceResetResetHasNoEffect(connection_id) => {
@@ -150,6 +164,8 @@ fn main() {
             EngineEvent::ConnectionSucceeded(_) |
             EngineEvent::ConnectionFailed(_,_) |
             EngineEvent::SessionEstablished(_) => {},
+            //Events added in the future.
+            _ => {},
         }
     }
 }
@@ -117,24 +117,11 @@ pub fn build_message(input: TokenStream) -> TokenStream {
             }
         }
 
-        pub struct #build_message_name {
-            cache: message::BuildMessageInternalCache,
-        }
+        pub struct #build_message_name;
 
         impl #build_message_name {
             fn new() -> #build_message_name {
-                #build_message_name {
-                    cache: message::BuildMessageInternalCache {
-                        fields_fix40: None,
-                        fields_fix41: None,
-                        fields_fix42: None,
-                        fields_fix43: None,
-                        fields_fix44: None,
-                        fields_fix50: None,
-                        fields_fix50sp1: None,
-                        fields_fix50sp2: None,
-                    },
-                }
+                #build_message_name
             }
 
             fn new_into_box() -> Box<message::BuildMessage + Send> {
@@ -151,27 +138,8 @@ pub fn build_message(input: TokenStream) -> TokenStream {
                 #message_name::field_count(version)
             }
 
-            fn fields(&mut self,version: message_version::MessageVersion) -> message::FieldHashMap {
-                fn get_or_set_fields(option_fields: &mut Option<message::FieldHashMap>,
-                                     version: message_version::MessageVersion) -> message::FieldHashMap {
-                    if option_fields.is_none() {
-                        let fields = #message_name::fields(version);
-                        *option_fields = Some(fields);
-                    }
-
-                    option_fields.as_ref().unwrap().clone()
-                }
-
-                match version {
-                    message_version::MessageVersion::FIX40 => get_or_set_fields(&mut self.cache.fields_fix40,version),
-                    message_version::MessageVersion::FIX41 => get_or_set_fields(&mut self.cache.fields_fix41,version),
-                    message_version::MessageVersion::FIX42 => get_or_set_fields(&mut self.cache.fields_fix42,version),
-                    message_version::MessageVersion::FIX43 => get_or_set_fields(&mut self.cache.fields_fix43,version),
-                    message_version::MessageVersion::FIX44 => get_or_set_fields(&mut self.cache.fields_fix44,version),
-                    message_version::MessageVersion::FIX50 => get_or_set_fields(&mut self.cache.fields_fix50,version),
-                    message_version::MessageVersion::FIX50SP1 => get_or_set_fields(&mut self.cache.fields_fix50sp1,version),
-                    message_version::MessageVersion::FIX50SP2 => get_or_set_fields(&mut self.cache.fields_fix50sp2,version),
-                }
+            fn fields(&self,version: message_version::MessageVersion) -> message::FieldHashMap {
+                #message_name::fields(version)
             }
 
             fn required_fields(&self,version: message_version::MessageVersion) -> message::FieldHashSet {
@@ -99,6 +99,10 @@ impl FIXTMessage for Heartbeat {
         Box::new(Heartbeat::new())
     }
 
+    fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+        Box::new(self.clone())
+    }
+
     fn msg_type(&self) -> &'static [u8] {
         b"0"
     }
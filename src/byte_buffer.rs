@@ -66,6 +66,20 @@ impl ByteBuffer {
         self.valid_bytes_end = self.bytes.len();
     }
 
+    pub fn append_all<F,R>(&mut self,append_all_func: F) -> R
+        where F: FnOnce(&mut Vec<u8>) -> R {
+        //Drop the consumed bytes so new bytes are appended right after the valid ones without
+        //copying them out first.
+        self.bytes.truncate(self.valid_bytes_end);
+        self.bytes.drain(..self.valid_bytes_begin);
+        let result = append_all_func(&mut self.bytes);
+
+        self.valid_bytes_begin = 0;
+        self.valid_bytes_end = self.bytes.len();
+
+        result
+    }
+
     pub fn consume(&mut self,count: usize) {
         assert!(self.valid_bytes_begin + count <= self.valid_bytes_end);
         self.valid_bytes_begin += count;
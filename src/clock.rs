@@ -0,0 +1,116 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono;
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::sync::{Arc,Mutex};
+use std::time::{Duration,Instant};
+
+//Source of time for the engine. Every timeout (Heartbeats, TestRequests, logout and logon limits,
+//resend pacing, etc.) and message time-to-live is measured with it. Set with
+//EngineBuilder::clock(). Defaults to SystemClock.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+    fn utc_now(&self) -> DateTime<UTC>;
+
+    fn follows_real_time(&self) -> bool {
+        //Whether time moves forward on its own. When false, the engine never waits on a timeout
+        //and instead relies on on_advance() to find out when time has moved.
+        true
+    }
+
+    fn on_advance(&self,_callback: Box<Fn() + Send>) {
+        //Register a function to be called every time the clock jumps forward. Clocks that follow
+        //real time never jump so they can ignore it.
+    }
+}
+
+//Follows the operating system's clock.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<UTC> {
+        UTC::now()
+    }
+}
+
+struct SimulatedClockState {
+    elapsed: Duration,
+    callbacks: Vec<Box<Fn() + Send>>,
+}
+
+//Stands still until advance() is called. Useful for testing timeouts without having to wait for
+//them in real time. Starts at the time it was created. SimulatedClock is a cheap handle that can be
+//cloned so the test can keep one while the engine uses another.
+#[derive(Clone)]
+pub struct SimulatedClock {
+    start: Instant,
+    start_utc: DateTime<UTC>,
+    state: Arc<Mutex<SimulatedClockState>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> SimulatedClock {
+        SimulatedClock {
+            start: Instant::now(),
+            start_utc: UTC::now(),
+            state: Arc::new(Mutex::new(SimulatedClockState {
+                elapsed: Duration::from_secs(0),
+                callbacks: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    pub fn advance(&self,duration: Duration) {
+        //Move time forward and wake up everything waiting on the clock so expired timeouts are
+        //handled. Handling happens on the engine's thread so it might not be finished by the time
+        //this returns.
+        let state = &mut *self.state.lock().unwrap();
+        state.elapsed += duration;
+        for callback in &state.callbacks {
+            callback();
+        }
+    }
+}
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        SimulatedClock::new()
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn utc_now(&self) -> DateTime<UTC> {
+        self.start_utc + chrono::Duration::from_std(self.elapsed()).unwrap_or(chrono::Duration::zero())
+    }
+
+    fn follows_real_time(&self) -> bool {
+        false
+    }
+
+    fn on_advance(&self,callback: Box<Fn() + Send>) {
+        self.state.lock().unwrap().callbacks.push(callback);
+    }
+}
@@ -14,14 +14,16 @@ use chrono::datetime::DateTime;
 use chrono::offset::utc::UTC;
 use chrono::naive::datetime::NaiveDateTime;
 use std::any::Any;
+use std::cell::Cell;
 use std::marker::PhantomData;
 use std::io::Write;
 use std::str::FromStr;
 
 use constant::VALUE_END;
+use field_tag::FieldTag;
 use field_type::FieldType;
 use fix_version::FIXVersion;
-use message::{Message,MessageBuildable,SetValueError};
+use message::{Message,MessageBuildable,MessageDetails,MessageMetadata,MessageVisitor,SetValueError};
 use message_version::MessageVersion;
 use rule::Rule;
 
@@ -562,6 +564,10 @@ impl FieldType for DataFieldType {
         Default::default()
     }
 
+    fn reset_value(field: &mut Self::Type) {
+        field.clear();
+    }
+
     fn set_value(field: &mut Self::Type,bytes: &[u8]) -> Result<(),SetValueError> {
         field.resize(bytes.len(),0);
         field.copy_from_slice(bytes);
@@ -908,6 +914,10 @@ impl FieldType for StringFieldType {
         Default::default()
     }
 
+    fn reset_value(field: &mut Self::Type) {
+        field.clear();
+    }
+
     fn set_value(field: &mut Self::Type,bytes: &[u8]) -> Result<(),SetValueError> {
         field.clear();
         field.extend_from_slice(bytes);
@@ -931,7 +941,7 @@ pub struct RepeatingGroupFieldType<T: Message + PartialEq> {
     message_type: PhantomData<T>,
 }
 
-impl<T: Message + MessageBuildable + Any + Clone + Default + PartialEq + Send + Sized> FieldType for RepeatingGroupFieldType<T> {
+impl<T: Message + MessageBuildable + MessageDetails + Any + Clone + Default + PartialEq + Send + Sized> FieldType for RepeatingGroupFieldType<T> {
     type Type = Vec<Box<T>>;
 
     fn rule() -> Option<Rule> {
@@ -943,6 +953,10 @@ impl<T: Message + MessageBuildable + Any + Clone + Default + PartialEq + Send +
         Default::default()
     }
 
+    fn reset_value(field: &mut Self::Type) {
+        field.clear();
+    }
+
     fn set_groups(field: &mut Self::Type,mut groups: Vec<Box<Message>>) -> bool {
         field.clear();
 
@@ -961,6 +975,10 @@ impl<T: Message + MessageBuildable + Any + Clone + Default + PartialEq + Send +
         true
     }
 
+    fn group_metadata() -> Option<MessageMetadata> {
+        Some(<T as MessageDetails>::metadata())
+    }
+
     fn is_empty(field: &Self::Type) -> bool {
         field.is_empty()
     }
@@ -982,6 +1000,16 @@ impl<T: Message + MessageBuildable + Any + Clone + Default + PartialEq + Send +
 
         result
     }
+
+    fn visit(field: &Self::Type,fix_version: FIXVersion,message_version: MessageVersion,tag: FieldTag,name: &'static str,visitor: &mut MessageVisitor) {
+        visitor.begin_group(tag,name,field.len());
+        for (index,group) in field.iter().enumerate() {
+            visitor.begin_group_entry(index);
+            group.visit(fix_version,message_version,visitor);
+            visitor.end_group_entry(index);
+        }
+        visitor.end_group(tag,name);
+    }
 }
 
 pub struct UTCTimeOnlyFieldType;
@@ -1046,21 +1074,70 @@ impl FieldType for UTCTimeOnlyFieldType {
     }
 }
 
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum TimestampPrecision {
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+}
+
+impl TimestampPrecision {
+    fn digits(&self) -> usize {
+        match *self {
+            TimestampPrecision::Seconds => 0,
+            TimestampPrecision::Milliseconds => 3,
+            TimestampPrecision::Microseconds => 6,
+            TimestampPrecision::Nanoseconds => 9,
+        }
+    }
+
+    fn truncate(&self,nanoseconds: u32) -> u32 {
+        let divisor = match *self {
+            TimestampPrecision::Seconds => 1_000_000_000,
+            TimestampPrecision::Milliseconds => 1_000_000,
+            TimestampPrecision::Microseconds => 1_000,
+            TimestampPrecision::Nanoseconds => 1,
+        };
+
+        nanoseconds - nanoseconds % divisor
+    }
+}
+
+//Precision used by UTCTimestampFieldType on the current thread. See
+//UTCTimestampFieldType::with_precision().
+thread_local!(static TIMESTAMP_PRECISION: Cell<TimestampPrecision> = Cell::new(TimestampPrecision::Milliseconds));
+
 pub struct UTCTimestampFieldType;
 
 impl UTCTimestampFieldType {
     pub fn new_now() -> <UTCTimestampFieldType as FieldType>::Type {
         let spec = ::time::get_time();
 
-        //Strip nanoseconds so only whole milliseconds remain (with truncation based rounding).
-        //This is because UTCTimestamp does not support sub-millisecond precision.
-        let mut nsec = spec.nsec as u32;
-        nsec -= nsec % 1_000_000;
+        //Strip the digits that won't be serialized (with truncation based rounding) so the
+        //timestamp compares equal to itself after a round trip.
+        let nsec = UTCTimestampFieldType::precision().truncate(spec.nsec as u32);
 
         let naive = NaiveDateTime::from_timestamp(spec.sec,nsec);
         DateTime::from_utc(naive,UTC)
     }
 
+    pub fn precision() -> TimestampPrecision {
+        TIMESTAMP_PRECISION.with(|precision| precision.get())
+    }
+
+    pub fn with_precision<T,F: FnOnce() -> T>(precision: TimestampPrecision,func: F) -> T {
+        //Timestamps created with new_now() and serialized within func use the given precision
+        //instead of the default milliseconds. The precision has to be carried on the thread
+        //because FieldType::read() has no way to know which connection a message is for.
+        let previous_precision = UTCTimestampFieldType::precision();
+        TIMESTAMP_PRECISION.with(|thread_precision| thread_precision.set(precision));
+        let result = func();
+        TIMESTAMP_PRECISION.with(|thread_precision| thread_precision.set(previous_precision));
+
+        result
+    }
+
     pub fn new_empty() -> <UTCTimestampFieldType as FieldType>::Type {
         //Create a new time stamp that can be considered empty. An Option<_> might be preferred
         //but that would make using the timestamp needlessly complicated.
@@ -1089,15 +1166,19 @@ impl FieldType for UTCTimestampFieldType {
         let hours = try!(slice_to_int::<u32>(&bytes[9..11]));
         let minutes = try!(slice_to_int::<u32>(&bytes[12..14]));
         let seconds = try!(slice_to_int::<u32>(&bytes[15..17]));
-        let milliseconds = if bytes.len() == 17 {
+        //Accept any number of fractional second digits up to nanoseconds regardless of the
+        //precision used for outbound timestamps. Counterparties don't all agree on one.
+        let nanoseconds = if bytes.len() == 17 {
             0
         }
-        else if bytes.len() == 21 {
+        else if bytes.len() >= 19 && bytes.len() <= 27 {
             if bytes[17] != b'.' {
                 return Err(SetValueError::WrongFormat);
             }
 
-            try!(slice_to_int::<u32>(&bytes[18..21]))
+            let fraction_digits = bytes.len() - 18;
+            let fraction = try!(slice_to_int::<u32>(&bytes[18..]));
+            fraction * 10u32.pow(9 - fraction_digits as u32)
         }
         else {
             return Err(SetValueError::WrongFormat);
@@ -1105,7 +1186,7 @@ impl FieldType for UTCTimestampFieldType {
 
         *field = DateTime::<UTC>::from_utc(
             NaiveDate::from_ymd(year,month,day)
-                       .and_hms_milli(hours,minutes,seconds,milliseconds),
+                       .and_hms_nano(hours,minutes,seconds,nanoseconds),
             UTC
         );
 
@@ -1123,19 +1204,28 @@ impl FieldType for UTCTimestampFieldType {
     fn read(field: &Self::Type,_fix_version: FIXVersion,_message_version: MessageVersion,buf: &mut Vec<u8>) -> usize {
         assert!(!Self::is_empty(&field)); //Was required field not set?
 
-        buf.reserve(21);
+        let precision = UTCTimestampFieldType::precision();
+        let digits = precision.digits();
+        buf.reserve(27);
         let naive_utc = field.naive_utc();
         write!(buf,
-               "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+               "{:04}{:02}{:02}-{:02}:{:02}:{:02}",
                naive_utc.year(),
                naive_utc.month(),
                naive_utc.day(),
                naive_utc.hour(),
                naive_utc.minute(),
-               naive_utc.second(),
-               naive_utc.nanosecond() / 1_000_000).unwrap();
+               naive_utc.second()).unwrap();
+        if digits == 0 {
+            return 17;
+        }
+
+        //Leap seconds are represented with nanoseconds past 1,000,000,000. Clamp them to the last
+        //representable fraction instead of overflowing the field width.
+        let nanoseconds = precision.truncate(::std::cmp::min(naive_utc.nanosecond(),999_999_999));
+        write!(buf,".{:0width$}",nanoseconds / 10u32.pow(9 - digits as u32),width = digits).unwrap();
 
-        21
+        18 + digits
     }
 }
 
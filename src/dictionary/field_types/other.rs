@@ -62,6 +62,75 @@ impl FieldType for ApplVerIDFieldType {
     }
 }
 
+define_enum_field_type!(
+    FIELD AdvSide {
+        Buy => b"B",
+        Sell => b"S",
+        Trade => b"T",
+        Cross => b"X",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] AdvSideFieldType
+);
+
+define_enum_field_type!(
+    FIELD AdvTransType {
+        New => b"N",
+        Cancel => b"C",
+        Replace => b"R",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_STRING] AdvTransTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD AffirmStatus {
+        Received => b"1",
+        ConfirmRejected => b"2",
+        Affirmed => b"3",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] AffirmStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD AllocRejCode {
+        UnknownAccount => b"0",
+        IncorrectQuantity => b"1",
+        IncorrectAveragePrice => b"2",
+        UnknownExecutingBrokerMnemonic => b"3",
+        CommissionDifference => b"4",
+        UnknownOrderID => b"5",
+        UnknownListID => b"6",
+        Other => b"7",
+        IncorrectAllocatedQuantity => b"8",
+        CalculationDifference => b"9",
+        UnknownOrStaleExecID => b"10",
+        MismatchedData => b"11",
+        UnknownClOrdID => b"12",
+        WarehouseRequestRejected => b"13",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] AllocRejCodeFieldType
+);
+
+define_enum_field_type!(
+    FIELD AllocStatus {
+        Accepted => b"0",
+        BlockLevelReject => b"1",
+        AccountLevelReject => b"2",
+        Received => b"3",
+        Incomplete => b"4",
+        RejectedByIntermediary => b"5",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] AllocStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD BidType {
+        NonDisclosed => b"1",
+        Disclosed => b"2",
+        NoBiddingProcess => b"3",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] BidTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD BusinessRejectReason {
         Other => b"0",
@@ -77,6 +146,79 @@ define_enum_field_type!(
     FIELD_TYPE [REQUIRED,MUST_BE_INT] BusinessRejectReasonFieldType
 );
 
+define_enum_field_type!(
+    FIELD CollAsgnReason {
+        Initial => b"0",
+        Scheduled => b"1",
+        TimeWarning => b"2",
+        MarginDeficiency => b"3",
+        MarginExcess => b"4",
+        ForwardCollateralDemand => b"5",
+        EventOfDefault => b"6",
+        AdverseTaxEvent => b"7",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CollAsgnReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD CollAsgnRejectReason {
+        UnknownDeal => b"0",
+        UnknownOrInvalidInstrument => b"1",
+        UnauthorizedTransaction => b"2",
+        InsufficientCollateral => b"3",
+        InvalidTypeOfCollateral => b"4",
+        ExcessiveSubstitution => b"5",
+        Other => b"99",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] CollAsgnRejectReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD CollAsgnRespType {
+        Received => b"0",
+        Accepted => b"1",
+        Declined => b"2",
+        Rejected => b"3",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CollAsgnRespTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD CollAsgnTransType {
+        New => b"0",
+        Replace => b"1",
+        Cancel => b"2",
+        Release => b"3",
+        Reverse => b"4",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CollAsgnTransTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD CollInquiryQualifier {
+        TradeDate => b"0",
+        GCInstrument => b"1",
+        CollateralInstrument => b"2",
+        SubstitutionEligible => b"3",
+        NotAssigned => b"4",
+        PartiallyAssigned => b"5",
+        FullyAssigned => b"6",
+        OutstandingTrades => b"7",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CollInquiryQualifierFieldType
+);
+
+define_enum_field_type!(
+    FIELD CollStatus {
+        Unassigned => b"0",
+        PartiallyAssigned => b"1",
+        AssignmentProposed => b"2",
+        Assigned => b"3",
+        Challenged => b"4",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CollStatusFieldType
+);
+
 define_enum_field_type!(
     FIELD ComplexEventCondition {
         And => b"1",
@@ -120,6 +262,44 @@ define_enum_field_type!(
     FIELD_TYPE [REQUIRED,MUST_BE_INT] ComplexEventTypeFieldType
 );
 
+define_enum_field_type!(
+    FIELD ConfirmRejReason {
+        MismatchedAccount => b"1",
+        MissingSettlementInstructions => b"2",
+        Other => b"99",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] ConfirmRejReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD ConfirmStatus {
+        Received => b"1",
+        MismatchedAccount => b"2",
+        MissingSettlementInstructions => b"3",
+        Confirmed => b"4",
+        RequestRejected => b"5",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] ConfirmStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD ConfirmTransType {
+        New => b"0",
+        Replace => b"1",
+        Cancel => b"2",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] ConfirmTransTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD ConfirmType {
+        Status => b"1",
+        Confirmation => b"2",
+        ConfirmationRequestRejected => b"3",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] ConfirmTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD ContractMultiplierUnit {
         Shares => b"0",
@@ -138,6 +318,50 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED] CPProgramFieldType
 );
 
+define_enum_field_type!(
+    FIELD CrossPrioritization {
+        None => b"0",
+        BuySideIsPrioritized => b"1",
+        SellSideIsPrioritized => b"2",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CrossPrioritizationFieldType
+);
+
+define_enum_field_type!(
+    FIELD CrossType {
+        CrossAONBothSidesMustBeFullyExecuted => b"1",
+        CrossIOCAtLeastOneSideMustBeFullyExecuted => b"2",
+        CrossOneSideIsFullyExecuted => b"3",
+        CrossSamePrice => b"4",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] CrossTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD CxlRejReason {
+        TooLateToCancel => b"0",
+        UnknownOrder => b"1",
+        BrokerCredit => b"2",
+        OrderAlreadyInPendingCancelOrPendingReplaceStatus => b"3",
+        UnableToProcessOrderMassCancelRequest => b"4",
+        OrigOrdModTimeDidNotMatchLastTransactTimeOfOrder => b"5",
+        DuplicateClOrdIDReceived => b"6",
+        PriceExceedsCurrentPrice => b"7",
+        PriceExceedsCurrentPriceBand => b"8",
+        InvalidPriceIncrement => b"18",
+        Other => b"99",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] CxlRejReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD CxlRejResponseTo {
+        OrderCancelRequest => b"1",
+        OrderCancelReplaceRequest => b"2",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] CxlRejResponseToFieldType
+);
+
 pub struct DefaultApplVerIDFieldType;
 
 impl FieldType for DefaultApplVerIDFieldType {
@@ -219,6 +443,34 @@ define_enum_field_type!(
     FIELD_TYPE [REQUIRED] EventTypeFieldType
 );
 
+define_enum_field_type!(
+    FIELD ExecType {
+        New => b"0",
+        PartialFill => b"1", //Deprecated in FIX 4.4
+        Fill => b"2", //Deprecated in FIX 4.4
+        DoneForDay => b"3",
+        Canceled => b"4",
+        Replaced => b"5",
+        PendingCancel => b"6",
+        Stopped => b"7",
+        Rejected => b"8",
+        Suspended => b"9",
+        PendingNew => b"A",
+        Calculated => b"B",
+        Expired => b"C",
+        Restated => b"D",
+        PendingReplace => b"E",
+        Trade => b"F",
+        TradeCorrect => b"G",
+        TradeCancel => b"H",
+        OrderStatus => b"I",
+        TradeInAClearingHold => b"J",
+        TradeHasBeenReleasedToClearing => b"K",
+        TriggeredOrActivatedBySystem => b"L",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] ExecTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD ExerciseStyle {
         European => b"0",
@@ -239,6 +491,18 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED] FlowScheduleTypeFieldType
 );
 
+define_enum_field_type!(
+    FIELD HaltReason {
+        OrderImbalance => b"I",
+        EquipmentChangeover => b"X",
+        NewsPending => b"P",
+        NewsDissemination => b"D",
+        OrderInflux => b"E",
+        AdditionalInformation => b"M",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] HaltReasonFieldType
+);
+
 define_enum_field_type!(
     FIELD HandlInst {
         AutomatedExecutionOrderPrivateNoBrokerIntervention => b"1",
@@ -256,6 +520,48 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] InstrmtAssignmentMethodFieldType
 );
 
+define_enum_field_type!(
+    FIELD IOIQltyInd {
+        High => b"H",
+        Low => b"L",
+        Medium => b"M",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] IOIQltyIndFieldType
+);
+
+define_enum_field_type!(
+    FIELD IOIQualifier {
+        AllOrNone => b"A",
+        MarketOnClose => b"B",
+        AtTheClose => b"C",
+        VWAP => b"D",
+        InTouchWith => b"I",
+        Limit => b"L",
+        MoreBehind => b"M",
+        AtTheOpen => b"O",
+        TakingAPosition => b"P",
+        AtTheMarket => b"Q",
+        ReadyToTrade => b"R",
+        PortfolioShown => b"S",
+        ThroughTheDay => b"T",
+        Versus => b"V",
+        Indication => b"W",
+        CrossingOpportunity => b"X",
+        AtTheMidpoint => b"Y",
+        PreOpen => b"Z",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] IOIQualifierFieldType
+);
+
+define_enum_field_type!(
+    FIELD IOITransType {
+        New => b"N",
+        Cancel => b"C",
+        Replace => b"R",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] IOITransTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD Issuer {
         CouncilOfEurope => b"COE",
@@ -362,6 +668,17 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_STRING] IssuerFieldType
 );
 
+define_enum_field_type!(
+    FIELD ListExecInstType {
+        Immediate => b"1",
+        WaitForExecuteInstruction => b"2",
+        ExchangeSwitchCIVOrderSellDriven => b"3",
+        ExchangeSwitchCIVOrderBuyDrivenCashTopUp => b"4",
+        ExchangeSwitchCIVOrderBuyDrivenCashWithdraw => b"5",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] ListExecInstTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD ListMethod {
         PreListedOnly => b"0",
@@ -370,6 +687,90 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] ListMethodFieldType
 );
 
+define_enum_field_type!(
+    FIELD ListOrderStatus {
+        InBiddingProcess => b"1",
+        ReceivedForExecution => b"2",
+        Executing => b"3",
+        Cancelling => b"4",
+        Alert => b"5",
+        AllDone => b"6",
+        Reject => b"7",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] ListOrderStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD ListStatusType {
+        Ack => b"1",
+        Response => b"2",
+        Timed => b"3",
+        ExecStarted => b"4",
+        AllDone => b"5",
+        Alert => b"6",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] ListStatusTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD MDEntryType {
+        Bid => b"0",
+        Offer => b"1",
+        Trade => b"2",
+        IndexValue => b"3",
+        OpeningPrice => b"4",
+        ClosingPrice => b"5",
+        SettlementPrice => b"6",
+        TradingSessionHighPrice => b"7",
+        TradingSessionLowPrice => b"8",
+        TradingSessionVWAPPrice => b"9",
+        Imbalance => b"A",
+        TradeVolume => b"B",
+        OpenInterest => b"C",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] MDEntryTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD MDReqRejReason {
+        UnknownSymbol => b"0",
+        DuplicateMDReqID => b"1",
+        InsufficientBandwidth => b"2",
+        InsufficientPermissions => b"3",
+        UnsupportedSubscriptionRequestType => b"4",
+        UnsupportedMarketDepth => b"5",
+        UnsupportedMDUpdateType => b"6",
+        UnsupportedAggregatedBook => b"7",
+        UnsupportedMDEntryType => b"8",
+        UnsupportedTradingSessionID => b"9",
+        UnsupportedScope => b"A",
+        UnsupportedOpenCloseSettleFlag => b"B",
+        UnsupportedMDImplicitDelete => b"C",
+        InsufficientCredit => b"D",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] MDReqRejReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD MDUpdateAction {
+        New => b"0",
+        Change => b"1",
+        Delete => b"2",
+        DeleteThru => b"3",
+        DeleteFrom => b"4",
+        Overlay => b"5",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] MDUpdateActionFieldType
+);
+
+define_enum_field_type!(
+    FIELD MDUpdateType {
+        FullRefresh => b"0",
+        IncrementalRefresh => b"1",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] MDUpdateTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD MsgDirection {
         Receive => b"R",
@@ -387,6 +788,27 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] OptPayoutTypeFieldType
 );
 
+define_enum_field_type!(
+    FIELD OrdStatus {
+        New => b"0",
+        PartiallyFilled => b"1",
+        Filled => b"2",
+        DoneForDay => b"3",
+        Canceled => b"4",
+        Replaced => b"5", //Deprecated in FIX 4.3
+        PendingCancel => b"6",
+        Stopped => b"7",
+        Rejected => b"8",
+        Suspended => b"9",
+        PendingNew => b"A",
+        Calculated => b"B",
+        Expired => b"C",
+        AcceptedForBidding => b"D",
+        PendingReplace => b"E",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] OrdStatusFieldType
+);
+
 define_enum_field_type!(
     FIELD OrdType {
         Market => b"1",
@@ -599,6 +1021,15 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] ProductFieldType
 );
 
+define_enum_field_type!(
+    FIELD ProgRptReqs {
+        BuySideRequests => b"1",
+        SellSideSends => b"2",
+        RealTimeExecutionReports => b"3",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] ProgRptReqsFieldType
+);
+
 define_enum_field_type!(
     FIELD PutOrCall {
         Put => b"0",
@@ -607,6 +1038,83 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] PutOrCallFieldType
 );
 
+define_enum_field_type!(
+    FIELD QuoteRequestRejectReason {
+        UnknownSymbol => b"1",
+        ExchangeClosed => b"2",
+        QuoteRequestExceedsLimit => b"3",
+        TooLateToEnter => b"4",
+        InvalidPrice => b"5",
+        NotAuthorizedToRequestQuote => b"6",
+        NoMatchForInquiry => b"7",
+        NoMarketForInstrument => b"8",
+        NoInventory => b"9",
+        Pass => b"10",
+        InsufficientCredit => b"11",
+        Other => b"99",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] QuoteRequestRejectReasonFieldType
+);
+
+define_enum_field_type!(
+    FIELD QuoteRequestType {
+        Manual => b"1",
+        Automatic => b"2",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] QuoteRequestTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD QuoteRespType {
+        HitLift => b"1",
+        Counter => b"2",
+        Expired => b"3",
+        Cover => b"4",
+        DoneAway => b"5",
+        Pass => b"6",
+        EndTrade => b"7",
+        TimedOut => b"8",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] QuoteRespTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD QuoteStatus {
+        Accepted => b"0",
+        CanceledForSymbol => b"1",
+        CanceledForSecurityType => b"2",
+        CanceledForUnderlying => b"3",
+        CanceledAll => b"4",
+        Rejected => b"5",
+        RemovedFromMarket => b"6",
+        Expired => b"7",
+        Query => b"8",
+        QuoteNotFound => b"9",
+        Pending => b"10",
+        Pass => b"11",
+        LockedMarketWarning => b"12",
+        CrossMarketWarning => b"13",
+        CanceledDueToLockMarket => b"14",
+        CanceledDueToCrossMarket => b"15",
+        Active => b"16",
+        Canceled => b"17",
+        UnsolicitedQuoteReplenishment => b"18",
+        PendingEndTrade => b"19",
+        TooLateToEnd => b"20",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] QuoteStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD QuoteType {
+        Indicative => b"0",
+        Tradeable => b"1",
+        RestrictedTradeable => b"2",
+        Counter => b"3",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] QuoteTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD RateSource {
         Bloomberg => b"0",
@@ -635,6 +1143,65 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED,MUST_BE_STRING] RestructuringTypeFieldType
 );
 
+define_enum_field_type!(
+    FIELD RiskLimitRequestResult {
+        Successful => b"0",
+        InvalidParty => b"1",
+        InvalidRelatedParty => b"2",
+        InvalidRiskLimitType => b"3",
+        InvalidRiskLimitID => b"4",
+        InvalidRiskLimitAmount => b"5",
+        InvalidRiskWarningLevelAction => b"6",
+        InvalidRiskInstrumentScope => b"7",
+        RiskLimitActionsNotSupported => b"8",
+        WarningLevelsNotSupported => b"9",
+        WarningLevelActionsNotSupported => b"10",
+        RiskInstrumentScopeNotSupported => b"11",
+        RiskLimitNotApprovedForParty => b"12",
+        RiskLimitAlreadyDefinedForParty => b"13",
+        InstrumentNotApprovedForParty => b"14",
+        NotAuthorized => b"98",
+        Other => b"99",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] RiskLimitRequestResultFieldType
+);
+
+define_enum_field_type!(
+    FIELD RiskLimitRequestStatus {
+        Accepted => b"0",
+        Rejected => b"1",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] RiskLimitRequestStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD RiskLimitRequestType {
+        Definitions => b"1",
+        Utilization => b"2",
+        DefinitionsAndUtilization => b"3",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] RiskLimitRequestTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD RiskLimitType {
+        GrossLimit => b"1",
+        NetLimit => b"2",
+        Exposure => b"3",
+        LongLimit => b"4",
+        ShortLimit => b"5",
+        CashMargin => b"6",
+        AdditionalMargin => b"7",
+        TotalMargin => b"8",
+        LimitConsumed => b"9",
+        ClipSize => b"10",
+        MaxNotionalOrderSize => b"11",
+        DV01PV01Limit => b"12",
+        CS01Limit => b"13",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] RiskLimitTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD RoutingType {
         TargetFirm => b"1",
@@ -673,6 +1240,58 @@ define_enum_field_type!(
     FIELD_TYPE [REQUIRED_AND_NOT_REQUIRED,BYTES] RequiredSecurityIDSourceFieldType NotRequiredSecurityIDSourceFieldType
 );
 
+define_enum_field_type!(
+    FIELD SecurityListRequestType {
+        Symbol => b"0",
+        SecurityTypeAndOrCFICode => b"1",
+        Product => b"2",
+        TradingSessionID => b"3",
+        AllSecurities => b"4",
+        MarketIDOrMarketSegmentID => b"5",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] SecurityListRequestTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD SecurityRequestResult {
+        ValidRequest => b"0",
+        InvalidOrUnsupportedRequest => b"1",
+        NoInstrumentsFoundThatMatchSelectionCriteria => b"2",
+        NotAuthorizedToRetrieveInstrumentData => b"3",
+        InstrumentDataTemporarilyUnavailable => b"4",
+        RequestForInstrumentDataNotSupported => b"5",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] SecurityRequestResultFieldType
+);
+
+define_enum_field_type!(
+    FIELD SecurityRequestType {
+        RequestSecurityIdentityAndSpecifications => b"0",
+        RequestSecurityIdentityForTheSpecificationsProvided => b"1",
+        RequestListSecurityTypes => b"2",
+        RequestListSecurities => b"3",
+        Symbol => b"4",
+        SecurityTypeAndOrCFICode => b"5",
+        Product => b"6",
+        TradingSessionID => b"7",
+        AllSecurities => b"8",
+        MarketIDOrMarketSegmentID => b"9",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_INT] SecurityRequestTypeFieldType
+);
+
+define_enum_field_type!(
+    FIELD SecurityResponseType {
+        AcceptSecurityProposalAsIs => b"1",
+        AcceptSecurityProposalWithRevisionsAsIndicatedInTheMessage => b"2",
+        ListOfSecurityTypesReturnedPerRequest => b"3",
+        ListOfSecuritiesReturnedPerRequest => b"4",
+        RejectSecurityProposal => b"5",
+        CanNotMatchSelectionCriteria => b"6",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] SecurityResponseTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD SecurityStatus {
         Active => b"1",
@@ -681,6 +1300,34 @@ define_enum_field_type!(
     FIELD_TYPE [REQUIRED,MUST_BE_STRING] SecurityStatusFieldType
 );
 
+define_enum_field_type!(
+    FIELD SecurityTradingStatus {
+        OpeningDelay => b"1",
+        TradingHalt => b"2",
+        Resume => b"3",
+        NoOpen => b"4",
+        PriceIndication => b"5",
+        TradingRangeIndication => b"6",
+        MarketImbalanceBuy => b"7",
+        MarketImbalanceSell => b"8",
+        MarketOnCloseImbalanceBuy => b"9",
+        MarketOnCloseImbalanceSell => b"10",
+        NoMarketImbalance => b"12",
+        NoMarketOnCloseImbalance => b"13",
+        ITSPreOpening => b"14",
+        NewPriceIndication => b"15",
+        TradeDisseminationTime => b"16",
+        ReadyToTrade => b"17",
+        NotAvailableForTrading => b"18",
+        NotTradedOnThisMarket => b"19",
+        UnknownOrInvalid => b"20",
+        PreOpen => b"21",
+        OpeningRotation => b"22",
+        FastMarket => b"23",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_INT] SecurityTradingStatusFieldType
+);
+
 define_enum_field_type!(
     FIELD SecurityType {
         USTreasureNote => b"UST", //Deprecated in FIX 4.4, use USTreasuryNote.
@@ -840,6 +1487,43 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED] SessionRejectReasonFieldType
 );
 
+define_enum_field_type!(
+    FIELD SessionStatus {
+        SessionActive => 0,
+        SessionPasswordChanged => 1,
+        SessionPasswordDueToExpire => 2,
+        NewSessionPasswordDoesNotComplyWithPolicy => 3,
+        SessionLogoutComplete => 4,
+        InvalidUsernameOrPassword => 5,
+        AccountLocked => 6,
+        LogonsAreNotAllowedAtThisTime => 7,
+        PasswordExpired => 8,
+        ReceivedMsgSeqNumIsTooLow => 9,
+        ReceivedNextExpectedMsgSeqNumIsTooHigh => 10,
+    } Reserved100Plus => WITH_MINIMUM 100,
+    FIELD_TYPE [NOT_REQUIRED] SessionStatusFieldType
+);
+
+define_enum_field_type!(
+    FIELD SettlInstMode {
+        Default => b"0",
+        StandingInstructionsProvided => b"1",
+        SpecificOrderForASingleAccount => b"4",
+        RequestReject => b"5",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] SettlInstModeFieldType
+);
+
+define_enum_field_type!(
+    FIELD SettlInstTransType {
+        New => b"N",
+        Cancel => b"C",
+        Replace => b"R",
+        Restate => b"T",
+    },
+    FIELD_TYPE [NOT_REQUIRED,MUST_BE_CHAR] SettlInstTransTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD SettlMethod {
         CashSettlementRequired => b"C",
@@ -1104,6 +1788,15 @@ define_enum_field_type!(
     FIELD_TYPE [NOT_REQUIRED] StrikePriceDeterminationMethodFieldType
 );
 
+define_enum_field_type!(
+    FIELD SubscriptionRequestType {
+        Snapshot => b"0",
+        SnapshotPlusUpdates => b"1",
+        DisablePreviousSnapshotPlusUpdateRequest => b"2",
+    },
+    FIELD_TYPE [REQUIRED,MUST_BE_CHAR] SubscriptionRequestTypeFieldType
+);
+
 define_enum_field_type!(
     FIELD SymbolSfx {
         EUCPWithLumpSumInterestRatherThanDiscountPrice => b"CD",
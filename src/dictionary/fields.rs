@@ -11,7 +11,7 @@
 
 use dictionary::field_types::generic::{BoolTrueOrBlankFieldType,CharFieldType,CountryFieldType,CurrencyFieldType,DataFieldType,DayOfMonthFieldType,IntFieldType,LengthFieldType,LocalMktDateFieldType,MonthYearFieldType,NoneFieldType,RepeatingGroupFieldType,SeqNumFieldType,StringFieldType,UTCTimeOnlyFieldType,UTCTimestampFieldType};
 use dictionary::field_types::other as other_field_types;
-use dictionary::field_types::other::{ApplVerIDFieldType,BusinessRejectReasonFieldType,ComplexEventConditionFieldType,ComplexEventPriceBoundaryMethodFieldType,ComplexEventPriceTimeTypeFieldType,ComplexEventTypeFieldType,ContractMultiplierUnitFieldType,CPProgramFieldType,DefaultApplVerIDFieldType,EmailTypeFieldType,EncryptMethodFieldType,EventTypeFieldType,ExerciseStyleFieldType,FlowScheduleTypeFieldType,HandlInstFieldType,InstrmtAssignmentMethodFieldType,IssuerFieldType,ListMethodFieldType,MsgDirectionFieldType,NotRequiredSecurityIDSourceFieldType,NotRequiredSecurityTypeFieldType as SecurityTypeFieldType,NotRequiredSideFieldType,NotRequiredSymbolSfxFieldType as SymbolSfxFieldType,NotRequiredTimeUnitFieldType as TimeUnitFieldType,OptPayoutTypeFieldType,OrdTypeFieldType,PartyIDSourceFieldType,PartyRoleFieldType,PartySubIDTypeFieldType,PriceQuoteMethodFieldType,ProductFieldType,PutOrCallFieldType,RateSourceFieldType,RateSourceTypeFieldType,RequiredSecurityIDSourceFieldType,RequiredSideFieldType,RequiredStipulationTypeFieldType as StipulationTypeFieldType,RestructuringTypeFieldType,RoutingTypeFieldType,SecurityStatusFieldType,SeniorityFieldType,SessionRejectReasonFieldType,SettlMethodFieldType,SettlTypeFieldType,StrikePriceBoundaryMethodFieldType,StrikePriceDeterminationMethodFieldType,TimeInForceFieldType,UnderlyingCashTypeFieldType,UnderlyingFXRateCalcFieldType,UnderlyingPriceDeterminationMethodFieldType,UnderlyingSettlementTypeFieldType,UnitOfMeasureFieldType,ValuationMethodFieldType};
+use dictionary::field_types::other::{AdvSideFieldType,AdvTransTypeFieldType,AffirmStatusFieldType,AllocRejCodeFieldType,AllocStatusFieldType,ApplVerIDFieldType,BidTypeFieldType,BusinessRejectReasonFieldType,CollAsgnReasonFieldType,CollAsgnRejectReasonFieldType,CollAsgnRespTypeFieldType,CollAsgnTransTypeFieldType,CollInquiryQualifierFieldType,CollStatusFieldType,ComplexEventConditionFieldType,ComplexEventPriceBoundaryMethodFieldType,ComplexEventPriceTimeTypeFieldType,ComplexEventTypeFieldType,ConfirmRejReasonFieldType,ConfirmStatusFieldType,ConfirmTransTypeFieldType,ConfirmTypeFieldType,ContractMultiplierUnitFieldType,CPProgramFieldType,CrossPrioritizationFieldType,CrossTypeFieldType,CxlRejReasonFieldType,CxlRejResponseToFieldType,DefaultApplVerIDFieldType,EmailTypeFieldType,EncryptMethodFieldType,EventTypeFieldType,ExecTypeFieldType,ExerciseStyleFieldType,FlowScheduleTypeFieldType,HaltReasonFieldType,HandlInstFieldType,InstrmtAssignmentMethodFieldType,IOIQltyIndFieldType,IOIQualifierFieldType,IOITransTypeFieldType,IssuerFieldType,ListExecInstTypeFieldType,ListMethodFieldType,ListOrderStatusFieldType,ListStatusTypeFieldType,MDEntryTypeFieldType,MDReqRejReasonFieldType,MDUpdateActionFieldType,MDUpdateTypeFieldType,MsgDirectionFieldType,NotRequiredSecurityIDSourceFieldType,NotRequiredSecurityTypeFieldType as SecurityTypeFieldType,NotRequiredSideFieldType,NotRequiredSymbolSfxFieldType as SymbolSfxFieldType,NotRequiredTimeUnitFieldType as TimeUnitFieldType,OptPayoutTypeFieldType,OrdStatusFieldType,OrdTypeFieldType,PartyIDSourceFieldType,PartyRoleFieldType,PartySubIDTypeFieldType,PriceQuoteMethodFieldType,ProductFieldType,ProgRptReqsFieldType,PutOrCallFieldType,QuoteRequestRejectReasonFieldType,QuoteRequestTypeFieldType,QuoteRespTypeFieldType,QuoteStatusFieldType,QuoteTypeFieldType,RateSourceFieldType,RateSourceTypeFieldType,RequiredSecurityIDSourceFieldType,RequiredSideFieldType,RequiredStipulationTypeFieldType as StipulationTypeFieldType,RestructuringTypeFieldType,RiskLimitRequestResultFieldType,RiskLimitRequestStatusFieldType,RiskLimitRequestTypeFieldType,RiskLimitTypeFieldType,RoutingTypeFieldType,SecurityListRequestTypeFieldType,SecurityRequestResultFieldType,SecurityRequestTypeFieldType,SecurityResponseTypeFieldType,SecurityStatusFieldType,SecurityTradingStatusFieldType,SeniorityFieldType,SessionRejectReasonFieldType,SessionStatusFieldType,SettlInstModeFieldType,SettlInstTransTypeFieldType,SettlMethodFieldType,SettlTypeFieldType,StrikePriceBoundaryMethodFieldType,StrikePriceDeterminationMethodFieldType,SubscriptionRequestTypeFieldType,TimeInForceFieldType,UnderlyingCashTypeFieldType,UnderlyingFXRateCalcFieldType,UnderlyingPriceDeterminationMethodFieldType,UnderlyingSettlementTypeFieldType,UnitOfMeasureFieldType,ValuationMethodFieldType};
 use field_tag;
 use fix_version::FIXVersion;
 use message::{self,REQUIRED,NOT_REQUIRED};
@@ -28,18 +28,35 @@ type ExchangeFieldType = StringFieldType; //See ISO 10383 for a complete list: h
 
 define_fields!(
     Account: StringFieldType = 1,
+    AdvId: StringFieldType = 2,
+    AdvRefID: StringFieldType = 3,
+    AdvSide: AdvSideFieldType = 4,
+    AdvTransType: AdvTransTypeFieldType = 5,
+    AvgPx: StringFieldType = 6, //Price
     BeginSeqNo: SeqNumFieldType = 7,
     ClOrdID: StringFieldType = 11,
+    CumQty: StringFieldType = 14, //Qty
     Currency: CurrencyFieldType = 15,
     EndSeqNo: SeqNumFieldType = 16,
+    ExecID: StringFieldType = 17,
     HandlInst: HandlInstFieldType = 21,
     SecurityIDSource: NotRequiredSecurityIDSourceFieldType = 22,
+    IOIID: StringFieldType = 23,
+    IOIQltyInd: IOIQltyIndFieldType = 25,
+    IOIRefID: StringFieldType = 26,
+    IOIQty: StringFieldType = 27, //S = Small, M = Medium, L = Large, or a specific quantity. Named IOIShares before FIX 4.4.
+    IOITransType: IOITransTypeFieldType = 28,
+    LastMkt: ExchangeFieldType = 30,
+    LastPx: StringFieldType = 31, //Price
+    LastQty: StringFieldType = 32, //Qty
     NoLinesOfText: RepeatingGroupFieldType<LinesOfTextGrp> = 33,
     MsgSeqNum: SeqNumFieldType = 34, //TODO: Special field probably might be better off built into the parser.
     NewSeqNo: SeqNumFieldType = 36,
     OrderID: StringFieldType = 37,
     OrderQty: StringFieldType = 38, //Qty
+    OrdStatus: OrdStatusFieldType = 39,
     OrdType: OrdTypeFieldType = 40,
+    OrigClOrdID: StringFieldType = 41,
     OrigTime: UTCTimestampFieldType = 42,
     PossDupFlag: BoolTrueOrBlankFieldType = 43,
     Price: StringFieldType = 44, //Price
@@ -49,19 +66,37 @@ define_fields!(
     SenderCompID: StringFieldType = 49,
     SenderSubID: StringFieldType = 50,
     SendingTime: UTCTimestampFieldType = 52,
+    Quantity: QtyFieldType = 53, //Named Shares before FIX 4.3.
     SideField: RequiredSideFieldType = 54,
+    NotRequiredSideField: NotRequiredSideFieldType = 54, //Same tag as SideField except it can be left out. Used where a side is optional, like in two-sided quotes.
     Symbol: StringFieldType = 55,
     TargetCompID: StringFieldType = 56,
     TargetSubID: StringFieldType = 57,
     Text: StringFieldType = 58,
     TimeInForce: TimeInForceFieldType = 59,
     TransactTime: UTCTimestampFieldType = 60,
+    ValidUntilTime: UTCTimestampFieldType = 62,
     SettlType: SettlTypeFieldType = 63,
     SettlDate: LocalMktDateFieldType = 64,
     SymbolSfx: SymbolSfxFieldType = 65,
+    ListID: StringFieldType = 66,
+    ListSeqNo: IntFieldType = 67,
+    TotNoOrders: IntFieldType = 68,
+    ListExecInst: StringFieldType = 69,
+    AllocID: StringFieldType = 70,
     //NoOrders: RepeatingGroupFieldType<Order> = 73, //TODO: The repeating group type here depends on the message using NoOrders.
+    NoListOrders: RepeatingGroupFieldType<ListOrdGrp> = 73, //Same tag as NoOrders except used by NewOrderList. See NoOrders TODO.
+    NoListStatusOrders: RepeatingGroupFieldType<OrdListStatGrp> = 73, //Same tag as NoOrders except used by ListStatus. See NoOrders TODO.
+    TradeDate: LocalMktDateFieldType = 75,
     //NoAllocs: RepeatingGroupFieldType<Alloc> = 78, //TODO: The repeating group type here depends on the message using NoAllocs.
+    NoAllocAcks: RepeatingGroupFieldType<AllocAckGrp> = 78, //Same tag as NoAllocs except used by AllocationInstructionAck. See NoAllocs TODO.
     AllocAccount: StringFieldType = 79,
+    AllocQty: QtyFieldType = 80,
+    NoRpts: IntFieldType = 82,
+    RptSeq: IntFieldType = 83,
+    CxlQty: StringFieldType = 84, //Qty
+    AllocStatus: AllocStatusFieldType = 87,
+    AllocRejCode: AllocRejCodeFieldType = 88,
     Signature: DataFieldType = 89 => Rule::ConfirmPreviousTag{ previous_tag: SignatureLength::tag() },
     SecureDataLen: NoneFieldType = 90 => Rule::PrepareForBytes{ bytes_tag: SecureData::tag() },
     SecureData: DataFieldType = 91 => Rule::ConfirmPreviousTag{ previous_tag: SecureDataLen::tag() },
@@ -71,6 +106,8 @@ define_fields!(
     RawData: DataFieldType = 96 => Rule::ConfirmPreviousTag{ previous_tag: RawDataLength::tag() },
     PossResend: StringFieldType = 97, //Bool
     EncryptMethod: EncryptMethodFieldType = 98,
+    CxlRejReason: CxlRejReasonFieldType = 102,
+    IOIQualifier: IOIQualifierFieldType = 104,
     Issuer: IssuerFieldType = 106,
     SecurityDesc: StringFieldType = 107,
     HeartBtInt: IntFieldType = 108,
@@ -79,21 +116,43 @@ define_fields!(
     TestReqID: StringFieldType = 112,
     OnBehalfOfCompID: StringFieldType = 115,
     OnBehalfOfSubID: StringFieldType = 116,
+    QuoteID: StringFieldType = 117,
+    NetMoney: AmtFieldType = 118,
     OrigSendingTime: UTCTimestampFieldType = 122,
     GapFillFlag: BoolTrueOrBlankFieldType = 123,
+    ExpireTime: UTCTimestampFieldType = 126,
     DeliverToCompID: StringFieldType = 128,
     DeliverToSubID: StringFieldType = 129,
+    IOINaturalFlag: StringFieldType = 130, //Bool
+    QuoteReqID: StringFieldType = 131,
+    BidPx: PriceFieldType = 132,
+    OfferPx: PriceFieldType = 133,
     BidSize: StringFieldType = 134, //Qty
+    OfferSize: QtyFieldType = 135,
     ResetSeqNumFlag: BoolTrueOrBlankFieldType = 141,
     SenderLocationID: StringFieldType = 142,
     TargetLocationID: StringFieldType = 143,
     OnBehalfOfLocationID: StringFieldType = 144,
     DeliverToLocationID: StringFieldType = 145,
     NoRelatedSym: RepeatingGroupFieldType<Instrument> = 146,
+    NoQuoteRelatedSym: RepeatingGroupFieldType<QuotReqGrp> = 146, //Same tag as NoRelatedSym except used by QuoteRequest and QuoteRequestReject.
     Subject: StringFieldType = 147,
+    URLLink: StringFieldType = 149,
+    ExecType: ExecTypeFieldType = 150,
+    LeavesQty: StringFieldType = 151, //Qty
     CashOrderQty: StringFieldType = 152, //Qty
+    SettlInstMode: SettlInstModeFieldType = 160,
+    AllocText: StringFieldType = 161,
+    SettlInstID: StringFieldType = 162,
+    SettlInstTransType: SettlInstTransTypeFieldType = 163,
     EmailThreadID: StringFieldType = 164,
     SecurityType: SecurityTypeFieldType = 167,
+    EffectiveTime: UTCTimestampFieldType = 168,
+    StandInstDbType: StringFieldType = 169, //Int
+    StandInstDbName: StringFieldType = 170,
+    StandInstDbID: StringFieldType = 171,
+    SettlDeliveryType: StringFieldType = 172, //Int
+    NoIOIQualifiers: RepeatingGroupFieldType<IOIQualGrp> = 199,
     MaturityMonthYear: MonthYearFieldType = 200,
     PutOrCall: PutOrCallFieldType = 201,
     StrikePrice: StringFieldType = 202, //Price
@@ -102,6 +161,7 @@ define_fields!(
     SecurityExchange: ExchangeFieldType = 207,
     XmlDataLen: NoneFieldType = 212 => Rule::PrepareForBytes{ bytes_tag: XmlData::tag() },
     XmlData: DataFieldType = 213 => Rule::ConfirmPreviousTag{ previous_tag: XmlDataLen::tag() },
+    SettlInstRefID: StringFieldType = 214,
     NoRoutingIDs: RepeatingGroupFieldType<RoutingGrp> = 215,
     RoutingType: RoutingTypeFieldType = 216,
     RoutingID: StringFieldType = 217,
@@ -131,6 +191,20 @@ define_fields!(
     CreditRating: StringFieldType = 255,
     UnderlyingCreditRating: StringFieldType = 256,
     LegCreditRating: StringFieldType = 257,
+    MDReqID: StringFieldType = 262,
+    SubscriptionRequestType: SubscriptionRequestTypeFieldType = 263,
+    MarketDepth: IntFieldType = 264, //0 = Full Book, 1 = Top of Book, N > 1 = Report best N price tiers.
+    MDUpdateType: MDUpdateTypeFieldType = 265,
+    NoMDEntryTypes: RepeatingGroupFieldType<MDReqGrp> = 267,
+    NoMDEntries: RepeatingGroupFieldType<MDFullGrp> = 268,
+    NoMDIncEntries: RepeatingGroupFieldType<MDIncGrp> = 268, //Same tag as NoMDEntries except used by MarketDataIncrementalRefresh. See NoOrders TODO.
+    MDEntryType: MDEntryTypeFieldType = 269,
+    MDEntryPx: PriceFieldType = 270,
+    MDEntrySize: QtyFieldType = 271,
+    MDUpdateAction: MDUpdateActionFieldType = 279,
+    MDReqRejReason: MDReqRejReasonFieldType = 281,
+    QuoteStatus: QuoteStatusFieldType = 297,
+    QuoteRequestType: QuoteRequestTypeFieldType = 303,
     UnderlyingSecurityIDSource: NotRequiredSecurityIDSourceFieldType = 305,
     UnderlyingIssuer: IssuerFieldType = 306,
     UnderlyingSecurityDesc: StringFieldType = 307,
@@ -144,6 +218,22 @@ define_fields!(
     UnderlyingStrikePrice: StringFieldType = 316, //Price
     UnderlyingOptAttribute: CharFieldType = 317,
     UnderlyingCurrency: CurrencyFieldType = 318,
+    SecurityReqID: StringFieldType = 320,
+    SecurityRequestType: SecurityRequestTypeFieldType = 321,
+    SecurityResponseID: StringFieldType = 322,
+    SecurityResponseType: SecurityResponseTypeFieldType = 323,
+    SecurityStatusReqID: StringFieldType = 324,
+    UnsolicitedIndicator: BoolTrueOrBlankFieldType = 325,
+    SecurityTradingStatus: SecurityTradingStatusFieldType = 326,
+    HaltReason: HaltReasonFieldType = 327, //TODO: Changed to an int in FIX 5.0.
+    InViewOfCommon: BoolTrueOrBlankFieldType = 328,
+    DueToRelated: BoolTrueOrBlankFieldType = 329,
+    BuyVolume: QtyFieldType = 330,
+    SellVolume: QtyFieldType = 331,
+    HighPx: PriceFieldType = 332,
+    LowPx: PriceFieldType = 333,
+    Adjustment: StringFieldType = 334, //Int
+    TradingSessionID: StringFieldType = 336,
     MessageEncoding: StringFieldType = 347,
     EncodedIssuerLen: NoneFieldType = 348 => Rule::PrepareForBytes{ bytes_tag: EncodedIssuer::tag() },
     EncodedIssuer: DataFieldType = 349 => Rule::ConfirmPreviousTag{ previous_tag: EncodedIssuerLen::tag() },
@@ -157,6 +247,7 @@ define_fields!(
     EncodedUnderlyingIssuer: DataFieldType = 363 => Rule::ConfirmPreviousTag{ previous_tag: EncodedUnderlyingIssuerLen::tag() },
     EncodedUnderlyingSecurityDescLen: NoneFieldType = 364 => Rule::PrepareForBytes{ bytes_tag: EncodedUnderlyingSecurityDesc::tag() },
     EncodedUnderlyingSecurityDesc: DataFieldType = 365 => Rule::ConfirmPreviousTag{ previous_tag: EncodedUnderlyingSecurityDescLen::tag() },
+    AllocPrice: PriceFieldType = 366,
     LastMsgSeqNumProcessed: SeqNumFieldType = 369,
     OnBehalfOfSendingTime: UTCTimestampFieldType = 370,
     RefTagID: StringFieldType = 371, //int
@@ -164,11 +255,25 @@ define_fields!(
     SessionRejectReason: SessionRejectReasonFieldType = 373,
     BusinessRejectRefID: StringFieldType = 379,
     BusinessRejectReason: BusinessRejectReasonFieldType = 380,
+    GrossTradeAmt: AmtFieldType = 381,
     MaxMessageSize: LengthFieldType = 383,
     NoMsgTypeGrp: RepeatingGroupFieldType<MsgTypeGrp> = 384,
     MsgDirection: MsgDirectionFieldType = 385,
+    BidID: StringFieldType = 390,
+    ClientBidID: StringFieldType = 391,
+    TotNoRelatedSym: IntFieldType = 393,
+    BidType: BidTypeFieldType = 394,
+    ProgRptReqs: ProgRptReqsFieldType = 414,
+    ProgPeriodInterval: StringFieldType = 415, //Int
+    PriceType: StringFieldType = 423, //Int
+    ListStatusType: ListStatusTypeFieldType = 429,
+    ListOrderStatus: ListOrderStatusFieldType = 431,
+    ListExecInstType: ListExecInstTypeFieldType = 433,
+    CxlRejResponseTo: CxlRejResponseToFieldType = 434,
     UnderlyingCouponRate: PercentageFieldType = 435,
     UnderlyingContractMultiplier: StringFieldType = 436, //Float
+    MultiLegReportingType: CharFieldType = 442, //1 = Single Security, 2 = Individual leg of a multi-leg security, 3 = Multi-leg security
+    ListStatusText: StringFieldType = 444,
     NoSecurityAltID: RepeatingGroupFieldType<SecAltIDGrp> = 454,
     SecurityAltID: StringFieldType = 455,
     SecurityAltIDSource: RequiredSecurityIDSourceFieldType = 456,
@@ -180,17 +285,36 @@ define_fields!(
     UnderlyingProduct: ProductFieldType = 462,
     UnderlyingCFICode: StringFieldType = 463,
     TestMessageIndicator: StringFieldType = 464, //Bool
+    IndividualAllocID: StringFieldType = 467,
     CountryOfIssue: CountryFieldType = 470,
     StateOrProvinceOfIssue: StringFieldType = 471,
     LocaleOfIssue: StringFieldType = 472, //Full code list is available for purchase here: http://www.iata.org/publications/store/Pages/airline-coding-directory.aspx
+    QuoteType: QuoteTypeFieldType = 537,
     MaturityDate: LocalMktDateFieldType = 541,
     UnderlyingMaturityDate: LocalMktDateFieldType = 542,
     InstrRegistry: StringFieldType = 543,
+    CrossID: StringFieldType = 548,
+    CrossType: CrossTypeFieldType = 549,
+    CrossPrioritization: CrossPrioritizationFieldType = 550,
+    OrigCrossID: StringFieldType = 551,
+    //NoSides: RepeatingGroupFieldType<Side> = 552, //TODO: The repeating group type here depends on the message using NoSides.
+    NoCrossSides: RepeatingGroupFieldType<SideCrossOrdModGrp> = 552, //Same tag as NoSides except used by NewOrderCross and CrossOrderCancelReplaceRequest. See NoSides TODO.
+    NoCrossCancelSides: RepeatingGroupFieldType<SideCrossOrdCxlGrp> = 552, //Same tag as NoSides except used by CrossOrderCancelRequest. See NoSides TODO.
     Username: StringFieldType = 553,
     Password: StringFieldType = 554,
     NoLegs: RepeatingGroupFieldType<InstrumentLeg> = 555,
+    NoLegOrds: RepeatingGroupFieldType<LegOrdGrp> = 555, //Same tag as NoLegs except used by NewOrderMultileg. See NoOrders TODO.
+    NoLegExecs: RepeatingGroupFieldType<InstrmtLegExecGrp> = 555, //Same tag as NoLegs except used by ExecutionReport. See NoOrders TODO.
     LegCurrency: CurrencyFieldType = 556,
+    SecurityListRequestType: SecurityListRequestTypeFieldType = 559,
+    SecurityRequestResult: SecurityRequestResultFieldType = 560,
+    RoundLot: QtyFieldType = 561,
+    LegPositionEffect: CharFieldType = 564, //See PositionEffect (77).
+    LegCoveredOrUncovered: StringFieldType = 565, //Int
     LegPrice: PriceFieldType = 566,
+    TradeReportID: StringFieldType = 571,
+    LegSettlType: StringFieldType = 587, //See SettlType (63).
+    LegSettlDate: LocalMktDateFieldType = 588,
     UnderlyingCountryOfIssue: CountryFieldType = 592,
     UnderlyingStateOrProvinceOfIssue: StringFieldType = 593,
     UnderlyingLocaleOfIssue: StringFieldType = 594, //See LocaleOfIssue (472).
@@ -224,20 +348,43 @@ define_fields!(
     EncodedLegSecurityDesc: DataFieldType = 622 => Rule::ConfirmPreviousTag{ previous_tag: EncodedLegSecurityDescLen::tag() },
     LegRatioQty: StringFieldType = 623, //Float
     LegSide: NotRequiredSideFieldType = 624,
+    TradingSessionSubID: StringFieldType = 625,
+    AllocType: StringFieldType = 626, //Int
     NoHops: RepeatingGroupFieldType<HopGrp> = 627,
     HopCompID: StringFieldType = 628,
     HopSendingTime: UTCTimestampFieldType = 629,
     HopRefID: SeqNumFieldType = 630,
+    LegLastPx: PriceFieldType = 637,
+    LegRefID: StringFieldType = 654,
+    QuoteRequestRejectReason: QuoteRequestRejectReasonFieldType = 658,
+    ConfirmID: StringFieldType = 664,
+    ConfirmStatus: ConfirmStatusFieldType = 665,
+    ConfirmTransType: ConfirmTransTypeFieldType = 666,
     ContractSettlMonth: MonthYearFieldType = 667,
+    LegQty: QtyFieldType = 687,
+    LegSwapType: StringFieldType = 690, //Int
     Pool: StringFieldType = 691,
+    QuoteRespID: StringFieldType = 693,
+    QuoteRespType: QuoteRespTypeFieldType = 694,
     NoUnderlyings: RepeatingGroupFieldType<UnderlyingInstrument> = 711,
     LegDatedDate: LocalMktDateFieldType = 739,
     LegPool: StringFieldType = 740,
     SecuritySubType: StringFieldType = 762,
     UnderlyingSecuritySubType: StringFieldType = 763,
     LegSecuritySubType: StringFieldType = 764,
+    ConfirmRefID: StringFieldType = 772,
+    ConfirmType: ConfirmTypeFieldType = 773,
+    ConfirmRejReason: ConfirmRejReasonFieldType = 774,
+    IndividualAllocRejCode: StringFieldType = 776, //Int
+    SettlInstMsgID: StringFieldType = 777,
+    NoSettlInst: RepeatingGroupFieldType<SettlInstGrp> = 778,
+    LastUpdateTime: UTCTimestampFieldType = 779,
     NextExpectedMsgSeqNum: SeqNumFieldType = 789,
+    SettlInstReqID: StringFieldType = 791,
+    SettlInstReqRejCode: StringFieldType = 792, //Int
+    SecondaryAllocID: StringFieldType = 793,
     UnderlyingPx: PriceFieldType = 810,
+    SecondaryTradeReportID: StringFieldType = 818,
     NoEvents: RepeatingGroupFieldType<EvntGrp> = 864,
     EventType: EventTypeFieldType = 865,
     EventDate: LocalMktDateFieldType = 866,
@@ -258,7 +405,28 @@ define_fields!(
     NoUnderlyingStips: RepeatingGroupFieldType<UnderlyingStipulation> = 887,
     UnderlyingStipType: StipulationTypeFieldType = 888,
     UnderlyingStipValue: StringFieldType = 889, //TODO: Parsable expression.
+    LastFragment: BoolTrueOrBlankFieldType = 893,
+    CollReqID: StringFieldType = 894,
+    CollAsgnReason: CollAsgnReasonFieldType = 895,
+    CollInquiryQualifier: CollInquiryQualifierFieldType = 896,
+    NoTrades: RepeatingGroupFieldType<TrdCollGrp> = 897,
+    MarginExcess: AmtFieldType = 899,
+    TotalNetValue: AmtFieldType = 900,
+    CashOutstanding: AmtFieldType = 901,
+    CollAsgnID: StringFieldType = 902,
+    CollAsgnTransType: CollAsgnTransTypeFieldType = 903,
+    CollRespID: StringFieldType = 904,
+    CollAsgnRespType: CollAsgnRespTypeFieldType = 905,
+    CollAsgnRejectReason: CollAsgnRejectReasonFieldType = 906,
+    CollAsgnRefID: StringFieldType = 907,
+    CollRptID: StringFieldType = 908,
+    CollInquiryID: StringFieldType = 909,
+    CollStatus: CollStatusFieldType = 910,
+    TotNumReports: StringFieldType = 911, //Int
+    LastRptRequested: BoolTrueOrBlankFieldType = 912,
     NewPassword: StringFieldType = 925,
+    NoCollInquiryQualifier: RepeatingGroupFieldType<CollInqQualGrp> = 938,
+    AffirmStatus: AffirmStatusFieldType = 940,
     UnderlyingStrikeCurrency: CurrencyFieldType = 941,
     LegStrikeCurrency: CurrencyFieldType = 942,
     StrikeCurrency: CurrencyFieldType = 947,
@@ -339,7 +507,7 @@ define_fields!(
     RefApplExtID: StringFieldType = 1406, //int
     DefaultApplExtID: StringFieldType = 1407, //int
     DefaultCstmApplVerID: StringFieldType = 1408,
-    SessionStatus: StringFieldType = 1409, //int
+    SessionStatus: SessionStatusFieldType = 1409,
     DefaultVerIndicator: BoolTrueOrBlankFieldType = 1410,
     UnderlyingExerciseStyle: ExerciseStyleFieldType = 1419,
     LegExerciseStyle: ExerciseStyleFieldType = 1420,
@@ -389,10 +557,41 @@ define_fields!(
     NoComplexEventTimes: RepeatingGroupFieldType<ComplexEventTime> = 1494,
     ComplexEventStartTime: UTCTimeOnlyFieldType = 1495, //TODO: Must always be less than end time.
     ComplexEventEndTime: UTCTimeOnlyFieldType = 1496, //TODO: Must always be greater than start time.
+    NoRiskLimitTypes: RepeatingGroupFieldType<RiskLimitTypesGrp> = 1529,
+    RiskLimitType: RiskLimitTypeFieldType = 1530,
+    RiskLimitAmount: AmtFieldType = 1531,
+    RiskLimitCurrency: CurrencyFieldType = 1532,
+    RiskLimitPlatform: StringFieldType = 1533,
+    RiskLimitRequestID: StringFieldType = 1666,
+    RiskLimitReportID: StringFieldType = 1667,
+    NoRiskLimits: RepeatingGroupFieldType<RiskLimitsGrp> = 1669,
+    RiskLimitID: StringFieldType = 1670,
+    NoPartyDetails: RepeatingGroupFieldType<PartyDetailGrp> = 1671,
+    NoPartyRiskLimits: RepeatingGroupFieldType<PartyRiskLimitsGrp> = 1677,
+    PartyDetailID: StringFieldType = 1691, //Valid PartyDetailID values are dependent on PartyDetailIDSource and PartyDetailRole.
+    PartyDetailIDSource: PartyIDSourceFieldType = 1692,
+    PartyDetailRole: PartyRoleFieldType = 1693,
+    RiskLimitRequestType: RiskLimitRequestTypeFieldType = 1760,
+    RiskLimitRequestResult: RiskLimitRequestResultFieldType = 1761,
+    RiskLimitRequestStatus: RiskLimitRequestStatusFieldType = 1762,
+    RiskLimitUtilizationAmount: AmtFieldType = 1765,
+    RiskLimitUtilizationPercent: PercentageFieldType = 1766,
 );
 
 //Repeating Groups (Sorted Alphabetically)
 
+define_message!(AllocAckGrp {
+    REQUIRED, alloc_account: AllocAccount [FIX44..],
+    NOT_REQUIRED, alloc_price: AllocPrice [FIX44..],
+    NOT_REQUIRED, individual_alloc_id: IndividualAllocID [FIX44..],
+    NOT_REQUIRED, individual_alloc_rej_code: IndividualAllocRejCode [FIX44..],
+    NOT_REQUIRED, alloc_text: AllocText [FIX44..],
+});
+
+define_message!(CollInqQualGrp {
+    REQUIRED, coll_inquiry_qualifier: CollInquiryQualifier [FIX44..],
+});
+
 define_message!(ComplexEvent {
     REQUIRED, complex_event_type: ComplexEventType [FIX50SP2..],
     NOT_REQUIRED, complex_opt_payout_amount: ComplexOptPayoutAmount [FIX50SP2..],
@@ -429,6 +628,34 @@ define_message!(HopGrp {
     NOT_REQUIRED, hop_ref_id: HopRefID [FIX43..],
 });
 
+define_message!(InstrmtLegExecGrp {
+    REQUIRED, leg_symbol: LegSymbol [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_symbol_sfx: LegSymbolSfx [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_id: LegSecurityID [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_id_source: LegSecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &InstrmtLegExecGrp,_| { !message.leg_security_id.is_empty() }, //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_product: LegProduct [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_cfi_code: LegCFICode [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_type: LegSecurityType [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_maturity_month_year: LegMaturityMonthYear [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_maturity_date: LegMaturityDate [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_strike_price: LegStrikePrice [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_contract_multiplier: LegContractMultiplier [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_exchange: LegSecurityExchange [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_desc: LegSecurityDesc [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_ratio_qty: LegRatioQty [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_side: LegSide [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_currency: LegCurrency [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_qty: LegQty [FIX44..],
+    NOT_REQUIRED, leg_swap_type: LegSwapType [FIX44..],
+    NOT_REQUIRED, leg_position_effect: LegPositionEffect [FIX44..],
+    NOT_REQUIRED, leg_covered_or_uncovered: LegCoveredOrUncovered [FIX44..],
+    NOT_REQUIRED, leg_ref_id: LegRefID [FIX44..],
+    NOT_REQUIRED, leg_price: LegPrice [FIX44..],
+    NOT_REQUIRED, leg_settl_type: LegSettlType [FIX44..],
+    NOT_REQUIRED, leg_settl_date: LegSettlDate [FIX44..],
+    NOT_REQUIRED, leg_last_px: LegLastPx [FIX44..],
+});
+
 define_message!(Instrument {
     REQUIRED, related_sym: RelatedSym [FIX42],
     REQUIRED, symbol: Symbol [FIX43..],
@@ -591,6 +818,33 @@ define_message!(InstrumentPtysSubGrp {
     REQUIRED, instrument_party_sub_id_type: InstrumentPartySubIDType [FIX50..],
 });
 
+define_message!(LegOrdGrp {
+    REQUIRED, leg_symbol: LegSymbol [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_symbol_sfx: LegSymbolSfx [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_id: LegSecurityID [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_id_source: LegSecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &LegOrdGrp,_| { !message.leg_security_id.is_empty() }, //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_product: LegProduct [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_cfi_code: LegCFICode [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_type: LegSecurityType [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_maturity_month_year: LegMaturityMonthYear [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_maturity_date: LegMaturityDate [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_strike_price: LegStrikePrice [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_contract_multiplier: LegContractMultiplier [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_exchange: LegSecurityExchange [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_security_desc: LegSecurityDesc [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_ratio_qty: LegRatioQty [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_side: LegSide [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_currency: LegCurrency [FIX44..], //TODO: Part of the InstrumentLeg block.
+    NOT_REQUIRED, leg_qty: LegQty [FIX44..],
+    NOT_REQUIRED, leg_swap_type: LegSwapType [FIX44..],
+    NOT_REQUIRED, leg_position_effect: LegPositionEffect [FIX44..],
+    NOT_REQUIRED, leg_covered_or_uncovered: LegCoveredOrUncovered [FIX44..],
+    NOT_REQUIRED, leg_ref_id: LegRefID [FIX44..],
+    NOT_REQUIRED, leg_price: LegPrice [FIX44..],
+    NOT_REQUIRED, leg_settl_type: LegSettlType [FIX44..],
+    NOT_REQUIRED, leg_settl_date: LegSettlDate [FIX44..],
+});
+
 define_message!(LegSecAltIDGrp {
     REQUIRED, leg_security_alt_id: LegSecurityAltID [FIX44..],
     REQUIRED, leg_security_alt_id_source: LegSecurityAltIDSource [FIX44..],
@@ -602,6 +856,48 @@ define_message!(LinesOfTextGrp {
     NOT_REQUIRED, encoded_text: EncodedText [FIX42..],
 });
 
+define_message!(IOIQualGrp {
+    REQUIRED, ioi_qualifier: IOIQualifier [FIX42..],
+});
+
+define_message!(ListOrdGrp {
+    REQUIRED, cl_ord_id: ClOrdID [FIX42..],
+    REQUIRED, list_seq_no: ListSeqNo [FIX42..],
+    NOT_REQUIRED, account: Account [FIX42..],
+    NOT_REQUIRED, settl_type: SettlType [FIX42..],
+    NOT_REQUIRED, settl_date: SettlDate [FIX42..],
+    NOT_REQUIRED, handl_inst: HandlInst [FIX42..],
+    REQUIRED, symbol: Symbol [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX42..] => REQUIRED_WHEN |message: &ListOrdGrp,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, side: SideField [FIX42..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX42..],
+    REQUIRED, ord_type: OrdType [FIX42..],
+    NOT_REQUIRED, price: Price [FIX42..],
+    NOT_REQUIRED, time_in_force: TimeInForce [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+});
+
+define_message!(MDFullGrp {
+    REQUIRED, md_entry_type: MDEntryType [FIX42..],
+    NOT_REQUIRED, md_entry_px: MDEntryPx [FIX42..],
+    NOT_REQUIRED, md_entry_size: MDEntrySize [FIX42..],
+});
+
+define_message!(MDIncGrp {
+    REQUIRED, md_update_action: MDUpdateAction [FIX42..],
+    REQUIRED, md_entry_type: MDEntryType [FIX42..],
+    NOT_REQUIRED, symbol: Symbol [FIX42..],
+    NOT_REQUIRED, no_legs: NoLegs [FIX44..],
+    NOT_REQUIRED, md_entry_px: MDEntryPx [FIX42..],
+    NOT_REQUIRED, md_entry_size: MDEntrySize [FIX42..],
+});
+
+define_message!(MDReqGrp {
+    REQUIRED, md_entry_type: MDEntryType [FIX42..],
+});
+
 define_message!(MsgTypeGrp {
     REQUIRED, ref_msg_type: RefMsgType [FIX42..],
     REQUIRED, msg_direction: MsgDirection [FIX42..],
@@ -611,12 +907,64 @@ define_message!(MsgTypeGrp {
     NOT_REQUIRED, default_ver_indicator: DefaultVerIndicator [FIX50SP1..],
 });
 
+define_message!(OrdListStatGrp {
+    REQUIRED, cl_ord_id: ClOrdID [FIX42..],
+    REQUIRED, cum_qty: CumQty [FIX42..],
+    REQUIRED, ord_status: OrdStatus [FIX42..],
+    REQUIRED, leaves_qty: LeavesQty [FIX42..],
+    REQUIRED, cxl_qty: CxlQty [FIX42..],
+    REQUIRED, avg_px: AvgPx [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+});
+
+define_message!(PartyDetailGrp {
+    REQUIRED, party_detail_id: PartyDetailID [FIX50SP2..],
+    REQUIRED, party_detail_id_source: PartyDetailIDSource [FIX50SP2..], //Conditionally required if PartyDetailID is specified, but PartyDetailID is required, so this is also required.
+    NOT_REQUIRED, party_detail_role: PartyDetailRole [FIX50SP2..],
+});
+
+define_message!(PartyRiskLimitsGrp {
+    REQUIRED, no_party_details: NoPartyDetails [FIX50SP2..],
+    NOT_REQUIRED, no_risk_limits: NoRiskLimits [FIX50SP2..],
+});
+
+define_message!(QuotReqGrp {
+    REQUIRED, symbol: Symbol [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX42..] => REQUIRED_WHEN |message: &QuotReqGrp,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, quote_request_type: QuoteRequestType [FIX42..],
+    NOT_REQUIRED, quote_type: QuoteType [FIX44..],
+    NOT_REQUIRED, side: NotRequiredSideField [FIX42..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX42..],
+    NOT_REQUIRED, settl_type: SettlType [FIX42..],
+    NOT_REQUIRED, settl_date: SettlDate [FIX42..],
+    NOT_REQUIRED, currency: Currency [FIX42..],
+    NOT_REQUIRED, valid_until_time: ValidUntilTime [FIX44..],
+    NOT_REQUIRED, expire_time: ExpireTime [FIX42..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, price: Price [FIX44..],
+});
+
 define_message!(RateSourceGrp {
     REQUIRED, rate_source: RateSource [FIX50SP2..],
     REQUIRED, rate_source_type: RateSourceType [FIX50SP2..],
     NOT_REQUIRED, reference_page: ReferencePage [FIX50SP2..] => REQUIRED_WHEN |message: &RateSourceGrp,_| { message.rate_source == other_field_types::RateSource::Other },
 });
 
+define_message!(RiskLimitsGrp {
+    REQUIRED, no_risk_limit_types: NoRiskLimitTypes [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_id: RiskLimitID [FIX50SP2..],
+});
+
+define_message!(RiskLimitTypesGrp {
+    REQUIRED, risk_limit_type: RiskLimitType [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_amount: RiskLimitAmount [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_currency: RiskLimitCurrency [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_platform: RiskLimitPlatform [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_utilization_amount: RiskLimitUtilizationAmount [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_utilization_percent: RiskLimitUtilizationPercent [FIX50SP2..],
+});
+
 define_message!(RoutingGrp {
     REQUIRED, routing_type: RoutingType [FIX42..],
     REQUIRED, routing_id: RoutingID [FIX42..],
@@ -627,6 +975,44 @@ define_message!(SecAltIDGrp {
     REQUIRED, security_alt_id_source: SecurityAltIDSource [FIX43..],
 });
 
+define_message!(SettlInstGrp {
+    REQUIRED, settl_inst_id: SettlInstID [FIX44..],
+    NOT_REQUIRED, settl_inst_trans_type: SettlInstTransType [FIX44..],
+    NOT_REQUIRED, settl_inst_ref_id: SettlInstRefID [FIX44..] => REQUIRED_WHEN |message: &SettlInstGrp,_| { message.settl_inst_trans_type == Some(other_field_types::SettlInstTransType::Replace) || message.settl_inst_trans_type == Some(other_field_types::SettlInstTransType::Cancel) },
+    NOT_REQUIRED, product: Product [FIX44..],
+    NOT_REQUIRED, security_type: SecurityType [FIX44..],
+    NOT_REQUIRED, cfi_code: CFICode [FIX44..],
+    NOT_REQUIRED, effective_time: EffectiveTime [FIX44..],
+    NOT_REQUIRED, expire_time: ExpireTime [FIX44..],
+    NOT_REQUIRED, last_update_time: LastUpdateTime [FIX44..],
+    NOT_REQUIRED, settl_delivery_type: SettlDeliveryType [FIX44..],
+    NOT_REQUIRED, stand_inst_db_type: StandInstDbType [FIX44..],
+    NOT_REQUIRED, stand_inst_db_name: StandInstDbName [FIX44..],
+    NOT_REQUIRED, stand_inst_db_id: StandInstDbID [FIX44..],
+});
+
+define_message!(SideCrossOrdCxlGrp {
+    REQUIRED, side: SideField [FIX43..],
+    REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX43..],
+    REQUIRED, cl_ord_id: ClOrdID [FIX43..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_message!(SideCrossOrdModGrp {
+    REQUIRED, side: SideField [FIX43..],
+    REQUIRED, cl_ord_id: ClOrdID [FIX43..],
+    NOT_REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX43..], //Only used by CrossOrderCancelReplaceRequest.
+    NOT_REQUIRED, account: Account [FIX43..],
+    REQUIRED, order_qty: OrderQty [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_message!(TrdCollGrp {
+    REQUIRED, trade_report_id: TradeReportID [FIX44..],
+    NOT_REQUIRED, secondary_trade_report_id: SecondaryTradeReportID [FIX44..],
+});
+
 define_message!(UnderlyingInstrument {
     REQUIRED, underlying_symbol: UnderlyingSymbol [FIX43..],
     NOT_REQUIRED, underlying_symbol_sfx: UnderlyingSymbolSfx [FIX43..],
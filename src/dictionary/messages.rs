@@ -11,6 +11,7 @@
 
 use std::any::Any;
 
+use dictionary::field_types::other as other_field_types;
 use dictionary::fields::*;
 use field::Field;
 use field_tag::{self,FieldTag};
@@ -18,7 +19,7 @@ use field_type::FieldType;
 use fix_version::FIXVersion;
 use fixt;
 use fixt::message::FIXTMessage;
-use message::{self,REQUIRED,NOT_REQUIRED,Message,Meta,SetValueError};
+use message::{self,REQUIRED,NOT_REQUIRED,Message,MessageVisitor,Meta,SetValueError};
 use message_version::{self,MessageVersion};
 
 pub struct NullMessage {
@@ -64,6 +65,10 @@ impl Message for NullMessage {
     fn read_body(&self,_fix_version: FIXVersion,_message_version: MessageVersion,_buf: &mut Vec<u8>) -> usize {
         unimplemented!();
     }
+
+    fn visit(&self,_fix_version: FIXVersion,_message_version: MessageVersion,_visitor: &mut MessageVisitor) {
+        unimplemented!();
+    }
 }
 
 impl FIXTMessage for NullMessage {
@@ -71,6 +76,10 @@ impl FIXTMessage for NullMessage {
         unimplemented!();
     }
 
+    fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+        unimplemented!();
+    }
+
     fn msg_type(&self) -> &'static [u8] {
         unimplemented!();
     }
@@ -118,6 +127,7 @@ impl FIXTMessage for NullMessage {
 //FIXT Administrative Messages
 define_fixt_message!(Heartbeat: ADMIN b"0" => {
     NOT_REQUIRED, test_req_id: TestReqID [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..], //Not part of the spec but some venues use it for status, like throttling warnings. See EngineEvent::AdminNotice.
 });
 
 define_fixt_message!(Logon: ADMIN b"A" => {
@@ -129,7 +139,6 @@ define_fixt_message!(Logon: ADMIN b"A" => {
     NOT_REQUIRED, next_expected_msg_seq_num: NextExpectedMsgSeqNum [FIX44..],
     NOT_REQUIRED, max_message_size: MaxMessageSize [FIX42..],
     NOT_REQUIRED, no_msg_types: NoMsgTypeGrp [FIX42..],
-    NOT_REQUIRED, test_message_indicator: TestMessageIndicator [FIX43..],
     NOT_REQUIRED, username: Username [FIX43..],
     NOT_REQUIRED, password: Password [FIX43..],
     NOT_REQUIRED, new_password: NewPassword [FIX50SP1..],
@@ -149,6 +158,7 @@ define_fixt_message!(Logon: ADMIN b"A" => {
 
 define_fixt_message!(TestRequest: ADMIN b"1" => {
     REQUIRED, test_req_id: TestReqID [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..], //Not part of the spec. See Heartbeat.
 });
 
 define_fixt_message!(ResendRequest: ADMIN b"2" => {
@@ -215,6 +225,48 @@ define_fixt_message!(BusinessMessageReject: b"j" => {
     NOT_REQUIRED, encoded_text: EncodedText [FIX42..],
 });
 
+define_fixt_message!(Advertisement: b"7" => { //TODO: Only the fields needed to distribute advertisements are included so far.
+    REQUIRED, adv_id: AdvId [FIX40..],
+    REQUIRED, adv_trans_type: AdvTransType [FIX40..],
+    NOT_REQUIRED, adv_ref_id: AdvRefID [FIX40..] => REQUIRED_WHEN |message: &Advertisement,_| { message.adv_trans_type != other_field_types::AdvTransType::New },
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &Advertisement,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, adv_side: AdvSide [FIX40..],
+    REQUIRED, quantity: Quantity [FIX40..],
+    NOT_REQUIRED, price: Price [FIX40..],
+    NOT_REQUIRED, currency: Currency [FIX43..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX43..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, url_link: URLLink [FIX42..],
+    NOT_REQUIRED, last_mkt: LastMkt [FIX41..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX42..],
+    NOT_REQUIRED, trading_session_sub_id: TradingSessionSubID [FIX43..],
+});
+
+define_fixt_message!(IOI: b"6" => { //TODO: Only the fields needed to distribute indications of interest are included so far.
+    REQUIRED, ioi_id: IOIID [FIX40..],
+    REQUIRED, ioi_trans_type: IOITransType [FIX40..],
+    NOT_REQUIRED, ioi_ref_id: IOIRefID [FIX40..] => REQUIRED_WHEN |message: &IOI,_| { message.ioi_trans_type != other_field_types::IOITransType::New },
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &IOI,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, side: SideField [FIX40..],
+    REQUIRED, ioi_qty: IOIQty [FIX40..],
+    NOT_REQUIRED, currency: Currency [FIX40..],
+    NOT_REQUIRED, price_type: PriceType [FIX43..],
+    NOT_REQUIRED, price: Price [FIX40..],
+    NOT_REQUIRED, valid_until_time: ValidUntilTime [FIX40..],
+    NOT_REQUIRED, ioi_qlty_ind: IOIQltyInd [FIX40..],
+    NOT_REQUIRED, ioi_natural_flag: IOINaturalFlag [FIX40..],
+    NOT_REQUIRED, no_ioi_qualifiers: NoIOIQualifiers [FIX42..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX40..],
+    NOT_REQUIRED, url_link: URLLink [FIX42..],
+    NOT_REQUIRED, no_routing_ids: NoRoutingIDs [FIX42..],
+});
+
 define_fixt_message!(NewOrderSingle: b"D" => { //TODO: All version info for this message is wrong.
     REQUIRED, cl_ord_id: ClOrdID [FIX40..],
     /*NOT_REQUIRED, secondary_cl_ord_id: SecondaryClOrdID,
@@ -316,3 +368,562 @@ define_fixt_message!(NewOrderSingle: b"D" => { //TODO: All version info for this
     NOT_REQUIRED, ref_order_id: RefOrderID,
     NOT_REQUIRED, ref_order_id_source: RefOrderIDSource,*/
 });
+
+define_fixt_message!(ExecutionReport: b"8" => { //TODO: Only the fields needed to track orders are included so far.
+    REQUIRED, order_id: OrderID [FIX40..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX40..],
+    NOT_REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX41..],
+    REQUIRED, exec_id: ExecID [FIX40..],
+    REQUIRED, exec_type: ExecType [FIX41..],
+    REQUIRED, ord_status: OrdStatus [FIX40..],
+    NOT_REQUIRED, account: Account [FIX40..],
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &ExecutionReport,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, side: SideField [FIX40..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX40..],
+    NOT_REQUIRED, ord_type: OrdType [FIX40..],
+    NOT_REQUIRED, price: Price [FIX40..],
+    NOT_REQUIRED, last_qty: LastQty [FIX40..],
+    NOT_REQUIRED, last_px: LastPx [FIX40..],
+    REQUIRED, leaves_qty: LeavesQty [FIX41..],
+    REQUIRED, cum_qty: CumQty [FIX40..],
+    NOT_REQUIRED, avg_px: AvgPx [FIX40..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, multi_leg_reporting_type: MultiLegReportingType [FIX43..],
+    NOT_REQUIRED, no_legs: NoLegExecs [FIX44..],
+});
+
+define_fixt_message!(NewOrderMultileg: b"AB" => { //TODO: Only the fields needed to send spread and strategy orders are included so far.
+    REQUIRED, cl_ord_id: ClOrdID [FIX43..],
+    NOT_REQUIRED, account: Account [FIX43..],
+    NOT_REQUIRED, handl_inst: HandlInst [FIX43..],
+    REQUIRED, side: SideField [FIX43..],
+    REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &NewOrderMultileg,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, no_legs: NoLegOrds [FIX44..], //TODO: FIX 4.3 legs are not supported yet.
+    REQUIRED, transact_time: TransactTime [FIX43..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX43..],
+    REQUIRED, ord_type: OrdType [FIX43..],
+    NOT_REQUIRED, price: Price [FIX43..],
+    NOT_REQUIRED, time_in_force: TimeInForce [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(NewOrderList: b"E" => { //TODO: FIX 4.0 and 4.1 sent one order per message and are not supported.
+    REQUIRED, list_id: ListID [FIX42..],
+    NOT_REQUIRED, bid_id: BidID [FIX42..],
+    NOT_REQUIRED, client_bid_id: ClientBidID [FIX42..],
+    NOT_REQUIRED, prog_rpt_reqs: ProgRptReqs [FIX42..],
+    REQUIRED, bid_type: BidType [FIX42..],
+    NOT_REQUIRED, prog_period_interval: ProgPeriodInterval [FIX42..],
+    NOT_REQUIRED, list_exec_inst_type: ListExecInstType [FIX42..],
+    NOT_REQUIRED, list_exec_inst: ListExecInst [FIX42..],
+    REQUIRED, tot_no_orders: TotNoOrders [FIX42..],
+    NOT_REQUIRED, last_fragment: LastFragment [FIX44..],
+    REQUIRED, no_orders: NoListOrders [FIX42..],
+});
+
+define_fixt_message!(ListStatus: b"N" => {
+    REQUIRED, list_id: ListID [FIX42..],
+    REQUIRED, list_status_type: ListStatusType [FIX42..],
+    REQUIRED, no_rpts: NoRpts [FIX42..],
+    REQUIRED, list_order_status: ListOrderStatus [FIX42..],
+    REQUIRED, rpt_seq: RptSeq [FIX42..],
+    NOT_REQUIRED, list_status_text: ListStatusText [FIX42..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX42..],
+    REQUIRED, tot_no_orders: TotNoOrders [FIX42..],
+    NOT_REQUIRED, last_fragment: LastFragment [FIX44..],
+    REQUIRED, no_orders: NoListStatusOrders [FIX42..],
+});
+
+define_fixt_message!(ListExecute: b"L" => {
+    REQUIRED, list_id: ListID [FIX42..],
+    NOT_REQUIRED, client_bid_id: ClientBidID [FIX42..],
+    NOT_REQUIRED, bid_id: BidID [FIX42..],
+    REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+});
+
+define_fixt_message!(ListCancelRequest: b"K" => {
+    REQUIRED, list_id: ListID [FIX42..],
+    REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+});
+
+define_fixt_message!(OrderCancelReject: b"9" => {
+    REQUIRED, order_id: OrderID [FIX40..],
+    REQUIRED, cl_ord_id: ClOrdID [FIX40..],
+    NOT_REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX41..],
+    REQUIRED, ord_status: OrdStatus [FIX41..],
+    REQUIRED, cxl_rej_response_to: CxlRejResponseTo [FIX42..],
+    NOT_REQUIRED, cxl_rej_reason: CxlRejReason [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..],
+});
+
+define_fixt_message!(OrderCancelRequest: b"F" => { //TODO: Only the fields needed to track orders are included so far.
+    REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX40..],
+    NOT_REQUIRED, order_id: OrderID [FIX40..],
+    REQUIRED, cl_ord_id: ClOrdID [FIX40..],
+    NOT_REQUIRED, account: Account [FIX40..],
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    REQUIRED, side: SideField [FIX40..],
+    REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..],
+});
+
+define_fixt_message!(OrderCancelReplaceRequest: b"G" => { //TODO: Only the fields needed to track orders are included so far.
+    NOT_REQUIRED, order_id: OrderID [FIX40..],
+    REQUIRED, orig_cl_ord_id: OrigClOrdID [FIX40..],
+    REQUIRED, cl_ord_id: ClOrdID [FIX40..],
+    NOT_REQUIRED, account: Account [FIX40..],
+    NOT_REQUIRED, handl_inst: HandlInst [FIX40..],
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    REQUIRED, side: SideField [FIX40..],
+    REQUIRED, transact_time: TransactTime [FIX42..],
+    REQUIRED, order_qty: OrderQty [FIX40..],
+    REQUIRED, ord_type: OrdType [FIX40..],
+    NOT_REQUIRED, price: Price [FIX40..],
+    NOT_REQUIRED, time_in_force: TimeInForce [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..],
+});
+
+define_fixt_message!(NewOrderCross: b"s" => { //TODO: Only the fields needed to send crossed orders are included so far.
+    REQUIRED, cross_id: CrossID [FIX43..],
+    REQUIRED, cross_type: CrossType [FIX43..],
+    REQUIRED, cross_prioritization: CrossPrioritization [FIX43..],
+    REQUIRED, no_sides: NoCrossSides [FIX43..],
+    REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &NewOrderCross,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, handl_inst: HandlInst [FIX43..],
+    REQUIRED, transact_time: TransactTime [FIX43..],
+    REQUIRED, ord_type: OrdType [FIX43..],
+    NOT_REQUIRED, price: Price [FIX43..],
+    NOT_REQUIRED, time_in_force: TimeInForce [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(CrossOrderCancelReplaceRequest: b"t" => { //TODO: Only the fields needed to send crossed orders are included so far.
+    NOT_REQUIRED, order_id: OrderID [FIX43..],
+    REQUIRED, cross_id: CrossID [FIX43..],
+    REQUIRED, orig_cross_id: OrigCrossID [FIX43..],
+    REQUIRED, cross_type: CrossType [FIX43..],
+    REQUIRED, cross_prioritization: CrossPrioritization [FIX43..],
+    REQUIRED, no_sides: NoCrossSides [FIX43..],
+    REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &CrossOrderCancelReplaceRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, handl_inst: HandlInst [FIX43..],
+    REQUIRED, transact_time: TransactTime [FIX43..],
+    REQUIRED, ord_type: OrdType [FIX43..],
+    NOT_REQUIRED, price: Price [FIX43..],
+    NOT_REQUIRED, time_in_force: TimeInForce [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(CrossOrderCancelRequest: b"u" => {
+    NOT_REQUIRED, order_id: OrderID [FIX43..],
+    REQUIRED, cross_id: CrossID [FIX43..],
+    REQUIRED, orig_cross_id: OrigCrossID [FIX43..],
+    REQUIRED, cross_type: CrossType [FIX43..],
+    REQUIRED, cross_prioritization: CrossPrioritization [FIX43..],
+    REQUIRED, no_sides: NoCrossCancelSides [FIX43..],
+    REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &CrossOrderCancelRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, transact_time: TransactTime [FIX43..],
+});
+
+define_fixt_message!(MarketDataRequest: b"V" => {
+    REQUIRED, md_req_id: MDReqID [FIX42..],
+    REQUIRED, subscription_request_type: SubscriptionRequestType [FIX42..],
+    REQUIRED, market_depth: MarketDepth [FIX42..],
+    NOT_REQUIRED, md_update_type: MDUpdateType [FIX42..] => REQUIRED_WHEN |message: &MarketDataRequest,_| { message.subscription_request_type == other_field_types::SubscriptionRequestType::SnapshotPlusUpdates },
+    REQUIRED, no_md_entry_types: NoMDEntryTypes [FIX42..],
+    REQUIRED, no_related_sym: NoRelatedSym [FIX42..],
+});
+
+define_fixt_message!(MarketDataSnapshotFullRefresh: b"W" => {
+    NOT_REQUIRED, md_req_id: MDReqID [FIX42..],
+    REQUIRED, symbol: Symbol [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX42..] => REQUIRED_WHEN |message: &MarketDataSnapshotFullRefresh,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, no_legs: NoLegs [FIX44..],
+    REQUIRED, no_md_entries: NoMDEntries [FIX42..],
+});
+
+define_fixt_message!(MarketDataIncrementalRefresh: b"X" => {
+    NOT_REQUIRED, md_req_id: MDReqID [FIX42..],
+    REQUIRED, no_md_entries: NoMDIncEntries [FIX42..],
+});
+
+define_fixt_message!(MarketDataRequestReject: b"Y" => {
+    REQUIRED, md_req_id: MDReqID [FIX42..],
+    NOT_REQUIRED, md_req_rej_reason: MDReqRejReason [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+    NOT_REQUIRED, encoded_text_len: EncodedTextLen [FIX42..],
+    NOT_REQUIRED, encoded_text: EncodedText [FIX42..],
+});
+
+define_fixt_message!(SecurityDefinitionRequest: b"c" => { //TODO: Only the fields needed to fetch reference data are included so far.
+    REQUIRED, security_req_id: SecurityReqID [FIX40..],
+    REQUIRED, security_request_type: SecurityRequestType [FIX40..],
+    NOT_REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &SecurityDefinitionRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_type: SecurityType [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_exchange: SecurityExchange [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, currency: Currency [FIX40..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX42..],
+});
+
+define_fixt_message!(SecurityDefinition: b"d" => { //TODO: Only the fields needed to fetch reference data are included so far.
+    NOT_REQUIRED, security_req_id: SecurityReqID [FIX40..],
+    NOT_REQUIRED, security_response_id: SecurityResponseID [FIX40..],
+    NOT_REQUIRED, security_response_type: SecurityResponseType [FIX40..],
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &SecurityDefinition,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_type: SecurityType [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_exchange: SecurityExchange [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_desc: SecurityDesc [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, contract_multiplier: ContractMultiplier [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, min_price_increment: MinPriceIncrement [FIX50..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, currency: Currency [FIX40..],
+    NOT_REQUIRED, round_lot: RoundLot [FIX44..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX42..],
+});
+
+define_fixt_message!(SecurityListRequest: b"x" => { //TODO: Only the fields needed to fetch reference data are included so far.
+    REQUIRED, security_req_id: SecurityReqID [FIX43..],
+    REQUIRED, security_list_request_type: SecurityListRequestType [FIX43..],
+    NOT_REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &SecurityListRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_type: SecurityType [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_exchange: SecurityExchange [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, currency: Currency [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX43..],
+});
+
+define_fixt_message!(SecurityList: b"y" => { //TODO: Only the fields needed to fetch reference data are included so far.
+    NOT_REQUIRED, security_req_id: SecurityReqID [FIX43..],
+    REQUIRED, security_response_id: SecurityResponseID [FIX43..],
+    NOT_REQUIRED, security_request_result: SecurityRequestResult [FIX43..],
+    NOT_REQUIRED, tot_no_related_sym: TotNoRelatedSym [FIX44..],
+    NOT_REQUIRED, last_fragment: LastFragment [FIX44..],
+    NOT_REQUIRED, no_related_sym: NoRelatedSym [FIX43..],
+});
+
+define_fixt_message!(SecurityStatusRequest: b"e" => {
+    REQUIRED, security_status_req_id: SecurityStatusReqID [FIX42..],
+    REQUIRED, symbol: Symbol [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX42..] => REQUIRED_WHEN |message: &SecurityStatusRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_exchange: SecurityExchange [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, currency: Currency [FIX42..],
+    REQUIRED, subscription_request_type: SubscriptionRequestType [FIX42..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX42..],
+    NOT_REQUIRED, trading_session_sub_id: TradingSessionSubID [FIX43..],
+});
+
+define_fixt_message!(SecurityStatus: b"f" => {
+    NOT_REQUIRED, security_status_req_id: SecurityStatusReqID [FIX42..],
+    REQUIRED, symbol: Symbol [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX42..] => REQUIRED_WHEN |message: &SecurityStatus,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_exchange: SecurityExchange [FIX42..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, currency: Currency [FIX42..],
+    NOT_REQUIRED, trading_session_id: TradingSessionID [FIX42..],
+    NOT_REQUIRED, trading_session_sub_id: TradingSessionSubID [FIX43..],
+    NOT_REQUIRED, unsolicited_indicator: UnsolicitedIndicator [FIX42..],
+    NOT_REQUIRED, security_trading_status: SecurityTradingStatus [FIX42..],
+    NOT_REQUIRED, halt_reason: HaltReason [FIX42..],
+    NOT_REQUIRED, in_view_of_common: InViewOfCommon [FIX42..],
+    NOT_REQUIRED, due_to_related: DueToRelated [FIX42..],
+    NOT_REQUIRED, buy_volume: BuyVolume [FIX42..],
+    NOT_REQUIRED, sell_volume: SellVolume [FIX42..],
+    NOT_REQUIRED, high_px: HighPx [FIX42..],
+    NOT_REQUIRED, low_px: LowPx [FIX42..],
+    NOT_REQUIRED, last_px: LastPx [FIX42..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, adjustment: Adjustment [FIX42..],
+    NOT_REQUIRED, text: Text [FIX42..],
+});
+
+define_fixt_message!(QuoteRequest: b"R" => { //TODO: FIX 4.0 and 4.1 requested a single symbol without a repeating group and are not supported.
+    REQUIRED, quote_req_id: QuoteReqID [FIX42..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX43..],
+    REQUIRED, no_related_sym: NoQuoteRelatedSym [FIX42..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(QuoteRequestReject: b"AG" => {
+    REQUIRED, quote_req_id: QuoteReqID [FIX43..],
+    REQUIRED, quote_request_reject_reason: QuoteRequestRejectReason [FIX43..],
+    REQUIRED, no_related_sym: NoQuoteRelatedSym [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(Quote: b"S" => { //TODO: Only the fields needed to negotiate quotes are included so far.
+    NOT_REQUIRED, quote_req_id: QuoteReqID [FIX40..],
+    REQUIRED, quote_id: QuoteID [FIX40..],
+    NOT_REQUIRED, quote_resp_id: QuoteRespID [FIX44..],
+    NOT_REQUIRED, quote_type: QuoteType [FIX44..],
+    REQUIRED, symbol: Symbol [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX40..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX40..] => REQUIRED_WHEN |message: &Quote,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, side: NotRequiredSideField [FIX44..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX44..],
+    NOT_REQUIRED, bid_px: BidPx [FIX40..],
+    NOT_REQUIRED, offer_px: OfferPx [FIX40..],
+    NOT_REQUIRED, bid_size: BidSize [FIX40..],
+    NOT_REQUIRED, offer_size: OfferSize [FIX40..],
+    NOT_REQUIRED, valid_until_time: ValidUntilTime [FIX40..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX42..],
+    NOT_REQUIRED, currency: Currency [FIX42..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(QuoteResponse: b"AJ" => { //TODO: Only the fields needed to negotiate quotes are included so far.
+    REQUIRED, quote_resp_id: QuoteRespID [FIX44..],
+    NOT_REQUIRED, quote_id: QuoteID [FIX44..],
+    REQUIRED, quote_resp_type: QuoteRespType [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &QuoteResponse,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, side: NotRequiredSideField [FIX44..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX44..],
+    NOT_REQUIRED, bid_px: BidPx [FIX44..],
+    NOT_REQUIRED, offer_px: OfferPx [FIX44..],
+    NOT_REQUIRED, valid_until_time: ValidUntilTime [FIX44..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, price: Price [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(QuoteStatusReport: b"AI" => { //TODO: Only the fields needed to negotiate quotes are included so far.
+    NOT_REQUIRED, quote_req_id: QuoteReqID [FIX43..],
+    REQUIRED, quote_id: QuoteID [FIX43..],
+    NOT_REQUIRED, quote_resp_id: QuoteRespID [FIX44..],
+    NOT_REQUIRED, quote_type: QuoteType [FIX44..],
+    REQUIRED, symbol: Symbol [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX43..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX43..] => REQUIRED_WHEN |message: &QuoteStatusReport,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, side: NotRequiredSideField [FIX44..],
+    NOT_REQUIRED, order_qty: OrderQty [FIX44..],
+    NOT_REQUIRED, bid_px: BidPx [FIX43..],
+    NOT_REQUIRED, offer_px: OfferPx [FIX43..],
+    NOT_REQUIRED, valid_until_time: ValidUntilTime [FIX43..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX43..],
+    NOT_REQUIRED, quote_status: QuoteStatus [FIX43..],
+    NOT_REQUIRED, text: Text [FIX43..],
+});
+
+define_fixt_message!(AllocationInstructionAck: b"P" => { //TODO: Only the fields needed to track allocations are included so far. Named AllocationAck before FIX 4.4.
+    REQUIRED, alloc_id: AllocID [FIX40..],
+    NOT_REQUIRED, secondary_alloc_id: SecondaryAllocID [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX40..], //Required before FIX 4.4.
+    NOT_REQUIRED, transact_time: TransactTime [FIX40..], //Required in FIX 4.4 and later.
+    REQUIRED, alloc_status: AllocStatus [FIX40..],
+    NOT_REQUIRED, alloc_rej_code: AllocRejCode [FIX40..],
+    NOT_REQUIRED, alloc_type: AllocType [FIX44..],
+    NOT_REQUIRED, text: Text [FIX40..],
+    NOT_REQUIRED, no_allocs: NoAllocAcks [FIX44..],
+});
+
+define_fixt_message!(Confirmation: b"AK" => { //TODO: Only the fields needed to track allocations are included so far.
+    REQUIRED, confirm_id: ConfirmID [FIX44..],
+    NOT_REQUIRED, confirm_ref_id: ConfirmRefID [FIX44..] => REQUIRED_WHEN |message: &Confirmation,_| { message.confirm_trans_type != other_field_types::ConfirmTransType::New },
+    REQUIRED, confirm_trans_type: ConfirmTransType [FIX44..],
+    REQUIRED, confirm_type: ConfirmType [FIX44..],
+    REQUIRED, confirm_status: ConfirmStatus [FIX44..],
+    NOT_REQUIRED, alloc_id: AllocID [FIX44..],
+    NOT_REQUIRED, secondary_alloc_id: SecondaryAllocID [FIX44..],
+    NOT_REQUIRED, individual_alloc_id: IndividualAllocID [FIX44..],
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    REQUIRED, trade_date: TradeDate [FIX44..],
+    REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &Confirmation,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    REQUIRED, alloc_qty: AllocQty [FIX44..],
+    REQUIRED, side: SideField [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, last_mkt: LastMkt [FIX44..],
+    REQUIRED, alloc_account: AllocAccount [FIX44..],
+    REQUIRED, avg_px: AvgPx [FIX44..],
+    REQUIRED, gross_trade_amt: GrossTradeAmt [FIX44..],
+    REQUIRED, net_money: NetMoney [FIX44..],
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(ConfirmationAck: b"AU" => {
+    REQUIRED, confirm_id: ConfirmID [FIX44..],
+    REQUIRED, trade_date: TradeDate [FIX44..],
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    REQUIRED, affirm_status: AffirmStatus [FIX44..],
+    NOT_REQUIRED, confirm_rej_reason: ConfirmRejReason [FIX44..] => REQUIRED_WHEN |message: &ConfirmationAck,_| { message.affirm_status == other_field_types::AffirmStatus::ConfirmRejected },
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(SettlementInstructions: b"T" => { //TODO: FIX 4.0 through 4.3 used a completely different layout and are not supported.
+    REQUIRED, settl_inst_msg_id: SettlInstMsgID [FIX44..],
+    NOT_REQUIRED, settl_inst_req_id: SettlInstReqID [FIX44..],
+    REQUIRED, settl_inst_mode: SettlInstMode [FIX44..],
+    NOT_REQUIRED, settl_inst_req_rej_code: SettlInstReqRejCode [FIX44..] => REQUIRED_WHEN |message: &SettlementInstructions,_| { message.settl_inst_mode == other_field_types::SettlInstMode::RequestReject },
+    NOT_REQUIRED, text: Text [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, no_settl_inst: NoSettlInst [FIX44..],
+});
+
+define_fixt_message!(CollateralRequest: b"AX" => { //TODO: Only the fields needed to exchange collateral with clearing and margin systems are included so far.
+    REQUIRED, coll_req_id: CollReqID [FIX44..],
+    REQUIRED, coll_asgn_reason: CollAsgnReason [FIX44..],
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, expire_time: ExpireTime [FIX44..],
+    NOT_REQUIRED, account: Account [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    NOT_REQUIRED, order_id: OrderID [FIX44..],
+    NOT_REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &CollateralRequest,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, quantity: Quantity [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, no_trades: NoTrades [FIX44..],
+    NOT_REQUIRED, margin_excess: MarginExcess [FIX44..],
+    NOT_REQUIRED, total_net_value: TotalNetValue [FIX44..],
+    NOT_REQUIRED, cash_outstanding: CashOutstanding [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(CollateralAssignment: b"AY" => { //TODO: Only the fields needed to exchange collateral with clearing and margin systems are included so far.
+    REQUIRED, coll_asgn_id: CollAsgnID [FIX44..],
+    NOT_REQUIRED, coll_req_id: CollReqID [FIX44..],
+    REQUIRED, coll_asgn_reason: CollAsgnReason [FIX44..],
+    REQUIRED, coll_asgn_trans_type: CollAsgnTransType [FIX44..],
+    NOT_REQUIRED, coll_asgn_ref_id: CollAsgnRefID [FIX44..] => REQUIRED_WHEN |message: &CollateralAssignment,_| { message.coll_asgn_trans_type != other_field_types::CollAsgnTransType::New },
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, expire_time: ExpireTime [FIX44..],
+    NOT_REQUIRED, account: Account [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    NOT_REQUIRED, order_id: OrderID [FIX44..],
+    NOT_REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &CollateralAssignment,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, quantity: Quantity [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, no_trades: NoTrades [FIX44..],
+    NOT_REQUIRED, margin_excess: MarginExcess [FIX44..],
+    NOT_REQUIRED, total_net_value: TotalNetValue [FIX44..],
+    NOT_REQUIRED, cash_outstanding: CashOutstanding [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(CollateralResponse: b"AZ" => { //TODO: Only the fields needed to exchange collateral with clearing and margin systems are included so far.
+    REQUIRED, coll_resp_id: CollRespID [FIX44..],
+    REQUIRED, coll_asgn_id: CollAsgnID [FIX44..],
+    NOT_REQUIRED, coll_req_id: CollReqID [FIX44..],
+    REQUIRED, coll_asgn_reason: CollAsgnReason [FIX44..],
+    REQUIRED, coll_asgn_resp_type: CollAsgnRespType [FIX44..],
+    NOT_REQUIRED, coll_asgn_reject_reason: CollAsgnRejectReason [FIX44..],
+    REQUIRED, transact_time: TransactTime [FIX44..],
+    NOT_REQUIRED, account: Account [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    NOT_REQUIRED, order_id: OrderID [FIX44..],
+    NOT_REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &CollateralResponse,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, quantity: Quantity [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, no_trades: NoTrades [FIX44..],
+    NOT_REQUIRED, margin_excess: MarginExcess [FIX44..],
+    NOT_REQUIRED, total_net_value: TotalNetValue [FIX44..],
+    NOT_REQUIRED, cash_outstanding: CashOutstanding [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(CollateralReport: b"BA" => { //TODO: Only the fields needed to exchange collateral with clearing and margin systems are included so far.
+    REQUIRED, coll_rpt_id: CollRptID [FIX44..],
+    NOT_REQUIRED, coll_inquiry_id: CollInquiryID [FIX44..],
+    REQUIRED, coll_status: CollStatus [FIX44..],
+    NOT_REQUIRED, tot_num_reports: TotNumReports [FIX44..],
+    NOT_REQUIRED, last_rpt_requested: LastRptRequested [FIX44..],
+    NOT_REQUIRED, account: Account [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    NOT_REQUIRED, order_id: OrderID [FIX44..],
+    NOT_REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &CollateralReport,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, quantity: Quantity [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, no_trades: NoTrades [FIX44..],
+    NOT_REQUIRED, margin_excess: MarginExcess [FIX44..],
+    NOT_REQUIRED, total_net_value: TotalNetValue [FIX44..],
+    NOT_REQUIRED, cash_outstanding: CashOutstanding [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(CollateralInquiry: b"BB" => { //TODO: Only the fields needed to exchange collateral with clearing and margin systems are included so far.
+    NOT_REQUIRED, coll_inquiry_id: CollInquiryID [FIX44..],
+    NOT_REQUIRED, no_coll_inquiry_qualifier: NoCollInquiryQualifier [FIX44..],
+    NOT_REQUIRED, account: Account [FIX44..],
+    NOT_REQUIRED, cl_ord_id: ClOrdID [FIX44..],
+    NOT_REQUIRED, order_id: OrderID [FIX44..],
+    NOT_REQUIRED, symbol: Symbol [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id: SecurityID [FIX44..], //TODO: Part of the Instrument block.
+    NOT_REQUIRED, security_id_source: SecurityIDSource [FIX44..] => REQUIRED_WHEN |message: &CollateralInquiry,_| { !message.security_id.is_empty() }, //TODO: Part of the Instrument block.
+    NOT_REQUIRED, settl_date: SettlDate [FIX44..],
+    NOT_REQUIRED, quantity: Quantity [FIX44..],
+    NOT_REQUIRED, currency: Currency [FIX44..],
+    NOT_REQUIRED, no_trades: NoTrades [FIX44..],
+    NOT_REQUIRED, margin_excess: MarginExcess [FIX44..],
+    NOT_REQUIRED, total_net_value: TotalNetValue [FIX44..],
+    NOT_REQUIRED, cash_outstanding: CashOutstanding [FIX44..],
+    NOT_REQUIRED, trade_date: TradeDate [FIX44..],
+    NOT_REQUIRED, text: Text [FIX44..],
+});
+
+define_fixt_message!(PartyRiskLimitsRequest: b"CL" => { //TODO: Only the fields needed to pull risk limits from sponsors and clearers are included so far.
+    REQUIRED, risk_limit_request_id: RiskLimitRequestID [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_request_type: RiskLimitRequestType [FIX50SP2..],
+    NOT_REQUIRED, no_party_details: NoPartyDetails [FIX50SP2..],
+    NOT_REQUIRED, no_risk_limit_types: NoRiskLimitTypes [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_id: RiskLimitID [FIX50SP2..],
+    NOT_REQUIRED, text: Text [FIX50SP2..],
+});
+
+define_fixt_message!(PartyRiskLimitsReport: b"CM" => { //TODO: Only the fields needed to pull risk limits from sponsors and clearers are included so far.
+    REQUIRED, risk_limit_report_id: RiskLimitReportID [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_request_id: RiskLimitRequestID [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_request_result: RiskLimitRequestResult [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_request_status: RiskLimitRequestStatus [FIX50SP2..],
+    NOT_REQUIRED, no_party_risk_limits: NoPartyRiskLimits [FIX50SP2..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX50SP2..],
+    NOT_REQUIRED, text: Text [FIX50SP2..],
+});
+
+define_fixt_message!(PartyRiskLimitsUpdateReport: b"CR" => { //TODO: Only the fields needed to pull risk limits from sponsors and clearers are included so far.
+    REQUIRED, risk_limit_report_id: RiskLimitReportID [FIX50SP2..],
+    NOT_REQUIRED, risk_limit_request_id: RiskLimitRequestID [FIX50SP2..],
+    NOT_REQUIRED, no_party_risk_limits: NoPartyRiskLimits [FIX50SP2..],
+    NOT_REQUIRED, transact_time: TransactTime [FIX50SP2..],
+    NOT_REQUIRED, text: Text [FIX50SP2..],
+});
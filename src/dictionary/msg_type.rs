@@ -0,0 +1,204 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+macro_rules! define_msg_types {
+    ( $( $variant:ident => $value:expr, )+ ) => {
+        //Every standard MsgType by name. Use this instead of spelling out the bytes so a typo is a
+        //compile error instead of a message that silently never matches. Custom MsgTypes agreed
+        //on with a counterparty, and ones added after FIX 5.0 SP2 like PartyRiskLimitsRequest,
+        //aren't included and are still passed around as bytes.
+        #[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+        pub enum MsgType {
+            $( $variant ),+
+        }
+
+        impl MsgType {
+            pub fn from_bytes(bytes: &[u8]) -> Option<MsgType> {
+                static MAPPING: ::phf::Map<&'static [u8],MsgType> = phf_map! {
+                    $( $value => MsgType::$variant, )+
+                };
+
+                MAPPING.get(bytes).cloned()
+            }
+
+            pub fn as_bytes(&self) -> &'static [u8] {
+                match *self {
+                    $( MsgType::$variant => $value, )+
+                }
+            }
+
+            pub fn all() -> &'static [MsgType] {
+                //In the same order as FIX 5.0 SP2 Volume 6, page 13.
+                static ALL: &'static [MsgType] = &[
+                    $( MsgType::$variant, )+
+                ];
+
+                ALL
+            }
+        }
+    };
+}
+
+define_msg_types!(
+    Heartbeat => b"0",
+    TestRequest => b"1",
+    ResendRequest => b"2",
+    Reject => b"3",
+    SequenceReset => b"4",
+    Logout => b"5",
+    IOI => b"6",
+    Advertisement => b"7",
+    ExecutionReport => b"8",
+    OrderCancelReject => b"9",
+    Logon => b"A",
+    DerivativeSecurityList => b"AA",
+    NewOrderMultileg => b"AB",
+    MultilegOrderCancelReplace => b"AC",
+    TradeCaptureReportRequest => b"AD",
+    TradeCaptureReport => b"AE",
+    OrderMassStatusRequest => b"AF",
+    QuoteRequestReject => b"AG",
+    RFQRequest => b"AH",
+    QuoteStatusReport => b"AI",
+    QuoteResponse => b"AJ",
+    Confirmation => b"AK",
+    PositionMaintenanceRequest => b"AL",
+    PositionMaintenanceReport => b"AM",
+    RequestForPositions => b"AN",
+    RequestForPositionsAck => b"AO",
+    PositionReport => b"AP",
+    TradeCaptureReportRequestAck => b"AQ",
+    TradeCaptureReportAck => b"AR",
+    AllocationReport => b"AS",
+    AllocationReportAck => b"AT",
+    ConfirmationAck => b"AU",
+    SettlementInstructionRequest => b"AV",
+    AssignmentReport => b"AW",
+    CollateralRequest => b"AX",
+    CollateralAssignment => b"AY",
+    CollateralResponse => b"AZ",
+    News => b"B",
+    CollateralReport => b"BA",
+    CollateralInquiry => b"BB",
+    NetworkCounterpartySystemStatusRequest => b"BC",
+    NetworkCounterpartySystemStatusResponse => b"BD",
+    UserRequest => b"BE",
+    UserResponse => b"BF",
+    CollateralInquiryAck => b"BG",
+    ConfirmationRequest => b"BH",
+    TradingSessionListRequest => b"BI",
+    TradingSessionList => b"BJ",
+    SecurityListUpdateReport => b"BK",
+    AdjustedPositionReport => b"BL",
+    AllocationInstructionAlert => b"BM",
+    ExecutionAcknowledgement => b"BN",
+    ContraryIntentionReport => b"BO",
+    SecurityDefinitionUpdateReport => b"BP",
+    SettlementObligationReport => b"BQ",
+    DerivativeSecurityListUpdateReport => b"BR",
+    TradingSessionListUpdateReport => b"BS",
+    MarketDefinitionRequest => b"BT",
+    MarketDefinition => b"BU",
+    MarketDefinitionUpdateReport => b"BV",
+    ApplicationMessageRequest => b"BW",
+    ApplicationMessageRequestAck => b"BX",
+    ApplicationMessageReport => b"BY",
+    OrderMassActionReport => b"BZ",
+    Email => b"C",
+    OrderMassActionRequest => b"CA",
+    UserNotification => b"CB",
+    StreamAssignmentRequest => b"CC",
+    StreamAssignmentReport => b"CD",
+    StreamAssignmentReportAck => b"CE",
+    PartyDetailsListRequest => b"CF",
+    PartyDetailsListReport => b"CG",
+    NewOrderSingle => b"D",
+    NewOrderList => b"E",
+    OrderCancelRequest => b"F",
+    OrderCancelReplaceRequest => b"G",
+    OrderStatusRequest => b"H",
+    AllocationInstruction => b"J",
+    ListCancelRequest => b"K",
+    ListExecute => b"L",
+    ListStatusRequest => b"M",
+    ListStatus => b"N",
+    AllocationInstructionAck => b"P",
+    DontKnowTrade => b"Q",
+    QuoteRequest => b"R",
+    Quote => b"S",
+    SettlementInstructions => b"T",
+    MarketDataRequest => b"V",
+    MarketDataSnapshotFullRefresh => b"W",
+    MarketDataIncrementalRefresh => b"X",
+    MarketDataRequestReject => b"Y",
+    QuoteCancel => b"Z",
+    QuoteStatusRequest => b"a",
+    MassQuoteAcknowledgement => b"b",
+    SecurityDefinitionRequest => b"c",
+    SecurityDefinition => b"d",
+    SecurityStatusRequest => b"e",
+    SecurityStatus => b"f",
+    TradingSessionStatusRequest => b"g",
+    TradingSessionStatus => b"h",
+    MassQuote => b"i",
+    BusinessMessageReject => b"j",
+    BidRequest => b"k",
+    BidResponse => b"l",
+    ListStrikePrice => b"m",
+    XMLNonFIX => b"n",
+    RegistrationInstructions => b"o",
+    RegistrationInstructionsResponse => b"p",
+    OrderMassCancelRequest => b"q",
+    OrderMassCancelReport => b"r",
+    NewOrderCross => b"s",
+    CrossOrderCancelReplaceRequest => b"t",
+    CrossOrderCancelRequest => b"u",
+    SecurityTypeRequest => b"v",
+    SecurityTypes => b"w",
+    SecurityListRequest => b"x",
+    SecurityList => b"y",
+    DerivativeSecurityListRequest => b"z",
+);
+
+impl MsgType {
+    pub fn is_administrative(&self) -> bool {
+        match *self {
+            MsgType::Heartbeat |
+            MsgType::TestRequest |
+            MsgType::ResendRequest |
+            MsgType::Reject |
+            MsgType::SequenceReset |
+            MsgType::Logout |
+            MsgType::Logon => true,
+            _ => false,
+        }
+    }
+}
+
+impl AsRef<[u8]> for MsgType {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<MsgType> for &'static [u8] {
+    fn from(msg_type: MsgType) -> &'static [u8] {
+        msg_type.as_bytes()
+    }
+}
+
+impl fmt::Display for MsgType {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"{}",String::from_utf8_lossy(self.as_bytes()))
+    }
+}
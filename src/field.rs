@@ -12,6 +12,7 @@
 use field_tag::FieldTag;
 use field_type::FieldType;
 use fix_version::FIXVersion;
+use message::MessageVisitor;
 use message_version::MessageVersion;
 use rule::Rule;
 
@@ -20,8 +21,12 @@ pub trait Field {
     fn rule() -> Rule;
     fn tag_bytes() -> &'static [u8];
     fn tag() -> FieldTag;
+    fn name() -> &'static str;
+    fn type_name() -> &'static str;
     fn read(field: &<<Self as Field>::Type as FieldType>::Type,fix_version: FIXVersion,message_version: MessageVersion,buf: &mut Vec<u8>,required: bool) -> usize
         where <Self as Field>::Type: FieldType;
+    fn visit(field: &<<Self as Field>::Type as FieldType>::Type,fix_version: FIXVersion,message_version: MessageVersion,name: &'static str,visitor: &mut MessageVisitor,required: bool)
+        where <Self as Field>::Type: FieldType;
 }
 
 #[macro_export]
@@ -63,6 +68,14 @@ macro_rules! define_fields {
                 Self::tag()
             }
 
+            fn name() -> &'static str {
+                stringify!($field_name)
+            }
+
+            fn type_name() -> &'static str {
+                stringify!($field_type)
+            }
+
             fn read(field: &<<Self as $crate::field::Field>::Type as $crate::field_type::FieldType>::Type,fix_version: $crate::fix_version::FIXVersion,message_version: $crate::message_version::MessageVersion,buf: &mut Vec<u8>,required: bool) -> usize {
                 use ::std::io::Write;
 
@@ -114,6 +127,30 @@ macro_rules! define_fields {
 
                 result
             }
+
+            fn visit(field: &<<Self as $crate::field::Field>::Type as $crate::field_type::FieldType>::Type,fix_version: $crate::fix_version::FIXVersion,message_version: $crate::message_version::MessageVersion,name: &'static str,visitor: &mut $crate::message::MessageVisitor,required: bool) {
+                //Skip exactly the same fields that read() would leave out so visiting matches what
+                //goes over the wire.
+                if !required && <$field_type as $crate::field_type::FieldType>::is_empty(field) {
+                    return;
+                }
+
+                match <$field_name as $crate::field::Field>::rule() {
+                    //The length half of a Rule::PrepareForBytes and Rule::ConfirmPreviousTag pair
+                    //is implied by the value it describes.
+                    $crate::rule::Rule::PrepareForBytes{ .. } => {
+                        return;
+                    },
+                    $crate::rule::Rule::RequiresFIXVersion{ fix_version: required_fix_version } => {
+                        if fix_version != required_fix_version {
+                            return;
+                        }
+                    },
+                    _ => {},
+                };
+
+                <$field_type as $crate::field_type::FieldType>::visit(field,fix_version,message_version,Self::tag(),name,visitor);
+            }
         }
     )*};
 }
@@ -9,8 +9,9 @@
 // at your option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use field_tag::FieldTag;
 use fix_version::FIXVersion;
-use message::{Message,SetValueError};
+use message::{Message,MessageMetadata,MessageVisitor,SetValueError};
 use message_version::MessageVersion;
 use rule::Rule;
 
@@ -23,6 +24,12 @@ pub trait FieldType {
 
     fn default_value() -> Self::Type;
 
+    fn reset_value(field: &mut Self::Type) {
+        //Return the field to its default value so the message it belongs to can be reused. Types
+        //that own memory should override this to keep it around.
+        *field = Self::default_value();
+    }
+
     fn set_value(_field: &mut Self::Type,_bytes: &[u8]) -> Result<(),SetValueError> {
         Err(SetValueError::WrongFormat)
     }
@@ -31,8 +38,20 @@ pub trait FieldType {
         false
     }
 
+    fn group_metadata() -> Option<MessageMetadata> {
+        None
+    }
+
     fn is_empty(field: &Self::Type) -> bool;
     fn len(field: &Self::Type) -> usize;
     fn read(field: &Self::Type,fix_version: FIXVersion,message_version: MessageVersion,buf: &mut Vec<u8>) -> usize;
+
+    fn visit(field: &Self::Type,fix_version: FIXVersion,message_version: MessageVersion,tag: FieldTag,name: &'static str,visitor: &mut MessageVisitor) {
+        //Hand over the value exactly as it would be serialized so visitors never need to know the
+        //concrete type.
+        let mut value = Vec::new();
+        Self::read(field,fix_version,message_version,&mut value);
+        visitor.visit_field(tag,name,&value[..]);
+    }
 }
 
@@ -10,16 +10,18 @@
 // except according to those terms.
 
 use std::borrow::Borrow;
+use std::cmp;
 use std::collections::{HashMap,HashSet};
 use std::collections::hash_map::Entry;
 use std::fmt;
 use std::iter::FromIterator;
 use std::mem;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use constant::{FIX_4_0_BEGIN_STRING,FIX_4_1_BEGIN_STRING,FIX_4_2_BEGIN_STRING,FIX_4_3_BEGIN_STRING,FIX_4_4_BEGIN_STRING,FIXT_1_1_BEGIN_STRING,TAG_END,VALUE_END};
 use dictionary::messages::{Logon,NullMessage};
-use dictionary::fields::{ApplVerID,SenderCompID,TargetCompID};
+use dictionary::fields::{ApplVerID,MsgSeqNum,PossDupFlag,SenderCompID,TargetCompID};
 use dictionary::field_types::other::DefaultApplVerIDFieldType;
 use field::Field;
 use field_tag::FieldTag;
@@ -41,6 +43,8 @@ const BODYLENGTH_TAG: FieldTag = FieldTag(9);
 const MSGTYPE_TAG: FieldTag = FieldTag(35);
 const CHECKSUM_TAG_BYTES: &'static [u8] = b"10";
 const CHECKSUM_TAG: FieldTag = FieldTag(10);
+const ERROR_EXCERPT_BYTES_BEFORE: usize = 48;
+const ERROR_EXCERPT_BYTES_AFTER: usize = 16;
 
 pub enum ParseError {
     MissingRequiredTag(FieldTag,Box<FIXTMessage + Send>), //Required tag was not included in message.
@@ -68,12 +72,178 @@ pub enum ParseError {
     RepeatingGroupTagWithNoRepeatingGroup(FieldTag), //Repeating group tag was found outside of a repeating group.
     MissingFirstRepeatingGroupTagAfterNumberOfRepeatingGroupTag(FieldTag), //Tag indicating start of a repeating group was not found immediatelly after tag indicating the number of repeating groups.
     MessageSizeTooBig,
+    ParserNotIdle, //Parser was in the middle of another message when asked to decode a LazyMessage.
+}
+
+//Named bundles of ValidationSettings.
+//
+//Strict: Everything the spec says to reject is rejected. This is the default.
+//
+//Interop: Tolerates the mistakes commonly made by counterparties that don't otherwise affect how
+//a message is understood. Duplicate tags keep their first value and an ApplVerID that isn't the
+//sixth tag is ignored.
+//
+//Permissive: Accepts anything that can still be parsed. Meant for reading captures and logs or
+//talking to a counterparty that can't be fixed. Also ignores CheckSum, values that aren't one of
+//the allowed choices, and BeginString not matching the session.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ValidationProfile {
+    Strict,
+    Interop,
+    Permissive,
+}
+
+impl ValidationProfile {
+    pub fn settings(&self) -> ValidationSettings {
+        match *self {
+            ValidationProfile::Strict => ValidationSettings {
+                validate_checksum: true,
+                reject_duplicate_tags: true,
+                reject_out_of_range_values: true,
+                reject_misplaced_appl_ver_id: true,
+                reject_wrong_begin_string: true,
+            },
+            ValidationProfile::Interop => ValidationSettings {
+                validate_checksum: true,
+                reject_duplicate_tags: false,
+                reject_out_of_range_values: true,
+                reject_misplaced_appl_ver_id: false,
+                reject_wrong_begin_string: true,
+            },
+            ValidationProfile::Permissive => ValidationSettings {
+                validate_checksum: false,
+                reject_duplicate_tags: false,
+                reject_out_of_range_values: false,
+                reject_misplaced_appl_ver_id: false,
+                reject_wrong_begin_string: false,
+            },
+        }
+    }
+}
+
+//How strictly received messages are checked. Start from a ValidationProfile and override
+//individual settings as needed. The order of the FIXT.1.1 header (SenderCompID fourth and
+//TargetCompID fifth) is always enforced because parsing depends on it.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct ValidationSettings {
+    pub validate_checksum: bool, //Otherwise a CheckSum that doesn't match is ignored. It must still be three digits.
+    pub reject_duplicate_tags: bool, //Otherwise only the first value is kept.
+    pub reject_out_of_range_values: bool, //Otherwise the field is left with its default value.
+    pub reject_misplaced_appl_ver_id: bool, //Otherwise an ApplVerID that isn't the sixth tag is ignored.
+    pub reject_wrong_begin_string: bool, //Otherwise the Engine accepts messages with a different BeginString than the session. Not used by Parser.
+}
+
+impl Default for ValidationSettings {
+    fn default() -> Self {
+        ValidationProfile::Strict.settings()
+    }
 }
 
 fn tag_to_string(tag: &[u8]) -> String {
     String::from_utf8_lossy(tag).into_owned()
 }
 
+//Raw bytes the parser threw away instead of turning into a message. Only collected when enabled
+//with Parser::set_keep_discarded_bytes().
+pub struct DiscardedBytes {
+    pub bytes: Vec<u8>,
+    pub end: usize, //Index just past the last discarded byte in the slice passed to Parser::parse(). Can be less than bytes.len() when the bytes started in a previous call.
+    pub reason: String,
+}
+
+//Where and what the parser was working on when Parser::parse() returned an error. Written into the
+//reason of the DiscardedBytes for the failed message so a decode failure can be diagnosed from a
+//quarantine log alone.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ParseErrorContext {
+    pub offset: usize, //Index of the byte that triggered the error in the slice passed to Parser::parse().
+    pub tag: Option<FieldTag>, //None when the error happened before the tag was completely read.
+    pub msg_type: Option<Vec<u8>>,
+    pub msg_seq_num: Option<u64>,
+    pub excerpt_start: usize, //Index in the same slice where excerpt begins.
+    pub excerpt: Vec<u8>, //Bounded copy of the bytes leading up to and just after offset.
+}
+
+impl fmt::Display for ParseErrorContext {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f,"offset={}",self.offset));
+        if let Some(tag) = self.tag {
+            try!(write!(f," tag={}",tag));
+        }
+        if let Some(ref msg_type) = self.msg_type {
+            try!(write!(f," msg_type={}",String::from_utf8_lossy(msg_type)));
+        }
+        if let Some(msg_seq_num) = self.msg_seq_num {
+            try!(write!(f," msg_seq_num={}",msg_seq_num));
+        }
+
+        //SOH is shown as '|' and any other unprintable byte as '.' so the excerpt always fits on
+        //one line. The hex dump has the exact bytes.
+        try!(write!(f," excerpt_start={} ascii=",self.excerpt_start));
+        for byte in &self.excerpt {
+            let c = match *byte {
+                VALUE_END => '|',
+                b' '...b'~' => *byte as char,
+                _ => '.',
+            };
+            try!(write!(f,"{}",c));
+        }
+        try!(write!(f," hex="));
+        for byte in &self.excerpt {
+            try!(write!(f,"{:02x}",byte));
+        }
+
+        Ok(())
+    }
+}
+
+//Header values pulled out of a complete message by Parser::prescan() without building the
+//message. Slices point into the bytes that were scanned.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct PrescannedMessage<'a> {
+    pub length: usize, //Bytes from the start of BeginStr through the SOH after CheckSum.
+    pub msg_type: &'a [u8],
+    pub msg_seq_num: Option<u64>, //None when MsgSeqNum is missing or not a number.
+    pub poss_dup: bool,
+    pub sender_comp_id: &'a [u8],
+    pub target_comp_id: &'a [u8],
+}
+
+fn prescan_field<'a>(message_bytes: &'a [u8],index: &mut usize) -> Option<(FieldTag,&'a [u8])> {
+    //Read the tag=value pair starting at index and move index past its SOH.
+    let remaining = &message_bytes[*index..];
+    let tag_end = match remaining.iter().position(|c| *c == TAG_END) {
+        Some(tag_end) => tag_end,
+        None => return None,
+    };
+    let value_end = match remaining[tag_end + 1..].iter().position(|c| *c == VALUE_END) {
+        Some(value_length) => tag_end + 1 + value_length,
+        None => return None,
+    };
+
+    *index += value_end + 1;
+    Some((FieldTag::from(&remaining[..tag_end]),&remaining[tag_end + 1..value_end]))
+}
+
+fn prescan_number(bytes: &[u8]) -> Option<u64> {
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let mut number: u64 = 0;
+    for byte in bytes {
+        if *byte < b'0' || *byte > b'9' {
+            return None;
+        }
+        number = match number.checked_mul(10).and_then(|number| number.checked_add((*byte - b'0') as u64)) {
+            Some(number) => number,
+            None => return None,
+        };
+    }
+
+    Some(number)
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -102,6 +272,7 @@ impl fmt::Display for ParseError {
             ParseError::RepeatingGroupTagWithNoRepeatingGroup(ref tag) => write!(f,"ParseError::RepeatingGroupTagWithNoRepeatingGroup({})",tag),
             ParseError::MissingFirstRepeatingGroupTagAfterNumberOfRepeatingGroupTag(ref number_of_tag) => write!(f,"ParseError::MissingFirstRepeatingGroupTagAfterNumberOfRepeatingGroupTag({})",number_of_tag),
             ParseError::MessageSizeTooBig => write!(f,"ParseError::MessageSizeTooBig"),
+            ParseError::ParserNotIdle => write!(f,"ParseError::ParserNotIdle"),
         }
     }
 }
@@ -112,18 +283,22 @@ impl fmt::Debug for ParseError {
     }
 }
 
-struct ParseGroupState {
-    remaining_fields: FieldHashMap,
-    remaining_required_fields: FieldHashSet,
-    message: Box<Message>,
+//Fields supported by a message type for a specific message version. These are built once per
+//parser and shared by every message parsed afterwards.
+struct MessageFields {
+    fields: FieldHashMap,
+    required_fields: FieldHashSet,
 }
 
 struct ParseRepeatingGroupState {
     number_of_tag: FieldTag,
     group_count: usize,
     group_builder: Box<BuildMessage>,
+    group_fields: FieldHashMap, //Fields of group_builder so they aren't rebuilt for every group.
+    group_required_fields: FieldHashSet,
+    found_fields: FieldHashSet, //Fields found in the last group so far.
     first_tag: FieldTag,
-    groups: Vec<ParseGroupState>,
+    groups: Vec<Box<Message>>,
 }
 
 impl ParseRepeatingGroupState {
@@ -142,15 +317,15 @@ impl ParseRepeatingGroupState {
 
         //Check if the last group has had all of its required fields specified.
         if let Some(last_group) = self.groups.last() {
-            if let Some(tag) = last_group.remaining_required_fields.iter().next() {
+            if let Some(tag) = self.group_required_fields.iter().find(|tag| !self.found_fields.contains(tag)) {
                 *missing_tag = *tag;
                 return;
             }
 
             //TODO: Add test to confirm conditional require works for this and outer message
             //fields.
-            for tag in last_group.message.conditional_required_fields(message_version) {
-                if last_group.remaining_fields.contains_key(&tag) {
+            for tag in last_group.conditional_required_fields(message_version) {
+                if self.group_fields.contains_key(&tag) && !self.found_fields.contains(&tag) {
                     *missing_conditional_tag = tag;
                     return;
                 }
@@ -185,7 +360,7 @@ fn ascii_to_integer<T: FromStr>(ascii_bytes: &Vec<u8>) -> Result<T,<T as FromStr
     T::from_str(String::from_utf8_lossy(ascii_bytes.as_slice()).borrow())
 }
 
-fn set_message_value<T: Message + ?Sized>(message: &mut T,tag: FieldTag,bytes: &[u8]) -> Result<(),ParseError> {
+fn set_message_value<T: Message + ?Sized>(message: &mut T,tag: FieldTag,bytes: &[u8],reject_out_of_range_values: bool) -> Result<(),ParseError> {
     if let Err(e) = message.set_value(tag,bytes) {
         match e {
             //This means either the key could not be found in the message (an
@@ -195,19 +370,49 @@ fn set_message_value<T: Message + ?Sized>(message: &mut T,tag: FieldTag,bytes: &
             SetValueError::WrongFormat => return Err(ParseError::WrongFormatTag(tag)),
             //Value was formatted correctly but outside of the defined range or not
             //part of the list of allowed choices.
-            SetValueError::OutOfRange => return Err(ParseError::OutOfRangeTag(tag)),
+            SetValueError::OutOfRange => if reject_out_of_range_values {
+                return Err(ParseError::OutOfRangeTag(tag));
+            },
         };
     }
 
     Ok(())
 }
 
+fn cached_fields<'a>(fields_cache: &'a mut HashMap<&'static [u8],HashMap<MessageVersion,Arc<MessageFields>>>,
+                     message_dictionary: &HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
+                     msg_type: &[u8],
+                     message_version: MessageVersion) -> Option<&'a Arc<MessageFields>> {
+    //Builders can be shared between parsers so they don't cache anything themselves. Instead, each
+    //parser keeps the fields it has needed so far.
+    if !fields_cache.contains_key(msg_type) {
+        match message_dictionary.keys().find(|key| ***key == *msg_type) {
+            Some(key) => { fields_cache.insert(*key,HashMap::new()); },
+            None => return None,
+        }
+    }
+
+    let builder = &message_dictionary[msg_type];
+    Some(fields_cache.get_mut(msg_type).unwrap().entry(message_version).or_insert_with(|| {
+        Arc::new(MessageFields {
+            fields: builder.fields(message_version),
+            required_fields: builder.required_fields(message_version),
+        })
+    }))
+}
+
+//Most recycled messages kept per message type. See Parser::recycle_message().
+const MESSAGE_POOL_LEN_MAX: usize = 16;
+
 pub struct Parser {
-    message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
+    message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,
+    fields_cache: HashMap<&'static [u8],HashMap<MessageVersion,Arc<MessageFields>>>,
+    message_pool: HashMap<&'static [u8],Vec<Box<FIXTMessage + Send>>>,
     max_message_length: u64,
     default_message_version: MessageVersion,
     default_message_type_version: HashMap<&'static [u8],MessageVersion>,
     value_to_length_tags: HashMap<FieldTag,FieldTag>,
+    length_tags: FieldHashSet, //Every tag in value_to_length_tags' values. Used by prescan().
     found_message: FoundMessage,
     current_tag: FieldTag, //Tag if completely parsed, otherwise empty.
     current_bytes: Vec<u8>, //Bytes being parsed for current tag or value.
@@ -218,22 +423,38 @@ pub struct Parser {
     checksum: u8,
     sender_comp_id: Vec<u8>,
     target_comp_id: Vec<u8>,
+    msg_seq_num: Option<u64>, //Only used to describe parse errors.
     body_remaining_length: u64, //TODO: Do we really need this to be this long?
     previous_tag: FieldTag,
     next_tag_checksum: bool,
     tag_rule_mode_stack: Vec<Box<TagRuleMode>>,
     fast_track_bytes_remaining: usize,
     found_tag_count: usize,
-    remaining_fields: FieldHashMap,
-    remaining_required_fields: FieldHashSet,
+    current_fields: Option<Arc<MessageFields>>, //Fields supported by current_message.
+    found_fields: FieldHashSet, //Fields of current_message found so far.
     missing_tag: FieldTag,
     missing_conditional_tag: FieldTag,
     current_message: Box<FIXTMessage + Send>,
+    keep_discarded_bytes: bool,
+    filler_bytes: Vec<u8>, //Bytes skipped between messages without being treated as garbage.
+    filler_byte_count: u64,
+    message_start: usize, //Index where the current message starts in the slice passed to parse().
+    partial_message_bytes: Vec<u8>, //Raw bytes of the current message from previous calls to parse().
+    validation_settings: ValidationSettings,
+    last_error_context: Option<ParseErrorContext>,
     pub messages: Vec<Box<FIXTMessage + Send>>,
+    pub discarded: Vec<DiscardedBytes>,
 }
 
 impl Parser {
     pub fn new(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,max_message_length: u64) -> Parser {
+        Parser::with_shared_dictionary(Arc::new(message_dictionary),max_message_length)
+    }
+
+    pub fn with_shared_dictionary(message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,max_message_length: u64) -> Parser {
+        //Builders are never modified so any number of parsers, even on different threads, can use
+        //the same dictionary instead of each needing their own copy.
+
         //Perform a sanity check to make sure message dictionary was defined correctly. For now,
         //validate_message_dictionary() panics on failure because dictionaries should be composed
         //using a compile time macro. Thus, there's no practical reason to try and recover.
@@ -245,7 +466,7 @@ impl Parser {
         //provided for better error messages but probably isn't needed in practice.
         let mut value_to_length_tags = HashMap::new();
         let mut builder_stack = Vec::from_iter(message_dictionary.iter().map(|(_,builder)| { BuildMessage::new_into_box(&**builder) }));
-        while let Some(mut builder) = builder_stack.pop() {
+        while let Some(builder) = builder_stack.pop() {
             for message_version in MessageVersion::all() {
                 for (tag,rule) in builder.fields(message_version) {
                     match rule {
@@ -261,12 +482,17 @@ impl Parser {
             }
         }
 
+        let length_tags = value_to_length_tags.values().cloned().collect();
+
         Parser {
             message_dictionary: message_dictionary,
+            fields_cache: HashMap::new(),
+            message_pool: HashMap::new(),
             max_message_length: max_message_length,
             default_message_version: DefaultApplVerIDFieldType::default_value(),
             default_message_type_version: HashMap::new(),
             value_to_length_tags: value_to_length_tags,
+            length_tags: length_tags,
             found_message: FoundMessage::NotFound,
             current_tag: FieldTag::empty(),
             current_bytes: Vec::with_capacity(64),
@@ -277,18 +503,27 @@ impl Parser {
             checksum: 0,
             sender_comp_id: Vec::new(),
             target_comp_id: Vec::new(),
+            msg_seq_num: None,
             body_remaining_length: 0,
             previous_tag: FieldTag::empty(),
             next_tag_checksum: false,
             tag_rule_mode_stack: Vec::new(),
             fast_track_bytes_remaining: 0,
             found_tag_count: 0,
-            remaining_fields: HashMap::with_hasher(BuildFieldHasher),
-            remaining_required_fields: HashSet::with_hasher(BuildFieldHasher),
+            current_fields: None,
+            found_fields: HashSet::with_hasher(BuildFieldHasher),
             missing_tag: FieldTag::empty(),
             missing_conditional_tag: FieldTag::empty(),
             current_message: Box::new(NullMessage {}),
+            keep_discarded_bytes: false,
+            filler_bytes: Vec::new(),
+            filler_byte_count: 0,
+            message_start: 0,
+            partial_message_bytes: Vec::new(),
+            validation_settings: ValidationSettings::default(),
+            last_error_context: None,
             messages: Vec::new(),
+            discarded: Vec::new(),
         }
     }
 
@@ -301,17 +536,59 @@ impl Parser {
         self.checksum = 0;
         self.sender_comp_id.clear();
         self.target_comp_id.clear();
+        self.msg_seq_num = None;
         self.body_remaining_length = 0;
         self.previous_tag = FieldTag::empty();
         self.next_tag_checksum = false;
         self.tag_rule_mode_stack.clear();
         self.fast_track_bytes_remaining = 0;
         self.found_tag_count = 0;
-        self.remaining_fields.clear();
-        self.remaining_required_fields.clear();
+        self.current_fields = None;
+        self.found_fields.clear();
         self.missing_tag = FieldTag::empty();
         self.missing_conditional_tag = FieldTag::empty();
         self.current_message = Box::new(NullMessage {});
+        self.partial_message_bytes.clear();
+    }
+
+    pub fn reset(&mut self) {
+        //Return the parser to how it was when constructed so it can be reused for an unrelated
+        //connection, like when keeping a pool of parsers for a busy listener. Any partially parsed
+        //message and any messages or discarded bytes that weren't taken are thrown away. Buffers
+        //and cached message fields are kept so nothing needs to be reallocated. Settings are kept
+        //too, except for the default message versions because those are decided per session.
+        self.reset_parser();
+        self.fix_version = FIXVersion::FIX_4_0;
+        self.message_version = MessageVersion::FIX40;
+        self.default_message_version = DefaultApplVerIDFieldType::default_value();
+        self.default_message_type_version.clear();
+        self.message_start = 0;
+        self.filler_byte_count = 0;
+        self.last_error_context = None;
+        self.messages.clear();
+        self.discarded.clear();
+    }
+
+    pub fn shared_dictionary(&self) -> Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>> {
+        //Dictionary used by this parser. Pass it to with_shared_dictionary() to construct more
+        //parsers without copying it.
+        self.message_dictionary.clone()
+    }
+
+    pub fn recycle_message(&mut self,mut message: Box<FIXTMessage + Send>) {
+        //Hand back a message taken from messages once it's no longer needed. It's cleared and
+        //reused the next time a message of the same type is parsed, so its fields don't have to be
+        //allocated again. Repeating groups are not reused. Messages that can't be reset, or
+        //arrive after the pool for their type is full, are dropped.
+        let msg_type = message.msg_type();
+        if !self.message_dictionary.contains_key(msg_type) {
+            return;
+        }
+
+        let pool = self.message_pool.entry(msg_type).or_insert_with(Vec::new);
+        if pool.len() < MESSAGE_POOL_LEN_MAX && message.reset() {
+            pool.push(message);
+        }
     }
 
     pub fn set_default_message_version(&mut self,message_version: MessageVersion) {
@@ -342,6 +619,151 @@ impl Parser {
         self.max_message_length
     }
 
+    pub fn keep_discarded_bytes(&self) -> bool {
+        self.keep_discarded_bytes
+    }
+
+    pub fn set_keep_discarded_bytes(&mut self,keep_discarded_bytes: bool) {
+        //When enabled, garbage between messages and the raw bytes of every message that fails to
+        //parse are stored in discarded. Disabled by default because it copies every message.
+        self.keep_discarded_bytes = keep_discarded_bytes;
+        self.partial_message_bytes.clear();
+    }
+
+    pub fn filler_bytes(&self) -> &[u8] {
+        &self.filler_bytes[..]
+    }
+
+    pub fn set_filler_bytes(&mut self,filler_bytes: &[u8]) {
+        //Bytes that some middleware inserts between messages, like newlines or padding. Runs of
+        //them directly after a message, or at the start of the stream, are skipped and counted in
+        //filler_byte_count() instead of being treated as garbage. They are never stored in
+        //discarded. Filler that follows other garbage is still garbage. Empty by default.
+        assert!(!filler_bytes.contains(&BEGINSTR_TAG_BYTES[0]));
+
+        self.filler_bytes = filler_bytes.to_vec();
+    }
+
+    pub fn filler_byte_count(&self) -> u64 {
+        //Number of filler bytes skipped since the parser was constructed or reset.
+        self.filler_byte_count
+    }
+
+    pub fn last_error_context(&self) -> Option<&ParseErrorContext> {
+        //Context for the error returned by the most recent call to parse(). None if it succeeded.
+        self.last_error_context.as_ref()
+    }
+
+    pub fn validation_settings(&self) -> ValidationSettings {
+        self.validation_settings
+    }
+
+    pub fn set_validation_settings(&mut self,validation_settings: ValidationSettings) {
+        self.validation_settings = validation_settings;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        //Parser is idle when it is not in the middle of parsing a message.
+        self.found_message == FoundMessage::NotFound
+    }
+
+    pub fn prescan<'a>(&self,message_bytes: &'a [u8]) -> Option<PrescannedMessage<'a>> {
+        //Cheaply find MsgType, MsgSeqNum, PossDupFlag, SenderCompID, and TargetCompID of the
+        //complete message at the very start of message_bytes without building it or allocating.
+        //Lets the caller decide whether a message is worth a full parse(). Only the framing, the
+        //CheckSum, and length tags are checked so anything else wrong with the message is left for
+        //parse() to find. None when the message is incomplete or malformed, in which case parse()
+        //should be used as normal. The parser's own state is never touched.
+        let mut index = 0;
+
+        //BeginStr and BodyLength come first so where the message ends is known up front.
+        match prescan_field(message_bytes,&mut index) {
+            Some((tag,_)) if tag == BEGINSTR_TAG => {},
+            _ => return None,
+        }
+        let body_length = match prescan_field(message_bytes,&mut index) {
+            Some((tag,value)) if tag == BODYLENGTH_TAG => {
+                match prescan_number(value) {
+                    Some(body_length) if body_length <= self.max_message_length => body_length as usize,
+                    _ => return None,
+                }
+            },
+            _ => return None,
+        };
+
+        //CheckSum is always "10=" followed by three digits and SOH.
+        let body_end = index + body_length;
+        let message_end = body_end + 7;
+        if message_bytes.len() < message_end || &message_bytes[body_end..body_end + 3] != &b"10="[..] || message_bytes[message_end - 1] != VALUE_END {
+            return None;
+        }
+        let stated_checksum = match prescan_number(&message_bytes[body_end + 3..message_end - 1]) {
+            Some(stated_checksum) => stated_checksum,
+            None => return None,
+        };
+        if self.validation_settings.validate_checksum {
+            let checksum = message_bytes[..body_end].iter().fold(0u8,|checksum,byte| checksum.wrapping_add(*byte));
+            if checksum as u64 != stated_checksum {
+                return None;
+            }
+        }
+
+        let mut prescanned_message = PrescannedMessage {
+            length: message_end,
+            msg_type: b"",
+            msg_seq_num: None,
+            poss_dup: false,
+            sender_comp_id: b"",
+            target_comp_id: b"",
+        };
+        let body_bytes = &message_bytes[..body_end];
+        while index < body_end {
+            let (tag,value) = match prescan_field(body_bytes,&mut index) {
+                Some(field) => field,
+                None => return None,
+            };
+
+            if tag == MSGTYPE_TAG {
+                prescanned_message.msg_type = value;
+            }
+            else if tag == MsgSeqNum::tag() {
+                prescanned_message.msg_seq_num = prescan_number(value);
+            }
+            else if tag == PossDupFlag::tag() {
+                prescanned_message.poss_dup = value == &b"Y"[..];
+            }
+            else if tag == SenderCompID::tag() {
+                prescanned_message.sender_comp_id = value;
+            }
+            else if tag == TargetCompID::tag() {
+                prescanned_message.target_comp_id = value;
+            }
+            else if self.length_tags.contains(&tag) {
+                //The following value can contain SOH so it has to be skipped over using the
+                //length instead of being searched.
+                let value_length = match prescan_number(value) {
+                    Some(value_length) => value_length as usize,
+                    None => return None,
+                };
+                let tag_end = match body_bytes[index..].iter().position(|c| *c == TAG_END) {
+                    Some(tag_length) => index + tag_length,
+                    None => return None,
+                };
+                index = tag_end + 1 + value_length;
+                if index >= body_end || body_bytes[index] != VALUE_END {
+                    return None;
+                }
+                index += 1;
+            }
+        }
+
+        if prescanned_message.msg_type.is_empty() {
+            return None;
+        }
+
+        Some(prescanned_message)
+    }
+
     pub fn validate_message_dictionary(message_dictionary: &HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) {
         enum MessageType {
             Standard,
@@ -352,7 +774,7 @@ impl Parser {
         //including repeating and nested repeating groups.
         let mut all_messages = Vec::new();
         let mut builder_stack = Vec::from_iter(message_dictionary.iter().map(|(_,builder)| { (MessageType::Standard,BuildMessage::new_into_box(&**builder)) }));
-        while let Some((message_type,mut builder)) = builder_stack.pop() {
+        while let Some((message_type,builder)) = builder_stack.pop() {
             //Prevent lots of duplicates from different message versions.
             let mut found_repeating_groups = HashSet::new();
 
@@ -374,7 +796,7 @@ impl Parser {
 
         //All messages must have at least one field. All repeating group messages must make the
         //first field required. This must all be true for at least one message version.
-        for &mut (ref message_type,ref mut builder) in &mut all_messages {
+        for &(ref message_type,ref builder) in &all_messages {
             let mut no_fields = true;
             let mut first_field_not_in_fields = true;
             let mut repeating_group_first_field_not_in_required_fields = true;
@@ -416,7 +838,7 @@ impl Parser {
         //Run remaining validation against every supported message version.
         for message_version in MessageVersion::all() {
             //The required fields specified in a message must be a subset of the fields.
-            for &mut (_,ref mut builder) in &mut all_messages {
+            for &(_,ref builder) in &all_messages {
                 let fields = builder.fields(message_version);
                 let required_fields = builder.required_fields(message_version);
 
@@ -429,7 +851,7 @@ impl Parser {
 
             //Fields that specify Rule::PrepareForBytes have exactly one matching field that
             //specifies Rule::ConfirmPreviousTag within the same message.
-            for &mut (_,ref mut builder) in &mut all_messages {
+            for &(_,ref builder) in &all_messages {
                 let fields = builder.fields(message_version);
 
                 for (tag,rule) in &fields {
@@ -489,15 +911,27 @@ impl Parser {
     }
 
     fn prepare_for_message(&mut self) -> Result<(),ParseError> {
-        if let Some(builder) = self.message_dictionary.get_mut(&self.message_type[..]) {
-            self.current_message = BuildFIXTMessage::build(&**builder);
-            self.remaining_fields = builder.fields(self.message_version);
-            self.remaining_required_fields = builder.required_fields(self.message_version);
+        //Only a reference to the cached fields is kept. Which fields have been found is tracked
+        //separately so the cache never needs to be copied.
+        let current_fields = match cached_fields(&mut self.fields_cache,&self.message_dictionary,&self.message_type[..],self.message_version) {
+            Some(fields) => fields.clone(),
+            None => return Err(ParseError::MsgTypeUnknown(self.message_type.clone())),
+        };
+        self.current_fields = Some(current_fields);
+        self.found_fields.clear();
 
-            return Ok(());
-        }
+        //Prefer reusing a recycled message over building a new one.
+        self.current_message = match self.message_pool.get_mut(&self.message_type[..]).and_then(|pool| pool.pop()) {
+            Some(message) => message,
+            None => BuildFIXTMessage::build(&*self.message_dictionary[&self.message_type[..]]),
+        };
+
+        Ok(())
+    }
 
-        Err(ParseError::MsgTypeUnknown(self.message_type.clone()))
+    fn is_field_remaining(&self,tag: FieldTag) -> bool {
+        //Field is supported by the current message but hasn't been found yet.
+        self.current_fields.as_ref().map_or(false,|current_fields| current_fields.fields.contains_key(&tag)) && !self.found_fields.contains(&tag)
     }
 
     fn if_checksum_then_is_last_tag(&self) -> Result<(),ParseError> {
@@ -522,7 +956,7 @@ impl Parser {
         }
 
         match ascii_to_integer::<u8>(checksum_bytes) {
-            Ok(stated_checksum) => if checksum != stated_checksum {
+            Ok(stated_checksum) => if checksum != stated_checksum && self.validation_settings.validate_checksum {
                 return Err(ParseError::ChecksumDoesNotMatch(checksum,stated_checksum));
             },
             Err(_) => return Err(ParseError::ChecksumWrongFormat),
@@ -544,8 +978,18 @@ impl Parser {
             return;
         }
 
+        //Skip over filler before looking for garbage so it never ends up in discarded.
+        if self.found_message == FoundMessage::NotFound && !self.filler_bytes.is_empty() {
+            while *index < message_bytes.len() && self.filler_bytes.contains(&message_bytes[*index]) {
+                self.filler_byte_count += 1;
+                *index += 1;
+            }
+        }
+
         //If the scan previously found the BeginStr tag but ran out of bytes, resume from the same
         //state.
+        let resumed_first_byte = self.found_message == FoundMessage::FirstByte;
+        let scan_start = *index;
         let mut previous_byte = match self.found_message {
             FoundMessage::NotFound => 0,
             FoundMessage::FirstByte => BEGINSTR_TAG_BYTES[0],
@@ -564,6 +1008,10 @@ impl Parser {
             *index += 1;
         }
 
+        if self.keep_discarded_bytes {
+            self.keep_scanned_bytes(resumed_first_byte,scan_start,*index,message_bytes);
+        }
+
         if self.found_message == FoundMessage::SecondByte {
             //Act like the BeginStr tag was parsed so we don't duplicate work.
             self.current_tag = BEGINSTR_TAG;
@@ -577,6 +1025,39 @@ impl Parser {
         }
     }
 
+    fn keep_scanned_bytes(&mut self,resumed_first_byte: bool,scan_start: usize,scan_end: usize,message_bytes: &[u8]) {
+        //Everything scanned is garbage except for a '8' that starts a message or that might start
+        //one once more bytes arrive. A '8' left over from the previous call is garbage unless the
+        //very next byte finished the header.
+        let found_message = self.found_message == FoundMessage::SecondByte;
+        let ends_with_first_byte = scan_end > scan_start && message_bytes[scan_end - 1] == BEGINSTR_TAG_BYTES[0];
+        let garbage_end = if ends_with_first_byte { scan_end - 1 } else { scan_end };
+
+        let mut garbage = Vec::new();
+        if resumed_first_byte && scan_end > scan_start {
+            garbage.extend_from_slice(BEGINSTR_TAG_BYTES);
+        }
+        garbage.extend_from_slice(&message_bytes[scan_start..garbage_end]);
+        if !garbage.is_empty() {
+            self.discarded.push(DiscardedBytes {
+                bytes: garbage,
+                end: garbage_end,
+                reason: String::from("Garbage outside of a message"),
+            });
+        }
+
+        if found_message {
+            if scan_end == scan_start {
+                //Message started with the '8' from the previous call.
+                self.partial_message_bytes.extend_from_slice(BEGINSTR_TAG_BYTES);
+                self.message_start = scan_start;
+            }
+            else {
+                self.message_start = scan_end - 1;
+            }
+        }
+    }
+
     fn fast_track_read_bytes(&mut self,index: &mut usize,message_bytes: &[u8]) -> Result<(),ParseError> {
         loop {
             if *index >= message_bytes.len() || self.fast_track_bytes_remaining == 0 {
@@ -611,6 +1092,9 @@ impl Parser {
                             group_count: group_count,
                             first_tag: repeating_group_builder.first_field(self.message_version),
                             groups: Vec::new(),
+                            group_fields: repeating_group_builder.fields(self.message_version),
+                            group_required_fields: repeating_group_builder.required_fields(self.message_version),
+                            found_fields: HashSet::with_hasher(BuildFieldHasher),
                             group_builder: repeating_group_builder,
                         }))));
                         self.tag_rule_mode_stack.push(Box::new(TagRuleMode::RepeatingGroupStart(first_field)));
@@ -644,21 +1128,15 @@ impl Parser {
                 if let TagRuleMode::RepeatingGroups(ref mut prgs) = **first_tag_rule_mode {
                     for tag_rule_mode in tag_rule_mode_stack_iter {
                         if let TagRuleMode::RepeatingGroups(ref mut parent_prgs) = **tag_rule_mode {
-                            let mut groups = mem::replace(&mut prgs.groups,Vec::new());
-                            parent_prgs.groups.last_mut().unwrap().message.set_groups(
-                                prgs.number_of_tag,
-                                Vec::from_iter(groups.drain(0..).map(|group| { group.message }))
-                            );
+                            let groups = mem::replace(&mut prgs.groups,Vec::new());
+                            parent_prgs.groups.last_mut().unwrap().set_groups(prgs.number_of_tag,groups);
                             folded_down = true;
                         }
                     }
 
                     if !folded_down {
-                        let mut groups = mem::replace(&mut prgs.groups,Vec::new());
-                        self.current_message.set_groups(
-                            prgs.number_of_tag,
-                            Vec::from_iter(groups.drain(0..).map(|group| { group.message }))
-                        );
+                        let groups = mem::replace(&mut prgs.groups,Vec::new());
+                        self.current_message.set_groups(prgs.number_of_tag,groups);
                         folded_down = true;
                     }
                 }
@@ -718,6 +1196,9 @@ impl Parser {
     }
 
     fn match_value_end(&mut self,index: &mut usize,message_bytes: &[u8]) -> Result<MessageEnd,ParseError> {
+        //Copied so it can be read while parts of self are borrowed below.
+        let validation_settings = self.validation_settings;
+
         //Validate that the first three tags of a message are, in order: BeginStr,
         //BodyLength, and MsgType.
         if self.found_tag_count == 0 {
@@ -805,6 +1286,11 @@ impl Parser {
             return Err(ParseError::NoValueAfterTag(self.current_tag.clone()));
         }
         else {
+            //Remember MsgSeqNum so it can be reported if the rest of the message fails to parse.
+            if self.current_tag == MsgSeqNum::tag() {
+                self.msg_seq_num = prescan_number(&self.current_bytes[..]);
+            }
+
             //FIXT.1.1 requires that if the ApplVerID tag is specified, it must be the sixth field.
             let mut skip_set_value = false;
             if self.found_tag_count == 5 && self.fix_version == FIXVersion::FIXT_1_1 {
@@ -831,19 +1317,17 @@ impl Parser {
 
                 //Start the message by filling out the SenderCompID and TargetCompID portions of
                 //message. These fields are always required for FIXT.1.1 messages.
-                try!(set_message_value(&mut *self.current_message,SenderCompID::tag(),&self.sender_comp_id[..]));
-                self.remaining_fields.remove(&SenderCompID::tag());
-                self.remaining_required_fields.remove(&SenderCompID::tag());
-                try!(set_message_value(&mut *self.current_message,TargetCompID::tag(),&self.target_comp_id[..]));
-                self.remaining_fields.remove(&TargetCompID::tag());
-                self.remaining_required_fields.remove(&TargetCompID::tag());
+                try!(set_message_value(&mut *self.current_message,SenderCompID::tag(),&self.sender_comp_id[..],true));
+                self.found_fields.insert(SenderCompID::tag());
+                try!(set_message_value(&mut *self.current_message,TargetCompID::tag(),&self.target_comp_id[..],true));
+                self.found_fields.insert(TargetCompID::tag());
 
                 //Mark ApplVerID as found so we produce an error if it's encountered anywhere else
                 //in the message.
                 if self.current_tag == ApplVerID::tag() {
-                    try!(set_message_value(&mut *self.current_message,ApplVerID::tag(),&self.current_bytes[..]));
+                    try!(set_message_value(&mut *self.current_message,ApplVerID::tag(),&self.current_bytes[..],true));
                 }
-                self.remaining_fields.remove(&ApplVerID::tag());
+                self.found_fields.insert(ApplVerID::tag());
             }
 
             //Make sure checksum checks out when done reading a message.
@@ -869,13 +1353,8 @@ impl Parser {
 
                             //Begin a new group.
                             let group = prgs.group_builder.build();
-                            let remaining_fields = prgs.group_builder.fields(self.message_version);
-                            let remaining_required_fields = prgs.group_builder.required_fields(self.message_version);
-                            prgs.groups.push(ParseGroupState {
-                                message: group,
-                                remaining_fields: remaining_fields,
-                                remaining_required_fields: remaining_required_fields,
-                            });
+                            prgs.groups.push(group);
+                            prgs.found_fields.clear();
 
                             //Make sure we haven't exceeded the number of repeating
                             //groups originally stated.
@@ -885,14 +1364,17 @@ impl Parser {
                         }
 
                         if let Some(group) = prgs.groups.last_mut() {
-                            if let Some(rule) = group.remaining_fields.remove(&self.current_tag) {
-                                //Try to mark the field as found in case it's required.
-                                group.remaining_required_fields.remove(&self.current_tag);
-
+                            let rule = if prgs.group_fields.contains_key(&self.current_tag) && prgs.found_fields.insert(self.current_tag) {
+                                prgs.group_fields.get(&self.current_tag).cloned()
+                            }
+                            else {
+                                None
+                            };
+                            if let Some(rule) = rule {
                                 //Apply parsed value to group.
                                 if let Rule::BeginGroup{ .. } = rule {} //Ignore begin group tags, they will be handled below.
                                 else {
-                                    try!(set_message_value(&mut *group.message,self.current_tag,&self.current_bytes[..]));
+                                    try!(set_message_value(&mut **group,self.current_tag,&self.current_bytes[..],validation_settings.reject_out_of_range_values));
                                 }
 
                                 //Save rule to handle later.
@@ -904,8 +1386,14 @@ impl Parser {
 
                         if !tag_in_group {
                             //Figure out if this is an error or the end of the group.
-                            if prgs.group_builder.fields(self.message_version).contains_key(&self.current_tag) {
-                                return Err(ParseError::DuplicateTag(self.current_tag.clone()));
+                            if prgs.group_fields.contains_key(&self.current_tag) {
+                                if validation_settings.reject_duplicate_tags {
+                                    return Err(ParseError::DuplicateTag(self.current_tag.clone()));
+                                }
+
+                                //Keep the first value by treating the duplicate as already handled.
+                                tag_in_group = true;
+                                break;
                             }
                             else if prgs.groups.len() < prgs.group_count {
                                 return Err(ParseError::NonRepeatingGroupTagInRepeatingGroup(self.current_tag));
@@ -937,28 +1425,38 @@ impl Parser {
             }
 
             if !skip_set_value && !is_message_end && !tag_in_group {
-                //Mark field as found if required so we can quickly check if all required
-                //fields were found once we are done parsing the message.
-                self.remaining_required_fields.remove(&self.current_tag);
-
                 //Mark field as found so we can quickly check if a duplicate tag was
-                //encountered. As a side effect, we also handle any tag specific
-                //rules in consequence of being encountered.
-                if let Some(rule) = self.remaining_fields.remove(&self.current_tag) {
+                //encountered and if all required fields were found once we are done parsing the
+                //message. As a side effect, we also handle any tag specific rules in consequence
+                //of being encountered.
+                let rule = if self.is_field_remaining(self.current_tag) {
+                    self.found_fields.insert(self.current_tag);
+                    self.current_fields.as_ref().and_then(|current_fields| current_fields.fields.get(&self.current_tag).cloned())
+                }
+                else {
+                    None
+                };
+                if let Some(rule) = rule {
                     skip_set_value = try!(self.handle_rule_after_value(&rule));
                 }
                 else {
                     if self.is_current_tag_known() {
-                        let current_message_builder = self.message_dictionary.get_mut(&self.message_type[..]).unwrap();
-                        if current_message_builder.fields(self.message_version).contains_key(&self.current_tag) {
+                        let current_tag = self.current_tag;
+                        if cached_fields(&mut self.fields_cache,&self.message_dictionary,&self.message_type[..],self.message_version).map_or(false,|fields| fields.fields.contains_key(&current_tag)) {
                             //Special case where if ApplVerID tag is encountered after the sixth
                             //tag. This needs its own error so the correct SessionRejectReason can
                             //be specified in a Reject message.
                             if self.current_tag == ApplVerID::tag() {
-                                return Err(ParseError::ApplVerIDNotSixthTag);
+                                if validation_settings.reject_misplaced_appl_ver_id {
+                                    return Err(ParseError::ApplVerIDNotSixthTag);
+                                }
+                            }
+                            else if validation_settings.reject_duplicate_tags {
+                                return Err(ParseError::DuplicateTag(self.current_tag.clone()));
                             }
 
-                            return Err(ParseError::DuplicateTag(self.current_tag.clone()));
+                            //Keep the first value.
+                            skip_set_value = true;
                         }
                         else {
                             return Err(ParseError::UnexpectedTag(self.current_tag.clone()));
@@ -971,7 +1469,7 @@ impl Parser {
             }
 
             if !is_message_end && !tag_in_group && !skip_set_value {
-                try!(set_message_value(&mut *self.current_message,self.current_tag,&self.current_bytes[..]));
+                try!(set_message_value(&mut *self.current_message,self.current_tag,&self.current_bytes[..],validation_settings.reject_out_of_range_values));
             }
 
             if is_message_end {
@@ -989,15 +1487,18 @@ impl Parser {
                             mem::replace(&mut self.current_message,Box::new(NullMessage {}))));
                 }
 
-                if let Some(tag) = self.remaining_required_fields.iter().next() {
+                let missing_required_tag = self.current_fields.as_ref().and_then(|current_fields| {
+                    current_fields.required_fields.iter().find(|tag| !self.found_fields.contains(tag)).cloned()
+                });
+                if let Some(tag) = missing_required_tag {
                     return Err(
                         ParseError::MissingRequiredTag(
-                            *tag,
+                            tag,
                             mem::replace(&mut self.current_message,Box::new(NullMessage {}))));
                 }
 
                 for tag in self.current_message.conditional_required_fields(self.message_version) {
-                    if self.remaining_fields.contains_key(&tag) {
+                    if self.is_field_remaining(tag) {
                         return Err(
                             ParseError::MissingConditionallyRequiredTag(
                                 tag,
@@ -1045,8 +1546,9 @@ impl Parser {
     }
 
     fn is_current_tag_known(&mut self) -> bool {
-        for message in self.message_dictionary.values_mut() {
-            if message.fields(self.message_version).contains_key(&self.current_tag) {
+        let current_tag = self.current_tag;
+        for msg_type in self.message_dictionary.keys() {
+            if cached_fields(&mut self.fields_cache,&self.message_dictionary,msg_type,self.message_version).map_or(false,|fields| fields.fields.contains_key(&current_tag)) {
                 return true;
             }
         }
@@ -1058,9 +1560,30 @@ impl Parser {
         //Parse and bytes as possible. Either all bytes will be consumed or all bytes up until a
         //parse error is triggered -- whatever happens first.
         let mut index = 0;
+        self.message_start = 0;
+        self.last_error_context = None;
         match self.parse_private(&mut index,message_bytes) {
-            Ok(_) => (index,Ok(())),
+            Ok(_) => {
+                //Hold onto the start of an unfinished message in case it turns out to be bad.
+                if self.keep_discarded_bytes && self.found_message == FoundMessage::SecondByte {
+                    self.partial_message_bytes.extend_from_slice(&message_bytes[self.message_start..index]);
+                }
+
+                (index,Ok(()))
+            },
             Err(err) => {
+                let context = self.error_context(index,message_bytes);
+                if self.keep_discarded_bytes {
+                    let mut bytes = mem::replace(&mut self.partial_message_bytes,Vec::new());
+                    bytes.extend_from_slice(&message_bytes[self.message_start..index]);
+                    self.discarded.push(DiscardedBytes {
+                        bytes: bytes,
+                        end: index,
+                        reason: format!("{} {}",err,context),
+                    });
+                }
+                self.last_error_context = Some(context);
+
                 //Reset automatically so the next parse won't fail immediatelly.
                 self.reset_parser();
 
@@ -1069,6 +1592,22 @@ impl Parser {
         }
     }
 
+    fn error_context(&self,index: usize,message_bytes: &[u8]) -> ParseErrorContext {
+        //Must be called before reset_parser() so the partially parsed header is still available.
+        let offset = cmp::min(index,message_bytes.len());
+        let excerpt_start = offset.saturating_sub(ERROR_EXCERPT_BYTES_BEFORE);
+        let excerpt_end = cmp::min(offset.saturating_add(ERROR_EXCERPT_BYTES_AFTER),message_bytes.len());
+
+        ParseErrorContext {
+            offset: offset,
+            tag: if self.current_tag.is_empty() { None } else { Some(self.current_tag) },
+            msg_type: if self.message_type.is_empty() { None } else { Some(self.message_type.clone()) },
+            msg_seq_num: self.msg_seq_num,
+            excerpt_start: excerpt_start,
+            excerpt: message_bytes[excerpt_start..excerpt_end].to_vec(),
+        }
+    }
+
     fn parse_private(&mut self,index: &mut usize,message_bytes: &[u8]) -> Result<(),ParseError> {
         //Start by searching for the start of a message unless resuming.
         self.scan_for_message(index,message_bytes);
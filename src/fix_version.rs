@@ -12,7 +12,7 @@
 use constant::{FIX_4_0_BEGIN_STRING,FIX_4_1_BEGIN_STRING,FIX_4_2_BEGIN_STRING,FIX_4_3_BEGIN_STRING,FIX_4_4_BEGIN_STRING,FIXT_1_1_BEGIN_STRING};
 use message_version::MessageVersion;
 
-#[derive(Clone,Copy,Debug,PartialEq)]
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum FIXVersion {
     FIXT_1_1,
@@ -0,0 +1,245 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use dictionary::field_types::other::{AffirmStatus,AllocRejCode,AllocStatus,ConfirmRejReason,ConfirmStatus,ConfirmTransType};
+use dictionary::messages::{AllocationInstructionAck,Confirmation,ConfirmationAck};
+use fixt::message::FIXTMessage;
+
+//Account level result from the NoAllocs group of an AllocationInstructionAck.
+#[derive(Clone,Debug,PartialEq)]
+pub struct AllocAccountAck {
+    pub alloc_account: Vec<u8>,
+    pub individual_alloc_id: Vec<u8>,
+    pub individual_alloc_rej_code: Vec<u8>, //Empty when the account was not rejected.
+    pub alloc_text: Vec<u8>,
+}
+
+//A single Confirmation for an account in the allocation and its ConfirmationAck, if any.
+#[derive(Clone,Debug)]
+pub struct AllocConfirmation {
+    pub confirm_id: Vec<u8>,
+    pub alloc_account: Vec<u8>,
+    pub individual_alloc_id: Vec<u8>,
+    pub alloc_qty: Vec<u8>,
+    pub avg_px: Vec<u8>,
+    pub net_money: Vec<u8>,
+    pub confirm_status: ConfirmStatus,
+    pub affirm_status: Option<AffirmStatus>, //None until a ConfirmationAck is seen.
+    pub confirm_rej_reason: Option<ConfirmRejReason>,
+    pub canceled: bool,
+}
+
+impl AllocConfirmation {
+    pub fn is_affirmed(&self) -> bool {
+        self.affirm_status == Some(AffirmStatus::Affirmed)
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct Allocation {
+    pub alloc_id: Vec<u8>,
+    pub alloc_status: Option<AllocStatus>, //None until the first AllocationInstructionAck is seen.
+    pub alloc_rej_code: Option<AllocRejCode>,
+    pub text: Vec<u8>,
+    pub accounts: Vec<AllocAccountAck>, //From the most recent AllocationInstructionAck.
+    pub confirmations: Vec<AllocConfirmation>,
+}
+
+impl Allocation {
+    fn new(alloc_id: &[u8]) -> Allocation {
+        Allocation {
+            alloc_id: alloc_id.to_vec(),
+            alloc_status: None,
+            alloc_rej_code: None,
+            text: Vec::new(),
+            accounts: Vec::new(),
+            confirmations: Vec::new(),
+        }
+    }
+
+    pub fn get_confirmation(&self,confirm_id: &[u8]) -> Option<&AllocConfirmation> {
+        self.confirmations.iter().find(|confirmation| confirmation.confirm_id == confirm_id)
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.alloc_status == Some(AllocStatus::Accepted)
+    }
+
+    pub fn is_rejected(&self) -> bool {
+        match self.alloc_status {
+            Some(AllocStatus::BlockLevelReject) |
+            Some(AllocStatus::AccountLevelReject) |
+            Some(AllocStatus::RejectedByIntermediary) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        //The allocation was accepted and every outstanding confirmation was affirmed.
+        let mut confirmations = self.confirmations.iter().filter(|confirmation| !confirmation.canceled).peekable();
+        self.is_accepted() && confirmations.peek().is_some() && confirmations.all(|confirmation| confirmation.is_affirmed())
+    }
+
+    fn get_confirmation_mut(&mut self,confirm_id: &[u8]) -> Option<&mut AllocConfirmation> {
+        self.confirmations.iter_mut().find(|confirmation| confirmation.confirm_id == confirm_id)
+    }
+}
+
+//Tracks allocations through the post-trade AllocationInstructionAck, Confirmation, and
+//ConfirmationAck flow keyed by AllocID. A ConfirmationAck only carries the ConfirmID so it's
+//matched to the allocation through the Confirmation it acknowledges.
+//
+//Messages sent and received are both passed to on_message() because either side of the
+//connection may use the tracker: the buy side receives the AllocationInstructionAck and
+//Confirmation and sends the ConfirmationAck while the sell side does the opposite. An allocation
+//is tracked from the first message seen with its AllocID. Use one tracker per connection because
+//AllocIDs and ConfirmIDs are only unique per counterparty.
+pub struct AllocationTracker {
+    allocations: HashMap<Vec<u8>,Allocation>,
+    alloc_id_by_confirm_id: HashMap<Vec<u8>,Vec<u8>>,
+}
+
+impl AllocationTracker {
+    pub fn new() -> AllocationTracker {
+        AllocationTracker {
+            allocations: HashMap::new(),
+            alloc_id_by_confirm_id: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self,alloc_id: &[u8]) -> Option<&Allocation> {
+        self.allocations.get(alloc_id)
+    }
+
+    pub fn get_by_confirm_id(&self,confirm_id: &[u8]) -> Option<&Allocation> {
+        self.alloc_id_by_confirm_id.get(confirm_id).and_then(|alloc_id| self.allocations.get(alloc_id))
+    }
+
+    pub fn iter(&self) -> ::std::collections::hash_map::Values<Vec<u8>,Allocation> {
+        self.allocations.values()
+    }
+
+    pub fn remove(&mut self,alloc_id: &[u8]) -> Option<Allocation> {
+        let allocation = match self.allocations.remove(alloc_id) {
+            Some(allocation) => allocation,
+            None => return None,
+        };
+
+        for confirmation in &allocation.confirmations {
+            self.alloc_id_by_confirm_id.remove(&confirmation.confirm_id);
+        }
+
+        Some(allocation)
+    }
+
+    pub fn on_message(&mut self,message: &FIXTMessage) -> Option<&Allocation> {
+        //Returns the updated allocation or None if the message isn't allocation related or
+        //can't be matched to an allocation.
+        let message = message.as_any();
+        let alloc_id = if let Some(message) = message.downcast_ref::<AllocationInstructionAck>() {
+            self.on_allocation_instruction_ack(message)
+        }
+        else if let Some(message) = message.downcast_ref::<Confirmation>() {
+            self.on_confirmation(message)
+        }
+        else if let Some(message) = message.downcast_ref::<ConfirmationAck>() {
+            self.on_confirmation_ack(message)
+        }
+        else {
+            None
+        };
+
+        match alloc_id {
+            Some(alloc_id) => self.allocations.get(&alloc_id),
+            None => None,
+        }
+    }
+
+    fn on_allocation_instruction_ack(&mut self,message: &AllocationInstructionAck) -> Option<Vec<u8>> {
+        let allocation = self.allocations.entry(message.alloc_id.clone()).or_insert_with(|| Allocation::new(&message.alloc_id));
+
+        allocation.alloc_status = Some(message.alloc_status.clone());
+        allocation.alloc_rej_code = message.alloc_rej_code.clone();
+        allocation.text = message.text.clone();
+        if !message.no_allocs.is_empty() {
+            allocation.accounts = message.no_allocs.iter().map(|account| {
+                AllocAccountAck {
+                    alloc_account: account.alloc_account.clone(),
+                    individual_alloc_id: account.individual_alloc_id.clone(),
+                    individual_alloc_rej_code: account.individual_alloc_rej_code.clone(),
+                    alloc_text: account.alloc_text.clone(),
+                }
+            }).collect();
+        }
+
+        Some(message.alloc_id.clone())
+    }
+
+    fn on_confirmation(&mut self,message: &Confirmation) -> Option<Vec<u8>> {
+        //Confirmations that replace or cancel an earlier one might leave out AllocID.
+        let alloc_id = if !message.alloc_id.is_empty() {
+            message.alloc_id.clone()
+        }
+        else {
+            match self.alloc_id_by_confirm_id.get(&message.confirm_ref_id) {
+                Some(alloc_id) => alloc_id.clone(),
+                None => return None,
+            }
+        };
+        let allocation = self.allocations.entry(alloc_id.clone()).or_insert_with(|| Allocation::new(&alloc_id));
+
+        //A replacement Confirmation takes over from the one it refers to.
+        if message.confirm_trans_type != ConfirmTransType::New {
+            if let Some(confirmation) = allocation.get_confirmation_mut(&message.confirm_ref_id) {
+                confirmation.canceled = true;
+            }
+        }
+        if message.confirm_trans_type == ConfirmTransType::Cancel {
+            return Some(alloc_id);
+        }
+
+        let confirmation = AllocConfirmation {
+            confirm_id: message.confirm_id.clone(),
+            alloc_account: message.alloc_account.clone(),
+            individual_alloc_id: message.individual_alloc_id.clone(),
+            alloc_qty: message.alloc_qty.clone(),
+            avg_px: message.avg_px.clone(),
+            net_money: message.net_money.clone(),
+            confirm_status: message.confirm_status.clone(),
+            affirm_status: None,
+            confirm_rej_reason: None,
+            canceled: false,
+        };
+        match allocation.confirmations.iter().position(|existing_confirmation| existing_confirmation.confirm_id == message.confirm_id) {
+            Some(index) => allocation.confirmations[index] = confirmation,
+            None => allocation.confirmations.push(confirmation),
+        }
+        self.alloc_id_by_confirm_id.insert(message.confirm_id.clone(),alloc_id.clone());
+
+        Some(alloc_id)
+    }
+
+    fn on_confirmation_ack(&mut self,message: &ConfirmationAck) -> Option<Vec<u8>> {
+        let alloc_id = match self.alloc_id_by_confirm_id.get(&message.confirm_id) {
+            Some(alloc_id) => alloc_id.clone(),
+            None => return None,
+        };
+        let allocation = self.allocations.get_mut(&alloc_id).unwrap();
+        let confirmation = allocation.get_confirmation_mut(&message.confirm_id).unwrap();
+
+        confirmation.affirm_status = Some(message.affirm_status.clone());
+        confirmation.confirm_rej_reason = message.confirm_rej_reason.clone();
+
+        Some(alloc_id)
+    }
+}
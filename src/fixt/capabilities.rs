@@ -0,0 +1,43 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dictionary::field_types::other::SessionRejectReason;
+
+//A SessionRejectReason the engine sends on its own in a Reject(3) and what causes it.
+pub struct SessionRejectCapability {
+    pub reason: SessionRejectReason,
+    pub cause: &'static str,
+}
+
+//Every SessionRejectReason the engine can emit while parsing and validating received messages.
+//Reasons that aren't listed, such as DecryptionProblem or XMLValidationError, are never sent
+//because the engine doesn't support the related feature. Each entry is matched with a concrete
+//malformed message in tests/session_reject.rs so the list can't drift from what the engine
+//actually does.
+pub const SESSION_REJECT_CAPABILITIES: &'static [SessionRejectCapability] = &[
+    SessionRejectCapability { reason: SessionRejectReason::InvalidTagNumber, cause: "Tag is not defined in the dictionary at all." },
+    SessionRejectCapability { reason: SessionRejectReason::RequiredTagMissing, cause: "Required tag, or OrigSendingTime for a PossDup message, is missing." },
+    SessionRejectCapability { reason: SessionRejectReason::TagNotDefinedForThisMessageType, cause: "Known tag that isn't part of the message." },
+    SessionRejectCapability { reason: SessionRejectReason::TagSpecifiedWithoutAValue, cause: "Tag is followed by an empty value." },
+    SessionRejectCapability { reason: SessionRejectReason::ValueIsIncorrectForThisTag, cause: "Value is out of range, such as an unknown enumeration or EndSeqNo < BeginSeqNo." },
+    SessionRejectCapability { reason: SessionRejectReason::IncorrectDataFormatForValue, cause: "Value can't be parsed as the tag's type." },
+    SessionRejectCapability { reason: SessionRejectReason::CompIDProblem, cause: "SenderCompID or TargetCompID doesn't match the session." },
+    SessionRejectCapability { reason: SessionRejectReason::SendingTimeAccuracyProblem, cause: "OrigSendingTime is after SendingTime." },
+    SessionRejectCapability { reason: SessionRejectReason::InvalidMsgType, cause: "MsgType is neither in the dictionary nor the FIX specification." },
+    SessionRejectCapability { reason: SessionRejectReason::TagAppearsMoreThanOnce, cause: "Tag is repeated outside of a repeating group." },
+    SessionRejectCapability { reason: SessionRejectReason::TagSpecifiedOutOfRequiredOrder, cause: "Header or trailer tag is not where the specification requires." },
+    SessionRejectCapability { reason: SessionRejectReason::IncorrectNumInGroupCountForRepeatingGroup, cause: "Number of repeating groups doesn't match the NumInGroup count." },
+    SessionRejectCapability { reason: SessionRejectReason::Other, cause: "Message exceeds the maximum message size." },
+];
+
+pub fn can_emit_session_reject_reason(reason: &SessionRejectReason) -> bool {
+    SESSION_REJECT_CAPABILITIES.iter().any(|capability| capability.reason == *reason)
+}
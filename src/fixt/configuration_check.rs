@@ -0,0 +1,166 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self,OpenOptions};
+use std::io;
+use std::path::{Path,PathBuf};
+use std::time::Duration;
+
+use clock::Clock;
+use dictionary::administrative_msg_types;
+use fixt::engine::ConnectionBuilder;
+use fixt::message::BuildFIXTMessage;
+use fixt::schedule::SessionSchedule;
+use fixt::session_id::SessionID;
+
+const MAX_CLOCK_SKEW_MILLISECONDS: i64 = 1000;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+const WRITE_PROBE_FILE_NAME: &'static str = ".fix_rs_write_probe";
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum ConfigurationIssue {
+    MissingMsgType(Vec<u8>), //Message type the session needs isn't in the message dictionary so it can't be parsed.
+    MessageStoreNotWritable(PathBuf,io::ErrorKind), //Message store directory can't be created or written to.
+    LogNotWritable(PathBuf,io::ErrorKind), //Quarantine log or message log can't be opened for appending.
+    InvalidScheduleUtcOffset(i64), //Schedule's time zone reported an offset, in seconds, of a day or more.
+    ClockNotRealTime, //Engine's clock, such as a SimulatedClock, doesn't move on its own.
+    ClockSkew(Duration), //Engine's clock disagrees with the operating system's clock by more than a second.
+}
+
+impl fmt::Display for ConfigurationIssue {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigurationIssue::MissingMsgType(ref msg_type) => write!(f,"ConfigurationIssue::MissingMsgType({})",String::from_utf8_lossy(msg_type)),
+            ConfigurationIssue::MessageStoreNotWritable(ref path,kind) => write!(f,"ConfigurationIssue::MessageStoreNotWritable({},{:?})",path.display(),kind),
+            ConfigurationIssue::LogNotWritable(ref path,kind) => write!(f,"ConfigurationIssue::LogNotWritable({},{:?})",path.display(),kind),
+            ConfigurationIssue::InvalidScheduleUtcOffset(utc_offset) => write!(f,"ConfigurationIssue::InvalidScheduleUtcOffset({})",utc_offset),
+            ConfigurationIssue::ClockNotRealTime => write!(f,"ConfigurationIssue::ClockNotRealTime"),
+            ConfigurationIssue::ClockSkew(skew) => write!(f,"ConfigurationIssue::ClockSkew({:?})",skew),
+        }
+    }
+}
+
+//A connection that is going to be added to the Engine along with what it's expected to do.
+pub struct ConnectionConfiguration {
+    pub builder: ConnectionBuilder,
+    pub expected_msg_types: Vec<&'static [u8]>, //Application messages the session sends or receives. Administrative messages are always checked.
+    pub schedule: Option<SessionSchedule>, //When the session is supposed to be open, if it has a schedule.
+}
+
+impl ConnectionConfiguration {
+    pub fn new(builder: ConnectionBuilder) -> ConnectionConfiguration {
+        ConnectionConfiguration {
+            builder: builder,
+            expected_msg_types: Vec::new(),
+            schedule: None,
+        }
+    }
+}
+
+pub struct ConnectionReport {
+    pub session_id: SessionID,
+    pub next_session_start: Option<DateTime<UTC>>, //Next time the schedule opens. None without a schedule.
+    pub issues: Vec<ConfigurationIssue>,
+}
+
+//Result of EngineBuilder::validate_configuration(). Issues are listed in the order they were found.
+pub struct ConfigurationReport {
+    pub issues: Vec<ConfigurationIssue>, //Problems with the Engine itself, like its clock.
+    pub connections: Vec<ConnectionReport>, //Same order as the connections that were checked.
+}
+
+impl ConfigurationReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty() && self.connections.iter().all(|connection| connection.issues.is_empty())
+    }
+}
+
+fn check_directory_writable(directory: &Path) -> Result<(),io::Error> {
+    try!(fs::create_dir_all(directory));
+
+    //Write and remove a scratch file instead of opening the store so the check never touches the
+    //session's data.
+    let probe_path = directory.join(WRITE_PROBE_FILE_NAME);
+    try!(OpenOptions::new().create(true).write(true).truncate(true).open(&probe_path));
+    fs::remove_file(&probe_path)
+}
+
+fn check_log_writable(path: &Path) -> Result<(),io::Error> {
+    //Opened the same way the Engine opens it so nothing already written is lost.
+    OpenOptions::new().create(true).append(true).open(path).map(|_| ())
+}
+
+fn check_connection(message_dictionary: &HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,now: &DateTime<UTC>,configuration: &ConnectionConfiguration) -> ConnectionReport {
+    let builder = &configuration.builder;
+    let mut issues = Vec::new();
+
+    let mut msg_types = administrative_msg_types();
+    msg_types.extend(configuration.expected_msg_types.iter().cloned());
+    for msg_type in msg_types {
+        if !message_dictionary.contains_key(msg_type) && !issues.contains(&ConfigurationIssue::MissingMsgType(msg_type.to_vec())) {
+            issues.push(ConfigurationIssue::MissingMsgType(msg_type.to_vec()));
+        }
+    }
+
+    if let Some(directory) = builder.message_store_path() {
+        if let Err(e) = check_directory_writable(directory) {
+            issues.push(ConfigurationIssue::MessageStoreNotWritable(directory.to_path_buf(),e.kind()));
+        }
+    }
+    let log_paths = builder.quarantine_log_path().into_iter().chain(builder.message_log_paths());
+    for path in log_paths {
+        if let Err(e) = check_log_writable(path) {
+            issues.push(ConfigurationIssue::LogNotWritable(path.to_path_buf(),e.kind()));
+        }
+    }
+
+    //A time zone with an offset of a day or more can't be turned into a session window.
+    let mut next_session_start = None;
+    if let Some(ref schedule) = configuration.schedule {
+        let utc = now.naive_utc();
+        let utc_offset = schedule.utc_to_local(&utc).signed_duration_since(utc).num_seconds();
+        if utc_offset.abs() >= SECONDS_PER_DAY {
+            issues.push(ConfigurationIssue::InvalidScheduleUtcOffset(utc_offset));
+        }
+        else {
+            next_session_start = Some(schedule.next_start(now));
+        }
+    }
+
+    ConnectionReport {
+        session_id: builder.session_id(),
+        next_session_start: next_session_start,
+        issues: issues,
+    }
+}
+
+pub fn validate_configuration(message_dictionary: &HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,clock: &Clock,connections: &[ConnectionConfiguration]) -> ConfigurationReport {
+    let mut issues = Vec::new();
+    if !clock.follows_real_time() {
+        issues.push(ConfigurationIssue::ClockNotRealTime);
+    }
+    else {
+        let skew_milliseconds = clock.utc_now().signed_duration_since(UTC::now()).num_milliseconds().abs();
+        if skew_milliseconds > MAX_CLOCK_SKEW_MILLISECONDS {
+            issues.push(ConfigurationIssue::ClockSkew(Duration::from_millis(skew_milliseconds as u64)));
+        }
+    }
+
+    let now = clock.utc_now();
+    ConfigurationReport {
+        issues: issues,
+        connections: connections.iter().map(|configuration| check_connection(message_dictionary,&now,configuration)).collect(),
+    }
+}
@@ -0,0 +1,110 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[cfg(feature = "compression")]
+use flate2::read::GzDecoder;
+use std::io;
+#[cfg(feature = "compression")]
+use std::io::Read;
+
+//Turns the bytes received on a connection back into plain FIX messages before they reach the
+//parser. Some venues wrap FIX payloads in their own framing and compress them, usually for bulk
+//data such as security definitions. See Engine::set_inbound_decoder().
+//
+//decode() is called with every chunk of bytes read from the socket. A chunk can end in the middle
+//of a frame so anything that can't be decoded yet must be kept until the next call. Decoded bytes
+//are appended to output. Returning an error terminates the connection.
+//
+//Decoders run on the same thread that does all of the networking for every connection so they
+//must be quick and must not block or panic.
+pub trait InboundDecoder: Send {
+    fn decode(&mut self,bytes: &[u8],output: &mut Vec<u8>) -> Result<(),io::Error>;
+}
+
+#[cfg(feature = "compression")]
+const FRAME_HEADER_LEN: usize = 4;
+
+#[cfg(feature = "compression")]
+const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+//Decodes frames made up of a 4 byte big-endian length followed by that many bytes of gzip
+//compressed FIX messages. Requires the "compression" feature.
+#[cfg(feature = "compression")]
+pub struct GzipFrameDecoder {
+    pending: Vec<u8>, //Bytes of a frame that hasn't been completely received yet.
+    max_frame_len: usize,
+}
+
+#[cfg(feature = "compression")]
+impl GzipFrameDecoder {
+    pub fn new() -> GzipFrameDecoder {
+        GzipFrameDecoder {
+            pending: Vec::new(),
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    pub fn max_frame_len(mut self,max_frame_len: usize) -> GzipFrameDecoder {
+        //Largest frame allowed both before and after decompressing. Anything bigger is treated as
+        //an error instead of being buffered forever.
+        self.max_frame_len = max_frame_len;
+        self
+    }
+}
+
+#[cfg(feature = "compression")]
+impl Default for GzipFrameDecoder {
+    fn default() -> Self {
+        GzipFrameDecoder::new()
+    }
+}
+
+#[cfg(feature = "compression")]
+impl InboundDecoder for GzipFrameDecoder {
+    fn decode(&mut self,bytes: &[u8],output: &mut Vec<u8>) -> Result<(),io::Error> {
+        self.pending.extend_from_slice(bytes);
+
+        let mut offset = 0;
+        loop {
+            let remaining = &self.pending[offset..];
+            if remaining.len() < FRAME_HEADER_LEN {
+                break;
+            }
+
+            let frame_len = ((remaining[0] as usize) << 24) |
+                            ((remaining[1] as usize) << 16) |
+                            ((remaining[2] as usize) << 8) |
+                            (remaining[3] as usize);
+            if frame_len > self.max_frame_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,"Compressed frame is longer than max_frame_len"));
+            }
+            if remaining.len() < FRAME_HEADER_LEN + frame_len {
+                break;
+            }
+
+            //Read one byte past the limit so an oversized frame can be told apart from one that
+            //is exactly max_frame_len.
+            let frame = &remaining[FRAME_HEADER_LEN..FRAME_HEADER_LEN + frame_len];
+            let decoder = try!(GzDecoder::new(frame));
+            let mut decompressed = Vec::new();
+            try!(decoder.take(self.max_frame_len as u64 + 1).read_to_end(&mut decompressed));
+            if decompressed.len() > self.max_frame_len {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,"Decompressed frame is longer than max_frame_len"));
+            }
+            output.extend_from_slice(&decompressed);
+
+            offset += FRAME_HEADER_LEN + frame_len;
+        }
+        self.pending.drain(..offset);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono;
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::collections::{HashMap,VecDeque};
+use std::time::Duration;
+
+use dictionary::messages::NewOrderSingle;
+use fixt::message::FIXTMessage;
+
+//What happens when a NewOrderSingle reuses a ClOrdID that was sent within the window.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum DuplicateOrderAction {
+    Refuse, //Don't send the message. It's returned with EngineEvent::DuplicateOrderRefused.
+    Flag, //Send the message anyway and report it with EngineEvent::DuplicateOrderSent.
+}
+
+//A last line of defense against sending the same order twice because of an application bug. Every
+//NewOrderSingle passed to Engine::send_message() is checked against the ClOrdIDs of the
+//NewOrderSingles sent on the same connection within window. Set with
+//ConnectionBuilder::duplicate_order_guard() or Engine::set_duplicate_order_guard().
+//
+//When the connection also has a MessageStore, the ClOrdIDs sent within window are recovered from
+//the stored outbound messages using their SendingTime. A duplicate is then caught even if the
+//process was restarted in between. Without a MessageStore, only orders sent since the guard was set
+//are remembered.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct DuplicateOrderGuard {
+    pub window: Duration,
+    pub action: DuplicateOrderAction,
+}
+
+impl DuplicateOrderGuard {
+    pub fn new(window: Duration,action: DuplicateOrderAction) -> DuplicateOrderGuard {
+        DuplicateOrderGuard {
+            window: window,
+            action: action,
+        }
+    }
+}
+
+//Used by the engine's thread to remember which ClOrdIDs were sent and when.
+pub struct SentClOrdIDs {
+    guard: DuplicateOrderGuard,
+    window: chrono::Duration,
+    sent_times: HashMap<Vec<u8>,DateTime<UTC>>,
+    sent_order: VecDeque<(DateTime<UTC>,Vec<u8>)>, //Oldest first so expired ClOrdIDs can be dropped cheaply.
+}
+
+impl SentClOrdIDs {
+    pub fn new(guard: DuplicateOrderGuard) -> SentClOrdIDs {
+        SentClOrdIDs {
+            guard: guard,
+            window: chrono::Duration::from_std(guard.window).unwrap_or(chrono::Duration::max_value()),
+            sent_times: HashMap::new(),
+            sent_order: VecDeque::new(),
+        }
+    }
+
+    pub fn guard(&self) -> DuplicateOrderGuard {
+        self.guard
+    }
+
+    pub fn is_duplicate(&mut self,message: &FIXTMessage,now: DateTime<UTC>) -> bool {
+        //Only NewOrderSingles with a ClOrdID are checked.
+        let cl_ord_id = match cl_ord_id_to_guard(message) {
+            Some(cl_ord_id) => cl_ord_id,
+            None => return false,
+        };
+
+        self.remove_expired(now);
+        self.sent_times.contains_key(cl_ord_id)
+    }
+
+    pub fn on_sent(&mut self,message: &FIXTMessage,sent_time: DateTime<UTC>) {
+        //Remember a NewOrderSingle that was sent, or was found in the message store, at sent_time.
+        let cl_ord_id = match cl_ord_id_to_guard(message) {
+            Some(cl_ord_id) => cl_ord_id.to_vec(),
+            None => return,
+        };
+
+        //Keep the latest time when the same ClOrdID shows up again, such as when a duplicate was
+        //only flagged.
+        let is_newer = match self.sent_times.get(&cl_ord_id) {
+            Some(previous_sent_time) => sent_time > *previous_sent_time,
+            None => true,
+        };
+        if is_newer {
+            self.sent_times.insert(cl_ord_id.clone(),sent_time);
+            self.sent_order.push_back((sent_time,cl_ord_id));
+        }
+    }
+
+    pub fn is_within_window(&self,sent_time: DateTime<UTC>,now: DateTime<UTC>) -> bool {
+        sent_time > now - self.window
+    }
+
+    fn remove_expired(&mut self,now: DateTime<UTC>) {
+        let expires_before = now - self.window;
+        while self.sent_order.front().map_or(false,|&(sent_time,_)| sent_time <= expires_before) {
+            let (sent_time,cl_ord_id) = self.sent_order.pop_front().unwrap();
+
+            //A newer send of the same ClOrdID is further back in the queue and still counts.
+            if self.sent_times.get(&cl_ord_id) == Some(&sent_time) {
+                self.sent_times.remove(&cl_ord_id);
+            }
+        }
+    }
+}
+
+fn cl_ord_id_to_guard(message: &FIXTMessage) -> Option<&[u8]> {
+    if message.msg_type() != NewOrderSingle::msg_type() {
+        return None;
+    }
+
+    message.cl_ord_id_bytes()
+}
@@ -13,34 +13,64 @@
 
 use mio::{Events,Poll,PollOpt,Ready,Token};
 use mio::channel::{channel,Receiver,Sender};
-use std::collections::HashMap;
+use std::collections::{HashMap,VecDeque};
 use mio::tcp::TcpListener;
+use std::any::Any;
 use std::fmt;
+use std::fs::{File,OpenOptions};
 use std::io;
 use std::mem;
 use std::ops::Range;
-use std::net::{SocketAddr,ToSocketAddrs};
+use std::net::{IpAddr,SocketAddr,ToSocketAddrs};
+use std::path::{Path,PathBuf};
 use std::sync::{Arc,Mutex};
-use std::sync::mpsc::TryRecvError;
+use std::sync::mpsc::{self,TryRecvError};
 use std::thread;
 use std::time::{Duration,Instant};
 
-use dictionary::messages::Logon;
+use clock::{Clock,SystemClock};
+use dictionary::administrative_msg_types;
+use dictionary::field_types::generic::TimestampPrecision;
+use dictionary::field_types::other::SessionStatus;
+use dictionary::messages::{Heartbeat,Logon,MarketDataSnapshotFullRefresh,ResendRequest,SequenceReset,TestRequest};
+use fixt::configuration_check::{ConfigurationReport,ConnectionConfiguration,validate_configuration};
+use fixt::decompression::InboundDecoder;
+use fixt::duplicate_order_guard::DuplicateOrderGuard;
 use fixt::engine_thread::{CONNECTION_COUNT_MAX,BASE_CONNECTION_TOKEN,INTERNAL_ENGINE_EVENT_TOKEN,InternalEngineToThreadEvent,internal_engine_thread};
+use fixt::failover::SessionCoordinator;
+use fixt::health_check::{HealthCheckRegistry,HealthMonitor};
 use fixt::message::{BuildFIXTMessage,FIXTMessage};
-use fix::ParseError;
+use fixt::message_log::{MessageLog,MessageLogSettings};
+use fixt::message_signer::MessageSigner;
+use fixt::message_statistics::MessageStatisticsReport;
+use fixt::message_store::{FileMessageStore,MessageStore};
+use fixt::middleware::{InboundMiddleware,MiddlewareChain};
+use fixt::outbound_validation::{OutboundFieldError,OutboundValidation};
+use fixt::rate_anomaly::{RateAnomaly,RateAnomalySettings};
+use fixt::reference_data::{InstrumentReference,ReferenceDataCache};
+use fixt::session_id::SessionID;
+use fixt::session_provisioner::{LogonRequest,ProvisioningDecision,SessionProvisioner,SessionSettings};
+use fixt::symbol_mapper::SymbolMapper;
+use fixt::third_party_routing::ThirdPartyRoutingSettings;
+use fix::{ParseError,ValidationSettings};
 use fix_version::FIXVersion;
 use message_version::MessageVersion;
 use token_generator::TokenGenerator;
 
 const ENGINE_EVENT_TOKEN: Token = Token(0);
 
+//Identifies a connection. The generation is unique for the life of an Engine so a Connection kept
+//around after its connection was terminated can never refer to a different connection that
+//happens to reuse the same id. Any Engine function given such a stale Connection ignores it.
 #[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
-pub struct Connection(pub usize);
+pub struct Connection {
+    pub id: usize,
+    pub generation: u64,
+}
 
 impl fmt::Display for Connection {
     fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f,"{}",self.0)
+        write!(f,"{}",self.id)
     }
 }
 
@@ -59,7 +89,9 @@ pub enum ConnectionTerminatedReason {
     InboundMsgSeqNumLowerThanExpectedError,
     InboundResendRequestLoopError,
     LocalRequested,
+    LogonDuplicateSessionError,
     LogonHeartBtIntNegativeError,
+    LogonHeartBtIntOutOfRangeError,
     LogonParseError(ParseError),
     LogonNeverReceivedError,
     LogonNotFirstMessageError,
@@ -67,8 +99,10 @@ pub enum ConnectionTerminatedReason {
     LogoutNoHangUpError,
     LogoutNoResponseError,
     OutboundMsgSeqNumMaxExceededError,
+    ProbeNotRespondedError,
     RemoteRequested,
     SenderCompIDWrongError,
+    SessionCoordinatorRevokedError,
     SocketNotWritableTimeoutError,
     SocketReadError(io::Error),
     SocketWriteError(io::Error),
@@ -88,7 +122,9 @@ impl fmt::Debug for ConnectionTerminatedReason {
             ConnectionTerminatedReason::InboundMsgSeqNumLowerThanExpectedError => write!(f,"Received message with lower MsgSeqNum than expected."),
             ConnectionTerminatedReason::InboundResendRequestLoopError => write!(f,"Received too many ResendRequests with the same BeginSeqNo."),
             ConnectionTerminatedReason::LocalRequested => write!(f,"Local requested logout and it was performed cleanly."),
+            ConnectionTerminatedReason::LogonDuplicateSessionError => write!(f,"Remote tried to logon to a session that is already logged on."),
             ConnectionTerminatedReason::LogonHeartBtIntNegativeError => write!(f,"Response to logon included negative HeartBtInt."),
+            ConnectionTerminatedReason::LogonHeartBtIntOutOfRangeError => write!(f,"Remote tried to logon with a HeartBtInt outside of the range allowed by the listener."),
             ConnectionTerminatedReason::LogonParseError(_) => write!(f,"Could not parse logon response."), //Did you connect to a server not running a FIX engine?
             ConnectionTerminatedReason::LogonNeverReceivedError => write!(f,"Never received logon from new connection."),
             ConnectionTerminatedReason::LogonNotFirstMessageError => write!(f,"Remote responded to logon with a non-logon message."),
@@ -96,8 +132,10 @@ impl fmt::Debug for ConnectionTerminatedReason {
             ConnectionTerminatedReason::LogoutNoHangUpError => write!(f,"Remote requested logout but did not close socket after response."),
             ConnectionTerminatedReason::LogoutNoResponseError => write!(f,"Local requested logout but remote did not respond within a reasonable amount of time."),
             ConnectionTerminatedReason::OutboundMsgSeqNumMaxExceededError => write!(f,"Expected outbound MsgSeqNum exceeded maximum allowed."),
+            ConnectionTerminatedReason::ProbeNotRespondedError => write!(f,"Remote did not respond to any TestRequest probes sent after a quiet period."),
             ConnectionTerminatedReason::RemoteRequested => write!(f,"Remote requested logout and it was performed cleanly."),
             ConnectionTerminatedReason::SenderCompIDWrongError => write!(f,"Received message with SenderCompID not matching the expected value."),
+            ConnectionTerminatedReason::SessionCoordinatorRevokedError => write!(f,"Another engine process took over the session or the SessionCoordinator could not be reached."),
             ConnectionTerminatedReason::SocketNotWritableTimeoutError => write!(f,"Socket returned WouldBlock on write for an unreasonable amount of time."),
             ConnectionTerminatedReason::SocketReadError(ref error) => write!(f,"Socket could not be read from: {}",error),
             ConnectionTerminatedReason::SocketWriteError(ref error) => write!(f,"Socket could not be written to: {}",error),
@@ -107,24 +145,127 @@ impl fmt::Debug for ConnectionTerminatedReason {
     }
 }
 
+//Why a listener closed a connection right after accepting it. See ListenerSettings.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ConnectionRefusedReason {
+    AddressNotAllowed, //Address is denied or missing from a non-empty allow list.
+    TooManyAttempts, //Address connected too many times recently.
+    TooManyUnauthenticatedConnections, //Too many connections are still waiting to logon.
+}
+
+//Data for EngineEvent::ConnectionRefused.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct ConnectionRefusedData {
+    pub listener: Listener,
+    pub address: SocketAddr,
+    pub reason: ConnectionRefusedReason,
+}
+
+//Data for EngineEvent::ConnectionAccepted.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct ConnectionAcceptedData {
+    pub listener: Listener,
+    pub connection: Connection,
+    pub address: SocketAddr,
+}
+
+//Data for EngineEvent::ConnectionLoggingOn.
+pub struct ConnectionLoggingOnData {
+    pub listener: Listener,
+    pub connection: Connection,
+    pub message: Box<Logon>,
+    pub address: SocketAddr, //Where the connection came from.
+}
+
+impl fmt::Debug for ConnectionLoggingOnData {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,"ConnectionLoggingOnData {{ listener: {:?}, connection: {:?}, message: {:?}, address: {:?} }}",self.listener,self.connection,&*self.message as &FIXTMessage,self.address)
+    }
+}
+
+//Data for EngineEvent::SessionStatusReceived.
+#[derive(Clone,Debug,PartialEq)]
+pub struct SessionStatusReceivedData {
+    pub connection: Connection,
+    pub session_status: SessionStatus,
+    pub text: Vec<u8>,
+}
+
+//Data for EngineEvent::AdminNotice.
+#[derive(Clone,Debug,PartialEq)]
+pub struct AdminNoticeData {
+    pub connection: Connection,
+    pub msg_type: Vec<u8>,
+    pub msg_seq_num: u64,
+    pub text: Vec<u8>,
+}
+
+//Data for EngineEvent::MessageReceivedWithInstrument.
+#[derive(Debug)]
+pub struct MessageReceivedWithInstrumentData {
+    pub connection: Connection,
+    pub message: Box<FIXTMessage + Send>,
+    pub instrument: Arc<InstrumentReference>, //Reference data cached for the message's Symbol.
+}
+
+//Data for EngineEvent::MessageReceivedDuplicateSkipped.
+#[derive(Clone,Debug,PartialEq)]
+pub struct MessageReceivedDuplicateSkippedData {
+    pub connection: Connection,
+    pub msg_type: Vec<u8>,
+    pub msg_seq_num: u64,
+}
+
+//Data for EngineEvent::OutboundValidationFailed.
+#[derive(Debug)]
+pub struct OutboundValidationFailedData {
+    pub connection: Connection,
+    pub message: Box<FIXTMessage + Send>, //Message that was not sent.
+    pub errors: Vec<OutboundFieldError>, //Every problem found, not just the first.
+}
+
+//Events with more than a couple of values carry a dedicated data struct so fields can be added
+//later without changing the shape of the variant. New events are expected to follow suit. Match
+//against EngineEvent with a wildcard arm because new variants are added over time.
 pub enum EngineEvent {
     ConnectionFailed(Connection,io::Error), //Could not setup connection.
     ConnectionSucceeded(Connection), //Connection completed and ready to begin logon.
     ConnectionTerminated(Connection,ConnectionTerminatedReason), //Connection ended for ConnectionTerminatedReason reason.
     ConnectionDropped(Listener,SocketAddr), //Connection was dropped by listener because of a lock of resources.
-    ConnectionAccepted(Listener,Connection,SocketAddr), //Listener accepted a new connection and is awaiting a Logon message.
-    ConnectionLoggingOn(Listener,Connection,Box<Logon>),
+    ConnectionRefused(ConnectionRefusedData), //Connection was closed by listener because it broke one of the rules in ListenerSettings.
+    ConnectionAccepted(ConnectionAcceptedData), //Listener accepted a new connection and is awaiting a Logon message.
+    ConnectionLoggingOn(ConnectionLoggingOnData), //Connection sent a Logon message and is awaiting Engine::approve_new_connection() or Engine::reject_new_connection().
     SessionEstablished(Connection), //Connection completed logon process successfully.
+    MessagesNotSent(Connection,Vec<Box<FIXTMessage + Send>>), //Queued messages that were dropped while responding to a Logout or after the connection was lost. See LogoutResponsePolicy and OutboxPolicy.
+    MessagesExpired(Connection,Vec<Box<FIXTMessage + Send>>), //Queued messages that were dropped because their time-to-live passed before they could be sent. See Engine::send_message_with_time_to_live().
+    MessagesHeld(Connection,usize), //Number of messages left over from an earlier connection that are waiting for Engine::release_held_messages() or Engine::discard_held_messages(). See OutboxPolicy::Hold.
+    SessionStatusReceived(SessionStatusReceivedData), //Remote included SessionStatus (and Text) in a Logon or Logout. Sent just before the related SessionEstablished or ConnectionTerminated event.
     ListenerFailed(Listener,io::Error), //Could not setup listener.
     ListenerAcceptFailed(Listener,io::Error), //Could not accept a connection with listener.
     MessageReceived(Connection,Box<FIXTMessage + Send>), //New valid message was received.
+    AdminMessageReceived(Connection,Box<FIXTMessage + Send>), //New valid Heartbeat, TestRequest, ResendRequest, or SequenceReset was received. See EngineBuilder::admin_message_delivery().
+    AdminNotice(AdminNoticeData), //Heartbeat or TestRequest with a non-empty Text was received. Sent just before the message itself, which might never be delivered depending on EngineBuilder::admin_message_delivery().
+    MessageReceivedWithInstrument(MessageReceivedWithInstrumentData), //Same as MessageReceived but for a message whose Symbol was found in the ReferenceDataCache. See EngineBuilder::reference_data_cache().
     MessageReceivedGarbled(Connection,ParseError), //New message could not be parsed correctly. (If not garbled (FIXT 1.1, page 40), a Reject will be issued first)
     MessageReceivedDuplicate(Connection,Box<FIXTMessage + Send>), //Message with MsgSeqNum already seen was received.
+    MessageReceivedDuplicateSkipped(MessageReceivedDuplicateSkippedData), //MsgType and MsgSeqNum of a duplicate that was thrown away without being parsed. See Engine::set_skip_duplicate_parsing().
     MessageRejected(Connection,Box<FIXTMessage + Send>), //New message breaks session rules and was rejected.
     ResendRequested(Connection,Range<u64>), //Range of messages by MsgSeqNum that are requested to be resent. [Range::start,Range::end)
+    ResendCompleted(Connection,ResendSummary), //Last of the response passed to Engine::send_resend_response(), or built from the connection's MessageStore, was queued to be sent. Comes after the last chunk when ResendRequestLimits splits the response up.
     SequenceResetResetHasNoEffect(Connection),
     SequenceResetResetInThePast(Connection),
+    MessageStoreError(Connection,io::Error), //Message store could not be read or written. The connection continues but might not be resumable after a restart.
+    OutboundValidationFailed(OutboundValidationFailedData), //Message passed to Engine::send_message() was not sent because it's invalid according to the dictionary. See Engine::set_outbound_validation().
+    DuplicateOrderRefused(Connection,Box<FIXTMessage + Send>), //NewOrderSingle passed to Engine::send_message() was not sent because its ClOrdID was already sent. See Engine::set_duplicate_order_guard().
+    DuplicateOrderSent(Connection,Vec<u8>), //NewOrderSingle with this ClOrdID was sent even though the ClOrdID was already sent. See Engine::set_duplicate_order_guard().
+    InboundQuietPeriodExceeded(Connection,Duration), //Nothing has been received for this long even though the session is established. Only a warning. See Engine::set_inbound_quiet_period().
+    InboundRateAnomaly(Connection,RateAnomaly), //Rate of received messages of a MsgType became or stopped being anomalous. Only a warning. See Engine::set_inbound_rate_anomaly().
+    LogonRefusedByCoordinator(Connection,Box<FIXTMessage + Send>), //Logon passed to Engine::send_message() was not sent because another engine process is active for the session. See Engine::set_session_coordinator().
+    SessionCoordinatorError(Connection,io::Error), //SessionCoordinator could not be reached. Treated the same as another engine process being active.
+    TestRequestReceived(Connection,Vec<u8>), //TestReqID of a TestRequest the counterparty sent to check the connection. Only sent when enabled with TestRequestSettings::report_received.
     FatalError(&'static str,io::Error), //A critical error has occurred. No more events can be received and no more messages will be sent.
+    #[doc(hidden)]
+    __Nonexhaustive, //Never sent. Keeps matches from being exhaustive so new events aren't a breaking change.
 }
 
 impl fmt::Debug for EngineEvent {
@@ -134,23 +275,204 @@ impl fmt::Debug for EngineEvent {
             EngineEvent::ConnectionSucceeded(connection) => write!(f,"EngineEvent::ConnectionSucceeded({:?})",connection),
             EngineEvent::ConnectionTerminated(connection,ref reason) => write!(f,"EngineEvent::ConnectionTerminated({:?},{:?})",connection,reason),
             EngineEvent::ConnectionDropped(connection,addr) => write!(f,"EngineEvent::ConnectionDropped({:?},{:?})",connection,addr),
-            EngineEvent::ConnectionAccepted(listener,connection,addr) => write!(f,"EngineEvent::ConnectionAccepted({:?},{:?},{:?})",listener,connection,addr),
-            EngineEvent::ConnectionLoggingOn(listener,connection,ref message) => write!(f,"EngineEvent::ConnectionLoggingOn({:?},{:?},{:?})",listener,connection,&**message as &FIXTMessage),
+            EngineEvent::ConnectionRefused(ref data) => write!(f,"EngineEvent::ConnectionRefused({:?})",data),
+            EngineEvent::ConnectionAccepted(ref data) => write!(f,"EngineEvent::ConnectionAccepted({:?})",data),
+            EngineEvent::ConnectionLoggingOn(ref data) => write!(f,"EngineEvent::ConnectionLoggingOn({:?})",data),
             EngineEvent::SessionEstablished(connection) => write!(f,"EngineEvent::SessionEstablished({:?})",connection),
+            EngineEvent::MessagesNotSent(connection,ref messages) => write!(f,"EngineEvent::MessagesNotSent({:?},{:?})",connection,messages),
+            EngineEvent::MessagesExpired(connection,ref messages) => write!(f,"EngineEvent::MessagesExpired({:?},{:?})",connection,messages),
+            EngineEvent::MessagesHeld(connection,count) => write!(f,"EngineEvent::MessagesHeld({:?},{:?})",connection,count),
+            EngineEvent::SessionStatusReceived(ref data) => write!(f,"EngineEvent::SessionStatusReceived({:?})",data),
             EngineEvent::ListenerFailed(listener,ref error) => write!(f,"EngineEvent::ListenerFailed({:?},{:?})",listener,error),
             EngineEvent::ListenerAcceptFailed(listener,ref error) => write!(f,"EngineEvent::ListenerAcceptFailed({:?},{:?})",listener,error),
             EngineEvent::MessageReceived(connection,ref message) => write!(f,"EngineEvent::MessageReceived({:?},{:?})",connection,message),
+            EngineEvent::AdminMessageReceived(connection,ref message) => write!(f,"EngineEvent::AdminMessageReceived({:?},{:?})",connection,message),
+            EngineEvent::AdminNotice(ref data) => write!(f,"EngineEvent::AdminNotice({:?})",data),
+            EngineEvent::MessageReceivedWithInstrument(ref data) => write!(f,"EngineEvent::MessageReceivedWithInstrument({:?})",data),
             EngineEvent::MessageReceivedGarbled(connection,ref parse_error) => write!(f,"EngineEvent::MessageReceivedGarbled({:?},{:?})",connection,parse_error),
             EngineEvent::MessageReceivedDuplicate(connection,ref message) => write!(f,"EngineEvent::MessageReceivedDuplicate({:?},{:?})",connection,message),
+            EngineEvent::MessageReceivedDuplicateSkipped(ref data) => write!(f,"EngineEvent::MessageReceivedDuplicateSkipped({:?})",data),
             EngineEvent::MessageRejected(connection,ref message) => write!(f,"EngineEvent::MessageRejected({:?},{:?})",connection,message),
             EngineEvent::ResendRequested(connection,ref range) => write!(f,"EngineEvent::ResendRequested({:?},{:?})",connection,range),
+            EngineEvent::ResendCompleted(connection,ref summary) => write!(f,"EngineEvent::ResendCompleted({:?},{:?})",connection,summary),
             EngineEvent::SequenceResetResetHasNoEffect(connection) => write!(f,"EngineEvent:SequenceResetResetHasNoEffect({:?})",connection),
             EngineEvent::SequenceResetResetInThePast(connection) => write!(f,"EngineEvent:SequenceResetResetInThePast({:?})",connection),
+            EngineEvent::MessageStoreError(connection,ref error) => write!(f,"EngineEvent::MessageStoreError({:?},{:?})",connection,error),
+            EngineEvent::OutboundValidationFailed(ref data) => write!(f,"EngineEvent::OutboundValidationFailed({:?})",data),
+            EngineEvent::DuplicateOrderRefused(connection,ref message) => write!(f,"EngineEvent::DuplicateOrderRefused({:?},{:?})",connection,message),
+            EngineEvent::DuplicateOrderSent(connection,ref cl_ord_id) => write!(f,"EngineEvent::DuplicateOrderSent({:?},{:?})",connection,cl_ord_id),
+            EngineEvent::InboundQuietPeriodExceeded(connection,quiet_period) => write!(f,"EngineEvent::InboundQuietPeriodExceeded({:?},{:?})",connection,quiet_period),
+            EngineEvent::InboundRateAnomaly(connection,ref anomaly) => write!(f,"EngineEvent::InboundRateAnomaly({:?},{:?})",connection,anomaly),
+            EngineEvent::LogonRefusedByCoordinator(connection,ref message) => write!(f,"EngineEvent::LogonRefusedByCoordinator({:?},{:?})",connection,message),
+            EngineEvent::SessionCoordinatorError(connection,ref error) => write!(f,"EngineEvent::SessionCoordinatorError({:?},{:?})",connection,error),
+            EngineEvent::TestRequestReceived(connection,ref test_req_id) => write!(f,"EngineEvent::TestRequestReceived({:?},{:?})",connection,test_req_id),
             EngineEvent::FatalError(description,ref error) => write!(f,"EngineEvent::FatalError({:?},{:?})",description,error),
+            EngineEvent::__Nonexhaustive => write!(f,"EngineEvent::__Nonexhaustive"),
+        }
+    }
+}
+
+//Snapshot of a connection's session state. See Engine::connection_info().
+#[derive(Clone,Debug)]
+pub struct ConnectionInfo {
+    pub fix_version: FIXVersion,
+    pub default_message_version: MessageVersion, //Message version used for outbound messages when not specified.
+    pub heartbeat_interval: Option<Duration>, //None until Logon completes or when heartbeats are disabled.
+    pub inbound_msg_seq_num: u64, //MsgSeqNum expected for the next received message.
+    pub outbound_msg_seq_num: u64, //MsgSeqNum to be used for the next sent message.
+    pub inbound_bytes_parsed: u64, //Number of bytes received and consumed by the parser since the connection started.
+    pub inbound_filler_bytes_skipped: u64, //Number of inbound_bytes_parsed that were filler. See Engine::set_filler_bytes().
+    pub inbound_acknowledged_msg_seq_num: u64, //Highest MsgSeqNum passed to Engine::acknowledge_inbound(). 0 when nothing has been acknowledged.
+    pub session_id: SessionID, //TargetCompID is empty for connections accepted by a listener until a Logon is received.
+}
+
+//Answer to Engine::order_entry_status().
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum OrderEntryStatus {
+    NotSent, //No NewOrderSingle, OrderCancelRequest, or OrderCancelReplaceRequest with the ClOrdID was recorded by the message store.
+    Sent, //Sent but no ExecutionReport or OrderCancelReject has been received for it.
+    Acknowledged, //At least one ExecutionReport or OrderCancelReject has been received for it.
+}
+
+//What is carried over to the new connection by Engine::rotate_session_identity(). Nothing is by
+//default.
+#[derive(Clone,Copy,Debug,Default)]
+pub struct SessionMigration {
+    pub user_data: bool, //Attach the old connection's user data to the new one unless the ConnectionBuilder has its own.
+    pub shadow_connections: bool, //Mirror to and from the new connection wherever the old one was mirrored. See Engine::set_shadow_connection().
+}
+
+//Settings used to detect a half-open connection sooner than the heartbeat interval allows. After
+//quiet_period passes without receiving any data, a TestRequest is sent. Another is sent every
+//probe_interval until data is received. If max_unanswered_probes go unanswered, the connection is
+//terminated with ConnectionTerminatedReason::ProbeNotRespondedError.
+#[derive(Clone,Copy,Debug)]
+pub struct ProbeSettings {
+    pub quiet_period: Duration,
+    pub probe_interval: Duration,
+    pub max_unanswered_probes: u32,
+}
+
+//Controls for servicing very large inbound ResendRequests so the link isn't flooded. When the
+//requested range is longer than max_range, everything but the newest max_range MsgSeqNums is gap
+//filled automatically and EngineEvent::ResendRequested only covers what's left. The response
+//passed to Engine::send_resend_response() is then sent chunk_size items at a time with
+//chunk_interval between each chunk. A chunk_size of 0 sends the whole response at once.
+#[derive(Clone,Copy,Debug)]
+pub struct ResendRequestLimits {
+    pub max_range: Option<u64>,
+    pub chunk_size: usize,
+    pub chunk_interval: Duration,
+}
+
+//Most connections a listener accepts from a single address within interval.
+#[derive(Clone,Copy,Debug)]
+pub struct ConnectionAttemptLimit {
+    pub max_attempts: u32,
+    pub interval: Duration,
+}
+
+//HeartBtInt, in seconds, a listener allows the initiator to ask for in its Logon. Both ends are
+//inclusive.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct HeartBtIntRange {
+    pub min: u32,
+    pub max: u32,
+}
+
+//Network level protection for a listener. Exposed ports get scanned constantly so connections
+//breaking these rules are closed immediately after being accepted, before anything is read from
+//them, and reported with EngineEvent::ConnectionRefused. Everything is allowed by default.
+#[derive(Clone,Debug,Default)]
+pub struct ListenerSettings {
+    pub allowed_addresses: Vec<IpAddr>, //When not empty, only connections from these addresses are accepted.
+    pub denied_addresses: Vec<IpAddr>, //Connections from these addresses are always refused.
+    pub connection_attempt_limit: Option<ConnectionAttemptLimit>, //Applied to each address separately.
+    pub max_unauthenticated_connections: Option<usize>, //Most connections from this listener that haven't been approved yet.
+    pub heart_bt_int_range: Option<HeartBtIntRange>, //Logons asking for a HeartBtInt outside of this range are refused with a Logout before the application sees them.
+}
+
+//How the EndSeqNo of a ResendRequest is chosen when a MsgSeqNum gap is detected. Counterparties
+//differ on which they prefer.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ResendRequestRange {
+    OpenEnded, //Use EndSeqNo=0 to ask for everything after BeginSeqNo. This is the default.
+    Exact, //Use EndSeqNo set to the last missing MsgSeqNum.
+}
+
+//What to do with messages that are still queued when the remote sends a Logout. Any message that
+//ends up not being sent is returned with EngineEvent::MessagesNotSent.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum LogoutResponsePolicy {
+    Flush, //Send every queued message and then respond with a Logout. This is the default.
+    Discard, //Drop every queued message and respond with a Logout immediately.
+    Delay(Duration), //Keep sending queued messages for up to Duration before responding with a Logout. Anything still queued is dropped.
+}
+
+//How a TestRequest received from the counterparty is answered. The engine normally replies right
+//away with a Heartbeat carrying the same TestReqID. Delaying or suppressing the reply is only meant
+//for test harnesses and chaos testing because the counterparty eventually disconnects a session
+//that doesn't answer.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum TestRequestResponse {
+    Immediate, //Reply as soon as the TestRequest is received. This is the default.
+    Delay(Duration), //Reply after Duration has passed.
+    Suppress, //Never reply.
+}
+
+//How TestRequests received from the counterparty are handled.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct TestRequestSettings {
+    pub response: TestRequestResponse,
+    pub report_received: bool, //Send EngineEvent::TestRequestReceived for every TestRequest received, whether or not it was answered.
+}
+
+impl TestRequestSettings {
+    pub fn new() -> TestRequestSettings {
+        TestRequestSettings {
+            response: TestRequestResponse::Immediate,
+            report_received: false,
         }
     }
 }
 
+impl Default for TestRequestSettings {
+    fn default() -> Self {
+        TestRequestSettings::new()
+    }
+}
+
+//What to do with application messages that were queued but never sent when a connection is lost
+//without logging out cleanly. Messages left over are matched to the next connection added with
+//the same SessionID. They are given new MsgSeqNums when they finally go out.
+//Which one is right depends on the message. A stale market data request is harmless to resend
+//while a stale order usually is not.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum OutboxPolicy {
+    Discard, //Return the messages with EngineEvent::MessagesNotSent. This is the default.
+    Resend, //Send the messages automatically once the next connection's session is established.
+    Hold, //Keep the messages until the next connection's session is established and then wait for Engine::release_held_messages() or Engine::discard_held_messages(). See EngineEvent::MessagesHeld.
+}
+
+//How received Heartbeat, TestRequest, ResendRequest, and SequenceReset messages are delivered to
+//the application. The engine always handles them itself first so most applications never need to
+//see them. Other administrative messages, like Logout and Reject, are always delivered as normal.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum AdminMessageDelivery {
+    MessageReceived, //Same as every other message using EngineEvent::MessageReceived. This is the default.
+    Separate, //Using EngineEvent::AdminMessageReceived so they're easy to tell apart.
+    Suppress, //Never delivered.
+}
+
+//Record of how a ResendRequest was serviced. Useful for auditing recovery after an incident.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ResendSummary {
+    pub requested: Option<Range<u64>>, //Range from the matching EngineEvent::ResendRequested (or the ResendRequest answered using a MessageStore) or None if a response was sent without one.
+    pub messages_resent: u64, //Number of ResendResponse::Message items replayed.
+    pub gap_fills_sent: u64, //Number of SequenceReset-GapFill messages sent for ResendResponse::Gap items.
+    pub gap_filled_msg_seq_nums: u64, //Number of MsgSeqNums skipped over by the gap fills.
+}
+
 pub enum ResendResponse {
     Message(Option<MessageVersion>,Box<FIXTMessage + Send>),
     Gap(Range<u64>),
@@ -164,17 +486,116 @@ fn to_socket_addr<A: ToSocketAddrs>(address: A) -> Option<SocketAddr> {
     }
 }
 
-pub struct Engine {
-    token_generator: Arc<Mutex<TokenGenerator>>,
-    tx: Sender<InternalEngineToThreadEvent>,
-    rx: Receiver<EngineEvent>,
-    poll: Poll,
-    thread_handle: Option<thread::JoinHandle<()>>,
+fn open_log_file(path: &Path) -> Result<File,io::Error> {
+    //Append so restarting a connection doesn't wipe out what was already collected.
+    OpenOptions::new().create(true).append(true).open(path)
 }
 
-impl Engine {
-    pub fn new(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
-               max_message_size: u64) -> Result<Engine,io::Error> {
+//Collects the options used to create an Engine. Engine::new() is a shortcut for the common case.
+pub struct EngineBuilder {
+    message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,
+    max_message_size: u64,
+    thread_name: Option<String>,
+    thread_stack_size: Option<usize>,
+    middleware: MiddlewareChain,
+    conflate_market_data: bool,
+    admin_message_delivery: AdminMessageDelivery,
+    session_provisioner: Option<Box<SessionProvisioner>>,
+    reference_data_cache: Option<ReferenceDataCache>,
+    clock: Arc<Clock>,
+}
+
+impl EngineBuilder {
+    pub fn new<D: Into<Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>>>(message_dictionary: D,
+                                                                                   max_message_size: u64) -> EngineBuilder {
+        //The dictionary can be shared, such as with Parser::shared_dictionary(), so several
+        //engines and parsers don't each need their own copy.
+        EngineBuilder {
+            message_dictionary: message_dictionary.into(),
+            max_message_size: max_message_size,
+            thread_name: None,
+            thread_stack_size: None,
+            middleware: MiddlewareChain::new(),
+            conflate_market_data: false,
+            admin_message_delivery: AdminMessageDelivery::MessageReceived,
+            session_provisioner: None,
+            reference_data_cache: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    pub fn thread_name(mut self,thread_name: &str) -> EngineBuilder {
+        //Name of the internal thread that does all of the networking. Useful when profiling.
+        self.thread_name = Some(thread_name.to_string());
+        self
+    }
+
+    pub fn thread_stack_size(mut self,thread_stack_size: usize) -> EngineBuilder {
+        self.thread_stack_size = Some(thread_stack_size);
+        self
+    }
+
+    pub fn inbound_middleware<T: InboundMiddleware + 'static>(mut self,middleware: T) -> EngineBuilder {
+        //Add middleware to the end of the chain every received message passes through before
+        //being delivered. See InboundMiddleware.
+        self.middleware.push(middleware);
+        self
+    }
+
+    pub fn conflate_market_data(mut self,conflate_market_data: bool) -> EngineBuilder {
+        //When enabled and events are backing up because Engine::poll() isn't being called often
+        //enough, a waiting MarketDataSnapshotFullRefresh is replaced by a newer one for the same
+        //connection, MDReqID, and Symbol. The newer one takes the place of the older one at the back
+        //of the queue. Incremental refreshes are never conflated because each one depends on the
+        //last. See Engine::conflated_count(). Disabled by default.
+        self.conflate_market_data = conflate_market_data;
+        self
+    }
+
+    pub fn admin_message_delivery(mut self,admin_message_delivery: AdminMessageDelivery) -> EngineBuilder {
+        //Choose how session plumbing messages are delivered. See AdminMessageDelivery. Defaults to
+        //AdminMessageDelivery::MessageReceived.
+        self.admin_message_delivery = admin_message_delivery;
+        self
+    }
+
+    pub fn session_provisioner<T: SessionProvisioner + 'static>(mut self,session_provisioner: T) -> EngineBuilder {
+        //Decide whether to accept each Logon received by a listener, and how to configure the
+        //session, without handling EngineEvent::ConnectionLoggingOn by hand. See
+        //SessionProvisioner.
+        self.session_provisioner = Some(Box::new(session_provisioner));
+        self
+    }
+
+    pub fn reference_data_cache(mut self,reference_data_cache: ReferenceDataCache) -> EngineBuilder {
+        //Received SecurityDefinition and SecurityList messages are added to the cache as they're
+        //returned by Engine::poll(). Any other received message whose Symbol is in the cache is
+        //returned as EngineEvent::MessageReceivedWithInstrument instead of
+        //EngineEvent::MessageReceived so the tick size, contract multiplier, etc. are on hand
+        //without another lookup. Keep a clone of the cache to share it with the rest of the
+        //application.
+        self.reference_data_cache = Some(reference_data_cache);
+        self
+    }
+
+    pub fn clock<T: Clock + 'static>(mut self,clock: T) -> EngineBuilder {
+        //Measure every timeout and message time-to-live with clock instead of the system's clock.
+        //Mostly useful for testing with SimulatedClock. Defaults to SystemClock.
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    pub fn validate_configuration(&self,connections: &[ConnectionConfiguration]) -> ConfigurationReport {
+        //Check everything that can be checked before going live instead of finding out at Logon:
+        //the message dictionary has every administrative and expected message type, message
+        //stores and logs can be written to, schedules have sane time zones, and the clock follows
+        //real time. Nothing is sent over the network. Directories and log files that don't exist
+        //yet are created just like they would be when the connection is added.
+        //TODO: Check certificates and keys once connections can use a TLS transport.
+        validate_configuration(&self.message_dictionary,&*self.clock,connections)
+    }
+
+    pub fn build(self) -> Result<Engine,io::Error> {
         let engine_poll = try!(Poll::new());
         let (thread_to_engine_tx,thread_to_engine_rx) = channel::<EngineEvent>();
         try!(engine_poll.register(&thread_to_engine_rx,ENGINE_EVENT_TOKEN,Ready::readable(),PollOpt::level()));
@@ -184,29 +605,366 @@ impl Engine {
         try!(poll.register(&engine_to_thread_rx,INTERNAL_ENGINE_EVENT_TOKEN,Ready::readable(),PollOpt::level()));
 
         let token_generator = Arc::new(Mutex::new(TokenGenerator::new(BASE_CONNECTION_TOKEN.0,Some(CONNECTION_COUNT_MAX - BASE_CONNECTION_TOKEN.0))));
+        let health_checks: HealthCheckRegistry = Arc::new(Mutex::new(HashMap::new()));
+
+        let mut thread_builder = thread::Builder::new();
+        if let Some(thread_name) = self.thread_name {
+            thread_builder = thread_builder.name(thread_name);
+        }
+        if let Some(thread_stack_size) = self.thread_stack_size {
+            thread_builder = thread_builder.stack_size(thread_stack_size);
+        }
+
+        let message_dictionary = self.message_dictionary;
+        let max_message_size = self.max_message_size;
+        let middleware = self.middleware;
+        let thread_token_generator = token_generator.clone();
+        let thread_health_checks = health_checks.clone();
+        let thread_clock = self.clock.clone();
+        let thread_handle = try!(thread_builder.spawn(move || {
+            internal_engine_thread(poll,thread_token_generator,thread_to_engine_tx,engine_to_thread_rx,message_dictionary,max_message_size,middleware,thread_health_checks,thread_clock);
+        }));
 
         Ok(Engine {
-            token_generator: token_generator.clone(),
+            token_generator: token_generator,
+            health_checks: health_checks,
+            clock: self.clock,
             tx: engine_to_thread_tx,
             rx: thread_to_engine_rx,
+            conflate_market_data: self.conflate_market_data,
+            admin_message_delivery: self.admin_message_delivery,
+            session_provisioner: self.session_provisioner,
+            reference_data_cache: self.reference_data_cache,
+            pending_events: VecDeque::new(),
+            pending_event_offset: 0,
+            pending_snapshots: HashMap::new(),
+            conflated_count: 0,
+            user_data: HashMap::new(),
+            shadow_connections: HashMap::new(),
+            terminated_connections: Vec::new(),
             poll: engine_poll,
-            thread_handle: Some(thread::spawn(move || {
-                internal_engine_thread(poll,token_generator,thread_to_engine_tx,engine_to_thread_rx,message_dictionary,max_message_size);
-            })),
+            thread_handle: Some(thread_handle),
         })
     }
+}
+
+//Collects the options used to create a connection with Engine::add_connection_with_builder().
+//Options left unset use the same defaults as Engine::add_connection().
+#[derive(Clone)]
+pub struct ConnectionBuilder {
+    fix_version: FIXVersion,
+    default_message_version: MessageVersion,
+    sender_comp_id: Vec<u8>,
+    target_comp_id: Vec<u8>,
+    session_qualifier: Option<Vec<u8>>,
+    probe_settings: Option<ProbeSettings>,
+    inbound_quiet_period: Option<Duration>,
+    inbound_rate_anomaly: Option<RateAnomalySettings>,
+    resend_request_limits: Option<ResendRequestLimits>,
+    resend_request_range: Option<ResendRequestRange>,
+    resend_request_coalesce_interval: Option<Duration>,
+    logout_response_policy: Option<LogoutResponsePolicy>,
+    outbox_policy: Option<OutboxPolicy>,
+    test_request_settings: Option<TestRequestSettings>,
+    timestamp_precision: Option<TimestampPrecision>,
+    validation_settings: Option<ValidationSettings>,
+    outbound_validation: Option<OutboundValidation>,
+    duplicate_order_guard: Option<DuplicateOrderGuard>,
+    third_party_routing_settings: Option<ThirdPartyRoutingSettings>,
+    skip_duplicate_parsing: bool,
+    filler_bytes: Option<Vec<u8>>,
+    symbol_mapper: Option<Arc<SymbolMapper>>,
+    message_signer: Option<Arc<MessageSigner>>,
+    message_statistics: Option<u64>,
+    session_coordinator: Option<Arc<SessionCoordinator>>,
+    quarantine_log: Option<PathBuf>,
+    message_logs: Vec<(PathBuf,MessageLogSettings)>,
+    message_store: Option<PathBuf>,
+    inbound_checkpoint: Option<u64>,
+    user_data: Option<Arc<Any + Send + Sync>>,
+}
+
+impl ConnectionBuilder {
+    pub fn new(fix_version: FIXVersion,
+               default_message_version: MessageVersion,
+               sender_comp_id: &[u8],
+               target_comp_id: &[u8]) -> ConnectionBuilder {
+        ConnectionBuilder {
+            fix_version: fix_version,
+            default_message_version: default_message_version,
+            sender_comp_id: sender_comp_id.to_vec(),
+            target_comp_id: target_comp_id.to_vec(),
+            session_qualifier: None,
+            probe_settings: None,
+            inbound_quiet_period: None,
+            inbound_rate_anomaly: None,
+            resend_request_limits: None,
+            resend_request_range: None,
+            resend_request_coalesce_interval: None,
+            logout_response_policy: None,
+            outbox_policy: None,
+            test_request_settings: None,
+            timestamp_precision: None,
+            validation_settings: None,
+            outbound_validation: None,
+            duplicate_order_guard: None,
+            third_party_routing_settings: None,
+            skip_duplicate_parsing: false,
+            filler_bytes: None,
+            symbol_mapper: None,
+            message_signer: None,
+            message_statistics: None,
+            session_coordinator: None,
+            quarantine_log: None,
+            message_logs: Vec::new(),
+            message_store: None,
+            inbound_checkpoint: None,
+            user_data: None,
+        }
+    }
+
+    pub fn fix_version(&self) -> FIXVersion {
+        self.fix_version
+    }
+
+    pub fn default_message_version(&self) -> MessageVersion {
+        self.default_message_version
+    }
+
+    pub fn session_id(&self) -> SessionID {
+        SessionID {
+            fix_version: self.fix_version,
+            sender_comp_id: self.sender_comp_id.clone(),
+            target_comp_id: self.target_comp_id.clone(),
+            session_qualifier: self.session_qualifier.clone(),
+        }
+    }
+
+    pub fn message_store_path(&self) -> Option<&Path> {
+        self.message_store.as_ref().map(|directory| directory.as_path())
+    }
+
+    pub fn quarantine_log_path(&self) -> Option<&Path> {
+        self.quarantine_log.as_ref().map(|path| path.as_path())
+    }
+
+    pub fn message_log_paths(&self) -> Vec<&Path> {
+        self.message_logs.iter().map(|&(ref path,_)| path.as_path()).collect()
+    }
+
+    pub fn session_qualifier(mut self,session_qualifier: &[u8]) -> ConnectionBuilder {
+        //Only needed to tell apart several sessions with the same FIX version and CompIDs. See
+        //SessionID.
+        self.session_qualifier = Some(session_qualifier.to_vec());
+        self
+    }
+
+    pub fn probe_settings(mut self,probe_settings: ProbeSettings) -> ConnectionBuilder {
+        self.probe_settings = Some(probe_settings);
+        self
+    }
+
+    pub fn inbound_quiet_period(mut self,inbound_quiet_period: Duration) -> ConnectionBuilder {
+        self.inbound_quiet_period = Some(inbound_quiet_period);
+        self
+    }
+
+    pub fn inbound_rate_anomaly(mut self,rate_anomaly_settings: RateAnomalySettings) -> ConnectionBuilder {
+        self.inbound_rate_anomaly = Some(rate_anomaly_settings);
+        self
+    }
+
+    pub fn resend_request_limits(mut self,resend_request_limits: ResendRequestLimits) -> ConnectionBuilder {
+        self.resend_request_limits = Some(resend_request_limits);
+        self
+    }
+
+    pub fn resend_request_range(mut self,resend_request_range: ResendRequestRange) -> ConnectionBuilder {
+        self.resend_request_range = Some(resend_request_range);
+        self
+    }
+
+    pub fn resend_request_coalesce_interval(mut self,coalesce_interval: Duration) -> ConnectionBuilder {
+        self.resend_request_coalesce_interval = Some(coalesce_interval);
+        self
+    }
+
+    pub fn logout_response_policy(mut self,logout_response_policy: LogoutResponsePolicy) -> ConnectionBuilder {
+        self.logout_response_policy = Some(logout_response_policy);
+        self
+    }
+
+    pub fn outbox_policy(mut self,outbox_policy: OutboxPolicy) -> ConnectionBuilder {
+        self.outbox_policy = Some(outbox_policy);
+        self
+    }
+
+    pub fn test_request_settings(mut self,test_request_settings: TestRequestSettings) -> ConnectionBuilder {
+        self.test_request_settings = Some(test_request_settings);
+        self
+    }
+
+    pub fn timestamp_precision(mut self,timestamp_precision: TimestampPrecision) -> ConnectionBuilder {
+        self.timestamp_precision = Some(timestamp_precision);
+        self
+    }
+
+    pub fn validation_settings(mut self,validation_settings: ValidationSettings) -> ConnectionBuilder {
+        self.validation_settings = Some(validation_settings);
+        self
+    }
+
+    pub fn outbound_validation(mut self,outbound_validation: OutboundValidation) -> ConnectionBuilder {
+        self.outbound_validation = Some(outbound_validation);
+        self
+    }
+
+    pub fn duplicate_order_guard(mut self,duplicate_order_guard: DuplicateOrderGuard) -> ConnectionBuilder {
+        self.duplicate_order_guard = Some(duplicate_order_guard);
+        self
+    }
+
+    pub fn third_party_routing_settings(mut self,third_party_routing_settings: ThirdPartyRoutingSettings) -> ConnectionBuilder {
+        self.third_party_routing_settings = Some(third_party_routing_settings);
+        self
+    }
+
+    pub fn skip_duplicate_parsing(mut self,skip_duplicate_parsing: bool) -> ConnectionBuilder {
+        self.skip_duplicate_parsing = skip_duplicate_parsing;
+        self
+    }
+
+    pub fn filler_bytes(mut self,filler_bytes: &[u8]) -> ConnectionBuilder {
+        self.filler_bytes = Some(filler_bytes.to_vec());
+        self
+    }
+
+    pub fn symbol_mapper(mut self,symbol_mapper: Arc<SymbolMapper>) -> ConnectionBuilder {
+        //See Engine::set_symbol_mapper().
+        self.symbol_mapper = Some(symbol_mapper);
+        self
+    }
+
+    pub fn message_signer(mut self,message_signer: Arc<MessageSigner>) -> ConnectionBuilder {
+        //See Engine::set_message_signer().
+        self.message_signer = Some(message_signer);
+        self
+    }
+
+    pub fn session_coordinator(mut self,session_coordinator: Arc<SessionCoordinator>) -> ConnectionBuilder {
+        //See Engine::set_session_coordinator().
+        self.session_coordinator = Some(session_coordinator);
+        self
+    }
+
+    pub fn message_statistics(mut self,sample_interval: u64) -> ConnectionBuilder {
+        //See Engine::set_message_statistics().
+        self.message_statistics = Some(sample_interval);
+        self
+    }
+
+    pub fn quarantine_log<P: AsRef<Path>>(mut self,path: P) -> ConnectionBuilder {
+        self.quarantine_log = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn message_log<P: AsRef<Path>>(mut self,path: P,settings: MessageLogSettings) -> ConnectionBuilder {
+        //Can be called more than once to write to several files, each with its own format.
+        self.message_logs.push((path.as_ref().to_path_buf(),settings));
+        self
+    }
+
+    pub fn message_store<P: AsRef<Path>>(mut self,directory: P) -> ConnectionBuilder {
+        //Persist the session to a FileMessageStore in directory. See Engine::set_message_store().
+        self.message_store = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn inbound_checkpoint(mut self,acknowledged_msg_seq_num: u64) -> ConnectionBuilder {
+        //Resume a session from the last inbound MsgSeqNum the application finished processing
+        //before a restart. Usually ConnectionInfo::inbound_acknowledged_msg_seq_num saved from the
+        //previous run. The next expected inbound MsgSeqNum becomes acknowledged_msg_seq_num + 1 so
+        //everything that was never acknowledged is fetched again using a ResendRequest after Logon.
+        self.inbound_checkpoint = Some(acknowledged_msg_seq_num);
+        self
+    }
+
+    pub fn user_data(mut self,user_data: Arc<Any + Send + Sync>) -> ConnectionBuilder {
+        self.user_data = Some(user_data);
+        self
+    }
+}
+
+pub struct Engine {
+    token_generator: Arc<Mutex<TokenGenerator>>,
+    health_checks: HealthCheckRegistry,
+    clock: Arc<Clock>,
+    tx: Sender<InternalEngineToThreadEvent>,
+    rx: Receiver<EngineEvent>,
+    conflate_market_data: bool,
+    admin_message_delivery: AdminMessageDelivery,
+    session_provisioner: Option<Box<SessionProvisioner>>,
+    reference_data_cache: Option<ReferenceDataCache>,
+    pending_events: VecDeque<Option<EngineEvent>>, //Events already taken from rx while conflating market data. None where a snapshot was replaced by a newer one.
+    pending_event_offset: u64, //Position of the front of pending_events counting every event ever pushed.
+    pending_snapshots: HashMap<(Connection,Vec<u8>,Vec<u8>),u64>, //Position of the newest pending snapshot for each connection, MDReqID, and Symbol.
+    conflated_count: u64,
+    user_data: HashMap<Connection,Arc<Any + Send + Sync>>,
+    shadow_connections: HashMap<Connection,Connection>, //Connection to the connection its application messages are mirrored to.
+    terminated_connections: Vec<Connection>, //User data is kept until the poll() after the connection's termination event.
+    poll: Poll,
+    thread_handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Engine {
+    pub fn new<D: Into<Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>>>(message_dictionary: D,
+                                                                                   max_message_size: u64) -> Result<Engine,io::Error> {
+        EngineBuilder::new(message_dictionary,max_message_size).build()
+    }
 
     pub fn add_connection<A: ToSocketAddrs>(&mut self,
                                             fix_version: FIXVersion,
-                                            mut default_message_version: MessageVersion,
+                                            default_message_version: MessageVersion,
                                             sender_comp_id: &[u8],
                                             target_comp_id: &[u8],
                                             address: A) -> Option<Connection> {
+        self.add_connection_with_builder(ConnectionBuilder::new(fix_version,default_message_version,sender_comp_id,target_comp_id),address)
+    }
+
+    pub fn add_connection_with_builder<A: ToSocketAddrs>(&mut self,builder: ConnectionBuilder,address: A) -> Option<Connection> {
+        let fix_version = builder.fix_version;
+        let mut default_message_version = builder.default_message_version;
+        let session_id = builder.session_id();
+
         let address = match to_socket_addr(address) {
             Some(address) => address,
             None => return None,
         };
 
+        //Open the quarantine log, message logs, and message store up front so a bad path doesn't
+        //leave behind a connection that was only partially setup.
+        let quarantine_log = match builder.quarantine_log {
+            Some(ref path) => match open_log_file(path) {
+                Ok(file) => Some(file),
+                Err(_) => return None,
+            },
+            None => None,
+        };
+        let mut message_logs = Vec::new();
+        for &(ref path,settings) in &builder.message_logs {
+            match open_log_file(path) {
+                Ok(file) => message_logs.push(MessageLog::new(file,settings)),
+                Err(_) => return None,
+            }
+        }
+        let message_store = match builder.message_store {
+            Some(ref directory) => match FileMessageStore::open(directory) {
+                Ok(message_store) => Some(message_store),
+                Err(_) => return None,
+            },
+            None => None,
+        };
+
         //Force older FIX versions that don't support message versioning to use their respective
         //message versions.
         default_message_version = match fix_version {
@@ -224,14 +982,143 @@ impl Engine {
             None => return None,
         };
 
+        let connection = Connection {
+            id: token.0,
+            generation: self.token_generator.lock().unwrap().generation(token).unwrap(),
+        };
+
         //Tell thread to setup this connection by connecting a socket and logging on.
-        self.tx.send(InternalEngineToThreadEvent::NewConnection(token.clone(),fix_version,default_message_version,sender_comp_id.to_vec(),target_comp_id.to_vec(),address)).unwrap();
+        self.tx.send(InternalEngineToThreadEvent::NewConnection(connection,default_message_version,session_id,address)).unwrap();
+
+        //Apply any per-connection options. These are handled by the thread in order so they take
+        //effect before anything is received on the connection.
+        if let Some(probe_settings) = builder.probe_settings {
+            self.set_probe_settings(connection,probe_settings);
+        }
+        if let Some(inbound_quiet_period) = builder.inbound_quiet_period {
+            self.set_inbound_quiet_period(connection,inbound_quiet_period);
+        }
+        if let Some(rate_anomaly_settings) = builder.inbound_rate_anomaly {
+            self.set_inbound_rate_anomaly(connection,rate_anomaly_settings);
+        }
+        if let Some(resend_request_limits) = builder.resend_request_limits {
+            self.set_resend_request_limits(connection,resend_request_limits);
+        }
+        if let Some(resend_request_range) = builder.resend_request_range {
+            self.set_resend_request_range(connection,resend_request_range);
+        }
+        if let Some(coalesce_interval) = builder.resend_request_coalesce_interval {
+            self.set_resend_request_coalesce_interval(connection,coalesce_interval);
+        }
+        if let Some(logout_response_policy) = builder.logout_response_policy {
+            self.set_logout_response_policy(connection,logout_response_policy);
+        }
+        if let Some(outbox_policy) = builder.outbox_policy {
+            self.set_outbox_policy(connection,outbox_policy);
+        }
+        if let Some(test_request_settings) = builder.test_request_settings {
+            self.set_test_request_settings(connection,test_request_settings);
+        }
+        if let Some(timestamp_precision) = builder.timestamp_precision {
+            self.set_timestamp_precision(connection,timestamp_precision);
+        }
+        if let Some(validation_settings) = builder.validation_settings {
+            self.set_validation_settings(connection,validation_settings);
+        }
+        if let Some(outbound_validation) = builder.outbound_validation {
+            self.set_outbound_validation(connection,outbound_validation);
+        }
+        if let Some(duplicate_order_guard) = builder.duplicate_order_guard {
+            self.set_duplicate_order_guard(connection,Some(duplicate_order_guard));
+        }
+        if let Some(third_party_routing_settings) = builder.third_party_routing_settings {
+            self.set_third_party_routing_settings(connection,third_party_routing_settings);
+        }
+        if builder.skip_duplicate_parsing {
+            self.set_skip_duplicate_parsing(connection,true);
+        }
+        if let Some(ref filler_bytes) = builder.filler_bytes {
+            self.set_filler_bytes(connection,filler_bytes);
+        }
+        if let Some(ref symbol_mapper) = builder.symbol_mapper {
+            self.set_symbol_mapper(connection,Some(symbol_mapper.clone()));
+        }
+        if let Some(ref message_signer) = builder.message_signer {
+            self.set_message_signer(connection,Some(message_signer.clone()));
+        }
+        if let Some(sample_interval) = builder.message_statistics {
+            self.set_message_statistics(connection,Some(sample_interval));
+        }
+        if let Some(ref session_coordinator) = builder.session_coordinator {
+            self.set_session_coordinator(connection,Some(session_coordinator.clone()));
+        }
+        if quarantine_log.is_some() {
+            self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetQuarantineLog(token,quarantine_log));
+        }
+        for message_log in message_logs {
+            self.send_connection_event(connection,|token| InternalEngineToThreadEvent::AddMessageLog(token,message_log));
+        }
+        if let Some(message_store) = message_store {
+            self.set_message_store(connection,Box::new(message_store));
+        }
+        if let Some(acknowledged_msg_seq_num) = builder.inbound_checkpoint {
+            self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetInboundCheckpoint(token,acknowledged_msg_seq_num));
+        }
+        if let Some(user_data) = builder.user_data {
+            self.set_user_data(connection,user_data);
+        }
 
-        let connection = Connection(token.0);
         Some(connection)
     }
 
+    pub fn rotate_session_identity<A: ToSocketAddrs>(&mut self,connection: Connection,builder: ConnectionBuilder,address: A,migration: SessionMigration) -> Option<Connection> {
+        //Replace connection with a new session without restarting, usually because the
+        //counterparty migrated to a different SenderCompID or TargetCompID. The new session is
+        //created from builder exactly like add_connection_with_builder() and then the old one is
+        //logged out like logout(). Both run side by side until the old one terminates. Point
+        //ConnectionBuilder::message_store() at a new directory so the new identity starts with
+        //fresh MsgSeqNums. Returns None, leaving the old connection alone, if the new one couldn't
+        //be created.
+        //
+        //Application state like OrderTracker and MarketDataSubscriptions isn't known to the engine.
+        //Keep passing messages from the new connection to the same OrderTracker to keep tracking
+        //open orders and use MarketDataSubscriptions::resubscribe() to renew subscriptions because
+        //the counterparty won't carry them over to the new session.
+        let user_data = if migration.user_data && builder.user_data.is_none() {
+            self.user_data.get(&connection).cloned()
+        }
+        else {
+            None
+        };
+
+        let new_connection = match self.add_connection_with_builder(builder,address) {
+            Some(new_connection) => new_connection,
+            None => return None,
+        };
+        self.logout(connection);
+
+        if let Some(user_data) = user_data {
+            self.set_user_data(new_connection,user_data);
+        }
+        if migration.shadow_connections {
+            if let Some(shadow_connection) = self.shadow_connections.remove(&connection) {
+                self.shadow_connections.insert(new_connection,shadow_connection);
+            }
+            for shadow_connection in self.shadow_connections.values_mut() {
+                if *shadow_connection == connection {
+                    *shadow_connection = new_connection;
+                }
+            }
+        }
+
+        Some(new_connection)
+    }
+
     pub fn add_listener<A: ToSocketAddrs>(&mut self,sender_comp_id: &[u8],address: A) -> Result<Option<Listener>,io::Error> {
+        self.add_listener_with_settings(sender_comp_id,address,ListenerSettings::default())
+    }
+
+    pub fn add_listener_with_settings<A: ToSocketAddrs>(&mut self,sender_comp_id: &[u8],address: A,settings: ListenerSettings) -> Result<Option<Listener>,io::Error> {
         let address = match to_socket_addr(address) {
             Some(address) => address,
             None => return Ok(None),
@@ -243,7 +1130,7 @@ impl Engine {
             None => return Ok(None),
         };
 
-        self.tx.send(InternalEngineToThreadEvent::NewListener(token.clone(),sender_comp_id.to_vec(),listener)).unwrap();
+        self.tx.send(InternalEngineToThreadEvent::NewListener(token.clone(),sender_comp_id.to_vec(),listener,settings)).unwrap();
 
         let listener = Listener(token.0);
         Ok(Some(listener))
@@ -259,7 +1146,38 @@ impl Engine {
     }
 
     pub fn send_message_box_with_message_version<MV: Into<Option<MessageVersion>>>(&mut self,connection: Connection,message_version: MV,message: Box<FIXTMessage + Send>) {
-        self.tx.send(InternalEngineToThreadEvent::SendMessage(Token(connection.0),message_version.into(),message)).unwrap();
+        let message_version = message_version.into();
+        self.mirror_to_shadow_connection(connection,message_version,&*message,None);
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SendMessage(token,message_version,message,None));
+    }
+
+    pub fn send_message_with_time_to_live<T: 'static + FIXTMessage + Send>(&mut self,connection: Connection,message: T,time_to_live: Duration) {
+        self.send_message_box_with_time_to_live(connection,None,Box::new(message),time_to_live);
+    }
+
+    pub fn send_message_box_with_time_to_live<MV: Into<Option<MessageVersion>>>(&mut self,connection: Connection,message_version: MV,message: Box<FIXTMessage + Send>,time_to_live: Duration) {
+        //Same as send_message_box_with_message_version() except the message is dropped instead of
+        //sent if it can't be written to the socket within time_to_live. For example, because
+        //sending is paused or the remote isn't keeping up. Dropped messages are reported with
+        //EngineEvent::MessagesExpired. Useful for orders that are only valid for a short time.
+        let message_version = message_version.into();
+        let expires_at = self.clock.now() + time_to_live;
+        self.mirror_to_shadow_connection(connection,message_version,&*message,Some(expires_at));
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SendMessage(token,message_version,message,Some(expires_at)));
+    }
+
+    fn mirror_to_shadow_connection(&self,connection: Connection,message_version: Option<MessageVersion>,message: &FIXTMessage,expires_at: Option<Instant>) {
+        let shadow_connection = match self.shadow_connections.get(&connection) {
+            Some(shadow_connection) => *shadow_connection,
+            None => return,
+        };
+        if administrative_msg_types().contains(&message.msg_type()) {
+            return;
+        }
+
+        let mut shadow_message = message.clone_into_box();
+        shadow_message.set_is_test_message(true);
+        self.send_connection_event(shadow_connection,|token| InternalEngineToThreadEvent::SendMessage(token,message_version,shadow_message,expires_at));
     }
 
     pub fn send_resend_response(&mut self,connection: Connection,response: Vec<ResendResponse>) {
@@ -289,39 +1207,466 @@ impl Engine {
         }
 
         //Pass response on to actually be sent.
-        self.tx.send(InternalEngineToThreadEvent::ResendMessages(Token(connection.0),response)).unwrap();
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::ResendMessages(token,response));
+    }
+
+    pub fn pause_sending(&mut self,connection: Connection) {
+        //Application messages sent after this call are held until resume_sending() is called.
+        //Administrative messages (Heartbeat, TestRequest, etc) continue to be sent so the session
+        //stays alive.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::PauseSending(token));
+    }
+
+    pub fn resume_sending(&mut self,connection: Connection) {
+        //Send all held application messages in the order they were given and stop holding new
+        //ones.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::ResumeSending(token));
+    }
+
+    pub fn warm_up<MV: Into<Option<MessageVersion>>>(&mut self,connection: Connection,message_version: MV,message: Box<FIXTMessage + Send>) {
+        //Pre-allocate buffers and exercise the serialize and parse paths using a template message
+        //so the first real message doesn't pay for it. The message is never sent. Typically called
+        //before the market opens with a message similar to the ones that will be sent.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::WarmUp(token,message_version.into(),message));
+    }
+
+    pub fn set_probe_settings<PS: Into<Option<ProbeSettings>>>(&mut self,connection: Connection,probe_settings: PS) {
+        //Enable (or disable when None) half-open connection detection using TestRequest probes.
+        //See ProbeSettings. Disabled by default.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetProbeSettings(token,probe_settings.into()));
+    }
+
+    pub fn set_inbound_quiet_period<D: Into<Option<Duration>>>(&mut self,connection: Connection,inbound_quiet_period: D) {
+        //Send EngineEvent::InboundQuietPeriodExceeded when an established session receives nothing
+        //for this long. Meant as an early warning of a stalling counterparty so it should be
+        //shorter than the HeartBtInt, after which a TestRequest is sent and the connection is
+        //eventually dropped. Reported once per silence. Disabled (None) by default.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetInboundQuietPeriod(token,inbound_quiet_period.into()));
+    }
+
+    pub fn set_inbound_rate_anomaly<RAS: Into<Option<RateAnomalySettings>>>(&mut self,connection: Connection,rate_anomaly_settings: RAS) {
+        //Watch the rate of every MsgType received and send EngineEvent::InboundRateAnomaly when one
+        //becomes a storm or a drought compared to its own baseline, and again when it's back to
+        //normal. Changing the settings starts every baseline over. Disabled (None) by default. See
+        //RateAnomalySettings.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetInboundRateAnomaly(token,rate_anomaly_settings.into()));
+    }
+
+    pub fn set_resend_request_limits<RRL: Into<Option<ResendRequestLimits>>>(&mut self,connection: Connection,resend_request_limits: RRL) {
+        //Enable (or disable when None) capping and chunking of inbound ResendRequests. See
+        //ResendRequestLimits. Disabled by default.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetResendRequestLimits(token,resend_request_limits.into()));
+    }
+
+    pub fn set_resend_request_range(&mut self,connection: Connection,resend_request_range: ResendRequestRange) {
+        //Choose whether ResendRequests sent because of a MsgSeqNum gap are open-ended or ask for
+        //the exact range missing. See ResendRequestRange. Defaults to
+        //ResendRequestRange::OpenEnded.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetResendRequestRange(token,resend_request_range));
+    }
+
+    pub fn set_resend_request_coalesce_interval<D: Into<Option<Duration>>>(&mut self,connection: Connection,coalesce_interval: D) {
+        //When set, MsgSeqNum gaps detected within coalesce_interval of each other are merged into
+        //a single ResendRequest instead of one per gap. Disabled (None) by default. Ranges that are
+        //already covered by an outstanding ResendRequest are never requested again either way.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetResendRequestCoalesceInterval(token,coalesce_interval.into()));
+    }
+
+    pub fn set_logout_response_policy(&mut self,connection: Connection,logout_response_policy: LogoutResponsePolicy) {
+        //Choose what happens to queued messages when the remote sends a Logout. See
+        //LogoutResponsePolicy. Defaults to LogoutResponsePolicy::Flush.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetLogoutResponsePolicy(token,logout_response_policy));
+    }
+
+    pub fn set_outbox_policy(&mut self,connection: Connection,outbox_policy: OutboxPolicy) {
+        //Choose what happens to unsent application messages when the connection is lost. See
+        //OutboxPolicy. Defaults to OutboxPolicy::Discard.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetOutboxPolicy(token,outbox_policy));
+    }
+
+    pub fn release_held_messages(&mut self,connection: Connection) {
+        //Send the messages reported with EngineEvent::MessagesHeld as if they were just passed to
+        //send_message().
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::ReleaseHeldMessages(token));
+    }
+
+    pub fn discard_held_messages(&mut self,connection: Connection) {
+        //Drop the messages reported with EngineEvent::MessagesHeld. They are returned with
+        //EngineEvent::MessagesNotSent.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::DiscardHeldMessages(token));
+    }
+
+    pub fn set_test_request_settings(&mut self,connection: Connection,test_request_settings: TestRequestSettings) {
+        //Change how TestRequests received from the counterparty are answered and whether the
+        //application is told about them. Defaults to replying immediately without an event.
+        //Replies already waiting out a delay are still sent when it's up.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetTestRequestSettings(token,test_request_settings));
+    }
+
+    pub fn set_timestamp_precision(&mut self,connection: Connection,timestamp_precision: TimestampPrecision) {
+        //Number of fractional second digits used in every outbound UTCTimestamp field, such as
+        //SendingTime, OrigSendingTime, and TransactTime. Some counterparties reject anything other
+        //than exactly milliseconds while others want more. Defaults to
+        //TimestampPrecision::Milliseconds. Inbound timestamps are accepted with any precision.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetTimestampPrecision(token,timestamp_precision));
+    }
+
+    pub fn set_validation_settings(&mut self,connection: Connection,validation_settings: ValidationSettings) {
+        //How strictly received messages are checked. Defaults to ValidationProfile::Strict. Use
+        //ValidationProfile::settings() to start from one of the other profiles.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetValidationSettings(token,validation_settings));
+    }
+
+    pub fn set_outbound_validation(&mut self,connection: Connection,outbound_validation: OutboundValidation) {
+        //Check messages against the dictionary before they are queued to be sent. Invalid
+        //messages are returned with EngineEvent::OutboundValidationFailed instead of letting the
+        //counterparty reject them. Defaults to OutboundValidation::Disabled.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetOutboundValidation(token,outbound_validation));
+    }
+
+    pub fn set_duplicate_order_guard(&mut self,connection: Connection,duplicate_order_guard: Option<DuplicateOrderGuard>) {
+        //Refuse or flag NewOrderSingles that reuse a ClOrdID sent within the guard's window. When
+        //the connection has a MessageStore, orders sent before a restart are recovered from it.
+        //None, the default, turns the guard off. See DuplicateOrderGuard.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetDuplicateOrderGuard(token,duplicate_order_guard));
+    }
+
+    pub fn set_third_party_routing_settings(&mut self,connection: Connection,third_party_routing_settings: ThirdPartyRoutingSettings) {
+        //Change how OnBehalfOf and DeliverTo header fields of inbound messages are checked and
+        //whether messages the engine sends in response are addressed back through the hub.
+        //Outbound routing is set on each message using FIXTMessage::set_third_party_routing().
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetThirdPartyRoutingSettings(token,third_party_routing_settings));
+    }
+
+    pub fn set_skip_duplicate_parsing(&mut self,connection: Connection,skip_duplicate_parsing: bool) {
+        //Meant for very high-rate sessions. When enabled, the header of each received message is
+        //found with Parser::prescan() first. PossDup messages whose MsgSeqNum was already processed
+        //are then thrown away without being fully parsed and reported with
+        //EngineEvent::MessageReceivedDuplicateSkipped instead of
+        //EngineEvent::MessageReceivedDuplicate. The OrigSendingTime of these messages is never
+        //checked. Every other message is parsed as normal. Disabled by default.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetSkipDuplicateParsing(token,skip_duplicate_parsing));
+    }
+
+    pub fn recycle_message(&mut self,connection: Connection,message: Box<FIXTMessage + Send>) {
+        //Hand back a received message once it's no longer needed so the connection's parser can
+        //reuse it for the next message of the same type instead of allocating a new one. See
+        //Parser::recycle_message(). Any connection can be used but the one that received the
+        //message is the most likely to parse that message type again.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::RecycleMessage(token,message));
+    }
+
+    pub fn set_filler_bytes(&mut self,connection: Connection,filler_bytes: &[u8]) {
+        //Some middleware injects stray newlines or padding between messages. Received bytes in
+        //filler_bytes that show up between messages are skipped instead of being treated as
+        //garbage, so they are never written to the quarantine log. How many were skipped is kept
+        //in ConnectionInfo::inbound_filler_bytes_skipped. Empty by default. Must not contain '8'
+        //because that starts every message.
+        assert!(!filler_bytes.contains(&b'8'));
+
+        let filler_bytes = filler_bytes.to_vec();
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetFillerBytes(token,filler_bytes));
+    }
+
+    pub fn set_symbol_mapper(&mut self,connection: Connection,symbol_mapper: Option<Arc<SymbolMapper>>) {
+        //Translate the Symbol field between the counterparty's symbology and the application's
+        //own instrument ids. Received messages are mapped to internal ids before InboundMiddleware
+        //sees them. Messages passed to send_message() are mapped to venue symbols before they are
+        //validated, stored, and sent, so a message handed back in an event like
+        //EngineEvent::OutboundValidationFailed has the venue's symbol. Symbols the mapper doesn't
+        //know are left unchanged. None, the default, turns mapping off. See SymbolMapper.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetSymbolMapper(token,symbol_mapper));
+    }
+
+    pub fn set_message_signer(&mut self,connection: Connection,message_signer: Option<Arc<MessageSigner>>) {
+        //Sign every outbound message, including administrative messages and resends, using the
+        //Signature trailer field. None, the default, sends messages with whatever signature they
+        //were given. See MessageSigner.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetMessageSigner(token,message_signer));
+    }
+
+    pub fn set_session_coordinator(&mut self,connection: Connection,session_coordinator: Option<Arc<SessionCoordinator>>) {
+        //Coordinate with other engine processes that can resume the same session, like a
+        //primary and a standby sharing a FileMessageStore, so only one of them is ever logged on.
+        //A Logon passed to send_message() is refused with EngineEvent::LogonRefusedByCoordinator
+        //unless SessionCoordinator::try_acquire() succeeds. While logged on, the connection is
+        //dropped without a Logout as soon as SessionCoordinator::renew() fails or nothing can be
+        //sent because SessionCoordinator::is_active() is false. SessionCoordinator::release() is
+        //called once the connection is terminated for any reason. None, the default, turns
+        //coordination off. See FileLease.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetSessionCoordinator(token,session_coordinator));
+    }
+
+    pub fn set_message_statistics(&mut self,connection: Connection,sample_interval: Option<u64>) {
+        //Collect the size, field count, and most frequent tags of every sample_interval'th message
+        //of each MsgType sent and received. Meant for sizing buffers and tuning settings like
+        //max_message_size with real traffic. Results are retrieved with
+        //Engine::message_statistics(). Changing the interval starts over. None, the default, turns
+        //collection off.
+        assert!(sample_interval != Some(0));
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetMessageStatistics(token,sample_interval));
+    }
+
+    pub fn set_shadow_connection<C: Into<Option<Connection>>>(&mut self,connection: Connection,shadow_connection: C) {
+        //Mirror every application message sent on connection to shadow_connection as well. Useful
+        //for validating a new venue or gateway side by side with production without risking
+        //production flow. Each copy is marked with TestMessageIndicator and gets the shadow
+        //connection's own CompIDs and MsgSeqNum like any other message sent on it. Administrative
+        //messages are never mirrored and neither is anything sent directly on shadow_connection.
+        //Pass None to stop. Mirroring also stops once either connection is terminated.
+        match shadow_connection.into() {
+            Some(shadow_connection) => {
+                assert!(shadow_connection != connection);
+                self.shadow_connections.insert(connection,shadow_connection);
+            },
+            None => {
+                self.shadow_connections.remove(&connection);
+            },
+        }
+    }
+
+    pub fn set_quarantine_log<P: AsRef<Path>>(&mut self,connection: Connection,path: P) -> Result<(),io::Error> {
+        //Append every inbound byte the parser discards to the file at path. This includes garbage
+        //between messages and the raw bytes of messages that could not be parsed. Each entry lists
+        //the byte offset within the connection's inbound stream and the reason the bytes were
+        //discarded. Disabled by default.
+        let quarantine_log = try!(open_log_file(path.as_ref()));
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetQuarantineLog(token,Some(quarantine_log)));
+
+        Ok(())
+    }
+
+    pub fn clear_quarantine_log(&mut self,connection: Connection) {
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetQuarantineLog(token,None));
+    }
+
+    pub fn set_message_log<P: AsRef<Path>>(&mut self,connection: Connection,path: P,settings: MessageLogSettings) -> Result<(),io::Error> {
+        //Append every message sent or received on the connection to the file at path, replacing
+        //any message logs already in use. Messages are written using settings.format. Large
+        //messages are truncated and binary values are base64 encoded according to settings.
+        //Disabled by default.
+        let file = try!(open_log_file(path.as_ref()));
+        self.clear_message_log(connection);
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::AddMessageLog(token,MessageLog::new(file,settings)));
+
+        Ok(())
+    }
+
+    pub fn add_message_log<P: AsRef<Path>>(&mut self,connection: Connection,path: P,settings: MessageLogSettings) -> Result<(),io::Error> {
+        //Same as set_message_log() except existing message logs are kept. Useful for writing the
+        //same messages in several formats at once.
+        let file = try!(open_log_file(path.as_ref()));
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::AddMessageLog(token,MessageLog::new(file,settings)));
+
+        Ok(())
+    }
+
+    pub fn clear_message_log(&mut self,connection: Connection) {
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::ClearMessageLogs(token));
+    }
+
+    pub fn set_message_store(&mut self,connection: Connection,message_store: Box<MessageStore>) {
+        //Persist every outbound message and the next inbound and outbound MsgSeqNums to
+        //message_store. When set before the Logon message is sent, the connection starts from the
+        //stored MsgSeqNums instead of 1. ResendRequests are then answered automatically from the
+        //store and reported with EngineEvent::ResendCompleted instead of
+        //EngineEvent::ResendRequested. See MessageStore.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetMessageStore(token,message_store));
+    }
+
+    pub fn set_inbound_decoder(&mut self,connection: Connection,inbound_decoder: Box<InboundDecoder>) {
+        //Pass every byte received on the connection through inbound_decoder before it's parsed.
+        //Needed for venues that compress or otherwise wrap their messages. Set it right after
+        //Engine::add_connection() if the counterparty encodes everything, including its Logon.
+        //Otherwise, bytes received before the decoder is set are parsed as is. See InboundDecoder
+        //and GzipFrameDecoder.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetInboundDecoder(token,inbound_decoder));
+    }
+
+    pub fn set_user_data(&mut self,connection: Connection,user_data: Arc<Any + Send + Sync>) {
+        //Attach application state to the connection. It can be retrieved with Engine::user_data()
+        //while handling any event for the connection, including the ConnectionTerminated or
+        //ConnectionFailed event that ends it, and is passed to InboundMiddleware. Replaces any user
+        //data that was already attached.
+        if self.token(connection).is_some() {
+            self.user_data.insert(connection,user_data.clone());
+        }
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::SetUserData(token,user_data));
+    }
+
+    pub fn user_data(&self,connection: Connection) -> Option<Arc<Any + Send + Sync>> {
+        self.user_data.get(&connection).cloned()
+    }
+
+    pub fn acknowledge_inbound(&mut self,connection: Connection,msg_seq_num: u64) {
+        //Mark every inbound message up to and including msg_seq_num as fully processed by the
+        //application. The highest acknowledged MsgSeqNum is available from Engine::connection_info()
+        //so it can be persisted and passed to ConnectionBuilder::inbound_checkpoint() after a
+        //restart. Acknowledging a MsgSeqNum that hasn't been received yet is clamped to the last one
+        //that has.
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::AcknowledgeInbound(token,msg_seq_num));
+    }
+
+    pub fn connection_info(&mut self,connection: Connection) -> Option<ConnectionInfo> {
+        //Blocks until the engine thread has handled all previously sent events and responds.
+        //Returns None if the connection does not exist or has already been terminated.
+        let token = match self.token(connection) {
+            Some(token) => token,
+            None => return None,
+        };
+
+        let (response_tx,response_rx) = mpsc::channel();
+        self.tx.send(InternalEngineToThreadEvent::QueryConnectionInfo(token,response_tx)).unwrap();
+        response_rx.recv().unwrap_or(None)
+    }
+
+    pub fn order_entry_status(&mut self,connection: Connection,cl_ord_id: &[u8]) -> Option<OrderEntryStatus> {
+        //Whether an order message using cl_ord_id was sent and whether the counterparty responded
+        //to it. Answered from the connection's MessageStore so it still works after a crash once
+        //the connection is added again with the same store. Meant for deciding if an order whose
+        //fate is unknown must be sent again. An order that was sent but never acknowledged may
+        //still have reached the counterparty. Blocks like connection_info(). Returns None if the
+        //connection does not exist, has no MessageStore, or the store can't be read.
+        let token = match self.token(connection) {
+            Some(token) => token,
+            None => return None,
+        };
+
+        let (response_tx,response_rx) = mpsc::channel();
+        self.tx.send(InternalEngineToThreadEvent::QueryOrderEntryStatus(token,cl_ord_id.to_vec(),response_tx)).unwrap();
+        response_rx.recv().unwrap_or(None)
+    }
+
+    pub fn message_statistics(&mut self,connection: Connection) -> Option<MessageStatisticsReport> {
+        //Statistics collected since Engine::set_message_statistics() was called. Blocks like
+        //connection_info(). Returns None if the connection does not exist or isn't collecting
+        //statistics.
+        let token = match self.token(connection) {
+            Some(token) => token,
+            None => return None,
+        };
+
+        let (response_tx,response_rx) = mpsc::channel();
+        self.tx.send(InternalEngineToThreadEvent::QueryMessageStatistics(token,response_tx)).unwrap();
+        response_rx.recv().unwrap_or(None)
+    }
+
+    pub fn flush(&mut self,connection: Connection,timeout: Duration) -> bool {
+        //Blocks until every message queued on the connection so far, including any resend chunks
+        //still waiting to go out, has been handed off to the socket. Returns false if this takes
+        //longer than timeout or the connection does not exist or is terminated first. Messages
+        //held back by pause_sending() are not waited on.
+        let token = match self.token(connection) {
+            Some(token) => token,
+            None => return false,
+        };
+
+        let (response_tx,response_rx) = mpsc::channel();
+        self.tx.send(InternalEngineToThreadEvent::Flush(token,response_tx)).unwrap();
+        response_rx.recv_timeout(timeout).unwrap_or(false)
+    }
+
+    pub fn health_monitor(&self) -> HealthMonitor {
+        //Cheap alternative to connection_info() meant to be polled from other threads, such as an
+        //external watchdog making sure the engine is still servicing its connections. It can be
+        //cloned and outlive the Engine. See HealthCheck for what's available.
+        HealthMonitor::new(self.health_checks.clone(),self.clock.clone())
     }
 
     pub fn approve_new_connection<IMSN: Into<Option<u64>>>(&mut self,connection: Connection,message: Box<Logon>,inbound_msg_seq_num: IMSN) {
-        self.tx.send(InternalEngineToThreadEvent::ApproveNewConnection(connection,message,inbound_msg_seq_num.into().unwrap_or(2))).unwrap();
+        let inbound_msg_seq_num = inbound_msg_seq_num.into().unwrap_or(2);
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::ApproveNewConnection(token,message,inbound_msg_seq_num));
+    }
+
+    pub fn approve_new_connection_with_settings(&mut self,connection: Connection,settings: SessionSettings) {
+        //Same as approve_new_connection() except the session is configured using settings first.
+        settings.apply(self,connection);
     }
 
     pub fn reject_new_connection(&mut self,connection: Connection,reason: Option<Vec<u8>>) {
-        self.tx.send(InternalEngineToThreadEvent::RejectNewConnection(connection,reason)).unwrap();
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::RejectNewConnection(token,reason));
     }
 
     pub fn logout(&mut self,connection: Connection) {
-        self.tx.send(InternalEngineToThreadEvent::Logout(Token(connection.0))).unwrap();
+        self.send_connection_event(connection,|token| InternalEngineToThreadEvent::Logout(token));
+    }
+
+    fn token(&self,connection: Connection) -> Option<Token> {
+        //Returns None when connection doesn't refer to a currently active connection.
+        let token = Token(connection.id);
+        if self.token_generator.lock().unwrap().generation(token) == Some(connection.generation) {
+            Some(token)
+        }
+        else {
+            None
+        }
+    }
+
+    fn send_connection_event<F: FnOnce(Token) -> InternalEngineToThreadEvent>(&self,connection: Connection,build_event: F) {
+        if let Some(token) = self.token(connection) {
+            self.tx.send(build_event(token)).unwrap();
+        }
+        else {
+            //Silently ignore for a stale or invalid connection.
+            //TODO: Maybe submit this to a logging system or something?
+        }
     }
 
     pub fn poll<D: Into<Option<Duration>>>(&mut self,duration: D) -> Option<EngineEvent> {
+        self.release_terminated_connections();
+        self.poll_event(duration.into())
+    }
+
+    pub fn poll_batch(&mut self,max_events: usize,deadline: Instant) -> Vec<EngineEvent> {
+        //Collect up to max_events events, waiting until deadline for more to arrive. Returns as
+        //soon as max_events have been collected. Events that are already waiting are still
+        //collected once the deadline has passed. Saves waking up for every single event when they
+        //are handled in bulk, like when writing them to a database. Connections that ended during
+        //the batch keep their user data until the next poll() or poll_batch().
+        self.release_terminated_connections();
+
+        let mut events = Vec::with_capacity(max_events);
+        while events.len() < max_events {
+            let now = Instant::now();
+            let remaining = if deadline > now { deadline - now } else { Duration::from_millis(0) };
+            match self.poll_event(Some(remaining)) {
+                Some(event) => events.push(event),
+                None => break,
+            }
+        }
+
+        events
+    }
+
+    fn release_terminated_connections(&mut self) {
+        //The application has had a chance to look up the user data of connections that ended
+        //during the previous poll() so it can be released now.
+        for connection in self.terminated_connections.drain(..) {
+            self.user_data.remove(&connection);
+            self.shadow_connections.remove(&connection);
+            self.shadow_connections.retain(|_,shadow_connection| *shadow_connection != connection);
+        }
+    }
+
+    fn poll_event(&mut self,duration: Option<Duration>) -> Option<EngineEvent> {
         //Perform any book keeping needed to manage engine's state.
         fn update_engine(engine: &mut Engine,event: &EngineEvent) {
             match *event {
                 EngineEvent::ConnectionFailed(connection,_) |
                 EngineEvent::ConnectionTerminated(connection,_) => {
-                    engine.token_generator.lock().unwrap().remove(Token(connection.0));
+                    engine.token_generator.lock().unwrap().remove(Token(connection.id));
+                    engine.terminated_connections.push(connection);
                 },
                 _ => {},
             }
         };
 
-        if let Ok(event) = self.rx.try_recv() {
+        if let Ok(event) = self.try_recv() {
             update_engine(self,&event);
             return Some(event);
         }
 
-        if let Some(poll_duration) = duration.into() {
+        if let Some(poll_duration) = duration {
             let now = Instant::now(); //Watch time manually because Mio's poll::poll() can wake immediatelly and we'll have no idea how long has elapsed.
 
             while let Some(poll_duration) = poll_duration.checked_sub(now.elapsed()) {
@@ -330,7 +1675,7 @@ impl Engine {
                     return None;
                 }
 
-                let result = self.rx.try_recv();
+                let result = self.try_recv();
                 match result {
                     Ok(event) => {
                         update_engine(self,&event);
@@ -344,6 +1689,162 @@ impl Engine {
 
         None
     }
+
+    pub fn conflated_count(&self) -> u64 {
+        //Number of market data snapshots that were replaced by a newer one before being returned
+        //by Engine::poll(). See EngineBuilder::conflate_market_data().
+        self.conflated_count
+    }
+
+    fn try_recv(&mut self) -> Result<EngineEvent,TryRecvError> {
+        //Skip over any events handled by the session provisioner.
+        loop {
+            let event = try!(self.try_recv_conflated());
+            if let Some(event) = self.provision_session(event).and_then(|event| self.deliver_admin_message(event)) {
+                return Ok(self.attach_instrument(event));
+            }
+        }
+    }
+
+    fn provision_session(&mut self,event: EngineEvent) -> Option<EngineEvent> {
+        //Returns the event if it still needs to be delivered to the application.
+        let data = match event {
+            EngineEvent::ConnectionLoggingOn(data) => data,
+            event => return Some(event),
+        };
+
+        let decision = match self.session_provisioner {
+            Some(ref mut session_provisioner) => session_provisioner.on_logon(&LogonRequest {
+                listener: data.listener,
+                connection: data.connection,
+                address: data.address,
+                message: &*data.message,
+            }),
+            None => ProvisioningDecision::Defer,
+        };
+
+        match decision {
+            ProvisioningDecision::Accept(settings) => self.approve_new_connection_with_settings(data.connection,settings),
+            ProvisioningDecision::Reject(reason) => self.reject_new_connection(data.connection,reason),
+            ProvisioningDecision::Defer => return Some(EngineEvent::ConnectionLoggingOn(data)),
+        }
+
+        None
+    }
+
+    fn deliver_admin_message(&self,event: EngineEvent) -> Option<EngineEvent> {
+        //Returns the event if it still needs to be delivered to the application. See
+        //AdminMessageDelivery.
+        if self.admin_message_delivery == AdminMessageDelivery::MessageReceived {
+            return Some(event);
+        }
+
+        let (connection,message) = match event {
+            EngineEvent::MessageReceived(connection,message) => (connection,message),
+            event => return Some(event),
+        };
+
+        let msg_type = message.msg_type();
+        if msg_type != Heartbeat::msg_type() && msg_type != TestRequest::msg_type() &&
+           msg_type != ResendRequest::msg_type() && msg_type != SequenceReset::msg_type() {
+            return Some(EngineEvent::MessageReceived(connection,message));
+        }
+
+        match self.admin_message_delivery {
+            AdminMessageDelivery::MessageReceived => unreachable!(),
+            AdminMessageDelivery::Separate => Some(EngineEvent::AdminMessageReceived(connection,message)),
+            AdminMessageDelivery::Suppress => None,
+        }
+    }
+
+    fn attach_instrument(&self,event: EngineEvent) -> EngineEvent {
+        let reference_data_cache = match self.reference_data_cache {
+            Some(ref reference_data_cache) => reference_data_cache,
+            None => return event,
+        };
+        let (connection,message) = match event {
+            EngineEvent::MessageReceived(connection,message) => (connection,message),
+            event => return event,
+        };
+
+        if reference_data_cache.on_message(&*message) > 0 {
+            return EngineEvent::MessageReceived(connection,message);
+        }
+
+        let instrument = match message.symbol_bytes() {
+            Some(symbol) => reference_data_cache.get_by_symbol(symbol),
+            None => None,
+        };
+        match instrument {
+            Some(instrument) => EngineEvent::MessageReceivedWithInstrument(MessageReceivedWithInstrumentData {
+                connection: connection,
+                message: message,
+                instrument: instrument,
+            }),
+            None => EngineEvent::MessageReceived(connection,message),
+        }
+    }
+
+    fn try_recv_conflated(&mut self) -> Result<EngineEvent,TryRecvError> {
+        if !self.conflate_market_data {
+            return self.rx.try_recv();
+        }
+
+        //Take everything that's waiting so newer snapshots can replace older ones that haven't been
+        //returned yet. Without a backlog, this is the same as returning the next event.
+        loop {
+            match self.rx.try_recv() {
+                Ok(event) => self.push_pending_event(event),
+                Err(e) => {
+                    return match self.pop_pending_event() {
+                        Some(event) => Ok(event),
+                        None => Err(e),
+                    };
+                },
+            }
+        }
+    }
+
+    fn push_pending_event(&mut self,event: EngineEvent) {
+        //A replaced snapshot is found through pending_snapshots and left as a None in its place so
+        //this stays cheap no matter how far behind the application is.
+        if let Some(key) = snapshot_key(&event) {
+            let position = self.pending_event_offset + self.pending_events.len() as u64;
+            if let Some(replaced_position) = self.pending_snapshots.insert(key,position) {
+                self.pending_events[(replaced_position - self.pending_event_offset) as usize] = None;
+                self.conflated_count += 1;
+            }
+        }
+
+        self.pending_events.push_back(Some(event));
+    }
+
+    fn pop_pending_event(&mut self) -> Option<EngineEvent> {
+        while let Some(pending_event) = self.pending_events.pop_front() {
+            self.pending_event_offset += 1;
+            if let Some(event) = pending_event {
+                //Anything still pending is the newest snapshot for its key.
+                if let Some(key) = snapshot_key(&event) {
+                    self.pending_snapshots.remove(&key);
+                }
+
+                return Some(event);
+            }
+        }
+
+        None
+    }
+}
+
+fn snapshot_key(event: &EngineEvent) -> Option<(Connection,Vec<u8>,Vec<u8>)> {
+    //Snapshots with the same key replace each other when conflating market data.
+    if let EngineEvent::MessageReceived(connection,ref message) = *event {
+        if let Some(message) = message.as_any().downcast_ref::<MarketDataSnapshotFullRefresh>() {
+            return Some((connection,message.md_req_id.clone(),message.symbol.clone()));
+        }
+    }
+
+    None
 }
 
 impl Drop for Engine {
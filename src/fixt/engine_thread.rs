@@ -11,36 +11,56 @@
 
 #![allow(deprecated)]
 
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
 use mio::{Event,Events,Poll,PollOpt,Ready,Token};
 use mio::channel::{Receiver,Sender};
 use mio::tcp::{Shutdown,TcpListener,TcpStream};
 use mio::unix::UnixReady;
-use mio::timer::{Timeout,Timer};
-use mio::timer::Builder as TimerBuilder;
+use std::any::Any;
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap,VecDeque};
 use std::collections::hash_map::Entry;
 use std::fmt;
+use std::fs::File;
 use std::io::{self,Write};
 use std::mem;
-use std::net::SocketAddr;
+use std::net::{IpAddr,SocketAddr};
+use std::ops::Range;
 use std::sync::{Arc,Mutex};
-use std::time::Duration;
+use std::sync::mpsc;
+use std::time::{Duration,Instant};
 
 use byte_buffer::ByteBuffer;
-use dictionary::{CloneDictionary,administrative_msg_types,standard_msg_types};
-use dictionary::field_types::generic::UTCTimestampFieldType;
-use dictionary::field_types::other::{BusinessRejectReason,MsgDirection,SessionRejectReason};
+use clock::Clock;
+use dictionary::{administrative_msg_types,standard_msg_types};
+use dictionary::field_types::generic::{TimestampPrecision,UTCTimestampFieldType};
+use dictionary::field_types::other::{BusinessRejectReason,MsgDirection,SessionRejectReason,SessionStatus};
 use dictionary::fields::{ApplVerID,MsgSeqNum,SenderCompID,TargetCompID,OrigSendingTime};
-use dictionary::messages::{Logon,Logout,ResendRequest,TestRequest,Heartbeat,SequenceReset,Reject,BusinessMessageReject};
+use dictionary::messages::{Logon,Logout,ResendRequest,TestRequest,Heartbeat,SequenceReset,Reject,BusinessMessageReject,ExecutionReport,NewOrderSingle,OrderCancelReject,OrderCancelReplaceRequest,OrderCancelRequest};
 use field::Field;
 use field_type::FieldType;
-use fix::{Parser,ParseError};
+use fix::{DiscardedBytes,Parser,ParseError,ValidationSettings};
 use fix_version::FIXVersion;
-use fixt::engine::{EngineEvent,Connection,ConnectionTerminatedReason,Listener,ResendResponse};
-use fixt::message::{BuildFIXTMessage,FIXTMessage};
+use fixt::decompression::InboundDecoder;
+use fixt::duplicate_order_guard::{DuplicateOrderAction,DuplicateOrderGuard,SentClOrdIDs};
+use fixt::engine::{AdminNoticeData,EngineEvent,Connection,ConnectionAcceptedData,ConnectionInfo,ConnectionLoggingOnData,ConnectionRefusedData,ConnectionRefusedReason,ConnectionTerminatedReason,HeartBtIntRange,Listener,ListenerSettings,LogoutResponsePolicy,MessageReceivedDuplicateSkippedData,OrderEntryStatus,OutboundValidationFailedData,OutboxPolicy,ProbeSettings,ResendRequestLimits,ResendRequestRange,ResendResponse,ResendSummary,SessionStatusReceivedData,TestRequestResponse,TestRequestSettings};
+use fixt::failover::SessionCoordinator;
+use fixt::health_check::{HealthCheck,HealthCheckRegistry};
+use fixt::message::{BuildFIXTMessage,FIXTMessage,display_versions};
+use fixt::message_log::{MessageLog,MessageLogDirection};
+use fixt::message_signer::MessageSigner;
+use fixt::message_statistics::{MessageStatistics,MessageStatisticsReport,wire_size};
+use fixt::message_store::MessageStore;
+use fixt::middleware::{MiddlewareAction,MiddlewareChain};
+use fixt::outbound_validation::{OutboundValidation,validate_outbound_message};
+use fixt::rate_anomaly::{RateAnomalyDetector,RateAnomalySettings};
+use fixt::session_id::SessionID;
+use fixt::symbol_mapper::{SymbolMapper,map_inbound_symbol,map_outbound_symbol};
+use fixt::third_party_routing::ThirdPartyRoutingSettings;
 use message_version::MessageVersion;
 use network_read_retry::NetworkReadRetry;
+use timer::{Timeout,Timer};
 use token_generator::TokenGenerator;
 
 //TODO: Make sure Logon message is sent automatically instead of waiting on caller. Althought, we
@@ -49,8 +69,8 @@ use token_generator::TokenGenerator;
 //ResendRequest, and the other side continues to send garbled messages.
 //TODO: Implement ConnectionStatus handling using a state machine pattern to reduce chance of
 //mistake.
-//TODO: Need to make inbound and outbound MsgSeqNums adjustable at connection setup and available
-//on connection termination to support persistent sessions.
+//TODO: Make inbound and outbound MsgSeqNums available on connection termination so persistent
+//sessions can be supported without a MessageStore.
 //TODO: Stop allowing outgoing messages when performing an emergency logout.
 //TODO: Need to sanitize output strings when serializing.
 
@@ -59,15 +79,15 @@ const AUTO_DISCONNECT_AFTER_LOGOUT_RESPONSE_SECS: u64 = 10;
 const AUTO_DISCONNECT_AFTER_INITIATING_LOGOUT_SECS: u64 = 10;
 const AUTO_CONTINUE_AFTER_LOGOUT_RESEND_REQUEST_SECS: u64 = 10;
 const AUTO_DISCONNECT_AFTER_WRITE_BLOCKS_SECS: u64 = 10;
+const SESSION_COORDINATOR_RETRY_MS: u64 = 10;
 pub const AUTO_DISCONNECT_AFTER_INBOUND_RESEND_REQUEST_LOOP_COUNT: u64 = 5;
 pub const AUTO_DISCONNECT_AFTER_NO_LOGON_RECEIVED_SECONDS: u64 = 10;
 const EVENT_POLL_CAPACITY: usize = 1024;
 pub const INBOUND_MESSAGES_BUFFER_LEN_MAX: usize = 10;
 pub const INBOUND_BYTES_BUFFER_CAPACITY: usize = 2048;
-const TIMER_TICK_MS: u64 = 100;
-const TIMER_TIMEOUTS_PER_TICK_MAX: usize = 256;
+const OUTBOUND_MESSAGES_WARM_UP_CAPACITY: usize = 64;
+const LOAD_SENT_CL_ORD_IDS_BATCH_LEN: u64 = 256;
 pub const CONNECTION_COUNT_MAX: usize = 65536;
-const TIMEOUTS_PER_CONNECTION_MAX: usize = 3;
 
 pub const INTERNAL_ENGINE_EVENT_TOKEN: Token = Token(0);
 const TIMEOUT_TOKEN: Token = Token(1);
@@ -214,7 +234,16 @@ enum TimeoutType {
     Inbound,
     InboundTestRequest,
     InboundBlocked,
+    InboundQuietPeriod,
+    InboundRateAnomalyWindow,
+    Probe,
     ContinueLogout,
+    LogoutResponse,
+    ResendChunk,
+    ResendRequestCoalesce,
+    SessionCoordinatorRenew,
+    SessionCoordinatorRetry,
+    TestRequestResponse,
     NoLogon,
     Logout,
     HangUp,
@@ -226,6 +255,7 @@ struct OutboundMessage {
     message: Box<FIXTMessage + Send>,
     message_version: Option<MessageVersion>,
     auto_msg_seq_num: bool,
+    expires_at: Option<Instant>, //Message is dropped instead of sent if this passes before it's written.
 }
 
 impl OutboundMessage {
@@ -234,6 +264,7 @@ impl OutboundMessage {
             message: Box::new(message),
             message_version: None,
             auto_msg_seq_num: auto_msg_seq_num,
+            expires_at: None,
         }
     }
 
@@ -242,6 +273,7 @@ impl OutboundMessage {
             message: Box::new(message),
             message_version: None,
             auto_msg_seq_num: true,
+            expires_at: None,
         }
     }
 
@@ -250,6 +282,7 @@ impl OutboundMessage {
             message: message,
             message_version: None,
             auto_msg_seq_num: true,
+            expires_at: None,
         }
     }
 }
@@ -264,7 +297,7 @@ fn reset_timeout(timer: &mut Timer<(TimeoutType,Token)>,timeout: &mut Option<Tim
             timer.set_timeout(
                 duration,
                 (timeout_type,*token)
-            ).unwrap()
+            )
         )
     }
     else {
@@ -282,6 +315,18 @@ fn reset_outbound_timeout(timer: &mut Timer<(TimeoutType,Token)>,outbound_timeou
     );
 }
 
+fn write_quarantine_entry(quarantine_log: &mut File,now: DateTime<UTC>,offset: u64,discarded: &DiscardedBytes) {
+    //Each entry is a header line followed by the raw bytes exactly as received and a newline.
+    //Offset is where the first byte appeared in the inbound stream since the connection started.
+    let offset = offset + discarded.end as u64 - discarded.bytes.len() as u64;
+    let header = format!("{} offset={} length={} reason={}\n",now.format("%Y%m%d-%H:%M:%S%.3f"),offset,discarded.bytes.len(),discarded.reason);
+
+    //A failed write shouldn't take down the connection. The log is only a diagnostic aid.
+    let _ = quarantine_log.write_all(header.as_bytes())
+        .and_then(|_| quarantine_log.write_all(&discarded.bytes))
+        .and_then(|_| quarantine_log.write_all(b"\n"));
+}
+
 fn reset_inbound_timeout(timer: &mut Timer<(TimeoutType,Token)>,inbound_timeout: &mut Option<Timeout>,inbound_timeout_duration: &Option<Duration>,token: &Token) {
     reset_timeout(
         timer,
@@ -292,13 +337,81 @@ fn reset_inbound_timeout(timer: &mut Timer<(TimeoutType,Token)>,inbound_timeout:
     );
 }
 
+fn order_entry_cl_ord_id(message: &FIXTMessage) -> Option<&[u8]> {
+    //ClOrdID of an order message that Engine::order_entry_status() keeps track of.
+    let message = message.as_any();
+    let cl_ord_id = if let Some(new_order_single) = message.downcast_ref::<NewOrderSingle>() {
+        &new_order_single.cl_ord_id
+    }
+    else if let Some(order_cancel_request) = message.downcast_ref::<OrderCancelRequest>() {
+        &order_cancel_request.cl_ord_id
+    }
+    else if let Some(order_cancel_replace_request) = message.downcast_ref::<OrderCancelReplaceRequest>() {
+        &order_cancel_replace_request.cl_ord_id
+    }
+    else {
+        return None;
+    };
+
+    if cl_ord_id.is_empty() { None } else { Some(&cl_ord_id[..]) }
+}
+
+fn logon_default_message_version(fix_version: FIXVersion,logon: &Logon) -> MessageVersion {
+    //Only FIXT.1.1 has a DefaultApplVerID. Classic FIX versions always use the message version
+    //that matches BeginStr so whatever the field was left at must be ignored.
+    if let FIXVersion::FIXT_1_1 = fix_version {
+        logon.default_appl_ver_id
+    }
+    else {
+        fix_version.max_message_version()
+    }
+}
+
 pub enum InternalEngineToThreadEvent {
-    NewConnection(Token,FIXVersion,MessageVersion,<<SenderCompID as Field>::Type as FieldType>::Type,<<TargetCompID as Field>::Type as FieldType>::Type,SocketAddr),
-    NewListener(Token,<<SenderCompID as Field>::Type as FieldType>::Type,TcpListener),
-    SendMessage(Token,Option<MessageVersion>,Box<FIXTMessage + Send>),
+    NewConnection(Connection,MessageVersion,SessionID,SocketAddr),
+    NewListener(Token,<<SenderCompID as Field>::Type as FieldType>::Type,TcpListener,ListenerSettings),
+    SendMessage(Token,Option<MessageVersion>,Box<FIXTMessage + Send>,Option<Instant>),
     ResendMessages(Token,Vec<ResendResponse>),
-    ApproveNewConnection(Connection,Box<Logon>,u64),
-    RejectNewConnection(Connection,Option<Vec<u8>>),
+    PauseSending(Token),
+    ResumeSending(Token),
+    WarmUp(Token,Option<MessageVersion>,Box<FIXTMessage + Send>),
+    SetProbeSettings(Token,Option<ProbeSettings>),
+    SetInboundQuietPeriod(Token,Option<Duration>),
+    SetInboundRateAnomaly(Token,Option<RateAnomalySettings>),
+    SetLogoutResponsePolicy(Token,LogoutResponsePolicy),
+    SetOutboxPolicy(Token,OutboxPolicy),
+    ReleaseHeldMessages(Token),
+    DiscardHeldMessages(Token),
+    SetTestRequestSettings(Token,TestRequestSettings),
+    SetTimestampPrecision(Token,TimestampPrecision),
+    SetValidationSettings(Token,ValidationSettings),
+    SetOutboundValidation(Token,OutboundValidation),
+    SetDuplicateOrderGuard(Token,Option<DuplicateOrderGuard>),
+    SetThirdPartyRoutingSettings(Token,ThirdPartyRoutingSettings),
+    SetSkipDuplicateParsing(Token,bool),
+    RecycleMessage(Token,Box<FIXTMessage + Send>),
+    SetFillerBytes(Token,Vec<u8>),
+    SetSymbolMapper(Token,Option<Arc<SymbolMapper>>),
+    SetMessageSigner(Token,Option<Arc<MessageSigner>>),
+    SetMessageStatistics(Token,Option<u64>),
+    SetSessionCoordinator(Token,Option<Arc<SessionCoordinator>>),
+    SetQuarantineLog(Token,Option<File>),
+    AddMessageLog(Token,MessageLog),
+    ClearMessageLogs(Token),
+    SetMessageStore(Token,Box<MessageStore>),
+    SetInboundDecoder(Token,Box<InboundDecoder>),
+    SetUserData(Token,Arc<Any + Send + Sync>),
+    SetInboundCheckpoint(Token,u64),
+    AcknowledgeInbound(Token,u64),
+    SetResendRequestLimits(Token,Option<ResendRequestLimits>),
+    SetResendRequestRange(Token,ResendRequestRange),
+    SetResendRequestCoalesceInterval(Token,Option<Duration>),
+    QueryConnectionInfo(Token,mpsc::Sender<Option<ConnectionInfo>>),
+    QueryOrderEntryStatus(Token,Vec<u8>,mpsc::Sender<Option<OrderEntryStatus>>),
+    QueryMessageStatistics(Token,mpsc::Sender<Option<MessageStatisticsReport>>),
+    Flush(Token,mpsc::Sender<bool>),
+    ApproveNewConnection(Token,Box<Logon>,u64),
+    RejectNewConnection(Token,Option<Vec<u8>>),
     Logout(Token),
     Shutdown,
 }
@@ -319,6 +432,7 @@ enum ConnectionEventError {
 enum ConnectionReadMessage {
     Message(Box<FIXTMessage + Send>),
     Error(ParseError),
+    SkippedDuplicate(Vec<u8>,MsgSeqNumType), //MsgType and MsgSeqNum of a duplicate that was never parsed.
 }
 
 struct LastSeenResendRequest {
@@ -331,40 +445,103 @@ struct InternalConnection {
     default_message_version: MessageVersion,
     socket: TcpStream,
     token: Token,
+    generation: u64,
     outbound_messages: Vec<OutboundMessage>,
+    outbound_paused: bool,
+    outbound_paused_messages: Vec<OutboundMessage>, //Application messages held back while outbound_paused is true.
     outbound_buffer: ByteBuffer,
     outbound_msg_seq_num: MsgSeqNumType,
     outbound_heartbeat_timeout: Option<Timeout>,
     outbound_heartbeat_timeout_duration: Option<Duration>,
     inbound_buffer: ByteBuffer,
+    inbound_decoder: Option<Box<InboundDecoder>>, //Decodes inbound_buffer into decoded_inbound_buffer for venues that compress or wrap their messages.
+    decoded_inbound_buffer: ByteBuffer, //Parsed instead of inbound_buffer when there is an inbound_decoder.
+    user_data: Option<Arc<Any + Send + Sync>>, //Application state attached with Engine::set_user_data().
+    inbound_bytes_parsed: u64, //Total bytes handed to the parser. Used as the offset in the quarantine log.
     inbound_msg_seq_num: MsgSeqNumType,
+    inbound_acknowledged_msg_seq_num: MsgSeqNumType, //Highest MsgSeqNum the application says it's done processing.
     inbound_testrequest_timeout: Option<Timeout>,
     inbound_testrequest_timeout_duration: Option<Duration>,
     inbound_resend_request_msg_seq_num: Option<MsgSeqNumType>,
+    inbound_resend_request_begin_seq_no: MsgSeqNumType, //BeginSeqNo of the most recently sent ResendRequest.
+    inbound_resend_request_end_seq_no: MsgSeqNumType, //Last MsgSeqNum covered by the most recently sent ResendRequest.
+    resend_request_range: ResendRequestRange,
+    resend_request_coalesce_interval: Option<Duration>,
+    resend_request_coalesce_timeout: Option<Timeout>,
+    pending_resend_request: Option<Range<MsgSeqNumType>>, //Missing MsgSeqNums waiting for resend_request_coalesce_timeout.
     inbound_last_seen_resend_request: LastSeenResendRequest,
+    inbound_resend_request_range: Option<Range<MsgSeqNumType>>, //Most recent range passed along with EngineEvent::ResendRequested.
+    resend_request_limits: Option<ResendRequestLimits>,
+    resend_chunk_messages: Vec<OutboundMessage>, //Resent messages waiting for the next chunk to go out.
+    resend_held_messages: Vec<OutboundMessage>, //Application messages held back until resend_chunk_messages is empty.
+    resend_summaries: Vec<ResendSummary>, //Waiting to be reported with EngineEvent::ResendCompleted once resend_chunk_messages is empty.
+    resend_chunk_timeout: Option<Timeout>,
     inbound_blocked: bool,
     inbound_blocked_timeout: Option<Timeout>,
+    probe_settings: Option<ProbeSettings>,
+    probe_timeout: Option<Timeout>,
+    probe_unanswered_count: u32,
+    inbound_quiet_period: Option<Duration>, //Silence after which EngineEvent::InboundQuietPeriodExceeded is sent. None when the watchdog is off.
+    inbound_quiet_period_timeout: Option<Timeout>,
+    inbound_rate_anomaly: Option<RateAnomalyDetector>, //Counts received messages per MsgType to spot storms and droughts. None when turned off.
+    inbound_rate_anomaly_timeout: Option<Timeout>, //When the current counting window ends.
+    logout_response_policy: LogoutResponsePolicy,
+    logout_response_timeout: Option<Timeout>, //Set while LogoutResponsePolicy::Delay is waiting for queued messages to go out.
+    outbox_policy: OutboxPolicy,
+    test_request_settings: TestRequestSettings,
+    delayed_test_request_responses: VecDeque<Heartbeat>, //Replies to TestRequests waiting out TestRequestResponse::Delay. Oldest first.
+    outbound_validation: OutboundValidation,
+    sent_cl_ord_ids: Option<SentClOrdIDs>, //ClOrdIDs checked by the DuplicateOrderGuard. None when the guard is off.
+    third_party_routing_settings: ThirdPartyRoutingSettings,
+    skip_duplicate_parsing: bool, //Throw away already processed PossDup messages found with Parser::prescan() instead of parsing them.
+    symbol_mapper: Option<Arc<SymbolMapper>>, //Translates Symbol fields between the counterparty's symbology and the application's.
+    message_signer: Option<Arc<MessageSigner>>, //Fills in the Signature trailer field of every outbound message.
+    inbound_message_statistics: Option<MessageStatistics>, //Sampled sizes and fields of received messages. None when statistics are off.
+    outbound_message_statistics: Option<MessageStatistics>, //Same as inbound_message_statistics but for sent messages.
+    session_coordinator: Option<Arc<SessionCoordinator>>, //Makes sure no other engine process is logged on to the same session.
+    session_coordinator_timeout: Option<Timeout>, //Set from Logon until SessionCoordinator::renew() is next due or while SessionCoordinator::try_acquire() is retried.
+    session_coordinator_acquired: bool, //SessionCoordinator::try_acquire() succeeded so release() must be called once the connection is terminated.
+    session_coordinator_logon: Option<OutboundMessage>, //Logon waiting on SessionCoordinator::try_acquire().
+    outbox: Vec<OutboundMessage>, //Application messages that never went out on this or an earlier connection of the same session. See OutboxPolicy.
+    unsent_messages: Vec<Box<FIXTMessage + Send>>, //Dropped messages waiting to be reported with EngineEvent::MessagesNotSent.
+    expired_messages: Vec<Box<FIXTMessage + Send>>, //Messages whose time-to-live passed waiting to be reported with EngineEvent::MessagesExpired.
+    timestamp_precision: TimestampPrecision, //Precision of outbound UTCTimestamp fields like SendingTime and TransactTime.
+    quarantine_log: Option<File>, //Where the parser's discarded bytes are written.
+    message_logs: Vec<MessageLog>, //Where every inbound and outbound message is written. Each can use a different format.
+    last_inbound_time: Option<Instant>,
+    last_outbound_time: Option<Instant>,
+    health_check: Arc<Mutex<HealthCheck>>, //Shared with Engine::health_monitor() so it can be checked from any thread.
+    flush_waiters: Vec<mpsc::Sender<bool>>, //Notified once every pending outbound byte is handed to the socket.
+    message_store: Option<Box<MessageStore>>, //Where outbound messages and MsgSeqNums are persisted so the session survives a restart.
+    message_store_parser: Option<Parser>, //Parses stored messages when answering a ResendRequest. Separate so the network parser's state is never disturbed.
+    message_store_errors: Vec<io::Error>, //Waiting to be reported with EngineEvent::MessageStoreError.
     logout_timeout: Option<Timeout>,
     parser: Parser,
     is_connected: bool, //TODO: Might belong better as part of ConnectionStatus if the state machine design works well.
     status: ConnectionStatus,
     sender_comp_id: <<SenderCompID as Field>::Type as FieldType>::Type,
     target_comp_id: <<TargetCompID as Field>::Type as FieldType>::Type,
+    session_qualifier: Option<Vec<u8>>,
+    remote_address: SocketAddr,
+    listener: Option<Listener>, //Listener that accepted the connection. None when added with Engine::add_connection().
+    heart_bt_int_range: Option<HeartBtIntRange>, //Copied from the listener's settings.
 }
 
 impl InternalConnection {
-    fn new(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
+    fn new(message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,
            max_message_size: u64,
            fix_version: FIXVersion,
            default_message_version: MessageVersion,
            socket: TcpStream,
            token: Token,
+           generation: u64,
            sender_comp_id: <<SenderCompID as Field>::Type as FieldType>::Type,
-           target_comp_id: <<TargetCompID as Field>::Type as FieldType>::Type) -> InternalConnection {
+           target_comp_id: <<TargetCompID as Field>::Type as FieldType>::Type,
+           remote_address: SocketAddr) -> InternalConnection {
         //Force all administrative messages to use the newest message version for the
         //specified FIX version. This way they can't be overridden during Logon and it
         //makes sure the Logon message supports all of the fields we support.
-        let mut parser = Parser::new(message_dictionary,max_message_size);
+        let mut parser = Parser::with_shared_dictionary(message_dictionary,max_message_size);
         for msg_type in administrative_msg_types() {
             parser.set_default_message_type_version(msg_type,fix_version.max_message_version());
         }
@@ -374,28 +551,89 @@ impl InternalConnection {
             default_message_version: default_message_version,
             socket: socket,
             token: token,
+            generation: generation,
             outbound_messages: Vec::new(),
+            outbound_paused: false,
+            outbound_paused_messages: Vec::new(),
             outbound_buffer: ByteBuffer::new(),
             outbound_msg_seq_num: 1, //Starts at 1. FIXT v1.1, page 5.
             outbound_heartbeat_timeout: None,
             outbound_heartbeat_timeout_duration: None,
             inbound_buffer: ByteBuffer::with_capacity(INBOUND_BYTES_BUFFER_CAPACITY),
+            inbound_decoder: None,
+            user_data: None,
+            decoded_inbound_buffer: ByteBuffer::new(),
+            inbound_bytes_parsed: 0,
             inbound_msg_seq_num: 1, //Starts at 1 as well.
+            inbound_acknowledged_msg_seq_num: 0,
             inbound_testrequest_timeout: None,
             inbound_testrequest_timeout_duration: None,
             inbound_resend_request_msg_seq_num: None,
+            inbound_resend_request_begin_seq_no: 0,
+            inbound_resend_request_end_seq_no: 0,
+            resend_request_range: ResendRequestRange::OpenEnded,
+            resend_request_coalesce_interval: None,
+            resend_request_coalesce_timeout: None,
+            pending_resend_request: None,
             inbound_last_seen_resend_request: LastSeenResendRequest {
                 begin_seq_no: 0,
                 count: 0,
             },
+            inbound_resend_request_range: None,
+            resend_request_limits: None,
+            resend_chunk_messages: Vec::new(),
+            resend_summaries: Vec::new(),
+            resend_held_messages: Vec::new(),
+            resend_chunk_timeout: None,
             inbound_blocked: false,
             inbound_blocked_timeout: None,
+            probe_settings: None,
+            probe_timeout: None,
+            probe_unanswered_count: 0,
+            inbound_quiet_period: None,
+            inbound_quiet_period_timeout: None,
+            inbound_rate_anomaly: None,
+            inbound_rate_anomaly_timeout: None,
+            logout_response_policy: LogoutResponsePolicy::Flush,
+            logout_response_timeout: None,
+            outbox_policy: OutboxPolicy::Discard,
+            test_request_settings: TestRequestSettings::new(),
+            delayed_test_request_responses: VecDeque::new(),
+            outbound_validation: OutboundValidation::Disabled,
+            sent_cl_ord_ids: None,
+            third_party_routing_settings: ThirdPartyRoutingSettings::new(),
+            skip_duplicate_parsing: false,
+            symbol_mapper: None,
+            message_signer: None,
+            inbound_message_statistics: None,
+            outbound_message_statistics: None,
+            session_coordinator: None,
+            session_coordinator_timeout: None,
+            session_coordinator_acquired: false,
+            session_coordinator_logon: None,
+            outbox: Vec::new(),
+            unsent_messages: Vec::new(),
+            expired_messages: Vec::new(),
+            timestamp_precision: TimestampPrecision::Milliseconds,
+            quarantine_log: None,
+            message_logs: Vec::new(),
+            last_inbound_time: None,
+            last_outbound_time: None,
+            health_check: Arc::new(Mutex::new(HealthCheck::new())),
+            flush_waiters: Vec::new(),
+            message_store: None,
+            message_store_parser: None,
+            message_store_errors: Vec::new(),
             logout_timeout: None,
             parser: parser,
             is_connected: false,
             status: ConnectionStatus::SendingLogon,
             sender_comp_id: sender_comp_id,
             target_comp_id: target_comp_id,
+            session_qualifier: None,
+            remote_address: remote_address,
+            listener: None,
+            heart_bt_int_range: None,
         }
     }
 
@@ -409,6 +647,13 @@ impl InternalConnection {
                 if self.outbound_messages.is_empty() {
                     //Nothing left to write.
 
+                    //Queued messages have all gone out so a delayed Logout response can finally be
+                    //sent.
+                    if self.logout_response_timeout.is_some() {
+                        self.send_logout_response(timer);
+                        continue;
+                    }
+
                     //If a Logout message was sent after an unrecoverable error, close the socket
                     //immediately.
                     if self.status.is_logging_out_with_error() {
@@ -431,26 +676,67 @@ impl InternalConnection {
                             timer.set_timeout(
                                 Duration::from_secs(AUTO_DISCONNECT_AFTER_LOGOUT_RESPONSE_SECS),
                                 (TimeoutType::HangUp,self.token)
-                            ).unwrap()
+                            )
                         );
                     }
                     break;
                 }
 
-                //Setup message to go out and serialize it.
+                //Never send anything once another engine process could have taken over the
+                //session. The lease can run out between renewals when renew() keeps failing.
+                if self.session_coordinator_acquired && !self.session_coordinator.as_ref().map_or(true,|session_coordinator| session_coordinator.is_active()) {
+                    let _ = self.socket.shutdown(Shutdown::Both);
+                    return Err(ConnectionTerminatedReason::SessionCoordinatorRevokedError);
+                }
+
+                //Setup message to go out and serialize it. Unless it waited too long.
                 let mut message = self.outbound_messages.remove(0);
-                message.message.setup_fixt_session_header(
-                    if message.auto_msg_seq_num {
-                        let result = Some(self.outbound_msg_seq_num);
-                        try!(self.increment_outbound_msg_seq_num());
-                        result
-                    } else { None },
-                    self.sender_comp_id.clone(),
-                    self.target_comp_id.clone()
-                );
+                if message.expires_at.map_or(false,|expires_at| timer.now() >= expires_at) {
+                    self.expired_messages.push(message.message);
+                    continue;
+                }
+                let msg_seq_num = if message.auto_msg_seq_num {
+                    let result = Some(self.outbound_msg_seq_num);
+                    try!(self.increment_outbound_msg_seq_num());
+                    result
+                } else { None };
                 let fix_version = self.fix_version;
                 let message_version = if let Some(message_version) = message.message_version { message_version } else { self.default_message_version };
-                message.message.read(fix_version,message_version,&mut self.outbound_buffer);
+                UTCTimestampFieldType::with_precision(self.timestamp_precision,|| {
+                    message.message.setup_fixt_session_header(
+                        msg_seq_num,
+                        self.sender_comp_id.clone(),
+                        self.target_comp_id.clone()
+                    );
+                    if let Some(ref message_signer) = self.message_signer {
+                        if let Some(signature) = message_signer.sign(&*message.message,fix_version,message_version) {
+                            message.message.set_signature(&signature[..]);
+                        }
+                    }
+                    message.message.read(fix_version,message_version,&mut self.outbound_buffer);
+                });
+                for message_log in &mut self.message_logs {
+                    let _ = message_log.write(MessageLogDirection::Outbound,fix_version,message_version,&*message.message);
+                }
+                if let Some(ref mut message_statistics) = self.outbound_message_statistics {
+                    message_statistics.record(&*message.message,fix_version,message_version,self.outbound_buffer.len());
+                }
+                if let (Some(msg_seq_num),Some(message_store)) = (msg_seq_num,self.message_store.as_mut()) {
+                    if let Err(e) = message_store.store_outbound(msg_seq_num,self.outbound_buffer.bytes()) {
+                        self.message_store_errors.push(e);
+                    }
+                    else if let Some(cl_ord_id) = order_entry_cl_ord_id(&*message.message) {
+                        if let Err(e) = message_store.store_order_sent(cl_ord_id) {
+                            self.message_store_errors.push(e);
+                        }
+                    }
+                }
+
+                //Only orders that actually went out count for the DuplicateOrderGuard. Ones that
+                //expired or were discarded while queued can be sent again with the same ClOrdID.
+                if let Some(ref mut sent_cl_ord_ids) = self.sent_cl_ord_ids {
+                    sent_cl_ord_ids.on_sent(&*message.message,timer.clock().utc_now());
+                }
 
                 //TODO: Hold onto message and pass it off to the engine or some callback so the
                 //library user knows exactly which messages have been sent -- although not
@@ -461,6 +747,7 @@ impl InternalConnection {
             match self.outbound_buffer.write(&mut self.socket) {
                 Ok(_) => {
                     sent_data = true;
+                    self.last_outbound_time = Some(timer.now());
 
                     //When data has been successfully sent, it's okay to start reading in new data
                     //again.
@@ -498,15 +785,70 @@ impl InternalConnection {
     }
 
     fn read(&mut self,timer: &mut Timer<(TimeoutType,Token)>) -> Result<(Vec<ConnectionReadMessage>),::std::io::Error> {
-        fn parse_bytes(connection: &mut InternalConnection,messages: &mut Vec<ConnectionReadMessage>) -> bool {
-            while !connection.inbound_buffer.is_empty() {
-                let (bytes_parsed,result) = connection.parser.parse(connection.inbound_buffer.bytes());
+        fn parse_bytes(connection: &mut InternalConnection,messages: &mut Vec<ConnectionReadMessage>,clock: &Clock) -> bool {
+            let inbound_buffer = if connection.inbound_decoder.is_some() {
+                &mut connection.decoded_inbound_buffer
+            }
+            else {
+                &mut connection.inbound_buffer
+            };
+
+            while !inbound_buffer.is_empty() {
+                //Look ahead at the header and throw away duplicates before spending any time
+                //parsing them. Only done between messages once the session is established so
+                //the expected inbound MsgSeqNum can't be lowered by a Logon later on. Everything
+                //that isn't obviously a duplicate falls through to the parser as normal.
+                let mut skipped_duplicate = None;
+                if connection.skip_duplicate_parsing && connection.status.is_established() && connection.parser.is_idle() {
+                    if let Some(prescanned_message) = connection.parser.prescan(inbound_buffer.bytes()) {
+                        if let Some(msg_seq_num) = prescanned_message.msg_seq_num {
+                            if prescanned_message.poss_dup &&
+                               msg_seq_num < connection.inbound_msg_seq_num &&
+                               prescanned_message.msg_type != SequenceReset::msg_type() &&
+                               prescanned_message.sender_comp_id == &connection.target_comp_id[..] &&
+                               prescanned_message.target_comp_id == &connection.sender_comp_id[..] {
+                                skipped_duplicate = Some((prescanned_message.length,prescanned_message.msg_type.to_vec(),msg_seq_num));
+                            }
+                        }
+                    }
+                }
+                if let Some((length,msg_type,msg_seq_num)) = skipped_duplicate {
+                    inbound_buffer.consume(length);
+                    connection.inbound_bytes_parsed += length as u64;
+                    messages.push(ConnectionReadMessage::SkippedDuplicate(msg_type,msg_seq_num));
+                    if messages.len() >= INBOUND_MESSAGES_BUFFER_LEN_MAX {
+                        return false;
+                    }
+                    continue;
+                }
+
+                let (bytes_parsed,result) = connection.parser.parse(inbound_buffer.bytes());
 
                 assert!(bytes_parsed > 0);
-                connection.inbound_buffer.consume(bytes_parsed);
+                inbound_buffer.consume(bytes_parsed);
+
+                //Record anything the parser threw away before it's lost for good.
+                let offset = connection.inbound_bytes_parsed;
+                connection.inbound_bytes_parsed += bytes_parsed as u64;
+                if let Some(ref mut quarantine_log) = connection.quarantine_log {
+                    for discarded in connection.parser.discarded.drain(..) {
+                        write_quarantine_entry(quarantine_log,clock.utc_now(),offset,&discarded);
+                    }
+                }
 
                 //Retain order by extracting messages and then the error from parser.
                 for message in connection.parser.messages.drain(..) {
+                    let (fix_version,message_version) = display_versions(message.meta());
+                    for message_log in &mut connection.message_logs {
+                        //A failed write shouldn't take down the connection.
+                        let _ = message_log.write(MessageLogDirection::Inbound,fix_version,message_version,&*message);
+                    }
+                    if let (Some(message_statistics),&Some(ref meta)) = (connection.inbound_message_statistics.as_mut(),message.meta()) {
+                        message_statistics.record(&*message,fix_version,message_version,wire_size(meta));
+                    }
+                    if let Some(ref mut rate_anomaly_detector) = connection.inbound_rate_anomaly {
+                        rate_anomaly_detector.record(message.msg_type());
+                    }
                     messages.push(ConnectionReadMessage::Message(message));
                 }
                 if let Err(e) = result {
@@ -530,8 +872,11 @@ impl InternalConnection {
             true
         }
 
+        //Bytes left over from before a decoder was set still need to be decoded.
+        try!(self.decode_inbound_buffer());
+
         let mut messages = Vec::new();
-        let mut keep_reading = parse_bytes(self,&mut messages);
+        let mut keep_reading = parse_bytes(self,&mut messages,&**timer.clock());
 
         //Don't read in any new messages for now. This happens when we can't write to the socket
         //right now. The block applies some back pressure and prevents us from being put into a
@@ -551,9 +896,11 @@ impl InternalConnection {
                         //Socket exhausted.
                         break;
                     }
+                    self.last_inbound_time = Some(timer.now());
 
                     //Parse all of the read bytes.
-                    keep_reading = parse_bytes(self,&mut messages);
+                    try!(self.decode_inbound_buffer());
+                    keep_reading = parse_bytes(self,&mut messages,&**timer.clock());
                 },
                 Err(e) => {
                     if let io::ErrorKind::WouldBlock = e.kind() {
@@ -570,13 +917,358 @@ impl InternalConnection {
         //be called unless data is available (due to poll()) so we don't have to check if any data
         //bytes were actually read.
         reset_inbound_timeout(timer,&mut self.inbound_testrequest_timeout,&self.inbound_testrequest_timeout_duration,&self.token);
+        self.reset_probe_timeout(timer);
+        reset_timeout(timer,&mut self.inbound_quiet_period_timeout,&self.inbound_quiet_period,TimeoutType::InboundQuietPeriod,&self.token);
 
         Ok(messages)
     }
 
+    fn decode_inbound_buffer(&mut self) -> Result<(),io::Error> {
+        //Move everything in inbound_buffer through the decoder and into decoded_inbound_buffer.
+        //Decoded bytes the parser hasn't gotten to yet are kept in front.
+        if self.inbound_buffer.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(ref mut inbound_decoder) = self.inbound_decoder {
+            let inbound_buffer = &mut self.inbound_buffer;
+            let result = self.decoded_inbound_buffer.append_all(|decoded_bytes| inbound_decoder.decode(inbound_buffer.bytes(),decoded_bytes));
+            inbound_buffer.clear();
+            try!(result);
+        }
+
+        Ok(())
+    }
+
+    fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            fix_version: self.fix_version,
+            default_message_version: self.default_message_version,
+            heartbeat_interval: self.outbound_heartbeat_timeout_duration,
+            inbound_msg_seq_num: self.inbound_msg_seq_num,
+            outbound_msg_seq_num: self.outbound_msg_seq_num,
+            inbound_bytes_parsed: self.inbound_bytes_parsed,
+            inbound_filler_bytes_skipped: self.parser.filler_byte_count(),
+            inbound_acknowledged_msg_seq_num: self.inbound_acknowledged_msg_seq_num,
+            session_id: self.session_id(),
+        }
+    }
+
+    fn session_id(&self) -> SessionID {
+        SessionID {
+            fix_version: self.fix_version,
+            sender_comp_id: self.sender_comp_id.clone(),
+            target_comp_id: self.target_comp_id.clone(),
+            session_qualifier: self.session_qualifier.clone(),
+        }
+    }
+
+    fn publish_health_check(&self) {
+        //HealthCheck::checked_time is filled in by HealthMonitor when the HealthCheck is taken.
+        let mut health_check = self.health_check.lock().unwrap();
+        health_check.established = self.status.is_established();
+        health_check.last_inbound_time = self.last_inbound_time;
+        health_check.last_outbound_time = self.last_outbound_time;
+        health_check.inbound_msg_seq_num = self.inbound_msg_seq_num;
+        health_check.outbound_msg_seq_num = self.outbound_msg_seq_num;
+    }
+
+    fn notify_flush_waiters(&mut self) {
+        //Messages held back by Engine::pause_sending() aren't pending because they won't be
+        //written until sending is resumed.
+        if self.flush_waiters.is_empty() ||
+           !self.outbound_messages.is_empty() ||
+           !self.outbound_buffer.is_empty() ||
+           !self.resend_chunk_messages.is_empty() {
+            return;
+        }
+
+        for flush_waiter in self.flush_waiters.drain(..) {
+            let _ = flush_waiter.send(true); //Engine might have stopped waiting.
+        }
+    }
+
+    fn sync_message_store(&mut self) {
+        //Persist the MsgSeqNums only when they have changed so an idle connection doesn't touch
+        //the store.
+        if let Some(ref mut message_store) = self.message_store {
+            if message_store.next_inbound_msg_seq_num() != self.inbound_msg_seq_num {
+                if let Err(e) = message_store.set_next_inbound_msg_seq_num(self.inbound_msg_seq_num) {
+                    self.message_store_errors.push(e);
+                }
+            }
+            if message_store.next_outbound_msg_seq_num() != self.outbound_msg_seq_num {
+                if let Err(e) = message_store.set_next_outbound_msg_seq_num(self.outbound_msg_seq_num) {
+                    self.message_store_errors.push(e);
+                }
+            }
+        }
+    }
+
+    fn load_sent_cl_ord_ids(&mut self,now: DateTime<UTC>) {
+        //Recover the ClOrdIDs of NewOrderSingles sent within the DuplicateOrderGuard's window from
+        //the message store so duplicates are still caught after a restart. Stored messages are read
+        //newest first, a batch at a time, and reading stops at the first one sent before the
+        //window so the rest of the store is never parsed. Stored messages that can no longer be
+        //parsed are skipped.
+        let mut sent_cl_ord_ids = match self.sent_cl_ord_ids {
+            Some(ref sent_cl_ord_ids) => SentClOrdIDs::new(sent_cl_ord_ids.guard()),
+            None => return,
+        };
+        let message_store = match self.message_store {
+            Some(ref mut message_store) => message_store,
+            None => return,
+        };
+
+        let parser = self.message_store_parser.as_mut().expect("Message store parser should be set");
+        parser.set_default_message_version(self.default_message_version);
+
+        let mut sent_messages = Vec::new(); //Newest first.
+        let mut end_msg_seq_num = message_store.next_outbound_msg_seq_num();
+        let mut is_before_window = false;
+        while !is_before_window && end_msg_seq_num > 1 {
+            let begin_msg_seq_num = cmp::max(end_msg_seq_num.saturating_sub(LOAD_SENT_CL_ORD_IDS_BATCH_LEN),1);
+            let stored_messages = match message_store.outbound_messages(begin_msg_seq_num..end_msg_seq_num) {
+                Ok(stored_messages) => stored_messages,
+                Err(e) => {
+                    self.message_store_errors.push(e);
+                    return;
+                },
+            };
+            end_msg_seq_num = begin_msg_seq_num;
+
+            for (_,bytes) in stored_messages.into_iter().rev() {
+                let (_,result) = parser.parse(&bytes);
+                if result.is_ok() {
+                    for message in parser.messages.drain(..) {
+                        if sent_cl_ord_ids.is_within_window(message.sending_time(),now) {
+                            sent_messages.push(message);
+                        }
+                        else {
+                            is_before_window = true;
+                        }
+                    }
+                }
+                parser.messages.clear();
+                parser.reset_parser();
+
+                if is_before_window {
+                    break;
+                }
+            }
+        }
+
+        //Oldest first so SentClOrdIDs can expire them in order.
+        for message in sent_messages.iter().rev() {
+            sent_cl_ord_ids.on_sent(&**message,message.sending_time());
+        }
+        self.sent_cl_ord_ids = Some(sent_cl_ord_ids);
+    }
+
+    fn store_order_ack(&mut self,message: &FIXTMessage) {
+        //Remember which orders the counterparty has responded to so order_entry_status() still
+        //knows after a restart.
+        let message = message.as_any();
+        let cl_ord_id = if let Some(execution_report) = message.downcast_ref::<ExecutionReport>() {
+            &execution_report.cl_ord_id
+        }
+        else if let Some(order_cancel_reject) = message.downcast_ref::<OrderCancelReject>() {
+            &order_cancel_reject.cl_ord_id
+        }
+        else {
+            return;
+        };
+
+        if cl_ord_id.is_empty() {
+            return;
+        }
+
+        if let Some(ref mut message_store) = self.message_store {
+            if let Err(e) = message_store.store_order_ack(cl_ord_id) {
+                self.message_store_errors.push(e);
+            }
+        }
+    }
+
+    fn order_entry_status(&mut self,cl_ord_id: &[u8]) -> Option<OrderEntryStatus> {
+        //Answered from the ClOrdIDs the message store keeps track of so stored messages don't have
+        //to be parsed.
+        let message_store = match self.message_store {
+            Some(ref mut message_store) => message_store,
+            None => return None,
+        };
+
+        let result = match message_store.is_order_acknowledged(cl_ord_id) {
+            Ok(true) => Ok(OrderEntryStatus::Acknowledged),
+            Ok(false) => message_store.is_order_sent(cl_ord_id).map(|is_sent| if is_sent { OrderEntryStatus::Sent } else { OrderEntryStatus::NotSent }),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(order_entry_status) => Some(order_entry_status),
+            Err(e) => {
+                self.message_store_errors.push(e);
+                None
+            },
+        }
+    }
+
+    fn message_store_resend_response(&mut self,range: Range<MsgSeqNumType>) -> Vec<ResendResponse> {
+        //Build the same response the application would pass to Engine::send_resend_response()
+        //using the messages in the message store. Administrative messages (except Reject), messages
+        //missing from the store and messages that can no longer be parsed are gap filled. Adjacent
+        //gaps are combined into a single SequenceReset-GapFill.
+        let stored_messages = match self.message_store.as_mut().expect("Message store should be set").outbound_messages(range.clone()) {
+            Ok(stored_messages) => stored_messages,
+            Err(e) => {
+                self.message_store_errors.push(e);
+                Vec::new()
+            },
+        };
+
+        let parser = self.message_store_parser.as_mut().expect("Message store parser should be set");
+        parser.set_default_message_version(self.default_message_version);
+
+        let mut response = Vec::new();
+        let mut gap_begin = None;
+        let mut next_msg_seq_num = range.start;
+        for (msg_seq_num,bytes) in stored_messages {
+            if msg_seq_num > next_msg_seq_num {
+                gap_begin = gap_begin.or(Some(next_msg_seq_num));
+            }
+            next_msg_seq_num = msg_seq_num + 1;
+
+            let (_,result) = parser.parse(&bytes);
+            let message = if result.is_ok() && !parser.messages.is_empty() { Some(parser.messages.remove(0)) } else { None };
+            parser.messages.clear();
+            parser.reset_parser();
+
+            let is_resendable = match message {
+                Some(ref message) => message.msg_type() == Reject::msg_type() || !administrative_msg_types().contains(&message.msg_type()),
+                None => false,
+            };
+            if !is_resendable {
+                gap_begin = gap_begin.or(Some(msg_seq_num));
+                continue;
+            }
+            let message = message.unwrap();
+
+            if let Some(gap_begin) = gap_begin.take() {
+                response.push(ResendResponse::Gap(gap_begin..msg_seq_num));
+            }
+            let message_version = message.meta().as_ref().map(|meta| meta.message_version);
+            response.push(ResendResponse::Message(message_version,message));
+        }
+        if next_msg_seq_num < range.end {
+            gap_begin = gap_begin.or(Some(next_msg_seq_num));
+        }
+        if let Some(gap_begin) = gap_begin {
+            response.push(ResendResponse::Gap(gap_begin..range.end));
+        }
+
+        response
+    }
+
+    fn queue_resend_response(&mut self,response: Vec<ResendResponse>,timer: &mut Timer<(TimeoutType,Token)>,tx: &Sender<EngineEvent>) {
+        //TODO: It might make sense to take these responses as a group and do a sorted insert into
+        //outbound_messages. This way we at least try to prevent excessive ResendRequests from
+        //being sent to us later.
+        let mut summary = ResendSummary {
+            requested: self.inbound_resend_request_range.take(),
+            messages_resent: 0,
+            gap_fills_sent: 0,
+            gap_filled_msg_seq_nums: 0,
+        };
+        for message in response {
+            match message {
+                ResendResponse::Message(message_version,mut message) => {
+                    summary.messages_resent += 1;
+
+                    //Make sure message is marked as a potential duplicate or else we'll trigger an
+                    //InboundMsgSeqNumLowerThanExpectedError or equivalent on the other side of the
+                    //connection.
+                    message.set_is_poss_dup(true);
+                    let orig_sending_time = message.sending_time();
+                    message.set_orig_sending_time(orig_sending_time);
+
+                    let mut outbound_message = OutboundMessage::from_box(message);
+                    outbound_message.message_version = message_version;
+                    outbound_message.auto_msg_seq_num = false; //We must preserve MsgSeqNum for response.
+                    self.resend_chunk_messages.push(outbound_message);
+                },
+                ResendResponse::Gap(range) => {
+                    summary.gap_fills_sent += 1;
+                    summary.gap_filled_msg_seq_nums += range.end - range.start;
+
+                    let mut sequence_reset = SequenceReset::new();
+                    sequence_reset.gap_fill_flag = true;
+                    sequence_reset.msg_seq_num = range.start;
+                    sequence_reset.new_seq_no = range.end;
+                    self.resend_chunk_messages.push(OutboundMessage::new(sequence_reset,false));
+                },
+            }
+        }
+        self.resend_summaries.push(summary);
+        self.send_resend_chunk(timer,tx);
+    }
+
+    fn reset_probe_timeout(&mut self,timer: &mut Timer<(TimeoutType,Token)>) {
+        //Any received data counts as a response to all outstanding probes.
+        self.probe_unanswered_count = 0;
+
+        let quiet_period = self.probe_settings.map(|probe_settings| probe_settings.quiet_period);
+        reset_timeout(
+            timer,
+            &mut self.probe_timeout,
+            &quiet_period,
+            TimeoutType::Probe,
+            &self.token
+        );
+    }
+
+    fn warm_up(&mut self,mut message: Box<FIXTMessage + Send>,message_version: Option<MessageVersion>) {
+        //Pre-touch the outbound path by serializing the message into the outbound buffer. The
+        //buffer keeps its allocation after being cleared so the first real message doesn't have to
+        //grow it. This can only be done when nothing is waiting to be sent.
+        if !self.outbound_buffer.is_empty() {
+            return;
+        }
+
+        self.outbound_messages.reserve(OUTBOUND_MESSAGES_WARM_UP_CAPACITY);
+
+        //MsgSeqNum is filled in but not incremented because the message is never sent.
+        let message_version = if let Some(message_version) = message_version { message_version } else { self.default_message_version };
+        UTCTimestampFieldType::with_precision(self.timestamp_precision,|| {
+            message.setup_fixt_session_header(
+                Some(self.outbound_msg_seq_num),
+                self.sender_comp_id.clone(),
+                self.target_comp_id.clone()
+            );
+            message.read(self.fix_version,message_version,&mut self.outbound_buffer);
+        });
+
+        //Exercise the parse path with the serialized message. This is skipped if the parser is in
+        //the middle of a message received over the network so it isn't corrupted. The result
+        //doesn't matter. For example, the message type might not be in the dictionary.
+        if self.parser.is_idle() {
+            let keep_discarded_bytes = self.parser.keep_discarded_bytes();
+            self.parser.set_keep_discarded_bytes(false);
+            let _ = self.parser.parse(self.outbound_buffer.bytes());
+            self.parser.messages.clear();
+            self.parser.reset_parser();
+            self.parser.set_keep_discarded_bytes(keep_discarded_bytes);
+        }
+
+        self.outbound_buffer.clear();
+    }
+
     fn shutdown(&mut self) {
         let _ = self.socket.shutdown(Shutdown::Both);
+        self.save_outbox();
         self.outbound_messages.clear();
+        self.outbound_paused_messages.clear();
+        self.resend_chunk_messages.clear();
+        self.resend_summaries.clear();
+        self.resend_held_messages.clear();
         self.outbound_buffer.clear();
     }
 
@@ -594,7 +1286,13 @@ impl InternalConnection {
 
         //TODO: The clearing of outbound messages might be optional. Probably need a receipt or
         //something for those that are left unprocessed.
+        if let LoggingOutType::Error(_) = logging_out_type {
+            //Connection is about to be lost so keep what didn't go out. See OutboxPolicy.
+            self.save_outbox();
+        }
         self.outbound_messages.clear(); //TODO: May want to store unprocessed messages so engine knows what didn't go out.
+        self.outbound_paused_messages.clear();
+        self.resend_held_messages.clear();
         self.outbound_messages.push(OutboundMessage::from(logout));
 
         //If attempting to logout cleanly, setup timer to auto-logout if we don't get a Logout
@@ -604,22 +1302,230 @@ impl InternalConnection {
                 timer.set_timeout(
                     Duration::from_secs(AUTO_DISCONNECT_AFTER_INITIATING_LOGOUT_SECS),
                     (TimeoutType::Logout,self.token)
-                ).unwrap()
+                )
             );
         }
 
         self.status = ConnectionStatus::LoggingOut(logging_out_type);
     }
 
-    fn respond_to_logout(&mut self) {
+    fn send_resend_chunk(&mut self,timer: &mut Timer<(TimeoutType,Token)>,tx: &Sender<EngineEvent>) {
+        //Move the next chunk of resent messages into the outbound queue and schedule the chunk
+        //after it, if any. The resend is only reported as completed after the last chunk.
+        let (chunk_size,chunk_interval) = match self.resend_request_limits {
+            Some(ref resend_request_limits) if resend_request_limits.chunk_size > 0 => (resend_request_limits.chunk_size,resend_request_limits.chunk_interval),
+            _ => (self.resend_chunk_messages.len(),Duration::from_secs(0)), //Limits were removed. Send everything.
+        };
+
+        let chunk_size = cmp::min(chunk_size,self.resend_chunk_messages.len());
+        let remaining_messages = self.resend_chunk_messages.split_off(chunk_size);
+        let chunk_messages = mem::replace(&mut self.resend_chunk_messages,remaining_messages);
+        self.outbound_messages.extend(chunk_messages);
+
+        //Application messages sent during the resend go out after the last chunk so they can't
+        //be mixed in with the resent MsgSeqNums.
+        if self.resend_chunk_messages.is_empty() {
+            let held_messages = mem::replace(&mut self.resend_held_messages,Vec::new());
+            if self.outbound_paused {
+                self.outbound_paused_messages.extend(held_messages);
+            }
+            else {
+                self.outbound_messages.extend(held_messages);
+            }
+
+            let connection = self.as_connection();
+            for summary in self.resend_summaries.drain(..) {
+                tx.send(EngineEvent::ResendCompleted(connection,summary)).unwrap();
+            }
+        }
+
+        if let Some(resend_chunk_timeout) = self.resend_chunk_timeout.take() {
+            timer.cancel_timeout(&resend_chunk_timeout);
+        }
+        if !self.resend_chunk_messages.is_empty() {
+            self.resend_chunk_timeout = Some(
+                timer.set_timeout(chunk_interval,(TimeoutType::ResendChunk,self.token))
+            );
+        }
+    }
+
+    fn respond_to_logout(&mut self,timer: &mut Timer<(TimeoutType,Token)>) {
         assert!(self.status.is_established() || self.status.is_logging_out_with_resending_request_initiated_by_remote());
 
+        //Decide what happens to any messages that haven't been sent yet.
+        match self.logout_response_policy {
+            LogoutResponsePolicy::Flush => {},
+            LogoutResponsePolicy::Discard => self.discard_outbound_messages(),
+            LogoutResponsePolicy::Delay(duration) => {
+                if !self.outbound_messages.is_empty() {
+                    //Logout response is sent by write() once the queue is empty or by
+                    //TimeoutType::LogoutResponse, whichever happens first.
+                    if self.logout_response_timeout.is_none() {
+                        self.logout_response_timeout = Some(
+                            timer.set_timeout(duration,(TimeoutType::LogoutResponse,self.token))
+                        );
+                    }
+                    return;
+                }
+            },
+        }
+
+        self.send_logout_response(timer);
+    }
+
+    fn send_logout_response(&mut self,timer: &mut Timer<(TimeoutType,Token)>) {
+        if let Some(logout_response_timeout) = self.logout_response_timeout.take() {
+            timer.cancel_timeout(&logout_response_timeout);
+        }
+
+        //Paused messages can never go out once logging out.
+        let paused_messages = mem::replace(&mut self.outbound_paused_messages,Vec::new());
+        self.unsent_messages.extend(paused_messages.into_iter().map(|outbound_message| outbound_message.message));
+        let held_messages = mem::replace(&mut self.resend_held_messages,Vec::new());
+        self.unsent_messages.extend(held_messages.into_iter().map(|outbound_message| outbound_message.message));
+
         let logout = Logout::new();
         self.outbound_messages.push(OutboundMessage::from(logout));
 
         self.status = ConnectionStatus::LoggingOut(LoggingOutType::Responding);
     }
 
+    fn save_outbox(&mut self) {
+        //Move every application message that hasn't been sent yet into the outbox. Administrative
+        //messages and resent messages only make sense on the connection they were made for.
+        for outbound_message in self.outbound_messages.drain(..).chain(self.outbound_paused_messages.drain(..)).chain(self.resend_held_messages.drain(..)) {
+            if outbound_message.auto_msg_seq_num && !administrative_msg_types().contains(&outbound_message.message.msg_type()) {
+                self.outbox.push(outbound_message);
+            }
+        }
+    }
+
+    fn on_session_established_outbox(&mut self,tx: &Sender<EngineEvent>) {
+        //Deal with any messages left over from an earlier connection of the same session.
+        if self.outbox.is_empty() {
+            return;
+        }
+
+        match self.outbox_policy {
+            OutboxPolicy::Discard => self.discard_outbox(),
+            OutboxPolicy::Resend => self.release_outbox(),
+            OutboxPolicy::Hold => tx.send(EngineEvent::MessagesHeld(self.as_connection(),self.outbox.len())).unwrap(),
+        }
+    }
+
+    fn release_outbox(&mut self) {
+        let outbox = mem::replace(&mut self.outbox,Vec::new());
+        if self.outbound_paused {
+            self.outbound_paused_messages.extend(outbox);
+        }
+        else {
+            self.outbound_messages.extend(outbox);
+        }
+    }
+
+    fn discard_outbox(&mut self) {
+        let outbox = mem::replace(&mut self.outbox,Vec::new());
+        self.unsent_messages.extend(outbox.into_iter().map(|outbound_message| outbound_message.message));
+    }
+
+    fn discard_outbound_messages(&mut self) {
+        let outbound_messages = mem::replace(&mut self.outbound_messages,Vec::new());
+        self.unsent_messages.extend(outbound_messages.into_iter().map(|outbound_message| outbound_message.message));
+    }
+
+    fn acquire_session_coordinator(&mut self,timer: &mut Timer<(TimeoutType,Token)>,tx: &Sender<EngineEvent>) {
+        //Only let the waiting Logon go out when no other engine process is active for this
+        //session. When the SessionCoordinator can't answer yet, try again shortly instead of
+        //blocking the engine thread.
+        let logon = match self.session_coordinator_logon.take() {
+            Some(logon) => logon,
+            None => return,
+        };
+        let session_coordinator = match self.session_coordinator {
+            Some(ref session_coordinator) => session_coordinator.clone(),
+            None => {
+                //Coordination was turned off while waiting.
+                self.outbound_messages.push(logon);
+                return;
+            },
+        };
+
+        if let Some(timeout) = self.session_coordinator_timeout.take() {
+            timer.cancel_timeout(&timeout);
+        }
+        match session_coordinator.try_acquire() {
+            Ok(true) => {
+                self.session_coordinator_acquired = true;
+                self.session_coordinator_timeout = Some(
+                    timer.set_timeout(
+                        session_coordinator.renew_interval(),
+                        (TimeoutType::SessionCoordinatorRenew,self.token)
+                    )
+                );
+                self.outbound_messages.push(logon);
+            },
+            Ok(false) => {
+                tx.send(EngineEvent::LogonRefusedByCoordinator(self.as_connection(),logon.message)).unwrap();
+            },
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.session_coordinator_logon = Some(logon);
+                self.session_coordinator_timeout = Some(
+                    timer.set_timeout(
+                        Duration::from_millis(SESSION_COORDINATOR_RETRY_MS),
+                        (TimeoutType::SessionCoordinatorRetry,self.token)
+                    )
+                );
+            },
+            Err(e) => {
+                tx.send(EngineEvent::SessionCoordinatorError(self.as_connection(),e)).unwrap();
+                tx.send(EngineEvent::LogonRefusedByCoordinator(self.as_connection(),logon.message)).unwrap();
+            },
+        }
+    }
+
+    fn renew_session_coordinator(&mut self,timer: &mut Timer<(TimeoutType,Token)>,tx: &Sender<EngineEvent>) -> Result<(),ConnectionTerminatedReason> {
+        //Confirm this is still the only engine process logged on to the session. A busy
+        //SessionCoordinator is retried shortly for as long as the claim hasn't expired locally.
+        self.session_coordinator_timeout = None;
+        let session_coordinator = match self.session_coordinator {
+            Some(ref session_coordinator) => session_coordinator.clone(),
+            None => return Ok(()), //Coordination was turned off after timeout was set.
+        };
+
+        let renew_interval = match session_coordinator.renew() {
+            Ok(true) => session_coordinator.renew_interval(),
+            Ok(false) => return Err(ConnectionTerminatedReason::SessionCoordinatorRevokedError),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock && session_coordinator.is_active() => Duration::from_millis(SESSION_COORDINATOR_RETRY_MS),
+            Err(e) => {
+                tx.send(EngineEvent::SessionCoordinatorError(self.as_connection(),e)).unwrap();
+                return Err(ConnectionTerminatedReason::SessionCoordinatorRevokedError);
+            },
+        };
+        self.session_coordinator_timeout = Some(
+            timer.set_timeout(
+                renew_interval,
+                (TimeoutType::SessionCoordinatorRenew,self.token)
+            )
+        );
+
+        Ok(())
+    }
+
+    fn release_session_coordinator(&mut self,tx: &Sender<EngineEvent>) {
+        //Step down so a standby engine process can take over without waiting for the claim to
+        //expire.
+        if !self.session_coordinator_acquired {
+            return;
+        }
+        self.session_coordinator_acquired = false;
+
+        if let Some(ref session_coordinator) = self.session_coordinator {
+            if let Err(e) = session_coordinator.release() {
+                tx.send(EngineEvent::SessionCoordinatorError(self.as_connection(),e)).unwrap();
+            }
+        }
+    }
+
     fn increment_outbound_msg_seq_num(&mut self) -> Result<(),ConnectionTerminatedReason> {
         //Check for overflow before incrementing. Just force the connection to terminate if this
         //occurs. This number is so large that the only way it can be reached is if the other party
@@ -643,13 +1549,87 @@ impl InternalConnection {
         Ok(())
     }
 
+    fn request_resend(&mut self,begin_seq_no: MsgSeqNumType,end_seq_no: MsgSeqNumType) {
+        //Ask the remote for the messages in [begin_seq_no,end_seq_no]. end_seq_no is only sent
+        //when using ResendRequestRange::Exact.
+        let mut resend_request = ResendRequest::new();
+        resend_request.begin_seq_no = begin_seq_no;
+        resend_request.end_seq_no = match self.resend_request_range {
+            ResendRequestRange::OpenEnded => 0,
+            ResendRequestRange::Exact => end_seq_no,
+        };
+        self.outbound_messages.push(OutboundMessage::from(resend_request));
+
+        self.inbound_resend_request_begin_seq_no = begin_seq_no;
+        self.inbound_resend_request_end_seq_no = end_seq_no;
+    }
+
+    fn request_missing(&mut self,last_missing_seq_no: MsgSeqNumType,timer: &mut Timer<(TimeoutType,Token)>) {
+        //Ask for the messages from the next expected MsgSeqNum through last_missing_seq_no while
+        //skipping anything that has already been asked for. This keeps a burst of gaps from
+        //turning into a storm of overlapping ResendRequests.
+
+        //Still waiting to coalesce an earlier gap. Just extend it.
+        if let Some(ref mut pending_resend_request) = self.pending_resend_request {
+            pending_resend_request.end = cmp::max(pending_resend_request.end,last_missing_seq_no + 1);
+            return;
+        }
+
+        let mut begin_seq_no = self.inbound_msg_seq_num;
+        if self.inbound_resend_request_msg_seq_num.is_some() {
+            match self.resend_request_range {
+                ResendRequestRange::OpenEnded => {
+                    //An outstanding open-ended ResendRequest already covers everything. Only ask
+                    //again once the remote has answered part of it and then moved on. Some
+                    //counterparties answer open-ended requests incrementally like this instead of
+                    //all at once.
+                    if self.inbound_resend_request_begin_seq_no == self.inbound_msg_seq_num {
+                        return;
+                    }
+                },
+                ResendRequestRange::Exact => {
+                    begin_seq_no = cmp::max(begin_seq_no,self.inbound_resend_request_end_seq_no + 1);
+                },
+            }
+        }
+        if begin_seq_no > last_missing_seq_no {
+            return;
+        }
+
+        if let Some(resend_request_coalesce_interval) = self.resend_request_coalesce_interval {
+            self.pending_resend_request = Some(begin_seq_no..last_missing_seq_no + 1);
+            self.resend_request_coalesce_timeout = Some(
+                timer.set_timeout(resend_request_coalesce_interval,(TimeoutType::ResendRequestCoalesce,self.token))
+            );
+        }
+        else {
+            self.request_resend(begin_seq_no,last_missing_seq_no);
+        }
+    }
+
+    fn send_pending_resend_request(&mut self) {
+        if let Some(pending_resend_request) = self.pending_resend_request.take() {
+            //Part or all of the gap might have been filled while waiting.
+            let begin_seq_no = cmp::max(pending_resend_request.start,self.inbound_msg_seq_num);
+            if self.inbound_resend_request_msg_seq_num.is_some() && begin_seq_no < pending_resend_request.end {
+                self.request_resend(begin_seq_no,pending_resend_request.end - 1);
+            }
+        }
+    }
+
     fn clear_inbound_resend_request_msg_seq_num(&mut self,timer: &mut Timer<(TimeoutType,Token)>) {
         self.inbound_resend_request_msg_seq_num = None;
 
+        //Nothing is missing anymore so don't bother asking for it.
+        self.pending_resend_request = None;
+        if let Some(resend_request_coalesce_timeout) = self.resend_request_coalesce_timeout.take() {
+            timer.cancel_timeout(&resend_request_coalesce_timeout);
+        }
+
         //If remote started a logout, we noticed missing messaged, and have now
         //received all of those messages, finally respond to logout.
         if self.status.is_logging_out_with_resending_request_initiated_by_remote() {
-            self.respond_to_logout();
+            self.respond_to_logout(timer);
         }
         //Same as above except engine initiated logout and suspended it long enough to
         //retrieve messages.
@@ -670,7 +1650,7 @@ impl InternalConnection {
         self.inbound_blocked_timeout = Some(timer.set_timeout(
             Duration::from_secs(AUTO_DISCONNECT_AFTER_WRITE_BLOCKS_SECS),
             (TimeoutType::InboundBlocked,self.token)
-        ).unwrap());
+        ));
     }
 
     fn end_blocking_inbound(&mut self,timer: &mut Timer<(TimeoutType,Token)>,network_read_retry: &mut NetworkReadRetry) {
@@ -690,13 +1670,41 @@ impl InternalConnection {
     }
 
     fn as_connection(&self) -> Connection {
-        Connection(self.token.0)
+        Connection {
+            id: self.token.0,
+            generation: self.generation,
+        }
     }
 }
 
 macro_rules! try_write_connection_or_terminate {
     ( $connection_entry:ident, $internal_thread:ident ) => {
-        if let Err(e) = $connection_entry.get_mut().write(&mut $internal_thread.timer,&mut $internal_thread.network_read_retry) {
+        let result = $connection_entry.get_mut().write(&mut $internal_thread.timer,&mut $internal_thread.network_read_retry);
+
+        //Report any messages that were dropped while responding to a Logout.
+        if !$connection_entry.get().unsent_messages.is_empty() {
+            let unsent_messages = mem::replace(&mut $connection_entry.get_mut().unsent_messages,Vec::new());
+            $internal_thread.tx.send(EngineEvent::MessagesNotSent($connection_entry.get().as_connection(),unsent_messages)).unwrap();
+        }
+
+        //Report any messages that weren't sent in time.
+        if !$connection_entry.get().expired_messages.is_empty() {
+            let expired_messages = mem::replace(&mut $connection_entry.get_mut().expired_messages,Vec::new());
+            $internal_thread.tx.send(EngineEvent::MessagesExpired($connection_entry.get().as_connection(),expired_messages)).unwrap();
+        }
+
+        //Persist any MsgSeqNum changes and report problems with the message store.
+        $connection_entry.get_mut().sync_message_store();
+        for e in mem::replace(&mut $connection_entry.get_mut().message_store_errors,Vec::new()) {
+            $internal_thread.tx.send(EngineEvent::MessageStoreError($connection_entry.get().as_connection(),e)).unwrap();
+        }
+
+        //Every kind of event ends up writing so this is a convenient place to keep the health
+        //check up to date.
+        $connection_entry.get().publish_health_check();
+        $connection_entry.get_mut().notify_flush_waiters();
+
+        if let Err(e) = result {
             return Err(ConnectionEventError::TerminateConnection($connection_entry.remove(),e));
         }
     }
@@ -706,12 +1714,50 @@ struct InternalListener {
     socket: TcpListener,
     token: Token,
     sender_comp_id: <<SenderCompID as Field>::Type as FieldType>::Type,
+    settings: ListenerSettings,
+    connection_attempts: HashMap<IpAddr,VecDeque<Instant>>, //Recent connection times per address. Only kept when settings.connection_attempt_limit is set.
 }
 
 impl InternalListener {
     fn as_listener(&self) -> Listener {
         Listener(self.token.0)
     }
+
+    fn check_connection(&mut self,address: &SocketAddr,unauthenticated_connection_count: usize,now: Instant) -> Option<ConnectionRefusedReason> {
+        let ip = address.ip();
+        if self.settings.denied_addresses.contains(&ip) ||
+           (!self.settings.allowed_addresses.is_empty() && !self.settings.allowed_addresses.contains(&ip)) {
+            return Some(ConnectionRefusedReason::AddressNotAllowed);
+        }
+
+        if let Some(connection_attempt_limit) = self.settings.connection_attempt_limit {
+            //Forget attempts that are too old to matter so addresses that stop connecting don't
+            //stick around forever.
+            self.connection_attempts.retain(|_,attempts| {
+                while attempts.front().map_or(false,|attempt| now.duration_since(*attempt) >= connection_attempt_limit.interval) {
+                    attempts.pop_front();
+                }
+
+                !attempts.is_empty()
+            });
+
+            //Refused attempts count too. Otherwise, a scanner could keep trying as fast as it
+            //likes and get through every time the oldest attempt expires.
+            let attempts = self.connection_attempts.entry(ip).or_insert_with(VecDeque::new);
+            attempts.push_back(now);
+            if attempts.len() > connection_attempt_limit.max_attempts as usize {
+                return Some(ConnectionRefusedReason::TooManyAttempts);
+            }
+        }
+
+        if let Some(max_unauthenticated_connections) = self.settings.max_unauthenticated_connections {
+            if unauthenticated_connection_count >= max_unauthenticated_connections {
+                return Some(ConnectionRefusedReason::TooManyUnauthenticatedConnections);
+            }
+        }
+
+        None
+    }
 }
 
 struct InternalThread {
@@ -719,12 +1765,15 @@ struct InternalThread {
     token_generator: Arc<Mutex<TokenGenerator>>,
     tx: Sender<EngineEvent>,
     rx: Receiver<InternalEngineToThreadEvent>,
-    message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
+    message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>, //Shared by every connection's parser.
     max_message_size: u64,
     connections: HashMap<Token,InternalConnection>,
     listeners: HashMap<Token,InternalListener>,
     timer: Timer<(TimeoutType,Token)>,
     network_read_retry: NetworkReadRetry,
+    middleware: MiddlewareChain,
+    health_checks: HealthCheckRegistry,
+    outboxes: HashMap<SessionID,Vec<OutboundMessage>>, //Unsent messages from lost connections waiting for the next connection of the same session.
 }
 
 impl InternalThread {
@@ -736,23 +1785,27 @@ impl InternalThread {
 
         match engine_event {
             //Engine wants to setup a new connection.
-            InternalEngineToThreadEvent::NewConnection(token,fix_version,default_message_version,sender_comp_id,target_comp_id,address) => {
+            InternalEngineToThreadEvent::NewConnection(connection,default_message_version,session_id,address) => {
+                let token = Token(connection.id);
                 let socket = match TcpStream::connect(&address) {
                     Ok(socket) => socket,
                     Err(e) => {
-                        self.tx.send(EngineEvent::ConnectionFailed(Connection(token.0),e)).unwrap();
+                        self.tx.send(EngineEvent::ConnectionFailed(connection,e)).unwrap();
                         return Ok(())
                     },
                 };
 
-                let connection = InternalConnection::new(self.message_dictionary.clone(),
+                let mut connection = InternalConnection::new(self.message_dictionary.clone(),
                                                          self.max_message_size,
-                                                         fix_version,
+                                                         session_id.fix_version,
                                                          default_message_version,
                                                          socket,
                                                          token,
-                                                         sender_comp_id,
-                                                         target_comp_id);
+                                                         connection.generation,
+                                                         session_id.sender_comp_id.clone(),
+                                                         session_id.target_comp_id.clone(),
+                                                         address);
+                connection.session_qualifier = session_id.session_qualifier.clone();
 
                 //Have poll let us know when we can can read or write.
                 if let Err(e) = self.poll.register(&connection.socket,
@@ -762,92 +1815,598 @@ impl InternalThread {
                     self.tx.send(EngineEvent::ConnectionFailed(connection.as_connection(),e)).unwrap();
                     return Ok(())
                 }
-
-                self.connections.insert(token,connection);
+
+                //Pick up any messages left over from an earlier connection of the same session.
+                if let Some(outbox) = self.outboxes.remove(&session_id) {
+                    connection.outbox = outbox;
+                }
+
+                self.health_checks.lock().unwrap().insert(connection.as_connection(),connection.health_check.clone());
+                self.connections.insert(token,connection);
+            },
+            //Engine wants to setup a listener to accept new connections.
+            InternalEngineToThreadEvent::NewListener(token,sender_comp_id,socket,settings) => {
+                let listener = InternalListener {
+                    socket: socket,
+                    token: token,
+                    sender_comp_id: sender_comp_id,
+                    settings: settings,
+                    connection_attempts: HashMap::new(),
+                };
+
+                if let Err(e) = self.poll.register(&listener.socket,listener.token,Ready::readable(),PollOpt::edge()) {
+                    self.tx.send(EngineEvent::ListenerFailed(listener.as_listener(),e)).unwrap();
+                    return Ok(())
+                }
+
+                self.listeners.insert(token,listener);
+            },
+            //Engine wants to send a message over a connection.
+            InternalEngineToThreadEvent::SendMessage(token,message_version,mut message,expires_at) => {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    //Logon is sent using the latest message version for the session's FIX version
+                    //unless told otherwise, just like the Logon response when approving a
+                    //connection. The default message version could be older than the Logon fields
+                    //the FIX version requires, such as DefaultApplVerID for FIXT.1.1.
+                    let message_version = if message_version.is_none() && message.msg_type() == Logon::msg_type() {
+                        Some(connection_entry.get().fix_version.max_message_version())
+                    }
+                    else {
+                        message_version
+                    };
+
+                    //Everything past this point, including the counterparty, only sees the venue's
+                    //symbol.
+                    if let Some(ref symbol_mapper) = connection_entry.get().symbol_mapper {
+                        map_outbound_symbol(&**symbol_mapper,&mut *message);
+                    }
+
+                    //Return the message instead of sending it if it's invalid according to the
+                    //dictionary.
+                    let is_administrative = administrative_msg_types().contains(&message.msg_type());
+                    if connection_entry.get().outbound_validation.applies_to(message.msg_type(),is_administrative) {
+                        if let Some(builder) = self.message_dictionary.get(message.msg_type()) {
+                            let connection = connection_entry.get();
+                            let message_version = message_version.unwrap_or(connection.default_message_version);
+                            let errors = validate_outbound_message(&*message,&**builder,connection.fix_version,message_version);
+                            if !errors.is_empty() {
+                                self.tx.send(EngineEvent::OutboundValidationFailed(OutboundValidationFailedData {
+                                    connection: connection.as_connection(),
+                                    message: message,
+                                    errors: errors,
+                                })).unwrap();
+                                return Ok(());
+                            }
+                        }
+                    }
+
+                    //Refuse or flag a NewOrderSingle whose ClOrdID was already sent within the
+                    //DuplicateOrderGuard's window.
+                    let connection = connection_entry.get().as_connection();
+                    if let Some(ref mut sent_cl_ord_ids) = connection_entry.get_mut().sent_cl_ord_ids {
+                        let now = self.timer.clock().utc_now();
+                        if sent_cl_ord_ids.is_duplicate(&*message,now) {
+                            match sent_cl_ord_ids.guard().action {
+                                DuplicateOrderAction::Refuse => {
+                                    self.tx.send(EngineEvent::DuplicateOrderRefused(connection,message)).unwrap();
+                                    return Ok(());
+                                },
+                                DuplicateOrderAction::Flag => {
+                                    let cl_ord_id = message.cl_ord_id_bytes().unwrap_or(b"").to_vec();
+                                    self.tx.send(EngineEvent::DuplicateOrderSent(connection,cl_ord_id)).unwrap();
+                                },
+                            }
+                        }
+                    }
+
+                    let mut outbound_message = OutboundMessage::from_box(message);
+                    outbound_message.message_version = message_version;
+                    outbound_message.expires_at = expires_at;
+
+                    //Only log on when no other engine process is active for this session. Keep
+                    //confirming it for as long as the connection is up.
+                    if connection_entry.get().session_coordinator.is_some() && outbound_message.message.msg_type() == Logon::msg_type() {
+                        connection_entry.get_mut().session_coordinator_logon = Some(outbound_message);
+                        connection_entry.get_mut().acquire_session_coordinator(&mut self.timer,&self.tx);
+                        try_write_connection_or_terminate!(connection_entry,self);
+                        return Ok(());
+                    }
+
+                    //Hold onto application messages while sending is paused. Administrative
+                    //messages still go out so the session is kept alive.
+                    if connection_entry.get().outbound_paused && !administrative_msg_types().contains(&outbound_message.message.msg_type()) {
+                        connection_entry.get_mut().outbound_paused_messages.push(outbound_message);
+                        return Ok(());
+                    }
+
+                    //Same while a resend is still being sent in chunks. Otherwise, new messages
+                    //would be sent with higher MsgSeqNums before the resend is finished and look
+                    //like another gap to the other side.
+                    if !connection_entry.get().resend_chunk_messages.is_empty() && !administrative_msg_types().contains(&outbound_message.message.msg_type()) {
+                        connection_entry.get_mut().resend_held_messages.push(outbound_message);
+                        return Ok(());
+                    }
+
+                    connection_entry.get_mut().outbound_messages.push(outbound_message);
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Silently ignore message for invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to send a set of messages as a response to a resend request.
+            InternalEngineToThreadEvent::ResendMessages(token,response) => {
+            if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    connection_entry.get_mut().queue_resend_response(response,&mut self.timer,&self.tx);
+
+                    //If we are still waiting on a response to our own RespondRequest, send a new
+                    //RespondRequest. Deferring like this is the correct behavior according to FIXT
+                    //v1.1, page 13.
+                    if let Some(resend_request_msg_seq_num) = connection_entry.get().inbound_resend_request_msg_seq_num {
+                        let begin_seq_no = connection_entry.get().inbound_msg_seq_num;
+                        connection_entry.get_mut().request_resend(begin_seq_no,resend_request_msg_seq_num - 1);
+                    }
+
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Silently ignore message for invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to hold application messages for a connection until resumed.
+            InternalEngineToThreadEvent::PauseSending(token) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.outbound_paused = true;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to send all held application messages and stop holding new ones.
+            InternalEngineToThreadEvent::ResumeSending(token) => {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    {
+                        let connection = connection_entry.get_mut();
+                        if !connection.outbound_paused {
+                            return Ok(());
+                        }
+
+                        connection.outbound_paused = false;
+                        let paused_messages = mem::replace(&mut connection.outbound_paused_messages,Vec::new());
+                        if connection.resend_chunk_messages.is_empty() {
+                            connection.outbound_messages.extend(paused_messages);
+                        }
+                        else {
+                            connection.resend_held_messages.extend(paused_messages);
+                        }
+                    }
+
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to prepare a connection so the first real message is sent quickly.
+            InternalEngineToThreadEvent::WarmUp(token,message_version,message) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.warm_up(message,message_version);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how a connection is probed for being half-open.
+            InternalEngineToThreadEvent::SetProbeSettings(token,probe_settings) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.probe_settings = probe_settings;
+                    connection.reset_probe_timeout(&mut self.timer);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to be warned when a connection goes quiet.
+            InternalEngineToThreadEvent::SetInboundQuietPeriod(token,inbound_quiet_period) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.inbound_quiet_period = inbound_quiet_period;
+                    reset_timeout(&mut self.timer,&mut connection.inbound_quiet_period_timeout,&connection.inbound_quiet_period,TimeoutType::InboundQuietPeriod,&connection.token);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to watch for anomalous inbound message rates.
+            InternalEngineToThreadEvent::SetInboundRateAnomaly(token,rate_anomaly_settings) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.inbound_rate_anomaly = rate_anomaly_settings.map(RateAnomalyDetector::new);
+                    let window = rate_anomaly_settings.map(|rate_anomaly_settings| rate_anomaly_settings.window);
+                    reset_timeout(&mut self.timer,&mut connection.inbound_rate_anomaly_timeout,&window,TimeoutType::InboundRateAnomalyWindow,&connection.token);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how large inbound ResendRequests are serviced.
+            InternalEngineToThreadEvent::SetResendRequestLimits(token,resend_request_limits) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.resend_request_limits = resend_request_limits;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how the range of gap triggered ResendRequests is chosen.
+            InternalEngineToThreadEvent::SetResendRequestRange(token,resend_request_range) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.resend_request_range = resend_request_range;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to merge ResendRequests for gaps detected close together.
+            InternalEngineToThreadEvent::SetResendRequestCoalesceInterval(token,coalesce_interval) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.resend_request_coalesce_interval = coalesce_interval;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change what happens to queued messages when the remote logs out.
+            InternalEngineToThreadEvent::SetLogoutResponsePolicy(token,logout_response_policy) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.logout_response_policy = logout_response_policy;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change what happens to unsent messages when the connection is lost.
+            InternalEngineToThreadEvent::SetOutboxPolicy(token,outbox_policy) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.outbox_policy = outbox_policy;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to send messages left over from an earlier connection.
+            InternalEngineToThreadEvent::ReleaseHeldMessages(token) => {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    connection_entry.get_mut().release_outbox();
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to drop messages left over from an earlier connection.
+            InternalEngineToThreadEvent::DiscardHeldMessages(token) => {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    connection_entry.get_mut().discard_outbox();
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how received TestRequests are handled.
+            InternalEngineToThreadEvent::SetTestRequestSettings(token,test_request_settings) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.test_request_settings = test_request_settings;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change the precision of outbound timestamps.
+            InternalEngineToThreadEvent::SetTimestampPrecision(token,timestamp_precision) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.timestamp_precision = timestamp_precision;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how strictly received messages are checked.
+            InternalEngineToThreadEvent::SetValidationSettings(token,validation_settings) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.parser.set_validation_settings(validation_settings);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change which outbound messages are checked against the dictionary.
+            InternalEngineToThreadEvent::SetOutboundValidation(token,outbound_validation) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.outbound_validation = outbound_validation;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to guard against sending the same NewOrderSingle twice.
+            InternalEngineToThreadEvent::SetDuplicateOrderGuard(token,duplicate_order_guard) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.sent_cl_ord_ids = duplicate_order_guard.map(SentClOrdIDs::new);
+                    connection.load_sent_cl_ord_ids(self.timer.clock().utc_now());
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to change how third party routing fields are handled.
+            InternalEngineToThreadEvent::SetThirdPartyRoutingSettings(token,third_party_routing_settings) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.third_party_routing_settings = third_party_routing_settings;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants duplicates to be thrown away without being parsed.
+            InternalEngineToThreadEvent::SetSkipDuplicateParsing(token,skip_duplicate_parsing) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.skip_duplicate_parsing = skip_duplicate_parsing;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
             },
-            //Engine wants to setup a listener to accept new connections.
-            InternalEngineToThreadEvent::NewListener(token,sender_comp_id,socket) => {
-                let listener = InternalListener {
-                    socket: socket,
-                    token: token,
-                    sender_comp_id: sender_comp_id,
-                };
-
-                if let Err(e) = self.poll.register(&listener.socket,listener.token,Ready::readable(),PollOpt::edge()) {
-                    self.tx.send(EngineEvent::ListenerFailed(listener.as_listener(),e)).unwrap();
-                    return Ok(())
+            //Engine is done with a received message so it can be reused by the parser.
+            InternalEngineToThreadEvent::RecycleMessage(token,message) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.parser.recycle_message(message);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
                 }
-
-                self.listeners.insert(token,listener);
             },
-            //Engine wants to send a message over a connection.
-            InternalEngineToThreadEvent::SendMessage(token,message_version,message) => {
+            //Engine wants bytes between messages to be skipped as filler.
+            InternalEngineToThreadEvent::SetFillerBytes(token,filler_bytes) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.parser.set_filler_bytes(&filler_bytes[..]);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants Symbol fields translated between venue and internal ids.
+            InternalEngineToThreadEvent::SetSymbolMapper(token,symbol_mapper) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.symbol_mapper = symbol_mapper;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants outbound messages signed.
+            InternalEngineToThreadEvent::SetMessageSigner(token,message_signer) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.message_signer = message_signer;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants logons coordinated with other engine processes.
+            InternalEngineToThreadEvent::SetSessionCoordinator(token,session_coordinator) => {
                 if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
-                    let mut outbound_message = OutboundMessage::from_box(message);
-                    outbound_message.message_version = message_version;
-                    connection_entry.get_mut().outbound_messages.push(outbound_message);
+                    //Step down from the old SessionCoordinator. A Logon still waiting on it
+                    //is tried again with the new one.
+                    if let Some(timeout) = connection_entry.get_mut().session_coordinator_timeout.take() {
+                        self.timer.cancel_timeout(&timeout);
+                    }
+                    connection_entry.get_mut().release_session_coordinator(&self.tx);
+                    connection_entry.get_mut().session_coordinator = session_coordinator;
+                    connection_entry.get_mut().acquire_session_coordinator(&mut self.timer,&self.tx);
                     try_write_connection_or_terminate!(connection_entry,self);
                 }
                 else {
-                    //Silently ignore message for invalid connection.
+                    //Silently ignore for an invalid connection.
                     //TODO: Maybe submit this to a logging system or something?
                 }
             },
-            //Engine wants to send a set of messages as a response to a resend request.
-            InternalEngineToThreadEvent::ResendMessages(token,response) => {
-            if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
-                    //TODO: It might make sense to take these responses as a group and do a sorted
-                    //insert into outbound_messages. This way we at least try to prevent excessive
-                    //ResendRequests from being sent to us later.
-                    for message in response {
-                        match message {
-                            ResendResponse::Message(message_version,mut message) => {
-                                //Make sure message is marked as a potential duplicate or else
-                                //we'll trigger an InboundMsgSeqNumLowerThanExpectedError or
-                                //equivalent on the other side of the connection.
-                                message.set_is_poss_dup(true);
-                                let orig_sending_time = message.sending_time();
-                                message.set_orig_sending_time(orig_sending_time);
-
-                                let mut outbound_message = OutboundMessage::from_box(message);
-                                outbound_message.message_version = message_version;
-                                outbound_message.auto_msg_seq_num = false; //We must preserve MsgSeqNum for response.
-                                connection_entry.get_mut().outbound_messages.push(outbound_message);
-                            },
-                            ResendResponse::Gap(range) => {
-                                let mut sequence_reset = SequenceReset::new();
-                                sequence_reset.gap_fill_flag = true;
-                                sequence_reset.msg_seq_num = range.start;
-                                sequence_reset.new_seq_no = range.end;
-                                connection_entry.get_mut().outbound_messages.push(
-                                    OutboundMessage::new(sequence_reset,false)
-                                );
-                            },
-                        }
+            //Engine wants sampled statistics collected about every message sent and received.
+            InternalEngineToThreadEvent::SetMessageStatistics(token,sample_interval) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.inbound_message_statistics = sample_interval.map(MessageStatistics::new);
+                    connection.outbound_message_statistics = sample_interval.map(MessageStatistics::new);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to start or stop writing discarded inbound bytes to a file.
+            InternalEngineToThreadEvent::SetQuarantineLog(token,quarantine_log) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.parser.set_keep_discarded_bytes(quarantine_log.is_some());
+                    connection.quarantine_log = quarantine_log;
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to start writing every message to another file.
+            InternalEngineToThreadEvent::AddMessageLog(token,message_log) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.message_logs.push(message_log);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to stop writing messages to every file.
+            InternalEngineToThreadEvent::ClearMessageLogs(token) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.message_logs.clear();
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to decode inbound bytes before they are parsed.
+            InternalEngineToThreadEvent::SetInboundDecoder(token,inbound_decoder) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.inbound_decoder = Some(inbound_decoder);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to attach application state so middleware can use it.
+            InternalEngineToThreadEvent::SetUserData(token,user_data) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    connection.user_data = Some(user_data);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to persist a session so it can be resumed after a restart.
+            InternalEngineToThreadEvent::SetMessageStore(token,message_store) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    //Pick up where the previous run left off, but only if nothing has been sent or
+                    //received yet. Otherwise, the store is brought up to date with the connection
+                    //instead.
+                    if connection.status.is_sending_logon() && connection.inbound_msg_seq_num == 1 && connection.outbound_msg_seq_num == 1 {
+                        connection.inbound_msg_seq_num = message_store.next_inbound_msg_seq_num();
+                        connection.outbound_msg_seq_num = message_store.next_outbound_msg_seq_num();
                     }
 
-                    //If we are still waiting on a response to our own RespondRequest, send a new
-                    //RespondRequest. Deferring like this is the correct behavior according to FIXT
-                    //v1.1, page 13.
-                    if connection_entry.get().inbound_resend_request_msg_seq_num.is_some() {
-                        let mut resend_request = ResendRequest::new();
-                        resend_request.begin_seq_no = connection_entry.get().inbound_msg_seq_num;
-                        resend_request.end_seq_no = 0;
-                        connection_entry.get_mut().outbound_messages.push(
-                            OutboundMessage::from(resend_request)
-                        );
+                    let mut parser = Parser::with_shared_dictionary(self.message_dictionary.clone(),self.max_message_size);
+                    for msg_type in administrative_msg_types() {
+                        parser.set_default_message_type_version(msg_type,connection.fix_version.max_message_version());
                     }
-
-                    try_write_connection_or_terminate!(connection_entry,self);
+                    connection.message_store = Some(message_store);
+                    connection.message_store_parser = Some(parser);
+                    connection.load_sent_cl_ord_ids(self.timer.clock().utc_now());
                 }
                 else {
-                    //Silently ignore message for invalid connection.
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants to resume a session from a previous run's acknowledged MsgSeqNum.
+            InternalEngineToThreadEvent::SetInboundCheckpoint(token,acknowledged_msg_seq_num) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    //Only makes sense before anything has been received. The Logon response will
+                    //then be ahead of the expected MsgSeqNum and trigger a ResendRequest for every
+                    //unacknowledged message.
+                    if connection.status.is_sending_logon() && connection.inbound_msg_seq_num == 1 && acknowledged_msg_seq_num < u64::max_value() {
+                        connection.inbound_msg_seq_num = acknowledged_msg_seq_num + 1;
+                        connection.inbound_acknowledged_msg_seq_num = acknowledged_msg_seq_num;
+                    }
+                }
+                else {
+                    //Silently ignore for an invalid connection.
                     //TODO: Maybe submit this to a logging system or something?
                 }
             },
+            //Engine wants to mark inbound messages as processed by the application.
+            InternalEngineToThreadEvent::AcknowledgeInbound(token,msg_seq_num) => {
+                if let Some(connection) = self.connections.get_mut(&token) {
+                    let msg_seq_num = cmp::min(msg_seq_num,connection.inbound_msg_seq_num - 1);
+                    connection.inbound_acknowledged_msg_seq_num = cmp::max(connection.inbound_acknowledged_msg_seq_num,msg_seq_num);
+                }
+                else {
+                    //Silently ignore for an invalid connection.
+                    //TODO: Maybe submit this to a logging system or something?
+                }
+            },
+            //Engine wants a snapshot of a connection's session state.
+            InternalEngineToThreadEvent::QueryConnectionInfo(token,response_tx) => {
+                let connection_info = self.connections.get(&token).map(|connection| connection.connection_info());
+                let _ = response_tx.send(connection_info); //Engine might have stopped waiting.
+            },
+            //Engine wants to know what happened to an order, usually after a restart.
+            InternalEngineToThreadEvent::QueryOrderEntryStatus(token,cl_ord_id,response_tx) => {
+                let order_entry_status = self.connections.get_mut(&token).and_then(|connection| connection.order_entry_status(&cl_ord_id[..]));
+                let _ = response_tx.send(order_entry_status); //Engine might have stopped waiting.
+            },
+            //Engine wants the statistics collected so far.
+            InternalEngineToThreadEvent::QueryMessageStatistics(token,response_tx) => {
+                let report = self.connections.get(&token).and_then(|connection| {
+                    match (connection.inbound_message_statistics.as_ref(),connection.outbound_message_statistics.as_ref()) {
+                        (Some(inbound),Some(outbound)) => Some(MessageStatisticsReport {
+                            inbound: inbound.report(),
+                            outbound: outbound.report(),
+                        }),
+                        _ => None,
+                    }
+                });
+                let _ = response_tx.send(report); //Engine might have stopped waiting.
+            },
+            //Engine wants to know when everything queued on a connection has been written.
+            InternalEngineToThreadEvent::Flush(token,response_tx) => {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                    connection_entry.get_mut().flush_waiters.push(response_tx);
+                    try_write_connection_or_terminate!(connection_entry,self);
+                }
+                else {
+                    //Dropping response_tx lets the Engine know the connection is invalid.
+                }
+            },
             //Engine wants to approve logon of a connection that was accepted by a listener.
-            InternalEngineToThreadEvent::ApproveNewConnection(connection,message,inbound_msg_seq_num) => {
-                if let Entry::Occupied(mut connection_entry) = self.connections.entry(Token(connection.0)) {
+            InternalEngineToThreadEvent::ApproveNewConnection(token,message,inbound_msg_seq_num) => {
+                //Only one connection at a time may be logged on using the same SenderCompID and
+                //TargetCompID pair. Refuse the new connection with a Logout instead of approving it.
+                let is_duplicate_session = match self.connections.get(&token) {
+                    Some(connection) => self.connections.values().any(|other_connection| {
+                        other_connection.token != token &&
+                        other_connection.status.is_established() &&
+                        other_connection.sender_comp_id == connection.sender_comp_id &&
+                        other_connection.target_comp_id == connection.target_comp_id
+                    }),
+                    None => false,
+                };
+                if is_duplicate_session {
+                    if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+                        if connection_entry.get().status.is_approving_logon() {
+                            connection_entry.get_mut().initiate_logout(&mut self.timer,LoggingOutType::Error(ConnectionTerminatedReason::LogonDuplicateSessionError),b"Session is already logged on");
+                            try_write_connection_or_terminate!(connection_entry,self);
+                        }
+                    }
+
+                    return Ok(());
+                }
+
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
                     {
                         let connection = connection_entry.get_mut();
                         if !connection.status.is_approving_logon() {
@@ -862,12 +2421,7 @@ impl InternalThread {
                         //Setup the version messages should be serialized against by default when
                         //being sent. Only FIXT 1.1 makes this adjustable and it MUST be set by the
                         //response Logon message in the DefaultApplVerID field.
-                        connection.default_message_version = if let FIXVersion::FIXT_1_1 = connection.fix_version {
-                            message.default_appl_ver_id
-                        }
-                        else {
-                            connection.fix_version.max_message_version()
-                        };
+                        connection.default_message_version = logon_default_message_version(connection.fix_version,&message);
 
                         //Send the Logon response. It's always sent using the latest message version
                         //for the selected FIX version. This is probably what is always wanted unless a
@@ -879,14 +2433,12 @@ impl InternalThread {
                         connection.outbound_messages.push(outbound_message);
 
                         if inbound_msg_seq_num < connection.inbound_msg_seq_num {
+                            let end_seq_no = connection.inbound_msg_seq_num - 1;
                             connection.inbound_msg_seq_num = inbound_msg_seq_num;
 
                             //Fetch the messages the remote says were sent but we never
                             //received using a ResendRequest.
-                            let mut resend_request = ResendRequest::new();
-                            resend_request.begin_seq_no = inbound_msg_seq_num;
-                            resend_request.end_seq_no = 0;
-                            connection.outbound_messages.push(OutboundMessage::from(resend_request));
+                            connection.request_resend(inbound_msg_seq_num,end_seq_no);
                         }
                         else if inbound_msg_seq_num > connection.inbound_msg_seq_num {
                             //TODO: Investigate exact handling of this. Maybe SequenceReset?
@@ -906,12 +2458,12 @@ impl InternalThread {
                 }
             },
             //Engine wants to reject logon of a connection that was accepted by a listener.
-            InternalEngineToThreadEvent::RejectNewConnection(connection,reason) => {
+            InternalEngineToThreadEvent::RejectNewConnection(token,reason) => {
                 //This should only be used for new connections but there is no check for now so
                 //user of the engine has a way to arbitrarily disconnect over an error instead of
                 //logging out cleanly.
 
-                if let Entry::Occupied(mut connection_entry) = self.connections.entry(Token(connection.0)) {
+                if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
                     //When a reason is supplied, send a Logout message with the reason as an
                     //explanation. Otherwise, disconnect immediately.
                     if let Some(reason) = reason {
@@ -958,84 +2510,187 @@ impl InternalThread {
         Ok(())
     }
 
-    fn on_timeout(&mut self) -> Result<(),ConnectionEventError> {
-        if let Some((timeout_type,token)) = self.timer.poll() {
-            if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
-                match timeout_type {
-                    TimeoutType::Outbound if connection_entry.get().status.is_established() => {
-                        //We haven't sent any data in a while. Send a Heartbeat to let other side
-                        //know we're still around.
-                        let mut heartbeat = Heartbeat::new();
-                        heartbeat.test_req_id = Vec::new(); //Left blank when not responding to TestRequest.
-                        connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(heartbeat));
-                    },
-                    TimeoutType::Inbound if connection_entry.get().status.is_established() => {
-                        //Other side hasn't sent any data in a while. Send a TestRequest to see if
-                        //it's still around.
-                        let mut test_request = TestRequest::new();
-
-                        //Use current time as TestReqID as recommended. This might not exactly
-                        //match the SendingTime field depending on when it gets sent though.
-                        let now_time = UTCTimestampFieldType::new_now();
-                        UTCTimestampFieldType::read(&now_time,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut test_request.test_req_id);
-
-                        connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(test_request));
-
-                        //Start a TimeoutType::InboundTestRequest timer to auto-disconnect if we
-                        //don't get a response in time. Note that any reploy what-so-ever will stop
-                        //the auto-disconnect -- even if this TestRequest is ignored and later gap
-                        //filled. The overhead in maintaining a list of sent TestReqIds does not
-                        //seem worth the effort. It would only be useful for debugging reasons,
-                        //right?
-                        //TODO: This might belong in the InternalConnection::write() function so we
-                        //don't disconnect before the TestRequest is actually sent. On the other
-                        //hand, if this doesn't go out in a reasonable amount of time, we're
-                        //backlogged and might be having negative consequences on the network.
-                        connection_entry.get_mut().inbound_testrequest_timeout = Some(
-                            self.timer.set_timeout(
-                                connection_entry.get_mut().inbound_testrequest_timeout_duration.unwrap(),
-                                (TimeoutType::InboundTestRequest,token),
-                            ).unwrap()
-                        );
-                    },
-                    TimeoutType::InboundTestRequest if connection_entry.get().status.is_established() => {
-                        connection_entry.get_mut().shutdown();
-                        println!("Shutting down connection after other side failed to respond to TestRequest before timeout");
-                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::TestRequestNotRespondedError));
-                    },
-                    TimeoutType::InboundBlocked => {
-                        connection_entry.get_mut().shutdown();
-                        println!("Shutting down connection after writing to socket resulted in WouldBlock for too long");
-                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::SocketNotWritableTimeoutError));
+    fn on_timeout(&mut self,timeout_type: TimeoutType,token: Token) -> Result<(),ConnectionEventError> {
+        if let Entry::Occupied(mut connection_entry) = self.connections.entry(token) {
+            match timeout_type {
+                TimeoutType::Outbound if connection_entry.get().status.is_established() => {
+                    //We haven't sent any data in a while. Send a Heartbeat to let other side
+                    //know we're still around.
+                    let mut heartbeat = Heartbeat::new();
+                    heartbeat.test_req_id = Vec::new(); //Left blank when not responding to TestRequest.
+                    connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(heartbeat));
+                },
+                TimeoutType::Inbound if connection_entry.get().status.is_established() => {
+                    //Other side hasn't sent any data in a while. Send a TestRequest to see if
+                    //it's still around.
+                    let mut test_request = TestRequest::new();
+
+                    //Use current time as TestReqID as recommended. This might not exactly
+                    //match the SendingTime field depending on when it gets sent though.
+                    let now_time = UTCTimestampFieldType::new_now();
+                    UTCTimestampFieldType::read(&now_time,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut test_request.test_req_id);
+
+                    connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(test_request));
+
+                    //Start a TimeoutType::InboundTestRequest timer to auto-disconnect if we
+                    //don't get a response in time. Note that any reploy what-so-ever will stop
+                    //the auto-disconnect -- even if this TestRequest is ignored and later gap
+                    //filled. The overhead in maintaining a list of sent TestReqIds does not
+                    //seem worth the effort. It would only be useful for debugging reasons,
+                    //right?
+                    //TODO: This might belong in the InternalConnection::write() function so we
+                    //don't disconnect before the TestRequest is actually sent. On the other
+                    //hand, if this doesn't go out in a reasonable amount of time, we're
+                    //backlogged and might be having negative consequences on the network.
+                    connection_entry.get_mut().inbound_testrequest_timeout = Some(
+                        self.timer.set_timeout(
+                            connection_entry.get_mut().inbound_testrequest_timeout_duration.unwrap(),
+                            (TimeoutType::InboundTestRequest,token),
+                        )
+                    );
+                },
+                TimeoutType::InboundTestRequest if connection_entry.get().status.is_established() => {
+                    connection_entry.get_mut().shutdown();
+                    println!("Shutting down connection after other side failed to respond to TestRequest before timeout");
+                    return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::TestRequestNotRespondedError));
+                },
+                TimeoutType::InboundQuietPeriod if connection_entry.get().status.is_established() => {
+                    //Nothing has been received for a while. Only a warning: the heartbeat timers
+                    //still decide when to give up on the connection. The watchdog isn't started
+                    //again until more data arrives so a long silence is only reported once.
+                    connection_entry.get_mut().inbound_quiet_period_timeout = None;
+                    if let Some(inbound_quiet_period) = connection_entry.get().inbound_quiet_period {
+                        self.tx.send(EngineEvent::InboundQuietPeriodExceeded(connection_entry.get().as_connection(),inbound_quiet_period)).unwrap();
                     }
-                    TimeoutType::ContinueLogout if connection_entry.get().status.is_logging_out_with_resending_request_initiated_by_remote() => {
-                        connection_entry.get_mut().respond_to_logout();
-                    },
-                    TimeoutType::NoLogon => {
-                        assert!(connection_entry.get().status.is_receiving_logon());
-                        connection_entry.get_mut().shutdown();
-                        println!("Shutting down connection after no initial Logon received before timeout");
-                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogonNeverReceivedError));
-                    },
-                    TimeoutType::Logout => {
-                        connection_entry.get_mut().shutdown();
-                        println!("Shutting down connection after no Logout response before timeout");
-                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogoutNoResponseError));
-                    },
-                    TimeoutType::HangUp => {
+                },
+                TimeoutType::InboundRateAnomalyWindow if connection_entry.get().inbound_rate_anomaly_timeout.is_some() => {
+                    //Compare the window that just ended to each MsgType's baseline and start the
+                    //next one.
+                    let connection = connection_entry.get().as_connection();
+                    let window = match connection_entry.get_mut().inbound_rate_anomaly {
+                        Some(ref mut rate_anomaly_detector) => {
+                            for anomaly in rate_anomaly_detector.end_window() {
+                                self.tx.send(EngineEvent::InboundRateAnomaly(connection,anomaly)).unwrap();
+                            }
+
+                            rate_anomaly_detector.settings().window
+                        },
+                        None => return Ok(()), //Turned off after timeout was set.
+                    };
+                    connection_entry.get_mut().inbound_rate_anomaly_timeout = Some(
+                        self.timer.set_timeout(
+                            window,
+                            (TimeoutType::InboundRateAnomalyWindow,token),
+                        )
+                    );
+                },
+                TimeoutType::Probe if connection_entry.get().status.is_established() => {
+                    //Nothing has been received since the last quiet period or probe. Give up
+                    //if too many probes have gone unanswered. Otherwise, send another.
+                    let probe_settings = match connection_entry.get().probe_settings {
+                        Some(probe_settings) => probe_settings,
+                        None => return Ok(()), //Probing was disabled after timeout was set.
+                    };
+
+                    if connection_entry.get().probe_unanswered_count >= probe_settings.max_unanswered_probes {
                         connection_entry.get_mut().shutdown();
-                        println!("Shutting down connection after other side failed to disconnect before timeout");
-                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogoutNoHangUpError));
-                    },
-                    TimeoutType::Outbound |
-                    TimeoutType::Inbound |
-                    TimeoutType::InboundTestRequest |
-                    TimeoutType::ContinueLogout => {}, //Special conditions only. Handled above.
-                }
+                        println!("Shutting down connection after other side failed to respond to TestRequest probes");
+                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::ProbeNotRespondedError));
+                    }
 
-                //Write any new Heartbeat or TestRequest messages.
-                try_write_connection_or_terminate!(connection_entry,self);
+                    let mut test_request = TestRequest::new();
+                    let now_time = UTCTimestampFieldType::new_now();
+                    UTCTimestampFieldType::read(&now_time,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut test_request.test_req_id);
+                    connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(test_request));
+
+                    connection_entry.get_mut().probe_unanswered_count += 1;
+                    connection_entry.get_mut().probe_timeout = Some(
+                        self.timer.set_timeout(
+                            probe_settings.probe_interval,
+                            (TimeoutType::Probe,token),
+                        )
+                    );
+                },
+                TimeoutType::InboundBlocked => {
+                    connection_entry.get_mut().shutdown();
+                    println!("Shutting down connection after writing to socket resulted in WouldBlock for too long");
+                    return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::SocketNotWritableTimeoutError));
+                }
+                TimeoutType::ContinueLogout if connection_entry.get().status.is_logging_out_with_resending_request_initiated_by_remote() => {
+                    connection_entry.get_mut().respond_to_logout(&mut self.timer);
+                },
+                TimeoutType::ResendRequestCoalesce if connection_entry.get().resend_request_coalesce_timeout.is_some() => {
+                    //Done waiting for more gaps. Ask for everything still missing at once.
+                    connection_entry.get_mut().resend_request_coalesce_timeout = None;
+                    connection_entry.get_mut().send_pending_resend_request();
+                },
+                TimeoutType::ResendChunk if connection_entry.get().resend_chunk_timeout.is_some() => {
+                    //Time for the next chunk of resent messages.
+                    connection_entry.get_mut().resend_chunk_timeout = None;
+                    connection_entry.get_mut().send_resend_chunk(&mut self.timer,&self.tx);
+                },
+                TimeoutType::LogoutResponse if connection_entry.get().logout_response_timeout.is_some() => {
+                    //Ran out of time waiting for queued messages to be sent. Drop the rest
+                    //and respond to the Logout.
+                    connection_entry.get_mut().logout_response_timeout = None;
+                    connection_entry.get_mut().discard_outbound_messages();
+                    connection_entry.get_mut().send_logout_response(&mut self.timer);
+                },
+                TimeoutType::SessionCoordinatorRenew if connection_entry.get().session_coordinator_timeout.is_some() => {
+                    //If another engine process might have taken over, drop the connection right
+                    //away without a Logout because anything sent now could conflict with it.
+                    if let Err(reason) = connection_entry.get_mut().renew_session_coordinator(&mut self.timer,&self.tx) {
+                        connection_entry.get_mut().shutdown();
+                        println!("Shutting down connection after another engine process took over the session");
+                        return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),reason));
+                    }
+                },
+                TimeoutType::SessionCoordinatorRetry if connection_entry.get().session_coordinator_timeout.is_some() => {
+                    //SessionCoordinator was busy when the Logon was sent.
+                    connection_entry.get_mut().session_coordinator_timeout = None;
+                    connection_entry.get_mut().acquire_session_coordinator(&mut self.timer,&self.tx);
+                },
+                TimeoutType::TestRequestResponse if !connection_entry.get().delayed_test_request_responses.is_empty() => {
+                    //A delayed reply to a TestRequest is due. Every reply is delayed by the same
+                    //amount so the oldest one is always next.
+                    let heartbeat = connection_entry.get_mut().delayed_test_request_responses.pop_front().unwrap();
+                    if connection_entry.get().status.is_established() {
+                        connection_entry.get_mut().outbound_messages.push(OutboundMessage::from(heartbeat));
+                    }
+                },
+                TimeoutType::NoLogon => {
+                    assert!(connection_entry.get().status.is_receiving_logon());
+                    connection_entry.get_mut().shutdown();
+                    println!("Shutting down connection after no initial Logon received before timeout");
+                    return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogonNeverReceivedError));
+                },
+                TimeoutType::Logout => {
+                    connection_entry.get_mut().shutdown();
+                    println!("Shutting down connection after no Logout response before timeout");
+                    return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogoutNoResponseError));
+                },
+                TimeoutType::HangUp => {
+                    connection_entry.get_mut().shutdown();
+                    println!("Shutting down connection after other side failed to disconnect before timeout");
+                    return Err(ConnectionEventError::TerminateConnection(connection_entry.remove(),ConnectionTerminatedReason::LogoutNoHangUpError));
+                },
+                TimeoutType::Outbound |
+                TimeoutType::Inbound |
+                TimeoutType::InboundTestRequest |
+                TimeoutType::InboundQuietPeriod |
+                TimeoutType::InboundRateAnomalyWindow |
+                TimeoutType::Probe |
+                TimeoutType::ContinueLogout |
+                TimeoutType::LogoutResponse |
+                TimeoutType::ResendChunk |
+                TimeoutType::ResendRequestCoalesce |
+                TimeoutType::SessionCoordinatorRenew |
+                TimeoutType::SessionCoordinatorRetry |
+                TimeoutType::TestRequestResponse => {}, //Special conditions only. Handled above.
             }
+
+            //Write any new Heartbeat or TestRequest messages.
+            try_write_connection_or_terminate!(connection_entry,self);
         }
 
         Ok(())
@@ -1068,9 +2723,17 @@ impl InternalThread {
                     for message in messages {
                         let result = match message {
                             ConnectionReadMessage::Message(message) =>
-                                InternalThread::on_network_message(connection_entry.get_mut(),message,&self.tx,&mut self.timer),
+                                InternalThread::on_network_message(connection_entry.get_mut(),message,&self.tx,&mut self.timer,&mut self.middleware),
                             ConnectionReadMessage::Error(parse_error) =>
                                 InternalThread::on_network_parse_error(connection_entry.get_mut(),parse_error,&self.tx),
+                            ConnectionReadMessage::SkippedDuplicate(msg_type,msg_seq_num) => {
+                                self.tx.send(EngineEvent::MessageReceivedDuplicateSkipped(MessageReceivedDuplicateSkippedData {
+                                    connection: connection_entry.get().as_connection(),
+                                    msg_type: msg_type,
+                                    msg_seq_num: msg_seq_num,
+                                })).unwrap();
+                                Ok(())
+                            },
                         };
 
                         if let Err(e) = result {
@@ -1121,18 +2784,41 @@ impl InternalThread {
             if event.kind().is_readable() {
                 match listener_entry.get_mut().socket.accept() {
                     Ok((socket,addr)) => {
-                        let token = match self.token_generator.lock().unwrap().create() {
-                            Some(token) => token,
-                            None => {
-                                let _ = socket.shutdown(Shutdown::Both);
-                                self.tx.send(EngineEvent::ConnectionDropped(listener_entry.get().as_listener(),addr)).unwrap();
-                                return Ok(());
-                            },
+                        //Close connections that break the listener's rules before spending
+                        //anything on them.
+                        let listener = listener_entry.get().as_listener();
+                        let unauthenticated_connection_count = self.connections.values().filter(|connection| {
+                            connection.listener == Some(listener) && (connection.status.is_receiving_logon() || connection.status.is_approving_logon())
+                        }).count();
+                        if let Some(reason) = listener_entry.get_mut().check_connection(&addr,unauthenticated_connection_count,self.timer.now()) {
+                            let _ = socket.shutdown(Shutdown::Both);
+                            self.tx.send(EngineEvent::ConnectionRefused(ConnectionRefusedData {
+                                listener: listener,
+                                address: addr,
+                                reason: reason,
+                            })).unwrap();
+                            return Ok(());
+                        }
+
+                        let (token,generation) = {
+                            let mut token_generator = self.token_generator.lock().unwrap();
+                            match token_generator.create() {
+                                Some(token) => (token,token_generator.generation(token).unwrap()),
+                                None => {
+                                    let _ = socket.shutdown(Shutdown::Both);
+                                    self.tx.send(EngineEvent::ConnectionDropped(listener_entry.get().as_listener(),addr)).unwrap();
+                                    return Ok(());
+                                },
+                            }
                         };
 
                         //Let engine know about the connection and have a chance to reject it
                         //before remote sends a Logon message.
-                        self.tx.send(EngineEvent::ConnectionAccepted(listener_entry.get().as_listener(),Connection(token.0),addr.clone())).unwrap();
+                        self.tx.send(EngineEvent::ConnectionAccepted(ConnectionAcceptedData {
+                            listener: listener_entry.get().as_listener(),
+                            connection: Connection { id: token.0, generation: generation },
+                            address: addr.clone(),
+                        })).unwrap();
 
                         let fix_version = FIXVersion::max_version(); //Accept the latest message version at first. This works out because Logon is forwards version compatible.
                         let mut connection = InternalConnection::new(self.message_dictionary.clone(),
@@ -1141,12 +2827,16 @@ impl InternalThread {
                                                                      MessageVersion::FIX50SP2, //Overwritten when connection is approved using the response message's default_appl_ver_id.
                                                                      socket,
                                                                      token,
+                                                                     generation,
                                                                      listener_entry.get().sender_comp_id.clone(),
-                                                                     Vec::new());
+                                                                     Vec::new(),
+                                                                     addr.clone());
                         connection.is_connected = true; //Accepted connections don't have to wait for connect().
+                        connection.listener = Some(listener);
+                        connection.heart_bt_int_range = listener_entry.get().settings.heart_bt_int_range;
                         let timeout = self.timer.set_timeout(
                             Duration::from_secs(AUTO_DISCONNECT_AFTER_NO_LOGON_RECEIVED_SECONDS),
-                            (TimeoutType::NoLogon,token)).unwrap();
+                            (TimeoutType::NoLogon,token));
                         connection.status = ConnectionStatus::ReceivingLogon(listener_entry.get().as_listener(),timeout);
 
                         //Have poll let us know when we can can read or write.
@@ -1159,6 +2849,7 @@ impl InternalThread {
                             return Ok(())
                         }
 
+                        self.health_checks.lock().unwrap().insert(connection.as_connection(),connection.health_check.clone());
                         self.connections.insert(token,connection);
                     },
                     Err(err) => {
@@ -1171,7 +2862,7 @@ impl InternalThread {
         Ok(())
     }
 
-    fn on_network_message(connection: &mut InternalConnection,mut message: Box<FIXTMessage + Send>,tx: &Sender<EngineEvent>,timer: &mut Timer<(TimeoutType,Token)>) -> Result<(),ConnectionTerminatedReason>  {
+    fn on_network_message(connection: &mut InternalConnection,mut message: Box<FIXTMessage + Send>,tx: &Sender<EngineEvent>,timer: &mut Timer<(TimeoutType,Token)>,middleware: &mut MiddlewareChain) -> Result<(),ConnectionTerminatedReason>  {
         //Perform book keeping needed to maintain the FIX connection and then pass off the message
         //to the engine.
 
@@ -1225,7 +2916,33 @@ impl InternalThread {
                     //Notify the engine of which messages are requested. Then it's up to the engine
                     //to give said messages to us so we can send them.
                     let end_seq_no = if resend_request.end_seq_no == 0 { connection.outbound_msg_seq_num } else { resend_request.end_seq_no + 1 }; //TODO: Handle potential overflow.
-                    tx.send(EngineEvent::ResendRequested(connection.as_connection(),resend_request.begin_seq_no..end_seq_no)).unwrap();
+                    let mut begin_seq_no = resend_request.begin_seq_no;
+
+                    //Gap fill older history automatically when the range is too large so only the
+                    //most recent messages have to be replayed.
+                    if let Some(max_range) = connection.resend_request_limits.and_then(|resend_request_limits| resend_request_limits.max_range) {
+                        if end_seq_no > begin_seq_no && end_seq_no - begin_seq_no > max_range {
+                            let new_begin_seq_no = end_seq_no - max_range;
+
+                            let mut sequence_reset = SequenceReset::new();
+                            sequence_reset.gap_fill_flag = true;
+                            sequence_reset.msg_seq_num = begin_seq_no;
+                            sequence_reset.new_seq_no = new_begin_seq_no;
+                            connection.outbound_messages.push(OutboundMessage::new(sequence_reset,false));
+
+                            begin_seq_no = new_begin_seq_no;
+                        }
+                    }
+
+                    connection.inbound_resend_request_range = Some(begin_seq_no..end_seq_no);
+                    if connection.message_store.is_some() {
+                        //Answer using the message store without involving the engine.
+                        let response = connection.message_store_resend_response(begin_seq_no..end_seq_no);
+                        connection.queue_resend_response(response,timer,tx);
+                    }
+                    else {
+                        tx.send(EngineEvent::ResendRequested(connection.as_connection(),begin_seq_no..end_seq_no)).unwrap();
+                    }
                 }
 
                 //If:
@@ -1255,6 +2972,18 @@ impl InternalThread {
             }
         }
 
+        fn send_session_status(connection: &InternalConnection,session_status: &Option<SessionStatus>,text: &[u8],tx: &Sender<EngineEvent>) {
+            //SessionStatus is the main way a remote explains why a Logon or Logout happened (ie.
+            //invalid password or sequence numbers reset) so pass it along when present.
+            if let Some(ref session_status) = *session_status {
+                tx.send(EngineEvent::SessionStatusReceived(SessionStatusReceivedData {
+                    connection: connection.as_connection(),
+                    session_status: session_status.clone(),
+                    text: text.to_vec(),
+                })).unwrap();
+            }
+        }
+
         fn reject_for_sending_time_accuracy(connection: &mut InternalConnection,message: Box<FIXTMessage + Send>,msg_seq_num: MsgSeqNumType,tx: &Sender<EngineEvent>) {
             let mut reject = Reject::new();
             reject.ref_seq_num = msg_seq_num;
@@ -1275,12 +3004,10 @@ impl InternalThread {
 
             //Fetch the messages the remote says were sent but we never received using
             //ResendRequest. The one exception is if we are _receiving_ a ResendRequest message
-            //because then we're suppose to defer until after we respond.
-            if message.as_any().downcast_ref::<ResendRequest>().is_none() {
-                let mut resend_request = ResendRequest::new();
-                resend_request.begin_seq_no = connection.inbound_msg_seq_num;
-                resend_request.end_seq_no = 0;
-                connection.outbound_messages.push(OutboundMessage::from(resend_request));
+            //because then we're suppose to defer until after we respond. A message store has
+            //already responded above so there is nothing to defer.
+            if message.as_any().downcast_ref::<ResendRequest>().is_none() || connection.message_store.is_some() {
+                connection.request_missing(msg_seq_num - 1,timer);
             }
 
             //Keep track of the newest msg_seq_num that's been seen so we know when the message gap has
@@ -1425,6 +3152,8 @@ impl InternalThread {
 
             //Handle Logout messages.
             if let Some(logout) = message.as_any().downcast_ref::<Logout>() {
+                send_session_status(connection,&logout.session_status,&logout.text,tx);
+
                 //Remote responded to our Logout.
                 if let ConnectionStatus::LoggingOut(_) = connection.status {
                     connection.shutdown();
@@ -1432,7 +3161,7 @@ impl InternalThread {
                 }
                 //Remote started logout process.
                 else {
-                    connection.respond_to_logout();
+                    connection.respond_to_logout(timer);
                 }
             }
 
@@ -1441,8 +3170,9 @@ impl InternalThread {
 
         //Start by making sure the message is using the expected FIX version. Otherwise, we should
         //logout and disconnect immediately. This test is skipped for newly accepted connections
-        //because the expected FIX version has not been decided yet.
-        if !connection.status.is_receiving_logon() {
+        //because the expected FIX version has not been decided yet. It can also be turned off with
+        //ValidationSettings::reject_wrong_begin_string.
+        if !connection.status.is_receiving_logon() && connection.parser.validation_settings().reject_wrong_begin_string {
             let ref received_fix_version = message.meta().as_ref().expect("Meta should be set by parser").begin_string;
             let expected_fix_version = connection.fix_version;
             if *received_fix_version != expected_fix_version {
@@ -1533,7 +3263,7 @@ impl InternalThread {
                 //Make parser use the specified message version by default. This is only used if
                 //the FIXVersion >= FIXT_1_1. Earlier versions always use the same message version
                 //as the FIX version specified in the BeginStr tag.
-                connection.parser.set_default_message_version(message.default_appl_ver_id);
+                connection.parser.set_default_message_version(logon_default_message_version(connection.fix_version,message));
 
                 //Make parser use the Message Type Default Application Version if specified.
                 for msg_type in &message.no_msg_types {
@@ -1544,9 +3274,16 @@ impl InternalThread {
 
                 //TODO: Need to take MaxMessageSize into account.
                 //TODO: Optionally support filtering message types (NoMsgTypes).
+                send_session_status(connection,&message.session_status,&message.text,tx);
                 tx.send(EngineEvent::SessionEstablished(connection.as_connection())).unwrap();
+                connection.on_session_established_outbox(tx);
             }
             else {
+                //Remote might have rejected the Logon with a Logout explaining why.
+                if let Some(logout) = message.as_any().downcast_ref::<Logout>() {
+                    send_session_status(connection,&logout.session_status,&logout.text,tx);
+                }
+
                 connection.initiate_logout(timer,LoggingOutType::Error(ConnectionTerminatedReason::LogonNotFirstMessageError),b"First message not a logon");
                 return Ok(());
             }
@@ -1565,10 +3302,20 @@ impl InternalThread {
                 //It'll be up to the user of the library to reject if they don't want to support
                 //these to the full extent of the library (e.g. an older FIX version).
                 connection.fix_version = message.meta.as_ref().expect("Meta should be set by parser").begin_string;
-                connection.parser.set_default_message_version(message.default_appl_ver_id);
+                connection.parser.set_default_message_version(logon_default_message_version(connection.fix_version,message));
                 connection.inbound_msg_seq_num = message.msg_seq_num + 1;
                 connection.target_comp_id = message.sender_comp_id.clone();
 
+                //Negotiate HeartBtInt. The initiator picks it but the listener can limit what it's
+                //willing to use. A negative value is refused below.
+                if let Some(heart_bt_int_range) = connection.heart_bt_int_range {
+                    if message.heart_bt_int >= 0 && (message.heart_bt_int < heart_bt_int_range.min as i64 || message.heart_bt_int > heart_bt_int_range.max as i64) {
+                        let text = format!("HeartBtInt must be between {} and {}",heart_bt_int_range.min,heart_bt_int_range.max);
+                        connection.initiate_logout(timer,LoggingOutType::Error(ConnectionTerminatedReason::LogonHeartBtIntOutOfRangeError),text.as_bytes());
+                        return Ok(());
+                    }
+                }
+
                 if message.heart_bt_int > 0 {
                     connection.outbound_heartbeat_timeout_duration = Some(
                         Duration::from_secs(message.heart_bt_int as u64)
@@ -1601,7 +3348,12 @@ impl InternalThread {
                 //automatically unblocked when the Logon response is sent.
                 connection.begin_blocking_inbound(timer);
 
-                tx.send(EngineEvent::ConnectionLoggingOn(listener,connection.as_connection(),Box::new(message.clone()))).unwrap();
+                tx.send(EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData {
+                    listener: listener,
+                    connection: connection.as_connection(),
+                    message: Box::new(message.clone()),
+                    address: connection.remote_address,
+                })).unwrap();
 
                 return Ok(());
             }
@@ -1677,11 +3429,93 @@ impl InternalThread {
             }
         }
 
-        //Reply to TestRequest automatically with a Heartbeat. Typical keep alive stuff.
+        //Make sure the third party routing fields are acceptable. The message was already
+        //accepted as far as MsgSeqNum is concerned so only it is rejected and the session carries
+        //on. The Logon is left alone because it's handled above. See ThirdPartyRoutingSettings.
+        let routing = message.third_party_routing();
+        if !just_logged_on {
+            if let Err(error) = connection.third_party_routing_settings.validate(&routing) {
+                let mut reject = Reject::new();
+                reject.ref_msg_type = message.msg_type().to_vec();
+                reject.ref_tag_id = error.tag_bytes().to_vec();
+                reject.ref_seq_num = msg_seq_num;
+                reject.session_reject_reason = Some(SessionRejectReason::CompIDProblem);
+                reject.text = error.to_string().into_bytes();
+                if connection.third_party_routing_settings.reflect_in_responses {
+                    reject.set_third_party_routing(&routing.reply());
+                }
+                connection.outbound_messages.push(OutboundMessage::from(reject));
+
+                tx.send(EngineEvent::MessageRejected(connection.as_connection(),message)).unwrap();
+                return Ok(());
+            }
+        }
+
+        //Reply to TestRequest automatically with a Heartbeat. Typical keep alive stuff. A
+        //TestRequest sent on behalf of another firm is answered back through the hub.
+        //The reply can be held back or skipped entirely for testing. See TestRequestResponse.
         if let Some(test_request) = message.as_any().downcast_ref::<TestRequest>() {
             let mut heartbeat = Heartbeat::new();
             heartbeat.test_req_id = test_request.test_req_id.clone();
-            connection.outbound_messages.push(OutboundMessage::from(heartbeat));
+            if connection.third_party_routing_settings.reflect_in_responses {
+                heartbeat.set_third_party_routing(&routing.reply());
+            }
+            match connection.test_request_settings.response {
+                TestRequestResponse::Immediate => connection.outbound_messages.push(OutboundMessage::from(heartbeat)),
+                TestRequestResponse::Delay(delay) => {
+                    connection.delayed_test_request_responses.push_back(heartbeat);
+                    timer.set_timeout(delay,(TimeoutType::TestRequestResponse,connection.token));
+                },
+                TestRequestResponse::Suppress => {},
+            }
+
+            if connection.test_request_settings.report_received {
+                tx.send(EngineEvent::TestRequestReceived(connection.as_connection(),test_request.test_req_id.clone())).unwrap();
+            }
+        }
+
+        //Some venues put status, like throttling warnings, in the Text of Heartbeats and
+        //TestRequests. Report it on its own so it isn't lost when admin messages are suppressed.
+        {
+            let admin_text = if let Some(heartbeat) = message.as_any().downcast_ref::<Heartbeat>() {
+                &heartbeat.text[..]
+            }
+            else if let Some(test_request) = message.as_any().downcast_ref::<TestRequest>() {
+                &test_request.text[..]
+            }
+            else {
+                &b""[..]
+            };
+            if !admin_text.is_empty() {
+                tx.send(EngineEvent::AdminNotice(AdminNoticeData {
+                    connection: connection.as_connection(),
+                    msg_type: message.msg_type().to_vec(),
+                    msg_seq_num: msg_seq_num,
+                    text: admin_text.to_vec(),
+                })).unwrap();
+            }
+        }
+
+        //Persist acknowledgements before the application sees them so they can't be lost if the
+        //process crashes while handling the message.
+        connection.store_order_ack(&*message);
+
+        //Hand the application its own instrument ids instead of the venue's symbology.
+        if let Some(ref symbol_mapper) = connection.symbol_mapper {
+            map_inbound_symbol(&**symbol_mapper,&mut *message);
+        }
+
+        //Give the application's middleware a chance to transform or filter the message now that
+        //the session layer is done with it.
+        if !middleware.is_empty() {
+            match middleware.process(connection.as_connection(),connection.user_data.as_ref(),timer.clock().utc_now(),message) {
+                MiddlewareAction::Continue(processed_message) => message = processed_message,
+                MiddlewareAction::Drop => return Ok(()),
+                MiddlewareAction::Reject(message) => {
+                    tx.send(EngineEvent::MessageRejected(connection.as_connection(),message)).unwrap();
+                    return Ok(());
+                },
+            }
         }
 
         tx.send(EngineEvent::MessageReceived(connection.as_connection(),message)).unwrap();
@@ -1739,6 +3573,11 @@ impl InternalThread {
                     ParseError::ApplVerIDNotSixthTag => {
                         try!(push_reject(connection,b"",ApplVerID::tag_bytes(),SessionRejectReason::TagSpecifiedOutOfRequiredOrder,b"ApplVerID must be the 6th tag if specified"));
                     },
+                    ParseError::ParserNotIdle => {
+                        //Only returned when decoding a LazyMessage, never while reading from the
+                        //network.
+                        unreachable!();
+                    },
                     ParseError::MessageSizeTooBig => {
                         let mut error_text = b"Message size exceeds MaxMessageSize=".to_vec();
                         error_text.extend_from_slice(connection.parser.max_message_size().to_string().as_bytes());
@@ -1813,8 +3652,11 @@ pub fn internal_engine_thread(poll: Poll,
                               token_generator: Arc<Mutex<TokenGenerator>>,
                               tx: Sender<EngineEvent>,
                               rx: Receiver<InternalEngineToThreadEvent>,
-                              message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,
-                              max_message_size: u64) {
+                              message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,
+                              max_message_size: u64,
+                              middleware: MiddlewareChain,
+                              health_checks: HealthCheckRegistry,
+                              clock: Arc<Clock>) {
     //TODO: There should probably be a mechanism to log every possible message, even those we
     //handle automatically. One method might be to have a layer above this that handles the
     //automatic stuff and allows for logging...this is probably just too low level.
@@ -1828,12 +3670,11 @@ pub fn internal_engine_thread(poll: Poll,
         max_message_size: max_message_size,
         connections: HashMap::new(),
         listeners: HashMap::new(),
-        timer: TimerBuilder::default()
-            .tick_duration(Duration::from_millis(TIMER_TICK_MS))
-            .num_slots(TIMER_TIMEOUTS_PER_TICK_MAX)
-            .capacity(CONNECTION_COUNT_MAX * TIMEOUTS_PER_CONNECTION_MAX)
-            .build(),
+        timer: Timer::new(clock),
         network_read_retry: NetworkReadRetry::new(),
+        middleware: middleware,
+        health_checks: health_checks,
+        outboxes: HashMap::new(),
     };
     let mut terminated_connections: Vec<(InternalConnection,ConnectionTerminatedReason)> = Vec::new();
 
@@ -1856,7 +3697,10 @@ pub fn internal_engine_thread(poll: Poll,
     //on a per-connection basis.
     let mut events = Events::with_capacity(EVENT_POLL_CAPACITY);
     loop {
-        if let Err(e) = internal_thread.poll.poll(&mut events,None) {
+        //Only wait until the next timeout expires. A clock that doesn't follow real time wakes up
+        //poll itself when it's advanced.
+        let poll_timeout = internal_thread.timer.next_timeout();
+        if let Err(e) = internal_thread.poll.poll(&mut events,poll_timeout) {
             internal_thread.tx.send(EngineEvent::FatalError("Cannot poll events",e)).unwrap();
             return;
         }
@@ -1864,7 +3708,7 @@ pub fn internal_engine_thread(poll: Poll,
         for event in events.iter() {
             let result = match event.token() {
                 INTERNAL_ENGINE_EVENT_TOKEN => internal_thread.on_internal_engine_event(),
-                TIMEOUT_TOKEN => internal_thread.on_timeout(),
+                TIMEOUT_TOKEN => Ok(()), //Expired timeouts are handled below.
                 NETWORK_READ_RETRY_TOKEN => {
                     if let Some(token) = internal_thread.network_read_retry.poll() {
                         internal_thread.on_network(&Event::new(Ready::readable(),token))
@@ -1890,9 +3734,21 @@ pub fn internal_engine_thread(poll: Poll,
             }
         }
 
+        //Respond to every timeout that has expired, whether poll woke up because of one or not.
+        while let Some((timeout_type,token)) = internal_thread.timer.poll() {
+            if let Err(e) = internal_thread.on_timeout(timeout_type,token) {
+                match e {
+                    ConnectionEventError::TerminateConnection(connection,e) => {
+                        terminated_connections.push((connection,e));
+                    },
+                    ConnectionEventError::Shutdown => return,
+                };
+            }
+        }
+
         //Clean-up connections that have been shutdown (cleanly or on error).
         terminated_connections.drain(..).all(|terminated_connection| {
-            let (connection,e) = terminated_connection;
+            let (mut connection,e) = terminated_connection;
 
             let _ = internal_thread.poll.deregister(&connection.socket);
             if let Some(ref timeout) = connection.outbound_heartbeat_timeout {
@@ -1904,6 +3760,16 @@ pub fn internal_engine_thread(poll: Poll,
             if let Some(ref timeout) = connection.inbound_blocked_timeout {
                 internal_thread.timer.cancel_timeout(timeout);
             }
+            if let Some(ref timeout) = connection.inbound_quiet_period_timeout {
+                internal_thread.timer.cancel_timeout(timeout);
+            }
+            if let Some(ref timeout) = connection.inbound_rate_anomaly_timeout {
+                internal_thread.timer.cancel_timeout(timeout);
+            }
+            if let Some(ref timeout) = connection.session_coordinator_timeout {
+                internal_thread.timer.cancel_timeout(timeout);
+            }
+            connection.release_session_coordinator(&internal_thread.tx);
             if let Some(ref timeout) = connection.logout_timeout {
                 internal_thread.timer.cancel_timeout(timeout);
             }
@@ -1912,6 +3778,26 @@ pub fn internal_engine_thread(poll: Poll,
             }
 
             internal_thread.network_read_retry.remove_all(connection.token);
+            internal_thread.health_checks.lock().unwrap().remove(&connection.as_connection());
+
+            //Deal with application messages that never went out. A clean logout already handled
+            //them using LogoutResponsePolicy so whatever is left can't be sent. Otherwise, the
+            //connection was lost and OutboxPolicy decides.
+            connection.save_outbox();
+            let outbox = mem::replace(&mut connection.outbox,Vec::new());
+            if !outbox.is_empty() {
+                let is_clean_logout = match e {
+                    ConnectionTerminatedReason::LocalRequested | ConnectionTerminatedReason::RemoteRequested => true,
+                    _ => false,
+                };
+                if is_clean_logout || connection.outbox_policy == OutboxPolicy::Discard {
+                    let messages = outbox.into_iter().map(|outbound_message| outbound_message.message).collect();
+                    internal_thread.tx.send(EngineEvent::MessagesNotSent(connection.as_connection(),messages)).unwrap();
+                }
+                else {
+                    internal_thread.outboxes.entry(connection.session_id()).or_insert_with(Vec::new).extend(outbox);
+                }
+            }
 
             //Notify user in the special case where connection was never even established. This
             //block is incredibly ugly but required to appease the borrow checker.
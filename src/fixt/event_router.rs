@@ -0,0 +1,219 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap,HashSet};
+use std::sync::mpsc::{self,Receiver,Sender,SyncSender,TrySendError};
+use std::time::Duration;
+
+use dictionary::msg_type::MsgType;
+use fixt::engine::{Engine,EngineEvent};
+use fixt::message::FIXTMessage;
+
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
+pub enum EventTopic {
+    Admin, //Connection and session lifecycle events along with administrative messages.
+    Orders, //Order entry messages, like ExecutionReport and OrderCancelReject.
+    MarketData, //MarketDataRequest, MarketDataSnapshotFullRefresh, MarketDataIncrementalRefresh, and MarketDataRequestReject.
+    Errors, //Failures, refusals, and rejected or garbled messages.
+    Other, //Any other application message.
+}
+
+pub enum EventSinkError {
+    Full(EngineEvent), //Sink is at capacity. The event is handed back.
+    Disconnected(EngineEvent), //Receiving side is gone. The event is handed back.
+}
+
+//Receiving end of a topic. Implemented for the standard library's channels. Channels from other
+//crates, such as crossbeam-channel or flume, can be used by implementing this for their senders.
+//Called on the thread doing the routing so it must not block.
+pub trait EventSink: Send {
+    fn try_send(&self,event: EngineEvent) -> Result<(),EventSinkError>;
+}
+
+impl EventSink for SyncSender<EngineEvent> {
+    fn try_send(&self,event: EngineEvent) -> Result<(),EventSinkError> {
+        match SyncSender::try_send(self,event) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(event)) => Err(EventSinkError::Full(event)),
+            Err(TrySendError::Disconnected(event)) => Err(EventSinkError::Disconnected(event)),
+        }
+    }
+}
+
+impl EventSink for Sender<EngineEvent> {
+    fn try_send(&self,event: EngineEvent) -> Result<(),EventSinkError> {
+        //Unbounded so it's never full.
+        self.send(event).map_err(|e| EventSinkError::Disconnected(e.0))
+    }
+}
+
+pub enum Dispatched {
+    Delivered(EventTopic),
+    Full(EventTopic,EngineEvent), //Topic's sink was full. The event is handed back so it can be retried or handled directly.
+    Dropped(EventTopic), //Topic's sink was full and the topic drops events when full so the event was thrown away. See EventRouter::drop_when_full().
+    Unrouted(EventTopic,EngineEvent), //Topic has no sink or its receiving side is gone. The event is handed back so it can be handled directly.
+}
+
+struct Route {
+    sink: Box<EventSink>,
+    dropped_count: u64,
+}
+
+//Splits the single stream of events from Engine::poll() into separate channels by topic so each
+//part of a multi-threaded application only sees what it cares about. For example, one thread can
+//handle orders while another handles market data and a third watches for errors. Every topic has
+//its own bounded capacity so a slow consumer of one topic can't hold up the others. Events for a
+//topic that is full are handed back instead of blocking the routing thread. Topics where only the
+//latest data matters can drop and count them instead. No topic does this by default.
+//
+//Messages are assigned to a topic by MsgType. Each application message not covered by the defaults
+//can be assigned with msg_type_topic().
+pub struct EventRouter {
+    routes: HashMap<EventTopic,Route>,
+    msg_type_topics: HashMap<Vec<u8>,EventTopic>,
+    drop_when_full_topics: HashSet<EventTopic>,
+}
+
+impl EventRouter {
+    pub fn new() -> EventRouter {
+        EventRouter {
+            routes: HashMap::new(),
+            msg_type_topics: HashMap::new(),
+            drop_when_full_topics: HashSet::new(),
+        }
+    }
+
+    pub fn route<S: EventSink + 'static>(&mut self,topic: EventTopic,sink: S) {
+        //Replaces any existing sink for the topic.
+        self.routes.insert(topic,Route {
+            sink: Box::new(sink),
+            dropped_count: 0,
+        });
+    }
+
+    pub fn bounded(&mut self,topic: EventTopic,capacity: usize) -> Receiver<EngineEvent> {
+        //Route topic to a new standard library channel that holds at most capacity events.
+        let (tx,rx) = mpsc::sync_channel(capacity);
+        self.route(topic,tx);
+
+        rx
+    }
+
+    pub fn msg_type_topic<T: AsRef<[u8]>>(&mut self,msg_type: T,topic: EventTopic) {
+        //Override which topic received messages of this MsgType go to.
+        self.msg_type_topics.insert(msg_type.as_ref().to_vec(),topic);
+    }
+
+    pub fn drop_when_full(&mut self,topic: EventTopic,drop_when_full: bool) {
+        //Choose whether events for a full topic are thrown away and counted, as Dispatched::Dropped,
+        //or handed back, as Dispatched::Full. Nothing is dropped by default. Be careful with
+        //MarketData because dropping a MarketDataIncrementalRefresh leaves the consumer's book
+        //wrong until the next snapshot.
+        if drop_when_full {
+            self.drop_when_full_topics.insert(topic);
+        }
+        else {
+            self.drop_when_full_topics.remove(&topic);
+        }
+    }
+
+    pub fn dropped_count(&self,topic: EventTopic) -> u64 {
+        //Number of events thrown away because the topic's sink was full. See drop_when_full().
+        self.routes.get(&topic).map_or(0,|route| route.dropped_count)
+    }
+
+    pub fn topic(&self,event: &EngineEvent) -> EventTopic {
+        match *event {
+            EngineEvent::MessageReceived(_,ref message) => self.message_topic(&**message),
+            EngineEvent::MessageReceivedWithInstrument(ref data) => self.message_topic(&*data.message),
+            EngineEvent::ConnectionFailed(..) |
+            EngineEvent::ConnectionDropped(..) |
+            EngineEvent::ConnectionRefused(..) |
+            EngineEvent::ListenerFailed(..) |
+            EngineEvent::ListenerAcceptFailed(..) |
+            EngineEvent::MessageReceivedGarbled(..) |
+            EngineEvent::MessageRejected(..) |
+            EngineEvent::MessageStoreError(..) |
+            EngineEvent::OutboundValidationFailed(..) |
+            EngineEvent::DuplicateOrderRefused(..) |
+            EngineEvent::LogonRefusedByCoordinator(..) |
+            EngineEvent::SessionCoordinatorError(..) |
+            EngineEvent::FatalError(..) => EventTopic::Errors,
+            _ => EventTopic::Admin,
+        }
+    }
+
+    pub fn dispatch(&mut self,event: EngineEvent) -> Dispatched {
+        let topic = self.topic(&event);
+        let result = match self.routes.get_mut(&topic) {
+            Some(route) => match route.sink.try_send(event) {
+                Ok(()) => return Dispatched::Delivered(topic),
+                Err(EventSinkError::Full(event)) => {
+                    if !self.drop_when_full_topics.contains(&topic) {
+                        return Dispatched::Full(topic,event);
+                    }
+
+                    route.dropped_count += 1;
+                    return Dispatched::Dropped(topic);
+                },
+                Err(EventSinkError::Disconnected(event)) => event,
+            },
+            None => return Dispatched::Unrouted(topic,event),
+        };
+
+        //Nobody is listening anymore so stop routing to it.
+        self.routes.remove(&topic);
+        Dispatched::Unrouted(topic,result)
+    }
+
+    pub fn poll_and_dispatch<D: Into<Option<Duration>>>(&mut self,engine: &mut Engine,duration: D) -> Option<Dispatched> {
+        //Wait for the next event like Engine::poll() and route it. Returns None if no event
+        //arrived in time.
+        engine.poll(duration).map(|event| self.dispatch(event))
+    }
+
+    fn message_topic(&self,message: &FIXTMessage) -> EventTopic {
+        if let Some(topic) = self.msg_type_topics.get(message.msg_type()) {
+            return *topic;
+        }
+
+        match MsgType::from_bytes(message.msg_type()) {
+            Some(msg_type) if msg_type.is_administrative() => EventTopic::Admin,
+            Some(MsgType::NewOrderSingle) |
+            Some(MsgType::NewOrderList) |
+            Some(MsgType::NewOrderMultileg) |
+            Some(MsgType::NewOrderCross) |
+            Some(MsgType::OrderCancelRequest) |
+            Some(MsgType::OrderCancelReplaceRequest) |
+            Some(MsgType::OrderCancelReject) |
+            Some(MsgType::OrderStatusRequest) |
+            Some(MsgType::OrderMassCancelRequest) |
+            Some(MsgType::OrderMassCancelReport) |
+            Some(MsgType::OrderMassStatusRequest) |
+            Some(MsgType::MultilegOrderCancelReplace) |
+            Some(MsgType::CrossOrderCancelRequest) |
+            Some(MsgType::CrossOrderCancelReplaceRequest) |
+            Some(MsgType::ExecutionReport) |
+            Some(MsgType::DontKnowTrade) => EventTopic::Orders,
+            Some(MsgType::MarketDataRequest) |
+            Some(MsgType::MarketDataSnapshotFullRefresh) |
+            Some(MsgType::MarketDataIncrementalRefresh) |
+            Some(MsgType::MarketDataRequestReject) => EventTopic::MarketData,
+            _ => EventTopic::Other,
+        }
+    }
+}
+
+impl Default for EventRouter {
+    fn default() -> Self {
+        EventRouter::new()
+    }
+}
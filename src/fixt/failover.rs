@@ -0,0 +1,237 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fs::{self,File,OpenOptions};
+use std::io::{self,Read,Write};
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path,PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration,SystemTime,UNIX_EPOCH};
+
+use fixt::session_id::SessionID;
+
+const LEASE_FILE_NAME: &'static str = "lease";
+const LEASE_TEMP_FILE_NAME: &'static str = "lease.tmp";
+const LEASE_LOCK_FILE_NAME: &'static str = "lease.lock";
+
+const LOCK_EX: c_int = 2;
+const LOCK_NB: c_int = 4;
+
+extern "C" {
+    fn flock(fd: c_int,operation: c_int) -> c_int;
+}
+
+//Decides which of several engine processes is the active one for a session so a standby can take
+//over from a failed primary without both ever being logged on at the same time. Attach one to a
+//connection with Engine::set_session_coordinator(). The engine calls try_acquire() before sending
+//a Logon and refuses to log on when it fails. Once logged on, the engine calls renew() every
+//renew_interval() and disconnects immediately, without a Logout, as soon as it fails or
+//is_active() returns false. release() is called when the connection is terminated.
+//
+//Every function is called on the engine's thread so none of them may block. Return an error with
+//io::ErrorKind::WouldBlock from try_acquire() or renew() when the answer isn't available yet, like
+//when another process is busy with the claim, and the engine will call it again shortly.
+pub trait SessionCoordinator: Send + Sync {
+    fn try_acquire(&self) -> Result<bool,io::Error>; //Become the active engine unless another process already is. Returns true if this process is now active.
+    fn renew(&self) -> Result<bool,io::Error>; //Extend this process's claim. Returns false if this process is no longer active, such as when a standby took over during a long pause.
+    fn release(&self) -> Result<(),io::Error>; //Step down so a standby can take over right away instead of waiting for the claim to expire.
+    fn is_active(&self) -> bool; //Whether this process's claim hasn't expired yet going by the local clock alone. Checked before every message is sent.
+    fn renew_interval(&self) -> Duration; //How often renew() is called while a connection is logged on.
+}
+
+struct LeaseRecord {
+    epoch: u64,
+    expires_at: u64, //Milliseconds since the UNIX epoch.
+    owner: String,
+}
+
+#[derive(Clone,Copy)]
+struct HeldLease {
+    epoch: u64,
+    expires_at: u64, //Same as the LeaseRecord last written by this process.
+}
+
+//Exclusive lock on the lease.lock file. The OS releases it when the file is closed, including
+//when the process dies, so a lock can never be left behind and nothing has to break one.
+struct LeaseLock {
+    _file: File,
+}
+
+//A SessionCoordinator backed by a lease file that every engine process can reach, such as on a
+//shared volume. The lease file lives next to the session's FileMessageStore files so a standby
+//finds everything it needs to resume the session in one directory:
+//  <root>/<SessionID::file_name()>/lease: "<epoch> <expires at> <owner>\n", replaced atomically
+//                                         using a rename.
+//  <root>/<SessionID::file_name()>/lease.lock: Locked with flock() while a process is reading or
+//                                              changing the lease. Never removed.
+//The lease is held for duration after each renewal so renew_interval() is a third of it. The epoch
+//increases every time the lease changes hands and can be used as a fencing token by anything else
+//the active engine writes to. Every process must use a different owner and have a reasonably
+//accurate clock. The shared volume must support flock(), which NFS does on Linux. The lock is only
+//tried once per call so a busy lease returns a WouldBlock error instead of waiting for the other
+//process.
+pub struct FileLease {
+    directory: PathBuf,
+    owner: String,
+    duration: Duration,
+    held: Mutex<Option<HeldLease>>, //Lease while this process holds it.
+}
+
+impl FileLease {
+    pub fn new<P: AsRef<Path>,S: Into<String>>(directory: P,owner: S,duration: Duration) -> FileLease {
+        FileLease {
+            directory: directory.as_ref().to_path_buf(),
+            owner: owner.into(),
+            duration: duration,
+            held: Mutex::new(None),
+        }
+    }
+
+    pub fn for_session<P: AsRef<Path>,S: Into<String>>(root_directory: P,session_id: &SessionID,owner: S,duration: Duration) -> FileLease {
+        //Use the same directory as FileMessageStore::open_session().
+        FileLease::new(root_directory.as_ref().join(session_id.file_name()),owner,duration)
+    }
+
+    pub fn epoch(&self) -> Option<u64> {
+        //Fencing token of the lease while this process holds it.
+        self.held.lock().unwrap().map(|held| held.epoch)
+    }
+
+    fn lock(&self) -> Result<LeaseLock,io::Error> {
+        try!(fs::create_dir_all(&self.directory));
+
+        //The lock file is shared by every process and left in place. Only the flock() on it
+        //matters so a file left over from a process that died doesn't block anyone.
+        let file = try!(OpenOptions::new().write(true).create(true).open(self.directory.join(LEASE_LOCK_FILE_NAME)));
+        if unsafe { flock(file.as_raw_fd(),LOCK_EX | LOCK_NB) } != 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::WouldBlock {
+                return Err(io::Error::new(io::ErrorKind::WouldBlock,"Lease is locked by another process"));
+            }
+            return Err(e);
+        }
+
+        Ok(LeaseLock { _file: file })
+    }
+
+    fn read(&self) -> Result<Option<LeaseRecord>,io::Error> {
+        let mut contents = String::new();
+        match File::open(self.directory.join(LEASE_FILE_NAME)) {
+            Ok(mut file) => { try!(file.read_to_string(&mut contents)); },
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let mut values = contents.trim_right_matches('\n').splitn(3,' ');
+        match (values.next().and_then(|epoch| epoch.parse().ok()),values.next().and_then(|expires_at| expires_at.parse().ok()),values.next()) {
+            (Some(epoch),Some(expires_at),Some(owner)) => Ok(Some(LeaseRecord {
+                epoch: epoch,
+                expires_at: expires_at,
+                owner: owner.to_string(),
+            })),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData,"lease file is corrupt")),
+        }
+    }
+
+    fn write(&self,epoch: u64,expires_at: u64) -> Result<(),io::Error> {
+        let temp_path = self.directory.join(LEASE_TEMP_FILE_NAME);
+        {
+            let mut temp_file = try!(File::create(&temp_path));
+            try!(write!(temp_file,"{} {} {}\n",epoch,expires_at,self.owner));
+            try!(temp_file.sync_all());
+        }
+
+        fs::rename(&temp_path,self.directory.join(LEASE_FILE_NAME))
+    }
+
+    fn expires_at(&self,now: u64) -> u64 {
+        now + self.duration.as_secs() * 1000 + (self.duration.subsec_nanos() / 1_000_000) as u64
+    }
+}
+
+impl SessionCoordinator for FileLease {
+    fn try_acquire(&self) -> Result<bool,io::Error> {
+        let _lock = try!(self.lock());
+        let now = now_millis();
+        let epoch = match try!(self.read()) {
+            Some(ref record) if record.owner == self.owner => record.epoch,
+            Some(ref record) if record.expires_at > now => {
+                *self.held.lock().unwrap() = None;
+                return Ok(false);
+            },
+            Some(ref record) => record.epoch + 1,
+            None => 1,
+        };
+        let expires_at = self.expires_at(now);
+        try!(self.write(epoch,expires_at));
+
+        *self.held.lock().unwrap() = Some(HeldLease {
+            epoch: epoch,
+            expires_at: expires_at,
+        });
+        Ok(true)
+    }
+
+    fn renew(&self) -> Result<bool,io::Error> {
+        let mut held = self.held.lock().unwrap();
+        let epoch = match *held {
+            Some(held) => held.epoch,
+            None => return Ok(false),
+        };
+
+        //An expired lease can still be renewed as long as nobody else took it in the meantime.
+        let _lock = try!(self.lock());
+        match try!(self.read()) {
+            Some(ref record) if record.owner == self.owner && record.epoch == epoch => {},
+            _ => {
+                *held = None;
+                return Ok(false);
+            },
+        }
+        let expires_at = self.expires_at(now_millis());
+        try!(self.write(epoch,expires_at));
+
+        *held = Some(HeldLease {
+            epoch: epoch,
+            expires_at: expires_at,
+        });
+        Ok(true)
+    }
+
+    fn release(&self) -> Result<(),io::Error> {
+        let mut held = self.held.lock().unwrap();
+        let epoch = match held.take() {
+            Some(held) => held.epoch,
+            None => return Ok(()),
+        };
+
+        //Expire the lease instead of removing it so the epoch keeps increasing.
+        let _lock = try!(self.lock());
+        match try!(self.read()) {
+            Some(ref record) if record.owner == self.owner && record.epoch == epoch => self.write(epoch,0),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.held.lock().unwrap().map_or(false,|held| now_millis() < held.expires_at)
+    }
+
+    fn renew_interval(&self) -> Duration {
+        self.duration / 3
+    }
+}
+
+fn now_millis() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or(Duration::from_secs(0));
+    now.as_secs() * 1000 + (now.subsec_nanos() / 1_000_000) as u64
+}
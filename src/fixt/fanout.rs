@@ -0,0 +1,170 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashSet;
+use std::sync::{Arc,Mutex};
+use std::sync::mpsc::{self,Receiver,Sender};
+
+use dictionary::fields::Symbol;
+use field::Field;
+use field_tag::FieldTag;
+use fixt::message::{FIXTMessage,display_versions};
+use message::MessageVisitor;
+
+//Selects which messages a fanout subscriber receives. An empty filter matches every message. When
+//both MsgTypes and symbols are given, a message must match both.
+#[derive(Clone,Debug,Default)]
+pub struct FanoutFilter {
+    msg_types: HashSet<Vec<u8>>,
+    symbols: HashSet<Vec<u8>>,
+}
+
+impl FanoutFilter {
+    pub fn new() -> FanoutFilter {
+        FanoutFilter {
+            msg_types: HashSet::new(),
+            symbols: HashSet::new(),
+        }
+    }
+
+    pub fn msg_type<T: AsRef<[u8]>>(mut self,msg_type: T) -> FanoutFilter {
+        //Accepts a MsgType or raw bytes for MsgTypes that aren't standard.
+        self.msg_types.insert(msg_type.as_ref().to_vec());
+        self
+    }
+
+    pub fn symbol(mut self,symbol: &[u8]) -> FanoutFilter {
+        self.symbols.insert(symbol.to_vec());
+        self
+    }
+
+    pub fn matches(&self,message: &FIXTMessage) -> bool {
+        if !self.msg_types.is_empty() && !self.msg_types.contains(message.msg_type()) {
+            return false;
+        }
+
+        if self.symbols.is_empty() {
+            return true;
+        }
+
+        //Most messages have at most one Symbol so check it directly before looking through any
+        //repeating groups.
+        if let Some(symbol) = message.symbol_bytes() {
+            if self.symbols.contains(symbol) {
+                return true;
+            }
+        }
+
+        message_symbols(message).iter().any(|symbol| self.symbols.contains(symbol))
+    }
+}
+
+struct Subscriber {
+    filter: FanoutFilter,
+    tx: Sender<Box<FIXTMessage + Send>>,
+}
+
+//Shares one session's inbound messages with any number of in-process consumers. The thread that
+//calls Engine::poll() passes each received message to dispatch() and every subscriber with a
+//matching filter gets its own copy over a channel. Fanout is a cheap handle that can be cloned and
+//handed to other threads so they can subscribe themselves. Subscribers are removed automatically
+//once their Receiver is dropped.
+#[derive(Clone)]
+pub struct Fanout {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl Fanout {
+    pub fn new() -> Fanout {
+        Fanout {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn subscribe(&self,filter: FanoutFilter) -> Receiver<Box<FIXTMessage + Send>> {
+        let (tx,rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(Subscriber {
+            filter: filter,
+            tx: tx,
+        });
+
+        rx
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+
+    pub fn dispatch(&self,message: Box<FIXTMessage + Send>) -> usize {
+        //Returns the number of subscribers the message was delivered to. The last matching
+        //subscriber gets the original message so it's only copied when there is more than one.
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let matching_indexes: Vec<usize> = subscribers.iter().enumerate()
+            .filter(|&(_,subscriber)| subscriber.filter.matches(&*message))
+            .map(|(index,_)| index)
+            .collect();
+
+        let mut message = Some(message);
+        let mut delivered_count = 0;
+        let mut disconnected_indexes = Vec::new();
+        for (i,index) in matching_indexes.iter().enumerate() {
+            let subscriber_message = if i + 1 == matching_indexes.len() {
+                message.take().unwrap()
+            }
+            else {
+                message.as_ref().unwrap().clone_into_box()
+            };
+
+            if subscribers[*index].tx.send(subscriber_message).is_ok() {
+                delivered_count += 1;
+            }
+            else {
+                disconnected_indexes.push(*index);
+            }
+        }
+
+        for index in disconnected_indexes.into_iter().rev() {
+            subscribers.remove(index);
+        }
+
+        delivered_count
+    }
+}
+
+impl Default for Fanout {
+    fn default() -> Self {
+        Fanout::new()
+    }
+}
+
+struct SymbolVisitor {
+    symbols: Vec<Vec<u8>>,
+}
+
+impl MessageVisitor for SymbolVisitor {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,value: &[u8]) {
+        if tag == Symbol::tag() {
+            self.symbols.push(value.to_vec());
+        }
+    }
+}
+
+fn message_symbols(message: &FIXTMessage) -> Vec<Vec<u8>> {
+    //Every Symbol in the message, including those inside repeating groups. For example, a
+    //MarketDataIncrementalRefresh can cover many symbols at once.
+    let (fix_version,message_version) = display_versions(message.meta());
+    let mut visitor = SymbolVisitor {
+        symbols: Vec::new(),
+    };
+    message.visit(fix_version,message_version,&mut visitor);
+
+    visitor.symbols
+}
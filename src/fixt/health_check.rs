@@ -0,0 +1,93 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::sync::{Arc,Mutex};
+use std::time::{Duration,Instant};
+
+use clock::Clock;
+use fixt::engine::Connection;
+
+//Liveness of a single connection at the moment it was last published by the engine's thread.
+//Published every time the connection is serviced so it's never more than one network event or
+//timeout behind.
+#[derive(Clone,Copy,Debug)]
+pub struct HealthCheck {
+    pub established: bool, //True once Logon completed and until logging out begins.
+    pub last_inbound_time: Option<Instant>, //When bytes were last received. None until something is received.
+    pub last_outbound_time: Option<Instant>, //When bytes were last sent. None until something is sent.
+    pub inbound_msg_seq_num: u64, //MsgSeqNum expected for the next received message.
+    pub outbound_msg_seq_num: u64, //MsgSeqNum to be used for the next sent message.
+    pub checked_time: Instant, //When the HealthCheck was taken according to the engine's Clock. Idle times are measured up to here.
+}
+
+impl HealthCheck {
+    pub fn new() -> HealthCheck {
+        HealthCheck {
+            established: false,
+            last_inbound_time: None,
+            last_outbound_time: None,
+            inbound_msg_seq_num: 1,
+            outbound_msg_seq_num: 1,
+            checked_time: Instant::now(),
+        }
+    }
+
+    pub fn inbound_idle(&self) -> Option<Duration> {
+        self.last_inbound_time.map(|time| self.checked_time.duration_since(time))
+    }
+
+    pub fn outbound_idle(&self) -> Option<Duration> {
+        self.last_outbound_time.map(|time| self.checked_time.duration_since(time))
+    }
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        HealthCheck::new()
+    }
+}
+
+pub type HealthCheckRegistry = Arc<Mutex<HashMap<Connection,Arc<Mutex<HealthCheck>>>>>;
+
+//Read-only view of every connection's HealthCheck that can be cloned and handed to another thread,
+//such as one running a watchdog. Checking never has to wait on the engine's thread servicing the
+//network so it's cheap enough to call as often as needed. Connections are removed once they are
+//terminated.
+#[derive(Clone)]
+pub struct HealthMonitor {
+    registry: HealthCheckRegistry,
+    clock: Arc<Clock>,
+}
+
+impl HealthMonitor {
+    pub fn new(registry: HealthCheckRegistry,clock: Arc<Clock>) -> HealthMonitor {
+        HealthMonitor {
+            registry: registry,
+            clock: clock,
+        }
+    }
+
+    pub fn health_check(&self,connection: Connection) -> Option<HealthCheck> {
+        let health_check = match self.registry.lock().unwrap().get(&connection) {
+            Some(health_check) => health_check.clone(),
+            None => return None,
+        };
+
+        let mut health_check = *health_check.lock().unwrap();
+        health_check.checked_time = self.clock.now();
+        Some(health_check)
+    }
+
+    pub fn connections(&self) -> Vec<Connection> {
+        self.registry.lock().unwrap().keys().cloned().collect()
+    }
+}
@@ -0,0 +1,347 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self,Write};
+
+use dictionary::fields::{ClOrdID,ExecID,ExecType,LastPx,LastQty,MsgSeqNum,OrderID,OrderQty,OrdStatus,OrigClOrdID,Price,SendingTime,SideField,Symbol,TransactTime};
+use dictionary::messages::{ExecutionReport,NewOrderMultileg,NewOrderSingle,OrderCancelReject,OrderCancelReplaceRequest,OrderCancelRequest};
+use field::Field;
+use field_tag::FieldTag;
+use fix::{ParseError,Parser};
+use fix_version::FIXVersion;
+use fixt::message::{BuildFIXTMessage,FIXTMessage};
+use fixt::message_store::MessageStore;
+use message::{Message,MessageVisitor};
+use message_version::MessageVersion;
+
+//What happened to an order according to a message. Messages that aren't one of these are not
+//exported.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum OrderEventType {
+    New, //NewOrderSingle or NewOrderMultileg.
+    Replace, //OrderCancelReplaceRequest.
+    Cancel, //OrderCancelRequest.
+    Execution, //ExecutionReport.
+    CancelReject, //OrderCancelReject.
+}
+
+impl OrderEventType {
+    pub fn from_msg_type(msg_type: &[u8]) -> Option<OrderEventType> {
+        if msg_type == NewOrderSingle::msg_type() || msg_type == NewOrderMultileg::msg_type() {
+            Some(OrderEventType::New)
+        }
+        else if msg_type == OrderCancelReplaceRequest::msg_type() {
+            Some(OrderEventType::Replace)
+        }
+        else if msg_type == OrderCancelRequest::msg_type() {
+            Some(OrderEventType::Cancel)
+        }
+        else if msg_type == ExecutionReport::msg_type() {
+            Some(OrderEventType::Execution)
+        }
+        else if msg_type == OrderCancelReject::msg_type() {
+            Some(OrderEventType::CancelReject)
+        }
+        else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            OrderEventType::New => "NEW",
+            OrderEventType::Replace => "REPLACE",
+            OrderEventType::Cancel => "CANCEL",
+            OrderEventType::Execution => "EXECUTION",
+            OrderEventType::CancelReject => "CANCEL_REJECT",
+        }
+    }
+}
+
+//One column of an exported record. The String is written in the header row.
+#[derive(Clone,Debug,PartialEq)]
+pub enum JournalColumn {
+    Field(String,FieldTag), //Serialized value of a field at the top level of the message. Empty when the message doesn't have it.
+    MsgType(String),
+    EventType(String), //See OrderEventType::name().
+    RootClOrdID(String), //First ClOrdID in the chain of OrigClOrdIDs that leads to this message. Ties every replace, cancel, and execution back to the original order.
+}
+
+impl JournalColumn {
+    pub fn field<F: Field>() -> JournalColumn {
+        //Column named after the field. Eg. JournalColumn::field::<ClOrdID>().
+        JournalColumn::Field(F::name().to_string(),F::tag())
+    }
+
+    fn header(&self) -> &str {
+        match *self {
+            JournalColumn::Field(ref header,_) |
+            JournalColumn::MsgType(ref header) |
+            JournalColumn::EventType(ref header) |
+            JournalColumn::RootClOrdID(ref header) => header,
+        }
+    }
+}
+
+//Controls the layout of the exported records.
+#[derive(Clone,Debug)]
+pub struct JournalExportSettings {
+    pub columns: Vec<JournalColumn>,
+    pub delimiter: u8, //Written between columns. Values containing it are quoted like CSV.
+    pub include_header: bool, //Write a row of column headers before the first record.
+    pub default_message_version: MessageVersion, //Used to parse stored messages that don't specify their own ApplVerID.
+}
+
+impl JournalExportSettings {
+    pub fn new() -> JournalExportSettings {
+        //A starting point that covers what most order event reports, like CAT or MiFID II
+        //transaction reports, are built from. Reorder, rename, and add columns to match the layout
+        //being submitted.
+        JournalExportSettings {
+            columns: vec![
+                JournalColumn::field::<MsgSeqNum>(),
+                JournalColumn::field::<SendingTime>(),
+                JournalColumn::field::<TransactTime>(),
+                JournalColumn::MsgType(String::from("MsgType")),
+                JournalColumn::EventType(String::from("EventType")),
+                JournalColumn::field::<ClOrdID>(),
+                JournalColumn::field::<OrigClOrdID>(),
+                JournalColumn::RootClOrdID(String::from("RootClOrdID")),
+                JournalColumn::field::<OrderID>(),
+                JournalColumn::field::<ExecID>(),
+                JournalColumn::field::<ExecType>(),
+                JournalColumn::field::<OrdStatus>(),
+                JournalColumn::field::<Symbol>(),
+                JournalColumn::Field(String::from("Side"),SideField::tag()),
+                JournalColumn::field::<OrderQty>(),
+                JournalColumn::field::<Price>(),
+                JournalColumn::field::<LastQty>(),
+                JournalColumn::field::<LastPx>(),
+            ],
+            delimiter: b',',
+            include_header: true,
+            default_message_version: MessageVersion::FIX50SP2,
+        }
+    }
+}
+
+impl Default for JournalExportSettings {
+    fn default() -> Self {
+        JournalExportSettings::new()
+    }
+}
+
+pub enum JournalExportError {
+    Io(io::Error),
+    Corrupt(u64), //Stored message with this MsgSeqNum is not a complete FIX message.
+    Parse(u64,ParseError), //Stored message with this MsgSeqNum could not be parsed.
+}
+
+impl From<io::Error> for JournalExportError {
+    fn from(e: io::Error) -> JournalExportError {
+        JournalExportError::Io(e)
+    }
+}
+
+impl fmt::Display for JournalExportError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JournalExportError::Io(ref e) => write!(f,"JournalExportError::Io({})",e),
+            JournalExportError::Corrupt(msg_seq_num) => write!(f,"JournalExportError::Corrupt({})",msg_seq_num),
+            JournalExportError::Parse(msg_seq_num,ref e) => write!(f,"JournalExportError::Parse({},{})",msg_seq_num,e),
+        }
+    }
+}
+
+impl fmt::Debug for JournalExportError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        <JournalExportError as fmt::Display>::fmt(self,f)
+    }
+}
+
+//Collects the serialized value of every field outside of repeating groups. Repeating group
+//counts are kept under the NoXXX tag.
+struct TopLevelFields {
+    depth: usize,
+    fields: HashMap<FieldTag,Vec<u8>>,
+}
+
+impl MessageVisitor for TopLevelFields {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,value: &[u8]) {
+        if self.depth == 0 {
+            self.fields.insert(tag,value.to_vec());
+        }
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,count: usize) {
+        if self.depth == 0 {
+            self.fields.insert(tag,count.to_string().into_bytes());
+        }
+        self.depth += 1;
+    }
+
+    fn end_group(&mut self,_tag: FieldTag,_name: &'static str) {
+        self.depth -= 1;
+    }
+}
+
+fn write_value<W: Write>(writer: &mut W,value: &[u8],delimiter: u8) -> Result<(),io::Error> {
+    //Quote values that would otherwise break the layout. Quotes inside are doubled like CSV.
+    if !value.iter().any(|byte| *byte == delimiter || *byte == b'"' || *byte == b'\n' || *byte == b'\r') {
+        return writer.write_all(value);
+    }
+
+    let mut quoted_value = Vec::with_capacity(value.len() + 2);
+    quoted_value.push(b'"');
+    for byte in value {
+        if *byte == b'"' {
+            quoted_value.push(b'"');
+        }
+        quoted_value.push(*byte);
+    }
+    quoted_value.push(b'"');
+
+    writer.write_all(&quoted_value)
+}
+
+//Writes one record per order event for compliance extracts, such as CAT or transaction reporting.
+//Records are written in the order messages are exported and ClOrdID chains are followed across
+//calls so export messages in the order they were sent or received. The message store only keeps
+//outbound messages so received messages, like ExecutionReports, need to be added separately with
+//export_message() when the report needs them.
+pub struct JournalExporter {
+    parser: Parser,
+    settings: JournalExportSettings,
+    root_cl_ord_ids: HashMap<Vec<u8>,Vec<u8>>, //ClOrdID to the first ClOrdID of its chain.
+    wrote_header: bool,
+}
+
+impl JournalExporter {
+    pub fn new(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,max_message_size: u64,settings: JournalExportSettings) -> JournalExporter {
+        //message_dictionary must include every order message that should be exported. Other
+        //messages in the store are skipped without being parsed.
+        let mut parser = Parser::new(message_dictionary,max_message_size);
+        parser.set_default_message_version(settings.default_message_version);
+
+        JournalExporter {
+            parser: parser,
+            settings: settings,
+            root_cl_ord_ids: HashMap::new(),
+            wrote_header: false,
+        }
+    }
+
+    pub fn export_store<W: Write>(&mut self,message_store: &mut MessageStore,writer: &mut W) -> Result<usize,JournalExportError> {
+        //Export every stored outbound message that is an order event in MsgSeqNum order. Returns
+        //the number of records written.
+        let end = message_store.next_outbound_msg_seq_num();
+        let stored_messages = try!(message_store.outbound_messages(1..end));
+
+        let mut record_count = 0;
+        for (msg_seq_num,bytes) in stored_messages {
+            //Only the header is needed to decide whether the message is exported.
+            let is_order_event = match self.parser.prescan(&bytes[..]) {
+                Some(prescanned_message) => OrderEventType::from_msg_type(prescanned_message.msg_type).is_some(),
+                None => return Err(JournalExportError::Corrupt(msg_seq_num)),
+            };
+            if !is_order_event {
+                continue;
+            }
+
+            let (_,result) = self.parser.parse(&bytes[..]);
+            if let Err(e) = result {
+                self.parser.reset_parser();
+                return Err(JournalExportError::Parse(msg_seq_num,e));
+            }
+            let message = match self.parser.messages.pop() {
+                Some(message) => message,
+                None => return Err(JournalExportError::Corrupt(msg_seq_num)),
+            };
+            self.parser.messages.clear();
+
+            if try!(self.export_message(&*message,writer)) {
+                record_count += 1;
+            }
+        }
+
+        Ok(record_count)
+    }
+
+    pub fn export_message<W: Write>(&mut self,message: &FIXTMessage,writer: &mut W) -> Result<bool,io::Error> {
+        //Write a record for message if it's an order event. Returns whether a record was written.
+        let event_type = match OrderEventType::from_msg_type(message.msg_type()) {
+            Some(event_type) => event_type,
+            None => return Ok(false),
+        };
+
+        if self.settings.include_header && !self.wrote_header {
+            try!(self.write_header(writer));
+        }
+        self.wrote_header = true;
+
+        let (fix_version,message_version) = match *message.meta() {
+            Some(ref meta) => (meta.begin_string,meta.message_version),
+            None => (FIXVersion::FIXT_1_1,self.settings.default_message_version),
+        };
+        let mut top_level_fields = TopLevelFields {
+            depth: 0,
+            fields: HashMap::new(),
+        };
+        message.visit(fix_version,message_version,&mut top_level_fields);
+        let fields = top_level_fields.fields;
+
+        //Prefer following OrigClOrdID because a replace introduces a new ClOrdID that hasn't been
+        //seen yet. The first ClOrdID seen starts a new chain.
+        let cl_ord_id = fields.get(&ClOrdID::tag()).cloned().unwrap_or_default();
+        let orig_cl_ord_id = fields.get(&OrigClOrdID::tag()).cloned().unwrap_or_default();
+        let root_cl_ord_id = self.root_cl_ord_ids.get(&orig_cl_ord_id)
+            .or_else(|| self.root_cl_ord_ids.get(&cl_ord_id))
+            .cloned()
+            .unwrap_or_else(|| if orig_cl_ord_id.is_empty() { cl_ord_id.clone() } else { orig_cl_ord_id.clone() });
+        if !cl_ord_id.is_empty() && !self.root_cl_ord_ids.contains_key(&cl_ord_id) {
+            self.root_cl_ord_ids.insert(cl_ord_id,root_cl_ord_id.clone());
+        }
+
+        let mut record = Vec::new();
+        for (index,column) in self.settings.columns.iter().enumerate() {
+            if index > 0 {
+                record.push(self.settings.delimiter);
+            }
+
+            let value: &[u8] = match *column {
+                JournalColumn::Field(_,tag) => fields.get(&tag).map_or(&b""[..],|value| &value[..]),
+                JournalColumn::MsgType(_) => message.msg_type(),
+                JournalColumn::EventType(_) => event_type.name().as_bytes(),
+                JournalColumn::RootClOrdID(_) => &root_cl_ord_id[..],
+            };
+            try!(write_value(&mut record,value,self.settings.delimiter));
+        }
+        record.push(b'\n');
+        try!(writer.write_all(&record));
+
+        Ok(true)
+    }
+
+    fn write_header<W: Write>(&self,writer: &mut W) -> Result<(),io::Error> {
+        let mut header = Vec::new();
+        for (index,column) in self.settings.columns.iter().enumerate() {
+            if index > 0 {
+                header.push(self.settings.delimiter);
+            }
+            try!(write_value(&mut header,column.header().as_bytes(),self.settings.delimiter));
+        }
+        header.push(b'\n');
+
+        writer.write_all(&header)
+    }
+}
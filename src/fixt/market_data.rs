@@ -0,0 +1,282 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono;
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize,Ordering};
+use std::time::Duration;
+
+use clock::{Clock,SystemClock};
+use dictionary::field_types::other::{MDEntryType,MDReqRejReason,MDUpdateType,SubscriptionRequestType};
+use dictionary::fields::{Instrument,MDReqGrp};
+use dictionary::messages::{MarketDataIncrementalRefresh,MarketDataRequest,MarketDataRequestReject,MarketDataSnapshotFullRefresh};
+use fixt::engine::Connection;
+use fixt::message::FIXTMessage;
+use fixt::middleware::{InboundMiddleware,MiddlewareAction};
+use id_generator::{IdGenerator,PrefixIdGenerator};
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum SubscriptionStatus {
+    Pending, //Request was created but no response has been received yet.
+    Active, //At least one data message has been received for a subscription.
+    Completed, //Snapshot was received for a snapshot-only request. No more data is expected.
+    Rejected(Option<MDReqRejReason>,Vec<u8>), //Remote rejected the request with a reason and text.
+    Unsubscribed, //Unsubscribe request was created. Any data still received is ignored.
+}
+
+#[derive(Clone,Debug)]
+pub struct Subscription {
+    pub md_req_id: Vec<u8>,
+    pub subscription_request_type: SubscriptionRequestType,
+    pub symbols: Vec<Vec<u8>>,
+    pub md_entry_types: Vec<MDEntryType>,
+    pub market_depth: i64,
+    pub md_update_type: Option<MDUpdateType>,
+    pub status: SubscriptionStatus,
+}
+
+impl Subscription {
+    fn new_request(&self,subscription_request_type: SubscriptionRequestType) -> MarketDataRequest {
+        let mut message = MarketDataRequest::new();
+        message.md_req_id = self.md_req_id.clone();
+        message.subscription_request_type = subscription_request_type;
+        message.market_depth = self.market_depth;
+        message.md_update_type = self.md_update_type.clone();
+        for md_entry_type in &self.md_entry_types {
+            let mut group = MDReqGrp::new();
+            group.md_entry_type = md_entry_type.clone();
+            message.no_md_entry_types.push(Box::new(group));
+        }
+        for symbol in &self.symbols {
+            //Symbol moved from RelatedSym to Symbol in FIX 4.3. Set both so the request can be sent
+            //using any version.
+            let mut group = Instrument::new();
+            group.related_sym = symbol.clone();
+            group.symbol = symbol.clone();
+            message.no_related_sym.push(Box::new(group));
+        }
+
+        message
+    }
+}
+
+//Builds MarketDataRequest messages and keeps track of the resulting subscriptions. Each request
+//is assigned a unique MDReqID so MarketDataRequestReject, MarketDataSnapshotFullRefresh, and
+//MarketDataIncrementalRefresh messages can be matched back to the subscription that caused them.
+//
+//The returned MarketDataRequest messages are not sent automatically. Pass them to
+//Engine::send_message() like any other message.
+pub struct MarketDataSubscriptions {
+    md_req_id_generator: Box<IdGenerator + Send>,
+    subscriptions: HashMap<Vec<u8>,Subscription>,
+}
+
+impl MarketDataSubscriptions {
+    pub fn new(md_req_id_prefix: &[u8]) -> MarketDataSubscriptions {
+        MarketDataSubscriptions::with_id_generator(Box::new(PrefixIdGenerator::new(md_req_id_prefix)))
+    }
+
+    pub fn with_id_generator(md_req_id_generator: Box<IdGenerator + Send>) -> MarketDataSubscriptions {
+        MarketDataSubscriptions {
+            md_req_id_generator: md_req_id_generator,
+            subscriptions: HashMap::new(),
+        }
+    }
+
+    pub fn id_generator(&self) -> &IdGenerator {
+        &*self.md_req_id_generator
+    }
+
+    pub fn snapshot(&mut self,symbols: &[&[u8]],md_entry_types: &[MDEntryType],market_depth: i64) -> MarketDataRequest {
+        self.new_subscription(SubscriptionRequestType::Snapshot,symbols,md_entry_types,market_depth,None)
+    }
+
+    pub fn subscribe(&mut self,symbols: &[&[u8]],md_entry_types: &[MDEntryType],market_depth: i64,md_update_type: MDUpdateType) -> MarketDataRequest {
+        self.new_subscription(SubscriptionRequestType::SnapshotPlusUpdates,symbols,md_entry_types,market_depth,Some(md_update_type))
+    }
+
+    pub fn unsubscribe(&mut self,md_req_id: &[u8]) -> Option<MarketDataRequest> {
+        //Only subscriptions that could still be receiving updates can be unsubscribed. The same
+        //MDReqID as the original request must be used.
+        let subscription = match self.subscriptions.get_mut(md_req_id) {
+            Some(subscription) => subscription,
+            None => return None,
+        };
+        if subscription.subscription_request_type != SubscriptionRequestType::SnapshotPlusUpdates {
+            return None;
+        }
+        match subscription.status {
+            SubscriptionStatus::Pending | SubscriptionStatus::Active => {},
+            _ => return None,
+        }
+
+        subscription.status = SubscriptionStatus::Unsubscribed;
+        Some(subscription.new_request(SubscriptionRequestType::DisablePreviousSnapshotPlusUpdateRequest))
+    }
+
+    pub fn resubscribe(&mut self) -> Vec<MarketDataRequest> {
+        //New requests for every subscription that could still be receiving updates. Needed after
+        //switching to a new session, such as with Engine::rotate_session_identity(), because the
+        //counterparty forgets subscriptions made on the old one. Each subscription is given a new
+        //MDReqID and goes back to SubscriptionStatus::Pending. Its old MDReqID is forgotten.
+        let mut md_req_ids: Vec<Vec<u8>> = self.subscriptions.values().filter(|subscription| {
+            match subscription.status {
+                SubscriptionStatus::Pending | SubscriptionStatus::Active => subscription.subscription_request_type == SubscriptionRequestType::SnapshotPlusUpdates,
+                _ => false,
+            }
+        }).map(|subscription| subscription.md_req_id.clone()).collect();
+        md_req_ids.sort();
+
+        let mut messages = Vec::new();
+        for md_req_id in md_req_ids {
+            let mut subscription = self.subscriptions.remove(&md_req_id).unwrap();
+            subscription.md_req_id = self.md_req_id_generator.next_id();
+            subscription.status = SubscriptionStatus::Pending;
+            messages.push(subscription.new_request(SubscriptionRequestType::SnapshotPlusUpdates));
+            self.subscriptions.insert(subscription.md_req_id.clone(),subscription);
+        }
+
+        messages
+    }
+
+    pub fn get(&self,md_req_id: &[u8]) -> Option<&Subscription> {
+        self.subscriptions.get(md_req_id)
+    }
+
+    pub fn remove(&mut self,md_req_id: &[u8]) -> Option<Subscription> {
+        self.subscriptions.remove(md_req_id)
+    }
+
+    pub fn iter(&self) -> ::std::collections::hash_map::Values<Vec<u8>,Subscription> {
+        self.subscriptions.values()
+    }
+
+    pub fn on_message(&mut self,message: &FIXTMessage) -> Option<&Subscription> {
+        //Match any market data related message to its subscription and update the subscription's
+        //status. Returns None for unrelated messages, unknown MDReqIDs, and subscriptions that
+        //were already unsubscribed.
+        let message = message.as_any();
+        let (md_req_id,new_status) = if let Some(message) = message.downcast_ref::<MarketDataRequestReject>() {
+            (&message.md_req_id,Some(SubscriptionStatus::Rejected(message.md_req_rej_reason.clone(),message.text.clone())))
+        }
+        else if let Some(message) = message.downcast_ref::<MarketDataSnapshotFullRefresh>() {
+            (&message.md_req_id,None)
+        }
+        else if let Some(message) = message.downcast_ref::<MarketDataIncrementalRefresh>() {
+            (&message.md_req_id,None)
+        }
+        else {
+            return None;
+        };
+
+        let subscription = match self.subscriptions.get_mut(&md_req_id[..]) {
+            Some(subscription) => subscription,
+            None => return None,
+        };
+        match subscription.status {
+            SubscriptionStatus::Unsubscribed |
+            SubscriptionStatus::Rejected(_,_) => return None,
+            _ => {},
+        }
+
+        subscription.status = if let Some(new_status) = new_status {
+            new_status
+        }
+        else if subscription.subscription_request_type == SubscriptionRequestType::Snapshot {
+            SubscriptionStatus::Completed
+        }
+        else {
+            SubscriptionStatus::Active
+        };
+
+        Some(subscription)
+    }
+
+    fn new_subscription(&mut self,
+                        subscription_request_type: SubscriptionRequestType,
+                        symbols: &[&[u8]],
+                        md_entry_types: &[MDEntryType],
+                        market_depth: i64,
+                        md_update_type: Option<MDUpdateType>) -> MarketDataRequest {
+        let md_req_id = self.md_req_id_generator.next_id();
+
+        let subscription = Subscription {
+            md_req_id: md_req_id.clone(),
+            subscription_request_type: subscription_request_type.clone(),
+            symbols: symbols.iter().map(|symbol| symbol.to_vec()).collect(),
+            md_entry_types: md_entry_types.to_vec(),
+            market_depth: market_depth,
+            md_update_type: md_update_type,
+            status: SubscriptionStatus::Pending,
+        };
+        let message = subscription.new_request(subscription_request_type);
+        self.subscriptions.insert(md_req_id,subscription);
+
+        message
+    }
+}
+
+//Middleware that drops MarketDataSnapshotFullRefresh and MarketDataIncrementalRefresh messages
+//whose SendingTime is more than max_age before they were received according to the engine's Clock.
+//After a stall, it's usually
+//better to skip the backlog of stale ticks than to act on them. Every other message is passed
+//through. Add it with EngineBuilder::inbound_middleware().
+//
+//The age relies on the remote's clock so max_age should leave room for clock skew. Messages that
+//appear to come from the future are never dropped.
+pub struct StaleMarketDataFilter {
+    max_age: chrono::Duration,
+    dropped_count: Arc<AtomicUsize>,
+}
+
+impl StaleMarketDataFilter {
+    pub fn new(max_age: Duration) -> StaleMarketDataFilter {
+        StaleMarketDataFilter {
+            max_age: chrono::Duration::from_std(max_age).unwrap_or(chrono::Duration::max_value()),
+            dropped_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn dropped_count(&self) -> Arc<AtomicUsize> {
+        //Number of messages dropped so far. Grab this before handing the filter to the engine so
+        //it can still be read afterwards.
+        self.dropped_count.clone()
+    }
+
+    pub fn is_stale(&self,message: &FIXTMessage,now: DateTime<UTC>) -> bool {
+        let msg_type = message.msg_type();
+        if msg_type != MarketDataSnapshotFullRefresh::msg_type() && msg_type != MarketDataIncrementalRefresh::msg_type() {
+            return false;
+        }
+
+        now.signed_duration_since(message.sending_time()) > self.max_age
+    }
+}
+
+impl InboundMiddleware for StaleMarketDataFilter {
+    fn on_message(&mut self,connection: Connection,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        //Only called directly when used outside of an engine so there is no engine Clock to follow.
+        self.on_message_at(connection,None,SystemClock.utc_now(),message)
+    }
+
+    fn on_message_at(&mut self,_connection: Connection,_user_data: Option<&Arc<Any + Send + Sync>>,now: DateTime<UTC>,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        if self.is_stale(&*message,now) {
+            self.dropped_count.fetch_add(1,Ordering::Relaxed);
+            return MiddlewareAction::Drop;
+        }
+
+        MiddlewareAction::Continue(message)
+    }
+}
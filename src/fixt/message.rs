@@ -15,7 +15,8 @@ use dictionary::fields::{MsgSeqNum,OrigSendingTime,SenderCompID,SendingTime,Targ
 use field::Field;
 use field_type::FieldType;
 use fix_version::FIXVersion;
-use message::{BuildMessage,Message};
+use fixt::third_party_routing::ThirdPartyRouting;
+use message::{BuildMessage,Message,Meta};
 use message_version::MessageVersion;
 
 pub trait BuildFIXTMessage: BuildMessage {
@@ -29,6 +30,7 @@ pub trait FIXTMessageBuildable {
 
 pub trait FIXTMessage: Message {
     fn new_into_box(&self) -> Box<FIXTMessage + Send>;
+    fn clone_into_box(&self) -> Box<FIXTMessage + Send>;
     fn msg_type(&self) -> &'static [u8];
     fn msg_seq_num(&self) -> <<MsgSeqNum as Field>::Type as FieldType>::Type;
     fn sender_comp_id(&self) -> &<<SenderCompID as Field>::Type as FieldType>::Type;
@@ -36,6 +38,51 @@ pub trait FIXTMessage: Message {
     fn is_poss_dup(&self) -> bool;
     fn set_is_poss_dup(&mut self,is_poss_dup: bool);
     fn sending_time(&self) -> <<SendingTime as Field>::Type as FieldType>::Type;
+
+    //Commonly needed fields that can be read without downcasting. They return None when the
+    //message type doesn't have the field or it's empty. For example, a routing layer can dispatch
+    //on msg_type() and symbol_bytes() alone.
+    fn symbol_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn cl_ord_id_bytes(&self) -> Option<&[u8]> {
+        None
+    }
+
+    //Replace the Symbol field. Returns false, without changing anything, when the message type
+    //doesn't have the field.
+    fn set_symbol_bytes(&mut self,_symbol: &[u8]) -> bool {
+        false
+    }
+
+    //OnBehalfOf and DeliverTo header fields. Messages without a standard header, such as raw
+    //templates, report no routing and ignore changes.
+    fn third_party_routing(&self) -> ThirdPartyRouting {
+        ThirdPartyRouting::new()
+    }
+
+    fn set_third_party_routing(&mut self,_routing: &ThirdPartyRouting) {
+    }
+
+    //TestMessageIndicator header field. Messages without a standard header are never test messages
+    //and ignore changes.
+    fn is_test_message(&self) -> bool {
+        false
+    }
+
+    fn set_is_test_message(&mut self,_is_test_message: bool) {
+    }
+
+    //Signature trailer field. SignatureLength is filled in automatically when serialized and never
+    //needs to be set. Messages without a standard trailer have no signature and ignore changes.
+    fn signature(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn set_signature(&mut self,_signature: &[u8]) {
+    }
+
     fn orig_sending_time(&self) -> <<OrigSendingTime as Field>::Type as FieldType>::Type;
     fn set_orig_sending_time(&mut self,orig_sending_time: <<OrigSendingTime as Field>::Type as FieldType>::Type);
     fn setup_fixt_session_header(&mut self,
@@ -56,6 +103,61 @@ impl fmt::Debug for FIXTMessage + Send {
     }
 }
 
+//Versions used when displaying a message. Messages that were parsed are displayed using the
+//versions they were received with. Otherwise, the newest versions are used.
+pub fn display_versions(meta: &Option<Meta>) -> (FIXVersion,MessageVersion) {
+    match *meta {
+        Some(ref meta) => (meta.begin_string,meta.message_version),
+        None => (FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2),
+    }
+}
+
+impl fmt::Display for FIXTMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (fix_version,message_version) = display_versions(self.meta());
+        write!(f,"{}",Message::to_fix_string(self,fix_version,message_version))
+    }
+}
+
+impl fmt::Display for FIXTMessage + Send {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (fix_version,message_version) = display_versions(self.meta());
+        write!(f,"{}",Message::to_fix_string(self,fix_version,message_version))
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fixt_message_symbol_bytes {
+    ( symbol, $value:expr ) => {
+        if !$value.is_empty() {
+            return Some(&$value[..]);
+        }
+    };
+    ( $field_name:ident, $value:expr ) => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fixt_message_set_symbol_bytes {
+    ( symbol, $value:expr, $symbol:expr ) => {
+        $value = $symbol.to_vec();
+        return true;
+    };
+    ( $field_name:ident, $value:expr, $symbol:expr ) => {};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! fixt_message_cl_ord_id_bytes {
+    ( cl_ord_id, $value:expr ) => {
+        if !$value.is_empty() {
+            return Some(&$value[..]);
+        }
+    };
+    ( $field_name:ident, $value:expr ) => {};
+}
+
 #[macro_export]
 macro_rules! define_fixt_message {
     ( $message_name:ident $( : ADMIN $message_type:expr => )* { $( $field_required:expr, $field_name:ident : $field_type:ty [$( $version:tt )*] $(=> REQUIRED_WHEN $required_when_expr:expr)* ),* $(),* } ) => {
@@ -102,6 +204,7 @@ macro_rules! define_fixt_message {
             $crate::message::NOT_REQUIRED, last_msg_seq_num_processed: $crate::dictionary::fields::LastMsgSeqNumProcessed [FIX42..],
             $crate::message::NOT_REQUIRED, on_behalf_of_sending_time: $crate::dictionary::fields::OnBehalfOfSendingTime [FIX42..FIX43],
             $crate::message::NOT_REQUIRED, hops: $crate::dictionary::fields::NoHops [FIX43..],
+            $crate::message::NOT_REQUIRED, test_message_indicator: $crate::dictionary::fields::TestMessageIndicator [FIX43..],
 
             //Other
             $( $field_required, $field_name : $field_type [$( $version )*] $(=> REQUIRED_WHEN $required_when_expr)*, )*
@@ -121,11 +224,22 @@ macro_rules! define_fixt_message {
             }
         }
 
+        impl ::std::fmt::Display for $message_name {
+            fn fmt(&self,f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                let (fix_version,message_version) = $crate::fixt::message::display_versions(&self.meta);
+                write!(f,"{}",$crate::message::Message::to_fix_string(self,fix_version,message_version))
+            }
+        }
+
         impl $crate::fixt::message::FIXTMessage for $message_name {
             fn new_into_box(&self) -> Box<$crate::fixt::message::FIXTMessage + Send> {
                 Box::new($message_name::new())
             }
 
+            fn clone_into_box(&self) -> Box<$crate::fixt::message::FIXTMessage + Send> {
+                Box::new(self.clone())
+            }
+
             fn msg_type(&self) -> &'static [u8] {
                 $message_name::msg_type()
             }
@@ -154,6 +268,61 @@ macro_rules! define_fixt_message {
                 self.sending_time
             }
 
+            fn symbol_bytes(&self) -> Option<&[u8]> {
+                $( fixt_message_symbol_bytes!($field_name,self.$field_name); )*
+
+                None
+            }
+
+            #[allow(unreachable_code,unused_variables)]
+            fn set_symbol_bytes(&mut self,symbol: &[u8]) -> bool {
+                $( fixt_message_set_symbol_bytes!($field_name,self.$field_name,symbol); )*
+
+                false
+            }
+
+            fn cl_ord_id_bytes(&self) -> Option<&[u8]> {
+                $( fixt_message_cl_ord_id_bytes!($field_name,self.$field_name); )*
+
+                None
+            }
+
+            fn third_party_routing(&self) -> $crate::fixt::third_party_routing::ThirdPartyRouting {
+                $crate::fixt::third_party_routing::ThirdPartyRouting {
+                    on_behalf_of_comp_id: self.on_behalf_of_comp_id.clone(),
+                    on_behalf_of_sub_id: self.on_behalf_of_sub_id.clone(),
+                    on_behalf_of_location_id: self.on_behalf_of_location_id.clone(),
+                    deliver_to_comp_id: self.deliver_to_comp_id.clone(),
+                    deliver_to_sub_id: self.deliver_to_sub_id.clone(),
+                    deliver_to_location_id: self.deliver_to_location_id.clone(),
+                }
+            }
+
+            fn set_third_party_routing(&mut self,routing: &$crate::fixt::third_party_routing::ThirdPartyRouting) {
+                self.on_behalf_of_comp_id = routing.on_behalf_of_comp_id.clone();
+                self.on_behalf_of_sub_id = routing.on_behalf_of_sub_id.clone();
+                self.on_behalf_of_location_id = routing.on_behalf_of_location_id.clone();
+                self.deliver_to_comp_id = routing.deliver_to_comp_id.clone();
+                self.deliver_to_sub_id = routing.deliver_to_sub_id.clone();
+                self.deliver_to_location_id = routing.deliver_to_location_id.clone();
+            }
+
+            fn is_test_message(&self) -> bool {
+                self.test_message_indicator == b"Y"
+            }
+
+            fn set_is_test_message(&mut self,is_test_message: bool) {
+                self.test_message_indicator = if is_test_message { b"Y".to_vec() } else { Vec::new() };
+            }
+
+            fn signature(&self) -> Option<&[u8]> {
+                if self.signature.is_empty() { None } else { Some(&self.signature[..]) }
+            }
+
+            fn set_signature(&mut self,signature: &[u8]) {
+                self.signature = signature.to_vec();
+            }
+
             fn orig_sending_time(&self) -> <<$crate::dictionary::fields::OrigSendingTime as $crate::field::Field>::Type as $crate::field_type::FieldType>::Type {
                 self.orig_sending_time
             }
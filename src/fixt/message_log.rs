@@ -0,0 +1,326 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::{Timelike,UTC};
+use std::cmp;
+use std::fs::File;
+use std::io::{self,Write};
+use std::usize;
+
+use byte_buffer::ByteBuffer;
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message::MessageVisitor;
+use message_version::MessageVersion;
+
+pub const DEFAULT_MESSAGE_LOG_MAX_LENGTH: usize = 64 * 1024;
+pub const BASE64_VALUE_PREFIX: &'static [u8] = b"base64:";
+const BASE64_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_CHUNK_LEN: usize = 3 * 1024; //Must be a multiple of 3 so only the final chunk is padded.
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum MessageLogDirection {
+    Inbound,
+    Outbound,
+}
+
+//How each entry in a message log is encoded.
+//
+//Text: One line per message containing a UTC timestamp, IN or OUT, and every field written as
+//tag=value followed by MessageLogSettings::soh_replacement. Values that aren't plain printable
+//ASCII, such as RawData or any value containing SOH or a newline, are base64 encoded and prefixed
+//with BASE64_VALUE_PREFIX. The log never contains a raw SOH and each entry is exactly one line.
+//
+//RawFIX: The message serialized exactly as FIX followed by a newline. Nothing is replaced or
+//truncated so the file can be fed straight back into a FIX parser.
+//
+//JsonLines: One JSON object per line in the form
+//{"time":"20170101-01:02:03.456","direction":"IN","fields":[[8,"FIXT.1.1"],[35,"0"],...]}. Values
+//are encoded the same way as Text. When the message is truncated, a "truncated" key holds the
+//number of bytes left out.
+//
+//LengthPrefixed: Binary frames made up of the UTC timestamp as nanoseconds since the Unix epoch
+//(u64), the direction as b'I' or b'O' (u8), the length of the message (u32), and then the message
+//serialized exactly as FIX. All integers are big endian. Nothing is truncated.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum MessageLogFormat {
+    Text,
+    RawFIX,
+    JsonLines,
+    LengthPrefixed,
+}
+
+//Controls how each message is written to a message log.
+#[derive(Clone,Copy,Debug)]
+pub struct MessageLogSettings {
+    pub format: MessageLogFormat,
+    pub max_length: Option<usize>, //Maximum bytes of fields written per Text or JsonLines entry before the rest is replaced with a truncation indicator. None disables truncation.
+    pub soh_replacement: u8, //Written between fields instead of SOH in Text entries. Must be printable ASCII.
+}
+
+impl MessageLogSettings {
+    pub fn new() -> MessageLogSettings {
+        MessageLogSettings {
+            format: MessageLogFormat::Text,
+            max_length: Some(DEFAULT_MESSAGE_LOG_MAX_LENGTH),
+            soh_replacement: b'|',
+        }
+    }
+}
+
+impl Default for MessageLogSettings {
+    fn default() -> Self {
+        MessageLogSettings::new()
+    }
+}
+
+fn base64_encoded_len(len: usize) -> usize {
+    (len + 2) / 3 * 4
+}
+
+fn base64_encode(bytes: &[u8],output: &mut Vec<u8>) {
+    for chunk in bytes.chunks(3) {
+        let b0 = chunk[0] as usize;
+        let b1 = if chunk.len() > 1 { chunk[1] as usize } else { 0 };
+        let b2 = if chunk.len() > 2 { chunk[2] as usize } else { 0 };
+
+        output.push(BASE64_ALPHABET[b0 >> 2]);
+        output.push(BASE64_ALPHABET[((b0 & 0x03) << 4) | (b1 >> 4)]);
+        output.push(if chunk.len() > 1 { BASE64_ALPHABET[((b1 & 0x0F) << 2) | (b2 >> 6)] } else { b'=' });
+        output.push(if chunk.len() > 2 { BASE64_ALPHABET[b2 & 0x3F] } else { b'=' });
+    }
+}
+
+struct EntryWriter<'a> {
+    settings: &'a MessageLogSettings,
+    entry: Vec<u8>,
+    truncated_len: usize,
+    field_count: usize,
+}
+
+impl<'a> EntryWriter<'a> {
+    fn remaining(&self) -> usize {
+        match self.settings.max_length {
+            Some(max_length) => max_length.saturating_sub(self.entry.len()),
+            None => usize::MAX,
+        }
+    }
+
+    fn push(&mut self,bytes: &[u8]) {
+        //Only count what doesn't fit so a huge message never takes up more memory than the limit.
+        let len = cmp::min(bytes.len(),self.remaining());
+        self.entry.extend_from_slice(&bytes[..len]);
+        self.truncated_len += bytes.len() - len;
+    }
+
+    fn push_base64(&mut self,value: &[u8]) {
+        let mut offset = 0;
+        let mut encoded = Vec::new();
+        while offset < value.len() && self.remaining() > 0 {
+            let end = cmp::min(offset + BASE64_CHUNK_LEN,value.len());
+            encoded.clear();
+            base64_encode(&value[offset..end],&mut encoded);
+            self.push(&encoded);
+            offset = end;
+        }
+        self.truncated_len += base64_encoded_len(value.len() - offset);
+    }
+
+    fn push_json_escaped(&mut self,value: &[u8]) {
+        //Never split an escape sequence in half or the JSON would be invalid.
+        for &byte in value {
+            let escaped: &[u8] = match byte {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                _ => b"",
+            };
+            if escaped.is_empty() {
+                self.push(&[byte]);
+            }
+            else if escaped.len() <= self.remaining() {
+                self.entry.extend_from_slice(escaped);
+            }
+            else {
+                self.truncated_len += escaped.len();
+            }
+        }
+    }
+
+    fn push_field(&mut self,tag: &[u8],value: &[u8]) {
+        if self.settings.format == MessageLogFormat::JsonLines {
+            //JSON structure is always written in full, even past max_length, so the entry stays
+            //valid. Fields after the one that was truncated are left out entirely.
+            if self.remaining() == 0 {
+                self.truncated_len += tag.len() + value.len();
+                return;
+            }
+
+            if self.field_count > 0 {
+                self.entry.push(b',');
+            }
+            self.entry.push(b'[');
+            self.entry.extend_from_slice(tag);
+            self.entry.extend_from_slice(b",\"");
+            if self.is_binary(value) {
+                self.push(BASE64_VALUE_PREFIX);
+                self.push_base64(value);
+            }
+            else {
+                self.push_json_escaped(value);
+            }
+            self.entry.extend_from_slice(b"\"]");
+        }
+        else {
+            self.push(tag);
+            self.push(b"=");
+            if self.is_binary(value) {
+                self.push(BASE64_VALUE_PREFIX);
+                self.push_base64(value);
+            }
+            else {
+                self.push(value);
+            }
+            let soh_replacement = [self.settings.soh_replacement];
+            self.push(&soh_replacement);
+        }
+        self.field_count += 1;
+    }
+
+    fn is_binary(&self,value: &[u8]) -> bool {
+        //Text values containing the separator are encoded too so fields can always be split apart
+        //again. Same for values that look like they were already encoded.
+        value.starts_with(BASE64_VALUE_PREFIX) ||
+        value.iter().any(|&byte| byte < 0x20 || byte >= 0x7F || (byte == self.settings.soh_replacement && self.settings.format == MessageLogFormat::Text))
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.settings.format == MessageLogFormat::JsonLines {
+            self.entry.push(b']');
+            if self.truncated_len > 0 {
+                let truncated = format!(",\"truncated\":{}",self.truncated_len);
+                self.entry.extend_from_slice(truncated.as_bytes());
+            }
+            self.entry.push(b'}');
+        }
+        else if self.truncated_len > 0 {
+            let indicator = format!("...[truncated {} bytes]",self.truncated_len);
+            self.entry.extend_from_slice(indicator.as_bytes());
+        }
+
+        self.entry
+    }
+}
+
+impl<'a> MessageVisitor for EntryWriter<'a> {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,value: &[u8]) {
+        self.push_field(&tag.to_bytes(),value);
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,count: usize) {
+        self.push_field(&tag.to_bytes(),count.to_string().as_bytes());
+    }
+}
+
+pub fn format_message(settings: &MessageLogSettings,fix_version: FIXVersion,message_version: MessageVersion,message: &FIXTMessage) -> Vec<u8> {
+    //Formats message according to settings.format without the timestamp and direction. For Text
+    //and JsonLines, BodyLength and CheckSum are left out because they describe the encoding
+    //rather than the message. JsonLines produces an object with just the "fields" key and
+    //"truncated" when needed.
+    assert!(settings.soh_replacement >= 0x20 && settings.soh_replacement < 0x7F);
+
+    match settings.format {
+        MessageLogFormat::RawFIX | MessageLogFormat::LengthPrefixed => {
+            let mut buffer = ByteBuffer::new();
+            message.read(fix_version,message_version,&mut buffer);
+            return buffer.bytes().to_vec();
+        },
+        MessageLogFormat::Text | MessageLogFormat::JsonLines => {},
+    }
+
+    let mut writer = EntryWriter {
+        settings: settings,
+        entry: Vec::new(),
+        truncated_len: 0,
+        field_count: 0,
+    };
+    if settings.format == MessageLogFormat::JsonLines {
+        writer.entry.extend_from_slice(b"{\"fields\":[");
+    }
+    writer.push_field(b"8",fix_version.begin_string());
+    writer.push_field(b"35",message.msg_type());
+    message.visit(fix_version,message_version,&mut writer);
+
+    writer.finish()
+}
+
+//A file that every message sent or received on a connection is appended to using the format
+//selected by MessageLogSettings. A connection can have several of these at once, such as one for
+//each downstream consumer.
+pub struct MessageLog {
+    file: File,
+    settings: MessageLogSettings,
+}
+
+impl MessageLog {
+    pub fn new(file: File,settings: MessageLogSettings) -> MessageLog {
+        MessageLog {
+            file: file,
+            settings: settings,
+        }
+    }
+
+    pub fn write(&mut self,direction: MessageLogDirection,fix_version: FIXVersion,message_version: MessageVersion,message: &FIXTMessage) -> Result<(),io::Error> {
+        let now = UTC::now();
+        let formatted_message = format_message(&self.settings,fix_version,message_version,message);
+        let mut entry = Vec::with_capacity(formatted_message.len() + 64);
+        match self.settings.format {
+            MessageLogFormat::Text => {
+                let direction = match direction { MessageLogDirection::Inbound => "IN", MessageLogDirection::Outbound => "OUT" };
+                entry.extend_from_slice(format!("{} {} ",now.format("%Y%m%d-%H:%M:%S%.3f"),direction).as_bytes());
+                entry.extend_from_slice(&formatted_message);
+                entry.push(b'\n');
+            },
+            MessageLogFormat::RawFIX => {
+                entry.extend_from_slice(&formatted_message);
+                entry.push(b'\n');
+            },
+            MessageLogFormat::JsonLines => {
+                //Splice the time and direction in front of the "fields" key.
+                let direction = match direction { MessageLogDirection::Inbound => "IN", MessageLogDirection::Outbound => "OUT" };
+                entry.extend_from_slice(format!("{{\"time\":\"{}\",\"direction\":\"{}\",",now.format("%Y%m%d-%H:%M:%S%.3f"),direction).as_bytes());
+                entry.extend_from_slice(&formatted_message[1..]);
+                entry.push(b'\n');
+            },
+            MessageLogFormat::LengthPrefixed => {
+                let nanoseconds = (now.timestamp() as u64) * 1_000_000_000 + (now.nanosecond() as u64);
+                push_u64_be(&mut entry,nanoseconds);
+                entry.push(match direction { MessageLogDirection::Inbound => b'I', MessageLogDirection::Outbound => b'O' });
+                push_u32_be(&mut entry,formatted_message.len() as u32);
+                entry.extend_from_slice(&formatted_message);
+            },
+        }
+
+        self.file.write_all(&entry)
+    }
+}
+
+fn push_u64_be(bytes: &mut Vec<u8>,value: u64) {
+    for shift in (0..8).rev() {
+        bytes.push((value >> (shift * 8)) as u8);
+    }
+}
+
+fn push_u32_be(bytes: &mut Vec<u8>,value: u32) {
+    for shift in (0..4).rev() {
+        bytes.push((value >> (shift * 8)) as u8);
+    }
+}
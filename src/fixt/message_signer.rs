@@ -0,0 +1,27 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message_version::MessageVersion;
+
+//Fills in the legacy Signature(89) trailer field of outbound messages for counterparties that
+//still authenticate with it. SignatureLength(93) is filled in automatically. Received signatures
+//are available unchanged from FIXTMessage::signature() so the application can check them.
+//
+//sign() is called for every message right before it's serialized, after the session header,
+//including MsgSeqNum and SendingTime, has been filled in. This includes messages that are resent
+//with PossDupFlag set. Signers are called on the same thread that does all of the networking for
+//every connection so they must be quick and must not block or panic.
+pub trait MessageSigner: Send + Sync {
+    //Signature for message. None leaves whatever signature the message already has, if any.
+    fn sign(&self,message: &FIXTMessage,fix_version: FIXVersion,message_version: MessageVersion) -> Option<Vec<u8>>;
+}
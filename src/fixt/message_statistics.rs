@@ -0,0 +1,211 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message::{MessageVisitor,Meta};
+use message_version::MessageVersion;
+
+const DEFAULT_TOP_TAG_COUNT: usize = 10;
+
+//Distribution of a value kept as a histogram with one bucket per power of two so memory stays
+//fixed no matter how many samples are recorded.
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Distribution {
+    pub count: u64,
+    pub min: u64,
+    pub max: u64,
+    pub total: u64,
+    pub buckets: Vec<(u64,u64)>, //Upper bound and sample count of every bucket with at least one sample, smallest first. A value is counted in the first bucket whose upper bound is at least the value.
+}
+
+impl Distribution {
+    pub fn new() -> Distribution {
+        Distribution {
+            count: 0,
+            min: 0,
+            max: 0,
+            total: 0,
+            buckets: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self,value: u64) {
+        if self.count == 0 || value < self.min {
+            self.min = value;
+        }
+        if value > self.max {
+            self.max = value;
+        }
+        self.count += 1;
+        self.total += value;
+
+        let upper_bound = value.next_power_of_two();
+        match self.buckets.binary_search_by_key(&upper_bound,|&(bucket_upper_bound,_)| bucket_upper_bound) {
+            Ok(index) => self.buckets[index].1 += 1,
+            Err(index) => self.buckets.insert(index,(upper_bound,1)),
+        }
+    }
+
+    pub fn mean(&self) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+
+        self.total / self.count
+    }
+}
+
+//Everything collected for one MsgType.
+#[derive(Clone,Debug,PartialEq)]
+pub struct MsgTypeStatistics {
+    pub msg_type: Vec<u8>,
+    pub message_count: u64, //Every message seen, including the ones that weren't sampled.
+    pub sizes: Distribution, //Bytes from BeginString through CheckSum of each sampled message.
+    pub field_counts: Distribution, //Fields in each sampled message, including the header, trailer, and every repeating group entry, but not BeginString, BodyLength, MsgType, or CheckSum.
+    pub top_tags: Vec<(FieldTag,u64)>, //How often the most frequent tags showed up in sampled messages, most frequent first.
+}
+
+struct MsgTypeState {
+    message_count: u64,
+    sizes: Distribution,
+    field_counts: Distribution,
+    tag_counts: HashMap<FieldTag,u64>,
+}
+
+struct FieldCounter<'a> {
+    field_count: u64,
+    tag_counts: &'a mut HashMap<FieldTag,u64>,
+}
+
+impl<'a> FieldCounter<'a> {
+    fn count(&mut self,tag: FieldTag) {
+        self.field_count += 1;
+        *self.tag_counts.entry(tag).or_insert(0) += 1;
+    }
+}
+
+impl<'a> MessageVisitor for FieldCounter<'a> {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,_value: &[u8]) {
+        self.count(tag);
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,_count: usize) {
+        //The NoXXX count field is serialized like any other.
+        self.count(tag);
+    }
+}
+
+//Samples messages to show what a session's traffic actually looks like: how big each MsgType's
+//messages are, how many fields they carry, and which tags show up the most. Useful for tuning
+//buffer sizes, max message size, and conflation with real data instead of guesses. Attach one to a
+//connection with Engine::set_message_statistics() or use it on its own.
+pub struct MessageStatistics {
+    sample_interval: u64,
+    top_tag_count: usize,
+    msg_types: HashMap<Vec<u8>,MsgTypeState>,
+}
+
+impl MessageStatistics {
+    pub fn new(sample_interval: u64) -> MessageStatistics {
+        //Only every sample_interval'th message of each MsgType, starting with the first, is
+        //measured. Messages are still counted. Use 1 to measure everything.
+        assert!(sample_interval > 0);
+
+        MessageStatistics {
+            sample_interval: sample_interval,
+            top_tag_count: DEFAULT_TOP_TAG_COUNT,
+            msg_types: HashMap::new(),
+        }
+    }
+
+    pub fn top_tag_count(mut self,top_tag_count: usize) -> MessageStatistics {
+        //How many of the most frequent tags are included for each MsgType. Defaults to 10.
+        self.top_tag_count = top_tag_count;
+        self
+    }
+
+    pub fn sample_interval(&self) -> u64 {
+        self.sample_interval
+    }
+
+    pub fn record(&mut self,message: &FIXTMessage,fix_version: FIXVersion,message_version: MessageVersion,size: usize) {
+        //size is the number of bytes the message took up on the wire.
+        let state = self.msg_types.entry(message.msg_type().to_vec()).or_insert_with(|| {
+            MsgTypeState {
+                message_count: 0,
+                sizes: Distribution::new(),
+                field_counts: Distribution::new(),
+                tag_counts: HashMap::new(),
+            }
+        });
+        state.message_count += 1;
+        if (state.message_count - 1) % self.sample_interval != 0 {
+            return;
+        }
+
+        let field_count = {
+            let mut field_counter = FieldCounter {
+                field_count: 0,
+                tag_counts: &mut state.tag_counts,
+            };
+            message.visit(fix_version,message_version,&mut field_counter);
+
+            field_counter.field_count
+        };
+        state.sizes.record(size as u64);
+        state.field_counts.record(field_count);
+    }
+
+    pub fn report(&self) -> Vec<MsgTypeStatistics> {
+        //One entry per MsgType seen, sorted by MsgType.
+        let mut result: Vec<MsgTypeStatistics> = self.msg_types.iter().map(|(msg_type,state)| {
+            let mut top_tags: Vec<(FieldTag,u64)> = state.tag_counts.iter().map(|(tag,count)| (*tag,*count)).collect();
+            top_tags.sort_by(|a,b| b.1.cmp(&a.1).then(a.0 .0.cmp(&b.0 .0))); //Ties are broken by tag.
+            top_tags.truncate(self.top_tag_count);
+
+            MsgTypeStatistics {
+                msg_type: msg_type.clone(),
+                message_count: state.message_count,
+                sizes: state.sizes.clone(),
+                field_counts: state.field_counts.clone(),
+                top_tags: top_tags,
+            }
+        }).collect();
+        result.sort_by(|a,b| a.msg_type.cmp(&b.msg_type));
+
+        result
+    }
+
+    pub fn clear(&mut self) {
+        self.msg_types.clear();
+    }
+}
+
+//Statistics collected by a connection. See Engine::message_statistics().
+#[derive(Clone,Debug,PartialEq)]
+pub struct MessageStatisticsReport {
+    pub inbound: Vec<MsgTypeStatistics>,
+    pub outbound: Vec<MsgTypeStatistics>,
+}
+
+pub fn wire_size(meta: &Meta) -> usize {
+    //Size of a parsed message as it was received: BeginString and BodyLength, the body, and the
+    //CheckSum.
+    let begin_string_len = b"8=".len() + meta.begin_string.begin_string().len() + 1;
+    let body_length_len = b"9=".len() + meta.body_length.to_string().len() + 1;
+    let checksum_len = b"10=000".len() + 1;
+
+    begin_string_len + body_length_len + meta.body_length as usize + checksum_len
+}
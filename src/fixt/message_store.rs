@@ -0,0 +1,711 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap,HashSet};
+use std::fs::{self,File,OpenOptions};
+use std::io::{self,Read,Seek,SeekFrom,Write};
+use std::ops::Range;
+use std::path::{Path,PathBuf};
+#[cfg(feature = "store-compression")]
+use zstd::block::{Compressor,Decompressor};
+#[cfg(feature = "store-compression")]
+use zstd::dict;
+
+use fixt::session_id::SessionID;
+#[cfg(feature = "store-encryption")]
+use fixt::store_encryption::StoreEncryption;
+
+const SEQ_NUMS_FILE_NAME: &'static str = "seqnums";
+const SEQ_NUMS_TEMP_FILE_NAME: &'static str = "seqnums.tmp";
+const MESSAGES_FILE_NAME: &'static str = "messages";
+const ORDER_ACKS_FILE_NAME: &'static str = "orderacks";
+const ORDERS_SENT_FILE_NAME: &'static str = "orderssent";
+#[cfg(feature = "store-compression")]
+const DEFAULT_ZSTD_LEVEL: i32 = 3;
+const ENCRYPTED_FLAG: &'static str = "E";
+const ORDER_ACKS_ADDITIONAL_DATA: &'static [u8] = b"orderacks";
+const ORDERS_SENT_ADDITIONAL_DATA: &'static [u8] = b"orderssent";
+const SEQ_NUMS_ADDITIONAL_DATA: &'static [u8] = b"seqnums";
+
+//Persists a session's state so it can continue where it left off after a restart. Set with
+//ConnectionBuilder::message_store(). The engine's thread then:
+//  1. Starts the connection using the stored inbound and outbound MsgSeqNums instead of 1.
+//  2. Stores every outbound message, exactly as serialized, under its MsgSeqNum.
+//  3. Updates the stored MsgSeqNums whenever they change.
+//  4. Answers ResendRequests from the stored messages without involving the application.
+//     Administrative messages and anything missing from the store are gap filled.
+//  5. Records the ClOrdID of every ExecutionReport and OrderCancelReject received, and of every
+//     NewOrderSingle, OrderCancelRequest and OrderCancelReplaceRequest sent, so
+//     Engine::order_entry_status() can tell what happened to an order before a restart without
+//     going through every stored message.
+//
+//Functions are called on the engine's thread so they should be quick. Errors are reported with
+//EngineEvent::MessageStoreError and do not terminate the connection.
+pub trait MessageStore: Send {
+    fn next_inbound_msg_seq_num(&self) -> u64;
+    fn next_outbound_msg_seq_num(&self) -> u64;
+    fn set_next_inbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error>;
+    fn set_next_outbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error>;
+    fn store_outbound(&mut self,msg_seq_num: u64,bytes: &[u8]) -> Result<(),io::Error>;
+    fn outbound_messages(&mut self,range: Range<u64>) -> Result<Vec<(u64,Vec<u8>)>,io::Error>; //Stored messages with MsgSeqNum in [Range::start,Range::end) in increasing order.
+    fn store_order_ack(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error>; //Called for every response so it's best to ignore ClOrdIDs that are already stored.
+    fn is_order_acknowledged(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error>;
+    fn store_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error>; //Called right after the order's message is passed to store_outbound().
+    fn is_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error>;
+}
+
+//Keeps everything in memory. Useful for tests and simulators where the session only needs to
+//survive reconnecting and not restarting the process.
+pub struct MemoryMessageStore {
+    next_inbound_msg_seq_num: u64,
+    next_outbound_msg_seq_num: u64,
+    messages: BTreeMap<u64,Vec<u8>>,
+    order_acks: HashSet<Vec<u8>>,
+    orders_sent: HashSet<Vec<u8>>,
+}
+
+impl MemoryMessageStore {
+    pub fn new() -> MemoryMessageStore {
+        MemoryMessageStore {
+            next_inbound_msg_seq_num: 1,
+            next_outbound_msg_seq_num: 1,
+            messages: BTreeMap::new(),
+            order_acks: HashSet::new(),
+            orders_sent: HashSet::new(),
+        }
+    }
+}
+
+impl Default for MemoryMessageStore {
+    fn default() -> Self {
+        MemoryMessageStore::new()
+    }
+}
+
+impl MessageStore for MemoryMessageStore {
+    fn next_inbound_msg_seq_num(&self) -> u64 {
+        self.next_inbound_msg_seq_num
+    }
+
+    fn next_outbound_msg_seq_num(&self) -> u64 {
+        self.next_outbound_msg_seq_num
+    }
+
+    fn set_next_inbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error> {
+        self.next_inbound_msg_seq_num = msg_seq_num;
+        Ok(())
+    }
+
+    fn set_next_outbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error> {
+        self.next_outbound_msg_seq_num = msg_seq_num;
+        Ok(())
+    }
+
+    fn store_outbound(&mut self,msg_seq_num: u64,bytes: &[u8]) -> Result<(),io::Error> {
+        self.messages.insert(msg_seq_num,bytes.to_vec());
+        Ok(())
+    }
+
+    fn outbound_messages(&mut self,range: Range<u64>) -> Result<Vec<(u64,Vec<u8>)>,io::Error> {
+        Ok(self.messages.range(range).map(|(msg_seq_num,bytes)| (*msg_seq_num,bytes.clone())).collect())
+    }
+
+    fn store_order_ack(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error> {
+        if !self.order_acks.contains(cl_ord_id) {
+            self.order_acks.insert(cl_ord_id.to_vec());
+        }
+        Ok(())
+    }
+
+    fn is_order_acknowledged(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error> {
+        Ok(self.order_acks.contains(cl_ord_id))
+    }
+
+    fn store_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error> {
+        if !self.orders_sent.contains(cl_ord_id) {
+            self.orders_sent.insert(cl_ord_id.to_vec());
+        }
+        Ok(())
+    }
+
+    fn is_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error> {
+        Ok(self.orders_sent.contains(cl_ord_id))
+    }
+}
+
+//Compresses every message a FileMessageStore writes using zstd. FIX messages are small and very
+//repetitive so a dictionary trained from a sample of a session's own messages, using
+//train_dictionary() or FileMessageStore::train_dictionary(), usually does several times better
+//than compressing each message on its own. The same dictionary must be given every time the store
+//is opened or the messages compressed with it can't be read. Requires the "store-compression"
+//feature.
+#[cfg(feature = "store-compression")]
+#[derive(Clone)]
+pub struct ZstdCompression {
+    level: i32,
+    dictionary: Vec<u8>, //Empty when not using a dictionary.
+}
+
+#[cfg(feature = "store-compression")]
+impl ZstdCompression {
+    pub fn new() -> ZstdCompression {
+        ZstdCompression {
+            level: DEFAULT_ZSTD_LEVEL,
+            dictionary: Vec::new(),
+        }
+    }
+
+    pub fn level(mut self,level: i32) -> ZstdCompression {
+        //zstd compression level. Defaults to 3.
+        self.level = level;
+        self
+    }
+
+    pub fn dictionary(mut self,dictionary: Vec<u8>) -> ZstdCompression {
+        self.dictionary = dictionary;
+        self
+    }
+
+    pub fn train_dictionary<S: AsRef<[u8]>>(samples: &[S],max_size: usize) -> Result<Vec<u8>,io::Error> {
+        //Build a dictionary of at most max_size bytes from sample messages. A few thousand
+        //messages and around 100KB is a good place to start.
+        dict::from_samples(samples,max_size)
+    }
+}
+
+#[cfg(feature = "store-compression")]
+impl Default for ZstdCompression {
+    fn default() -> Self {
+        ZstdCompression::new()
+    }
+}
+
+//Encrypts records when the "store-encryption" feature is enabled and the store was opened with
+//FileMessageStore::open_encrypted(). Otherwise, reading an encrypted record is an error.
+struct RecordCipher {
+    #[cfg(feature = "store-encryption")]
+    encryption: Option<StoreEncryption>,
+}
+
+impl RecordCipher {
+    #[cfg(feature = "store-encryption")]
+    fn none() -> RecordCipher {
+        RecordCipher {
+            encryption: None,
+        }
+    }
+
+    #[cfg(not(feature = "store-encryption"))]
+    fn none() -> RecordCipher {
+        RecordCipher {}
+    }
+
+    #[cfg(feature = "store-encryption")]
+    fn encrypt(&mut self,bytes: &[u8],additional_data: &[u8]) -> Result<Option<Vec<u8>>,io::Error> {
+        //Returns None when records are written unencrypted.
+        match self.encryption {
+            Some(ref mut encryption) => Ok(Some(try!(encryption.encrypt(bytes,additional_data)))),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(not(feature = "store-encryption"))]
+    fn encrypt(&mut self,_bytes: &[u8],_additional_data: &[u8]) -> Result<Option<Vec<u8>>,io::Error> {
+        Ok(None)
+    }
+
+    #[cfg(feature = "store-encryption")]
+    fn decrypt(&mut self,bytes: &[u8],additional_data: &[u8]) -> Result<Vec<u8>,io::Error> {
+        match self.encryption {
+            Some(ref mut encryption) => encryption.decrypt(bytes,additional_data),
+            None => Err(io::Error::new(io::ErrorKind::InvalidData,"Store is encrypted but was not opened with FileMessageStore::open_encrypted()")),
+        }
+    }
+
+    #[cfg(not(feature = "store-encryption"))]
+    fn decrypt(&mut self,_bytes: &[u8],_additional_data: &[u8]) -> Result<Vec<u8>,io::Error> {
+        Err(io::Error::new(io::ErrorKind::InvalidData,"Store is encrypted but the store-encryption feature is not enabled"))
+    }
+
+    #[cfg(feature = "store-encryption")]
+    fn check_unencrypted(&self) -> Result<(),io::Error> {
+        //Called for every unencrypted record read. They can't be authenticated so they're only
+        //accepted from a store that isn't encrypted or while migrating one.
+        match self.encryption {
+            Some(ref encryption) if !encryption.allows_unencrypted_records() => Err(io::Error::new(io::ErrorKind::InvalidData,"Store is encrypted but contains an unencrypted record")),
+            _ => Ok(()),
+        }
+    }
+
+    #[cfg(not(feature = "store-encryption"))]
+    fn check_unencrypted(&self) -> Result<(),io::Error> {
+        Ok(())
+    }
+}
+
+struct IndexEntry {
+    offset: u64, //Where the message starts within messages_file.
+    len: usize, //Length of the message as stored.
+    uncompressed_len: Option<usize>, //Length after decompressing when the message is compressed.
+    encrypted: bool,
+}
+
+//Stores a session in a directory with four files:
+//  seqnums: The next inbound and outbound MsgSeqNums separated by a space. Replaced atomically
+//           using a rename every time either changes.
+//  messages: Every outbound message appended as a "<MsgSeqNum> <length>\n" header line followed by
+//            the message and a newline. When a MsgSeqNum appears more than once, the last one
+//            wins. Compressed messages use a "<MsgSeqNum> <length> <uncompressed length>\n"
+//            header instead. See FileMessageStore::compression().
+//  orderacks: Every acknowledged ClOrdID appended once as a "<length>\n" header line followed by
+//             the ClOrdID and a newline.
+//  orderssent: Every sent ClOrdID in the same format as orderacks.
+//Only an index of where each message is located and the ClOrdIDs are kept in memory. A partially
+//written record at the end of any file except seqnums, such as after a crash, is discarded when
+//opened. Use a different directory for every session.
+//
+//When opened with open_encrypted(), every message and ClOrdID record is encrypted and its header
+//ends with " E". The seqnums file then holds "E\n" followed by the encrypted MsgSeqNums. Opening
+//fails if any unencrypted record is found. See StoreEncryption::allow_unencrypted_records().
+pub struct FileMessageStore {
+    directory: PathBuf,
+    messages_file: File,
+    messages_len: u64,
+    index: BTreeMap<u64,IndexEntry>, //MsgSeqNum to where the message is located within messages_file.
+    order_acks: ClOrdIDFile,
+    orders_sent: ClOrdIDFile,
+    next_inbound_msg_seq_num: u64,
+    next_outbound_msg_seq_num: u64,
+    #[cfg(feature = "store-compression")]
+    compressor: Option<Compressor>, //None when new messages are written uncompressed.
+    #[cfg(feature = "store-compression")]
+    compression_level: i32,
+    #[cfg(feature = "store-compression")]
+    decompressor: Decompressor,
+    cipher: RecordCipher,
+}
+
+impl FileMessageStore {
+    pub fn open<P: AsRef<Path>>(directory: P) -> Result<FileMessageStore,io::Error> {
+        FileMessageStore::open_with_cipher(directory.as_ref(),RecordCipher::none())
+    }
+
+    #[cfg(feature = "store-encryption")]
+    pub fn open_encrypted<P: AsRef<Path>>(directory: P,encryption: StoreEncryption) -> Result<FileMessageStore,io::Error> {
+        //Like open() but everything written is encrypted at rest. Reading an unencrypted record is
+        //an error unless StoreEncryption::allow_unencrypted_records() is used to migrate a store
+        //written before encryption was turned on. Requires the "store-encryption" feature.
+        let cipher = RecordCipher {
+            encryption: Some(encryption),
+        };
+        FileMessageStore::open_with_cipher(directory.as_ref(),cipher)
+    }
+
+    fn open_with_cipher(directory: &Path,mut cipher: RecordCipher) -> Result<FileMessageStore,io::Error> {
+        let directory = directory.to_path_buf();
+        try!(fs::create_dir_all(&directory));
+
+        let (next_inbound_msg_seq_num,next_outbound_msg_seq_num) = try!(read_seq_nums(&directory.join(SEQ_NUMS_FILE_NAME),&mut cipher));
+
+        let mut messages_file = try!(OpenOptions::new().read(true).append(true).create(true).open(directory.join(MESSAGES_FILE_NAME)));
+        let mut contents = Vec::new();
+        try!(messages_file.read_to_end(&mut contents));
+        let (index,messages_len) = index_messages(&contents);
+        if index.values().any(|entry| !entry.encrypted) {
+            try!(cipher.check_unencrypted());
+        }
+        if messages_len < contents.len() as u64 {
+            try!(messages_file.set_len(messages_len));
+        }
+
+        let order_acks = try!(ClOrdIDFile::open(&directory.join(ORDER_ACKS_FILE_NAME),ORDER_ACKS_ADDITIONAL_DATA,&mut cipher));
+        let orders_sent = try!(ClOrdIDFile::open(&directory.join(ORDERS_SENT_FILE_NAME),ORDERS_SENT_ADDITIONAL_DATA,&mut cipher));
+
+        Ok(FileMessageStore {
+            directory: directory,
+            messages_file: messages_file,
+            messages_len: messages_len,
+            index: index,
+            order_acks: order_acks,
+            orders_sent: orders_sent,
+            next_inbound_msg_seq_num: next_inbound_msg_seq_num,
+            next_outbound_msg_seq_num: next_outbound_msg_seq_num,
+            #[cfg(feature = "store-compression")]
+            compressor: None,
+            #[cfg(feature = "store-compression")]
+            compression_level: DEFAULT_ZSTD_LEVEL,
+            #[cfg(feature = "store-compression")]
+            decompressor: Decompressor::new(),
+            cipher: cipher,
+        })
+    }
+
+    #[cfg(feature = "store-compression")]
+    pub fn compression(mut self,compression: ZstdCompression) -> FileMessageStore {
+        //Compress messages written from now on. Messages that are already stored are left alone
+        //and can still be read, so compression can be turned on for an existing store. Requires
+        //the "store-compression" feature.
+        if compression.dictionary.is_empty() {
+            self.compressor = Some(Compressor::new());
+            self.decompressor = Decompressor::new();
+        }
+        else {
+            self.compressor = Some(Compressor::with_dict(compression.dictionary.clone()));
+            self.decompressor = Decompressor::with_dict(compression.dictionary);
+        }
+        self.compression_level = compression.level;
+        self
+    }
+
+    #[cfg(feature = "store-compression")]
+    pub fn train_dictionary(&mut self,max_size: usize) -> Result<Vec<u8>,io::Error> {
+        //Build a dictionary for ZstdCompression from the messages already in the store. Usually
+        //done once using a busy day's store and then reused for every later day.
+        let messages = try!(self.outbound_messages(0..u64::max_value()));
+        let samples: Vec<Vec<u8>> = messages.into_iter().map(|(_,bytes)| bytes).collect();
+        ZstdCompression::train_dictionary(&samples,max_size)
+    }
+
+    pub fn open_session<P: AsRef<Path>>(root_directory: P,session_id: &SessionID) -> Result<FileMessageStore,io::Error> {
+        //Open the store in a directory named after session_id within root_directory. One
+        //root_directory can then be shared by every session without them stepping on each other.
+        FileMessageStore::open(root_directory.as_ref().join(session_id.file_name()))
+    }
+
+    pub fn reset(&mut self) -> Result<(),io::Error> {
+        //Forget everything and start over at MsgSeqNum 1. Usually done at the start of each
+        //trading session or when both sides agree to reset using ResetSeqNumFlag.
+        try!(self.messages_file.set_len(0));
+        self.messages_len = 0;
+        self.index.clear();
+        try!(self.order_acks.clear());
+        try!(self.orders_sent.clear());
+        self.next_inbound_msg_seq_num = 1;
+        self.next_outbound_msg_seq_num = 1;
+
+        self.write_seq_nums()
+    }
+
+    fn write_seq_nums(&mut self) -> Result<(),io::Error> {
+        let seq_nums = format!("{} {}\n",self.next_inbound_msg_seq_num,self.next_outbound_msg_seq_num);
+        let contents = match try!(self.cipher.encrypt(seq_nums.as_bytes(),SEQ_NUMS_ADDITIONAL_DATA)) {
+            Some(encrypted_seq_nums) => {
+                let mut contents = format!("{}\n",ENCRYPTED_FLAG).into_bytes();
+                contents.extend_from_slice(&encrypted_seq_nums);
+                contents
+            },
+            None => seq_nums.into_bytes(),
+        };
+
+        let temp_path = self.directory.join(SEQ_NUMS_TEMP_FILE_NAME);
+        {
+            let mut temp_file = try!(File::create(&temp_path));
+            try!(temp_file.write_all(&contents));
+            try!(temp_file.sync_all());
+        }
+
+        fs::rename(&temp_path,self.directory.join(SEQ_NUMS_FILE_NAME))
+    }
+
+    #[cfg(feature = "store-compression")]
+    fn encode<'a>(&mut self,bytes: &'a [u8]) -> Result<(Cow<'a,[u8]>,Option<usize>),io::Error> {
+        //Returns the bytes to write and, when they're compressed, the original length.
+        match self.compressor {
+            Some(ref mut compressor) => Ok((Cow::Owned(try!(compressor.compress(bytes,self.compression_level))),Some(bytes.len()))),
+            None => Ok((Cow::Borrowed(bytes),None)),
+        }
+    }
+
+    #[cfg(not(feature = "store-compression"))]
+    fn encode<'a>(&mut self,bytes: &'a [u8]) -> Result<(Cow<'a,[u8]>,Option<usize>),io::Error> {
+        Ok((Cow::Borrowed(bytes),None))
+    }
+
+    #[cfg(feature = "store-compression")]
+    fn decode(&mut self,bytes: Vec<u8>,uncompressed_len: Option<usize>) -> Result<Vec<u8>,io::Error> {
+        match uncompressed_len {
+            Some(uncompressed_len) => self.decompressor.decompress(&bytes,uncompressed_len),
+            None => Ok(bytes),
+        }
+    }
+
+    #[cfg(not(feature = "store-compression"))]
+    fn decode(&mut self,bytes: Vec<u8>,uncompressed_len: Option<usize>) -> Result<Vec<u8>,io::Error> {
+        match uncompressed_len {
+            Some(_) => Err(io::Error::new(io::ErrorKind::InvalidData,"Message is compressed but the store-compression feature is not enabled")),
+            None => Ok(bytes),
+        }
+    }
+}
+
+fn read_seq_nums(path: &Path,cipher: &mut RecordCipher) -> Result<(u64,u64),io::Error> {
+    let mut contents = Vec::new();
+    match File::open(path) {
+        Ok(mut file) => { try!(file.read_to_end(&mut contents)); },
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok((1,1)),
+        Err(e) => return Err(e),
+    };
+
+    let encrypted_prefix = format!("{}\n",ENCRYPTED_FLAG);
+    if contents.starts_with(encrypted_prefix.as_bytes()) {
+        let decrypted_contents = try!(cipher.decrypt(&contents[encrypted_prefix.len()..],SEQ_NUMS_ADDITIONAL_DATA));
+        contents = decrypted_contents;
+    }
+    else {
+        try!(cipher.check_unencrypted());
+    }
+    let contents = String::from_utf8_lossy(&contents);
+
+    let seq_nums: Vec<u64> = contents.split_whitespace().filter_map(|seq_num| seq_num.parse().ok()).collect();
+    if seq_nums.len() != 2 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,"seqnums file is corrupt"));
+    }
+
+    Ok((seq_nums[0],seq_nums[1]))
+}
+
+fn split_header(header: &[u8]) -> (Vec<u64>,bool) {
+    //Returns the numbers in a record's header and whether the record is encrypted.
+    let header = String::from_utf8_lossy(header);
+    let mut header_values: Vec<&str> = header.split(' ').collect();
+    let encrypted = header_values.len() > 1 && header_values.last() == Some(&ENCRYPTED_FLAG);
+    if encrypted {
+        header_values.pop();
+    }
+
+    (header_values.iter().filter_map(|value| value.parse::<u64>().ok()).collect(),encrypted)
+}
+
+fn index_messages(contents: &[u8]) -> (BTreeMap<u64,IndexEntry>,u64) {
+    //Returns the index and the length of the file up to the end of the last complete message.
+    let mut index = BTreeMap::new();
+    let mut offset = 0;
+    loop {
+        let remaining = &contents[offset..];
+        let header_len = match remaining.iter().position(|byte| *byte == b'\n') {
+            Some(header_len) => header_len,
+            None => break,
+        };
+        let (header_values,encrypted) = split_header(&remaining[..header_len]);
+        let (msg_seq_num,len) = match (header_values.get(0),header_values.get(1)) {
+            (Some(msg_seq_num),Some(len)) => (*msg_seq_num,*len as usize),
+            _ => break,
+        };
+        let uncompressed_len = header_values.get(2).map(|uncompressed_len| *uncompressed_len as usize);
+
+        let record_len = header_len + 1 + len + 1;
+        if remaining.len() < record_len || remaining[record_len - 1] != b'\n' {
+            break;
+        }
+
+        index.insert(msg_seq_num,IndexEntry {
+            offset: (offset + header_len + 1) as u64,
+            len: len,
+            uncompressed_len: uncompressed_len,
+            encrypted: encrypted,
+        });
+        offset += record_len;
+    }
+
+    (index,offset as u64)
+}
+
+fn read_cl_ord_ids(contents: &[u8]) -> (Vec<(Vec<u8>,bool)>,u64) {
+    //Returns the ClOrdIDs, whether each is encrypted, and the length of the file up to the end of
+    //the last complete record.
+    let mut cl_ord_ids = Vec::new();
+    let mut offset = 0;
+    loop {
+        let remaining = &contents[offset..];
+        let header_len = match remaining.iter().position(|byte| *byte == b'\n') {
+            Some(header_len) => header_len,
+            None => break,
+        };
+        let (header_values,encrypted) = split_header(&remaining[..header_len]);
+        let len = match header_values.first() {
+            Some(len) if header_values.len() == 1 => *len as usize,
+            _ => break,
+        };
+
+        let record_len = header_len + 1 + len + 1;
+        if remaining.len() < record_len || remaining[record_len - 1] != b'\n' {
+            break;
+        }
+
+        cl_ord_ids.push((remaining[header_len + 1..header_len + 1 + len].to_vec(),encrypted));
+        offset += record_len;
+    }
+
+    (cl_ord_ids,offset as u64)
+}
+
+//A set of ClOrdIDs where each one is appended to a file the first time it's inserted.
+struct ClOrdIDFile {
+    file: File,
+    cl_ord_ids: HashSet<Vec<u8>>,
+    additional_data: &'static [u8], //Ties encrypted records to the file they were written to.
+}
+
+impl ClOrdIDFile {
+    fn open(path: &Path,additional_data: &'static [u8],cipher: &mut RecordCipher) -> Result<ClOrdIDFile,io::Error> {
+        let mut file = try!(OpenOptions::new().read(true).append(true).create(true).open(path));
+        let mut contents = Vec::new();
+        try!(file.read_to_end(&mut contents));
+        let (records,len) = read_cl_ord_ids(&contents);
+        let mut cl_ord_ids = HashSet::new();
+        for (cl_ord_id,encrypted) in records {
+            if encrypted {
+                cl_ord_ids.insert(try!(cipher.decrypt(&cl_ord_id,additional_data)));
+            }
+            else {
+                try!(cipher.check_unencrypted());
+                cl_ord_ids.insert(cl_ord_id);
+            }
+        }
+        if len < contents.len() as u64 {
+            try!(file.set_len(len));
+        }
+
+        Ok(ClOrdIDFile {
+            file: file,
+            cl_ord_ids: cl_ord_ids,
+            additional_data: additional_data,
+        })
+    }
+
+    fn insert(&mut self,cl_ord_id: &[u8],cipher: &mut RecordCipher) -> Result<(),io::Error> {
+        if self.cl_ord_ids.contains(cl_ord_id) {
+            return Ok(());
+        }
+
+        let encrypted_cl_ord_id = try!(cipher.encrypt(cl_ord_id,self.additional_data));
+        let (bytes,header) = match encrypted_cl_ord_id {
+            Some(ref encrypted_cl_ord_id) => (&encrypted_cl_ord_id[..],format!("{} {}\n",encrypted_cl_ord_id.len(),ENCRYPTED_FLAG)),
+            None => (cl_ord_id,format!("{}\n",cl_ord_id.len())),
+        };
+        let mut record = Vec::with_capacity(header.len() + bytes.len() + 1);
+        record.extend_from_slice(header.as_bytes());
+        record.extend_from_slice(&bytes);
+        record.push(b'\n');
+        try!(self.file.write_all(&record));
+
+        self.cl_ord_ids.insert(cl_ord_id.to_vec());
+
+        Ok(())
+    }
+
+    fn contains(&self,cl_ord_id: &[u8]) -> bool {
+        self.cl_ord_ids.contains(cl_ord_id)
+    }
+
+    fn clear(&mut self) -> Result<(),io::Error> {
+        try!(self.file.set_len(0));
+        self.cl_ord_ids.clear();
+
+        Ok(())
+    }
+}
+
+impl MessageStore for FileMessageStore {
+    fn next_inbound_msg_seq_num(&self) -> u64 {
+        self.next_inbound_msg_seq_num
+    }
+
+    fn next_outbound_msg_seq_num(&self) -> u64 {
+        self.next_outbound_msg_seq_num
+    }
+
+    fn set_next_inbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error> {
+        self.next_inbound_msg_seq_num = msg_seq_num;
+        self.write_seq_nums()
+    }
+
+    fn set_next_outbound_msg_seq_num(&mut self,msg_seq_num: u64) -> Result<(),io::Error> {
+        self.next_outbound_msg_seq_num = msg_seq_num;
+        self.write_seq_nums()
+    }
+
+    fn store_outbound(&mut self,msg_seq_num: u64,bytes: &[u8]) -> Result<(),io::Error> {
+        //Write the whole record at once so a crash can't leave a valid looking header without its
+        //message.
+        let (bytes,uncompressed_len) = try!(self.encode(bytes));
+        let encrypted_bytes = try!(self.cipher.encrypt(&bytes,msg_seq_num.to_string().as_bytes()));
+        let (bytes,encrypted) = match encrypted_bytes {
+            Some(encrypted_bytes) => (Cow::Owned(encrypted_bytes),true),
+            None => (bytes,false),
+        };
+        let mut header = match uncompressed_len {
+            Some(uncompressed_len) => format!("{} {} {}",msg_seq_num,bytes.len(),uncompressed_len),
+            None => format!("{} {}",msg_seq_num,bytes.len()),
+        };
+        if encrypted {
+            header.push(' ');
+            header.push_str(ENCRYPTED_FLAG);
+        }
+        header.push('\n');
+        let mut record = Vec::with_capacity(header.len() + bytes.len() + 1);
+        record.extend_from_slice(header.as_bytes());
+        record.extend_from_slice(&bytes);
+        record.push(b'\n');
+        try!(self.messages_file.write_all(&record));
+
+        self.index.insert(msg_seq_num,IndexEntry {
+            offset: self.messages_len + header.len() as u64,
+            len: bytes.len(),
+            uncompressed_len: uncompressed_len,
+            encrypted: encrypted,
+        });
+        self.messages_len += record.len() as u64;
+
+        Ok(())
+    }
+
+    fn outbound_messages(&mut self,range: Range<u64>) -> Result<Vec<(u64,Vec<u8>)>,io::Error> {
+        let mut stored_messages = Vec::new();
+        for (msg_seq_num,entry) in self.index.range(range) {
+            let mut bytes = vec![0;entry.len];
+            try!(self.messages_file.seek(SeekFrom::Start(entry.offset)));
+            try!(self.messages_file.read_exact(&mut bytes));
+            stored_messages.push((*msg_seq_num,bytes,entry.uncompressed_len,entry.encrypted));
+        }
+
+        let mut messages = Vec::with_capacity(stored_messages.len());
+        for (msg_seq_num,bytes,uncompressed_len,encrypted) in stored_messages {
+            let bytes = if encrypted {
+                try!(self.cipher.decrypt(&bytes,msg_seq_num.to_string().as_bytes()))
+            }
+            else {
+                bytes
+            };
+            messages.push((msg_seq_num,try!(self.decode(bytes,uncompressed_len))));
+        }
+
+        Ok(messages)
+    }
+
+    fn store_order_ack(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error> {
+        //Every fill produces another ExecutionReport so only the first one is written.
+        self.order_acks.insert(cl_ord_id,&mut self.cipher)
+    }
+
+    fn is_order_acknowledged(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error> {
+        Ok(self.order_acks.contains(cl_ord_id))
+    }
+
+    fn store_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<(),io::Error> {
+        self.orders_sent.insert(cl_ord_id,&mut self.cipher)
+    }
+
+    fn is_order_sent(&mut self,cl_ord_id: &[u8]) -> Result<bool,io::Error> {
+        Ok(self.orders_sent.contains(cl_ord_id))
+    }
+}
@@ -0,0 +1,452 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::any::Any;
+use std::fmt;
+
+use byte_buffer::ByteBuffer;
+use constant::{TAG_END,VALUE_END};
+use dictionary::field_types::generic::UTCTimestampFieldType;
+use dictionary::fields::{MsgSeqNum,OrigSendingTime,PossDupFlag,SenderCompID,SendingTime,TargetCompID};
+use field::Field;
+use field_tag::FieldTag;
+use field_type::FieldType;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message::{Message,MessageVisitor,Meta,SetValueError};
+use message_version::MessageVersion;
+
+const BODY_LENGTH_TAG: FieldTag = FieldTag(9);
+const MSG_TYPE_TAG: FieldTag = FieldTag(35);
+const CHECKSUM_TAG: FieldTag = FieldTag(10);
+const CHECKSUM_TAG_PREFIX_LEN: usize = 3; //10=
+const BODY_LENGTH_FIELD_INDEX: usize = 0; //BodyLength is always the first tracked field.
+
+type MsgSeqNumType = <<MsgSeqNum as Field>::Type as FieldType>::Type;
+type SendingTimeType = <<SendingTime as Field>::Type as FieldType>::Type;
+type OrigSendingTimeType = <<OrigSendingTime as Field>::Type as FieldType>::Type;
+
+pub enum MessageTemplateError {
+    MissingTag(FieldTag), //Tag to be patched was not found in the serialized message.
+    DuplicateTag(FieldTag), //Tag to be patched appears more than once (ie. inside a repeating group).
+    NotVariableTag(FieldTag), //Tag was not marked as variable when the template was created.
+}
+
+impl fmt::Display for MessageTemplateError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MessageTemplateError::MissingTag(ref tag) => write!(f,"MessageTemplateError::MissingTag({})",tag),
+            MessageTemplateError::DuplicateTag(ref tag) => write!(f,"MessageTemplateError::DuplicateTag({})",tag),
+            MessageTemplateError::NotVariableTag(ref tag) => write!(f,"MessageTemplateError::NotVariableTag({})",tag),
+        }
+    }
+}
+
+impl fmt::Debug for MessageTemplateError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        <MessageTemplateError as fmt::Display>::fmt(self,f)
+    }
+}
+
+#[derive(Clone)]
+struct TemplateField {
+    tag: FieldTag,
+    value_begin: usize,
+    value_end: usize,
+}
+
+//A message that is serialized once and then sent many times with only a handful of fields
+//changed in place. The standard header fields managed by the Engine (MsgSeqNum, SenderCompID,
+//TargetCompID, and SendingTime) can always be patched. Any other field must be listed when the
+//template is created. BodyLength and CheckSum are kept up to date after every patch.
+//
+//Note: The template is always sent using the FIX version and message version it was created with.
+//Fields are located by scanning the serialized bytes so raw data fields containing SOH characters
+//followed by something that looks like a tag can confuse the scan.
+#[derive(Clone)]
+pub struct MessageTemplate {
+    msg_type: &'static [u8],
+    msg_type_header: &'static [u8],
+    fix_version: FIXVersion,
+    message_version: MessageVersion,
+    meta: Option<Meta>,
+    bytes: Vec<u8>,
+    fields: Vec<TemplateField>, //Sorted by position in bytes.
+    body_length: usize, //Running BodyLength so it doesn't have to be recounted after each patch.
+    checksum: u8, //Running CheckSum so it doesn't have to be recalculated after each patch.
+    msg_seq_num: MsgSeqNumType,
+    sender_comp_id: <<SenderCompID as Field>::Type as FieldType>::Type,
+    target_comp_id: <<TargetCompID as Field>::Type as FieldType>::Type,
+    poss_dup_flag: bool,
+    sending_time: SendingTimeType,
+    orig_sending_time: OrigSendingTimeType,
+}
+
+impl MessageTemplate {
+    pub fn new<T: FIXTMessage + ?Sized>(message: &T,fix_version: FIXVersion,message_version: MessageVersion,variable_tags: &[FieldTag]) -> Result<MessageTemplate,MessageTemplateError> {
+        let mut buffer = ByteBuffer::new();
+        message.read(fix_version,message_version,&mut buffer);
+        let bytes = buffer.bytes().to_vec();
+
+        //Tags that must be found and tags that are only tracked when they happen to be included.
+        let mut required_tags = vec![BODY_LENGTH_TAG,MSG_TYPE_TAG,MsgSeqNum::tag(),SenderCompID::tag(),TargetCompID::tag(),SendingTime::tag(),CHECKSUM_TAG];
+        for tag in variable_tags {
+            if !required_tags.contains(tag) {
+                required_tags.push(*tag);
+            }
+        }
+        let optional_tags = [PossDupFlag::tag(),OrigSendingTime::tag()];
+
+        //Walk each tag=value pair and remember where the value of every tracked tag is located.
+        let mut fields: Vec<TemplateField> = Vec::new();
+        let mut index = 0;
+        while index < bytes.len() {
+            let tag_end = match bytes[index..].iter().position(|byte| *byte == TAG_END) {
+                Some(offset) => index + offset,
+                None => break,
+            };
+            let value_end = match bytes[tag_end..].iter().position(|byte| *byte == VALUE_END) {
+                Some(offset) => tag_end + offset,
+                None => break,
+            };
+
+            let tag = FieldTag::from(&bytes[index..tag_end]);
+            if required_tags.contains(&tag) || optional_tags.contains(&tag) {
+                if fields.iter().any(|field| field.tag == tag) {
+                    return Err(MessageTemplateError::DuplicateTag(tag));
+                }
+
+                fields.push(TemplateField {
+                    tag: tag,
+                    value_begin: tag_end + 1,
+                    value_end: value_end,
+                });
+            }
+
+            index = value_end + 1;
+        }
+
+        for tag in required_tags {
+            if !fields.iter().any(|field| field.tag == tag) {
+                return Err(MessageTemplateError::MissingTag(tag));
+            }
+        }
+        assert!(fields.first().unwrap().tag == BODY_LENGTH_TAG);
+        assert!(fields.last().unwrap().tag == CHECKSUM_TAG);
+
+        //Count the starting BodyLength and CheckSum once. From here on out, they are only adjusted
+        //by the bytes that change.
+        let body_begin = fields.first().unwrap().value_end + 1;
+        let checksum_end = fields.last().unwrap().value_begin - CHECKSUM_TAG_PREFIX_LEN;
+        let mut checksum: u8 = 0;
+        for byte in &bytes[..checksum_end] {
+            checksum = checksum.wrapping_add(*byte);
+        }
+
+        Ok(MessageTemplate {
+            msg_type: message.msg_type(),
+            msg_type_header: message.msg_type_header(),
+            fix_version: fix_version,
+            message_version: message_version,
+            meta: None,
+            bytes: bytes,
+            fields: fields,
+            body_length: checksum_end - body_begin,
+            checksum: checksum,
+            msg_seq_num: message.msg_seq_num(),
+            sender_comp_id: message.sender_comp_id().clone(),
+            target_comp_id: message.target_comp_id().clone(),
+            poss_dup_flag: message.is_poss_dup(),
+            sending_time: message.sending_time(),
+            orig_sending_time: message.orig_sending_time(),
+        })
+    }
+
+    pub fn fix_version(&self) -> FIXVersion {
+        self.fix_version
+    }
+
+    pub fn message_version(&self) -> MessageVersion {
+        self.message_version
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+
+    pub fn patch(&mut self,tag: FieldTag,value: &[u8]) -> Result<(),MessageTemplateError> {
+        let field_index = match self.field_index(tag) {
+            Some(field_index) => field_index,
+            None => return Err(MessageTemplateError::NotVariableTag(tag)),
+        };
+
+        self.replace_value(field_index,value);
+        self.update_body_length_and_checksum();
+
+        Ok(())
+    }
+
+    pub fn patch_field<F: Field>(&mut self,value: &<<F as Field>::Type as FieldType>::Type) -> Result<(),MessageTemplateError>
+        where <F as Field>::Type: FieldType {
+        let mut value_bytes = Vec::new();
+        <<F as Field>::Type as FieldType>::read(value,self.fix_version,self.message_version,&mut value_bytes);
+        self.patch(F::tag(),&value_bytes[..])
+    }
+
+    fn field_index(&self,tag: FieldTag) -> Option<usize> {
+        self.fields.iter().position(|field| field.tag == tag)
+    }
+
+    fn replace_bytes(&mut self,begin: usize,end: usize,new_bytes: &[u8]) {
+        //Adjust the running CheckSum and BodyLength by only the bytes being changed. The CheckSum
+        //field itself is not included in either.
+        let body_begin = self.fields[BODY_LENGTH_FIELD_INDEX].value_end + 1;
+        let checksum_end = self.fields.last().unwrap().value_begin - CHECKSUM_TAG_PREFIX_LEN;
+        if begin <= checksum_end {
+            for byte in &self.bytes[begin..end] {
+                self.checksum = self.checksum.wrapping_sub(*byte);
+            }
+            for byte in new_bytes {
+                self.checksum = self.checksum.wrapping_add(*byte);
+            }
+
+            if begin >= body_begin {
+                self.body_length = self.body_length + new_bytes.len() - (end - begin);
+            }
+        }
+
+        if new_bytes.len() == end - begin {
+            self.bytes[begin..end].copy_from_slice(new_bytes);
+            return;
+        }
+
+        let tail = self.bytes.split_off(end);
+        self.bytes.truncate(begin);
+        self.bytes.extend_from_slice(new_bytes);
+        self.bytes.extend_from_slice(&tail[..]);
+
+        //Move every field located after the replaced bytes.
+        let delta = new_bytes.len() as isize - (end - begin) as isize;
+        for field in &mut self.fields {
+            if field.value_begin > begin {
+                field.value_begin = (field.value_begin as isize + delta) as usize;
+                field.value_end = (field.value_end as isize + delta) as usize;
+            }
+        }
+    }
+
+    fn replace_value(&mut self,field_index: usize,value: &[u8]) {
+        let value_begin = self.fields[field_index].value_begin;
+        let value_end = self.fields[field_index].value_end;
+        self.replace_bytes(value_begin,value_end,value);
+        self.fields[field_index].value_end = value_begin + value.len();
+    }
+
+    fn insert_field_after(&mut self,after_tag: FieldTag,tag: FieldTag,value: &[u8]) {
+        let after_index = self.field_index(after_tag).expect("Template is missing tag to insert after");
+        let insert_index = self.fields[after_index].value_end + 1;
+
+        let mut field_bytes = tag.to_bytes();
+        field_bytes.push(TAG_END);
+        let value_begin = insert_index + field_bytes.len();
+        field_bytes.extend_from_slice(value);
+        field_bytes.push(VALUE_END);
+
+        self.replace_bytes(insert_index,insert_index,&field_bytes[..]);
+        self.fields.insert(after_index + 1,TemplateField {
+            tag: tag,
+            value_begin: value_begin,
+            value_end: value_begin + value.len(),
+        });
+    }
+
+    fn set_or_insert_value(&mut self,tag: FieldTag,value: &[u8]) {
+        if let Some(field_index) = self.field_index(tag) {
+            self.replace_value(field_index,value);
+        }
+        else {
+            self.insert_field_after(SendingTime::tag(),tag,value);
+        }
+    }
+
+    fn update_body_length_and_checksum(&mut self) {
+        //Both values are kept up to date incrementally by replace_bytes() so only their digits need
+        //to be written. Writing BodyLength can change the number of digits which changes the
+        //CheckSum. So it must be written first.
+        let body_length_str = self.body_length.to_string();
+        if self.bytes[self.fields[BODY_LENGTH_FIELD_INDEX].value_begin..self.fields[BODY_LENGTH_FIELD_INDEX].value_end] != *body_length_str.as_bytes() {
+            self.replace_value(BODY_LENGTH_FIELD_INDEX,body_length_str.as_bytes());
+        }
+
+        //CheckSum must always be three digits. FIXT version 1.1, page 55.
+        let checksum_field_index = self.fields.len() - 1;
+        let checksum_begin = self.fields[checksum_field_index].value_begin;
+        self.bytes[checksum_begin] = b'0' + self.checksum / 100;
+        self.bytes[checksum_begin + 1] = b'0' + (self.checksum / 10) % 10;
+        self.bytes[checksum_begin + 2] = b'0' + self.checksum % 10;
+    }
+}
+
+impl Message for MessageTemplate {
+    fn conditional_required_fields(&self,_version: MessageVersion) -> Vec<FieldTag> {
+        Vec::new()
+    }
+
+    fn meta(&self) -> &Option<Meta> {
+        &self.meta
+    }
+
+    fn set_meta(&mut self,meta: Meta) {
+        self.meta = Some(meta);
+    }
+
+    fn set_value(&mut self,key: FieldTag,value: &[u8]) -> Result<(),SetValueError> {
+        match self.patch(key,value) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(SetValueError::WrongFormat),
+        }
+    }
+
+    fn set_groups(&mut self,_key: FieldTag,_groups: Vec<Box<Message>>) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut Any {
+        self
+    }
+
+    fn new_into_box(&self) -> Box<Message + Send> {
+        Box::new(self.clone())
+    }
+
+    fn msg_type_header(&self) -> &'static [u8] {
+        self.msg_type_header
+    }
+
+    fn read_body(&self,_fix_version: FIXVersion,_message_version: MessageVersion,buf: &mut Vec<u8>) -> usize {
+        let msg_type_field_index = self.field_index(MSG_TYPE_TAG).unwrap();
+        let body_begin = self.fields[msg_type_field_index].value_end + 1;
+        let body_end = self.fields.last().unwrap().value_begin - CHECKSUM_TAG_PREFIX_LEN;
+        buf.extend_from_slice(&self.bytes[body_begin..body_end]);
+
+        body_end - body_begin
+    }
+
+    fn visit(&self,_fix_version: FIXVersion,_message_version: MessageVersion,visitor: &mut MessageVisitor) {
+        //Field names and repeating group boundaries are lost once serialized so every field in the
+        //body is reported in order, without a name and without nesting.
+        let msg_type_field_index = self.field_index(MSG_TYPE_TAG).unwrap();
+        let body_begin = self.fields[msg_type_field_index].value_end + 1;
+        let body_end = self.fields.last().unwrap().value_begin - CHECKSUM_TAG_PREFIX_LEN;
+        for field_bytes in self.bytes[body_begin..body_end].split(|byte| *byte == VALUE_END) {
+            if let Some(tag_end) = field_bytes.iter().position(|byte| *byte == TAG_END) {
+                visitor.visit_field(FieldTag::from(&field_bytes[..tag_end]),"",&field_bytes[tag_end + 1..]);
+            }
+        }
+    }
+
+    fn read(&self,_fix_version: FIXVersion,_message_version: MessageVersion,buf: &mut ByteBuffer) -> usize {
+        //The template was already serialized when it was created so just copy it.
+        buf.clear_and_read_all(|bytes| {
+            bytes.extend_from_slice(&self.bytes[..]);
+        });
+
+        buf.len()
+    }
+}
+
+impl FIXTMessage for MessageTemplate {
+    fn new_into_box(&self) -> Box<FIXTMessage + Send> {
+        Box::new(self.clone())
+    }
+
+    fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+        Box::new(self.clone())
+    }
+
+    fn msg_type(&self) -> &'static [u8] {
+        self.msg_type
+    }
+
+    fn msg_seq_num(&self) -> MsgSeqNumType {
+        self.msg_seq_num
+    }
+
+    fn sender_comp_id(&self) -> &<<SenderCompID as Field>::Type as FieldType>::Type {
+        &self.sender_comp_id
+    }
+
+    fn target_comp_id(&self) -> &<<TargetCompID as Field>::Type as FieldType>::Type {
+        &self.target_comp_id
+    }
+
+    fn is_poss_dup(&self) -> bool {
+        self.poss_dup_flag
+    }
+
+    fn set_is_poss_dup(&mut self,is_poss_dup: bool) {
+        self.poss_dup_flag = is_poss_dup;
+
+        //PossDupFlag is only added when it's needed. Otherwise, it's left as is.
+        if is_poss_dup || self.field_index(PossDupFlag::tag()).is_some() {
+            self.set_or_insert_value(PossDupFlag::tag(),if is_poss_dup { b"Y" } else { b"N" });
+            self.update_body_length_and_checksum();
+        }
+    }
+
+    fn sending_time(&self) -> SendingTimeType {
+        self.sending_time
+    }
+
+    fn orig_sending_time(&self) -> OrigSendingTimeType {
+        self.orig_sending_time
+    }
+
+    fn set_orig_sending_time(&mut self,orig_sending_time: OrigSendingTimeType) {
+        self.orig_sending_time = orig_sending_time;
+
+        let mut value = Vec::new();
+        <<OrigSendingTime as Field>::Type as FieldType>::read(&orig_sending_time,self.fix_version,self.message_version,&mut value);
+        self.set_or_insert_value(OrigSendingTime::tag(),&value[..]);
+        self.update_body_length_and_checksum();
+    }
+
+    fn setup_fixt_session_header(&mut self,
+                                 msg_seq_num: Option<MsgSeqNumType>,
+                                 sender_comp_id: <<SenderCompID as Field>::Type as FieldType>::Type,
+                                 target_comp_id: <<TargetCompID as Field>::Type as FieldType>::Type) {
+        if let Some(msg_seq_num) = msg_seq_num {
+            self.msg_seq_num = msg_seq_num;
+            let field_index = self.field_index(MsgSeqNum::tag()).unwrap();
+            self.replace_value(field_index,msg_seq_num.to_string().as_bytes());
+        }
+
+        let field_index = self.field_index(SenderCompID::tag()).unwrap();
+        self.replace_value(field_index,&sender_comp_id[..]);
+        self.sender_comp_id = sender_comp_id;
+
+        let field_index = self.field_index(TargetCompID::tag()).unwrap();
+        self.replace_value(field_index,&target_comp_id[..]);
+        self.target_comp_id = target_comp_id;
+
+        self.sending_time = UTCTimestampFieldType::new_now();
+        let mut value = Vec::new();
+        UTCTimestampFieldType::read(&self.sending_time,self.fix_version,self.message_version,&mut value);
+        let field_index = self.field_index(SendingTime::tag()).unwrap();
+        self.replace_value(field_index,&value[..]);
+
+        self.update_body_length_and_checksum();
+    }
+}
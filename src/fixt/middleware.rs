@@ -0,0 +1,92 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::any::Any;
+use std::sync::Arc;
+
+use fixt::engine::Connection;
+use fixt::message::FIXTMessage;
+
+pub enum MiddlewareAction {
+    Continue(Box<FIXTMessage + Send>), //Pass the message, possibly modified or replaced, to the next middleware or the application.
+    Drop, //Discard the message. Nothing is delivered to the application.
+    Reject(Box<FIXTMessage + Send>), //Stop the chain and deliver the message with EngineEvent::MessageRejected instead.
+}
+
+//Transforms or filters received messages on the engine's thread before they are delivered with
+//EngineEvent::MessageReceived. Middleware only sees messages after the session layer is done with
+//them so MsgSeqNum tracking, ResendRequests, and automatic responses (ie. Heartbeat to TestRequest)
+//are never affected by what it does. A dropped or rejected message still counts as received.
+//
+//Middleware runs on the same thread that does all of the networking for every connection so it
+//must be quick and must not block or panic.
+pub trait InboundMiddleware: Send {
+    fn on_message(&mut self,connection: Connection,message: Box<FIXTMessage + Send>) -> MiddlewareAction;
+
+    //Same as on_message() but also given any user data attached with Engine::set_user_data().
+    //Override this instead when the middleware needs per-connection application state.
+    fn on_message_with_user_data(&mut self,connection: Connection,_user_data: Option<&Arc<Any + Send + Sync>>,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        self.on_message(connection,message)
+    }
+
+    //Same as on_message_with_user_data() but also given the current time according to the
+    //engine's Clock. Override this instead when the middleware needs to know what time it is so
+    //it follows EngineBuilder::clock() instead of the system clock.
+    fn on_message_at(&mut self,connection: Connection,user_data: Option<&Arc<Any + Send + Sync>>,_now: DateTime<UTC>,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        self.on_message_with_user_data(connection,user_data,message)
+    }
+}
+
+//Ordered list of middleware. Each message is passed through every middleware in the order they were
+//added until one of them returns MiddlewareAction::Drop or MiddlewareAction::Reject.
+pub struct MiddlewareChain {
+    middleware: Vec<Box<InboundMiddleware>>,
+}
+
+impl MiddlewareChain {
+    pub fn new() -> MiddlewareChain {
+        MiddlewareChain {
+            middleware: Vec::new(),
+        }
+    }
+
+    pub fn push<T: InboundMiddleware + 'static>(&mut self,middleware: T) {
+        self.middleware.push(Box::new(middleware));
+    }
+
+    pub fn len(&self) -> usize {
+        self.middleware.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.middleware.is_empty()
+    }
+
+    pub fn process(&mut self,connection: Connection,user_data: Option<&Arc<Any + Send + Sync>>,now: DateTime<UTC>,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        let mut message = message;
+        for middleware in &mut self.middleware {
+            message = match middleware.on_message_at(connection,user_data,now,message) {
+                MiddlewareAction::Continue(message) => message,
+                action => return action,
+            };
+        }
+
+        MiddlewareAction::Continue(message)
+    }
+}
+
+impl Default for MiddlewareChain {
+    fn default() -> Self {
+        MiddlewareChain::new()
+    }
+}
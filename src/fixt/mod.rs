@@ -9,10 +9,46 @@
 // at your option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+pub mod allocations;
+pub mod capabilities;
+pub mod configuration_check;
+pub mod decompression;
+pub mod duplicate_order_guard;
 pub mod engine;
 mod engine_thread;
+pub mod event_router;
+pub mod failover;
+pub mod fanout;
+pub mod health_check;
+pub mod journal_export;
 #[macro_use]
 pub mod message;
+pub mod message_log;
+pub mod message_signer;
+pub mod message_statistics;
+pub mod message_store;
+pub mod market_data;
+pub mod message_template;
+pub mod middleware;
+pub mod multileg;
+pub mod order_list;
+pub mod order_tracker;
+pub mod outbound_validation;
+pub mod positions;
+pub mod rate_anomaly;
+pub mod reconcile;
+pub mod reference_data;
+pub mod reject_reason;
+pub mod replay;
+pub mod schedule;
+pub mod security_status;
+pub mod session_analyzer;
+pub mod session_id;
+pub mod session_provisioner;
+#[cfg(feature = "store-encryption")]
+pub mod store_encryption;
+pub mod symbol_mapper;
+pub mod third_party_routing;
 
 pub mod tests {
     pub use super::engine_thread::{
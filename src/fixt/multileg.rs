@@ -0,0 +1,111 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dictionary::field_types::generic::UTCTimestampFieldType;
+use dictionary::field_types::other::{OrdType,Side};
+use dictionary::fields::LegOrdGrp;
+use dictionary::messages::NewOrderMultileg;
+
+//Side of a leg when the strategy as a whole is traded on strategy_side. Selling a strategy reverses
+//every leg.
+pub fn leg_side_for(strategy_side: Side,leg_side: Side) -> Side {
+    match (strategy_side,leg_side) {
+        (Side::Sell,Side::Buy) => Side::Sell,
+        (Side::Sell,Side::Sell) => Side::Buy,
+        (_,leg_side) => leg_side,
+    }
+}
+
+pub fn new_leg(leg_symbol: &[u8],leg_side: Side,leg_ratio_qty: &[u8]) -> LegOrdGrp {
+    let mut leg = LegOrdGrp::new();
+    leg.leg_symbol = leg_symbol.to_vec();
+    leg.leg_side = Some(leg_side);
+    leg.leg_ratio_qty = leg_ratio_qty.to_vec();
+
+    leg
+}
+
+//Builds NewOrderMultileg messages for spreads and other strategies traded as a single order. Side
+//and OrderQty apply to the strategy as a whole. Legs are described as if the strategy is being
+//bought and are reversed automatically when it's sold. Each leg's LegRatioQty is the quantity of
+//that instrument traded per unit of the strategy.
+//
+//The returned message is not sent automatically. Pass it to Engine::send_message() like any other
+//message. Legs only exist in FIX 4.4 and later.
+pub struct MultilegOrderBuilder {
+    message: NewOrderMultileg,
+}
+
+impl MultilegOrderBuilder {
+    pub fn new(cl_ord_id: &[u8],symbol: &[u8],side: Side,order_qty: &[u8]) -> MultilegOrderBuilder {
+        let mut message = NewOrderMultileg::new();
+        message.cl_ord_id = cl_ord_id.to_vec();
+        message.symbol = symbol.to_vec();
+        message.side = side;
+        message.order_qty = order_qty.to_vec();
+        message.ord_type = OrdType::Market;
+
+        MultilegOrderBuilder {
+            message: message,
+        }
+    }
+
+    pub fn account(mut self,account: &[u8]) -> MultilegOrderBuilder {
+        self.message.account = account.to_vec();
+        self
+    }
+
+    pub fn limit_price(mut self,price: &[u8]) -> MultilegOrderBuilder {
+        //Price of the whole strategy. For spreads this is usually the net price of the legs and
+        //can be negative.
+        self.message.ord_type = OrdType::Limit;
+        self.message.price = price.to_vec();
+        self
+    }
+
+    pub fn leg(self,leg_symbol: &[u8],leg_side: Side,leg_ratio_qty: &[u8]) -> MultilegOrderBuilder {
+        self.add_leg(new_leg(leg_symbol,leg_side,leg_ratio_qty))
+    }
+
+    pub fn add_leg(mut self,mut leg: LegOrdGrp) -> MultilegOrderBuilder {
+        //Use this for legs that need more than a symbol, side, and ratio such as options with a
+        //strike price and maturity.
+        if let Some(leg_side) = leg.leg_side.take() {
+            leg.leg_side = Some(leg_side_for(self.message.side.clone(),leg_side));
+        }
+        self.message.no_legs.push(Box::new(leg));
+        self
+    }
+
+    pub fn build(mut self) -> NewOrderMultileg {
+        self.message.transact_time = UTCTimestampFieldType::new_now();
+
+        self.message
+    }
+}
+
+//Two legged spread that buys one unit of buy_leg_symbol and sells one unit of sell_leg_symbol per
+//unit of the spread. A calendar spread, for example, buys the front month and sells the back
+//month.
+pub fn spread(cl_ord_id: &[u8],symbol: &[u8],side: Side,order_qty: &[u8],buy_leg_symbol: &[u8],sell_leg_symbol: &[u8]) -> MultilegOrderBuilder {
+    MultilegOrderBuilder::new(cl_ord_id,symbol,side,order_qty)
+        .leg(buy_leg_symbol,Side::Buy,b"1")
+        .leg(sell_leg_symbol,Side::Sell,b"1")
+}
+
+//Three legged butterfly that buys one of each wing and sells two of the body per unit of the
+//butterfly.
+pub fn butterfly(cl_ord_id: &[u8],symbol: &[u8],side: Side,order_qty: &[u8],lower_wing_symbol: &[u8],body_symbol: &[u8],upper_wing_symbol: &[u8]) -> MultilegOrderBuilder {
+    MultilegOrderBuilder::new(cl_ord_id,symbol,side,order_qty)
+        .leg(lower_wing_symbol,Side::Buy,b"1")
+        .leg(body_symbol,Side::Sell,b"2")
+        .leg(upper_wing_symbol,Side::Buy,b"1")
+}
@@ -0,0 +1,349 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use dictionary::field_types::generic::UTCTimestampFieldType;
+use dictionary::field_types::other::{BidType,ListExecInstType,ListOrderStatus,OrdStatus,OrdType,Side};
+use dictionary::fields::ListOrdGrp;
+use dictionary::messages::{BusinessMessageReject,ExecutionReport,ListCancelRequest,ListExecute,ListStatus,NewOrderList};
+use fixt::message::FIXTMessage;
+use fixt::reject_reason::Rejection;
+
+pub fn new_list_order(cl_ord_id: &[u8],symbol: &[u8],side: Side,order_qty: &[u8],ord_type: OrdType) -> ListOrdGrp {
+    let mut order = ListOrdGrp::new();
+    order.cl_ord_id = cl_ord_id.to_vec();
+    order.symbol = symbol.to_vec();
+    order.side = side;
+    order.order_qty = order_qty.to_vec();
+    order.ord_type = ord_type;
+
+    order
+}
+
+//Builds a NewOrderList containing every order in the list. ListSeqNo is assigned in the order the
+//orders are given. Lists too large for one message must be split by the caller into several
+//NewOrderList messages sharing the same ListID and TotNoOrders, with LastFragment set on the last
+//one.
+pub fn new_order_list(list_id: &[u8],bid_type: BidType,list_exec_inst_type: Option<ListExecInstType>,orders: Vec<ListOrdGrp>) -> NewOrderList {
+    let mut message = NewOrderList::new();
+    message.list_id = list_id.to_vec();
+    message.bid_type = bid_type;
+    message.list_exec_inst_type = list_exec_inst_type;
+    message.tot_no_orders = orders.len() as i64;
+    for (index,mut order) in orders.into_iter().enumerate() {
+        order.list_seq_no = (index + 1) as i64;
+        message.no_orders.push(Box::new(order));
+    }
+
+    message
+}
+
+pub fn list_execute(list_id: &[u8]) -> ListExecute {
+    let mut message = ListExecute::new();
+    message.list_id = list_id.to_vec();
+    message.transact_time = UTCTimestampFieldType::new_now();
+
+    message
+}
+
+pub fn list_cancel_request(list_id: &[u8]) -> ListCancelRequest {
+    let mut message = ListCancelRequest::new();
+    message.list_id = list_id.to_vec();
+    message.transact_time = UTCTimestampFieldType::new_now();
+
+    message
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum PendingListRequest {
+    Execute, //ListExecute was sent.
+    Cancel, //ListCancelRequest was sent.
+}
+
+//A single order in a list. ord_status, cum_qty, leaves_qty, cxl_qty, and avg_px come from the most
+//recent ListStatus or ExecutionReport for the order.
+#[derive(Clone,Debug)]
+pub struct ListOrder {
+    pub cl_ord_id: Vec<u8>,
+    pub list_seq_no: i64,
+    pub symbol: Vec<u8>,
+    pub side: Side,
+    pub order_qty: Vec<u8>,
+    pub ord_status: Option<OrdStatus>, //None until the order is first reported on.
+    pub cum_qty: Vec<u8>,
+    pub leaves_qty: Vec<u8>,
+    pub cxl_qty: Vec<u8>,
+    pub avg_px: Vec<u8>,
+}
+
+impl ListOrder {
+    pub fn is_done(&self) -> bool {
+        match self.ord_status {
+            Some(OrdStatus::Filled) |
+            Some(OrdStatus::Canceled) |
+            Some(OrdStatus::Rejected) |
+            Some(OrdStatus::Expired) |
+            Some(OrdStatus::DoneForDay) => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct OrderList {
+    pub list_id: Vec<u8>,
+    pub bid_type: BidType,
+    pub list_exec_inst_type: Option<ListExecInstType>,
+    pub tot_no_orders: i64,
+    pub orders: Vec<ListOrder>, //In the order they were submitted across every NewOrderList fragment.
+    pub list_order_status: Option<ListOrderStatus>, //None until the first ListStatus is received.
+    pub list_status_text: Vec<u8>,
+    pub pending_request: Option<PendingListRequest>,
+    pub rejection: Option<Rejection>, //Most recent BusinessMessageReject for this list.
+}
+
+impl OrderList {
+    pub fn get(&self,cl_ord_id: &[u8]) -> Option<&ListOrder> {
+        self.orders.iter().find(|order| order.cl_ord_id == cl_ord_id)
+    }
+
+    pub fn is_fully_submitted(&self) -> bool {
+        //False while more NewOrderList fragments are still expected.
+        self.orders.len() as i64 >= self.tot_no_orders
+    }
+
+    pub fn is_executing(&self) -> bool {
+        self.list_order_status == Some(ListOrderStatus::Executing)
+    }
+
+    pub fn is_done(&self) -> bool {
+        match self.list_order_status {
+            Some(ListOrderStatus::AllDone) |
+            Some(ListOrderStatus::Reject) => true,
+            _ => false,
+        }
+    }
+
+    pub fn all_orders_done(&self) -> bool {
+        self.is_fully_submitted() && self.orders.iter().all(|order| order.is_done())
+    }
+
+    fn get_mut(&mut self,cl_ord_id: &[u8]) -> Option<&mut ListOrder> {
+        self.orders.iter_mut().find(|order| order.cl_ord_id == cl_ord_id)
+    }
+}
+
+//Tracks program trading lists through NewOrderList, ListExecute, and ListCancelRequest flows. The
+//state of the list as a whole comes from ListStatus messages. The state of each order in the list
+//comes from both ListStatus and any ExecutionReport sent for the order's ClOrdID.
+//
+//Like OrderTracker, outbound messages must be passed to on_message_sent() and inbound messages to
+//on_message(). Use one tracker per connection because ListIDs and ClOrdIDs are only unique per
+//counterparty.
+pub struct OrderListTracker {
+    lists: HashMap<Vec<u8>,OrderList>,
+    list_by_cl_ord_id: HashMap<Vec<u8>,Vec<u8>>,
+}
+
+impl OrderListTracker {
+    pub fn new() -> OrderListTracker {
+        OrderListTracker {
+            lists: HashMap::new(),
+            list_by_cl_ord_id: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self,list_id: &[u8]) -> Option<&OrderList> {
+        self.lists.get(list_id)
+    }
+
+    pub fn get_by_cl_ord_id(&self,cl_ord_id: &[u8]) -> Option<&OrderList> {
+        self.list_by_cl_ord_id.get(cl_ord_id).and_then(|list_id| self.lists.get(list_id))
+    }
+
+    pub fn iter(&self) -> ::std::collections::hash_map::Values<Vec<u8>,OrderList> {
+        self.lists.values()
+    }
+
+    pub fn remove(&mut self,list_id: &[u8]) -> Option<OrderList> {
+        let list = match self.lists.remove(list_id) {
+            Some(list) => list,
+            None => return None,
+        };
+
+        for order in &list.orders {
+            self.list_by_cl_ord_id.remove(&order.cl_ord_id);
+        }
+
+        Some(list)
+    }
+
+    pub fn on_message_sent(&mut self,message: &FIXTMessage) {
+        let message = message.as_any();
+        if let Some(message) = message.downcast_ref::<NewOrderList>() {
+            self.on_new_order_list(message);
+        }
+        else if let Some(message) = message.downcast_ref::<ListExecute>() {
+            if let Some(list) = self.lists.get_mut(&message.list_id) {
+                list.pending_request = Some(PendingListRequest::Execute);
+            }
+        }
+        else if let Some(message) = message.downcast_ref::<ListCancelRequest>() {
+            if let Some(list) = self.lists.get_mut(&message.list_id) {
+                list.pending_request = Some(PendingListRequest::Cancel);
+            }
+        }
+    }
+
+    pub fn on_message(&mut self,message: &FIXTMessage) -> Option<&OrderList> {
+        //Returns the updated list or None if the message isn't list related or refers to an
+        //unknown list.
+        let message = message.as_any();
+        let list_id = if let Some(message) = message.downcast_ref::<ListStatus>() {
+            self.on_list_status(message)
+        }
+        else if let Some(message) = message.downcast_ref::<ExecutionReport>() {
+            self.on_execution_report(message)
+        }
+        else if let Some(message) = message.downcast_ref::<BusinessMessageReject>() {
+            self.on_business_message_reject(message)
+        }
+        else {
+            None
+        };
+
+        match list_id {
+            Some(list_id) => self.lists.get(&list_id),
+            None => None,
+        }
+    }
+
+    fn on_new_order_list(&mut self,message: &NewOrderList) {
+        //Later fragments of a list share the ListID of the first fragment.
+        let list = self.lists.entry(message.list_id.clone()).or_insert_with(|| {
+            OrderList {
+                list_id: message.list_id.clone(),
+                bid_type: message.bid_type.clone(),
+                list_exec_inst_type: message.list_exec_inst_type.clone(),
+                tot_no_orders: message.tot_no_orders,
+                orders: Vec::new(),
+                list_order_status: None,
+                list_status_text: Vec::new(),
+                pending_request: None,
+                rejection: None,
+            }
+        });
+
+        for order in &message.no_orders {
+            list.orders.push(ListOrder {
+                cl_ord_id: order.cl_ord_id.clone(),
+                list_seq_no: order.list_seq_no,
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                order_qty: order.order_qty.clone(),
+                ord_status: None,
+                cum_qty: Vec::new(),
+                leaves_qty: Vec::new(),
+                cxl_qty: Vec::new(),
+                avg_px: Vec::new(),
+            });
+            self.list_by_cl_ord_id.insert(order.cl_ord_id.clone(),message.list_id.clone());
+        }
+    }
+
+    fn on_list_status(&mut self,message: &ListStatus) -> Option<Vec<u8>> {
+        let list = match self.lists.get_mut(&message.list_id) {
+            Some(list) => list,
+            None => return None,
+        };
+
+        list.list_order_status = Some(message.list_order_status.clone());
+        if !message.list_status_text.is_empty() {
+            list.list_status_text = message.list_status_text.clone();
+        }
+        for status in &message.no_orders {
+            if let Some(order) = list.get_mut(&status.cl_ord_id) {
+                order.ord_status = Some(status.ord_status.clone());
+                order.cum_qty = status.cum_qty.clone();
+                order.leaves_qty = status.leaves_qty.clone();
+                order.cxl_qty = status.cxl_qty.clone();
+                order.avg_px = status.avg_px.clone();
+            }
+        }
+
+        //Resolve the pending request once the counterparty reports the list moved on. A
+        //ListStatus sent while the request is still being worked on leaves it alone.
+        let resolved = match list.pending_request {
+            Some(PendingListRequest::Execute) => match message.list_order_status {
+                ListOrderStatus::Executing |
+                ListOrderStatus::Cancelling |
+                ListOrderStatus::AllDone |
+                ListOrderStatus::Reject => true,
+                _ => false,
+            },
+            Some(PendingListRequest::Cancel) => match message.list_order_status {
+                ListOrderStatus::Cancelling |
+                ListOrderStatus::AllDone |
+                ListOrderStatus::Reject => true,
+                _ => false,
+            },
+            None => false,
+        };
+        if resolved {
+            list.pending_request = None;
+        }
+
+        Some(message.list_id.clone())
+    }
+
+    fn on_execution_report(&mut self,message: &ExecutionReport) -> Option<Vec<u8>> {
+        let list_id = match self.list_by_cl_ord_id.get(&message.cl_ord_id) {
+            Some(list_id) => list_id.clone(),
+            None => return None,
+        };
+        let list = self.lists.get_mut(&list_id).unwrap();
+        let order = list.get_mut(&message.cl_ord_id).unwrap();
+
+        order.ord_status = Some(message.ord_status.clone());
+        order.cum_qty = message.cum_qty.clone();
+        order.leaves_qty = message.leaves_qty.clone();
+        if !message.avg_px.is_empty() {
+            order.avg_px = message.avg_px.clone();
+        }
+
+        Some(list_id)
+    }
+
+    fn on_business_message_reject(&mut self,message: &BusinessMessageReject) -> Option<Vec<u8>> {
+        //BusinessRejectRefID is the ListID when a list message is rejected at the application
+        //level instead of with a ListStatus.
+        if message.ref_msg_type != b"E" && message.ref_msg_type != b"L" && message.ref_msg_type != b"K" {
+            return None;
+        }
+        let list = match self.lists.get_mut(&message.business_reject_ref_id) {
+            Some(list) => list,
+            None => return None,
+        };
+
+        if message.ref_msg_type == b"E" {
+            if list.list_order_status.is_none() {
+                list.list_order_status = Some(ListOrderStatus::Reject);
+            }
+        }
+        else {
+            list.pending_request = None;
+        }
+        list.rejection = Rejection::from_message(message);
+
+        Some(message.business_reject_ref_id.clone())
+    }
+}
@@ -0,0 +1,376 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap,HashSet,VecDeque};
+
+use dictionary::field_types::other::{CxlRejReason,ExecType,OrdStatus,OrdType,Side};
+use dictionary::messages::{BusinessMessageReject,ExecutionReport,NewOrderSingle,OrderCancelReject,OrderCancelReplaceRequest,OrderCancelRequest};
+use fixt::message::FIXTMessage;
+use fixt::reject_reason::Rejection;
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum PendingRequest {
+    Cancel(Vec<u8>), //ClOrdID of the OrderCancelRequest.
+    Replace(Vec<u8>,Vec<u8>,Vec<u8>), //ClOrdID, OrderQty, and Price of the OrderCancelReplaceRequest.
+}
+
+impl PendingRequest {
+    pub fn cl_ord_id(&self) -> &[u8] {
+        match *self {
+            PendingRequest::Cancel(ref cl_ord_id) |
+            PendingRequest::Replace(ref cl_ord_id,_,_) => cl_ord_id,
+        }
+    }
+}
+
+//Live view of an order across every ClOrdID it has ever used. ord_status, cum_qty, leaves_qty, and
+//avg_px always come from the most recent ExecutionReport or OrderCancelReject regardless of which
+//ClOrdID in the chain the counterparty used.
+#[derive(Clone,Debug)]
+pub struct Order {
+    pub cl_ord_ids: Vec<Vec<u8>>, //Every accepted ClOrdID in order. The last one is the current ClOrdID.
+    pub order_id: Vec<u8>,
+    pub symbol: Vec<u8>,
+    pub side: Side,
+    pub ord_type: OrdType,
+    pub order_qty: Vec<u8>,
+    pub price: Vec<u8>,
+    pub ord_status: Option<OrdStatus>, //None until the first ExecutionReport is received.
+    pub cum_qty: Vec<u8>,
+    pub leaves_qty: Vec<u8>,
+    pub avg_px: Vec<u8>,
+    pub pending_request: Option<PendingRequest>,
+    pub rejection: Option<Rejection>, //Most recent OrderCancelReject or BusinessMessageReject for this order.
+}
+
+impl Order {
+    pub fn orig_cl_ord_id(&self) -> &[u8] {
+        &self.cl_ord_ids[0]
+    }
+
+    pub fn cl_ord_id(&self) -> &[u8] {
+        &self.cl_ord_ids[self.cl_ord_ids.len() - 1]
+    }
+
+    pub fn is_pending_replace(&self) -> bool {
+        if let Some(PendingRequest::Replace(_,_,_)) = self.pending_request { true } else { false }
+    }
+
+    pub fn is_pending_cancel(&self) -> bool {
+        if let Some(PendingRequest::Cancel(_)) = self.pending_request { true } else { false }
+    }
+
+    pub fn is_done(&self) -> bool {
+        match self.ord_status {
+            Some(OrdStatus::Filled) |
+            Some(OrdStatus::Canceled) |
+            Some(OrdStatus::Rejected) |
+            Some(OrdStatus::Expired) |
+            Some(OrdStatus::DoneForDay) => true,
+            _ => false,
+        }
+    }
+}
+
+//Tracks orders through NewOrderSingle, OrderCancelRequest, and OrderCancelReplaceRequest flows.
+//Every ClOrdID used by an order (including pending cancel and replace requests) is mapped back to
+//a single chain so an ExecutionReport that arrives on an older ClOrdID, which some venues do for
+//fills while a replace is still pending, still updates the right order.
+//
+//Outbound messages must be passed to on_message_sent() and inbound messages to on_message(). The
+//tracker does not send anything on its own. Use one tracker per connection because ClOrdIDs and
+//ExecIDs are only unique per counterparty.
+pub struct OrderTracker {
+    orders: HashMap<Vec<u8>,Order>, //Keyed by the first ClOrdID in the chain.
+    chain_by_cl_ord_id: HashMap<Vec<u8>,Vec<u8>>,
+    chain_by_order_id: HashMap<Vec<u8>,Vec<u8>>,
+    exec_id_window: usize, //Zero when ExecutionReport deduplication is disabled.
+    exec_ids: HashSet<Vec<u8>>,
+    exec_ids_in_order: VecDeque<Vec<u8>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> OrderTracker {
+        OrderTracker {
+            orders: HashMap::new(),
+            chain_by_cl_ord_id: HashMap::new(),
+            chain_by_order_id: HashMap::new(),
+            exec_id_window: 0,
+            exec_ids: HashSet::new(),
+            exec_ids_in_order: VecDeque::new(),
+        }
+    }
+
+    pub fn set_exec_id_dedup_window<W: Into<Option<usize>>>(&mut self,window: W) {
+        //Remember the last `window` ExecIDs and ignore any ExecutionReport that reuses one of them.
+        //Duplicates are common after a resend or when a venue reports the same fill twice. Pass
+        //None to disable deduplication.
+        self.exec_id_window = window.into().unwrap_or(0);
+        while self.exec_ids_in_order.len() > self.exec_id_window {
+            let exec_id = self.exec_ids_in_order.pop_front().unwrap();
+            self.exec_ids.remove(&exec_id);
+        }
+    }
+
+    pub fn has_seen_exec_id(&self,exec_id: &[u8]) -> bool {
+        self.exec_ids.contains(exec_id)
+    }
+
+    pub fn get(&self,cl_ord_id: &[u8]) -> Option<&Order> {
+        //Any ClOrdID in the chain, including a pending one, can be used to find the order.
+        self.chain_by_cl_ord_id.get(cl_ord_id).and_then(|chain_id| self.orders.get(chain_id))
+    }
+
+    pub fn get_by_order_id(&self,order_id: &[u8]) -> Option<&Order> {
+        self.chain_by_order_id.get(order_id).and_then(|chain_id| self.orders.get(chain_id))
+    }
+
+    pub fn iter(&self) -> ::std::collections::hash_map::Values<Vec<u8>,Order> {
+        self.orders.values()
+    }
+
+    pub fn remove(&mut self,cl_ord_id: &[u8]) -> Option<Order> {
+        let chain_id = match self.chain_by_cl_ord_id.get(cl_ord_id) {
+            Some(chain_id) => chain_id.clone(),
+            None => return None,
+        };
+        let order = match self.orders.remove(&chain_id) {
+            Some(order) => order,
+            None => return None,
+        };
+
+        //Pending requests that were rejected or replaced by another request have already been
+        //forgotten so this covers every ClOrdID still mapped to the chain.
+        for cl_ord_id in &order.cl_ord_ids {
+            self.chain_by_cl_ord_id.remove(cl_ord_id);
+        }
+        if let Some(ref pending_request) = order.pending_request {
+            self.chain_by_cl_ord_id.remove(pending_request.cl_ord_id());
+        }
+        self.chain_by_order_id.remove(&order.order_id);
+
+        Some(order)
+    }
+
+    pub fn on_message_sent(&mut self,message: &FIXTMessage) {
+        let message = message.as_any();
+        if let Some(message) = message.downcast_ref::<NewOrderSingle>() {
+            self.on_new_order_single(message);
+        }
+        else if let Some(message) = message.downcast_ref::<OrderCancelRequest>() {
+            let pending_request = PendingRequest::Cancel(message.cl_ord_id.clone());
+            self.on_pending_request(&message.orig_cl_ord_id,pending_request);
+        }
+        else if let Some(message) = message.downcast_ref::<OrderCancelReplaceRequest>() {
+            let pending_request = PendingRequest::Replace(message.cl_ord_id.clone(),message.order_qty.clone(),message.price.clone());
+            self.on_pending_request(&message.orig_cl_ord_id,pending_request);
+        }
+    }
+
+    pub fn on_message(&mut self,message: &FIXTMessage) -> Option<&Order> {
+        //Returns the updated order or None if the message isn't order related, refers to an
+        //unknown order, or is a duplicate ExecutionReport.
+        let message = message.as_any();
+        let chain_id = if let Some(message) = message.downcast_ref::<ExecutionReport>() {
+            self.on_execution_report(message)
+        }
+        else if let Some(message) = message.downcast_ref::<OrderCancelReject>() {
+            self.on_order_cancel_reject(message)
+        }
+        else if let Some(message) = message.downcast_ref::<BusinessMessageReject>() {
+            self.on_business_message_reject(message)
+        }
+        else {
+            None
+        };
+
+        match chain_id {
+            Some(chain_id) => self.orders.get(&chain_id),
+            None => None,
+        }
+    }
+
+    fn on_new_order_single(&mut self,message: &NewOrderSingle) {
+        let order = Order {
+            cl_ord_ids: vec![message.cl_ord_id.clone()],
+            order_id: Vec::new(),
+            symbol: message.symbol.clone(),
+            side: message.side.clone(),
+            ord_type: message.ord_type.clone(),
+            order_qty: message.order_qty.clone(),
+            price: message.price.clone(),
+            ord_status: None,
+            cum_qty: Vec::new(),
+            leaves_qty: Vec::new(),
+            avg_px: Vec::new(),
+            pending_request: None,
+            rejection: None,
+        };
+        self.chain_by_cl_ord_id.insert(message.cl_ord_id.clone(),message.cl_ord_id.clone());
+        self.orders.insert(message.cl_ord_id.clone(),order);
+    }
+
+    fn on_pending_request(&mut self,orig_cl_ord_id: &[u8],pending_request: PendingRequest) {
+        let chain_id = match self.chain_by_cl_ord_id.get(orig_cl_ord_id) {
+            Some(chain_id) => chain_id.clone(),
+            None => return,
+        };
+
+        self.chain_by_cl_ord_id.insert(pending_request.cl_ord_id().to_vec(),chain_id.clone());
+        if let Some(order) = self.orders.get_mut(&chain_id) {
+            //Only the most recent request is tracked so forget the ClOrdID of any earlier one.
+            if let Some(old_pending_request) = order.pending_request.take() {
+                if old_pending_request.cl_ord_id() != pending_request.cl_ord_id() {
+                    self.chain_by_cl_ord_id.remove(old_pending_request.cl_ord_id());
+                }
+            }
+            order.pending_request = Some(pending_request);
+        }
+    }
+
+    fn find_chain_id(&self,cl_ord_id: &[u8],orig_cl_ord_id: &[u8],order_id: &[u8]) -> Option<Vec<u8>> {
+        self.chain_by_cl_ord_id.get(cl_ord_id)
+            .or_else(|| self.chain_by_cl_ord_id.get(orig_cl_ord_id))
+            .or_else(|| self.chain_by_order_id.get(order_id))
+            .cloned()
+    }
+
+    fn remember_exec_id(&mut self,exec_id: &[u8]) -> bool {
+        //Returns false if the ExecID was already seen.
+        if self.exec_id_window == 0 {
+            return true;
+        }
+        if self.exec_ids.contains(exec_id) {
+            return false;
+        }
+
+        if self.exec_ids_in_order.len() == self.exec_id_window {
+            let oldest_exec_id = self.exec_ids_in_order.pop_front().unwrap();
+            self.exec_ids.remove(&oldest_exec_id);
+        }
+        self.exec_ids.insert(exec_id.to_vec());
+        self.exec_ids_in_order.push_back(exec_id.to_vec());
+
+        true
+    }
+
+    fn on_execution_report(&mut self,message: &ExecutionReport) -> Option<Vec<u8>> {
+        let chain_id = match self.find_chain_id(&message.cl_ord_id,&message.orig_cl_ord_id,&message.order_id) {
+            Some(chain_id) => chain_id,
+            None => return None,
+        };
+        if !self.remember_exec_id(&message.exec_id) {
+            return None;
+        }
+        let order = self.orders.get_mut(&chain_id).unwrap();
+
+        if order.order_id.is_empty() && !message.order_id.is_empty() {
+            order.order_id = message.order_id.clone();
+            self.chain_by_order_id.insert(message.order_id.clone(),chain_id.clone());
+        }
+        order.ord_status = Some(message.ord_status.clone());
+        order.cum_qty = message.cum_qty.clone();
+        order.leaves_qty = message.leaves_qty.clone();
+        if !message.avg_px.is_empty() {
+            order.avg_px = message.avg_px.clone();
+        }
+
+        //Resolve the pending request once the counterparty confirms it. Fills and other reports
+        //that use an older ClOrdID leave the pending request alone.
+        let resolved = match order.pending_request {
+            Some(PendingRequest::Replace(ref cl_ord_id,ref order_qty,ref price)) if message.exec_type == ExecType::Replaced && message.cl_ord_id == *cl_ord_id => {
+                order.cl_ord_ids.push(cl_ord_id.clone());
+                order.order_qty = if !message.order_qty.is_empty() { message.order_qty.clone() } else { order_qty.clone() };
+                order.price = if !message.price.is_empty() { message.price.clone() } else { price.clone() };
+                true
+            },
+            Some(PendingRequest::Cancel(ref cl_ord_id)) if message.exec_type == ExecType::Canceled && message.cl_ord_id == *cl_ord_id => {
+                order.cl_ord_ids.push(cl_ord_id.clone());
+                true
+            },
+            Some(_) if order.is_done() => {
+                //Order finished (ie. filled or canceled by the venue) before the request was
+                //acknowledged. The counterparty should still reject the request but there's
+                //nothing left to replace or cancel.
+                true
+            },
+            _ => false,
+        };
+        if resolved {
+            //A request that finished without being accepted leaves its ClOrdID behind otherwise.
+            let pending_request = order.pending_request.take().unwrap();
+            if order.cl_ord_id() != pending_request.cl_ord_id() {
+                self.chain_by_cl_ord_id.remove(pending_request.cl_ord_id());
+            }
+        }
+
+        Some(chain_id)
+    }
+
+    fn on_order_cancel_reject(&mut self,message: &OrderCancelReject) -> Option<Vec<u8>> {
+        let chain_id = match self.find_chain_id(&message.cl_ord_id,&message.orig_cl_ord_id,&message.order_id) {
+            Some(chain_id) => chain_id,
+            None => return None,
+        };
+        let order = self.orders.get_mut(&chain_id).unwrap();
+
+        let rejects_pending_request = match order.pending_request {
+            Some(ref pending_request) => pending_request.cl_ord_id() == &message.cl_ord_id[..],
+            None => false,
+        };
+        if rejects_pending_request {
+            //The rejected ClOrdID never becomes part of the chain.
+            order.pending_request = None;
+            self.chain_by_cl_ord_id.remove(&message.cl_ord_id);
+        }
+
+        //OrdStatus is set to Rejected when the counterparty doesn't know about the order at all so
+        //it says nothing about the order itself.
+        if message.cxl_rej_reason != Some(CxlRejReason::UnknownOrder) {
+            order.ord_status = Some(message.ord_status.clone());
+        }
+        order.rejection = Rejection::from_message(message);
+
+        Some(chain_id)
+    }
+
+    fn on_business_message_reject(&mut self,message: &BusinessMessageReject) -> Option<Vec<u8>> {
+        //BusinessRejectRefID is the ClOrdID when an order message is rejected at the application
+        //level instead of with an ExecutionReport or OrderCancelReject.
+        if message.business_reject_ref_id.is_empty() {
+            return None;
+        }
+        let chain_id = match self.chain_by_cl_ord_id.get(&message.business_reject_ref_id) {
+            Some(chain_id) => chain_id.clone(),
+            None => return None,
+        };
+        let order = self.orders.get_mut(&chain_id).unwrap();
+
+        if message.ref_msg_type == b"D" {
+            if order.ord_status.is_none() {
+                order.ord_status = Some(OrdStatus::Rejected);
+            }
+        }
+        else if message.ref_msg_type == b"F" || message.ref_msg_type == b"G" {
+            let rejects_pending_request = match order.pending_request {
+                Some(ref pending_request) => pending_request.cl_ord_id() == &message.business_reject_ref_id[..],
+                None => false,
+            };
+            if rejects_pending_request {
+                order.pending_request = None;
+                self.chain_by_cl_ord_id.remove(&message.business_reject_ref_id);
+            }
+        }
+        order.rejection = Rejection::from_message(message);
+
+        Some(chain_id)
+    }
+}
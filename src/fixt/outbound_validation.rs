@@ -0,0 +1,250 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap,HashSet};
+use std::fmt;
+
+use dictionary::fields::{MsgSeqNum,SenderCompID,SendingTime,TargetCompID};
+use field::Field;
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use fixt::message::{BuildFIXTMessage,FIXTMessage};
+use hash::BuildFieldHasher;
+use message::{FieldHashMap,FieldHashSet,MessageVisitor};
+use message_diff::GroupEntry;
+use message_version::MessageVersion;
+use rule::Rule;
+
+//Which messages passed to Engine::send_message() are checked against the dictionary before they
+//are queued. Messages that fail are returned with EngineEvent::OutboundValidationFailed instead of
+//being sent. Messages the engine creates itself, such as Heartbeats, are never checked.
+#[derive(Clone,Debug,PartialEq)]
+pub enum OutboundValidation {
+    Disabled, //This is the default.
+    ApplicationMessages, //Every message that isn't an administrative message.
+    AllMessages,
+    MsgTypes(Vec<Vec<u8>>), //Only messages with one of these MsgTypes.
+}
+
+impl OutboundValidation {
+    pub fn applies_to(&self,msg_type: &[u8],is_administrative: bool) -> bool {
+        match *self {
+            OutboundValidation::Disabled => false,
+            OutboundValidation::ApplicationMessages => !is_administrative,
+            OutboundValidation::AllMessages => true,
+            OutboundValidation::MsgTypes(ref msg_types) => msg_types.iter().any(|other| &other[..] == msg_type),
+        }
+    }
+}
+
+impl Default for OutboundValidation {
+    fn default() -> Self {
+        OutboundValidation::Disabled
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum OutboundFieldErrorReason {
+    MissingRequiredField, //Field is required but empty. Required repeating groups must have at least one entry.
+    MissingConditionallyRequiredField, //Field is required because of another field's value but empty.
+    FieldNotInMessageVersion, //Field is set but doesn't exist in the message version being sent so it would be silently left out.
+}
+
+//A single problem found with an outbound message.
+#[derive(Clone,Debug,PartialEq)]
+pub struct OutboundFieldError {
+    pub groups: Vec<GroupEntry>, //Outermost group first. Empty for fields that aren't in a group.
+    pub tag: FieldTag,
+    pub reason: OutboundFieldErrorReason,
+}
+
+impl fmt::Display for OutboundFieldError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        for &(ref tag,ref index) in &self.groups {
+            try!(write!(f,"{}[{}].",tag,index));
+        }
+        write!(f,"{}: {:?}",self.tag,self.reason)
+    }
+}
+
+struct Level {
+    fields: FieldHashMap,
+    required_fields: FieldHashSet,
+    present_fields: Vec<FieldTag>,
+    groups: Vec<GroupEntry>,
+}
+
+impl Level {
+    fn missing_required_fields(&self,errors: &mut Vec<OutboundFieldError>) {
+        //Header fields filled in by the engine right before the message is sent don't count.
+        let engine_managed_tags = [SenderCompID::tag(),TargetCompID::tag(),MsgSeqNum::tag(),SendingTime::tag()];
+
+        let mut missing_tags: Vec<FieldTag> = self.required_fields.iter()
+            .filter(|tag| !self.present_fields.contains(tag) && (!self.groups.is_empty() || !engine_managed_tags.contains(tag)))
+            .cloned()
+            .collect();
+        missing_tags.sort_by_key(|tag| tag.0);
+        for tag in missing_tags {
+            errors.push(OutboundFieldError {
+                groups: self.groups.clone(),
+                tag: tag,
+                reason: OutboundFieldErrorReason::MissingRequiredField,
+            });
+        }
+    }
+}
+
+//Walks the message keeping track of which fields are actually set in each repeating group entry.
+struct RequiredFieldsVisitor {
+    message_version: MessageVersion,
+    levels: Vec<Level>,
+    group_tags: Vec<FieldTag>,
+    errors: Vec<OutboundFieldError>,
+}
+
+impl MessageVisitor for RequiredFieldsVisitor {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,value: &[u8]) {
+        if !value.is_empty() {
+            self.levels.last_mut().unwrap().present_fields.push(tag);
+        }
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,count: usize) {
+        if count > 0 {
+            self.levels.last_mut().unwrap().present_fields.push(tag);
+        }
+        self.group_tags.push(tag);
+    }
+
+    fn begin_group_entry(&mut self,index: usize) {
+        let group_tag = *self.group_tags.last().unwrap();
+        let (fields,required_fields) = match self.levels.last().unwrap().fields.get(&group_tag) {
+            Some(&Rule::BeginGroup{ builder_func }) => {
+                let builder = builder_func();
+                (builder.fields(self.message_version),builder.required_fields(self.message_version))
+            },
+            _ => (HashMap::with_hasher(BuildFieldHasher),HashSet::with_hasher(BuildFieldHasher)),
+        };
+        let mut groups = self.levels.last().unwrap().groups.clone();
+        groups.push((group_tag,index));
+
+        self.levels.push(Level {
+            fields: fields,
+            required_fields: required_fields,
+            present_fields: Vec::new(),
+            groups: groups,
+        });
+    }
+
+    fn end_group_entry(&mut self,_index: usize) {
+        let level = self.levels.pop().unwrap();
+        level.missing_required_fields(&mut self.errors);
+    }
+
+    fn end_group(&mut self,_tag: FieldTag,_name: &'static str) {
+        self.group_tags.pop();
+    }
+}
+
+//Collects the tags of every top-level field that would be serialized.
+struct TopLevelTagsVisitor {
+    depth: usize,
+    tags: Vec<FieldTag>,
+}
+
+impl MessageVisitor for TopLevelTagsVisitor {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,_value: &[u8]) {
+        if self.depth == 0 {
+            self.tags.push(tag);
+        }
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,_count: usize) {
+        if self.depth == 0 {
+            self.tags.push(tag);
+        }
+    }
+
+    fn begin_group_entry(&mut self,_index: usize) {
+        self.depth += 1;
+    }
+
+    fn end_group_entry(&mut self,_index: usize) {
+        self.depth -= 1;
+    }
+}
+
+fn top_level_tags(message: &FIXTMessage,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<FieldTag> {
+    let mut visitor = TopLevelTagsVisitor {
+        depth: 0,
+        tags: Vec::new(),
+    };
+    message.visit(fix_version,message_version,&mut visitor);
+
+    visitor.tags
+}
+
+//Checks a message against its definition in the dictionary as if it was about to be serialized
+//using fix_version and message_version. Conditionally required fields are only checked outside of
+//repeating groups. Returns every problem found or an empty list when the message is valid.
+pub fn validate_outbound_message(message: &FIXTMessage,builder: &BuildFIXTMessage,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<OutboundFieldError> {
+    let mut visitor = RequiredFieldsVisitor {
+        message_version: message_version,
+        levels: vec![Level {
+            fields: builder.fields(message_version),
+            required_fields: builder.required_fields(message_version),
+            present_fields: Vec::new(),
+            groups: Vec::new(),
+        }],
+        group_tags: Vec::new(),
+        errors: Vec::new(),
+    };
+    message.visit(fix_version,message_version,&mut visitor);
+
+    let mut errors = Vec::new();
+    let level = visitor.levels.pop().unwrap();
+    level.missing_required_fields(&mut errors);
+    for tag in message.conditional_required_fields(message_version) {
+        if !level.present_fields.contains(&tag) {
+            errors.push(OutboundFieldError {
+                groups: Vec::new(),
+                tag: tag,
+                reason: OutboundFieldErrorReason::MissingConditionallyRequiredField,
+            });
+        }
+    }
+    errors.append(&mut visitor.errors);
+
+    //Anything set that only exists in other message versions would be dropped without warning.
+    //Required fields of other versions always have a value so there's no telling if they were set
+    //on purpose.
+    let sent_tags = top_level_tags(message,fix_version,message_version);
+    let mut set_tags = Vec::new();
+    for other_message_version in MessageVersion::all() {
+        let other_required_fields = builder.required_fields(other_message_version);
+        for tag in top_level_tags(message,fix_version,other_message_version) {
+            if !other_required_fields.contains(&tag) && !set_tags.contains(&tag) {
+                set_tags.push(tag);
+            }
+        }
+    }
+    for tag in set_tags {
+        if !sent_tags.contains(&tag) && !errors.iter().any(|error| error.groups.is_empty() && error.tag == tag) {
+            errors.push(OutboundFieldError {
+                groups: Vec::new(),
+                tag: tag,
+                reason: OutboundFieldErrorReason::FieldNotInMessageVersion,
+            });
+        }
+    }
+
+    errors
+}
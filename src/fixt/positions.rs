@@ -0,0 +1,106 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::str::{self,FromStr};
+
+use dictionary::field_types::other::{ExecType,Side};
+use dictionary::messages::ExecutionReport;
+use fixt::message::FIXTMessage;
+
+fn parse_f64(bytes: &[u8]) -> Option<f64> {
+    str::from_utf8(bytes).ok().and_then(|string| f64::from_str(string).ok())
+}
+
+#[derive(Clone,Debug,Default,PartialEq)]
+pub struct Position {
+    pub net_qty: f64, //Positive when long and negative when short.
+    pub bought_qty: f64,
+    pub sold_qty: f64,
+    pub realized_qty: f64, //Quantity that closed out part of an existing position.
+    pub fill_count: u64,
+}
+
+//Aggregates fills into a net position per symbol. Only pass messages that the OrderTracker has
+//already accepted (ie. OrderTracker::on_message() returned Some) so duplicate ExecutionReports are
+//not counted twice.
+pub struct Positions {
+    positions: HashMap<Vec<u8>,Position>,
+}
+
+impl Positions {
+    pub fn new() -> Positions {
+        Positions {
+            positions: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self,symbol: &[u8]) -> Option<&Position> {
+        self.positions.get(symbol)
+    }
+
+    pub fn iter(&self) -> ::std::collections::hash_map::Iter<Vec<u8>,Position> {
+        self.positions.iter()
+    }
+
+    pub fn snapshot(&self) -> HashMap<Vec<u8>,Position> {
+        self.positions.clone()
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+    }
+
+    pub fn on_message(&mut self,message: &FIXTMessage) -> Option<&Position> {
+        //Returns the updated position if the message was an ExecutionReport for a fill.
+        let message = match message.as_any().downcast_ref::<ExecutionReport>() {
+            Some(message) => message,
+            None => return None,
+        };
+        match message.exec_type {
+            ExecType::Trade |
+            ExecType::PartialFill |
+            ExecType::Fill => {},
+            _ => return None,
+        }
+        let last_qty = match parse_f64(&message.last_qty) {
+            Some(last_qty) if last_qty > 0.0 => last_qty,
+            _ => return None,
+        };
+
+        self.on_fill(&message.symbol,&message.side,last_qty)
+    }
+
+    pub fn on_fill(&mut self,symbol: &[u8],side: &Side,qty: f64) -> Option<&Position> {
+        let signed_qty = match *side {
+            Side::Buy | Side::BuyMinus => qty,
+            Side::Sell | Side::SellPlus | Side::SellShort | Side::SellShortExempt => -qty,
+            _ => return None,
+        };
+
+        let position = self.positions.entry(symbol.to_vec()).or_insert_with(Position::default);
+        if signed_qty > 0.0 {
+            position.bought_qty += qty;
+        }
+        else {
+            position.sold_qty += qty;
+        }
+
+        //Any part of the fill that moves the position towards flat is realized.
+        if position.net_qty != 0.0 && position.net_qty.signum() != signed_qty.signum() {
+            position.realized_qty += qty.min(position.net_qty.abs());
+        }
+        position.net_qty += signed_qty;
+        position.fill_count += 1;
+
+        Some(position)
+    }
+}
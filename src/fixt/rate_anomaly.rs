@@ -0,0 +1,151 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+//Settings for noticing when the number of messages received of a MsgType suddenly departs from
+//normal, like a storm of ExecutionReports or market data that dries up. Messages are counted over
+//back to back windows. Each MsgType's baseline is the average count per window, starting with a
+//plain average over the first baseline_windows windows and then a moving average that weighs
+//recent windows more. Nothing is reported until baseline_windows windows have passed.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct RateAnomalySettings {
+    pub window: Duration,
+    pub baseline_windows: u32,
+    pub storm_factor: f64, //A window with more than this many times the baseline is a storm.
+    pub drought_factor: f64, //A window with less than this fraction of the baseline is a drought.
+    pub min_count: u64, //Storms need at least this many messages in a window and droughts need a baseline of at least this many so quiet MsgTypes don't cause noise.
+}
+
+impl RateAnomalySettings {
+    pub fn new() -> RateAnomalySettings {
+        RateAnomalySettings {
+            window: Duration::from_secs(10),
+            baseline_windows: 30,
+            storm_factor: 10.0,
+            drought_factor: 0.1,
+            min_count: 10,
+        }
+    }
+}
+
+impl Default for RateAnomalySettings {
+    fn default() -> Self {
+        RateAnomalySettings::new()
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum RateAnomalyKind {
+    Storm, //Far more messages than the baseline.
+    Drought, //Far fewer messages than the baseline.
+    Normal, //Back within range after a Storm or Drought.
+}
+
+//A change in whether a MsgType's rate is anomalous. Only changes are reported so a long storm
+//produces one Storm and, eventually, one Normal.
+#[derive(Clone,Debug,PartialEq)]
+pub struct RateAnomaly {
+    pub msg_type: Vec<u8>,
+    pub kind: RateAnomalyKind,
+    pub count: u64, //Messages in the window that caused the change.
+    pub baseline: f64, //Average messages per window before that window.
+}
+
+struct MsgTypeRate {
+    count: u64,
+    baseline: f64,
+    window_count: u32,
+    kind: RateAnomalyKind, //Last kind reported.
+}
+
+//Tracks message rates per MsgType using RateAnomalySettings. Used by the engine for
+//Engine::set_inbound_rate_anomaly() but works on its own too: call record() for every message and
+//end_window() every RateAnomalySettings::window.
+pub struct RateAnomalyDetector {
+    settings: RateAnomalySettings,
+    msg_types: HashMap<Vec<u8>,MsgTypeRate>,
+}
+
+impl RateAnomalyDetector {
+    pub fn new(settings: RateAnomalySettings) -> RateAnomalyDetector {
+        RateAnomalyDetector {
+            settings: settings,
+            msg_types: HashMap::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &RateAnomalySettings {
+        &self.settings
+    }
+
+    pub fn record(&mut self,msg_type: &[u8]) {
+        if let Some(rate) = self.msg_types.get_mut(msg_type) {
+            rate.count += 1;
+            return;
+        }
+
+        self.msg_types.insert(msg_type.to_vec(),MsgTypeRate {
+            count: 1,
+            baseline: 0.0,
+            window_count: 0,
+            kind: RateAnomalyKind::Normal,
+        });
+    }
+
+    pub fn end_window(&mut self) -> Vec<RateAnomaly> {
+        //Returns every MsgType whose rate became or stopped being anomalous, sorted by MsgType.
+        let settings = self.settings;
+        let mut result = Vec::new();
+        for (msg_type,rate) in &mut self.msg_types {
+            let count = rate.count as f64;
+            if rate.window_count >= settings.baseline_windows {
+                let kind = if rate.count >= settings.min_count && count > rate.baseline * settings.storm_factor {
+                    RateAnomalyKind::Storm
+                }
+                else if rate.baseline >= settings.min_count as f64 && count < rate.baseline * settings.drought_factor {
+                    RateAnomalyKind::Drought
+                }
+                else {
+                    RateAnomalyKind::Normal
+                };
+
+                if kind != rate.kind {
+                    result.push(RateAnomaly {
+                        msg_type: msg_type.clone(),
+                        kind: kind,
+                        count: rate.count,
+                        baseline: rate.baseline,
+                    });
+                    rate.kind = kind;
+                }
+
+                let smoothing = 2.0 / (settings.baseline_windows as f64 + 1.0);
+                rate.baseline += (count - rate.baseline) * smoothing;
+            }
+            else {
+                rate.window_count += 1;
+                rate.baseline += (count - rate.baseline) / rate.window_count as f64;
+            }
+
+            rate.count = 0;
+        }
+        result.sort_by(|a,b| a.msg_type.cmp(&b.msg_type));
+
+        result
+    }
+
+    pub fn clear(&mut self) {
+        //Forget every baseline and start over.
+        self.msg_types.clear();
+    }
+}
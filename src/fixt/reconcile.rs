@@ -0,0 +1,185 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::{HashMap,HashSet};
+use std::time::{Duration,Instant};
+
+use dictionary::fields::{SenderCompID,TargetCompID};
+use dictionary::messages::ExecutionReport;
+use field::Field;
+use fixt::message::FIXTMessage;
+use fixt::order_tracker::OrderTracker;
+use message_diff::{FieldDiff,diff};
+use message_hash::MessageHasher;
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum ReconcileBreak {
+    MissingFromDropCopy(Vec<u8>,Vec<u8>), //ExecID and ClOrdID of an execution only seen on the trading session.
+    MissingFromTrading(Vec<u8>,Vec<u8>), //ExecID and ClOrdID of an execution only seen on the drop-copy session.
+    Mismatch(Vec<u8>,Vec<FieldDiff>), //ExecID and every field that differs. Left is the trading session, right is the drop-copy.
+}
+
+struct PendingExecution {
+    message: ExecutionReport,
+    hash: u64,
+    received: Instant,
+}
+
+//Matches ExecutionReports seen on a trading session against the same executions reported on a
+//drop-copy session using ExecID. Matched executions are compared by content hash so a break is
+//reported as soon as the second copy arrives if anything other than the excluded fields differs.
+//Executions that never show up on the other session are reported by expire().
+//
+//Duplicates on either session (ie. from a resend) are ignored by ExecID. The trading session's
+//orders are also tracked so a break can be looked up with orders().get(). ExecIDs are remembered
+//for the life of the reconciler so use one per trading day.
+pub struct Reconciler {
+    hasher: MessageHasher,
+    orders: OrderTracker,
+    trading_executions: HashMap<Vec<u8>,PendingExecution>,
+    drop_copy_executions: HashMap<Vec<u8>,PendingExecution>,
+    matched_exec_ids: HashSet<Vec<u8>>,
+    matched_count: usize,
+}
+
+impl Reconciler {
+    pub fn new() -> Reconciler {
+        Reconciler {
+            //Drop-copy sessions always use their own CompIDs.
+            hasher: MessageHasher::new().exclude_tag(SenderCompID::tag()).exclude_tag(TargetCompID::tag()),
+            orders: OrderTracker::new(),
+            trading_executions: HashMap::new(),
+            drop_copy_executions: HashMap::new(),
+            matched_exec_ids: HashSet::new(),
+            matched_count: 0,
+        }
+    }
+
+    pub fn set_hasher(&mut self,hasher: MessageHasher) {
+        //Replace the hasher to ignore more fields, such as ones only the drop-copy session fills in.
+        //Fields excluded by the hasher are also left out of ReconcileBreak::Mismatch.
+        self.hasher = hasher;
+    }
+
+    pub fn orders(&self) -> &OrderTracker {
+        &self.orders
+    }
+
+    pub fn matched_count(&self) -> usize {
+        self.matched_count
+    }
+
+    pub fn unmatched_count(&self) -> usize {
+        self.trading_executions.len() + self.drop_copy_executions.len()
+    }
+
+    pub fn on_trading_message_sent(&mut self,message: &FIXTMessage) {
+        self.orders.on_message_sent(message);
+    }
+
+    pub fn on_trading_message(&mut self,message: &FIXTMessage) -> Option<ReconcileBreak> {
+        self.orders.on_message(message);
+        match message.as_any().downcast_ref::<ExecutionReport>() {
+            Some(message) => self.on_execution_report(message,false),
+            None => None,
+        }
+    }
+
+    pub fn on_drop_copy_message(&mut self,message: &FIXTMessage) -> Option<ReconcileBreak> {
+        match message.as_any().downcast_ref::<ExecutionReport>() {
+            Some(message) => self.on_execution_report(message,true),
+            None => None,
+        }
+    }
+
+    pub fn expire(&mut self,max_age: Duration) -> Vec<ReconcileBreak> {
+        //Report and forget every execution that has waited longer than max_age for its copy from
+        //the other session. Breaks are in the order the executions were received.
+        let mut expired = Vec::new();
+        Reconciler::expire_executions(&mut self.trading_executions,max_age,ReconcileBreak::MissingFromDropCopy,&mut expired);
+        Reconciler::expire_executions(&mut self.drop_copy_executions,max_age,ReconcileBreak::MissingFromTrading,&mut expired);
+        expired.sort_by_key(|&(received,_)| received);
+
+        expired.into_iter().map(|(_,reconcile_break)| reconcile_break).collect()
+    }
+
+    pub fn finish(&mut self) -> Vec<ReconcileBreak> {
+        //Report every execution that is still unmatched, ie. at the end of the trading day.
+        self.expire(Duration::from_secs(0))
+    }
+
+    fn expire_executions(executions: &mut HashMap<Vec<u8>,PendingExecution>,max_age: Duration,new_break: fn(Vec<u8>,Vec<u8>) -> ReconcileBreak,expired: &mut Vec<(Instant,ReconcileBreak)>) {
+        let expired_exec_ids: Vec<Vec<u8>> = executions.iter()
+            .filter(|&(_,execution)| execution.received.elapsed() >= max_age)
+            .map(|(exec_id,_)| exec_id.clone())
+            .collect();
+        for exec_id in expired_exec_ids {
+            let execution = executions.remove(&exec_id).unwrap();
+            expired.push((execution.received,new_break(exec_id,execution.message.cl_ord_id)));
+        }
+    }
+
+    fn on_execution_report(&mut self,message: &ExecutionReport,from_drop_copy: bool) -> Option<ReconcileBreak> {
+        if message.exec_id.is_empty() || self.matched_exec_ids.contains(&message.exec_id) {
+            return None;
+        }
+
+        let hash = self.hasher.hash(message);
+        let (executions,other_executions) = if from_drop_copy {
+            (&mut self.drop_copy_executions,&mut self.trading_executions)
+        }
+        else {
+            (&mut self.trading_executions,&mut self.drop_copy_executions)
+        };
+        if executions.contains_key(&message.exec_id) {
+            return None;
+        }
+
+        let other_execution = match other_executions.remove(&message.exec_id) {
+            Some(other_execution) => other_execution,
+            None => {
+                executions.insert(message.exec_id.clone(),PendingExecution {
+                    message: message.clone(),
+                    hash: hash,
+                    received: Instant::now(),
+                });
+                return None;
+            },
+        };
+
+        self.matched_exec_ids.insert(message.exec_id.clone());
+        if other_execution.hash == hash {
+            self.matched_count += 1;
+            return None;
+        }
+
+        let differences = {
+            let (trading_message,drop_copy_message) = if from_drop_copy {
+                (&other_execution.message,message)
+            }
+            else {
+                (message,&other_execution.message)
+            };
+            let hasher = &self.hasher;
+            diff(trading_message,drop_copy_message).into_iter()
+                .filter(|difference| !hasher.is_excluded(difference.tag) && !difference.groups.iter().any(|&(tag,_)| hasher.is_excluded(tag)))
+                .collect()
+        };
+
+        Some(ReconcileBreak::Mismatch(message.exec_id.clone(),differences))
+    }
+}
+
+impl Default for Reconciler {
+    fn default() -> Self {
+        Reconciler::new()
+    }
+}
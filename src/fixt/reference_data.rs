@@ -0,0 +1,277 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::sync::{Arc,Mutex};
+use std::time::{Duration,Instant};
+
+use dictionary::field_types::other::{SecurityIDSource,SecurityRequestResult,SecurityType};
+use dictionary::fields::Instrument;
+use dictionary::messages::{SecurityDefinition,SecurityList};
+use fixt::message::FIXTMessage;
+
+//Identifies an instrument in the cache. SecurityID is preferred when it's available because
+//Symbol is not always unique. An empty SecurityID means the instrument is looked up by Symbol.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+pub struct InstrumentKey {
+    pub symbol: Vec<u8>,
+    pub security_id: Vec<u8>,
+}
+
+impl InstrumentKey {
+    pub fn new(symbol: &[u8]) -> InstrumentKey {
+        InstrumentKey {
+            symbol: symbol.to_vec(),
+            security_id: Vec::new(),
+        }
+    }
+
+    pub fn with_security_id(symbol: &[u8],security_id: &[u8]) -> InstrumentKey {
+        InstrumentKey {
+            symbol: symbol.to_vec(),
+            security_id: security_id.to_vec(),
+        }
+    }
+}
+
+//Reference data for a single instrument as reported by the remote. Numeric fields are left as the
+//raw bytes received and are empty when the remote didn't send them.
+#[derive(Clone,Debug,PartialEq)]
+pub struct InstrumentReference {
+    pub symbol: Vec<u8>,
+    pub security_id: Vec<u8>,
+    pub security_id_source: Option<SecurityIDSource>,
+    pub security_type: Option<SecurityType>,
+    pub security_exchange: Vec<u8>,
+    pub security_desc: Vec<u8>,
+    pub currency: Vec<u8>,
+    pub contract_multiplier: Vec<u8>,
+    pub min_price_increment: Vec<u8>, //Tick size.
+    pub round_lot: Vec<u8>,
+}
+
+impl InstrumentReference {
+    pub fn key(&self) -> InstrumentKey {
+        InstrumentKey::with_security_id(&self.symbol,&self.security_id)
+    }
+
+    fn from_security_definition(message: &SecurityDefinition) -> InstrumentReference {
+        InstrumentReference {
+            symbol: message.symbol.clone(),
+            security_id: message.security_id.clone(),
+            security_id_source: message.security_id_source.clone(),
+            security_type: message.security_type.clone(),
+            security_exchange: message.security_exchange.clone(),
+            security_desc: message.security_desc.clone(),
+            currency: message.currency.clone(),
+            contract_multiplier: message.contract_multiplier.clone(),
+            min_price_increment: message.min_price_increment.clone(),
+            round_lot: message.round_lot.clone(),
+        }
+    }
+
+    fn from_instrument(instrument: &Instrument) -> InstrumentReference {
+        //RelatedSym was replaced by Symbol in FIX 4.3.
+        let symbol = if !instrument.symbol.is_empty() { &instrument.symbol } else { &instrument.related_sym };
+
+        InstrumentReference {
+            symbol: symbol.clone(),
+            security_id: instrument.security_id.clone(),
+            security_id_source: instrument.security_id_source.clone(),
+            security_type: instrument.security_type.clone(),
+            security_exchange: instrument.security_exchange.clone(),
+            security_desc: instrument.security_desc.clone(),
+            currency: Vec::new(),
+            contract_multiplier: instrument.contract_multiplier.clone(),
+            min_price_increment: instrument.min_price_increment.clone(),
+            round_lot: Vec::new(),
+        }
+    }
+}
+
+struct CachedInstrument {
+    reference: Arc<InstrumentReference>,
+    expires_at: Instant,
+}
+
+struct ReferenceDataCacheState {
+    instruments: HashMap<Vec<u8>,CachedInstrument>, //Keyed by Symbol.
+    symbols_by_security_id: HashMap<Vec<u8>,Vec<u8>>,
+}
+
+impl ReferenceDataCacheState {
+    fn remove(&mut self,symbol: &[u8]) {
+        if let Some(cached_instrument) = self.instruments.remove(symbol) {
+            self.symbols_by_security_id.remove(&cached_instrument.reference.security_id);
+        }
+    }
+}
+
+//Shares instrument reference data received in SecurityDefinition and SecurityList messages with
+//every component in the process so each of them doesn't have to request it again. The thread that
+//calls Engine::poll() passes each received message to on_message() and any component can then look
+//up instruments with get(). ReferenceDataCache is a cheap handle that can be cloned and handed to
+//other threads.
+//
+//Entries expire after the time given to new() so stale reference data isn't used forever. Expired
+//entries are never returned and are removed the next time they're looked up or when
+//remove_expired() is called. Use is_cached() to decide whether a new SecurityDefinitionRequest or
+//SecurityListRequest needs to be sent.
+#[derive(Clone)]
+pub struct ReferenceDataCache {
+    expiry: Duration,
+    state: Arc<Mutex<ReferenceDataCacheState>>,
+}
+
+impl ReferenceDataCache {
+    pub fn new(expiry: Duration) -> ReferenceDataCache {
+        ReferenceDataCache {
+            expiry: expiry,
+            state: Arc::new(Mutex::new(ReferenceDataCacheState {
+                instruments: HashMap::new(),
+                symbols_by_security_id: HashMap::new(),
+            })),
+        }
+    }
+
+    pub fn expiry(&self) -> Duration {
+        self.expiry
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self,key: &InstrumentKey) -> Option<Arc<InstrumentReference>> {
+        let mut state = self.state.lock().unwrap();
+        let symbol = if !key.security_id.is_empty() {
+            match state.symbols_by_security_id.get(&key.security_id) {
+                Some(symbol) => symbol.clone(),
+                None => return None,
+            }
+        }
+        else {
+            key.symbol.clone()
+        };
+
+        let is_expired = match state.instruments.get(&symbol) {
+            Some(cached_instrument) if cached_instrument.expires_at > Instant::now() => return Some(cached_instrument.reference.clone()),
+            Some(_) => true,
+            None => false,
+        };
+        if is_expired {
+            state.remove(&symbol);
+        }
+
+        None
+    }
+
+    pub fn get_by_symbol(&self,symbol: &[u8]) -> Option<Arc<InstrumentReference>> {
+        self.get(&InstrumentKey::new(symbol))
+    }
+
+    pub fn is_cached(&self,key: &InstrumentKey) -> bool {
+        self.get(key).is_some()
+    }
+
+    pub fn insert(&self,reference: InstrumentReference) -> Arc<InstrumentReference> {
+        //Replaces any existing entry with the same Symbol and restarts its expiry.
+        let reference = Arc::new(reference);
+        let mut state = self.state.lock().unwrap();
+        state.remove(&reference.symbol);
+        if !reference.security_id.is_empty() {
+            state.symbols_by_security_id.insert(reference.security_id.clone(),reference.symbol.clone());
+        }
+        state.instruments.insert(reference.symbol.clone(),CachedInstrument {
+            reference: reference.clone(),
+            expires_at: Instant::now() + self.expiry,
+        });
+
+        reference
+    }
+
+    pub fn remove(&self,key: &InstrumentKey) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let symbol = if !key.security_id.is_empty() {
+            match state.symbols_by_security_id.get(&key.security_id) {
+                Some(symbol) => symbol.clone(),
+                None => return false,
+            }
+        }
+        else {
+            key.symbol.clone()
+        };
+
+        let was_cached = state.instruments.contains_key(&symbol);
+        state.remove(&symbol);
+
+        was_cached
+    }
+
+    pub fn remove_expired(&self) -> usize {
+        //Returns the number of entries removed.
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let expired_symbols: Vec<Vec<u8>> = state.instruments.iter()
+            .filter(|&(_,cached_instrument)| cached_instrument.expires_at <= now)
+            .map(|(symbol,_)| symbol.clone())
+            .collect();
+        for symbol in &expired_symbols {
+            state.remove(symbol);
+        }
+
+        expired_symbols.len()
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.instruments.clear();
+        state.symbols_by_security_id.clear();
+    }
+
+    pub fn on_message(&self,message: &FIXTMessage) -> usize {
+        //Returns the number of instruments added or refreshed. Messages that don't carry reference
+        //data are ignored.
+        let message = message.as_any();
+        if let Some(message) = message.downcast_ref::<SecurityDefinition>() {
+            if message.symbol.is_empty() {
+                return 0;
+            }
+
+            self.insert(InstrumentReference::from_security_definition(message));
+            return 1;
+        }
+        else if let Some(message) = message.downcast_ref::<SecurityList>() {
+            match message.security_request_result {
+                None | Some(SecurityRequestResult::ValidRequest) => {},
+                _ => return 0,
+            }
+
+            let mut count = 0;
+            for instrument in &message.no_related_sym {
+                let reference = InstrumentReference::from_instrument(instrument);
+                if reference.symbol.is_empty() {
+                    continue;
+                }
+
+                self.insert(reference);
+                count += 1;
+            }
+            return count;
+        }
+
+        0
+    }
+}
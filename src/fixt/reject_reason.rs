@@ -0,0 +1,92 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dictionary::field_types::other::{BusinessRejectReason,CxlRejReason,MDReqRejReason,SessionRejectReason};
+use dictionary::fields;
+use dictionary::messages::{BusinessMessageReject,MarketDataRequestReject,OrderCancelReject,Reject};
+use field::Field;
+use field_type::FieldType;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message_version::MessageVersion;
+
+fn raw_code<F: Field>(value: &<<F as Field>::Type as FieldType>::Type) -> Vec<u8>
+    where <F as Field>::Type: FieldType {
+    //Reason codes are serialized the same way in every version so any version works here.
+    let mut buf = Vec::new();
+    <<F as Field>::Type as FieldType>::read(value,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut buf);
+    buf
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum RejectReason {
+    Session(Option<SessionRejectReason>), //Reject(3)
+    Business(BusinessRejectReason), //BusinessMessageReject(j)
+    OrderCancel(Option<CxlRejReason>), //OrderCancelReject(9)
+    MarketData(Option<MDReqRejReason>), //MarketDataRequestReject(Y)
+}
+
+//Typed reason for any of the reject messages along with the reason code exactly as it was sent and
+//the ID the reject refers to. raw_code is empty when the optional reason field was left out.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Rejection {
+    pub reason: RejectReason,
+    pub raw_code: Vec<u8>,
+    pub ref_seq_num: Option<u64>, //MsgSeqNum of the rejected message, if known.
+    pub ref_id: Vec<u8>, //ClOrdID, MDReqID, or BusinessRejectRefID of the rejected message, if known.
+    pub text: Vec<u8>,
+}
+
+impl Rejection {
+    pub fn from_message(message: &FIXTMessage) -> Option<Rejection> {
+        //Returns None when the message isn't a reject.
+        let message = message.as_any();
+        if let Some(message) = message.downcast_ref::<Reject>() {
+            Some(Rejection {
+                reason: RejectReason::Session(message.session_reject_reason.clone()),
+                raw_code: raw_code::<fields::SessionRejectReason>(&message.session_reject_reason),
+                ref_seq_num: Some(message.ref_seq_num),
+                ref_id: Vec::new(),
+                text: message.text.clone(),
+            })
+        }
+        else if let Some(message) = message.downcast_ref::<BusinessMessageReject>() {
+            Some(Rejection {
+                reason: RejectReason::Business(message.business_reject_reason.clone()),
+                raw_code: raw_code::<fields::BusinessRejectReason>(&message.business_reject_reason),
+                ref_seq_num: if message.ref_seq_num != 0 { Some(message.ref_seq_num) } else { None },
+                ref_id: message.business_reject_ref_id.clone(),
+                text: message.text.clone(),
+            })
+        }
+        else if let Some(message) = message.downcast_ref::<OrderCancelReject>() {
+            Some(Rejection {
+                reason: RejectReason::OrderCancel(message.cxl_rej_reason.clone()),
+                raw_code: raw_code::<fields::CxlRejReason>(&message.cxl_rej_reason),
+                ref_seq_num: None,
+                ref_id: message.cl_ord_id.clone(),
+                text: message.text.clone(),
+            })
+        }
+        else if let Some(message) = message.downcast_ref::<MarketDataRequestReject>() {
+            Some(Rejection {
+                reason: RejectReason::MarketData(message.md_req_rej_reason.clone()),
+                raw_code: raw_code::<fields::MDReqRejReason>(&message.md_req_rej_reason),
+                ref_seq_num: None,
+                ref_id: message.md_req_id.clone(),
+                text: message.text.clone(),
+            })
+        }
+        else {
+            None
+        }
+    }
+}
@@ -0,0 +1,241 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::cmp;
+use std::io::{self,Read,Write};
+use std::net::{Shutdown,SocketAddr,TcpListener,TcpStream};
+use std::sync::mpsc::channel;
+use std::thread::{self,JoinHandle};
+use std::time::{Duration,Instant};
+
+use byte_buffer::ByteBuffer;
+use clock::SimulatedClock;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message::Message;
+use message_version::MessageVersion;
+
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+
+//How long to wait between time slices compared to when they were recorded.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ReplaySpeed {
+    Original, //Same as recorded.
+    Accelerated(u32), //Recorded time divided by this. Eg. Accelerated(10) replays an hour in six minutes.
+    Unpaced, //Never wait. Usually combined with SessionReplay::clock().
+}
+
+fn nanoseconds(duration: Duration) -> u64 {
+    duration.as_secs() * NANOSECONDS_PER_SECOND + duration.subsec_nanos() as u64
+}
+
+fn from_nanoseconds(nanoseconds: u64) -> Duration {
+    Duration::new(nanoseconds / NANOSECONDS_PER_SECOND,(nanoseconds % NANOSECONDS_PER_SECOND) as u32)
+}
+
+//Replays a recorded inbound message stream, such as one decoded by PcapDecoder, to an Engine as if
+//it came from the original counterparty. The replay listens on a socket that the Engine connects
+//to like any other connection so every message goes through the same session layer and produces
+//the same events as in production. Use the same CompIDs, FIX version, and default message version
+//for the connection as in the recording.
+//
+//Messages are grouped into time slices by their recorded timestamp relative to the first message.
+//Each slice is written all at once after waiting according to ReplaySpeed. Replaying starts as
+//soon as the Engine sends its first bytes, usually its Logon, so the recording should start with
+//the counterparty's Logon response. Everything the Engine sends is read and thrown away.
+pub struct SessionReplay {
+    messages: Vec<(DateTime<UTC>,Vec<u8>)>,
+    speed: ReplaySpeed,
+    time_slice: Duration,
+    clock: Option<SimulatedClock>,
+}
+
+impl SessionReplay {
+    pub fn new() -> SessionReplay {
+        SessionReplay {
+            messages: Vec::new(),
+            speed: ReplaySpeed::Original,
+            time_slice: Duration::from_millis(1),
+            clock: None,
+        }
+    }
+
+    pub fn speed(mut self,speed: ReplaySpeed) -> SessionReplay {
+        //Defaults to ReplaySpeed::Original.
+        if let ReplaySpeed::Accelerated(factor) = speed {
+            assert!(factor > 0);
+        }
+
+        self.speed = speed;
+        self
+    }
+
+    pub fn time_slice(mut self,time_slice: Duration) -> SessionReplay {
+        //Messages recorded within the same time slice are written together. Larger slices reduce
+        //how often the replay wakes up at the cost of timing precision. Defaults to 1ms.
+        assert!(nanoseconds(time_slice) > 0);
+
+        self.time_slice = time_slice;
+        self
+    }
+
+    pub fn clock(mut self,clock: SimulatedClock) -> SessionReplay {
+        //Advance clock by the recorded time before writing each time slice. When it's the clock
+        //the Engine was built with, timeouts like Heartbeats fire exactly as they did during the
+        //recording no matter how fast it's replayed.
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn add_bytes(&mut self,timestamp: DateTime<UTC>,bytes: &[u8]) {
+        //Add raw bytes exactly as they were received. Must be added in the order received.
+        self.messages.push((timestamp,bytes.to_vec()));
+    }
+
+    pub fn add_message(&mut self,timestamp: DateTime<UTC>,fix_version: FIXVersion,message_version: MessageVersion,message: &FIXTMessage) {
+        //Same as add_bytes() but the message is serialized first.
+        let mut bytes = ByteBuffer::new();
+        message.read(fix_version,message_version,&mut bytes);
+        self.messages.push((timestamp,bytes.bytes().to_vec()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    pub fn start(self,address: SocketAddr) -> Result<ReplayHandle,io::Error> {
+        //Listen on address and replay to the first connection accepted on a separate thread. Use
+        //port 0 to pick any available port and then connect to ReplayHandle::local_addr().
+        let listener = try!(TcpListener::bind(address));
+        let local_addr = try!(listener.local_addr());
+        let thread = thread::spawn(move || self.run(listener));
+
+        Ok(ReplayHandle {
+            local_addr: local_addr,
+            thread: thread,
+        })
+    }
+
+    fn run(self,listener: TcpListener) -> Result<(usize,TcpStream),io::Error> {
+        let (mut stream,_) = try!(listener.accept());
+        try!(stream.set_nodelay(true));
+
+        //Keep reading whatever the Engine sends so it never blocks on a full socket. The first
+        //bytes start the replay.
+        let mut reader = try!(stream.try_clone());
+        let (started_tx,started_rx) = channel();
+        thread::spawn(move || {
+            let mut buffer = [0;4096];
+            let mut started_tx = Some(started_tx);
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        if let Some(started_tx) = started_tx.take() {
+                            let _ = started_tx.send(());
+                        }
+                    },
+                }
+            }
+        });
+        if started_rx.recv().is_err() {
+            //Engine hung up without sending anything.
+            return Ok((0,stream));
+        }
+
+        let first_timestamp = match self.messages.first() {
+            Some(&(first_timestamp,_)) => first_timestamp,
+            None => return Ok((0,stream)),
+        };
+        let slice_nanoseconds = nanoseconds(self.time_slice);
+        let start = Instant::now();
+        let mut replayed_slice_start = 0;
+        let mut index = 0;
+        while index < self.messages.len() {
+            //Timestamps that go backwards are treated as happening at the same time as the one
+            //before them.
+            let offset = self.messages[index].0.signed_duration_since(first_timestamp).to_std().map(nanoseconds).unwrap_or(0);
+            let slice_start = cmp::max(offset / slice_nanoseconds * slice_nanoseconds,replayed_slice_start);
+            let slice_end = slice_start + slice_nanoseconds;
+
+            let mut bytes = Vec::new();
+            while index < self.messages.len() {
+                let offset = self.messages[index].0.signed_duration_since(first_timestamp).to_std().map(nanoseconds).unwrap_or(0);
+                if offset >= slice_end {
+                    break;
+                }
+
+                bytes.extend_from_slice(&self.messages[index].1);
+                index += 1;
+            }
+
+            let wait_nanoseconds = match self.speed {
+                ReplaySpeed::Original => Some(slice_start),
+                ReplaySpeed::Accelerated(factor) => Some(slice_start / factor as u64),
+                ReplaySpeed::Unpaced => None,
+            };
+            if let Some(wait_nanoseconds) = wait_nanoseconds {
+                let due = start + from_nanoseconds(wait_nanoseconds);
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep(due - now);
+                }
+            }
+            if let Some(ref clock) = self.clock {
+                if slice_start > replayed_slice_start {
+                    clock.advance(from_nanoseconds(slice_start - replayed_slice_start));
+                }
+            }
+            replayed_slice_start = slice_start;
+
+            try!(stream.write_all(&bytes));
+        }
+
+        Ok((index,stream))
+    }
+}
+
+impl Default for SessionReplay {
+    fn default() -> Self {
+        SessionReplay::new()
+    }
+}
+
+//A replay running in the background. The connection is kept open after every message has been
+//replayed until join() is called so the Engine isn't disconnected before it's done.
+pub struct ReplayHandle {
+    local_addr: SocketAddr,
+    thread: JoinHandle<Result<(usize,TcpStream),io::Error>>,
+}
+
+impl ReplayHandle {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn join(self) -> Result<usize,io::Error> {
+        //Wait until every message has been replayed and then disconnect. Returns the number of
+        //messages replayed.
+        let (message_count,stream) = match self.thread.join() {
+            Ok(result) => try!(result),
+            Err(_) => return Err(io::Error::new(io::ErrorKind::Other,"replay thread panicked")),
+        };
+        let _ = stream.shutdown(Shutdown::Both);
+
+        Ok(message_count)
+    }
+}
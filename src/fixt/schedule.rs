@@ -0,0 +1,146 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::{Duration,NaiveDate,NaiveTime};
+use chrono::datetime::DateTime;
+use chrono::naive::datetime::NaiveDateTime;
+use chrono::offset::utc::UTC;
+#[cfg(feature = "timezones")]
+use chrono::{Offset,TimeZone};
+#[cfg(feature = "timezones")]
+use chrono_tz::Tz;
+
+//Time zone database used to convert a venue's local session times to UTC. Implement this to inject
+//a custom database, such as a fixed set of transitions in tests. With the "timezones" feature,
+//chrono_tz::Tz implements it using the IANA database.
+pub trait SessionTimeZone {
+    //Seconds added to UTC to get the local time in effect at the given UTC time.
+    fn utc_offset_at(&self,utc: &NaiveDateTime) -> i32;
+}
+
+//A time zone that never observes daylight saving time.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct FixedTimeZone {
+    pub utc_offset: i32, //Seconds added to UTC to get local time.
+}
+
+impl FixedTimeZone {
+    pub fn utc() -> FixedTimeZone {
+        FixedTimeZone {
+            utc_offset: 0,
+        }
+    }
+}
+
+impl SessionTimeZone for FixedTimeZone {
+    fn utc_offset_at(&self,_utc: &NaiveDateTime) -> i32 {
+        self.utc_offset
+    }
+}
+
+#[cfg(feature = "timezones")]
+impl SessionTimeZone for Tz {
+    fn utc_offset_at(&self,utc: &NaiveDateTime) -> i32 {
+        self.offset_from_utc_datetime(utc).fix().local_minus_utc()
+    }
+}
+
+//A session that opens and closes at the same local times every day. When end is not after start,
+//the session ends on the following day so 17:00 to 17:00 is a 24 hour session that is only closed
+//for an instant.
+//
+//Local times are converted using the offset in effect on that day. A start or end time that falls
+//inside a spring forward gap happens when the wall clock would have shown it had it not jumped
+//(ie. 02:30 becomes 03:30). A time that happens twice after falling back uses the first
+//occurrence. So a session can be shorter or longer than usual on the days clocks change. A leap
+//second is treated as part of the second before it.
+pub struct SessionSchedule {
+    start: NaiveTime,
+    end: NaiveTime,
+    time_zone: Box<SessionTimeZone + Send>,
+}
+
+impl SessionSchedule {
+    pub fn new<T: SessionTimeZone + Send + 'static>(start: NaiveTime,end: NaiveTime,time_zone: T) -> SessionSchedule {
+        SessionSchedule {
+            start: start,
+            end: end,
+            time_zone: Box::new(time_zone),
+        }
+    }
+
+    pub fn utc_to_local(&self,utc: &NaiveDateTime) -> NaiveDateTime {
+        *utc + Duration::seconds(self.time_zone.utc_offset_at(utc) as i64)
+    }
+
+    pub fn local_to_utc(&self,local: &NaiveDateTime) -> NaiveDateTime {
+        //A local time matches zero (spring forward), one, or two (fall back) UTC times. Only the
+        //offsets in effect a day on either side need to be checked as long as a time zone doesn't
+        //change more than once per day.
+        let offset_before = self.time_zone.utc_offset_at(&(*local - Duration::days(1)));
+        let offset_after = self.time_zone.utc_offset_at(&(*local + Duration::days(1)));
+
+        let mut result: Option<NaiveDateTime> = None;
+        for offset in &[offset_before,offset_after] {
+            let utc = *local - Duration::seconds(*offset as i64);
+            if self.time_zone.utc_offset_at(&utc) == *offset {
+                result = match result {
+                    Some(earlier_utc) if earlier_utc <= utc => Some(earlier_utc),
+                    _ => Some(utc),
+                };
+            }
+        }
+
+        //Inside a gap, the old offset is still the right one for a wall clock that didn't jump.
+        result.unwrap_or(*local - Duration::seconds(offset_before as i64))
+    }
+
+    pub fn session_window(&self,now: &DateTime<UTC>) -> Option<(DateTime<UTC>,DateTime<UTC>)> {
+        //Returns the start and end of the session that is open at now or None when closed.
+        let now = now.naive_utc();
+        let local_date = self.utc_to_local(&now).date();
+
+        //Sessions starting the day before can still be open when they cross midnight.
+        for start_date in &[local_date.pred(),local_date] {
+            let (start,end) = self.window_starting_on(*start_date);
+            if start <= now && now < end {
+                return Some((DateTime::from_utc(start,UTC),DateTime::from_utc(end,UTC)));
+            }
+        }
+
+        None
+    }
+
+    pub fn is_active(&self,now: &DateTime<UTC>) -> bool {
+        self.session_window(now).is_some()
+    }
+
+    pub fn next_start(&self,now: &DateTime<UTC>) -> DateTime<UTC> {
+        //Returns when the next session starts. This is always after now, even when a session is
+        //open.
+        let now = now.naive_utc();
+        let mut start_date = self.utc_to_local(&now).date().pred();
+        loop {
+            let (start,_) = self.window_starting_on(start_date);
+            if start > now {
+                return DateTime::from_utc(start,UTC);
+            }
+
+            start_date = start_date.succ();
+        }
+    }
+
+    fn window_starting_on(&self,start_date: NaiveDate) -> (NaiveDateTime,NaiveDateTime) {
+        let end_date = if self.end > self.start { start_date } else { start_date.succ() };
+
+        (self.local_to_utc(&start_date.and_time(self.start)),self.local_to_utc(&end_date.and_time(self.end)))
+    }
+}
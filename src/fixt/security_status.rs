@@ -0,0 +1,119 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+use std::sync::{Arc,Mutex};
+use std::sync::mpsc::{self,Receiver,Sender};
+
+use dictionary::field_types::other::{HaltReason,SecurityTradingStatus};
+use dictionary::messages::SecurityStatus;
+use fixt::engine::Connection;
+use fixt::message::FIXTMessage;
+use fixt::middleware::{InboundMiddleware,MiddlewareAction};
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum TradingHaltEvent {
+    Halted {
+        connection: Connection,
+        symbol: Vec<u8>,
+        halt_reason: Option<HaltReason>,
+        text: Vec<u8>,
+    },
+    Resumed {
+        connection: Connection,
+        symbol: Vec<u8>,
+        security_trading_status: SecurityTradingStatus, //Resume or ReadyToTrade.
+    },
+}
+
+//Watches SecurityStatus messages and reports when a security is halted or resumes trading. Only
+//changes are reported: repeating a halt for a security that's already halted doesn't produce
+//another event. Any SecurityTradingStatus other than TradingHalt, Resume, and ReadyToTrade leaves
+//the halt state alone. State is kept per connection because each counterparty reports its own
+//market.
+//
+//Add it with EngineBuilder::inbound_middleware(). Every message is passed through unchanged.
+//Grab the Receiver from events() before handing the monitor to the engine.
+pub struct TradingHaltMonitor {
+    halted: HashMap<(Connection,Vec<u8>),bool>,
+    subscribers: Arc<Mutex<Vec<Sender<TradingHaltEvent>>>>,
+}
+
+impl TradingHaltMonitor {
+    pub fn new() -> TradingHaltMonitor {
+        TradingHaltMonitor {
+            halted: HashMap::new(),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn events(&self) -> Receiver<TradingHaltEvent> {
+        let (tx,rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+
+        rx
+    }
+
+    pub fn is_halted(&self,connection: Connection,symbol: &[u8]) -> bool {
+        *self.halted.get(&(connection,symbol.to_vec())).unwrap_or(&false)
+    }
+
+    pub fn on_security_status(&mut self,connection: Connection,message: &SecurityStatus) -> Option<TradingHaltEvent> {
+        //Returns the event sent to subscribers or None if the halt state didn't change.
+        let is_halted = match message.security_trading_status {
+            Some(SecurityTradingStatus::TradingHalt) => true,
+            Some(SecurityTradingStatus::Resume) | Some(SecurityTradingStatus::ReadyToTrade) => false,
+            _ => return None,
+        };
+
+        let was_halted = self.halted.insert((connection,message.symbol.clone()),is_halted).unwrap_or(false);
+        if was_halted == is_halted {
+            return None;
+        }
+
+        let event = if is_halted {
+            TradingHaltEvent::Halted {
+                connection: connection,
+                symbol: message.symbol.clone(),
+                halt_reason: message.halt_reason.clone(),
+                text: message.text.clone(),
+            }
+        }
+        else {
+            TradingHaltEvent::Resumed {
+                connection: connection,
+                symbol: message.symbol.clone(),
+                security_trading_status: message.security_trading_status.clone().unwrap(),
+            }
+        };
+
+        //Subscribers are removed once their Receiver is dropped.
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(event.clone()).is_ok());
+
+        Some(event)
+    }
+
+    pub fn clear_connection(&mut self,connection: Connection) {
+        //Forget the halt state of every security reported on a connection. Call this when the
+        //connection is terminated because nothing more will be heard about those securities.
+        self.halted.retain(|&(halted_connection,_),_| halted_connection != connection);
+    }
+}
+
+impl InboundMiddleware for TradingHaltMonitor {
+    fn on_message(&mut self,connection: Connection,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+        if let Some(security_status) = message.as_any().downcast_ref::<SecurityStatus>() {
+            self.on_security_status(connection,security_status);
+        }
+
+        MiddlewareAction::Continue(message)
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::Duration;
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::cmp;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use dictionary::messages::{Heartbeat,Logon,ResendRequest,SequenceReset};
+use fixt::message::FIXTMessage;
+
+//How far past HeartBtInt a quiet period can go before it counts as a missed heartbeat. Matches the
+//"reasonable transmission time" of 20% suggested by the FIX spec.
+const HEARTBEAT_GRACE_PERCENT: i64 = 20;
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct DurationPercentiles {
+    pub count: usize,
+    pub min: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl DurationPercentiles {
+    fn from_samples(samples: &[Duration]) -> Option<DurationPercentiles> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        //Nearest-rank so every reported value is one that was actually observed.
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let percentile = |percent: usize| sorted[cmp::max((percent * sorted.len() + 99) / 100,1) - 1];
+
+        Some(DurationPercentiles {
+            count: sorted.len(),
+            min: sorted[0],
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+//Everything learned about the messages sent from one CompID to another.
+#[derive(Clone,Debug)]
+pub struct DirectionReport {
+    pub sender_comp_id: Vec<u8>,
+    pub target_comp_id: Vec<u8>,
+    pub message_count: u64,
+    pub gaps: Vec<Range<u64>>, //MsgSeqNums that were skipped and never resent or gap filled.
+    pub resend_requests: u64, //ResendRequests sent in this direction.
+    pub poss_dup_messages: u64, //Messages resent with PossDupFlag set.
+    pub gap_fills: u64, //SequenceReset-GapFill messages.
+    pub sequence_resets: u64, //SequenceReset-Reset messages and Logons with ResetSeqNumFlag set.
+    pub heart_bt_int: Option<i64>, //HeartBtInt from the most recent Logon sent in this direction.
+    pub missed_heartbeats: u64, //Quiet periods longer than HeartBtInt plus HEARTBEAT_GRACE_PERCENT.
+    pub heartbeat_intervals: Option<DurationPercentiles>, //Time between consecutive Heartbeats.
+    pub inter_message_intervals: Option<DurationPercentiles>, //Time between consecutive messages of any type.
+    pub transit_latency: Option<DurationPercentiles>, //Capture or log time minus SendingTime. Includes any difference between the two clocks.
+}
+
+struct DirectionState {
+    message_count: u64,
+    next_msg_seq_num: Option<u64>, //None until the first message or after a Logon resets MsgSeqNums.
+    gaps: Vec<Range<u64>>,
+    resend_requests: u64,
+    poss_dup_messages: u64,
+    gap_fills: u64,
+    sequence_resets: u64,
+    heart_bt_int: Option<i64>,
+    missed_heartbeats: u64,
+    last_message_time: Option<DateTime<UTC>>,
+    last_heartbeat_time: Option<DateTime<UTC>>,
+    heartbeat_intervals: Vec<Duration>,
+    inter_message_intervals: Vec<Duration>,
+    transit_latencies: Vec<Duration>,
+}
+
+impl DirectionState {
+    fn new() -> DirectionState {
+        DirectionState {
+            message_count: 0,
+            next_msg_seq_num: None,
+            gaps: Vec::new(),
+            resend_requests: 0,
+            poss_dup_messages: 0,
+            gap_fills: 0,
+            sequence_resets: 0,
+            heart_bt_int: None,
+            missed_heartbeats: 0,
+            last_message_time: None,
+            last_heartbeat_time: None,
+            heartbeat_intervals: Vec::new(),
+            inter_message_intervals: Vec::new(),
+            transit_latencies: Vec::new(),
+        }
+    }
+
+    fn add_message(&mut self,timestamp: DateTime<UTC>,message: &FIXTMessage) {
+        self.message_count += 1;
+        self.transit_latencies.push(timestamp.signed_duration_since(message.sending_time()));
+
+        //Quiet periods are measured against the HeartBtInt that was in effect during them.
+        if let Some(last_message_time) = self.last_message_time {
+            let interval = timestamp.signed_duration_since(last_message_time);
+            if let Some(heart_bt_int) = self.heart_bt_int {
+                if heart_bt_int > 0 && interval > Duration::milliseconds(heart_bt_int * 10 * (100 + HEARTBEAT_GRACE_PERCENT)) {
+                    self.missed_heartbeats += 1;
+                }
+            }
+            self.inter_message_intervals.push(interval);
+        }
+        self.last_message_time = Some(timestamp);
+
+        if message.is_poss_dup() {
+            self.poss_dup_messages += 1;
+        }
+
+        let msg_seq_num = message.msg_seq_num();
+        let mut reset_msg_seq_num = None;
+        let mut gap_fill_new_seq_no = None;
+        if let Some(logon) = message.as_any().downcast_ref::<Logon>() {
+            self.heart_bt_int = Some(logon.heart_bt_int);
+            if logon.reset_seq_num_flag {
+                self.sequence_resets += 1;
+                self.next_msg_seq_num = None;
+            }
+        }
+        else if let Some(sequence_reset) = message.as_any().downcast_ref::<SequenceReset>() {
+            if sequence_reset.gap_fill_flag {
+                self.gap_fills += 1;
+                gap_fill_new_seq_no = Some(sequence_reset.new_seq_no);
+            }
+            else {
+                self.sequence_resets += 1;
+                reset_msg_seq_num = Some(sequence_reset.new_seq_no);
+            }
+        }
+        else if message.as_any().downcast_ref::<ResendRequest>().is_some() {
+            self.resend_requests += 1;
+        }
+        else if message.as_any().downcast_ref::<Heartbeat>().is_some() {
+            if let Some(last_heartbeat_time) = self.last_heartbeat_time {
+                self.heartbeat_intervals.push(timestamp.signed_duration_since(last_heartbeat_time));
+            }
+            self.last_heartbeat_time = Some(timestamp);
+        }
+
+        //SequenceReset-Reset ignores its own MsgSeqNum. Everything else either continues the
+        //sequence, skips ahead and leaves a gap, or fills in part of an earlier gap.
+        if let Some(new_seq_no) = reset_msg_seq_num {
+            self.next_msg_seq_num = Some(new_seq_no);
+            return;
+        }
+
+        let covered_end = match gap_fill_new_seq_no {
+            Some(new_seq_no) => cmp::max(new_seq_no,msg_seq_num + 1),
+            None => msg_seq_num + 1,
+        };
+        match self.next_msg_seq_num {
+            Some(next_msg_seq_num) if msg_seq_num > next_msg_seq_num => self.gaps.push(next_msg_seq_num..msg_seq_num),
+            Some(next_msg_seq_num) if msg_seq_num < next_msg_seq_num => remove_range(&mut self.gaps,msg_seq_num..covered_end),
+            _ => {},
+        }
+        self.next_msg_seq_num = Some(cmp::max(self.next_msg_seq_num.unwrap_or(0),covered_end));
+    }
+
+    fn report(&self,sender_comp_id: &[u8],target_comp_id: &[u8]) -> DirectionReport {
+        let mut gaps = self.gaps.clone();
+        gaps.sort_by_key(|gap| gap.start);
+
+        DirectionReport {
+            sender_comp_id: sender_comp_id.to_vec(),
+            target_comp_id: target_comp_id.to_vec(),
+            message_count: self.message_count,
+            gaps: gaps,
+            resend_requests: self.resend_requests,
+            poss_dup_messages: self.poss_dup_messages,
+            gap_fills: self.gap_fills,
+            sequence_resets: self.sequence_resets,
+            heart_bt_int: self.heart_bt_int,
+            missed_heartbeats: self.missed_heartbeats,
+            heartbeat_intervals: DurationPercentiles::from_samples(&self.heartbeat_intervals),
+            inter_message_intervals: DurationPercentiles::from_samples(&self.inter_message_intervals),
+            transit_latency: DurationPercentiles::from_samples(&self.transit_latencies),
+        }
+    }
+}
+
+fn remove_range(gaps: &mut Vec<Range<u64>>,filled: Range<u64>) {
+    let mut remaining_gaps = Vec::new();
+    for gap in gaps.drain(..) {
+        if filled.end <= gap.start || filled.start >= gap.end {
+            remaining_gaps.push(gap);
+            continue;
+        }
+
+        if gap.start < filled.start {
+            remaining_gaps.push(gap.start..filled.start);
+        }
+        if filled.end < gap.end {
+            remaining_gaps.push(filled.end..gap.end);
+        }
+    }
+
+    *gaps = remaining_gaps;
+}
+
+//Offline analysis of historical messages, such as those decoded from a message log or a network
+//capture. Messages are grouped by direction using their SenderCompID and TargetCompID and must be
+//added in the order they were sent or received. The timestamp is when the message was logged or
+//captured and is compared against SendingTime for latency.
+pub struct SessionAnalyzer {
+    directions: HashMap<(Vec<u8>,Vec<u8>),DirectionState>,
+}
+
+impl SessionAnalyzer {
+    pub fn new() -> SessionAnalyzer {
+        SessionAnalyzer {
+            directions: HashMap::new(),
+        }
+    }
+
+    pub fn add_message(&mut self,timestamp: DateTime<UTC>,message: &FIXTMessage) {
+        let key = (message.sender_comp_id().clone(),message.target_comp_id().clone());
+        self.directions.entry(key).or_insert_with(DirectionState::new).add_message(timestamp,message);
+    }
+
+    pub fn report(&self) -> Vec<DirectionReport> {
+        //Sorted by SenderCompID and then TargetCompID so reports are stable between runs.
+        let mut reports: Vec<DirectionReport> = self.directions.iter()
+            .map(|(&(ref sender_comp_id,ref target_comp_id),direction)| direction.report(sender_comp_id,target_comp_id))
+            .collect();
+        reports.sort_by(|a,b| (&a.sender_comp_id,&a.target_comp_id).cmp(&(&b.sender_comp_id,&b.target_comp_id)));
+
+        reports
+    }
+}
+
+impl Default for SessionAnalyzer {
+    fn default() -> Self {
+        SessionAnalyzer::new()
+    }
+}
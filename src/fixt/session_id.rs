@@ -0,0 +1,84 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use fix_version::FIXVersion;
+
+//Identifies a session independent of the connection it happens to be running on. A Connection
+//only lives as long as its socket while a SessionID stays the same across reconnects and restarts.
+//Use it as the key for anything kept about a counterparty, such as message stores and logs. The
+//session qualifier tells apart several sessions that share the same BeginString and CompIDs.
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
+pub struct SessionID {
+    pub fix_version: FIXVersion,
+    pub sender_comp_id: Vec<u8>,
+    pub target_comp_id: Vec<u8>,
+    pub session_qualifier: Option<Vec<u8>>,
+}
+
+impl SessionID {
+    pub fn new(fix_version: FIXVersion,sender_comp_id: &[u8],target_comp_id: &[u8]) -> SessionID {
+        SessionID {
+            fix_version: fix_version,
+            sender_comp_id: sender_comp_id.to_vec(),
+            target_comp_id: target_comp_id.to_vec(),
+            session_qualifier: None,
+        }
+    }
+
+    pub fn session_qualifier(mut self,session_qualifier: &[u8]) -> SessionID {
+        self.session_qualifier = Some(session_qualifier.to_vec());
+        self
+    }
+
+    pub fn file_name(&self) -> String {
+        //Name that is safe to use as a file or directory name on any platform. Every byte other
+        //than an ASCII letter, digit, or '.' is escaped as %XX so different SessionIDs never end
+        //up with the same name. Parts are separated with '_'.
+        let mut result = String::new();
+        escape_file_name_part(self.fix_version.begin_string(),&mut result);
+        result.push('_');
+        escape_file_name_part(&self.sender_comp_id[..],&mut result);
+        result.push('_');
+        escape_file_name_part(&self.target_comp_id[..],&mut result);
+        if let Some(ref session_qualifier) = self.session_qualifier {
+            result.push('_');
+            escape_file_name_part(&session_qualifier[..],&mut result);
+        }
+
+        result
+    }
+}
+
+fn escape_file_name_part(bytes: &[u8],result: &mut String) {
+    for byte in bytes {
+        match *byte {
+            b'a'...b'z' | b'A'...b'Z' | b'0'...b'9' | b'.' => result.push(*byte as char),
+            _ => result.push_str(&format!("%{:02X}",byte)),
+        }
+    }
+}
+
+impl fmt::Display for SessionID {
+    //Formatted like "FIXT.1.1:SENDER->TARGET" with ":QUALIFIER" appended when there is one.
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        try!(write!(f,"{}:{}->{}",
+                    String::from_utf8_lossy(self.fix_version.begin_string()),
+                    String::from_utf8_lossy(&self.sender_comp_id[..]),
+                    String::from_utf8_lossy(&self.target_comp_id[..])));
+        if let Some(ref session_qualifier) = self.session_qualifier {
+            try!(write!(f,":{}",String::from_utf8_lossy(&session_qualifier[..])));
+        }
+
+        Ok(())
+    }
+}
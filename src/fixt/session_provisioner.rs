@@ -0,0 +1,191 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::any::Any;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use dictionary::field_types::generic::TimestampPrecision;
+use dictionary::messages::Logon;
+use fix::ValidationSettings;
+use fixt::engine::{Connection,Engine,Listener,LogoutResponsePolicy,OutboxPolicy,ProbeSettings,ResendRequestLimits};
+use fixt::message_store::MessageStore;
+use fixt::outbound_validation::OutboundValidation;
+use fixt::third_party_routing::ThirdPartyRoutingSettings;
+
+//Everything known about a connection accepted by a listener once its Logon arrives.
+pub struct LogonRequest<'a> {
+    pub listener: Listener,
+    pub connection: Connection,
+    pub address: SocketAddr, //Where the connection came from.
+    pub message: &'a Logon, //Includes the CompIDs and any credentials such as Username and Password.
+}
+
+pub enum ProvisioningDecision {
+    Accept(SessionSettings), //Apply the settings and then approve the logon.
+    Reject(Option<Vec<u8>>), //Same as Engine::reject_new_connection().
+    Defer, //Deliver EngineEvent::ConnectionLoggingOn as if there was no provisioner.
+}
+
+//Configuration applied to an accepted session before the Logon response is sent. Anything left
+//unset uses the same defaults as a connection added with Engine::add_connection().
+pub struct SessionSettings {
+    response: Box<Logon>,
+    inbound_msg_seq_num: Option<u64>,
+    probe_settings: Option<ProbeSettings>,
+    resend_request_limits: Option<ResendRequestLimits>,
+    logout_response_policy: Option<LogoutResponsePolicy>,
+    outbox_policy: Option<OutboxPolicy>,
+    timestamp_precision: Option<TimestampPrecision>,
+    validation_settings: Option<ValidationSettings>,
+    outbound_validation: Option<OutboundValidation>,
+    third_party_routing_settings: Option<ThirdPartyRoutingSettings>,
+    message_store: Option<Box<MessageStore>>,
+    user_data: Option<Arc<Any + Send + Sync>>,
+}
+
+impl SessionSettings {
+    pub fn new(logon: &Logon) -> SessionSettings {
+        //Respond by agreeing with everything the remote asked for in its Logon.
+        let mut response = Logon::new();
+        response.encrypt_method = logon.encrypt_method.clone();
+        response.heart_bt_int = logon.heart_bt_int;
+        response.default_appl_ver_id = logon.default_appl_ver_id;
+
+        SessionSettings {
+            response: Box::new(response),
+            inbound_msg_seq_num: None,
+            probe_settings: None,
+            resend_request_limits: None,
+            logout_response_policy: None,
+            outbox_policy: None,
+            timestamp_precision: None,
+            validation_settings: None,
+            outbound_validation: None,
+            third_party_routing_settings: None,
+            message_store: None,
+            user_data: None,
+        }
+    }
+
+    pub fn response(mut self,response: Logon) -> SessionSettings {
+        self.response = Box::new(response);
+        self
+    }
+
+    pub fn inbound_msg_seq_num(mut self,inbound_msg_seq_num: u64) -> SessionSettings {
+        //See Engine::approve_new_connection().
+        self.inbound_msg_seq_num = Some(inbound_msg_seq_num);
+        self
+    }
+
+    pub fn probe_settings(mut self,probe_settings: ProbeSettings) -> SessionSettings {
+        self.probe_settings = Some(probe_settings);
+        self
+    }
+
+    pub fn resend_request_limits(mut self,resend_request_limits: ResendRequestLimits) -> SessionSettings {
+        self.resend_request_limits = Some(resend_request_limits);
+        self
+    }
+
+    pub fn logout_response_policy(mut self,logout_response_policy: LogoutResponsePolicy) -> SessionSettings {
+        self.logout_response_policy = Some(logout_response_policy);
+        self
+    }
+
+    pub fn outbox_policy(mut self,outbox_policy: OutboxPolicy) -> SessionSettings {
+        self.outbox_policy = Some(outbox_policy);
+        self
+    }
+
+    pub fn timestamp_precision(mut self,timestamp_precision: TimestampPrecision) -> SessionSettings {
+        self.timestamp_precision = Some(timestamp_precision);
+        self
+    }
+
+    pub fn validation_settings(mut self,validation_settings: ValidationSettings) -> SessionSettings {
+        self.validation_settings = Some(validation_settings);
+        self
+    }
+
+    pub fn outbound_validation(mut self,outbound_validation: OutboundValidation) -> SessionSettings {
+        self.outbound_validation = Some(outbound_validation);
+        self
+    }
+
+    pub fn third_party_routing_settings(mut self,third_party_routing_settings: ThirdPartyRoutingSettings) -> SessionSettings {
+        self.third_party_routing_settings = Some(third_party_routing_settings);
+        self
+    }
+
+    pub fn message_store(mut self,message_store: Box<MessageStore>) -> SessionSettings {
+        //Stored MsgSeqNums are not loaded for accepted sessions. Use inbound_msg_seq_num() and
+        //the response's MsgSeqNum instead. The store is still kept up to date and used to answer
+        //ResendRequests.
+        self.message_store = Some(message_store);
+        self
+    }
+
+    pub fn user_data(mut self,user_data: Arc<Any + Send + Sync>) -> SessionSettings {
+        self.user_data = Some(user_data);
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn apply(self,engine: &mut Engine,connection: Connection) {
+        //Use Engine::approve_new_connection_with_settings() instead. Settings are queued before the
+        //approval so they take effect before the Logon response is sent.
+        if let Some(probe_settings) = self.probe_settings {
+            engine.set_probe_settings(connection,probe_settings);
+        }
+        if let Some(resend_request_limits) = self.resend_request_limits {
+            engine.set_resend_request_limits(connection,resend_request_limits);
+        }
+        if let Some(logout_response_policy) = self.logout_response_policy {
+            engine.set_logout_response_policy(connection,logout_response_policy);
+        }
+        if let Some(outbox_policy) = self.outbox_policy {
+            engine.set_outbox_policy(connection,outbox_policy);
+        }
+        if let Some(timestamp_precision) = self.timestamp_precision {
+            engine.set_timestamp_precision(connection,timestamp_precision);
+        }
+        if let Some(validation_settings) = self.validation_settings {
+            engine.set_validation_settings(connection,validation_settings);
+        }
+        if let Some(outbound_validation) = self.outbound_validation {
+            engine.set_outbound_validation(connection,outbound_validation);
+        }
+        if let Some(third_party_routing_settings) = self.third_party_routing_settings {
+            engine.set_third_party_routing_settings(connection,third_party_routing_settings);
+        }
+        if let Some(message_store) = self.message_store {
+            engine.set_message_store(connection,message_store);
+        }
+        if let Some(user_data) = self.user_data {
+            engine.set_user_data(connection,user_data);
+        }
+
+        engine.approve_new_connection(connection,self.response,self.inbound_msg_seq_num);
+    }
+}
+
+//Decides which connections accepted by a listener are allowed to logon and how each of their
+//sessions is configured. Useful for gateways that don't know every counterparty ahead of time.
+//Without one, every Logon is delivered with EngineEvent::ConnectionLoggingOn and must be approved
+//or rejected by hand.
+//
+//Called from Engine::poll() on the application's thread instead of the engine's thread so it's
+//fine to look up credentials somewhere slow. Other events wait in the meantime though.
+pub trait SessionProvisioner: Send {
+    fn on_logon(&mut self,request: &LogonRequest) -> ProvisioningDecision;
+}
@@ -0,0 +1,199 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use ring::aead::{self,AES_256_GCM,OpeningKey,SealingKey};
+use ring::rand::{SecureRandom,SystemRandom};
+use std::collections::HashMap;
+use std::env;
+use std::io;
+
+const KEY_LEN: usize = 32;
+const KEY_ID_LEN: usize = 4;
+const NONCE_LEN: usize = 12;
+const PREFIX_LEN: usize = KEY_ID_LEN + NONCE_LEN;
+
+//Supplies the 256-bit keys used to encrypt a FileMessageStore. Every encrypted record remembers
+//the ID of the key it was encrypted with so keys can be rotated by changing current_key_id() as
+//long as older keys can still be looked up. Closures taking a key ID can be used too, which is the
+//easiest way to fetch keys from a KMS or HSM. See StoreEncryption::from_fn().
+pub trait KeyProvider: Send {
+    fn current_key_id(&self) -> u32 {
+        //Key used for everything written from now on.
+        0
+    }
+
+    fn key(&self,key_id: u32) -> Result<Vec<u8>,io::Error>;
+}
+
+impl<F> KeyProvider for F where F: Fn(u32) -> Result<Vec<u8>,io::Error> + Send {
+    fn key(&self,key_id: u32) -> Result<Vec<u8>,io::Error> {
+        self(key_id)
+    }
+}
+
+//A single key kept in memory.
+pub struct StaticKeyProvider {
+    key: Vec<u8>,
+}
+
+impl StaticKeyProvider {
+    pub fn new(key: Vec<u8>) -> StaticKeyProvider {
+        StaticKeyProvider {
+            key: key,
+        }
+    }
+}
+
+impl KeyProvider for StaticKeyProvider {
+    fn key(&self,_key_id: u32) -> Result<Vec<u8>,io::Error> {
+        Ok(self.key.clone())
+    }
+}
+
+//A single key read from a hex encoded environment variable. The variable is read when the key is
+//first needed instead of when this is created.
+pub struct EnvKeyProvider {
+    variable: String,
+}
+
+impl EnvKeyProvider {
+    pub fn new<S: Into<String>>(variable: S) -> EnvKeyProvider {
+        EnvKeyProvider {
+            variable: variable.into(),
+        }
+    }
+}
+
+impl KeyProvider for EnvKeyProvider {
+    fn key(&self,_key_id: u32) -> Result<Vec<u8>,io::Error> {
+        let value = try!(env::var(&self.variable).map_err(|_| {
+            io::Error::new(io::ErrorKind::NotFound,format!("Environment variable {} is not set",self.variable))
+        }));
+
+        decode_hex(value.trim()).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData,format!("Environment variable {} is not hex encoded",self.variable))
+        })
+    }
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.len() % 2 != 0 {
+        return None;
+    }
+
+    let mut result = Vec::with_capacity(value.len() / 2);
+    for pair in value.as_bytes().chunks(2) {
+        match ((pair[0] as char).to_digit(16),(pair[1] as char).to_digit(16)) {
+            (Some(high),Some(low)) => result.push((high << 4 | low) as u8),
+            _ => return None,
+        }
+    }
+
+    Some(result)
+}
+
+//Encrypts records with AES-256-GCM. Each record is laid out as the big endian key ID, a random
+//96-bit nonce, the ciphertext, and the tag. Additional data passed in, such as a record's
+//MsgSeqNum, must match when decrypting so records can't be swapped around without being noticed.
+//Give one to FileMessageStore::open_encrypted(). Requires the "store-encryption" feature.
+pub struct StoreEncryption {
+    key_provider: Box<KeyProvider>,
+    keys: HashMap<u32,(SealingKey,OpeningKey)>,
+    rng: SystemRandom,
+    allow_unencrypted_records: bool,
+}
+
+impl StoreEncryption {
+    pub fn new<K: KeyProvider + 'static>(key_provider: K) -> StoreEncryption {
+        StoreEncryption {
+            key_provider: Box::new(key_provider),
+            keys: HashMap::new(),
+            rng: SystemRandom::new(),
+            allow_unencrypted_records: false,
+        }
+    }
+
+    pub fn from_fn<F: Fn(u32) -> Result<Vec<u8>,io::Error> + Send + 'static>(key_provider: F) -> StoreEncryption {
+        //Same as new() except a closure's argument type is inferred instead of having to be
+        //written out.
+        StoreEncryption::new(key_provider)
+    }
+
+    pub fn allow_unencrypted_records(mut self) -> StoreEncryption {
+        //Let FileMessageStore::open_encrypted() read records written before encryption was turned
+        //on. Otherwise, an unencrypted record is an error because anyone who can write to the
+        //store's files could add one without it being noticed. Only meant for migrating an
+        //existing store. Everything written is still encrypted.
+        self.allow_unencrypted_records = true;
+        self
+    }
+
+    pub fn allows_unencrypted_records(&self) -> bool {
+        self.allow_unencrypted_records
+    }
+
+    pub fn encrypt(&mut self,bytes: &[u8],additional_data: &[u8]) -> Result<Vec<u8>,io::Error> {
+        let key_id = self.key_provider.current_key_id();
+        let mut nonce = [0;NONCE_LEN];
+        try!(self.rng.fill(&mut nonce).map_err(|_| io::Error::new(io::ErrorKind::Other,"Could not generate nonce")));
+
+        let tag_len = AES_256_GCM.tag_len();
+        let mut result = Vec::with_capacity(PREFIX_LEN + bytes.len() + tag_len);
+        result.extend_from_slice(&[(key_id >> 24) as u8,(key_id >> 16) as u8,(key_id >> 8) as u8,key_id as u8]);
+        result.extend_from_slice(&nonce);
+        result.extend_from_slice(bytes);
+        result.resize(PREFIX_LEN + bytes.len() + tag_len,0);
+
+        let sealed_len = {
+            let keys = try!(self.keys(key_id));
+            try!(aead::seal_in_place(&keys.0,&nonce,additional_data,&mut result[PREFIX_LEN..],tag_len).map_err(|_| {
+                io::Error::new(io::ErrorKind::Other,"Could not encrypt record")
+            }))
+        };
+        result.truncate(PREFIX_LEN + sealed_len);
+
+        Ok(result)
+    }
+
+    pub fn decrypt(&mut self,bytes: &[u8],additional_data: &[u8]) -> Result<Vec<u8>,io::Error> {
+        if bytes.len() < PREFIX_LEN + AES_256_GCM.tag_len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData,"Encrypted record is too short"));
+        }
+
+        let key_id = bytes[..KEY_ID_LEN].iter().fold(0,|key_id,byte| (key_id << 8) | *byte as u32);
+        let mut result = bytes[PREFIX_LEN..].to_vec();
+        let opened_len = {
+            let keys = try!(self.keys(key_id));
+            try!(aead::open_in_place(&keys.1,&bytes[KEY_ID_LEN..PREFIX_LEN],additional_data,0,&mut result).map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidData,"Could not decrypt record. Either the key is wrong or the record was modified.")
+            })).len()
+        };
+        result.truncate(opened_len);
+
+        Ok(result)
+    }
+
+    fn keys(&mut self,key_id: u32) -> Result<&(SealingKey,OpeningKey),io::Error> {
+        //Keys are only fetched once since a KeyProvider might be slow or remote.
+        if !self.keys.contains_key(&key_id) {
+            let key = try!(self.key_provider.key(key_id));
+            if key.len() != KEY_LEN {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput,format!("Key {} is not a 256-bit key",key_id)));
+            }
+
+            let sealing_key = try!(SealingKey::new(&AES_256_GCM,&key).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput,"Invalid key")));
+            let opening_key = try!(OpeningKey::new(&AES_256_GCM,&key).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput,"Invalid key")));
+            self.keys.insert(key_id,(sealing_key,opening_key));
+        }
+
+        Ok(&self.keys[&key_id])
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::collections::HashMap;
+
+use fixt::message::FIXTMessage;
+
+//Translates between a venue's symbology and the application's own instrument ids. Each connection
+//can have its own mapper so an application trading the same instrument on several venues only ever
+//sees one id for it. Only the Symbol field at the top level of a message is mapped. Symbols inside
+//repeating groups, such as the NoRelatedSym group of a MarketDataRequest, are left alone.
+//
+//Mappers are called on the same thread that does all of the networking for every connection so
+//they must be quick and must not block or panic.
+pub trait SymbolMapper: Send + Sync {
+    //Symbol received from the venue to the internal id. None leaves the symbol unchanged.
+    fn to_internal(&self,venue_symbol: &[u8]) -> Option<Vec<u8>>;
+
+    //Internal id to the symbol the venue expects. None leaves the symbol unchanged.
+    fn to_venue(&self,internal_symbol: &[u8]) -> Option<Vec<u8>>;
+}
+
+//Mapper backed by a fixed table of venue symbols and internal ids.
+pub struct StaticSymbolMapper {
+    to_internal: HashMap<Vec<u8>,Vec<u8>>,
+    to_venue: HashMap<Vec<u8>,Vec<u8>>,
+}
+
+impl StaticSymbolMapper {
+    pub fn new() -> StaticSymbolMapper {
+        StaticSymbolMapper {
+            to_internal: HashMap::new(),
+            to_venue: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self,venue_symbol: &[u8],internal_symbol: &[u8]) {
+        //Replaces any existing mapping for either symbol.
+        if let Some(old_internal_symbol) = self.to_internal.insert(venue_symbol.to_vec(),internal_symbol.to_vec()) {
+            self.to_venue.remove(&old_internal_symbol);
+        }
+        if let Some(old_venue_symbol) = self.to_venue.insert(internal_symbol.to_vec(),venue_symbol.to_vec()) {
+            if old_venue_symbol != venue_symbol {
+                self.to_internal.remove(&old_venue_symbol);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.to_internal.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.to_internal.is_empty()
+    }
+}
+
+impl Default for StaticSymbolMapper {
+    fn default() -> Self {
+        StaticSymbolMapper::new()
+    }
+}
+
+impl SymbolMapper for StaticSymbolMapper {
+    fn to_internal(&self,venue_symbol: &[u8]) -> Option<Vec<u8>> {
+        self.to_internal.get(venue_symbol).cloned()
+    }
+
+    fn to_venue(&self,internal_symbol: &[u8]) -> Option<Vec<u8>> {
+        self.to_venue.get(internal_symbol).cloned()
+    }
+}
+
+pub fn map_inbound_symbol(mapper: &SymbolMapper,message: &mut FIXTMessage) -> bool {
+    //Replace a received message's Symbol with the internal id. Returns true if it was changed.
+    let internal_symbol = match message.symbol_bytes() {
+        Some(venue_symbol) => mapper.to_internal(venue_symbol),
+        None => None,
+    };
+
+    match internal_symbol {
+        Some(internal_symbol) => message.set_symbol_bytes(&internal_symbol[..]),
+        None => false,
+    }
+}
+
+pub fn map_outbound_symbol(mapper: &SymbolMapper,message: &mut FIXTMessage) -> bool {
+    //Replace a message's internal id with the venue's Symbol before it's sent. Returns true if it
+    //was changed.
+    let venue_symbol = match message.symbol_bytes() {
+        Some(internal_symbol) => mapper.to_venue(internal_symbol),
+        None => None,
+    };
+
+    match venue_symbol {
+        Some(venue_symbol) => message.set_symbol_bytes(&venue_symbol[..]),
+        None => false,
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use dictionary::fields::{DeliverToCompID,OnBehalfOfCompID};
+use field::Field;
+
+//Third party addressing header fields used when messages pass through a hub such as a broker
+//network or service bureau. The original sender sets DeliverToCompID to the final recipient. The
+//hub then forwards the message with TargetCompID set to the final recipient and OnBehalfOfCompID
+//set to the original sender. Responses go back through the hub the same way in reverse. See FIX
+//4.4 Volume 2, page 8.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ThirdPartyRouting {
+    pub on_behalf_of_comp_id: Vec<u8>,
+    pub on_behalf_of_sub_id: Vec<u8>,
+    pub on_behalf_of_location_id: Vec<u8>, //FIX 4.1 and later.
+    pub deliver_to_comp_id: Vec<u8>,
+    pub deliver_to_sub_id: Vec<u8>,
+    pub deliver_to_location_id: Vec<u8>, //FIX 4.1 and later.
+}
+
+impl ThirdPartyRouting {
+    pub fn new() -> ThirdPartyRouting {
+        ThirdPartyRouting {
+            on_behalf_of_comp_id: Vec::new(),
+            on_behalf_of_sub_id: Vec::new(),
+            on_behalf_of_location_id: Vec::new(),
+            deliver_to_comp_id: Vec::new(),
+            deliver_to_sub_id: Vec::new(),
+            deliver_to_location_id: Vec::new(),
+        }
+    }
+
+    pub fn deliver_to(deliver_to_comp_id: &[u8]) -> ThirdPartyRouting {
+        let mut routing = ThirdPartyRouting::new();
+        routing.deliver_to_comp_id = deliver_to_comp_id.to_vec();
+
+        routing
+    }
+
+    pub fn on_behalf_of(on_behalf_of_comp_id: &[u8]) -> ThirdPartyRouting {
+        let mut routing = ThirdPartyRouting::new();
+        routing.on_behalf_of_comp_id = on_behalf_of_comp_id.to_vec();
+
+        routing
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.on_behalf_of_comp_id.is_empty() &&
+        self.on_behalf_of_sub_id.is_empty() &&
+        self.on_behalf_of_location_id.is_empty() &&
+        self.deliver_to_comp_id.is_empty() &&
+        self.deliver_to_sub_id.is_empty() &&
+        self.deliver_to_location_id.is_empty()
+    }
+
+    //Routing to use for a response to a message that was received with this routing. A message
+    //sent on behalf of a firm must be answered by delivering to that firm and vice versa.
+    pub fn reply(&self) -> ThirdPartyRouting {
+        ThirdPartyRouting {
+            on_behalf_of_comp_id: self.deliver_to_comp_id.clone(),
+            on_behalf_of_sub_id: self.deliver_to_sub_id.clone(),
+            on_behalf_of_location_id: self.deliver_to_location_id.clone(),
+            deliver_to_comp_id: self.on_behalf_of_comp_id.clone(),
+            deliver_to_sub_id: self.on_behalf_of_sub_id.clone(),
+            deliver_to_location_id: self.on_behalf_of_location_id.clone(),
+        }
+    }
+}
+
+impl Default for ThirdPartyRouting {
+    fn default() -> Self {
+        ThirdPartyRouting::new()
+    }
+}
+
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum ThirdPartyRoutingError {
+    OnBehalfOfCompIDMissing, //OnBehalfOfSubID or OnBehalfOfLocationID was set without OnBehalfOfCompID.
+    DeliverToCompIDMissing, //DeliverToSubID or DeliverToLocationID was set without DeliverToCompID.
+    DeliverToNotSupported, //DeliverToCompID was set but this side of the connection can't forward messages.
+    OnBehalfOfCompIDNotAllowed, //OnBehalfOfCompID is not in ThirdPartyRoutingSettings::allowed_on_behalf_of_comp_ids.
+}
+
+impl ThirdPartyRoutingError {
+    pub fn tag_bytes(&self) -> &'static [u8] {
+        match *self {
+            ThirdPartyRoutingError::OnBehalfOfCompIDMissing |
+            ThirdPartyRoutingError::OnBehalfOfCompIDNotAllowed => OnBehalfOfCompID::tag_bytes(),
+            ThirdPartyRoutingError::DeliverToCompIDMissing |
+            ThirdPartyRoutingError::DeliverToNotSupported => DeliverToCompID::tag_bytes(),
+        }
+    }
+}
+
+impl fmt::Display for ThirdPartyRoutingError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ThirdPartyRoutingError::OnBehalfOfCompIDMissing => write!(f,"OnBehalfOfCompID is required when OnBehalfOfSubID or OnBehalfOfLocationID is set"),
+            ThirdPartyRoutingError::DeliverToCompIDMissing => write!(f,"DeliverToCompID is required when DeliverToSubID or DeliverToLocationID is set"),
+            ThirdPartyRoutingError::DeliverToNotSupported => write!(f,"DeliverToCompID is not supported"),
+            ThirdPartyRoutingError::OnBehalfOfCompIDNotAllowed => write!(f,"OnBehalfOfCompID is not allowed"),
+        }
+    }
+}
+
+//Checks applied to third party routing fields of inbound messages. Messages that fail are
+//rejected with SessionRejectReason::CompIDProblem and reported with EngineEvent::MessageRejected
+//but the session stays up.
+#[derive(Clone,Debug,PartialEq)]
+pub struct ThirdPartyRoutingSettings {
+    pub reject_incomplete: bool, //Reject messages with a SubID or LocationID but no matching CompID.
+    pub reject_deliver_to: bool, //Reject messages with DeliverToCompID set because this side is the final recipient and never forwards.
+    pub allowed_on_behalf_of_comp_ids: Option<Vec<Vec<u8>>>, //Reject messages sent on behalf of any firm not listed. Messages without OnBehalfOfCompID are always allowed.
+    pub reflect_in_responses: bool, //Address messages the engine sends in response to an inbound message, like Rejects and Heartbeats answering TestRequests, back through the hub.
+}
+
+impl ThirdPartyRoutingSettings {
+    pub fn new() -> ThirdPartyRoutingSettings {
+        ThirdPartyRoutingSettings {
+            reject_incomplete: false,
+            reject_deliver_to: false,
+            allowed_on_behalf_of_comp_ids: None,
+            reflect_in_responses: true,
+        }
+    }
+
+    pub fn validate(&self,routing: &ThirdPartyRouting) -> Result<(),ThirdPartyRoutingError> {
+        if self.reject_incomplete {
+            if routing.on_behalf_of_comp_id.is_empty() && (!routing.on_behalf_of_sub_id.is_empty() || !routing.on_behalf_of_location_id.is_empty()) {
+                return Err(ThirdPartyRoutingError::OnBehalfOfCompIDMissing);
+            }
+            if routing.deliver_to_comp_id.is_empty() && (!routing.deliver_to_sub_id.is_empty() || !routing.deliver_to_location_id.is_empty()) {
+                return Err(ThirdPartyRoutingError::DeliverToCompIDMissing);
+            }
+        }
+
+        if self.reject_deliver_to && !routing.deliver_to_comp_id.is_empty() {
+            return Err(ThirdPartyRoutingError::DeliverToNotSupported);
+        }
+
+        if let Some(ref allowed_on_behalf_of_comp_ids) = self.allowed_on_behalf_of_comp_ids {
+            if !routing.on_behalf_of_comp_id.is_empty() && !allowed_on_behalf_of_comp_ids.contains(&routing.on_behalf_of_comp_id) {
+                return Err(ThirdPartyRoutingError::OnBehalfOfCompIDNotAllowed);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for ThirdPartyRoutingSettings {
+    fn default() -> Self {
+        ThirdPartyRoutingSettings::new()
+    }
+}
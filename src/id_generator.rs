@@ -0,0 +1,120 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::{Timelike,UTC};
+
+//Generates unique IDs for fields like ClOrdID, MDReqID, and QuoteID. IDs only have to be unique
+//for the life of a generator. To stay unique across restarts, persist last_counter() somewhere
+//(ie. alongside any stored sequence numbers) and pass it back in when creating the generator
+//again. TimestampIdGenerator does not need this as long as the system clock doesn't go backwards
+//between runs.
+pub trait IdGenerator {
+    fn next_id(&mut self) -> Vec<u8>;
+    fn last_counter(&self) -> u64;
+}
+
+//Generates "1", "2", "3", etc.
+pub struct MonotonicIdGenerator {
+    last_counter: u64,
+}
+
+impl MonotonicIdGenerator {
+    pub fn new() -> MonotonicIdGenerator {
+        MonotonicIdGenerator::resume(0)
+    }
+
+    pub fn resume(last_counter: u64) -> MonotonicIdGenerator {
+        MonotonicIdGenerator {
+            last_counter: last_counter,
+        }
+    }
+}
+
+impl IdGenerator for MonotonicIdGenerator {
+    fn next_id(&mut self) -> Vec<u8> {
+        self.last_counter += 1;
+        self.last_counter.to_string().into_bytes()
+    }
+
+    fn last_counter(&self) -> u64 {
+        self.last_counter
+    }
+}
+
+//Generates the prefix followed by an incrementing counter. ie. "ORD1", "ORD2", etc. Using a
+//different prefix per process or per session is an easy way to keep IDs from colliding when
+//several generators share a counterparty.
+pub struct PrefixIdGenerator {
+    prefix: Vec<u8>,
+    counter: MonotonicIdGenerator,
+}
+
+impl PrefixIdGenerator {
+    pub fn new(prefix: &[u8]) -> PrefixIdGenerator {
+        PrefixIdGenerator::resume(prefix,0)
+    }
+
+    pub fn resume(prefix: &[u8],last_counter: u64) -> PrefixIdGenerator {
+        PrefixIdGenerator {
+            prefix: prefix.to_vec(),
+            counter: MonotonicIdGenerator::resume(last_counter),
+        }
+    }
+}
+
+impl IdGenerator for PrefixIdGenerator {
+    fn next_id(&mut self) -> Vec<u8> {
+        let mut id = self.prefix.clone();
+        id.extend_from_slice(&self.counter.next_id());
+        id
+    }
+
+    fn last_counter(&self) -> u64 {
+        self.counter.last_counter()
+    }
+}
+
+//Generates the prefix followed by the number of microseconds since the UNIX epoch. If more than
+//one ID is generated within the same microsecond, or the clock goes backwards, the previous value
+//is incremented instead so IDs are always strictly increasing.
+pub struct TimestampIdGenerator {
+    prefix: Vec<u8>,
+    last_counter: u64,
+}
+
+impl TimestampIdGenerator {
+    pub fn new(prefix: &[u8]) -> TimestampIdGenerator {
+        TimestampIdGenerator::resume(prefix,0)
+    }
+
+    pub fn resume(prefix: &[u8],last_counter: u64) -> TimestampIdGenerator {
+        TimestampIdGenerator {
+            prefix: prefix.to_vec(),
+            last_counter: last_counter,
+        }
+    }
+}
+
+impl IdGenerator for TimestampIdGenerator {
+    fn next_id(&mut self) -> Vec<u8> {
+        let now = UTC::now();
+        let timestamp = (now.timestamp() as u64) * 1_000_000 + ((now.nanosecond() / 1000) as u64);
+        self.last_counter = if timestamp > self.last_counter { timestamp } else { self.last_counter + 1 };
+
+        let mut id = self.prefix.clone();
+        id.extend_from_slice(self.last_counter.to_string().as_bytes());
+        id
+    }
+
+    fn last_counter(&self) -> u64 {
+        self.last_counter
+    }
+}
@@ -0,0 +1,205 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::any::Any;
+use std::cmp;
+use std::ops::Range;
+use std::sync::Arc;
+
+use fix::{ParseError,Parser,PrescannedMessage};
+use fixt::message::FIXTMessage;
+use message::Message;
+
+//A received message whose header was read eagerly with Parser::prescan() but whose body is only
+//decoded the first time it's needed. Messages that are filtered out by MsgType, MsgSeqNum, or
+//CompID then cost little more than copying their bytes. Decoding is done by whichever Parser is
+//passed in. It must be idle, share the dictionary of the Parser that prescanned the message, and
+//usually has the same message version defaults so the result is identical to parse().
+//
+//The raw bytes live in a chunk shared by every message of the same batch. See LazyMessageArena.
+pub struct LazyMessage {
+    chunk: Arc<Vec<u8>>,
+    bytes: Range<usize>, //Where the whole message is in chunk.
+    msg_type: Range<usize>,
+    msg_seq_num: Option<u64>,
+    poss_dup: bool,
+    sender_comp_id: Range<usize>,
+    target_comp_id: Range<usize>,
+    decoded: Option<Result<Box<FIXTMessage + Send>,ParseError>>,
+}
+
+impl LazyMessage {
+    pub fn new(prescanned_message: &PrescannedMessage,message_bytes: &[u8]) -> LazyMessage {
+        //message_bytes must be the same slice passed to Parser::prescan().
+        let chunk = Arc::new(message_bytes[..prescanned_message.length].to_vec());
+        LazyMessage::in_chunk(chunk,0,prescanned_message,message_bytes)
+    }
+
+    fn in_chunk(chunk: Arc<Vec<u8>>,offset: usize,prescanned_message: &PrescannedMessage,message_bytes: &[u8]) -> LazyMessage {
+        //message_bytes were copied into chunk starting at offset.
+        let range = |value: &[u8]| {
+            //Missing values are empty slices that don't necessarily point into message_bytes.
+            if value.is_empty() {
+                return offset..offset;
+            }
+
+            let start = offset + (value.as_ptr() as usize - message_bytes.as_ptr() as usize);
+            start..start + value.len()
+        };
+
+        LazyMessage {
+            bytes: offset..offset + prescanned_message.length,
+            msg_type: range(prescanned_message.msg_type),
+            msg_seq_num: prescanned_message.msg_seq_num,
+            poss_dup: prescanned_message.poss_dup,
+            sender_comp_id: range(prescanned_message.sender_comp_id),
+            target_comp_id: range(prescanned_message.target_comp_id),
+            decoded: None,
+            chunk: chunk,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.chunk[self.bytes.clone()]
+    }
+
+    pub fn msg_type(&self) -> &[u8] {
+        &self.chunk[self.msg_type.clone()]
+    }
+
+    pub fn msg_seq_num(&self) -> Option<u64> {
+        self.msg_seq_num
+    }
+
+    pub fn is_poss_dup(&self) -> bool {
+        self.poss_dup
+    }
+
+    pub fn sender_comp_id(&self) -> &[u8] {
+        &self.chunk[self.sender_comp_id.clone()]
+    }
+
+    pub fn target_comp_id(&self) -> &[u8] {
+        &self.chunk[self.target_comp_id.clone()]
+    }
+
+    pub fn is_decoded(&self) -> bool {
+        self.decoded.is_some()
+    }
+
+    pub fn message(&mut self,parser: &mut Parser) -> Result<&(FIXTMessage + Send),&ParseError> {
+        //Decode the body on the first call. Later calls, even with a different parser, return the
+        //same result. The exception is a parser that wasn't idle, which is retried next time.
+        let is_retry = if let Some(Err(ParseError::ParserNotIdle)) = self.decoded { true } else { false };
+        if self.decoded.is_none() || is_retry {
+            self.decoded = Some(decode(&self.chunk[self.bytes.clone()],parser));
+        }
+
+        match *self.decoded.as_ref().unwrap() {
+            Ok(ref message) => Ok(&**message),
+            Err(ref e) => Err(e),
+        }
+    }
+
+    pub fn get<T: FIXTMessage + Any>(&mut self,parser: &mut Parser) -> Result<Option<&T>,&ParseError> {
+        //Typed access to the decoded message. Ok(None) when the message is a different type.
+        self.message(parser).map(|message| message.as_any().downcast_ref::<T>())
+    }
+
+    pub fn into_message(self,parser: &mut Parser) -> Result<Box<FIXTMessage + Send>,ParseError> {
+        match self.decoded {
+            Some(Err(ParseError::ParserNotIdle)) | None => decode(&self.chunk[self.bytes.clone()],parser),
+            Some(decoded) => decoded,
+        }
+    }
+}
+
+//Bump arena for the raw bytes of lazily decoded messages. Every message prescanned in one call
+//to prescan_messages() is copied into a single chunk with one allocation instead of one per
+//message. Once the application drops every LazyMessage of a batch, the chunk is reset and reused
+//by the next batch so a busy session stops allocating for message storage altogether. A batch
+//that is still held onto keeps its chunk and the next batch gets a new one.
+pub struct LazyMessageArena {
+    chunk: Arc<Vec<u8>>,
+    capacity: usize,
+}
+
+impl LazyMessageArena {
+    pub fn new(capacity: usize) -> LazyMessageArena {
+        LazyMessageArena {
+            chunk: Arc::new(Vec::with_capacity(capacity)),
+            capacity: capacity,
+        }
+    }
+
+    pub fn is_released(&self) -> bool {
+        //Whether every LazyMessage from the previous batch has been dropped.
+        Arc::strong_count(&self.chunk) == 1
+    }
+
+    pub fn prescan_messages(&mut self,parser: &Parser,message_bytes: &[u8]) -> (usize,Vec<LazyMessage>) {
+        //Same as prescan_lazy_messages() but the messages are stored in the arena.
+        let mut index = 0;
+        let mut prescanned_messages = Vec::new();
+        while let Some(prescanned_message) = parser.prescan(&message_bytes[index..]) {
+            index += prescanned_message.length;
+            prescanned_messages.push(prescanned_message);
+        }
+        if prescanned_messages.is_empty() {
+            return (0,Vec::new());
+        }
+
+        //Reset the chunk if the previous batch was released. Otherwise, leave it to whoever is
+        //still using it.
+        if !self.is_released() {
+            self.chunk = Arc::new(Vec::with_capacity(cmp::max(self.capacity,index)));
+        }
+        {
+            let chunk = Arc::get_mut(&mut self.chunk).unwrap();
+            chunk.clear();
+            chunk.extend_from_slice(&message_bytes[..index]);
+        }
+
+        let mut offset = 0;
+        let mut messages = Vec::with_capacity(prescanned_messages.len());
+        for prescanned_message in &prescanned_messages {
+            messages.push(LazyMessage::in_chunk(self.chunk.clone(),offset,prescanned_message,&message_bytes[offset..]));
+            offset += prescanned_message.length;
+        }
+
+        (index,messages)
+    }
+}
+
+pub fn prescan_lazy_messages(parser: &Parser,message_bytes: &[u8]) -> (usize,Vec<LazyMessage>) {
+    //Split as many complete messages as possible off the front of message_bytes without decoding
+    //their bodies. Returns how many bytes were used. Scanning stops at the first message that
+    //Parser::prescan() can't handle, such as an incomplete or malformed one, so the rest can be
+    //passed to Parser::parse() to be reported as usual. The batch gets its own chunk. Use a
+    //LazyMessageArena to reuse one between batches.
+    LazyMessageArena::new(0).prescan_messages(parser,message_bytes)
+}
+
+fn decode(bytes: &[u8],parser: &mut Parser) -> Result<Box<FIXTMessage + Send>,ParseError> {
+    //Decoding with a parser that is part way through another message would mix the two up.
+    if !parser.is_idle() {
+        return Err(ParseError::ParserNotIdle);
+    }
+
+    //The parser might be holding messages from an earlier parse() that haven't been taken yet so
+    //only take the message this call adds.
+    let message_count = parser.messages.len();
+    let (_,result) = parser.parse(bytes);
+    try!(result);
+    assert!(parser.messages.len() > message_count);
+
+    Ok(parser.messages.pop().unwrap())
+}
@@ -32,13 +32,22 @@
 #![allow(unknown_lints)]
 
 extern crate chrono;
+#[cfg(feature = "timezones")]
+extern crate chrono_tz;
+#[cfg(feature = "compression")]
+extern crate flate2;
+#[cfg(feature = "store-compression")]
+extern crate zstd;
 #[macro_use]
 extern crate fix_rs_macros;
 extern crate mio;
 extern crate phf;
+#[cfg(feature = "store-encryption")]
+extern crate ring;
 extern crate time;
 
 pub mod byte_buffer;
+pub mod clock;
 #[macro_use]
 pub mod fixt;
 pub mod constant;
@@ -49,11 +58,19 @@ pub mod field_type;
 pub mod fix;
 pub mod fix_version;
 pub mod hash;
+pub mod id_generator;
+pub mod lazy_message;
 #[macro_use]
 pub mod message;
+pub mod message_conversion;
+pub mod message_diff;
+pub mod message_hash;
 pub mod message_version;
 mod network_read_retry;
+#[cfg(feature = "pcap")]
+pub mod pcap;
 pub mod rule;
+mod timer;
 mod token_generator;
 
 //Dictionary is put last because it needs the above macros.
@@ -11,6 +11,7 @@
 
 use std::any::Any;
 use std::collections::{HashMap,HashSet};
+use std::fmt;
 use std::mem;
 use std::io::Write;
 use std::ptr;
@@ -26,10 +27,10 @@ use rule::Rule;
 pub type FieldHashMap = HashMap<FieldTag,Rule,BuildFieldHasher>;
 pub type FieldHashSet = HashSet<FieldTag,BuildFieldHasher>;
 
-pub trait BuildMessage {
+pub trait BuildMessage: Sync {
     fn first_field(&self,version: MessageVersion) -> FieldTag;
     fn field_count(&self,version: MessageVersion) -> usize;
-    fn fields(&mut self,version: MessageVersion) -> FieldHashMap;
+    fn fields(&self,version: MessageVersion) -> FieldHashMap;
     fn required_fields(&self,version: MessageVersion) -> FieldHashSet;
 
     fn new_into_box(&self) -> Box<BuildMessage + Send>;
@@ -43,6 +44,83 @@ pub trait MessageBuildable {
 
 pub trait MessageDetails {
     fn msg_type() -> &'static [u8];
+    //Generated from the message definition. Format with Display to get a Markdown table of the
+    //fields for documentation.
+    fn metadata() -> MessageMetadata;
+
+    fn message_metadata() -> MessageMetadata where Self: Sized {
+        Self::metadata()
+    }
+}
+
+//Describes one field of a message as defined in the dictionary.
+#[derive(Clone,Debug,PartialEq)]
+pub struct FieldMetadata {
+    pub name: &'static str, //Name of the struct member holding the field's value.
+    pub field_name: &'static str, //Name of the field in the dictionary, such as "HeartBtInt".
+    pub field_type: &'static str, //Name of the FieldType used to parse and serialize the value.
+    pub tag: FieldTag,
+    pub required: bool,
+    pub conditionally_required: bool, //Only required when some other field in the message is set.
+    pub minimum_version: MessageVersion,
+    pub maximum_version: MessageVersion,
+    pub group: Option<MessageMetadata>, //Fields of each entry when this is a repeating group's count field.
+}
+
+impl FieldMetadata {
+    pub fn supports(&self,version: MessageVersion) -> bool {
+        version.as_value() >= self.minimum_version.as_value() && version.as_value() <= self.maximum_version.as_value()
+    }
+}
+
+//Describes which fields a message supports and in which versions. Use this to discover what the
+//dictionary covers at runtime instead of reading through the message definitions.
+#[derive(Clone,Debug,PartialEq)]
+pub struct MessageMetadata {
+    pub name: &'static str,
+    pub msg_type: &'static [u8], //Empty for repeating groups and components.
+    pub fields: Vec<FieldMetadata>, //In the order they are serialized.
+}
+
+impl MessageMetadata {
+    pub fn fields_for_version(&self,version: MessageVersion) -> Vec<&FieldMetadata> {
+        self.fields.iter().filter(|field| field.supports(version)).collect()
+    }
+
+    pub fn field(&self,tag: FieldTag) -> Option<&FieldMetadata> {
+        self.fields.iter().find(|field| field.tag == tag)
+    }
+}
+
+impl fmt::Display for MessageMetadata {
+    //Formats the fields as a Markdown table so it can be pasted into documentation.
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        try!(writeln!(f,"| Field | Tag | Required | Versions |"));
+        try!(writeln!(f,"|-------|-----|----------|----------|"));
+        for field in &self.fields {
+            let required = if field.required {
+                "Yes"
+            }
+            else if field.conditionally_required {
+                "Conditional"
+            }
+            else {
+                "No"
+            };
+            let versions = if field.minimum_version == field.maximum_version {
+                format!("{:?}",field.minimum_version)
+            }
+            else if field.maximum_version == MessageVersion::FIX50SP2 {
+                format!("{:?}+",field.minimum_version)
+            }
+            else {
+                format!("{:?}-{:?}",field.minimum_version,field.maximum_version)
+            };
+            try!(writeln!(f,"| {} | {} | {} | {} |",field.name,field.tag,required,versions));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone,PartialEq)]
@@ -58,6 +136,18 @@ pub enum SetValueError {
     OutOfRange,
 }
 
+//Walks the populated fields of a message in the order they would be serialized. Values are
+//handed over in their serialized form. Repeating groups are reported through the group callbacks
+//with each entry's fields visited in between.
+pub trait MessageVisitor {
+    fn visit_field(&mut self,tag: FieldTag,name: &'static str,value: &[u8]);
+
+    fn begin_group(&mut self,_tag: FieldTag,_name: &'static str,_count: usize) {}
+    fn begin_group_entry(&mut self,_index: usize) {}
+    fn end_group_entry(&mut self,_index: usize) {}
+    fn end_group(&mut self,_tag: FieldTag,_name: &'static str) {}
+}
+
 pub trait Message {
     fn conditional_required_fields(&self,version: MessageVersion) -> Vec<FieldTag>;
     fn meta(&self) -> &Option<Meta>;
@@ -69,8 +159,21 @@ pub trait Message {
     fn new_into_box(&self) -> Box<Message + Send>;
     fn msg_type_header(&self) -> &'static [u8];
     fn read_body(&self,fix_version: FIXVersion,message_version: MessageVersion,buf: &mut Vec<u8>) -> usize;
+    fn visit(&self,fix_version: FIXVersion,message_version: MessageVersion,visitor: &mut MessageVisitor);
+
+    fn reset(&mut self) -> bool {
+        //Clear every field so the message can be filled in again, like by Parser when recycling
+        //messages. Memory already owned by the fields is kept where possible. Returns false if
+        //the message doesn't support being reset and must be rebuilt instead.
+        false
+    }
 
     fn read(&self,fix_version: FIXVersion,message_version: MessageVersion,buf: &mut ByteBuffer) -> usize {
+        //Serialization is deterministic: the same message, FIX version, and message version
+        //always produce byte-identical output. Fields are written in the order they are defined
+        //by the message, never the order they were set in, and repeating groups in the order
+        //they are stored. MessageTemplate and the message logs rely on this. visit() walks the
+        //fields in the same order so message_hash can too. See tests/serialization.rs.
         const HEADER_PADDING_LEN: usize = 32;
 
         //Leave rooom at beginning of buffer for header.
@@ -155,7 +258,7 @@ pub trait Message {
         buf.len()
     }
 
-    fn debug(&self,fix_version: FIXVersion,message_version: MessageVersion) -> String {
+    fn to_fix_string(&self,fix_version: FIXVersion,message_version: MessageVersion) -> String {
         let mut buffer = ByteBuffer::with_capacity(512);
         self.read(fix_version,message_version,&mut buffer);
 
@@ -164,23 +267,15 @@ pub trait Message {
 
         String::from_utf8_lossy(&buffer[..]).into_owned()
     }
+
+    fn debug(&self,fix_version: FIXVersion,message_version: MessageVersion) -> String {
+        self.to_fix_string(fix_version,message_version)
+    }
 }
 
 pub const REQUIRED: bool = true;
 pub const NOT_REQUIRED: bool = false;
 
-#[derive(Clone)]
-pub struct BuildMessageInternalCache {
-    pub fields_fix40: Option<FieldHashMap>,
-    pub fields_fix41: Option<FieldHashMap>,
-    pub fields_fix42: Option<FieldHashMap>,
-    pub fields_fix43: Option<FieldHashMap>,
-    pub fields_fix44: Option<FieldHashMap>,
-    pub fields_fix50: Option<FieldHashMap>,
-    pub fields_fix50sp1: Option<FieldHashMap>,
-    pub fields_fix50sp2: Option<FieldHashMap>,
-}
-
 #[doc(hidden)]
 #[macro_export]
 macro_rules! symbol_to_message_version {
@@ -215,6 +310,29 @@ macro_rules! match_message_version {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! message_version_range {
+    ( $minimum_version:tt ) => {
+        (symbol_to_message_version!($minimum_version),symbol_to_message_version!($minimum_version))
+    };
+
+    ( $minimum_version:tt .. ) => {
+        (symbol_to_message_version!($minimum_version),$crate::message_version::MessageVersion::FIX50SP2)
+    };
+
+    ( $minimum_version:tt .. $maximum_version:tt ) => {
+        (symbol_to_message_version!($minimum_version),symbol_to_message_version!($maximum_version))
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! has_required_when {
+    () => { false };
+    ( $required_when_expr:expr ) => { true };
+}
+
 #[macro_export]
 macro_rules! define_message {
     ( $message_name:ident $( : $message_type:expr => )* { $( $field_required:expr, $field_name:ident : $field_type:ty [$( $version:tt )*] $(=> REQUIRED_WHEN $required_when_expr:expr)* ),* $(),* } ) => {
@@ -304,6 +422,27 @@ macro_rules! define_message {
 
                 b""
             }
+
+            fn metadata() -> $crate::message::MessageMetadata {
+                $crate::message::MessageMetadata {
+                    name: stringify!($message_name),
+                    msg_type: <$message_name as $crate::message::MessageDetails>::msg_type(),
+                    fields: vec![ $( {
+                        let (minimum_version,maximum_version) = message_version_range!($( $version )*);
+                        $crate::message::FieldMetadata {
+                            name: stringify!($field_name),
+                            field_name: <$field_type as $crate::field::Field>::name(),
+                            field_type: <$field_type as $crate::field::Field>::type_name(),
+                            tag: <$field_type as $crate::field::Field>::tag(),
+                            required: $field_required,
+                            conditionally_required: has_required_when!($( $required_when_expr )*),
+                            minimum_version: minimum_version,
+                            maximum_version: maximum_version,
+                            group: <<$field_type as $crate::field::Field>::Type as $crate::field_type::FieldType>::group_metadata(),
+                        }
+                    } ),* ],
+                }
+            }
         }
 
         impl $crate::message::Message for $message_name {
@@ -328,6 +467,16 @@ macro_rules! define_message {
                 self.meta = Some(meta);
             }
 
+            fn reset(&mut self) -> bool {
+                use $crate::field::Field;
+                use $crate::field_type::FieldType;
+
+                self.meta = None;
+                $( <$field_type as Field>::Type::reset_value(&mut self.$field_name); )*
+
+                true
+            }
+
             fn set_value(&mut self,key: $crate::field_tag::FieldTag,value: &[u8]) -> Result<(),$crate::message::SetValueError> {
                 use $crate::field::Field;
                 use $crate::field_type::FieldType;
@@ -378,6 +527,12 @@ macro_rules! define_message {
 
                 byte_count
             }
+
+            fn visit(&self,fix_version: $crate::fix_version::FIXVersion,message_version: $crate::message_version::MessageVersion,visitor: &mut $crate::message::MessageVisitor) {
+                $( if match_message_version!(message_version,$( $version )*) {
+                    <$field_type as $crate::field::Field>::visit(&self.$field_name,fix_version,message_version,stringify!($field_name),visitor,$field_required);
+                } )*
+            }
         }
 
         impl PartialEq for $message_name {
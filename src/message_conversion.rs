@@ -0,0 +1,158 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::str::{self,FromStr};
+
+use byte_buffer::ByteBuffer;
+use dictionary::field_types::other::{ExecType,OrdStatus};
+use dictionary::fields::{ExecType as ExecTypeField,OrdStatus as OrdStatusField};
+use dictionary::messages::ExecutionReport;
+use field::Field;
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use fixt::message::FIXTMessage;
+use message::MessageVisitor;
+use message_version::MessageVersion;
+
+fn parse_f64(bytes: &[u8]) -> Option<f64> {
+    str::from_utf8(bytes).ok().and_then(|string| f64::from_str(string).ok())
+}
+
+//Collects every tag that would be serialized, including repeating group count tags.
+struct TagVisitor {
+    tags: Vec<FieldTag>,
+}
+
+impl MessageVisitor for TagVisitor {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,_value: &[u8]) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,_count: usize) {
+        if !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+}
+
+fn visible_tags(message: &FIXTMessage,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<FieldTag> {
+    let mut visitor = TagVisitor {
+        tags: Vec::new(),
+    };
+    message.visit(fix_version,message_version,&mut visitor);
+
+    visitor.tags
+}
+
+//Oldest transport that can carry a message of this version. FIX 5.0 and later can only be sent
+//over FIXT.1.1.
+pub fn fix_version_for_message_version(message_version: MessageVersion) -> FIXVersion {
+    match message_version {
+        MessageVersion::FIX40 => FIXVersion::FIX_4_0,
+        MessageVersion::FIX41 => FIXVersion::FIX_4_1,
+        MessageVersion::FIX42 => FIXVersion::FIX_4_2,
+        MessageVersion::FIX43 => FIXVersion::FIX_4_3,
+        MessageVersion::FIX44 => FIXVersion::FIX_4_4,
+        MessageVersion::FIX50 |
+        MessageVersion::FIX50SP1 |
+        MessageVersion::FIX50SP2 => FIXVersion::FIXT_1_1,
+    }
+}
+
+pub struct ConvertedMessage {
+    pub message: Box<FIXTMessage + Send>,
+    pub fix_version: FIXVersion,
+    pub message_version: MessageVersion,
+    pub dropped_fields: Vec<FieldTag>, //Fields set in the original message that don't exist in message_version.
+    pub mapped_fields: Vec<FieldTag>, //Fields whose value was changed to one that exists in message_version.
+}
+
+impl ConvertedMessage {
+    pub fn read(&self,buf: &mut ByteBuffer) -> usize {
+        self.message.read(self.fix_version,self.message_version,buf)
+    }
+}
+
+//Best-effort conversion of a parsed message so it can be forwarded to a counterparty that uses a
+//different FIX version, such as a gateway between a FIX50SP2 venue and a FIX 4.2 client. Every
+//message type supports all versions in the same struct so fields that don't exist in the target
+//version are simply left out when the converted message is serialized. They're listed in
+//dropped_fields so the caller can decide if losing them is acceptable.
+//
+//Enum values that were deprecated in favor of a different value are mapped to the closest
+//equivalent:
+//  ExecType PartialFill(1) and Fill(2) become Trade(F) in FIX 4.4 and later. Going the other way,
+//    Trade becomes Fill when LeavesQty is zero and PartialFill otherwise.
+//  OrdStatus Replaced(5) becomes New, PartiallyFilled, or Filled in FIX 4.3 and later depending on
+//    CumQty and LeavesQty.
+//Fields that don't exist in either version, such as ExecTransType(20), are not synthesized.
+pub fn convert_message(message: &FIXTMessage,from: MessageVersion,to: MessageVersion) -> ConvertedMessage {
+    let from_fix_version = fix_version_for_message_version(from);
+    let to_fix_version = fix_version_for_message_version(to);
+
+    let mut converted_message = message.clone_into_box();
+    let mut mapped_fields = Vec::new();
+    if let Some(execution_report) = converted_message.as_any_mut().downcast_mut::<ExecutionReport>() {
+        map_execution_report(execution_report,to,&mut mapped_fields);
+    }
+
+    let to_tags = visible_tags(&*converted_message,to_fix_version,to);
+    let dropped_fields = visible_tags(message,from_fix_version,from).into_iter()
+        .filter(|tag| !to_tags.contains(tag))
+        .collect();
+
+    ConvertedMessage {
+        message: converted_message,
+        fix_version: to_fix_version,
+        message_version: to,
+        dropped_fields: dropped_fields,
+        mapped_fields: mapped_fields,
+    }
+}
+
+fn map_execution_report(message: &mut ExecutionReport,to: MessageVersion,mapped_fields: &mut Vec<FieldTag>) {
+    let to_value = to.as_value();
+    let leaves_qty = parse_f64(&message.leaves_qty).unwrap_or(0.0);
+    let cum_qty = parse_f64(&message.cum_qty).unwrap_or(0.0);
+
+    let new_exec_type = match message.exec_type {
+        ExecType::PartialFill |
+        ExecType::Fill if to_value >= MessageVersion::FIX44.as_value() => Some(ExecType::Trade),
+        ExecType::Trade if to_value < MessageVersion::FIX43.as_value() => {
+            if leaves_qty == 0.0 {
+                Some(ExecType::Fill)
+            }
+            else {
+                Some(ExecType::PartialFill)
+            }
+        },
+        _ => None,
+    };
+    if let Some(exec_type) = new_exec_type {
+        message.exec_type = exec_type;
+        mapped_fields.push(ExecTypeField::tag());
+    }
+
+    if message.ord_status == OrdStatus::Replaced && to_value >= MessageVersion::FIX43.as_value() {
+        message.ord_status = if cum_qty == 0.0 {
+            OrdStatus::New
+        }
+        else if leaves_qty == 0.0 {
+            OrdStatus::Filled
+        }
+        else {
+            OrdStatus::PartiallyFilled
+        };
+        mapped_fields.push(OrdStatusField::tag());
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::fmt;
+
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use message::{Message,MessageVisitor};
+use message_version::MessageVersion;
+
+//Location of a repeating group entry: the tag of the group's count field and the entry's index.
+pub type GroupEntry = (FieldTag,usize);
+
+//A single field that is different between two messages. The value is None when the field is not
+//present in that message. Repeating group count fields are compared like any other field so
+//adding or removing a group entry shows up both as a count change and as the entry's fields.
+#[derive(Clone,Debug,PartialEq)]
+pub struct FieldDiff {
+    pub groups: Vec<GroupEntry>, //Outermost group first. Empty for fields that aren't in a group.
+    pub tag: FieldTag,
+    pub name: &'static str,
+    pub left: Option<Vec<u8>>,
+    pub right: Option<Vec<u8>>,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        fn value_to_string(value: &Option<Vec<u8>>) -> String {
+            match *value {
+                Some(ref value) => String::from_utf8_lossy(&value[..]).into_owned(),
+                None => String::from("<missing>"),
+            }
+        }
+
+        for &(ref tag,ref index) in &self.groups {
+            try!(write!(f,"{}[{}].",tag,index));
+        }
+        if self.name.is_empty() {
+            write!(f,"{}: {} != {}",self.tag,value_to_string(&self.left),value_to_string(&self.right))
+        }
+        else {
+            write!(f,"{}({}): {} != {}",self.name,self.tag,value_to_string(&self.left),value_to_string(&self.right))
+        }
+    }
+}
+
+struct FlattenedField {
+    groups: Vec<GroupEntry>,
+    tag: FieldTag,
+    name: &'static str,
+    value: Vec<u8>,
+}
+
+//Flattens a message into a list of fields, each remembering which group entries it's nested in.
+struct FlattenVisitor {
+    fields: Vec<FlattenedField>,
+    groups: Vec<GroupEntry>,
+    current_group_tags: Vec<FieldTag>,
+}
+
+impl FlattenVisitor {
+    fn flatten<T: Message + ?Sized>(message: &T,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<FlattenedField> {
+        let mut visitor = FlattenVisitor {
+            fields: Vec::new(),
+            groups: Vec::new(),
+            current_group_tags: Vec::new(),
+        };
+        message.visit(fix_version,message_version,&mut visitor);
+
+        visitor.fields
+    }
+
+    fn push_field(&mut self,tag: FieldTag,name: &'static str,value: &[u8]) {
+        self.fields.push(FlattenedField {
+            groups: self.groups.clone(),
+            tag: tag,
+            name: name,
+            value: value.to_vec(),
+        });
+    }
+}
+
+impl MessageVisitor for FlattenVisitor {
+    fn visit_field(&mut self,tag: FieldTag,name: &'static str,value: &[u8]) {
+        self.push_field(tag,name,value);
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,name: &'static str,count: usize) {
+        self.push_field(tag,name,count.to_string().as_bytes());
+        self.current_group_tags.push(tag);
+    }
+
+    fn begin_group_entry(&mut self,index: usize) {
+        let tag = *self.current_group_tags.last().unwrap();
+        self.groups.push((tag,index));
+    }
+
+    fn end_group_entry(&mut self,_index: usize) {
+        self.groups.pop();
+    }
+
+    fn end_group(&mut self,_tag: FieldTag,_name: &'static str) {
+        self.current_group_tags.pop();
+    }
+}
+
+//Compares two messages field by field using the newest message version so every field either
+//message could contain is included. The messages don't need to be the same type.
+pub fn diff<A: Message + ?Sized,B: Message + ?Sized>(left: &A,right: &B) -> Vec<FieldDiff> {
+    diff_with_version(left,right,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)
+}
+
+//Same as diff() but only compares the fields that would be serialized for the given versions.
+pub fn diff_with_version<A: Message + ?Sized,B: Message + ?Sized>(left: &A,right: &B,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<FieldDiff> {
+    let left_fields = FlattenVisitor::flatten(left,fix_version,message_version);
+    let mut right_fields: Vec<Option<FlattenedField>> = FlattenVisitor::flatten(right,fix_version,message_version).into_iter().map(|field| Some(field)).collect();
+    let mut result = Vec::new();
+
+    //Walk the left message in order so the differences come out in serialization order. Anything
+    //left over on the right side afterwards is missing from the left message.
+    for left_field in left_fields {
+        let right_field = right_fields.iter_mut()
+            .find(|right_field| {
+                if let Some(ref right_field) = **right_field {
+                    right_field.tag == left_field.tag && right_field.groups == left_field.groups
+                }
+                else {
+                    false
+                }
+            })
+            .and_then(|right_field| right_field.take());
+
+        match right_field {
+            Some(right_field) => {
+                if right_field.value != left_field.value {
+                    result.push(FieldDiff {
+                        groups: left_field.groups,
+                        tag: left_field.tag,
+                        name: left_field.name,
+                        left: Some(left_field.value),
+                        right: Some(right_field.value),
+                    });
+                }
+            },
+            None => {
+                result.push(FieldDiff {
+                    groups: left_field.groups,
+                    tag: left_field.tag,
+                    name: left_field.name,
+                    left: Some(left_field.value),
+                    right: None,
+                });
+            },
+        }
+    }
+
+    for right_field in right_fields.into_iter().filter_map(|right_field| right_field) {
+        result.push(FieldDiff {
+            groups: right_field.groups,
+            tag: right_field.tag,
+            name: right_field.name,
+            left: None,
+            right: Some(right_field.value),
+        });
+    }
+
+    result
+}
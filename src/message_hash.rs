@@ -0,0 +1,156 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use constant::{TAG_END,VALUE_END};
+use dictionary::fields::{MsgSeqNum,OrigSendingTime,PossDupFlag,PossResend,SendingTime};
+use field::Field;
+use field_tag::FieldTag;
+use fix_version::FIXVersion;
+use message::{Message,MessageVisitor};
+use message_version::MessageVersion;
+
+//64-bit FNV-1a. The std hashers are not guaranteed to give the same result between Rust releases
+//so they can't be used for hashes that are stored or compared across processes.
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+struct FNVHasher {
+    value: u64,
+}
+
+impl FNVHasher {
+    fn new() -> FNVHasher {
+        FNVHasher {
+            value: FNV_OFFSET_BASIS,
+        }
+    }
+
+    fn write(&mut self,bytes: &[u8]) {
+        for byte in bytes {
+            self.value ^= *byte as u64;
+            self.value = self.value.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_field(&mut self,tag: FieldTag,value: &[u8]) {
+        //Hash fields as they'd appear on the wire so the boundary between tag and value, and
+        //between fields, can't be shifted around to produce the same hash.
+        self.write(&tag.to_bytes()[..]);
+        self.write(&[TAG_END]);
+        self.write(value);
+        self.write(&[VALUE_END]);
+    }
+}
+
+struct HashVisitor<'a> {
+    excluded_tags: &'a [FieldTag],
+    hasher: FNVHasher,
+    excluded_group_depth: usize, //Non-zero while inside a repeating group that is excluded.
+}
+
+impl<'a> HashVisitor<'a> {
+    fn is_excluded(&self,tag: FieldTag) -> bool {
+        self.excluded_group_depth > 0 || self.excluded_tags.contains(&tag)
+    }
+}
+
+impl<'a> MessageVisitor for HashVisitor<'a> {
+    fn visit_field(&mut self,tag: FieldTag,_name: &'static str,value: &[u8]) {
+        if !self.is_excluded(tag) {
+            self.hasher.write_field(tag,value);
+        }
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,_name: &'static str,count: usize) {
+        if self.is_excluded(tag) {
+            self.excluded_group_depth += 1;
+        }
+        else {
+            self.hasher.write_field(tag,count.to_string().as_bytes());
+        }
+    }
+
+    fn end_group(&mut self,_tag: FieldTag,_name: &'static str) {
+        if self.excluded_group_depth > 0 {
+            self.excluded_group_depth -= 1;
+        }
+    }
+}
+
+//Computes a content hash of a message that is stable across processes, sessions, and Rust
+//releases. Fields that change every time the same content is sent (sequence numbers, send times,
+//and resend markers) are excluded by default so a resent message hashes the same as the original.
+//Also exclude SenderCompID and TargetCompID when comparing messages seen on different sessions.
+//BeginString, BodyLength, and CheckSum are never part of the hash. Excluding a repeating group's
+//count tag excludes the entire group.
+#[derive(Clone)]
+pub struct MessageHasher {
+    excluded_tags: Vec<FieldTag>,
+    fix_version: FIXVersion,
+    message_version: MessageVersion,
+}
+
+impl MessageHasher {
+    pub fn new() -> MessageHasher {
+        MessageHasher {
+            excluded_tags: vec![MsgSeqNum::tag(),SendingTime::tag(),OrigSendingTime::tag(),PossDupFlag::tag(),PossResend::tag()],
+            fix_version: FIXVersion::FIXT_1_1,
+            message_version: MessageVersion::FIX50SP2,
+        }
+    }
+
+    pub fn exclude_tag(mut self,tag: FieldTag) -> MessageHasher {
+        if !self.excluded_tags.contains(&tag) {
+            self.excluded_tags.push(tag);
+        }
+        self
+    }
+
+    pub fn include_tag(mut self,tag: FieldTag) -> MessageHasher {
+        self.excluded_tags.retain(|excluded_tag| *excluded_tag != tag);
+        self
+    }
+
+    pub fn is_excluded(&self,tag: FieldTag) -> bool {
+        self.excluded_tags.contains(&tag)
+    }
+
+    pub fn version(mut self,fix_version: FIXVersion,message_version: MessageVersion) -> MessageHasher {
+        //Only the fields that would be serialized for these versions are hashed. Defaults to the
+        //newest versions so every field is included.
+        self.fix_version = fix_version;
+        self.message_version = message_version;
+        self
+    }
+
+    pub fn hash<T: Message + ?Sized>(&self,message: &T) -> u64 {
+        let mut visitor = HashVisitor {
+            excluded_tags: &self.excluded_tags[..],
+            hasher: FNVHasher::new(),
+            excluded_group_depth: 0,
+        };
+        visitor.hasher.write(message.msg_type_header());
+        message.visit(self.fix_version,self.message_version,&mut visitor);
+
+        visitor.hasher.value
+    }
+}
+
+impl Default for MessageHasher {
+    fn default() -> Self {
+        MessageHasher::new()
+    }
+}
+
+//Hashes a message using the default volatile field exclusions.
+pub fn hash<T: Message + ?Sized>(message: &T) -> u64 {
+    MessageHasher::new().hash(message)
+}
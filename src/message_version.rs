@@ -9,7 +9,7 @@
 // at your option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#[derive(Clone,Copy,Debug,PartialEq)]
+#[derive(Clone,Copy,Debug,Eq,Hash,PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum MessageVersion { //Based on the ApplVerId(1128) field.
     //Unsupported FIX27,
@@ -0,0 +1,583 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::TimeZone;
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self,Read};
+use std::net::{IpAddr,Ipv4Addr,Ipv6Addr,SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+
+use dictionary::administrative_msg_types;
+use dictionary::messages::Logon;
+use fix::{Parser,ParseError};
+use fix_version::FIXVersion;
+use fixt::message::{BuildFIXTMessage,FIXTMessage};
+use message_version::MessageVersion;
+
+const PCAP_HEADER_LEN: usize = 24;
+const PCAP_RECORD_HEADER_LEN: usize = 16;
+const PCAP_MAGIC_MICROSECONDS: u32 = 0xA1B2C3D4;
+const PCAP_MAGIC_NANOSECONDS: u32 = 0xA1B23C4D;
+
+const PCAPNG_BLOCK_OVERHEAD_LEN: usize = 12; //Block type, block length, and the trailing block length.
+const PCAPNG_SECTION_HEADER_BLOCK: u32 = 0x0A0D0D0A;
+const PCAPNG_INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const PCAPNG_ENHANCED_PACKET_BLOCK: u32 = 6;
+const PCAPNG_ENHANCED_PACKET_HEADER_LEN: usize = 20;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+const PCAPNG_OPTION_END: u16 = 0;
+const PCAPNG_OPTION_IF_TSRESOL: u16 = 9;
+const PCAPNG_OPTION_IF_TSOFFSET: u16 = 14;
+
+const LINKTYPE_NULL: u32 = 0;
+const LINKTYPE_ETHERNET: u32 = 1;
+const LINKTYPE_RAW: u32 = 101;
+const LINKTYPE_LINUX_SLL: u32 = 113;
+const LINKTYPE_LINUX_SLL2: u32 = 276;
+
+const ETHERTYPE_IPV4: u16 = 0x0800;
+const ETHERTYPE_IPV6: u16 = 0x86DD;
+const ETHERTYPE_VLAN: u16 = 0x8100;
+const ETHERTYPE_QINQ: u16 = 0x88A8;
+
+const IP_PROTOCOL_TCP: u8 = 6;
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_FLAG_RST: u8 = 0x04;
+
+const NANOSECONDS_PER_SECOND: u64 = 1_000_000_000;
+
+//Segments received past a gap are held until the gap is filled. If it never is, usually because
+//the capture dropped packets, the stream skips ahead once this many segments are waiting.
+const MAX_OUT_OF_ORDER_SEGMENTS: usize = 1024;
+
+pub enum PcapError {
+    Io(io::Error),
+    UnknownFormat, //Not a pcap or pcapng file.
+    InvalidBlock, //pcapng block with an impossible length or that refers to a missing interface.
+}
+
+impl From<io::Error> for PcapError {
+    fn from(e: io::Error) -> PcapError {
+        PcapError::Io(e)
+    }
+}
+
+impl fmt::Display for PcapError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PcapError::Io(ref e) => write!(f,"PcapError::Io({})",e),
+            PcapError::UnknownFormat => write!(f,"PcapError::UnknownFormat"),
+            PcapError::InvalidBlock => write!(f,"PcapError::InvalidBlock"),
+        }
+    }
+}
+
+impl fmt::Debug for PcapError {
+    fn fmt(&self,f: &mut fmt::Formatter) -> fmt::Result {
+        <PcapError as fmt::Display>::fmt(self,f)
+    }
+}
+
+pub struct CapturedMessage {
+    pub timestamp: DateTime<UTC>, //When the packet that completed the message was captured.
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub message: Result<Box<FIXTMessage + Send>,ParseError>, //Parse errors are kept in order with the messages around them.
+}
+
+#[derive(Clone,Copy)]
+struct Interface {
+    link_type: u32,
+    units_per_second: u64,
+    offset_seconds: i64,
+}
+
+impl Interface {
+    fn timestamp(&self,units: u64) -> DateTime<UTC> {
+        let seconds = (units / self.units_per_second) as i64 + self.offset_seconds;
+        let remainder = units % self.units_per_second;
+        let nanoseconds = if self.units_per_second <= NANOSECONDS_PER_SECOND {
+            remainder * NANOSECONDS_PER_SECOND / self.units_per_second
+        }
+        else {
+            remainder / (self.units_per_second / NANOSECONDS_PER_SECOND)
+        };
+
+        UTC.timestamp(seconds,nanoseconds as u32)
+    }
+}
+
+struct TcpStreamState {
+    next_seq: Option<u32>, //None until the first segment when the capture started mid-connection.
+    out_of_order: Vec<(u32,Vec<u8>)>,
+    parser: Parser,
+}
+
+impl TcpStreamState {
+    fn receive(&mut self,seq: u32,payload: &[u8],bytes: &mut Vec<u8>) {
+        //Appends every byte that is now in order to bytes. Retransmitted and overlapping bytes are
+        //only delivered once.
+        if self.next_seq.is_none() {
+            self.next_seq = Some(seq);
+        }
+        if !payload.is_empty() {
+            self.out_of_order.push((seq,payload.to_vec()));
+        }
+
+        loop {
+            let next_seq = self.next_seq.unwrap();
+            let index = self.out_of_order.iter().position(|&(seq,_)| (seq.wrapping_sub(next_seq) as i32) <= 0);
+            if let Some(index) = index {
+                let (seq,segment) = self.out_of_order.swap_remove(index);
+                let overlap_len = next_seq.wrapping_sub(seq) as usize;
+                if overlap_len < segment.len() {
+                    bytes.extend_from_slice(&segment[overlap_len..]);
+                    self.next_seq = Some(seq.wrapping_add(segment.len() as u32));
+                }
+            }
+            else if self.out_of_order.len() > MAX_OUT_OF_ORDER_SEGMENTS {
+                //Skip the gap. Whatever message was in progress is lost so the parser must start
+                //over too.
+                let earliest_seq = self.out_of_order.iter().map(|&(seq,_)| seq).min_by_key(|seq| seq.wrapping_sub(next_seq)).unwrap();
+                self.next_seq = Some(earliest_seq);
+                self.parser.reset_parser();
+            }
+            else {
+                break;
+            }
+        }
+    }
+}
+
+//Decodes FIX messages from a network capture in pcap or pcapng format. TCP streams are reassembled
+//per direction and fed through a Parser so every message comes out with the time it was captured
+//and where it was going. Ethernet (including VLAN tags), Linux cooked, loopback, and raw IP
+//captures over IPv4 or IPv6 are supported. Fragmented IPv4 packets and IPv6 extension headers are
+//skipped.
+//
+//Stream state is kept between calls so captures that were split into several files can be decoded
+//one after another. Requires the "pcap" feature.
+pub struct PcapDecoder {
+    message_dictionary: Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>,
+    max_message_size: u64,
+    default_message_version: MessageVersion,
+    ports: Vec<u16>,
+    streams: HashMap<(SocketAddr,SocketAddr),TcpStreamState>,
+}
+
+impl PcapDecoder {
+    pub fn new(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>,max_message_size: u64) -> PcapDecoder {
+        PcapDecoder {
+            message_dictionary: Arc::new(message_dictionary),
+            max_message_size: max_message_size,
+            default_message_version: FIXVersion::FIXT_1_1.max_message_version(),
+            ports: Vec::new(),
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn default_message_version(mut self,default_message_version: MessageVersion) -> PcapDecoder {
+        //Used for FIXT messages without an ApplVerID until a Logon with a DefaultApplVerID is seen
+        //on the same connection.
+        self.default_message_version = default_message_version;
+        self
+    }
+
+    pub fn port(mut self,port: u16) -> PcapDecoder {
+        //Only decode TCP streams to or from port. Can be called more than once. Every stream is
+        //decoded by default.
+        self.ports.push(port);
+        self
+    }
+
+    pub fn decode_file<P: AsRef<Path>>(&mut self,path: P) -> Result<Vec<CapturedMessage>,PcapError> {
+        let mut file = try!(File::open(path));
+        self.decode(&mut file)
+    }
+
+    pub fn decode<R: Read>(&mut self,reader: &mut R) -> Result<Vec<CapturedMessage>,PcapError> {
+        let mut bytes = Vec::new();
+        try!(reader.read_to_end(&mut bytes));
+
+        let mut messages = Vec::new();
+        if bytes.len() >= 4 && read_u32(&bytes,0,false) == PCAPNG_SECTION_HEADER_BLOCK {
+            try!(self.decode_pcapng(&bytes,&mut messages));
+        }
+        else {
+            try!(self.decode_pcap(&bytes,&mut messages));
+        }
+
+        Ok(messages)
+    }
+
+    fn decode_pcap(&mut self,bytes: &[u8],messages: &mut Vec<CapturedMessage>) -> Result<(),PcapError> {
+        if bytes.len() < PCAP_HEADER_LEN {
+            return Err(PcapError::UnknownFormat);
+        }
+
+        let (big_endian,nanosecond_resolution) = match (read_u32(bytes,0,false),read_u32(bytes,0,true)) {
+            (PCAP_MAGIC_MICROSECONDS,_) => (false,false),
+            (PCAP_MAGIC_NANOSECONDS,_) => (false,true),
+            (_,PCAP_MAGIC_MICROSECONDS) => (true,false),
+            (_,PCAP_MAGIC_NANOSECONDS) => (true,true),
+            _ => return Err(PcapError::UnknownFormat),
+        };
+        let link_type = read_u32(bytes,20,big_endian) & 0xFFFF; //Upper bits can hold FCS details.
+
+        //A capture that was stopped abruptly often ends part way through a packet. Everything
+        //before it is still decoded.
+        let mut offset = PCAP_HEADER_LEN;
+        while offset + PCAP_RECORD_HEADER_LEN <= bytes.len() {
+            let seconds = read_u32(bytes,offset,big_endian) as i64;
+            let fraction = read_u32(bytes,offset + 4,big_endian) as u64;
+            let captured_len = read_u32(bytes,offset + 8,big_endian) as usize;
+            offset += PCAP_RECORD_HEADER_LEN;
+            if offset + captured_len > bytes.len() {
+                break;
+            }
+
+            let nanoseconds = if nanosecond_resolution { fraction } else { fraction * 1000 };
+            let timestamp = UTC.timestamp(seconds,cmp::min(nanoseconds,NANOSECONDS_PER_SECOND - 1) as u32);
+            self.decode_packet(link_type,timestamp,&bytes[offset..offset + captured_len],messages);
+            offset += captured_len;
+        }
+
+        Ok(())
+    }
+
+    fn decode_pcapng(&mut self,bytes: &[u8],messages: &mut Vec<CapturedMessage>) -> Result<(),PcapError> {
+        let mut big_endian = false;
+        let mut interfaces: Vec<Interface> = Vec::new();
+
+        let mut offset = 0;
+        while offset + PCAPNG_BLOCK_OVERHEAD_LEN <= bytes.len() {
+            //The section header's block type reads the same in either byte order. Each section
+            //sets its own byte order and starts over with its own interfaces.
+            let block_type = read_u32(bytes,offset,big_endian);
+            if block_type == PCAPNG_SECTION_HEADER_BLOCK {
+                big_endian = if read_u32(bytes,offset + 8,false) == PCAPNG_BYTE_ORDER_MAGIC {
+                    false
+                }
+                else if read_u32(bytes,offset + 8,true) == PCAPNG_BYTE_ORDER_MAGIC {
+                    true
+                }
+                else {
+                    return Err(PcapError::UnknownFormat);
+                };
+                interfaces.clear();
+            }
+
+            let block_len = read_u32(bytes,offset + 4,big_endian) as usize;
+            if block_len < PCAPNG_BLOCK_OVERHEAD_LEN || block_len % 4 != 0 {
+                return Err(PcapError::InvalidBlock);
+            }
+            if offset + block_len > bytes.len() {
+                break;
+            }
+
+            let body = &bytes[offset + 8..offset + block_len - 4];
+            match block_type {
+                PCAPNG_INTERFACE_DESCRIPTION_BLOCK => {
+                    if body.len() < 8 {
+                        return Err(PcapError::InvalidBlock);
+                    }
+                    interfaces.push(parse_interface(body,big_endian));
+                },
+                PCAPNG_ENHANCED_PACKET_BLOCK => {
+                    if body.len() < PCAPNG_ENHANCED_PACKET_HEADER_LEN {
+                        return Err(PcapError::InvalidBlock);
+                    }
+
+                    let interface = match interfaces.get(read_u32(body,0,big_endian) as usize) {
+                        Some(interface) => *interface,
+                        None => return Err(PcapError::InvalidBlock),
+                    };
+                    let units = ((read_u32(body,4,big_endian) as u64) << 32) | (read_u32(body,8,big_endian) as u64);
+                    let captured_len = read_u32(body,12,big_endian) as usize;
+                    if PCAPNG_ENHANCED_PACKET_HEADER_LEN + captured_len > body.len() {
+                        return Err(PcapError::InvalidBlock);
+                    }
+
+                    let packet = &body[PCAPNG_ENHANCED_PACKET_HEADER_LEN..PCAPNG_ENHANCED_PACKET_HEADER_LEN + captured_len];
+                    self.decode_packet(interface.link_type,interface.timestamp(units),packet,messages);
+                },
+                _ => {}, //Simple packet blocks have no timestamp and the rest don't hold packets.
+            }
+
+            offset += block_len;
+        }
+
+        Ok(())
+    }
+
+    fn decode_packet(&mut self,link_type: u32,timestamp: DateTime<UTC>,packet: &[u8],messages: &mut Vec<CapturedMessage>) {
+        //Anything that isn't TCP over IP is quietly skipped.
+        let ip_packet = match link_layer_payload(link_type,packet) {
+            Some(ip_packet) => ip_packet,
+            None => return,
+        };
+        let (source_ip,destination_ip,segment) = match ip_payload(ip_packet) {
+            Some(result) => result,
+            None => return,
+        };
+        if segment.len() < 20 {
+            return;
+        }
+
+        let source_port = read_u16(segment,0,true);
+        let destination_port = read_u16(segment,2,true);
+        if !self.ports.is_empty() && !self.ports.contains(&source_port) && !self.ports.contains(&destination_port) {
+            return;
+        }
+
+        let seq = read_u32(segment,4,true);
+        let header_len = ((segment[12] >> 4) as usize) * 4;
+        if header_len < 20 || header_len > segment.len() {
+            return;
+        }
+        let flags = segment[13];
+
+        let source = SocketAddr::new(source_ip,source_port);
+        let destination = SocketAddr::new(destination_ip,destination_port);
+        self.decode_segment(source,destination,seq,flags,&segment[header_len..],timestamp,messages);
+    }
+
+    fn decode_segment(&mut self,source: SocketAddr,destination: SocketAddr,seq: u32,flags: u8,payload: &[u8],timestamp: DateTime<UTC>,messages: &mut Vec<CapturedMessage>) {
+        let key = (source,destination);
+
+        //A new connection between the same addresses starts over with a fresh parser.
+        if flags & (TCP_FLAG_SYN | TCP_FLAG_RST) != 0 {
+            self.streams.remove(&key);
+            if flags & TCP_FLAG_RST != 0 {
+                return;
+            }
+        }
+        if !self.streams.contains_key(&key) {
+            let parser = self.new_parser();
+            self.streams.insert(key,TcpStreamState {
+                next_seq: None,
+                out_of_order: Vec::new(),
+                parser: parser,
+            });
+        }
+
+        let mut logon_message_version = None;
+        {
+            let stream = self.streams.get_mut(&key).unwrap();
+            if flags & TCP_FLAG_SYN != 0 {
+                //SYN uses up one sequence number.
+                stream.next_seq = Some(seq.wrapping_add(1));
+                return;
+            }
+
+            let mut bytes = Vec::new();
+            stream.receive(seq,payload,&mut bytes);
+
+            let mut remaining_bytes = &bytes[..];
+            while !remaining_bytes.is_empty() {
+                let (bytes_parsed,result) = stream.parser.parse(remaining_bytes);
+                assert!(bytes_parsed > 0);
+                remaining_bytes = &remaining_bytes[bytes_parsed..];
+
+                for message in stream.parser.messages.drain(..) {
+                    if let Some(logon) = message.as_any().downcast_ref::<Logon>() {
+                        logon_message_version = Some(logon.default_appl_ver_id);
+                    }
+
+                    messages.push(CapturedMessage {
+                        timestamp: timestamp,
+                        source: source,
+                        destination: destination,
+                        message: Ok(message),
+                    });
+                }
+                if let Err(e) = result {
+                    messages.push(CapturedMessage {
+                        timestamp: timestamp,
+                        source: source,
+                        destination: destination,
+                        message: Err(e),
+                    });
+                }
+            }
+        }
+
+        //DefaultApplVerID from either side's Logon applies to the whole session.
+        if let Some(message_version) = logon_message_version {
+            for key in &[(source,destination),(destination,source)] {
+                if let Some(stream) = self.streams.get_mut(key) {
+                    stream.parser.set_default_message_version(message_version);
+                }
+            }
+        }
+    }
+
+    fn new_parser(&self) -> Parser {
+        let mut parser = Parser::with_shared_dictionary(self.message_dictionary.clone(),self.max_message_size);
+        parser.set_default_message_version(self.default_message_version);
+        for msg_type in administrative_msg_types() {
+            parser.set_default_message_type_version(msg_type,FIXVersion::FIXT_1_1.max_message_version());
+        }
+
+        parser
+    }
+}
+
+fn parse_interface(body: &[u8],big_endian: bool) -> Interface {
+    let mut interface = Interface {
+        link_type: read_u16(body,0,big_endian) as u32,
+        units_per_second: 1_000_000, //Microseconds unless if_tsresol says otherwise.
+        offset_seconds: 0,
+    };
+
+    //Options follow the link type, a reserved field, and the snap length. Each is padded to a
+    //multiple of 4 bytes.
+    let mut offset = 8;
+    while offset + 4 <= body.len() {
+        let code = read_u16(body,offset,big_endian);
+        let len = read_u16(body,offset + 2,big_endian) as usize;
+        offset += 4;
+        if code == PCAPNG_OPTION_END || offset + len > body.len() {
+            break;
+        }
+
+        match code {
+            PCAPNG_OPTION_IF_TSRESOL if len >= 1 => {
+                //High bit picks between a negative power of 2 and a negative power of 10.
+                let resolution = body[offset];
+                interface.units_per_second = if resolution & 0x80 == 0 {
+                    10u64.pow(cmp::min(resolution as u32,19))
+                }
+                else {
+                    1u64 << cmp::min(resolution & 0x7F,63)
+                };
+            },
+            PCAPNG_OPTION_IF_TSOFFSET if len >= 8 => {
+                interface.offset_seconds = read_u64(body,offset,big_endian) as i64;
+            },
+            _ => {},
+        }
+
+        offset += (len + 3) & !3;
+    }
+
+    interface
+}
+
+fn link_layer_payload(link_type: u32,packet: &[u8]) -> Option<&[u8]> {
+    fn ether_type_payload(ether_type: u16,payload: &[u8]) -> Option<&[u8]> {
+        if ether_type == ETHERTYPE_IPV4 || ether_type == ETHERTYPE_IPV6 {
+            Some(payload)
+        }
+        else {
+            None
+        }
+    }
+
+    match link_type {
+        LINKTYPE_ETHERNET => {
+            if packet.len() < 14 {
+                return None;
+            }
+
+            //Skip over any VLAN tags.
+            let mut ether_type = read_u16(packet,12,true);
+            let mut offset = 14;
+            while ether_type == ETHERTYPE_VLAN || ether_type == ETHERTYPE_QINQ {
+                if packet.len() < offset + 4 {
+                    return None;
+                }
+                ether_type = read_u16(packet,offset + 2,true);
+                offset += 4;
+            }
+
+            ether_type_payload(ether_type,&packet[offset..])
+        },
+        LINKTYPE_LINUX_SLL if packet.len() >= 16 => ether_type_payload(read_u16(packet,14,true),&packet[16..]),
+        LINKTYPE_LINUX_SLL2 if packet.len() >= 20 => ether_type_payload(read_u16(packet,0,true),&packet[20..]),
+        LINKTYPE_NULL if packet.len() >= 4 => Some(&packet[4..]), //Address family is in host byte order so let ip_payload() sort it out.
+        LINKTYPE_RAW => Some(packet),
+        _ => None,
+    }
+}
+
+fn ip_payload(packet: &[u8]) -> Option<(IpAddr,IpAddr,&[u8])> {
+    if packet.is_empty() {
+        return None;
+    }
+
+    match packet[0] >> 4 {
+        4 => {
+            if packet.len() < 20 {
+                return None;
+            }
+
+            //Total length trims off any Ethernet padding. Packets cut short by the snap length and
+            //fragments can't be used.
+            let header_len = ((packet[0] & 0x0F) as usize) * 4;
+            let total_len = read_u16(packet,2,true) as usize;
+            if header_len < 20 || total_len < header_len || total_len > packet.len() {
+                return None;
+            }
+            if read_u16(packet,6,true) & 0x3FFF != 0 || packet[9] != IP_PROTOCOL_TCP {
+                return None;
+            }
+
+            let source = Ipv4Addr::new(packet[12],packet[13],packet[14],packet[15]);
+            let destination = Ipv4Addr::new(packet[16],packet[17],packet[18],packet[19]);
+            Some((IpAddr::V4(source),IpAddr::V4(destination),&packet[header_len..total_len]))
+        },
+        6 => {
+            if packet.len() < 40 {
+                return None;
+            }
+
+            let total_len = 40 + read_u16(packet,4,true) as usize;
+            if total_len > packet.len() || packet[6] != IP_PROTOCOL_TCP {
+                return None;
+            }
+
+            Some((IpAddr::V6(read_ipv6_addr(&packet[8..24])),IpAddr::V6(read_ipv6_addr(&packet[24..40])),&packet[40..total_len]))
+        },
+        _ => None,
+    }
+}
+
+fn read_ipv6_addr(bytes: &[u8]) -> Ipv6Addr {
+    Ipv6Addr::new(read_u16(bytes,0,true),read_u16(bytes,2,true),read_u16(bytes,4,true),read_u16(bytes,6,true),
+                  read_u16(bytes,8,true),read_u16(bytes,10,true),read_u16(bytes,12,true),read_u16(bytes,14,true))
+}
+
+fn read_u16(bytes: &[u8],offset: usize,big_endian: bool) -> u16 {
+    let (high,low) = if big_endian { (bytes[offset],bytes[offset + 1]) } else { (bytes[offset + 1],bytes[offset]) };
+
+    ((high as u16) << 8) | (low as u16)
+}
+
+fn read_u32(bytes: &[u8],offset: usize,big_endian: bool) -> u32 {
+    let high = read_u16(bytes,if big_endian { offset } else { offset + 2 },big_endian) as u32;
+    let low = read_u16(bytes,if big_endian { offset + 2 } else { offset },big_endian) as u32;
+
+    (high << 16) | low
+}
+
+fn read_u64(bytes: &[u8],offset: usize,big_endian: bool) -> u64 {
+    let high = read_u32(bytes,if big_endian { offset } else { offset + 4 },big_endian) as u64;
+    let low = read_u32(bytes,if big_endian { offset + 4 } else { offset },big_endian) as u64;
+
+    (high << 32) | low
+}
@@ -0,0 +1,140 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use mio::{Evented,Poll,PollOpt,Ready,Registration,SetReadiness,Token};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration,Instant};
+
+use clock::Clock;
+
+#[derive(Clone,Copy,Debug,Eq,Ord,PartialEq,PartialOrd)]
+pub struct Timeout {
+    deadline: Instant,
+    id: u64, //Keeps timeouts with the same deadline apart and in the order they were set.
+}
+
+//Timeouts measured with a Clock instead of always following real time. Expired timeouts are taken
+//out with poll(). When the clock follows real time, the owner is expected to wait at most
+//next_timeout() for other events. Otherwise, the timer becomes readable whenever the clock is
+//advanced.
+pub struct Timer<T> {
+    clock: Arc<Clock>,
+    timeouts: BTreeMap<Timeout,T>,
+    next_id: u64,
+    registration: RefCell<Option<Registration>>,
+    set_readiness: RefCell<Option<SetReadiness>>,
+}
+
+impl<T> Timer<T> {
+    pub fn new(clock: Arc<Clock>) -> Timer<T> {
+        Timer {
+            clock: clock,
+            timeouts: BTreeMap::new(),
+            next_id: 0,
+            registration: RefCell::new(None),
+            set_readiness: RefCell::new(None),
+        }
+    }
+
+    pub fn clock(&self) -> &Arc<Clock> {
+        &self.clock
+    }
+
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    pub fn set_timeout(&mut self,delay: Duration,state: T) -> Timeout {
+        let timeout = Timeout {
+            deadline: self.clock.now() + delay,
+            id: self.next_id,
+        };
+        self.next_id += 1;
+        self.timeouts.insert(timeout,state);
+
+        timeout
+    }
+
+    pub fn cancel_timeout(&mut self,timeout: &Timeout) -> Option<T> {
+        self.timeouts.remove(timeout)
+    }
+
+    pub fn poll(&mut self) -> Option<T> {
+        //Returns the state of the earliest expired timeout, if any.
+        let now = self.clock.now();
+        let timeout = match self.timeouts.keys().next() {
+            Some(timeout) if timeout.deadline <= now => *timeout,
+            _ => {
+                if let Some(ref set_readiness) = *self.set_readiness.borrow() {
+                    let _ = set_readiness.set_readiness(Ready::empty());
+                }
+                return None;
+            },
+        };
+
+        self.timeouts.remove(&timeout)
+    }
+
+    pub fn next_timeout(&self) -> Option<Duration> {
+        //How long until the earliest timeout expires. None when nothing is waiting or when only
+        //advancing the clock can make something expire.
+        if !self.clock.follows_real_time() {
+            return None;
+        }
+
+        self.timeouts.keys().next().map(|timeout| {
+            let now = self.clock.now();
+            if timeout.deadline > now { timeout.deadline - now } else { Duration::from_secs(0) }
+        })
+    }
+}
+
+impl<T> Evented for Timer<T> {
+    fn register(&self,poll: &Poll,token: Token,interest: Ready,opts: PollOpt) -> io::Result<()> {
+        if self.registration.borrow().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other,"Timer already registered"));
+        }
+
+        let (registration,set_readiness) = Registration::new2();
+        try!(registration.register(poll,token,interest,opts));
+
+        //Wake up whoever is polling when the clock jumps forward.
+        let clock_set_readiness = set_readiness.clone();
+        self.clock.on_advance(Box::new(move || {
+            let _ = clock_set_readiness.set_readiness(Ready::readable());
+        }));
+
+        *self.registration.borrow_mut() = Some(registration);
+        *self.set_readiness.borrow_mut() = Some(set_readiness);
+
+        Ok(())
+    }
+
+    fn reregister(&self,poll: &Poll,token: Token,interest: Ready,opts: PollOpt) -> io::Result<()> {
+        if let Some(ref mut registration) = *self.registration.borrow_mut() {
+            return poll.reregister(registration,token,interest,opts);
+        }
+
+        Err(io::Error::new(io::ErrorKind::Other,"Timer not registered"))
+    }
+
+    fn deregister(&self,poll: &Poll) -> io::Result<()> {
+        if let Some(ref mut registration) = *self.registration.borrow_mut() {
+            return poll.deregister(registration);
+        }
+
+        Err(io::Error::new(io::ErrorKind::Other,"Timer not registered"))
+    }
+}
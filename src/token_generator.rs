@@ -10,7 +10,7 @@
 // except according to those terms.
 
 use mio::Token;
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 pub struct TokenGenerator {
     seed: usize,
@@ -19,7 +19,10 @@ pub struct TokenGenerator {
     //Let user set some arbitrary token limit for resource reasons. For example, only so many ports
     //may be reserved for use.
     max_tokens: usize,
-    active_tokens: HashSet<Token>,
+    //Each created token is tagged with a generation that's never reused so a stale copy of a
+    //token can be told apart from a new one that happens to have the same number.
+    active_tokens: HashMap<Token,u64>,
+    next_generation: u64,
 }
 
 impl TokenGenerator {
@@ -28,7 +31,8 @@ impl TokenGenerator {
             seed: reserved_end,
             reserved_end: reserved_end,
             max_tokens: max_tokens.unwrap_or(usize::max_value() - reserved_end),
-            active_tokens: HashSet::new(),
+            active_tokens: HashMap::new(),
+            next_generation: 1,
         }
     }
 
@@ -42,13 +46,18 @@ impl TokenGenerator {
             self.seed = self.seed.overflowing_add(1).0;
 
             let token = Token(token_id);
-            if !self.active_tokens.contains(&token) && token_id >= self.reserved_end {
-                self.active_tokens.insert(token);
+            if !self.active_tokens.contains_key(&token) && token_id >= self.reserved_end {
+                self.active_tokens.insert(token,self.next_generation);
+                self.next_generation += 1;
                 return Some(token);
             }
         }
     }
 
+    pub fn generation(&self,token: Token) -> Option<u64> {
+        self.active_tokens.get(&token).cloned()
+    }
+
     pub fn remove(&mut self,token: Token) {
         self.active_tokens.remove(&token);
     }
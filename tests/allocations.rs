@@ -0,0 +1,165 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::collections::HashMap;
+
+#[macro_use]
+mod common;
+use common::MAX_MESSAGE_SIZE;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::dictionary::field_types::other::{AffirmStatus,AllocStatus,ConfirmStatus,ConfirmTransType,ConfirmType,SettlInstMode,SettlInstTransType,Side};
+use fix_rs::dictionary::fields::{AllocAckGrp,SettlInstGrp};
+use fix_rs::dictionary::messages::{AllocationInstructionAck,Confirmation,ConfirmationAck,SettlementInstructions};
+use fix_rs::fix::Parser;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::allocations::AllocationTracker;
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn allocation_instruction_ack(alloc_status: AllocStatus) -> AllocationInstructionAck {
+    let mut message = new_fixt_message!(AllocationInstructionAck);
+    message.alloc_id = b"ALLOC1".to_vec();
+    message.transact_time = message.sending_time;
+    message.alloc_status = alloc_status;
+
+    message
+}
+
+fn confirmation(confirm_id: &[u8],confirm_trans_type: ConfirmTransType,confirm_ref_id: &[u8],alloc_account: &[u8]) -> Confirmation {
+    let mut message = new_fixt_message!(Confirmation);
+    message.confirm_id = confirm_id.to_vec();
+    message.confirm_ref_id = confirm_ref_id.to_vec();
+    message.confirm_trans_type = confirm_trans_type;
+    message.confirm_type = ConfirmType::Confirmation;
+    message.confirm_status = ConfirmStatus::Confirmed;
+    message.alloc_id = b"ALLOC1".to_vec();
+    message.transact_time = message.sending_time;
+    message.symbol = b"TEST".to_vec();
+    message.alloc_qty = b"100".to_vec();
+    message.side = Side::Buy;
+    message.alloc_account = alloc_account.to_vec();
+    message.avg_px = b"10".to_vec();
+    message.gross_trade_amt = b"1000".to_vec();
+    message.net_money = b"1001".to_vec();
+
+    message
+}
+
+fn confirmation_ack(confirm_id: &[u8],affirm_status: AffirmStatus) -> ConfirmationAck {
+    let mut message = new_fixt_message!(ConfirmationAck);
+    message.confirm_id = confirm_id.to_vec();
+    message.transact_time = message.sending_time;
+    message.affirm_status = affirm_status;
+
+    message
+}
+
+#[test]
+fn test_allocation_lifecycle() {
+    let mut tracker = AllocationTracker::new();
+
+    //Allocation is received and then accepted with account level details.
+    assert_eq!(tracker.on_message(&allocation_instruction_ack(AllocStatus::Received)).unwrap().alloc_status,Some(AllocStatus::Received));
+    {
+        let mut message = allocation_instruction_ack(AllocStatus::Accepted);
+        for alloc_account in &[&b"ACCOUNT1"[..],&b"ACCOUNT2"[..]] {
+            let mut group = AllocAckGrp::new();
+            group.alloc_account = alloc_account.to_vec();
+            message.no_allocs.push(Box::new(group));
+        }
+        let allocation = tracker.on_message(&message).unwrap();
+        assert!(allocation.is_accepted());
+        assert!(!allocation.is_complete());
+        assert_eq!(allocation.accounts.len(),2);
+        assert_eq!(allocation.accounts[1].alloc_account,b"ACCOUNT2");
+    }
+
+    //Each account is confirmed separately.
+    tracker.on_message(&confirmation(b"C1",ConfirmTransType::New,b"",b"ACCOUNT1"));
+    tracker.on_message(&confirmation(b"C2",ConfirmTransType::New,b"",b"ACCOUNT2"));
+    {
+        let allocation = tracker.on_message(&confirmation_ack(b"C1",AffirmStatus::Affirmed)).unwrap();
+        assert!(allocation.get_confirmation(b"C1").unwrap().is_affirmed());
+        assert!(!allocation.is_complete());
+    }
+
+    //A rejected confirmation is replaced and the replacement is affirmed.
+    {
+        let allocation = tracker.on_message(&confirmation_ack(b"C2",AffirmStatus::ConfirmRejected)).unwrap();
+        assert_eq!(allocation.get_confirmation(b"C2").unwrap().affirm_status,Some(AffirmStatus::ConfirmRejected));
+    }
+    {
+        let mut message = confirmation(b"C3",ConfirmTransType::Replace,b"C2",b"ACCOUNT2");
+        message.alloc_id.clear();
+        let allocation = tracker.on_message(&message).unwrap();
+        assert!(allocation.get_confirmation(b"C2").unwrap().canceled);
+        assert_eq!(allocation.confirmations.len(),3);
+    }
+    {
+        let allocation = tracker.on_message(&confirmation_ack(b"C3",AffirmStatus::Affirmed)).unwrap();
+        assert!(allocation.is_complete());
+    }
+    assert_eq!(tracker.get_by_confirm_id(b"C3").unwrap().alloc_id,b"ALLOC1");
+
+    //Unknown ConfirmIDs are ignored.
+    assert!(tracker.on_message(&confirmation_ack(b"C4",AffirmStatus::Affirmed)).is_none());
+
+    assert!(tracker.remove(b"ALLOC1").is_some());
+    assert!(tracker.get_by_confirm_id(b"C1").is_none());
+}
+
+#[test]
+fn test_allocation_rejected() {
+    let mut tracker = AllocationTracker::new();
+    let allocation = tracker.on_message(&allocation_instruction_ack(AllocStatus::BlockLevelReject)).unwrap();
+    assert!(allocation.is_rejected());
+    assert!(!allocation.is_complete());
+}
+
+#[test]
+fn test_settlement_instructions() {
+    let mut message = new_fixt_message!(SettlementInstructions);
+    message.settl_inst_msg_id = b"SI1".to_vec();
+    message.settl_inst_mode = SettlInstMode::StandingInstructionsProvided;
+    message.transact_time = message.sending_time;
+    let mut group = SettlInstGrp::new();
+    group.settl_inst_id = b"INST1".to_vec();
+    group.settl_inst_trans_type = Some(SettlInstTransType::New);
+    group.stand_inst_db_name = b"DB".to_vec();
+    message.no_settl_inst.push(Box::new(group));
+
+    let mut bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(SettlementInstructions::msg_type(),SettlementInstructions::new().builder());
+    let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+    let (_,result) = parser.parse(bytes.bytes());
+    assert!(result.is_ok());
+    let parsed_message = parser.messages[0].as_any().downcast_ref::<SettlementInstructions>().unwrap();
+    assert_eq!(parsed_message.settl_inst_mode,SettlInstMode::StandingInstructionsProvided);
+    assert_eq!(parsed_message.no_settl_inst.len(),1);
+    assert_eq!(parsed_message.no_settl_inst[0].settl_inst_id,b"INST1");
+    assert_eq!(parsed_message.no_settl_inst[0].settl_inst_trans_type,Some(SettlInstTransType::New));
+    assert_eq!(parsed_message.no_settl_inst[0].stand_inst_db_name,b"DB");
+}
@@ -0,0 +1,138 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::TestStream;
+use fix_rs::dictionary::messages::{Heartbeat,Logon,Logout,ResendRequest,SequenceReset,TestRequest};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::{EngineEvent,ConnectionTerminatedReason};
+use fix_rs::fixt::message::FIXTMessage;
+
+define_dictionary!(
+    Heartbeat,
+    Logon,
+    Logout,
+    ResendRequest,
+    SequenceReset,
+    TestRequest,
+);
+
+const CLASSIC_FIX_VERSIONS: &'static [FIXVersion] = &[FIXVersion::FIX_4_2,FIXVersion::FIX_4_4];
+
+#[test]
+fn test_classic_initiator_session() {
+    //Run a whole session, including recovering from a sequence gap, against a classic FIX.4.x
+    //counterparty. TestStream makes sure everything the Engine sends uses the session's
+    //BeginString without any FIXT header fields.
+    for fix_version in CLASSIC_FIX_VERSIONS {
+        //Connect and Logon.
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon_classic(*fix_version,build_dictionary());
+
+        //Make sure TestRequest is answered.
+        let mut message = new_fixt_message!(TestRequest);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"1".to_vec();
+        test_server.send_message(message);
+
+        let message = engine_poll_message!(client,connection,TestRequest);
+        assert_eq!(message.meta.as_ref().unwrap().begin_string,*fix_version);
+        let message = test_server.recv_message::<Heartbeat>();
+        assert_eq!(message.msg_seq_num,2);
+        assert_eq!(message.test_req_id,b"1".to_vec());
+
+        //Begin Logout.
+        client.logout(connection);
+        let message = test_server.recv_message::<Logout>();
+        assert_eq!(message.msg_seq_num,3);
+
+        //Respond with Logout containing high MsgSeqNum.
+        let mut message = new_fixt_message!(Logout);
+        message.msg_seq_num = 15;
+        test_server.send_message(message);
+
+        //Make sure client requests missing messages.
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.msg_seq_num,4);
+        assert_eq!(message.begin_seq_no,3);
+        assert!(message.end_seq_no == 0 || message.end_seq_no == 15);
+
+        //Tell client about missing messages.
+        let mut message = new_fixt_message!(SequenceReset);
+        message.gap_fill_flag = true;
+        message.msg_seq_num = 3;
+        message.new_seq_no = 16;
+        test_server.send_message(message);
+        let _ = engine_poll_message!(client,connection,SequenceReset);
+
+        //Make sure client attempts to logout again after being caught up.
+        let message = test_server.recv_message::<Logout>();
+        assert_eq!(message.msg_seq_num,5);
+
+        //Finish logging out cleanly.
+        let mut message = new_fixt_message!(Logout);
+        message.msg_seq_num = 16;
+        test_server.send_message(message);
+
+        engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+            assert_eq!(terminated_connection,connection);
+            assert!(if let ConnectionTerminatedReason::LocalRequested = reason { true } else { false });
+        });
+    }
+}
+
+#[test]
+fn test_classic_acceptor_session() {
+    //Same as test_classic_initiator_session() except the classic counterparty is the initiator.
+    for fix_version in CLASSIC_FIX_VERSIONS {
+        //Connect and Logon.
+        let (mut test_client,mut engine,_,connection) = TestStream::setup_test_client_and_logon_classic(*fix_version,build_dictionary());
+
+        //Make sure TestRequest is answered.
+        let mut message = new_fixt_message!(FROM_CLIENT TestRequest);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"1".to_vec();
+        test_client.send_message(message);
+
+        let message = engine_poll_message!(engine,connection,TestRequest);
+        assert_eq!(message.meta.as_ref().unwrap().begin_string,*fix_version);
+        let message = test_client.recv_message::<Heartbeat>();
+        assert_eq!(message.msg_seq_num,2);
+        assert_eq!(message.test_req_id,b"1".to_vec());
+
+        //Logout cleanly.
+        engine.logout(connection);
+        let message = test_client.recv_message::<Logout>();
+        assert_eq!(message.msg_seq_num,3);
+
+        let mut message = new_fixt_message!(FROM_CLIENT Logout);
+        message.msg_seq_num = 3;
+        test_client.send_message(message);
+
+        engine_poll_event!(engine,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+            assert_eq!(terminated_connection,connection);
+            assert!(if let ConnectionTerminatedReason::LocalRequested = reason { true } else { false });
+        });
+    }
+}
@@ -12,6 +12,7 @@
 #![feature(attr_literals)]
 #![feature(const_fn)]
 
+extern crate chrono;
 #[macro_use]
 extern crate fix_rs;
 #[macro_use]
@@ -19,8 +20,11 @@ extern crate fix_rs_macros;
 extern crate mio;
 extern crate phf;
 
-use mio::tcp::Shutdown;
-use std::io::Write;
+use mio::tcp::{Shutdown,TcpListener};
+use std::any::Any;
+use std::env;
+use std::fs::{self,File};
+use std::io::{self,Read,Write};
 use std::thread;
 use std::time::{Duration,Instant};
 use std::sync::{Arc,Mutex};
@@ -28,19 +32,31 @@ use std::sync::atomic::{AtomicBool,Ordering};
 
 #[macro_use]
 mod common;
-use common::{SERVER_SENDER_COMP_ID,SERVER_TARGET_COMP_ID,TestStream,new_logon_message};
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,MAX_MESSAGE_SIZE,SERVER_SENDER_COMP_ID,SERVER_TARGET_COMP_ID,TestStream,accept_with_timeout,new_logon_message,new_test_address,recv_bytes_with_timeout};
 use fix_rs::byte_buffer::ByteBuffer;
-use fix_rs::dictionary::field_types::other::{MsgDirection,SessionRejectReason};
-use fix_rs::dictionary::fields::{MsgTypeGrp,SenderCompID,TargetCompID,Text};
-use fix_rs::dictionary::messages::{Heartbeat,Logon,Logout,Reject,ResendRequest,SequenceReset,TestRequest};
+use fix_rs::dictionary::field_types::generic::TimestampPrecision;
+use fix_rs::dictionary::field_types::other::{ExecType,MsgDirection,OrdStatus,OrdType,SecurityIDSource,SessionRejectReason,SessionStatus,Side};
+use fix_rs::dictionary::fields::{ClOrdID,MsgTypeGrp,SenderCompID,TargetCompID,Text};
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,Logon,Logout,NewOrderSingle,Reject,ResendRequest,SequenceReset,TestRequest};
 use fix_rs::field::Field;
 use fix_rs::field_tag::{self,FieldTag};
 use fix_rs::fix::ParseError;
 use fix_rs::fix_version::FIXVersion;
 use fix_rs::fixt;
-use fix_rs::fixt::engine::{EngineEvent,ConnectionTerminatedReason,ResendResponse};
+use fix_rs::fixt::decompression::InboundDecoder;
+use fix_rs::fixt::duplicate_order_guard::{DuplicateOrderAction,DuplicateOrderGuard};
+use fix_rs::fixt::failover::SessionCoordinator;
+use fix_rs::fixt::engine::{AdminMessageDelivery,Connection,ConnectionBuilder,EngineBuilder,EngineEvent,ConnectionTerminatedReason,LogoutResponsePolicy,MessageReceivedDuplicateSkippedData,OrderEntryStatus,OutboxPolicy,ProbeSettings,ResendRequestLimits,ResendRequestRange,ResendResponse,SessionMigration,SessionStatusReceivedData,TestRequestResponse,TestRequestSettings};
 use fix_rs::fixt::tests::{AUTO_DISCONNECT_AFTER_INBOUND_RESEND_REQUEST_LOOP_COUNT,INBOUND_MESSAGES_BUFFER_LEN_MAX,INBOUND_BYTES_BUFFER_CAPACITY};
 use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::message_log::{MessageLogFormat,MessageLogSettings};
+use fix_rs::fixt::message_signer::MessageSigner;
+use fix_rs::fixt::middleware::{InboundMiddleware,MiddlewareAction};
+use fix_rs::fixt::outbound_validation::{OutboundFieldErrorReason,OutboundValidation};
+use fix_rs::fixt::rate_anomaly::{RateAnomalyKind,RateAnomalySettings};
+use fix_rs::fixt::session_id::SessionID;
+use fix_rs::fixt::symbol_mapper::StaticSymbolMapper;
+use fix_rs::fixt::third_party_routing::{ThirdPartyRouting,ThirdPartyRoutingSettings};
 use fix_rs::message::{self,NOT_REQUIRED,REQUIRED,Message};
 use fix_rs::message_version::{self,MessageVersion};
 
@@ -167,8 +183,8 @@ fn test_recv_logout_with_high_msg_seq_num_and_no_reply() {
     assert_eq!(message.begin_seq_no,2);
     assert!(message.end_seq_no == 0 || message.end_seq_no == 14);
 
-    //Wait around without replying to ResendRequest.
-    thread::sleep(Duration::from_millis(10500));
+    //Advance time without replying to ResendRequest.
+    test_server.advance_clock(Duration::from_millis(10500));
 
     //Make sure client responds with Logout even though it didn't get caught up.
     let message = test_server.recv_message::<Logout>();
@@ -217,14 +233,17 @@ fn test_recv_logout_send_logout_recv_resend_request() {
     //Handle the resend request.
     engine_gap_fill_resend_request!(client,connection,2..3);
     let _ = engine_poll_message!(client,connection,ResendRequest);
+    engine_poll_event!(client,EngineEvent::ResendCompleted(_,summary) => {
+        assert_eq!(summary.gap_fills_sent,1);
+    });
 
     //Make sure ResendRequest is responded to.
     let message = test_server.recv_message::<SequenceReset>();
     assert_eq!(message.msg_seq_num,2);
     assert_eq!(message.new_seq_no,3);
 
-    //Wait around to make sure the server requested logout was cancelled.
-    thread::sleep(Duration::from_millis(5500));
+    //Advance time to make sure the server requested logout was cancelled.
+    test_server.advance_clock(Duration::from_millis(5500));
     let _ = test_server.recv_message::<Heartbeat>();
     let _ = test_server.recv_message::<TestRequest>();
 
@@ -256,9 +275,9 @@ fn test_send_logout_and_recv_resend_request() {
     //Connect and Logon.
     let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-    //Wait around for a Heartbeat and TestRequest. Ignore these so we can send a valid
+    //Advance time until a Heartbeat and TestRequest are sent. Ignore these so we can send a valid
     //ResendRequest below.
-    thread::sleep(Duration::from_millis(5500));
+    test_server.advance_clock(Duration::from_millis(5500));
     let _ = test_server.recv_message::<Heartbeat>();
     let _ = test_server.recv_message::<TestRequest>();
 
@@ -275,6 +294,9 @@ fn test_send_logout_and_recv_resend_request() {
 
     engine_gap_fill_resend_request!(client,connection,2..5);
     let _ = engine_poll_message!(client,connection,ResendRequest);
+    engine_poll_event!(client,EngineEvent::ResendCompleted(_,summary) => {
+        assert_eq!(summary.gap_fills_sent,1);
+    });
 
     //Make sure client still responds to ResendRequest while logging out.
     let message = test_server.recv_message::<SequenceReset>();
@@ -372,8 +394,8 @@ fn test_send_logout_and_recv_logout_with_high_msg_seq_num_and_no_reply() {
     assert_eq!(message.begin_seq_no,2);
     assert!(message.end_seq_no == 0 || message.end_seq_no == 15);
 
-    //Wait around without replying to ResendRequest.
-    thread::sleep(Duration::from_millis(10500));
+    //Advance time without replying to ResendRequest.
+    test_server.advance_clock(Duration::from_millis(10500));
 
     //Make sure client disconnects instead of retrying the logout process. If the other end sends a
     //logout with an expected MsgSeqNum, then we saw a later MsgSeqNum once already and something
@@ -1297,6 +1319,10 @@ fn test_block_read_when_write_blocks() {
 
                 outbound_msg_seq_num += 1;
             }
+            else {
+                //Engine stopped reading because it can't write. Advance time until it gives up.
+                test_server.advance_clock(Duration::from_secs(1));
+            }
         }
     }
 }
@@ -1337,6 +1363,9 @@ fn test_inbound_resend_loop_detection() {
 
         engine_gap_fill_resend_request!(client,connection,2..3);
         let _ = engine_poll_message!(client,connection,ResendRequest);
+        engine_poll_event!(client,EngineEvent::ResendCompleted(_,summary) => {
+            assert_eq!(summary.gap_fills_sent,1);
+        });
 
         let message = test_server.recv_message::<SequenceReset>();
         assert_eq!(message.gap_fill_flag,true);
@@ -1359,3 +1388,2401 @@ fn test_inbound_resend_loop_detection() {
     });
     assert!(test_server.is_stream_closed(Duration::from_secs(3)));
 }
+
+#[test]
+fn test_pause_and_resume_sending() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+        TestRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Pause sending and then try to send an application message. It should be held.
+    client.pause_sending(connection);
+
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"0".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.security_id = b"0".to_vec();
+    new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.send_message(connection,new_order_single);
+
+    //Administrative messages should still go out while paused.
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 2;
+    message.test_req_id = b"test".to_vec();
+    test_server.send_message(message);
+    engine_poll_message!(client,connection,TestRequest);
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.test_req_id,b"test");
+    assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+    //Resume sending and make sure the held message goes out with the next MsgSeqNum.
+    client.resume_sending(connection);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.cl_ord_id,b"0");
+}
+
+#[test]
+fn test_message_time_to_live() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        TestMessage,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Hold messages long enough for one of them to expire.
+    client.pause_sending(connection);
+    let mut message = new_fixt_message!(TestMessage);
+    message.text = b"expires".to_vec();
+    client.send_message_with_time_to_live(connection,message,Duration::from_millis(10));
+    let mut message = new_fixt_message!(TestMessage);
+    message.text = b"lives".to_vec();
+    client.send_message_with_time_to_live(connection,message,Duration::from_secs(60));
+    let mut message = new_fixt_message!(TestMessage);
+    message.text = b"forever".to_vec();
+    client.send_message(connection,message);
+    test_server.advance_clock(Duration::from_millis(50));
+
+    //Expired message is reported and never sent. It doesn't use up a MsgSeqNum either.
+    client.resume_sending(connection);
+    engine_poll_event!(client,EngineEvent::MessagesExpired(expired_connection,messages) => {
+        assert_eq!(expired_connection,connection);
+        assert_eq!(messages.len(),1);
+        let message = messages[0].as_any().downcast_ref::<TestMessage>().expect("Not expected message type").clone();
+        assert_eq!(message.text,b"expires");
+    });
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.text,b"lives");
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.text,b"forever");
+}
+
+#[test]
+fn test_outbox_policy() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        TestMessage,
+    );
+
+    fn logon(test_server: &mut TestStream,client: &mut fixt::engine::Engine,connection: Connection) {
+        client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+        let message = test_server.recv_message::<Logon>();
+        let mut response_message = new_fixt_message!(Logon);
+        response_message.encrypt_method = message.encrypt_method;
+        response_message.heart_bt_int = message.heart_bt_int;
+        response_message.default_appl_ver_id = message.default_appl_ver_id;
+        test_server.send_message(response_message);
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    }
+
+    fn queue_message_and_disconnect(test_server: TestStream,client: &mut fixt::engine::Engine,connection: Connection,text: &[u8]) {
+        //Hold the message so it's still queued when the connection is lost. Querying the
+        //connection makes sure the message reached the engine's thread before disconnecting.
+        client.pause_sending(connection);
+        let mut message = new_fixt_message!(TestMessage);
+        message.text = text.to_vec();
+        client.send_message(connection,message);
+        assert!(client.connection_info(connection).is_some());
+        let _ = test_server.stream.shutdown(Shutdown::Both);
+    }
+
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .outbox_policy(OutboxPolicy::Resend);
+
+    //Connect, logon, and then lose the connection with a message still queued.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder.clone(),build_dictionary());
+    let clock = test_server.clock.clone();
+    logon(&mut test_server,&mut client,connection);
+    let _ = engine_poll_message!(client,connection,Logon);
+    queue_message_and_disconnect(test_server,&mut client,connection,b"resent");
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+
+    //OutboxPolicy::Resend sends the message automatically once the next connection logs on.
+    let (mut test_server,connection) = TestStream::setup_test_server_with_engine(&mut client,clock.clone(),connection_builder.clone(),build_dictionary());
+    logon(&mut test_server,&mut client,connection);
+    let _ = engine_poll_message!(client,connection,Logon);
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.text,b"resent");
+
+    //OutboxPolicy::Hold waits for the application to decide once the next connection logs on.
+    client.set_outbox_policy(connection,OutboxPolicy::Hold);
+    queue_message_and_disconnect(test_server,&mut client,connection,b"held");
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+
+    let (mut test_server,connection) = TestStream::setup_test_server_with_engine(&mut client,clock.clone(),connection_builder.clone().outbox_policy(OutboxPolicy::Hold),build_dictionary());
+    logon(&mut test_server,&mut client,connection);
+    engine_poll_event!(client,EngineEvent::MessagesHeld(held_connection,count) => {
+        assert_eq!(held_connection,connection);
+        assert_eq!(count,1);
+    });
+    let _ = engine_poll_message!(client,connection,Logon);
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(500)).is_none());
+
+    client.release_held_messages(connection);
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.text,b"held");
+
+    //OutboxPolicy::Discard returns the message immediately.
+    client.set_outbox_policy(connection,OutboxPolicy::Discard);
+    queue_message_and_disconnect(test_server,&mut client,connection,b"discarded");
+    engine_poll_event!(client,EngineEvent::MessagesNotSent(not_sent_connection,messages) => {
+        assert_eq!(not_sent_connection,connection);
+        assert_eq!(messages.len(),1);
+        let message = messages[0].as_any().downcast_ref::<TestMessage>().expect("Not expected message type").clone();
+        assert_eq!(message.text,b"discarded");
+    });
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+}
+
+#[test]
+fn test_warm_up() {
+    define_dictionary!(
+        Logon,
+        NewOrderSingle,
+    );
+
+    //Connect and warm up before logging on.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server(build_dictionary());
+
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"0".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.warm_up(connection,None,Box::new(new_order_single));
+
+    //Make sure nothing was sent and MsgSeqNum was not consumed.
+    assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+    client.send_message(connection,new_logon_message());
+    let message = test_server.recv_message::<Logon>();
+    assert_eq!(message.msg_seq_num,1);
+}
+
+#[test]
+fn test_sequence_gap_simulation() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        ResendRequest,
+        SequenceReset,
+        TestRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Replay the Logon's MsgSeqNum as a duplicate. Client should report it as such instead of
+    //treating it as a MsgSeqNum that's too low.
+    test_server.send_poss_dup_message(1,new_fixt_message!(TestRequest));
+    engine_poll_event!(client,EngineEvent::MessageReceivedDuplicate(duplicate_connection,message) => {
+        assert_eq!(duplicate_connection,connection);
+        assert!(message.is_poss_dup());
+        assert_eq!(message.msg_seq_num(),1);
+    });
+
+    //Skip MsgSeqNum 2 through 4 so client asks for them to be resent.
+    test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+    let message = test_server.recv_message::<ResendRequest>();
+    assert_eq!(message.begin_seq_no,2);
+    assert_eq!(message.end_seq_no,0);
+
+    //Fill the gap and then reset the sequence to something much higher.
+    test_server.send_sequence_reset_gap_fill(2,6);
+    test_server.send_sequence_reset_reset(20);
+
+    //Client should only accept messages following the reset.
+    let mut message = new_fixt_message!(TestRequest);
+    message.test_req_id = b"1".to_vec();
+    test_server.send_message_with_msg_seq_num(20,message);
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"1");
+}
+
+#[test]
+fn test_sending_time_fault_injection() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        Reject,
+        TestRequest,
+    );
+
+    //Stale SendingTime. The Engine does not compare SendingTime against the local clock so the
+    //message should be processed as normal.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+        let mut message = new_fixt_message!(TestRequest);
+        message.test_req_id = b"1".to_vec();
+        test_server.send_message_with_sending_time_offset(2,message,chrono::Duration::hours(-1));
+
+        let message = engine_poll_message!(client,connection,TestRequest);
+        assert_eq!(message.msg_seq_num,2);
+        let message = test_server.recv_message::<Heartbeat>();
+        assert_eq!(message.test_req_id,b"1");
+    }
+
+    //Future SendingTime is treated the same way.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+        let mut message = new_fixt_message!(TestRequest);
+        message.test_req_id = b"1".to_vec();
+        test_server.send_message_with_sending_time_offset(2,message,chrono::Duration::hours(1));
+
+        let message = engine_poll_message!(client,connection,TestRequest);
+        assert_eq!(message.msg_seq_num,2);
+        let message = test_server.recv_message::<Heartbeat>();
+        assert_eq!(message.test_req_id,b"1");
+    }
+
+    //PossDup with OrigSendingTime after SendingTime must be rejected.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+        test_server.send_poss_dup_message_with_future_orig_sending_time(2,new_fixt_message!(TestRequest),chrono::Duration::seconds(1));
+
+        let message = test_server.recv_message::<Reject>();
+        assert_eq!(message.ref_seq_num,2);
+        assert_eq!(message.session_reject_reason.unwrap(),SessionRejectReason::SendingTimeAccuracyProblem);
+        engine_poll_event!(client,EngineEvent::MessageRejected(msg_connection,_) => {
+            assert_eq!(msg_connection,connection);
+        });
+    }
+
+    //PossDup without OrigSendingTime must be rejected because the tag is conditionally required.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+        test_server.send_poss_dup_message_without_orig_sending_time(2,new_fixt_message!(TestRequest));
+
+        let message = test_server.recv_message::<Reject>();
+        assert_eq!(message.ref_seq_num,2);
+        assert_eq!(message.session_reject_reason.unwrap(),SessionRejectReason::RequiredTagMissing);
+        engine_poll_event!(client,EngineEvent::MessageReceivedGarbled(msg_connection,_) => {
+            assert_eq!(msg_connection,connection);
+        });
+    }
+}
+
+#[test]
+fn test_half_open_probes() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    let probe_settings = ProbeSettings {
+        quiet_period: Duration::from_millis(1000),
+        probe_interval: Duration::from_millis(500),
+        max_unanswered_probes: 2,
+    };
+
+    //Connection should be terminated after the probes go unanswered. This must happen well before
+    //the normal HeartBtInt based TestRequest would be sent.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+        client.set_probe_settings(connection,probe_settings);
+
+        test_server.advance_clock(probe_settings.quiet_period);
+        let _ = test_server.recv_message::<TestRequest>();
+        test_server.advance_clock(probe_settings.probe_interval);
+        let _ = test_server.recv_message::<TestRequest>();
+        test_server.advance_clock(probe_settings.probe_interval);
+        engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+            assert_eq!(terminated_connection,connection);
+            assert!(if let ConnectionTerminatedReason::ProbeNotRespondedError = reason { true } else { false });
+        });
+        assert!(test_server.is_stream_closed(Duration::from_secs(5)));
+    }
+
+    //Responding to each probe should keep the connection alive.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+        client.set_probe_settings(connection,probe_settings);
+
+        for msg_seq_num in 2..5 {
+            test_server.advance_clock(probe_settings.quiet_period);
+            let message = test_server.recv_message::<TestRequest>();
+
+            let mut response_message = new_fixt_message!(Heartbeat);
+            response_message.msg_seq_num = msg_seq_num;
+            response_message.test_req_id = message.test_req_id;
+            test_server.send_message(response_message);
+        }
+        assert!(!test_server.is_stream_closed(Duration::from_millis(100)));
+
+        //Disabling probing should stop probes from being sent.
+        client.set_probe_settings(connection,None);
+        test_server.advance_clock(Duration::from_millis(2000));
+        assert!(test_server.try_recv_fixt_message(Duration::from_millis(500)).is_none());
+    }
+}
+
+#[test]
+fn test_inbound_quiet_period() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    let quiet_period = Duration::from_millis(1000);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_inbound_quiet_period(connection,quiet_period);
+    let _ = client.connection_info(connection); //Wait until the engine's thread has the change.
+
+    //Silence is reported well before the HeartBtInt based TestRequest would be sent.
+    test_server.advance_clock(quiet_period);
+    engine_poll_event!(client,EngineEvent::InboundQuietPeriodExceeded(quiet_connection,duration) => {
+        assert_eq!(quiet_connection,connection);
+        assert_eq!(duration,quiet_period);
+    });
+
+    //Continued silence isn't reported again.
+    test_server.advance_clock(quiet_period);
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(100)).is_none());
+
+    //Receiving anything starts the watchdog over.
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 2;
+    test_server.send_message(heartbeat);
+    test_server.advance_clock(quiet_period);
+    engine_poll_event!(client,EngineEvent::InboundQuietPeriodExceeded(quiet_connection,_) => {
+        assert_eq!(quiet_connection,connection);
+    });
+
+    //Disabling the watchdog stops the warnings.
+    client.set_inbound_quiet_period(connection,None);
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 3;
+    test_server.send_message(heartbeat);
+    test_server.advance_clock(quiet_period);
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+}
+
+#[test]
+fn test_inbound_rate_anomaly() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    let window = Duration::from_secs(1);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_inbound_rate_anomaly(connection,RateAnomalySettings {
+        window: window,
+        baseline_windows: 2,
+        storm_factor: 3.0,
+        drought_factor: 0.5,
+        min_count: 3,
+    });
+    let _ = client.connection_info(connection); //Wait until the engine's thread has the change.
+
+    //Heartbeats received in each window. The first two only build the baseline.
+    let mut msg_seq_num = 2;
+    for &(heartbeat_count,expected_anomaly) in &[(4,None),
+                                                 (4,None),
+                                                 (20,Some((RateAnomalyKind::Storm,4.0))),
+                                                 (10,Some((RateAnomalyKind::Normal,44.0 / 3.0))),
+                                                 (10,None),
+                                                 (0,Some((RateAnomalyKind::Drought,284.0 / 27.0)))] {
+        for _ in 0..heartbeat_count {
+            test_server.send_message_with_msg_seq_num(msg_seq_num,new_fixt_message!(Heartbeat));
+            let _ = engine_poll_message!(client,connection,Heartbeat);
+            msg_seq_num += 1;
+        }
+
+        test_server.advance_clock(window);
+        if let Some((expected_kind,expected_baseline)) = expected_anomaly {
+            engine_poll_event!(client,EngineEvent::InboundRateAnomaly(anomaly_connection,anomaly) => {
+                assert_eq!(anomaly_connection,connection);
+                assert_eq!(anomaly.msg_type,b"0");
+                assert_eq!(anomaly.kind,expected_kind);
+                assert_eq!(anomaly.count,heartbeat_count);
+                assert!((anomaly.baseline - expected_baseline).abs() < 0.01);
+            });
+        }
+        else {
+            assert!(client.poll(Duration::from_millis(250)).is_none());
+        }
+    }
+
+    //Turning detection off stops the windows.
+    client.set_inbound_rate_anomaly(connection,None);
+    test_server.advance_clock(window);
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+}
+
+#[test]
+fn test_test_request_response() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    fn new_test_request(test_req_id: &[u8]) -> TestRequest {
+        let mut message = new_fixt_message!(TestRequest);
+        message.test_req_id = test_req_id.to_vec();
+
+        message
+    }
+
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Delayed replies are only sent once the delay is up. The application is told about each
+    //TestRequest as soon as it's received.
+    let delay = Duration::from_millis(1000);
+    client.set_test_request_settings(connection,TestRequestSettings {
+        response: TestRequestResponse::Delay(delay),
+        report_received: true,
+    });
+    test_server.send_message_with_msg_seq_num(2,new_test_request(b"1"));
+    engine_poll_event!(client,EngineEvent::TestRequestReceived(test_request_connection,test_req_id) => {
+        assert_eq!(test_request_connection,connection);
+        assert_eq!(test_req_id,b"1");
+    });
+    let message = engine_poll_message!(client,connection,TestRequest);
+    assert_eq!(message.test_req_id,b"1");
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(500)).is_none());
+    test_server.advance_clock(delay);
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"1");
+
+    //Suppressed replies are never sent.
+    client.set_test_request_settings(connection,TestRequestSettings {
+        response: TestRequestResponse::Suppress,
+        report_received: true,
+    });
+    let _ = client.connection_info(connection); //Wait until the engine's thread has the change.
+    test_server.send_message_with_msg_seq_num(3,new_test_request(b"2"));
+    engine_poll_event!(client,EngineEvent::TestRequestReceived(_,test_req_id) => {
+        assert_eq!(test_req_id,b"2");
+    });
+    let _ = engine_poll_message!(client,connection,TestRequest);
+    test_server.advance_clock(delay);
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(500)).is_none());
+
+    //The default replies immediately without an event.
+    client.set_test_request_settings(connection,TestRequestSettings::new());
+    let _ = client.connection_info(connection);
+    test_server.send_message_with_msg_seq_num(4,new_test_request(b"3"));
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"3");
+    let message = engine_poll_message!(client,connection,TestRequest);
+    assert_eq!(message.test_req_id,b"3");
+}
+
+#[test]
+fn test_poll_batch() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+    );
+
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    for msg_seq_num in 2..5 {
+        let mut heartbeat = new_fixt_message!(Heartbeat);
+        heartbeat.msg_seq_num = msg_seq_num;
+        test_server.send_message(heartbeat);
+    }
+
+    //Batch stops as soon as it's full.
+    let events = client.poll_batch(2,Instant::now() + Duration::from_secs(5));
+    assert_eq!(events.len(),2);
+
+    //Otherwise, it waits until the deadline for more.
+    let start = Instant::now();
+    let events = client.poll_batch(10,start + Duration::from_millis(500));
+    assert!(start.elapsed() >= Duration::from_millis(500));
+    assert_eq!(events.len(),1);
+    for event in events {
+        match event {
+            EngineEvent::MessageReceived(message_connection,message) => {
+                assert_eq!(message_connection,connection);
+                assert_eq!(message.msg_seq_num(),4);
+            },
+            event => panic!("Unexpected event: {:?}",event),
+        }
+    }
+
+    //A deadline that already passed only returns what is waiting.
+    assert!(client.poll_batch(10,Instant::now()).is_empty());
+}
+
+#[test]
+fn test_connection_info() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    let connection_info = client.connection_info(connection).unwrap();
+    assert_eq!(connection_info.fix_version,FIXVersion::FIXT_1_1);
+    assert_eq!(connection_info.default_message_version,MessageVersion::FIX50SP2);
+    assert_eq!(connection_info.heartbeat_interval,Some(Duration::from_secs(5)));
+    assert_eq!(connection_info.inbound_msg_seq_num,2);
+    assert_eq!(connection_info.outbound_msg_seq_num,2);
+    assert_eq!(connection_info.session_id,SessionID::new(FIXVersion::FIXT_1_1,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID));
+
+    //Exchange a TestRequest and Heartbeat so both MsgSeqNums are incremented.
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 2;
+    message.test_req_id = b"1".to_vec();
+    test_server.send_message(message);
+    let _ = test_server.recv_message::<Heartbeat>();
+
+    let connection_info = client.connection_info(connection).unwrap();
+    assert_eq!(connection_info.inbound_msg_seq_num,3);
+    assert_eq!(connection_info.outbound_msg_seq_num,3);
+
+    assert!(connection_info.inbound_bytes_parsed > 0);
+    assert_eq!(connection_info.inbound_acknowledged_msg_seq_num,0);
+
+    //Acknowledgements only move forward and never past what was received.
+    client.acknowledge_inbound(connection,2);
+    assert_eq!(client.connection_info(connection).unwrap().inbound_acknowledged_msg_seq_num,2);
+    client.acknowledge_inbound(connection,1);
+    assert_eq!(client.connection_info(connection).unwrap().inbound_acknowledged_msg_seq_num,2);
+    client.acknowledge_inbound(connection,100);
+    assert_eq!(client.connection_info(connection).unwrap().inbound_acknowledged_msg_seq_num,2);
+
+    //Unknown connections have no info.
+    assert!(client.connection_info(Connection { id: connection.id + 1, generation: connection.generation }).is_none());
+
+    //Neither do stale connections that happen to share an id with an active one.
+    assert!(client.connection_info(Connection { id: connection.id, generation: connection.generation + 1 }).is_none());
+}
+
+#[test]
+fn test_flush() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Everything queued before flushing must already be on the wire once flush returns.
+    for i in 0..100 {
+        let mut message = new_fixt_message!(Heartbeat);
+        message.test_req_id = i.to_string().into_bytes();
+        client.send_message(connection,message);
+    }
+    assert!(client.flush(connection,Duration::from_secs(5)));
+    for i in 0..100 {
+        let message = test_server.recv_message::<Heartbeat>();
+        assert_eq!(message.test_req_id,i.to_string().into_bytes());
+    }
+
+    //Nothing left to write so flushing again returns immediately.
+    assert!(client.flush(connection,Duration::from_secs(5)));
+
+    //Invalid and terminated connections can't be flushed.
+    assert!(!client.flush(Connection { id: connection.id + 1, generation: connection.generation },Duration::from_secs(5)));
+    let _ = test_server.stream.shutdown(Shutdown::Both);
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+    assert!(!client.flush(connection,Duration::from_secs(5)));
+}
+
+#[test]
+fn test_health_monitor() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    let _ = client.connection_info(connection); //Wait until the engine thread is caught up.
+
+    //Health checks can be made from any thread without involving the Engine.
+    let health_monitor = client.health_monitor();
+    let health_check = {
+        let health_monitor = health_monitor.clone();
+        thread::spawn(move || health_monitor.health_check(connection)).join().unwrap().unwrap()
+    };
+    assert!(health_check.established);
+    assert_eq!(health_check.inbound_msg_seq_num,2);
+    assert_eq!(health_check.outbound_msg_seq_num,2);
+    let first_inbound_time = health_check.last_inbound_time.unwrap();
+    let first_outbound_time = health_check.last_outbound_time.unwrap();
+    assert!(health_check.inbound_idle().unwrap() < Duration::from_secs(5));
+    assert_eq!(health_monitor.connections(),vec![connection]);
+
+    //Exchange a TestRequest and Heartbeat so both times and MsgSeqNums are updated.
+    test_server.advance_clock(Duration::from_millis(10));
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 2;
+    message.test_req_id = b"1".to_vec();
+    test_server.send_message(message);
+    let _ = test_server.recv_message::<Heartbeat>();
+    let _ = client.connection_info(connection);
+
+    let health_check = health_monitor.health_check(connection).unwrap();
+    assert_eq!(health_check.inbound_msg_seq_num,3);
+    assert_eq!(health_check.outbound_msg_seq_num,3);
+    assert!(health_check.last_inbound_time.unwrap() > first_inbound_time);
+    assert!(health_check.last_outbound_time.unwrap() > first_outbound_time);
+
+    //Terminated connections are removed.
+    let _ = test_server.stream.shutdown(Shutdown::Both);
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+    assert!(health_monitor.health_check(connection).is_none());
+    assert!(health_monitor.connections().is_empty());
+}
+
+#[test]
+fn test_logon_session_status() {
+    define_dictionary!(
+        Logon,
+        Logout,
+    );
+
+    //Connect and send Logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server(build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    assert_eq!(message.msg_seq_num,1);
+
+    //Respond with a Logon that includes SessionStatus. Engine should report the status before the
+    //session is established.
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    response_message.session_status = Some(SessionStatus::SessionPasswordDueToExpire);
+    response_message.text = b"Password expires soon".to_vec();
+    test_server.send_message_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,response_message);
+    engine_poll_event!(client,EngineEvent::SessionStatusReceived(SessionStatusReceivedData { connection: status_connection, session_status, text }) => {
+        assert_eq!(status_connection,connection);
+        assert_eq!(session_status,SessionStatus::SessionPasswordDueToExpire);
+        assert_eq!(text,b"Password expires soon");
+    });
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+}
+
+#[test]
+fn test_logon_uses_fix_version() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    //FIXT.1.1: Make sure Logon includes DefaultApplVerID even when the default message version
+    //predates it.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX44,build_dictionary());
+        client.send_message(connection,new_logon_message());
+
+        let bytes = recv_bytes_with_timeout(&mut test_server.stream,Duration::from_secs(5)).unwrap();
+        assert!(bytes.starts_with(b"8=FIXT.1.1\x01"));
+        assert!(bytes.windows(8).any(|window| window == b"\x011137=9\x01"));
+    }
+
+    //FIX.4.2: Make sure Logon doesn't include DefaultApplVerID and that the session uses FIX.4.2
+    //messages after the Logon response even though it doesn't include DefaultApplVerID either.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_ver(FIXVersion::FIX_4_2,MessageVersion::FIX42,build_dictionary());
+        client.send_message(connection,new_logon_message());
+        let message = test_server.recv_message::<Logon>();
+        assert_eq!(message.meta.as_ref().unwrap().begin_string,FIXVersion::FIX_4_2);
+
+        let mut response_message = new_fixt_message!(Logon);
+        response_message.encrypt_method = message.encrypt_method;
+        response_message.heart_bt_int = message.heart_bt_int;
+        test_server.send_message(response_message);
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+        let _ = engine_poll_message!(client,connection,Logon);
+
+        let mut message = new_fixt_message!(TestRequest);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"1".to_vec();
+        test_server.send_message(message);
+        let message = engine_poll_message!(client,connection,TestRequest);
+        assert_eq!(message.meta.as_ref().unwrap().message_version,MessageVersion::FIX42);
+
+        let message = test_server.recv_message::<Heartbeat>();
+        assert_eq!(message.meta.as_ref().unwrap().begin_string,FIXVersion::FIX_4_2);
+        assert_eq!(message.test_req_id,b"1".to_vec());
+    }
+}
+
+#[test]
+fn test_recv_logout_discards_queued_messages() {
+    define_dictionary!(
+        Logon,
+        Logout,
+        NewOrderSingle,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Hold an application message and ask for queued messages to be dropped on Logout.
+    client.pause_sending(connection);
+    client.set_logout_response_policy(connection,LogoutResponsePolicy::Discard);
+
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"0".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.security_id = b"0".to_vec();
+    new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.send_message(connection,new_order_single);
+
+    //Remote logs out. Engine should report the held message as not sent and respond with Logout
+    //without ever sending it.
+    let mut message = new_fixt_message!(Logout);
+    message.msg_seq_num = 2;
+    test_server.send_message(message);
+    engine_poll_event!(client,EngineEvent::MessagesNotSent(not_sent_connection,messages) => {
+        assert_eq!(not_sent_connection,connection);
+        assert_eq!(messages.len(),1);
+        let message = messages[0].as_any().downcast_ref::<NewOrderSingle>().expect("Not expected message type").clone();
+        assert_eq!(message.cl_ord_id,b"0");
+    });
+    let message = test_server.recv_message::<Logout>();
+    assert_eq!(message.msg_seq_num,2);
+    assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+}
+
+#[test]
+fn test_resend_completed_summary() {
+    define_dictionary!(
+        Logon,
+        NewOrderSingle,
+        ResendRequest,
+        SequenceReset,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Send an application message that the server will ask for again.
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"0".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.security_id = b"0".to_vec();
+    new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.send_message(connection,new_order_single);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,2);
+
+    //Request everything including the Logon.
+    let mut resend_request = new_fixt_message!(ResendRequest);
+    resend_request.msg_seq_num = 2;
+    resend_request.begin_seq_no = 1;
+    resend_request.end_seq_no = 0;
+    test_server.send_message(resend_request);
+
+    //Respond with a gap fill over the Logon and the original NewOrderSingle. Without any
+    //ResendRequestLimits, everything is queued at once so the engine should summarize what was
+    //sent right away.
+    engine_poll_event!(client,EngineEvent::ResendRequested(resend_connection,range) => {
+        assert_eq!(resend_connection,connection);
+        assert_eq!(range,1..3);
+
+        let mut response = Vec::new();
+        response.push(ResendResponse::Gap(1..2));
+        response.push(ResendResponse::Message(None,Box::new(message.clone())));
+        client.send_resend_response(connection,response);
+    });
+    let _ = engine_poll_message!(client,connection,ResendRequest);
+    engine_poll_event!(client,EngineEvent::ResendCompleted(summary_connection,summary) => {
+        assert_eq!(summary_connection,connection);
+        assert_eq!(summary.requested,Some(1..3));
+        assert_eq!(summary.messages_resent,1);
+        assert_eq!(summary.gap_fills_sent,1);
+        assert_eq!(summary.gap_filled_msg_seq_nums,1);
+    });
+
+    let message = test_server.recv_message::<SequenceReset>();
+    assert_eq!(message.msg_seq_num,1);
+    assert_eq!(message.new_seq_no,2);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,2);
+    assert!(message.poss_dup_flag);
+}
+
+#[test]
+fn test_resend_request_limits() {
+    define_dictionary!(
+        Logon,
+        NewOrderSingle,
+        ResendRequest,
+        SequenceReset,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Only replay the two newest messages and send them one at a time.
+    client.set_resend_request_limits(connection,ResendRequestLimits {
+        max_range: Some(2),
+        chunk_size: 1,
+        chunk_interval: Duration::from_millis(500),
+    });
+
+    //Send a couple of application messages that the server will ask for again.
+    let mut sent_messages = Vec::new();
+    for x in 0..2 {
+        let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        new_order_single.cl_ord_id = x.to_string().into_bytes();
+        new_order_single.symbol = b"TEST".to_vec();
+        new_order_single.security_id = b"0".to_vec();
+        new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+        new_order_single.side = Side::Buy;
+        new_order_single.transact_time = new_order_single.sending_time;
+        new_order_single.order_qty = b"1".to_vec();
+        new_order_single.ord_type = OrdType::Market;
+        client.send_message(connection,new_order_single);
+        sent_messages.push(test_server.recv_message::<NewOrderSingle>());
+    }
+
+    //Request everything. The Logon should be gap filled automatically and only the rest passed
+    //along to be replayed.
+    let mut resend_request = new_fixt_message!(ResendRequest);
+    resend_request.msg_seq_num = 2;
+    resend_request.begin_seq_no = 1;
+    resend_request.end_seq_no = 0;
+    test_server.send_message(resend_request);
+
+    engine_poll_event!(client,EngineEvent::ResendRequested(resend_connection,range) => {
+        assert_eq!(resend_connection,connection);
+        assert_eq!(range,2..4);
+
+        let mut response = Vec::new();
+        for message in &sent_messages {
+            response.push(ResendResponse::Message(None,Box::new(message.clone())));
+        }
+        client.send_resend_response(connection,response);
+    });
+    let _ = engine_poll_message!(client,connection,ResendRequest);
+
+    let message = test_server.recv_message::<SequenceReset>();
+    assert_eq!(message.msg_seq_num,1);
+    assert_eq!(message.new_seq_no,2);
+    assert!(message.gap_fill_flag);
+
+    //Replayed messages should be paced by chunk_interval.
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,2);
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(100)).is_none());
+
+    //Resend isn't completed until the last chunk goes out.
+    assert!(client.poll(Duration::from_millis(100)).is_none());
+
+    //New application messages must wait until the replay is finished.
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"2".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.security_id = b"0".to_vec();
+    new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.send_message(connection,new_order_single);
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(100)).is_none());
+
+    test_server.advance_clock(Duration::from_millis(500));
+    engine_poll_event!(client,EngineEvent::ResendCompleted(summary_connection,summary) => {
+        assert_eq!(summary_connection,connection);
+        assert_eq!(summary.requested,Some(2..4));
+        assert_eq!(summary.messages_resent,2);
+    });
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,3);
+    assert!(message.poss_dup_flag);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,4);
+    assert_eq!(message.cl_ord_id,b"2");
+    assert!(!message.poss_dup_flag);
+}
+
+#[test]
+fn test_resend_request_range() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        ResendRequest,
+        SequenceReset,
+    );
+
+    //Exact ranges should ask for precisely the missing MsgSeqNums.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+        client.set_resend_request_range(connection,ResendRequestRange::Exact);
+
+        test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,2);
+        assert_eq!(message.end_seq_no,4);
+    }
+
+    //Open-ended ranges should not be repeated while outstanding but should be sent again when the
+    //remote only answers part of the request.
+    {
+        let (mut test_server,_client,_) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+        test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,2);
+        assert_eq!(message.end_seq_no,0);
+
+        //Still missing the same messages. Nothing new to ask for.
+        test_server.send_message_with_msg_seq_num(6,new_fixt_message!(Heartbeat));
+        assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+        //Remote answers part of the request and then moves on.
+        test_server.send_sequence_reset_gap_fill(2,4);
+        test_server.send_message_with_msg_seq_num(7,new_fixt_message!(Heartbeat));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,4);
+        assert_eq!(message.end_seq_no,0);
+    }
+}
+
+#[test]
+fn test_resend_request_coalescing() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        ResendRequest,
+        SequenceReset,
+    );
+
+    //Gaps that are already covered by an outstanding exact ResendRequest should only cause the
+    //uncovered part to be requested.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+        client.set_resend_request_range(connection,ResendRequestRange::Exact);
+
+        test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,2);
+        assert_eq!(message.end_seq_no,4);
+
+        test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+        assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+        test_server.send_message_with_msg_seq_num(8,new_fixt_message!(Heartbeat));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,5);
+        assert_eq!(message.end_seq_no,7);
+    }
+
+    //Gaps detected within the coalesce interval should be merged into a single ResendRequest.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+        client.set_resend_request_range(connection,ResendRequestRange::Exact);
+        client.set_resend_request_coalesce_interval(connection,Duration::from_millis(500));
+
+        test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+        test_server.send_message_with_msg_seq_num(8,new_fixt_message!(Heartbeat));
+        test_server.send_message_with_msg_seq_num(12,new_fixt_message!(Heartbeat));
+        assert!(test_server.try_recv_fixt_message(Duration::from_millis(100)).is_none());
+        test_server.advance_clock(Duration::from_millis(500));
+        let message = test_server.recv_message::<ResendRequest>();
+        assert_eq!(message.begin_seq_no,2);
+        assert_eq!(message.end_seq_no,11);
+        assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+    }
+}
+
+#[test]
+fn test_connection_builder() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        ResendRequest,
+    );
+
+    //Connect using per-connection options from a builder.
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .resend_request_range(ResendRequestRange::Exact);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    assert_eq!(message.msg_seq_num,1);
+
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Builder's ResendRequestRange should already be in effect.
+    test_server.send_message_with_msg_seq_num(5,new_fixt_message!(Heartbeat));
+    let message = test_server.recv_message::<ResendRequest>();
+    assert_eq!(message.begin_seq_no,2);
+    assert_eq!(message.end_seq_no,4);
+}
+
+#[test]
+fn test_timestamp_precision() {
+    define_dictionary!(
+        Logon,
+    );
+
+    //Connect using microsecond precision for outbound timestamps.
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .timestamp_precision(TimestampPrecision::Microseconds);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+
+    //SendingTime should be written as YYYYMMDD-HH:MM:SS.ssssss.
+    let bytes = recv_bytes_with_timeout(&mut test_server.stream,Duration::from_secs(5)).unwrap();
+    let sending_time_begin = bytes.windows(4).position(|window| window == b"\x0152=").unwrap() + 4;
+    let sending_time_len = bytes[sending_time_begin..].iter().position(|byte| *byte == b'\x01').unwrap();
+    assert_eq!(sending_time_len,24);
+    assert_eq!(bytes[sending_time_begin + 17],b'.');
+}
+
+#[test]
+fn test_quarantine_log() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        Reject,
+        TestMessage,
+    );
+
+    let path = env::temp_dir().join("fix_rs_test_quarantine_log.log");
+    let _ = fs::remove_file(&path);
+
+    //Connect, logon, and start quarantining.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50,build_dictionary());
+    client.set_quarantine_log(connection,&path).unwrap();
+    let _ = client.connection_info(connection); //Wait until the engine thread has applied it.
+
+    //Send garbage followed by a message that fails to parse because SenderCompID is the fifth tag.
+    let mut bytes = b"GARBAGE".to_vec();
+    let bad_message = b"8=FIXT.1.1\x019=48\x0135=9999\x0156=TEST\x0149=TX\x0134=2\x0152=20170105-01:01:01\x0110=012\x01";
+    bytes.extend_from_slice(bad_message);
+    let bytes_written = test_server.stream.write(&bytes).unwrap();
+    assert_eq!(bytes_written,bytes.len());
+
+    let _ = test_server.recv_message::<Reject>();
+    engine_poll_event!(client,EngineEvent::MessageReceivedGarbled(msg_connection,parse_error) => {
+        assert_eq!(msg_connection,connection);
+        assert!(if let ParseError::SenderCompIDNotFourthTag = parse_error { true } else { false });
+    });
+
+    //Every discarded byte must be in the log, in order, with contiguous offsets.
+    let mut contents = Vec::new();
+    File::open(&path).unwrap().read_to_end(&mut contents).unwrap();
+    let mut quarantined_bytes = Vec::new();
+    let mut reasons = Vec::new();
+    let mut next_offset = None;
+    let mut remaining = &contents[..];
+    while !remaining.is_empty() {
+        let header_len = remaining.iter().position(|byte| *byte == b'\n').unwrap();
+        let header = String::from_utf8(remaining[..header_len].to_vec()).unwrap();
+        let offset = header.split(" offset=").nth(1).unwrap().split(' ').next().unwrap().parse::<u64>().unwrap();
+        let length = header.split(" length=").nth(1).unwrap().split(' ').next().unwrap().parse::<usize>().unwrap();
+        reasons.push(header.split(" reason=").nth(1).unwrap().to_string());
+        if let Some(next_offset) = next_offset {
+            assert_eq!(offset,next_offset);
+        }
+        next_offset = Some(offset + length as u64);
+
+        let entry_bytes = &remaining[header_len + 1..header_len + 1 + length];
+        quarantined_bytes.extend_from_slice(entry_bytes);
+        assert_eq!(remaining[header_len + 1 + length],b'\n');
+        remaining = &remaining[header_len + 1 + length + 1..];
+    }
+    assert_eq!(quarantined_bytes,bytes);
+    assert_eq!(reasons[0],"Garbage outside of a message");
+    assert!(reasons[1].starts_with("ParseError::SenderCompIDNotFourthTag offset="));
+    assert!(reasons[1].contains(" tag=56 msg_type=9999 "));
+
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn test_message_log() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    let path = env::temp_dir().join("fix_rs_test_message_log.log");
+    let json_path = env::temp_dir().join("fix_rs_test_message_log.jsonl");
+    let framed_path = env::temp_dir().join("fix_rs_test_message_log.bin");
+    for path in &[&path,&json_path,&framed_path] {
+        let _ = fs::remove_file(path);
+    }
+
+    //Write the same messages to several logs, each in a different format.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_message_log(connection,&path,MessageLogSettings::new()).unwrap();
+    let mut json_settings = MessageLogSettings::new();
+    json_settings.format = MessageLogFormat::JsonLines;
+    client.add_message_log(connection,&json_path,json_settings).unwrap();
+    let mut framed_settings = MessageLogSettings::new();
+    framed_settings.format = MessageLogFormat::LengthPrefixed;
+    client.add_message_log(connection,&framed_path,framed_settings).unwrap();
+    let _ = client.connection_info(connection); //Wait until the engine thread has applied it.
+
+    //Both the received TestRequest and the Heartbeat sent in response are logged.
+    let mut message = new_fixt_message!(TestRequest);
+    message.test_req_id = b"test".to_vec();
+    test_server.send_message_with_msg_seq_num(2,message);
+    let _ = test_server.recv_message::<Heartbeat>();
+    let _ = engine_poll_message!(client,connection,TestRequest);
+
+    client.clear_message_log(connection);
+    let _ = client.connection_info(connection);
+
+    let mut contents = String::new();
+    File::open(&path).unwrap().read_to_string(&mut contents).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(),2);
+    assert!(lines[0].split(' ').nth(1) == Some("IN"));
+    assert!(lines[0].contains(" 8=FIXT.1.1|35=1|"));
+    assert!(lines[0].contains("|34=2|"));
+    assert!(lines[0].contains("|112=test|"));
+    assert!(lines[1].split(' ').nth(1) == Some("OUT"));
+    assert!(lines[1].contains(" 8=FIXT.1.1|35=0|"));
+    assert!(lines[1].contains("|112=test|"));
+    assert!(!contents.contains('\x01'));
+
+    let mut contents = String::new();
+    File::open(&json_path).unwrap().read_to_string(&mut contents).unwrap();
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(),2);
+    assert!(lines[0].starts_with("{\"time\":\""));
+    assert!(lines[0].contains("\"direction\":\"IN\",\"fields\":[[8,\"FIXT.1.1\"],[35,\"1\"],"));
+    assert!(lines[1].contains("\"direction\":\"OUT\",\"fields\":[[8,\"FIXT.1.1\"],[35,\"0\"],"));
+    assert!(lines.iter().all(|line| line.ends_with("]]}")));
+
+    //Each frame is a timestamp, direction, length, and then the message exactly as FIX.
+    let mut contents = Vec::new();
+    File::open(&framed_path).unwrap().read_to_end(&mut contents).unwrap();
+    let mut directions = Vec::new();
+    let mut remaining = &contents[..];
+    while !remaining.is_empty() {
+        directions.push(remaining[8]);
+        let length = remaining[9..13].iter().fold(0,|length,byte| (length << 8) | *byte as usize);
+        let message_bytes = &remaining[13..13 + length];
+        assert!(message_bytes.starts_with(b"8=FIXT.1.1\x019="));
+        assert!(message_bytes.ends_with(b"\x01"));
+        remaining = &remaining[13 + length..];
+    }
+    assert_eq!(directions,b"IO");
+
+    for path in &[&path,&json_path,&framed_path] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[test]
+fn test_inbound_checkpoint() {
+    define_dictionary!(
+        Logon,
+        ResendRequest,
+    );
+
+    //Resume after the application finished processing up to MsgSeqNum 5 in a previous run.
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .inbound_checkpoint(5);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    let connection_info = client.connection_info(connection).unwrap();
+    assert_eq!(connection_info.inbound_msg_seq_num,6);
+    assert_eq!(connection_info.inbound_acknowledged_msg_seq_num,5);
+
+    //Logon where the remote has since sent up to MsgSeqNum 7.
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.msg_seq_num = 8;
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message(response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let message = engine_poll_message!(client,connection,Logon);
+    assert_eq!(message.msg_seq_num,8);
+
+    //Every unacknowledged message is requested again.
+    let message = test_server.recv_message::<ResendRequest>();
+    assert_eq!(message.begin_seq_no,6);
+    assert!(message.end_seq_no == 0 || message.end_seq_no == 7);
+}
+
+#[test]
+fn test_message_store() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        ResendRequest,
+        SequenceReset,
+        TestMessage,
+    );
+
+    fn logon(test_server: &mut TestStream,client: &mut fixt::engine::Engine,connection: Connection,msg_seq_num: u64) -> Logon {
+        client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+        let message = test_server.recv_message::<Logon>();
+        let mut response_message = new_fixt_message!(Logon);
+        response_message.msg_seq_num = msg_seq_num;
+        response_message.encrypt_method = message.encrypt_method;
+        response_message.heart_bt_int = message.heart_bt_int;
+        response_message.default_appl_ver_id = message.default_appl_ver_id;
+        test_server.send_message(response_message);
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+        let _ = engine_poll_message!(client,connection,Logon);
+
+        message
+    }
+
+    let directory = env::temp_dir().join("fix_rs_test_message_store");
+    let _ = fs::remove_dir_all(&directory);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .message_store(&directory);
+
+    //First run starts from the beginning and sends a mix of application and administrative
+    //messages.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder.clone(),build_dictionary());
+        let message = logon(&mut test_server,&mut client,connection,1);
+        assert_eq!(message.msg_seq_num,1);
+
+        let mut message = new_fixt_message!(TestMessage);
+        message.text = b"first".to_vec();
+        client.send_message(connection,message);
+        client.send_message(connection,new_fixt_message!(Heartbeat));
+        let mut message = new_fixt_message!(TestMessage);
+        message.text = b"second".to_vec();
+        client.send_message(connection,message);
+        assert!(client.flush(connection,Duration::from_secs(5)));
+        assert_eq!(test_server.recv_message::<TestMessage>().msg_seq_num,2);
+        assert_eq!(test_server.recv_message::<Heartbeat>().msg_seq_num,3);
+        assert_eq!(test_server.recv_message::<TestMessage>().msg_seq_num,4);
+    }
+
+    //After a restart, the session continues from the stored MsgSeqNums.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    let connection_info = client.connection_info(connection).unwrap();
+    assert_eq!(connection_info.inbound_msg_seq_num,2);
+    assert_eq!(connection_info.outbound_msg_seq_num,5);
+    let message = logon(&mut test_server,&mut client,connection,2);
+    assert_eq!(message.msg_seq_num,5);
+
+    //ResendRequest is answered from the store without involving the application. Administrative
+    //messages are gap filled.
+    let mut message = new_fixt_message!(ResendRequest);
+    message.msg_seq_num = 3;
+    message.begin_seq_no = 2;
+    message.end_seq_no = 0;
+    test_server.send_message(message);
+    engine_poll_event!(client,EngineEvent::ResendCompleted(completed_connection,summary) => {
+        assert_eq!(completed_connection,connection);
+        assert_eq!(summary.requested,Some(2..6));
+        assert_eq!(summary.messages_resent,2);
+        assert_eq!(summary.gap_fills_sent,2);
+        assert_eq!(summary.gap_filled_msg_seq_nums,2);
+    });
+
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert!(message.poss_dup_flag);
+    assert_eq!(message.text,b"first".to_vec());
+    let message = test_server.recv_message::<SequenceReset>();
+    assert_eq!(message.msg_seq_num,3);
+    assert!(message.gap_fill_flag);
+    assert_eq!(message.new_seq_no,4);
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,4);
+    assert!(message.poss_dup_flag);
+    assert_eq!(message.text,b"second".to_vec());
+    let message = test_server.recv_message::<SequenceReset>();
+    assert_eq!(message.msg_seq_num,5);
+    assert!(message.gap_fill_flag);
+    assert_eq!(message.new_seq_no,6);
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_inbound_middleware() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        TestMessage,
+    );
+
+    //Upper cases Text so the next middleware doesn't have to care.
+    struct NormalizeText;
+    impl InboundMiddleware for NormalizeText {
+        fn on_message(&mut self,_connection: Connection,mut message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+            if let Some(message) = message.as_any_mut().downcast_mut::<TestMessage>() {
+                message.text = String::from_utf8_lossy(&message.text).to_uppercase().into_bytes();
+            }
+
+            MiddlewareAction::Continue(message)
+        }
+    }
+
+    //Drops or rejects based on the normalized Text.
+    struct FilterText;
+    impl InboundMiddleware for FilterText {
+        fn on_message(&mut self,_connection: Connection,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+            let text = match message.as_any().downcast_ref::<TestMessage>() {
+                Some(message) => message.text.clone(),
+                None => return MiddlewareAction::Continue(message),
+            };
+
+            match &text[..] {
+                b"DROP" => MiddlewareAction::Drop,
+                b"REJECT" => MiddlewareAction::Reject(message),
+                _ => MiddlewareAction::Continue(message),
+            }
+        }
+    }
+
+    //Connect and logon. Administrative messages pass through untouched.
+    let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE)
+        .inbound_middleware(NormalizeText)
+        .inbound_middleware(FilterText);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_builders(engine_builder,connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let _ = test_server.recv_message::<Logon>();
+    test_server.send_message(new_logon_message());
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Middleware runs in the order it was added.
+    for (msg_seq_num,text) in vec![b"drop".to_vec(),b"reject".to_vec(),b"keep".to_vec()].into_iter().enumerate() {
+        let mut message = new_fixt_message!(TestMessage);
+        message.msg_seq_num = msg_seq_num as u64 + 2;
+        message.text = text;
+        test_server.send_message(message);
+    }
+
+    engine_poll_event!(client,EngineEvent::MessageRejected(msg_connection,rejected_message) => {
+        assert_eq!(msg_connection,connection);
+        let message = rejected_message.as_any().downcast_ref::<TestMessage>().expect("Not expected message type");
+        assert_eq!(message.msg_seq_num,3);
+        assert_eq!(message.text,b"REJECT");
+    });
+    let message = engine_poll_message!(client,connection,TestMessage);
+    assert_eq!(message.msg_seq_num,4);
+    assert_eq!(message.text,b"KEEP");
+
+    //Dropped and rejected messages still count as received.
+    assert_eq!(client.connection_info(connection).unwrap().inbound_msg_seq_num,5);
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(250)).is_none());
+}
+
+#[test]
+fn test_user_data() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+    );
+
+    //Records the MsgSeqNum of every Heartbeat into the connection's user data.
+    struct RecordHeartbeats;
+    impl InboundMiddleware for RecordHeartbeats {
+        fn on_message(&mut self,_connection: Connection,_message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+            unreachable!();
+        }
+
+        fn on_message_with_user_data(&mut self,_connection: Connection,user_data: Option<&Arc<Any + Send + Sync>>,message: Box<FIXTMessage + Send>) -> MiddlewareAction {
+            if message.as_any().is::<Heartbeat>() {
+                let user_data: &Any = &**user_data.unwrap();
+                user_data.downcast_ref::<Mutex<Vec<u64>>>().unwrap().lock().unwrap().push(message.msg_seq_num());
+            }
+
+            MiddlewareAction::Continue(message)
+        }
+    }
+
+    //Connect and logon with user data attached from the start.
+    let heartbeats: Arc<Any + Send + Sync> = Arc::new(Mutex::new(Vec::<u64>::new()));
+    let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE)
+        .inbound_middleware(RecordHeartbeats);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .user_data(heartbeats.clone());
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_builders(engine_builder,connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let _ = test_server.recv_message::<Logon>();
+    test_server.send_message(new_logon_message());
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Middleware and the application share the same user data.
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 2;
+    test_server.send_message(heartbeat);
+    let _ = engine_poll_message!(client,connection,Heartbeat);
+    let user_data = client.user_data(connection).unwrap();
+    let user_data: &Any = &*user_data;
+    assert_eq!(*user_data.downcast_ref::<Mutex<Vec<u64>>>().unwrap().lock().unwrap(),vec![2]);
+
+    //User data is still available while handling the termination event but released afterwards.
+    let _ = test_server.stream.shutdown(Shutdown::Both);
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+        assert!(client.user_data(connection).is_some());
+    });
+    assert!(client.poll(Duration::from_millis(0)).is_none());
+    assert!(client.user_data(connection).is_none());
+}
+
+#[test]
+fn test_outbound_validation() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+    );
+
+    fn new_order_single(cl_ord_id: &[u8]) -> NewOrderSingle {
+        let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        message.cl_ord_id = cl_ord_id.to_vec();
+        message.symbol = b"TEST".to_vec();
+        message.security_id = b"0".to_vec();
+        message.security_id_source = Some(SecurityIDSource::CUSIP);
+        message.side = Side::Buy;
+        message.transact_time = message.sending_time;
+        message.order_qty = b"1".to_vec();
+        message.ord_type = OrdType::Market;
+
+        message
+    }
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_outbound_validation(connection,OutboundValidation::ApplicationMessages);
+
+    //Send a message that is missing a required field. It should be handed back instead of sent.
+    client.send_message(connection,new_order_single(b""));
+    engine_poll_event!(client,EngineEvent::OutboundValidationFailed(data) => {
+        assert_eq!(data.connection,connection);
+        assert!(data.message.as_any().is::<NewOrderSingle>());
+        assert_eq!(data.errors.len(),1);
+        assert!(data.errors[0].groups.is_empty());
+        assert_eq!(data.errors[0].tag,ClOrdID::tag());
+        assert_eq!(data.errors[0].reason,OutboundFieldErrorReason::MissingRequiredField);
+    });
+    assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+    //A valid message goes out without using up a MsgSeqNum for the rejected one.
+    client.send_message(connection,new_order_single(b"1"));
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.cl_ord_id,b"1");
+}
+
+#[test]
+fn test_duplicate_order_guard() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+    );
+
+    fn new_order_single(cl_ord_id: &[u8]) -> NewOrderSingle {
+        let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        message.cl_ord_id = cl_ord_id.to_vec();
+        message.symbol = b"TEST".to_vec();
+        message.security_id = b"0".to_vec();
+        message.security_id_source = Some(SecurityIDSource::CUSIP);
+        message.side = Side::Buy;
+        message.transact_time = message.sending_time;
+        message.order_qty = b"1".to_vec();
+        message.ord_type = OrdType::Market;
+
+        message
+    }
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_duplicate_order_guard(connection,Some(DuplicateOrderGuard::new(Duration::from_secs(60),DuplicateOrderAction::Refuse)));
+
+    //Sending the same ClOrdID again hands the order back instead of sending it.
+    client.send_message(connection,new_order_single(b"1"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"1");
+    client.send_message(connection,new_order_single(b"1"));
+    engine_poll_event!(client,EngineEvent::DuplicateOrderRefused(refused_connection,message) => {
+        assert_eq!(refused_connection,connection);
+        assert_eq!(message.as_any().downcast_ref::<NewOrderSingle>().unwrap().cl_ord_id,b"1");
+    });
+    assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
+
+    //Other ClOrdIDs go out without using up a MsgSeqNum for the refused order.
+    client.send_message(connection,new_order_single(b"2"));
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.cl_ord_id,b"2");
+
+    //Flagged duplicates are still sent.
+    client.set_duplicate_order_guard(connection,Some(DuplicateOrderGuard::new(Duration::from_secs(60),DuplicateOrderAction::Flag)));
+    client.send_message(connection,new_order_single(b"3"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"3");
+    client.send_message(connection,new_order_single(b"3"));
+    engine_poll_event!(client,EngineEvent::DuplicateOrderSent(flagged_connection,cl_ord_id) => {
+        assert_eq!(flagged_connection,connection);
+        assert_eq!(cl_ord_id,b"3");
+    });
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"3");
+
+    //ClOrdIDs are forgotten once they fall outside of the window.
+    client.set_duplicate_order_guard(connection,Some(DuplicateOrderGuard::new(Duration::from_millis(100),DuplicateOrderAction::Refuse)));
+    client.send_message(connection,new_order_single(b"4"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"4");
+    test_server.advance_clock(Duration::from_millis(200));
+    client.send_message(connection,new_order_single(b"4"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"4");
+
+    //An order that expired before it could be sent doesn't count so it can be retried with the
+    //same ClOrdID.
+    client.set_duplicate_order_guard(connection,Some(DuplicateOrderGuard::new(Duration::from_secs(60),DuplicateOrderAction::Refuse)));
+    client.pause_sending(connection);
+    client.send_message_with_time_to_live(connection,new_order_single(b"5"),Duration::from_millis(10));
+    test_server.advance_clock(Duration::from_millis(50));
+    client.resume_sending(connection);
+    engine_poll_event!(client,EngineEvent::MessagesExpired(expired_connection,messages) => {
+        assert_eq!(expired_connection,connection);
+        assert_eq!(messages.len(),1);
+    });
+    client.send_message(connection,new_order_single(b"5"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"5");
+}
+
+#[test]
+fn test_duplicate_order_guard_message_store() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+    );
+
+    fn new_order_single(cl_ord_id: &[u8]) -> NewOrderSingle {
+        let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        message.cl_ord_id = cl_ord_id.to_vec();
+        message.symbol = b"TEST".to_vec();
+        message.security_id = b"0".to_vec();
+        message.security_id_source = Some(SecurityIDSource::CUSIP);
+        message.side = Side::Buy;
+        message.transact_time = message.sending_time;
+        message.order_qty = b"1".to_vec();
+        message.ord_type = OrdType::Market;
+
+        message
+    }
+
+    fn logon(test_server: &mut TestStream,client: &mut fixt::engine::Engine,connection: Connection,msg_seq_num: u64) {
+        client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+        let message = test_server.recv_message::<Logon>();
+        let mut response_message = new_fixt_message!(Logon);
+        response_message.msg_seq_num = msg_seq_num;
+        response_message.encrypt_method = message.encrypt_method;
+        response_message.heart_bt_int = message.heart_bt_int;
+        response_message.default_appl_ver_id = message.default_appl_ver_id;
+        test_server.send_message(response_message);
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+        let _ = engine_poll_message!(client,connection,Logon);
+    }
+
+    let directory = env::temp_dir().join("fix_rs_test_duplicate_order_guard_message_store");
+    let _ = fs::remove_dir_all(&directory);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .message_store(&directory)
+        .duplicate_order_guard(DuplicateOrderGuard::new(Duration::from_secs(60),DuplicateOrderAction::Refuse));
+
+    //First run sends an order.
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder.clone(),build_dictionary());
+        logon(&mut test_server,&mut client,connection,1);
+        client.send_message(connection,new_order_single(b"1"));
+        assert!(client.flush(connection,Duration::from_secs(5)));
+        assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"1");
+    }
+
+    //After a restart, the order sent by the previous run is still refused.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    logon(&mut test_server,&mut client,connection,2);
+    client.send_message(connection,new_order_single(b"1"));
+    engine_poll_event!(client,EngineEvent::DuplicateOrderRefused(refused_connection,_) => {
+        assert_eq!(refused_connection,connection);
+    });
+    client.send_message(connection,new_order_single(b"2"));
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"2");
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_order_entry_status() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        ExecutionReport,
+        NewOrderSingle,
+    );
+
+    fn new_order_single(cl_ord_id: &[u8]) -> NewOrderSingle {
+        let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        message.cl_ord_id = cl_ord_id.to_vec();
+        message.symbol = b"TEST".to_vec();
+        message.security_id = b"0".to_vec();
+        message.security_id_source = Some(SecurityIDSource::CUSIP);
+        message.side = Side::Buy;
+        message.transact_time = message.sending_time;
+        message.order_qty = b"1".to_vec();
+        message.ord_type = OrdType::Market;
+
+        message
+    }
+
+    fn logon(test_server: &mut TestStream,client: &mut fixt::engine::Engine,connection: Connection,msg_seq_num: u64) {
+        client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+        let message = test_server.recv_message::<Logon>();
+        let mut response_message = new_fixt_message!(Logon);
+        response_message.msg_seq_num = msg_seq_num;
+        response_message.encrypt_method = message.encrypt_method;
+        response_message.heart_bt_int = message.heart_bt_int;
+        response_message.default_appl_ver_id = message.default_appl_ver_id;
+        test_server.send_message(response_message);
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+        let _ = engine_poll_message!(client,connection,Logon);
+    }
+
+    let directory = env::temp_dir().join("fix_rs_test_order_entry_status");
+    let _ = fs::remove_dir_all(&directory);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .message_store(&directory);
+
+    //First run sends two orders but only the first one is acknowledged before the "crash".
+    {
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder.clone(),build_dictionary());
+        logon(&mut test_server,&mut client,connection,1);
+        client.send_message(connection,new_order_single(b"1"));
+        client.send_message(connection,new_order_single(b"2"));
+        assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"1");
+        assert_eq!(test_server.recv_message::<NewOrderSingle>().cl_ord_id,b"2");
+
+        let mut message = new_fixt_message!(ExecutionReport);
+        message.msg_seq_num = 2;
+        message.order_id = b"VENUE1".to_vec();
+        message.cl_ord_id = b"1".to_vec();
+        message.exec_id = b"E1".to_vec();
+        message.exec_type = ExecType::New;
+        message.ord_status = OrdStatus::New;
+        message.symbol = b"TEST".to_vec();
+        message.side = Side::Buy;
+        message.cum_qty = b"0".to_vec();
+        message.leaves_qty = b"1".to_vec();
+        test_server.send_message(message);
+        let _ = engine_poll_message!(client,connection,ExecutionReport);
+
+        assert_eq!(client.order_entry_status(connection,b"1"),Some(OrderEntryStatus::Acknowledged));
+        assert_eq!(client.order_entry_status(connection,b"2"),Some(OrderEntryStatus::Sent));
+    }
+
+    //After a restart, the store still knows what happened to each order.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+    logon(&mut test_server,&mut client,connection,3);
+    assert_eq!(client.order_entry_status(connection,b"1"),Some(OrderEntryStatus::Acknowledged));
+    assert_eq!(client.order_entry_status(connection,b"2"),Some(OrderEntryStatus::Sent));
+    assert_eq!(client.order_entry_status(connection,b"3"),Some(OrderEntryStatus::NotSent));
+
+    //Nothing is known without a message store.
+    let (_test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    assert_eq!(client.order_entry_status(connection,b"1"),None);
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_symbol_mapper() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        ExecutionReport,
+        NewOrderSingle,
+    );
+
+    let mut symbol_mapper = StaticSymbolMapper::new();
+    symbol_mapper.insert(b"VOD.L",b"1001");
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .symbol_mapper(Arc::new(symbol_mapper));
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message(response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Outbound internal ids are sent using the venue's symbol.
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"1".to_vec();
+    new_order_single.symbol = b"1001".to_vec();
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"100".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    client.send_message(connection,new_order_single);
+    assert_eq!(test_server.recv_message::<NewOrderSingle>().symbol,b"VOD.L");
+
+    //Inbound venue symbols are delivered as internal ids.
+    fn new_execution_report(msg_seq_num: u64,symbol: &[u8]) -> ExecutionReport {
+        let mut message = new_fixt_message!(ExecutionReport);
+        message.msg_seq_num = msg_seq_num;
+        message.order_id = b"VENUE1".to_vec();
+        message.cl_ord_id = b"1".to_vec();
+        message.exec_id = msg_seq_num.to_string().into_bytes();
+        message.exec_type = ExecType::New;
+        message.ord_status = OrdStatus::New;
+        message.symbol = symbol.to_vec();
+        message.side = Side::Buy;
+        message.cum_qty = b"0".to_vec();
+        message.leaves_qty = b"100".to_vec();
+
+        message
+    }
+    test_server.send_message(new_execution_report(2,b"VOD.L"));
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    assert_eq!(message.symbol,b"1001");
+
+    //Unknown symbols pass through unchanged.
+    test_server.send_message(new_execution_report(3,b"BARC.L"));
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    assert_eq!(message.symbol,b"BARC.L");
+
+    //Mapping can be turned off.
+    client.set_symbol_mapper(connection,None);
+    let _ = client.connection_info(connection); //Wait until the engine's thread has the change.
+    test_server.send_message(new_execution_report(4,b"VOD.L"));
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    assert_eq!(message.symbol,b"VOD.L");
+}
+
+#[test]
+fn test_session_coordinator() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    struct SwitchCoordinator {
+        is_active: AtomicBool,
+        is_released: AtomicBool,
+    }
+
+    impl SessionCoordinator for SwitchCoordinator {
+        fn try_acquire(&self) -> Result<bool,io::Error> {
+            Ok(self.is_active.load(Ordering::SeqCst))
+        }
+
+        fn renew(&self) -> Result<bool,io::Error> {
+            Ok(self.is_active.load(Ordering::SeqCst))
+        }
+
+        fn release(&self) -> Result<(),io::Error> {
+            self.is_released.store(true,Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn is_active(&self) -> bool {
+            self.is_active.load(Ordering::SeqCst)
+        }
+
+        fn renew_interval(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+    }
+
+    let session_coordinator = Arc::new(SwitchCoordinator {
+        is_active: AtomicBool::new(false),
+        is_released: AtomicBool::new(false),
+    });
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .session_coordinator(session_coordinator.clone());
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+
+    //Logon is refused while another engine process is active.
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    engine_poll_event!(client,EngineEvent::LogonRefusedByCoordinator(refused_connection,message) => {
+        assert_eq!(refused_connection,connection);
+        assert_eq!(message.msg_type(),Logon::msg_type());
+    });
+    assert!(test_server.try_recv_fixt_message(Duration::from_millis(100)).is_none());
+
+    //Once the other process steps down, logon goes ahead.
+    session_coordinator.is_active.store(true,Ordering::SeqCst);
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message(response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Renewing keeps the session going.
+    test_server.advance_clock(Duration::from_secs(1));
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+
+    //Losing the session to another process drops the connection without a Logout.
+    session_coordinator.is_active.store(false,Ordering::SeqCst);
+    test_server.advance_clock(Duration::from_secs(1));
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+        assert_eq!(terminated_connection,connection);
+        assert!(if let ConnectionTerminatedReason::SessionCoordinatorRevokedError = reason { true } else { false });
+    });
+    assert!(test_server.is_stream_closed(Duration::from_secs(3)));
+    assert!(session_coordinator.is_released.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_message_signer() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    struct MsgSeqNumSigner;
+
+    impl MessageSigner for MsgSeqNumSigner {
+        fn sign(&self,message: &FIXTMessage,_fix_version: FIXVersion,_message_version: MessageVersion) -> Option<Vec<u8>> {
+            //Signatures are data fields so they can contain anything, including SOH.
+            Some(format!("SIG\x01{}",message.msg_seq_num()).into_bytes())
+        }
+    }
+
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .message_signer(Arc::new(MsgSeqNumSigner));
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+
+    //Every outbound message is signed, starting with the Logon.
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    assert_eq!(message.signature,b"SIG\x011");
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message(response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let message = engine_poll_message!(client,connection,Logon);
+    assert!(message.signature().is_none());
+
+    let mut test_request = new_fixt_message!(FROM_CLIENT TestRequest);
+    test_request.test_req_id = b"1".to_vec();
+    client.send_message(connection,test_request);
+    let message = test_server.recv_message::<TestRequest>();
+    assert_eq!(message.signature(),Some(&b"SIG\x012"[..]));
+
+    //Received signatures are passed through untouched.
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 2;
+    heartbeat.signature = b"REMOTE\x01SIG".to_vec();
+    test_server.send_message(heartbeat);
+    let message = engine_poll_message!(client,connection,Heartbeat);
+    assert_eq!(message.signature(),Some(&b"REMOTE\x01SIG"[..]));
+
+    //Without a signer, messages keep the signature they were given.
+    client.set_message_signer(connection,None);
+    let mut test_request = new_fixt_message!(FROM_CLIENT TestRequest);
+    test_request.test_req_id = b"2".to_vec();
+    test_request.signature = b"MANUAL".to_vec();
+    client.send_message(connection,test_request);
+    let message = test_server.recv_message::<TestRequest>();
+    assert_eq!(message.signature,b"MANUAL");
+}
+
+#[test]
+fn test_message_statistics() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+    );
+
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID)
+        .message_statistics(2);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_connection_builder(connection_builder,build_dictionary());
+
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let message = test_server.recv_message::<Logon>();
+    let mut response_message = new_fixt_message!(Logon);
+    response_message.encrypt_method = message.encrypt_method;
+    response_message.heart_bt_int = message.heart_bt_int;
+    response_message.default_appl_ver_id = message.default_appl_ver_id;
+    test_server.send_message(response_message);
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Only the first and third orders are sampled but all three are counted.
+    for cl_ord_id in 1..4 {
+        let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+        new_order_single.cl_ord_id = cl_ord_id.to_string().into_bytes();
+        new_order_single.symbol = b"VOD.L".to_vec();
+        new_order_single.side = Side::Buy;
+        new_order_single.transact_time = new_order_single.sending_time;
+        new_order_single.order_qty = b"100".to_vec();
+        new_order_single.ord_type = OrdType::Market;
+        client.send_message(connection,new_order_single);
+        let _ = test_server.recv_message::<NewOrderSingle>();
+    }
+
+    let report = client.message_statistics(connection).unwrap();
+    assert_eq!(report.inbound.len(),1);
+    assert_eq!(report.inbound[0].msg_type,Logon::msg_type());
+    assert_eq!(report.inbound[0].message_count,1);
+    assert!(report.inbound[0].sizes.min > 0);
+
+    assert_eq!(report.outbound.len(),2);
+    assert_eq!(report.outbound[0].msg_type,Logon::msg_type());
+    assert_eq!(report.outbound[1].msg_type,NewOrderSingle::msg_type());
+    let statistics = &report.outbound[1];
+    assert_eq!(statistics.message_count,3);
+    assert_eq!(statistics.sizes.count,2);
+    assert_eq!(statistics.field_counts.count,2);
+    assert_eq!(statistics.field_counts.min,statistics.field_counts.max);
+    assert_eq!(statistics.sizes.buckets.iter().map(|&(_,count)| count).sum::<u64>(),2);
+    assert!(statistics.top_tags.len() <= 10);
+    assert!(statistics.top_tags.iter().all(|&(_,count)| count == 2));
+    assert!(statistics.top_tags.contains(&(ClOrdID::tag(),2)));
+
+    //Turning statistics off throws away what was collected.
+    client.set_message_statistics(connection,None);
+    assert!(client.message_statistics(connection).is_none());
+}
+
+#[test]
+fn test_third_party_routing() {
+    define_dictionary!(
+        Logon,
+        Heartbeat,
+        NewOrderSingle,
+        Reject,
+        TestRequest,
+    );
+
+    //Connect and logon. Only accept messages sent on behalf of FIRM_A and never accept messages
+    //that still need to be delivered to someone else.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    let mut third_party_routing_settings = ThirdPartyRoutingSettings::new();
+    third_party_routing_settings.reject_deliver_to = true;
+    third_party_routing_settings.allowed_on_behalf_of_comp_ids = Some(vec![b"FIRM_A".to_vec()]);
+    client.set_third_party_routing_settings(connection,third_party_routing_settings);
+
+    //Outbound routing is taken from the message as is.
+    let mut new_order_single = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    new_order_single.cl_ord_id = b"0".to_vec();
+    new_order_single.symbol = b"TEST".to_vec();
+    new_order_single.security_id = b"0".to_vec();
+    new_order_single.security_id_source = Some(SecurityIDSource::CUSIP);
+    new_order_single.side = Side::Buy;
+    new_order_single.transact_time = new_order_single.sending_time;
+    new_order_single.order_qty = b"1".to_vec();
+    new_order_single.ord_type = OrdType::Market;
+    new_order_single.set_third_party_routing(&ThirdPartyRouting::deliver_to(b"FIRM_A"));
+    client.send_message(connection,new_order_single);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.deliver_to_comp_id,b"FIRM_A");
+    assert!(message.on_behalf_of_comp_id.is_empty());
+
+    //Inbound routing is exposed to the application and automatic responses are addressed back
+    //through the hub.
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 2;
+    message.test_req_id = b"test".to_vec();
+    message.on_behalf_of_comp_id = b"FIRM_A".to_vec();
+    test_server.send_message(message);
+    let message = engine_poll_message!(client,connection,TestRequest);
+    assert_eq!(message.third_party_routing(),ThirdPartyRouting::on_behalf_of(b"FIRM_A"));
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"test");
+    assert_eq!(message.third_party_routing(),ThirdPartyRouting::deliver_to(b"FIRM_A"));
+
+    //Messages on behalf of a firm that isn't allowed are rejected without ending the session.
+    let mut message = new_fixt_message!(Heartbeat);
+    message.msg_seq_num = 3;
+    message.on_behalf_of_comp_id = b"FIRM_B".to_vec();
+    test_server.send_message(message);
+    engine_poll_event!(client,EngineEvent::MessageRejected(msg_connection,rejected_message) => {
+        assert_eq!(msg_connection,connection);
+        assert!(rejected_message.as_any().is::<Heartbeat>());
+    });
+    let message = test_server.recv_message::<Reject>();
+    assert_eq!(message.ref_seq_num,3);
+    assert_eq!(message.ref_tag_id,b"115");
+    assert_eq!(message.session_reject_reason.unwrap(),SessionRejectReason::CompIDProblem);
+    assert_eq!(message.deliver_to_comp_id,b"FIRM_B");
+
+    //Same for messages that still have to be delivered somewhere else.
+    let mut message = new_fixt_message!(Heartbeat);
+    message.msg_seq_num = 4;
+    message.deliver_to_comp_id = b"FIRM_C".to_vec();
+    test_server.send_message(message);
+    engine_poll_event!(client,EngineEvent::MessageRejected(_,_) => {});
+    let message = test_server.recv_message::<Reject>();
+    assert_eq!(message.ref_seq_num,4);
+    assert_eq!(message.ref_tag_id,b"128");
+
+    //The session continues with the next MsgSeqNum.
+    let mut message = new_fixt_message!(Heartbeat);
+    message.msg_seq_num = 5;
+    test_server.send_message(message);
+    let message = engine_poll_message!(client,connection,Heartbeat);
+    assert_eq!(message.msg_seq_num,5);
+}
+
+#[test]
+fn test_inbound_decoder() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        Reject,
+        TestMessage,
+    );
+
+    //Unwraps frames made up of a one byte length followed by that many bytes. A length of zero is
+    //treated as corrupt.
+    struct LengthPrefixedDecoder {
+        pending: Vec<u8>,
+    }
+    impl InboundDecoder for LengthPrefixedDecoder {
+        fn decode(&mut self,bytes: &[u8],output: &mut Vec<u8>) -> Result<(),io::Error> {
+            self.pending.extend_from_slice(bytes);
+            while !self.pending.is_empty() {
+                let frame_len = self.pending[0] as usize;
+                if frame_len == 0 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData,"Empty frame"));
+                }
+                else if self.pending.len() < frame_len + 1 {
+                    break;
+                }
+
+                output.extend_from_slice(&self.pending[1..frame_len + 1]);
+                self.pending.drain(..frame_len + 1);
+            }
+
+            Ok(())
+        }
+    }
+
+    //Connect and logon without any encoding and then start decoding.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50,build_dictionary());
+    client.set_inbound_decoder(connection,Box::new(LengthPrefixedDecoder { pending: Vec::new() }));
+    let _ = client.connection_info(connection); //Wait until the engine thread has applied it.
+
+    //Send two messages wrapped in small frames. Write them in pieces that split frames so the
+    //decoder has to wait for the rest.
+    let mut message_bytes = Vec::new();
+    for (msg_seq_num,text) in vec![b"first".to_vec(),b"second".to_vec()].into_iter().enumerate() {
+        let mut message = new_fixt_message!(TestMessage);
+        message.msg_seq_num = msg_seq_num as u64 + 2;
+        message.text = text;
+        serialize_and_append_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50,&mut message_bytes);
+    }
+    let mut encoded_bytes = Vec::new();
+    for frame in message_bytes.chunks(50) {
+        encoded_bytes.push(frame.len() as u8);
+        encoded_bytes.extend_from_slice(frame);
+    }
+    for piece in encoded_bytes.chunks(37) {
+        test_server.stream.write_all(piece).unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let message = engine_poll_message!(client,connection,TestMessage);
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.text,b"first");
+    let message = engine_poll_message!(client,connection,TestMessage);
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.text,b"second");
+
+    //A decoding error terminates the connection.
+    test_server.stream.write_all(&[0]).unwrap();
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,_) => {
+        assert_eq!(terminated_connection,connection);
+    });
+}
+
+#[test]
+fn test_skip_duplicate_parsing() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    client.set_skip_duplicate_parsing(connection,true);
+    let _ = client.connection_info(connection); //Wait until the engine thread has applied it.
+
+    //Duplicates of MsgSeqNums that were already processed are thrown away without being parsed.
+    let mut message = new_fixt_message!(TestRequest);
+    message.test_req_id = b"duplicate".to_vec();
+    test_server.send_poss_dup_message(1,message);
+    engine_poll_event!(client,EngineEvent::MessageReceivedDuplicateSkipped(data) => {
+        assert_eq!(data,MessageReceivedDuplicateSkippedData {
+            connection: connection,
+            msg_type: b"1".to_vec(),
+            msg_seq_num: 1,
+        });
+    });
+
+    //The expected MsgSeqNum is still processed as normal.
+    let mut message = new_fixt_message!(TestRequest);
+    message.test_req_id = b"new".to_vec();
+    test_server.send_message_with_msg_seq_num(2,message);
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"new");
+
+    //Turning it off reports duplicates with the fully parsed message again.
+    client.set_skip_duplicate_parsing(connection,false);
+    test_server.send_poss_dup_message(2,new_fixt_message!(TestRequest));
+    engine_poll_event!(client,EngineEvent::MessageReceivedDuplicate(duplicate_connection,message) => {
+        assert_eq!(duplicate_connection,connection);
+        assert_eq!(message.msg_seq_num(),2);
+    });
+}
+
+#[test]
+fn test_admin_message_delivery() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+        TestRequest,
+    );
+
+    fn setup(admin_message_delivery: AdminMessageDelivery) -> (TestStream,fixt::engine::Engine,Connection) {
+        let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE)
+            .admin_message_delivery(admin_message_delivery);
+        let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+        let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_builders(engine_builder,connection_builder,build_dictionary());
+        client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+        let _ = test_server.recv_message::<Logon>();
+        test_server.send_message(new_logon_message());
+        engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+
+        //Logon is not one of the plumbing messages so it's always delivered as normal.
+        let _ = engine_poll_message!(client,connection,Logon);
+
+        (test_server,client,connection)
+    }
+
+    //Separate delivers plumbing messages using their own event.
+    let (mut test_server,mut client,connection) = setup(AdminMessageDelivery::Separate);
+    test_server.send_message_with_msg_seq_num(2,new_fixt_message!(Heartbeat));
+    engine_poll_event!(client,EngineEvent::AdminMessageReceived(msg_connection,message) => {
+        assert_eq!(msg_connection,connection);
+        assert!(message.as_any().is::<Heartbeat>());
+        assert_eq!(message.msg_seq_num(),2);
+    });
+
+    //Suppress never delivers them but the engine still responds to them.
+    let (mut test_server,mut client,connection) = setup(AdminMessageDelivery::Suppress);
+    let mut message = new_fixt_message!(TestRequest);
+    message.test_req_id = b"test_id".to_vec();
+    test_server.send_message_with_msg_seq_num(2,message);
+    let message = test_server.recv_message::<Heartbeat>();
+    assert_eq!(message.test_req_id,b"test_id");
+    test_server.send_message_with_msg_seq_num(3,new_fixt_message!(Heartbeat));
+    assert_eq!(client.connection_info(connection).unwrap().inbound_msg_seq_num,4);
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+
+    //Text on a suppressed message is still reported.
+    let mut message = new_fixt_message!(Heartbeat);
+    message.text = b"Throttle limit approaching".to_vec();
+    test_server.send_message_with_msg_seq_num(4,message);
+    engine_poll_event!(client,EngineEvent::AdminNotice(data) => {
+        assert_eq!(data.connection,connection);
+        assert_eq!(data.msg_type,Heartbeat::msg_type());
+        assert_eq!(data.msg_seq_num,4);
+        assert_eq!(data.text,b"Throttle limit approaching");
+    });
+    assert!(client.poll(Duration::from_millis(250)).is_none());
+}
+
+#[test]
+fn test_shadow_connection() {
+    define_fixt_message!(TestMessage: b"9999" => {
+        NOT_REQUIRED, text: Text [FIX50..],
+    });
+
+    define_dictionary!(
+        Logon,
+        TestMessage,
+    );
+
+    fn new_test_message(text: &[u8]) -> TestMessage {
+        let mut message = new_fixt_message!(FROM_CLIENT TestMessage);
+        message.text = text.to_vec();
+
+        message
+    }
+
+    //Connect and logon to both production and the shadow.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+    let (mut shadow_server,shadow_connection) = TestStream::setup_test_server_with_engine(&mut client,test_server.clock.clone(),connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(shadow_connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let _ = shadow_server.recv_message::<Logon>();
+    shadow_server.send_message(new_logon_message());
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,shadow_connection,Logon);
+    client.set_shadow_connection(connection,shadow_connection);
+
+    //Messages sent directly on the shadow connection aren't mirrored back.
+    client.send_message(shadow_connection,new_test_message(b"direct"));
+    let message = shadow_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert!(message.test_message_indicator.is_empty());
+
+    //Application messages sent on production are copied to the shadow with its own MsgSeqNum and
+    //marked as test messages.
+    client.send_message(connection,new_test_message(b"mirrored"));
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.text,b"mirrored");
+    assert!(message.test_message_indicator.is_empty());
+    let message = shadow_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.text,b"mirrored");
+    assert_eq!(message.test_message_indicator,b"Y");
+
+    //Stop mirroring.
+    client.set_shadow_connection(connection,None);
+    client.send_message(connection,new_test_message(b"production only"));
+    let message = test_server.recv_message::<TestMessage>();
+    assert_eq!(message.msg_seq_num,3);
+    assert!(shadow_server.try_recv_fixt_message(Duration::from_millis(250)).is_none());
+}
+
+#[test]
+fn test_rotate_session_identity() {
+    define_dictionary!(
+        Logon,
+        Logout,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    let user_data: Arc<Any + Send + Sync> = Arc::new(String::from("session"));
+    client.set_user_data(connection,user_data);
+
+    //Rotate to a new SenderCompID at a new address while keeping the user data.
+    let address = new_test_address();
+    let listener = TcpListener::bind(&address).unwrap();
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,b"NEWTEST",CLIENT_TARGET_COMP_ID);
+    let migration = SessionMigration {
+        user_data: true,
+        ..SessionMigration::default()
+    };
+    let new_connection = client.rotate_session_identity(connection,connection_builder,address,migration).unwrap();
+    assert!(new_connection != connection);
+    let mut new_stream = accept_with_timeout(&listener,Duration::from_secs(5)).expect("Could not accept connection");
+    engine_poll_event!(client,EngineEvent::ConnectionSucceeded(succeeded_connection) => {
+        assert_eq!(succeeded_connection,new_connection);
+    });
+    assert_eq!(client.user_data(new_connection).unwrap().downcast_ref::<String>().unwrap(),"session");
+
+    //The new session uses the new identity and starts from MsgSeqNum 1.
+    client.send_message_box_with_message_version(new_connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let bytes = recv_bytes_with_timeout(&mut new_stream,Duration::from_secs(5)).unwrap();
+    assert!(bytes.windows(12).any(|window| window == b"\x0149=NEWTEST\x01"));
+    assert!(bytes.windows(6).any(|window| window == b"\x0134=1\x01"));
+
+    //The old session is logged out cleanly.
+    let _ = test_server.recv_message::<Logout>();
+    let mut message = new_fixt_message!(Logout);
+    message.msg_seq_num = 2;
+    test_server.send_message(message);
+    engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+        assert_eq!(terminated_connection,connection);
+        assert!(if let ConnectionTerminatedReason::LocalRequested = reason { true } else { false });
+    });
+}
@@ -0,0 +1,97 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::round_trip;
+use fix_rs::dictionary::field_types::other::{CollAsgnReason,CollAsgnRejectReason,CollAsgnRespType,CollAsgnTransType,CollInquiryQualifier,CollStatus};
+use fix_rs::dictionary::fields::{CollInqQualGrp,TrdCollGrp};
+use fix_rs::dictionary::messages::{CollateralAssignment,CollateralInquiry,CollateralReport,CollateralResponse};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_collateral_assignment() {
+    let mut message = new_fixt_message!(CollateralAssignment);
+    message.coll_asgn_id = b"ASGN2".to_vec();
+    message.coll_asgn_reason = CollAsgnReason::MarginDeficiency;
+    message.coll_asgn_trans_type = CollAsgnTransType::Replace;
+    message.coll_asgn_ref_id = b"ASGN1".to_vec();
+    message.transact_time = message.sending_time;
+    message.account = b"ACCOUNT".to_vec();
+    message.quantity = b"1000".to_vec();
+    message.currency = b"USD".to_vec();
+    for trade_report_id in &[&b"T1"[..],&b"T2"[..]] {
+        let mut group = TrdCollGrp::new();
+        group.trade_report_id = trade_report_id.to_vec();
+        message.no_trades.push(Box::new(group));
+    }
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.coll_asgn_id,b"ASGN2");
+    assert_eq!(parsed_message.coll_asgn_reason,CollAsgnReason::MarginDeficiency);
+    assert_eq!(parsed_message.coll_asgn_trans_type,CollAsgnTransType::Replace);
+    assert_eq!(parsed_message.coll_asgn_ref_id,b"ASGN1");
+    assert_eq!(parsed_message.quantity,b"1000");
+    assert_eq!(parsed_message.no_trades.len(),2);
+    assert_eq!(parsed_message.no_trades[1].trade_report_id,b"T2");
+
+    let mut message = new_fixt_message!(CollateralResponse);
+    message.coll_resp_id = b"RESP1".to_vec();
+    message.coll_asgn_id = b"ASGN2".to_vec();
+    message.coll_asgn_reason = CollAsgnReason::MarginDeficiency;
+    message.coll_asgn_resp_type = CollAsgnRespType::Rejected;
+    message.coll_asgn_reject_reason = Some(CollAsgnRejectReason::InsufficientCollateral);
+    message.transact_time = message.sending_time;
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.coll_asgn_resp_type,CollAsgnRespType::Rejected);
+    assert_eq!(parsed_message.coll_asgn_reject_reason,Some(CollAsgnRejectReason::InsufficientCollateral));
+}
+
+#[test]
+fn test_collateral_inquiry() {
+    let mut message = new_fixt_message!(CollateralInquiry);
+    message.coll_inquiry_id = b"INQ1".to_vec();
+    let mut group = CollInqQualGrp::new();
+    group.coll_inquiry_qualifier = CollInquiryQualifier::OutstandingTrades;
+    message.no_coll_inquiry_qualifier.push(Box::new(group));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.no_coll_inquiry_qualifier.len(),1);
+    assert_eq!(parsed_message.no_coll_inquiry_qualifier[0].coll_inquiry_qualifier,CollInquiryQualifier::OutstandingTrades);
+
+    let mut message = new_fixt_message!(CollateralReport);
+    message.coll_rpt_id = b"RPT1".to_vec();
+    message.coll_inquiry_id = b"INQ1".to_vec();
+    message.coll_status = CollStatus::PartiallyAssigned;
+    message.tot_num_reports = b"1".to_vec();
+    message.last_rpt_requested = true;
+    message.margin_excess = b"-250".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.coll_inquiry_id,b"INQ1");
+    assert_eq!(parsed_message.coll_status,CollStatus::PartiallyAssigned);
+    assert_eq!(parsed_message.last_rpt_requested,true);
+    assert_eq!(parsed_message.margin_excess,b"-250");
+}
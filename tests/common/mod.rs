@@ -20,18 +20,23 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::net::{Ipv4Addr,SocketAddr,SocketAddrV4};
 use std::io::Read;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize,Ordering};
 use std::thread;
 use std::time::{Duration,Instant};
 
 use fix_rs::byte_buffer::ByteBuffer;
-use fix_rs::dictionary::CloneDictionary;
-use fix_rs::dictionary::field_types::other::EncryptMethod;
-use fix_rs::dictionary::messages::Logon;
+use fix_rs::clock::SimulatedClock;
+use fix_rs::dictionary::field_types::generic::UTCTimestampFieldType;
+use fix_rs::dictionary::field_types::other::{EncryptMethod,OrdType,Side};
+use fix_rs::dictionary::fields::SendingTime;
+use fix_rs::dictionary::messages::{Logon,NewOrderSingle,SequenceReset};
+use fix_rs::field::Field;
+use fix_rs::field_type::FieldType;
 use fix_rs::fix::Parser;
 use fix_rs::fix_version::FIXVersion;
-use fix_rs::fixt::engine::{Engine,EngineEvent,Connection,Listener};
-use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage};
+use fix_rs::fixt::engine::{ConnectionAcceptedData,ConnectionBuilder,ConnectionLoggingOnData,Engine,EngineBuilder,EngineEvent,Connection,Listener,ListenerSettings};
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
 use fix_rs::message_version::MessageVersion;
 
 const SOCKET_BASE_PORT: usize = 7000;
@@ -42,7 +47,13 @@ pub const CLIENT_SENDER_COMP_ID: &'static [u8] = b"TEST";
 pub const SERVER_TARGET_COMP_ID: &'static [u8] = CLIENT_SENDER_COMP_ID;
 pub const SERVER_SENDER_COMP_ID: &'static [u8] = CLIENT_TARGET_COMP_ID;
 
-const MAX_MESSAGE_SIZE: u64 = 4096;
+pub const MAX_MESSAGE_SIZE: u64 = 4096;
+
+//Shared between the Engine and every TestStream so the dictionary is only built once per test.
+pub type MessageDictionary = Arc<HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>>;
+
+//How long advance_clock() waits in real time before moving the simulated clock forward.
+const CLOCK_SETTLE_MS: u64 = 50;
 
 //Helper function to make it easier to figure out what the body_length tag should be set to.
 #[allow(unused)]
@@ -147,6 +158,11 @@ macro_rules! new_fixt_message {
     }};
 }
 
+pub fn new_test_address() -> SocketAddr {
+    //Unique local address for each test so they can run at the same time.
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127,0,0,1),SOCKET_PORT.fetch_add(1,Ordering::SeqCst) as u16))
+}
+
 pub fn new_logon_message() -> Logon {
     let mut message = new_fixt_message!(FROM_SERVER Logon);
     message.encrypt_method = EncryptMethod::None;
@@ -156,6 +172,41 @@ pub fn new_logon_message() -> Logon {
     message
 }
 
+pub fn new_order_single(cl_ord_id: &[u8]) -> NewOrderSingle {
+    let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    message.cl_ord_id = cl_ord_id.to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.transact_time = message.sending_time;
+    message.order_qty = b"100".to_vec();
+    message.ord_type = OrdType::Limit;
+    message.price = b"9.5".to_vec();
+
+    message
+}
+
+pub fn serialize_message(message: &FIXTMessage,fix_version: FIXVersion,message_version: MessageVersion) -> Vec<u8> {
+    let mut bytes = ByteBuffer::new();
+    message.read(fix_version,message_version,&mut bytes);
+
+    bytes.bytes().to_vec()
+}
+
+pub fn round_trip<T: FIXTMessage + FIXTMessageBuildable + Default + Clone + 'static>(message: &T,fix_version: FIXVersion,message_version: MessageVersion) -> T {
+    //Serialize message and parse it back using a dictionary with only its own type.
+    let bytes = serialize_message(message,fix_version,message_version);
+
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(message.msg_type(),<T as Default>::default().builder());
+    let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+    parser.set_default_message_version(message_version);
+    let (_,result) = parser.parse(&bytes[..]);
+    assert!(result.is_ok());
+    assert_eq!(parser.messages.len(),1);
+
+    parser.messages[0].as_any().downcast_ref::<T>().unwrap().clone()
+}
+
 pub fn accept_with_timeout(listener: &TcpListener,timeout: Duration) -> Option<TcpStream> {
     let now = Instant::now();
 
@@ -221,12 +272,14 @@ pub struct TestStream {
     fix_version: FIXVersion,
     message_version: MessageVersion,
     pub stream: TcpStream,
+    pub clock: SimulatedClock, //Same clock used by the Engine on the other end.
     poll: Poll,
     parser: Parser,
+    classic: bool, //Received messages must be framed as a classic FIX.4.x session. See setup_test_server_and_logon_classic().
 }
 
 impl TestStream {
-    fn new(fix_version: FIXVersion,message_version: MessageVersion,stream: TcpStream,message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> TestStream {
+    fn new(fix_version: FIXVersion,message_version: MessageVersion,stream: TcpStream,clock: SimulatedClock,message_dictionary: MessageDictionary) -> TestStream {
         //Setup a single Poll to watch the TCPStream. This way we can check for disconnects in
         //is_stream_closed(). Unfortunately, as of mio 0.6.1, Linux implementation emulates OS X
         //and Windows where a stream can only be registered with one Poll for the life of the
@@ -234,26 +287,52 @@ impl TestStream {
         let poll = Poll::new().unwrap();
         poll.register(&stream,Token(0),Ready::readable() | Ready::writable() | UnixReady::hup() | UnixReady::error(),PollOpt::edge()).unwrap();
 
-        let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+        let mut parser = Parser::with_shared_dictionary(message_dictionary,MAX_MESSAGE_SIZE);
         parser.set_default_message_version(message_version);
 
         TestStream {
             fix_version: fix_version,
             message_version: message_version,
             stream: stream,
+            clock: clock,
             poll: poll,
-            parser: parser
+            parser: parser,
+            classic: false,
         }
     }
 
-    pub fn setup_test_server_with_ver(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Connection) {
+    pub fn setup_test_server_with_ver<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: D) -> (TestStream,Engine,Connection) {
+        let connection_builder = ConnectionBuilder::new(fix_version,message_version,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+        Self::setup_test_server_with_connection_builder(connection_builder,message_dictionary)
+    }
+
+    pub fn setup_test_server_with_connection_builder<D: Into<MessageDictionary>>(connection_builder: ConnectionBuilder,message_dictionary: D) -> (TestStream,Engine,Connection) {
+        let message_dictionary: MessageDictionary = message_dictionary.into();
+        let engine_builder = EngineBuilder::new(message_dictionary.clone(),MAX_MESSAGE_SIZE);
+        Self::setup_test_server_with_builders(engine_builder,connection_builder,message_dictionary)
+    }
+
+    pub fn setup_test_server_with_builders<D: Into<MessageDictionary>>(engine_builder: EngineBuilder,connection_builder: ConnectionBuilder,message_dictionary: D) -> (TestStream,Engine,Connection) {
+        let clock = SimulatedClock::new();
+        let mut client = engine_builder.thread_name("fix-rs test client").clock(clock.clone()).build().unwrap();
+        let (test_server,connection) = Self::setup_test_server_with_engine(&mut client,clock,connection_builder,message_dictionary);
+
+        (test_server,client,connection)
+    }
+
+    pub fn setup_test_server_with_engine<D: Into<MessageDictionary>>(client: &mut Engine,clock: SimulatedClock,connection_builder: ConnectionBuilder,message_dictionary: D) -> (TestStream,Connection) {
+        //Same as setup_test_server_with_builders() except an existing Engine is used. Useful for
+        //testing what happens when a session reconnects. clock must be the one the Engine was built
+        //with.
+        let fix_version = connection_builder.fix_version();
+        let message_version = connection_builder.default_message_version();
+
         //Setup server listener socket.
         let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127,0,0,1),SOCKET_PORT.fetch_add(1,Ordering::SeqCst) as u16));
         let listener = TcpListener::bind(&addr).unwrap();
 
-        //Setup client and connect to socket.
-        let mut client = Engine::new(message_dictionary.clone(),MAX_MESSAGE_SIZE).unwrap();
-        let connection = client.add_connection(fix_version,message_version,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,addr).unwrap();
+        //Connect client to socket.
+        let connection = client.add_connection_with_builder(connection_builder,addr).unwrap();
 
         //Try to accept connection from client. Fails on timeout or socket error.
         let stream = accept_with_timeout(&listener,Duration::from_secs(5)).expect("Could not accept connection");
@@ -262,16 +341,15 @@ impl TestStream {
         let event = client.poll(Duration::from_secs(5)).expect("Could not connect");
         assert!(if let EngineEvent::ConnectionSucceeded(success_connection) = event { success_connection == connection } else { false });
 
-        (TestStream::new(fix_version,message_version,stream,message_dictionary),
-         client,
+        (TestStream::new(fix_version,message_version,stream,clock,message_dictionary.into()),
          connection)
     }
 
-    pub fn setup_test_server(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Connection) {
+    pub fn setup_test_server<D: Into<MessageDictionary>>(message_dictionary: D) -> (TestStream,Engine,Connection) {
         Self::setup_test_server_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,message_dictionary)
     }
 
-    pub fn setup_test_server_and_logon_with_ver(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Connection) {
+    pub fn setup_test_server_and_logon_with_ver<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: D) -> (TestStream,Engine,Connection) {
         //Connect.
         let (mut test_server,mut client,connection) = Self::setup_test_server_with_ver(fix_version,message_version,message_dictionary);
         test_server.parser.set_default_message_version(MessageVersion::FIX50);
@@ -299,40 +377,80 @@ impl TestStream {
         (test_server,client,connection)
     }
 
-    pub fn setup_test_server_and_logon(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Connection) {
+    pub fn setup_test_server_and_logon<D: Into<MessageDictionary>>(message_dictionary: D) -> (TestStream,Engine,Connection) {
         Self::setup_test_server_and_logon_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,message_dictionary)
     }
 
-    pub fn setup_test_client_with_ver(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Listener,Connection) {
+    pub fn setup_test_server_and_logon_classic<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_dictionary: D) -> (TestStream,Engine,Connection) {
+        //Act as a classic FIX.4.x counterparty instead of a FIXT one. There's no DefaultApplVerID
+        //to negotiate so every message uses the version implied by BeginString. Every message
+        //received afterwards is checked to make sure the Engine didn't use any FIXT framing.
+        assert!(fix_version != FIXVersion::FIXT_1_1);
+        let (mut test_server,client,connection) = Self::setup_test_server_and_logon_with_ver(fix_version,fix_version.max_message_version(),message_dictionary);
+        test_server.classic = true;
+
+        (test_server,client,connection)
+    }
+
+    pub fn setup_test_clients_with_ver<D: Into<MessageDictionary>>(count: usize,fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: D) -> (Vec<TestStream>,Engine,Listener,Vec<Connection>) {
+        let message_dictionary: MessageDictionary = message_dictionary.into();
+        let engine_builder = EngineBuilder::new(message_dictionary.clone(),MAX_MESSAGE_SIZE);
+        Self::setup_test_clients_with_builder(count,fix_version,message_version,engine_builder,message_dictionary)
+    }
+
+    pub fn setup_test_clients_with_builder<D: Into<MessageDictionary>>(count: usize,fix_version: FIXVersion,message_version: MessageVersion,engine_builder: EngineBuilder,message_dictionary: D) -> (Vec<TestStream>,Engine,Listener,Vec<Connection>) {
+        Self::setup_test_clients_with_listener_settings(count,fix_version,message_version,engine_builder,ListenerSettings::default(),message_dictionary)
+    }
+
+    pub fn setup_test_clients_with_listener_settings<D: Into<MessageDictionary>>(count: usize,fix_version: FIXVersion,message_version: MessageVersion,engine_builder: EngineBuilder,listener_settings: ListenerSettings,message_dictionary: D) -> (Vec<TestStream>,Engine,Listener,Vec<Connection>) {
+        let message_dictionary: MessageDictionary = message_dictionary.into();
+
         //Setup client and listener.
         let addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127,0,0,1),SOCKET_PORT.fetch_add(1,Ordering::SeqCst) as u16));
-        let mut client = Engine::new(message_dictionary.clone(),MAX_MESSAGE_SIZE).unwrap();
-        let listener = client.add_listener(SERVER_SENDER_COMP_ID,&addr).unwrap().unwrap();
+        let clock = SimulatedClock::new();
+        let mut client = engine_builder.clock(clock.clone()).build().unwrap();
+        let listener = client.add_listener_with_settings(SERVER_SENDER_COMP_ID,&addr,listener_settings).unwrap().unwrap();
+
+        let mut test_streams = Vec::new();
+        let mut connections = Vec::new();
+        for _ in 0..count {
+            //Setup a client socket and connect to server.
+            let stream = TcpStream::connect(&addr).unwrap();
+
+            //Confirm client was able to connect.
+            let event = client.poll(Duration::from_secs(5)).expect("Could not accept");
+            let connection = match event {
+                EngineEvent::ConnectionAccepted(ConnectionAcceptedData { listener: success_listener, connection: accepted_connection, address: _ }) => {
+                    assert_eq!(success_listener,listener);
+                    accepted_connection
+                },
+                _ => panic!("Unexpected event")
+            };
+
+            test_streams.push(TestStream::new(fix_version,message_version,stream,clock.clone(),message_dictionary.clone()));
+            connections.push(connection);
+        }
 
-        //Setup a client socket and connect to server.
-        let stream = TcpStream::connect(&addr).unwrap();
+        (test_streams,
+         client,
+         listener,
+         connections)
+    }
 
-        //Confirm client was able to connect.
-        let event = client.poll(Duration::from_secs(5)).expect("Could not accept");
-        let connection = match event {
-            EngineEvent::ConnectionAccepted(success_listener,accepted_connection,_) => {
-                assert_eq!(success_listener,listener);
-                accepted_connection
-            },
-            _ => panic!("Unexpected event")
-        };
-
-        (TestStream::new(fix_version,message_version,stream,message_dictionary),
+    pub fn setup_test_client_with_ver<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: D) -> (TestStream,Engine,Listener,Connection) {
+        let (mut test_streams,client,listener,mut connections) = Self::setup_test_clients_with_ver(1,fix_version,message_version,message_dictionary);
+
+        (test_streams.remove(0),
          client,
          listener,
-         connection)
+         connections.remove(0))
     }
 
-    pub fn setup_test_client(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Listener,Connection) {
+    pub fn setup_test_client<D: Into<MessageDictionary>>(message_dictionary: D) -> (TestStream,Engine,Listener,Connection) {
         Self::setup_test_client_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,message_dictionary)
     }
 
-    pub fn setup_test_client_and_logon_with_ver(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Listener,Connection) {
+    pub fn setup_test_client_and_logon_with_ver<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_version: MessageVersion,message_dictionary: D) -> (TestStream,Engine,Listener,Connection) {
         //Connect.
         let (mut test_client,mut engine,listener,connection) = Self::setup_test_client_with_ver(fix_version,message_version,message_dictionary);
         test_client.parser.set_default_message_version(MessageVersion::FIX50);
@@ -344,7 +462,7 @@ impl TestStream {
         logon_message.default_appl_ver_id = message_version;
         test_client.send_message_with_ver(fix_version,fix_version.max_message_version(),logon_message);
 
-        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,logon_message) => {
+        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
             assert_eq!(some_listener,listener);
             assert_eq!(some_connection,connection);
             assert_eq!(logon_message.msg_seq_num,1);
@@ -366,10 +484,27 @@ impl TestStream {
         (test_client,engine,listener,connection)
     }
 
-    pub fn setup_test_client_and_logon(message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>>) -> (TestStream,Engine,Listener,Connection) {
+    pub fn setup_test_client_and_logon<D: Into<MessageDictionary>>(message_dictionary: D) -> (TestStream,Engine,Listener,Connection) {
         Self::setup_test_client_and_logon_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,message_dictionary)
     }
 
+    pub fn setup_test_client_and_logon_classic<D: Into<MessageDictionary>>(fix_version: FIXVersion,message_dictionary: D) -> (TestStream,Engine,Listener,Connection) {
+        //Same as setup_test_server_and_logon_classic() except the Engine is the acceptor.
+        assert!(fix_version != FIXVersion::FIXT_1_1);
+        let (mut test_client,engine,listener,connection) = Self::setup_test_client_and_logon_with_ver(fix_version,fix_version.max_message_version(),message_dictionary);
+        test_client.classic = true;
+
+        (test_client,engine,listener,connection)
+    }
+
+    pub fn advance_clock(&self,duration: Duration) {
+        //Move the Engine's time forward instead of sleeping. Wait a moment in real time first so
+        //the engine's thread can finish handling whatever was just sent or received. Otherwise, a
+        //timeout could expire before it was supposed to be reset.
+        thread::sleep(Duration::from_millis(CLOCK_SETTLE_MS));
+        self.clock.advance(duration);
+    }
+
     pub fn is_stream_closed(&self,timeout: Duration) -> bool {
         let now = Instant::now();
 
@@ -390,9 +525,20 @@ impl TestStream {
         false
     }
 
+    fn take_parsed_message(&mut self) -> Box<FIXTMessage + Send> {
+        let message = self.parser.messages.remove(0);
+        if self.classic {
+            let meta = message.meta().as_ref().expect("Meta should be set by parser");
+            assert_eq!(meta.begin_string,self.fix_version);
+            assert_eq!(meta.message_version,self.fix_version.max_message_version());
+        }
+
+        message
+    }
+
     pub fn try_recv_fixt_message(&mut self,timeout: Duration) -> Option<Box<FIXTMessage + Send>> {
         if !self.parser.messages.is_empty() {
-            return Some(self.parser.messages.remove(0));
+            return Some(self.take_parsed_message());
         }
 
         let now = Instant::now();
@@ -409,6 +555,13 @@ impl TestStream {
                 continue;
             };
 
+            //ApplVerID(1128) and friends only exist in FIXT headers.
+            if self.classic {
+                for fixt_tag in &[b"\x011128=",b"\x011129=",b"\x011156="] {
+                    assert!(!buffer[..bytes_read].windows(fixt_tag.len()).any(|bytes| bytes == &fixt_tag[..]),"Classic session received FIXT header field");
+                }
+            }
+
             let mut total_bytes_parsed = 0;
             while total_bytes_parsed < bytes_read {
                 let (bytes_parsed,result) = self.parser.parse(&buffer[total_bytes_parsed..bytes_read]);
@@ -422,7 +575,7 @@ impl TestStream {
             }
 
             if !self.parser.messages.is_empty() {
-                return Some(self.parser.messages.remove(0));
+                return Some(self.take_parsed_message());
             }
         }
 
@@ -457,5 +610,83 @@ impl TestStream {
         let message_version = self.message_version;
         send_message_with_timeout(&mut self.stream,fix_version,message_version,Box::new(message),Some(timeout))
     }
+
+    //Send a message using the given MsgSeqNum regardless of what was set in the message. Picking a
+    //MsgSeqNum past the next expected one simulates a counterparty that skipped sequence numbers.
+    pub fn send_message_with_msg_seq_num<T: FIXTMessage + Any + Send>(&mut self,msg_seq_num: u64,mut message: T) {
+        let sender_comp_id = message.sender_comp_id().clone();
+        let target_comp_id = message.target_comp_id().clone();
+        message.setup_fixt_session_header(Some(msg_seq_num),sender_comp_id,target_comp_id);
+        self.send_message(message);
+    }
+
+    //Replay a previously sent message the same way a counterparty responding to a ResendRequest
+    //would: PossDupFlag is set, the original SendingTime becomes OrigSendingTime, and SendingTime
+    //is set to now.
+    pub fn send_poss_dup_message<T: FIXTMessage + Any + Send>(&mut self,msg_seq_num: u64,mut message: T) {
+        let orig_sending_time = message.sending_time();
+        let sender_comp_id = message.sender_comp_id().clone();
+        let target_comp_id = message.target_comp_id().clone();
+        message.setup_fixt_session_header(Some(msg_seq_num),sender_comp_id,target_comp_id);
+        message.set_orig_sending_time(orig_sending_time);
+        message.set_is_poss_dup(true);
+        self.send_message(message);
+    }
+
+    //Send a message with SendingTime shifted away from now. A negative offset simulates a stale
+    //message (ie. delayed in transit) and a positive offset simulates a counterparty whose clock
+    //is running ahead.
+    pub fn send_message_with_sending_time_offset<T: FIXTMessage + Any + Send>(&mut self,msg_seq_num: u64,mut message: T,offset: chrono::Duration) {
+        let sender_comp_id = message.sender_comp_id().clone();
+        let target_comp_id = message.target_comp_id().clone();
+        message.setup_fixt_session_header(Some(msg_seq_num),sender_comp_id,target_comp_id);
+
+        let sending_time = message.sending_time() + offset;
+        let mut sending_time_bytes = Vec::new();
+        <UTCTimestampFieldType as FieldType>::read(&sending_time,self.fix_version,self.message_version,&mut sending_time_bytes);
+        message.set_value(SendingTime::tag(),&sending_time_bytes[..]).unwrap();
+
+        self.send_message(message);
+    }
+
+    //Send a message marked with PossDupFlag but with OrigSendingTime deliberately left out even
+    //though it's conditionally required.
+    pub fn send_poss_dup_message_without_orig_sending_time<T: FIXTMessage + Any + Send>(&mut self,msg_seq_num: u64,mut message: T) {
+        let sender_comp_id = message.sender_comp_id().clone();
+        let target_comp_id = message.target_comp_id().clone();
+        message.setup_fixt_session_header(Some(msg_seq_num),sender_comp_id,target_comp_id);
+        message.set_orig_sending_time(UTCTimestampFieldType::new_empty());
+        message.set_is_poss_dup(true);
+        self.send_message(message);
+    }
+
+    //Replay a message with PossDupFlag set but with OrigSendingTime after SendingTime, which is
+    //never valid.
+    pub fn send_poss_dup_message_with_future_orig_sending_time<T: FIXTMessage + Any + Send>(&mut self,msg_seq_num: u64,mut message: T,offset: chrono::Duration) {
+        let sender_comp_id = message.sender_comp_id().clone();
+        let target_comp_id = message.target_comp_id().clone();
+        message.setup_fixt_session_header(Some(msg_seq_num),sender_comp_id,target_comp_id);
+        let orig_sending_time = message.sending_time() + offset;
+        message.set_orig_sending_time(orig_sending_time);
+        message.set_is_poss_dup(true);
+        self.send_message(message);
+    }
+
+    //Send a SequenceReset-GapFill covering [msg_seq_num,new_seq_no).
+    pub fn send_sequence_reset_gap_fill(&mut self,msg_seq_num: u64,new_seq_no: u64) {
+        let mut message = new_fixt_message!(SequenceReset);
+        message.gap_fill_flag = true;
+        message.new_seq_no = new_seq_no;
+        self.send_poss_dup_message(msg_seq_num,message);
+    }
+
+    //Send a SequenceReset-Reset. The receiver ignores MsgSeqNum for this message so it's always
+    //sent as 1.
+    pub fn send_sequence_reset_reset(&mut self,new_seq_no: u64) {
+        let mut message = new_fixt_message!(SequenceReset);
+        message.gap_fill_flag = false;
+        message.new_seq_no = new_seq_no;
+        self.send_message(message);
+    }
 }
 
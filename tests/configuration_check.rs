@@ -0,0 +1,122 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate chrono;
+extern crate fix_rs;
+
+use chrono::NaiveTime;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self,File};
+
+use fix_rs::clock::SimulatedClock;
+use fix_rs::dictionary::messages::{Heartbeat,Logon,Logout,NewOrderSingle,Reject,ResendRequest,SequenceReset,TestRequest};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::configuration_check::{ConfigurationIssue,ConnectionConfiguration};
+use fix_rs::fixt::engine::{ConnectionBuilder,EngineBuilder};
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessageBuildable};
+use fix_rs::fixt::message_log::MessageLogSettings;
+use fix_rs::fixt::schedule::{FixedTimeZone,SessionSchedule};
+use fix_rs::message_version::MessageVersion;
+
+const MAX_MESSAGE_SIZE: u64 = 4096;
+
+fn message_dictionary_without_test_request() -> HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> {
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(Heartbeat::msg_type(),Heartbeat::new().builder());
+    message_dictionary.insert(Logon::msg_type(),Logon::new().builder());
+    message_dictionary.insert(Logout::msg_type(),Logout::new().builder());
+    message_dictionary.insert(Reject::msg_type(),Reject::new().builder());
+    message_dictionary.insert(ResendRequest::msg_type(),ResendRequest::new().builder());
+    message_dictionary.insert(SequenceReset::msg_type(),SequenceReset::new().builder());
+
+    message_dictionary
+}
+
+fn new_connection_builder() -> ConnectionBuilder {
+    ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,b"TX",b"TEST")
+}
+
+#[test]
+fn test_valid_configuration() {
+    let directory = env::temp_dir().join("fix_rs_test_valid_configuration");
+    let _ = fs::remove_dir_all(&directory);
+
+    let mut message_dictionary = message_dictionary_without_test_request();
+    message_dictionary.insert(TestRequest::msg_type(),TestRequest::new().builder());
+    message_dictionary.insert(NewOrderSingle::msg_type(),NewOrderSingle::new().builder());
+    let engine_builder = EngineBuilder::new(message_dictionary,MAX_MESSAGE_SIZE);
+
+    let mut configuration = ConnectionConfiguration::new(new_connection_builder()
+        .message_store(directory.join("store"))
+        .quarantine_log(directory.join("quarantine.log"))
+        .message_log(directory.join("messages.log"),MessageLogSettings::new()));
+    configuration.expected_msg_types.push(NewOrderSingle::msg_type());
+    configuration.schedule = Some(SessionSchedule::new(NaiveTime::from_hms(8,0,0),NaiveTime::from_hms(16,30,0),FixedTimeZone::utc()));
+
+    let report = engine_builder.validate_configuration(&[configuration]);
+    assert!(report.is_ok());
+    assert_eq!(report.connections.len(),1);
+    assert_eq!(report.connections[0].session_id,new_connection_builder().session_id());
+    assert!(report.connections[0].next_session_start.is_some());
+
+    //The store is created but left empty.
+    assert!(directory.join("store").is_dir());
+    assert_eq!(fs::read_dir(directory.join("store")).unwrap().count(),0);
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_invalid_configuration() {
+    let directory = env::temp_dir().join("fix_rs_test_invalid_configuration");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    File::create(directory.join("file")).unwrap();
+
+    let engine_builder = EngineBuilder::new(message_dictionary_without_test_request(),MAX_MESSAGE_SIZE)
+        .clock(SimulatedClock::new());
+
+    //Missing message types, a store that can't be created under a regular file, and a time zone
+    //that is more than a day away from UTC.
+    let mut configuration = ConnectionConfiguration::new(new_connection_builder()
+        .message_store(directory.join("file").join("store"))
+        .quarantine_log(directory.join("missing").join("quarantine.log")));
+    configuration.expected_msg_types.push(NewOrderSingle::msg_type());
+    configuration.schedule = Some(SessionSchedule::new(NaiveTime::from_hms(8,0,0),NaiveTime::from_hms(16,30,0),FixedTimeZone { utc_offset: 25 * 60 * 60 }));
+
+    //A second connection without anything wrong with it except the shared dictionary.
+    let other_configuration = ConnectionConfiguration::new(new_connection_builder());
+
+    let report = engine_builder.validate_configuration(&[configuration,other_configuration]);
+    assert!(!report.is_ok());
+    assert_eq!(report.issues,vec![ConfigurationIssue::ClockNotRealTime]);
+    assert_eq!(report.connections.len(),2);
+
+    let issues = &report.connections[0].issues;
+    assert_eq!(issues.len(),5);
+    assert_eq!(issues[0],ConfigurationIssue::MissingMsgType(b"1".to_vec()));
+    assert_eq!(issues[1],ConfigurationIssue::MissingMsgType(b"D".to_vec()));
+    match issues[2] {
+        ConfigurationIssue::MessageStoreNotWritable(ref path,_) => assert_eq!(*path,directory.join("file").join("store")),
+        ref issue => panic!("Unexpected issue: {}",issue),
+    }
+    match issues[3] {
+        ConfigurationIssue::LogNotWritable(ref path,_) => assert_eq!(*path,directory.join("missing").join("quarantine.log")),
+        ref issue => panic!("Unexpected issue: {}",issue),
+    }
+    assert_eq!(issues[4],ConfigurationIssue::InvalidScheduleUtcOffset(25 * 60 * 60));
+    assert!(report.connections[0].next_session_start.is_none());
+
+    assert_eq!(report.connections[1].issues,vec![ConfigurationIssue::MissingMsgType(b"1".to_vec())]);
+
+    let _ = fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,111 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,MarketDataIncrementalRefresh,MarketDataSnapshotFullRefresh,SecurityList};
+use fix_rs::fixt::engine::{Connection,EngineEvent};
+use fix_rs::fixt::event_router::{Dispatched,EventRouter,EventTopic};
+use fix_rs::fixt::message::FIXTMessage;
+
+const CONNECTION: Connection = Connection { id: 1, generation: 0 };
+
+fn message_received<T: FIXTMessage + Send + 'static>(message: T) -> EngineEvent {
+    EngineEvent::MessageReceived(CONNECTION,Box::new(message))
+}
+
+#[test]
+fn test_topics() {
+    let mut router = EventRouter::new();
+    let admin_rx = router.bounded(EventTopic::Admin,10);
+    let orders_rx = router.bounded(EventTopic::Orders,10);
+    let market_data_rx = router.bounded(EventTopic::MarketData,10);
+    let errors_rx = router.bounded(EventTopic::Errors,10);
+
+    assert!(if let Dispatched::Delivered(EventTopic::Admin) = router.dispatch(EngineEvent::SessionEstablished(CONNECTION)) { true } else { false });
+    assert!(if let Dispatched::Delivered(EventTopic::Admin) = router.dispatch(message_received(new_fixt_message!(Heartbeat))) { true } else { false });
+    assert!(if let Dispatched::Delivered(EventTopic::Orders) = router.dispatch(message_received(new_fixt_message!(ExecutionReport))) { true } else { false });
+    assert!(if let Dispatched::Delivered(EventTopic::MarketData) = router.dispatch(message_received(new_fixt_message!(MarketDataSnapshotFullRefresh))) { true } else { false });
+    assert!(if let Dispatched::Delivered(EventTopic::Errors) = router.dispatch(EngineEvent::MessageRejected(CONNECTION,Box::new(new_fixt_message!(ExecutionReport)))) { true } else { false });
+
+    //Each receiver only gets its own topic and can be handed to another thread.
+    let handle = thread::spawn(move || {
+        let event = orders_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(if let EngineEvent::MessageReceived(_,ref message) = event { message.msg_type() == ExecutionReport::msg_type() } else { false });
+        assert!(orders_rx.try_recv().is_err());
+    });
+    handle.join().unwrap();
+    assert!(if let EngineEvent::SessionEstablished(_) = admin_rx.try_recv().unwrap() { true } else { false });
+    assert!(if let EngineEvent::MessageReceived(_,_) = admin_rx.try_recv().unwrap() { true } else { false });
+    assert!(admin_rx.try_recv().is_err());
+    assert!(market_data_rx.try_recv().is_ok());
+    assert!(errors_rx.try_recv().is_ok());
+
+    //Application messages without a topic go to Other, which isn't routed here, unless they're
+    //assigned one.
+    match router.dispatch(message_received(new_fixt_message!(SecurityList))) {
+        Dispatched::Unrouted(EventTopic::Other,EngineEvent::MessageReceived(_,message)) => assert_eq!(message.msg_type(),SecurityList::msg_type()),
+        _ => panic!("SecurityList should not have been routed"),
+    }
+    router.msg_type_topic(SecurityList::msg_type(),EventTopic::MarketData);
+    assert!(if let Dispatched::Delivered(EventTopic::MarketData) = router.dispatch(message_received(new_fixt_message!(SecurityList))) { true } else { false });
+}
+
+#[test]
+fn test_capacity() {
+    let mut router = EventRouter::new();
+    let orders_rx = router.bounded(EventTopic::Orders,1);
+    let (admin_tx,admin_rx) = mpsc::channel();
+    router.route(EventTopic::Admin,admin_tx);
+
+    //A full topic hands its events back without holding up the others.
+    assert!(if let Dispatched::Delivered(_) = router.dispatch(message_received(new_fixt_message!(ExecutionReport))) { true } else { false });
+    match router.dispatch(message_received(new_fixt_message!(ExecutionReport))) {
+        Dispatched::Full(EventTopic::Orders,EngineEvent::MessageReceived(_,message)) => assert_eq!(message.msg_type(),ExecutionReport::msg_type()),
+        _ => assert!(false),
+    }
+    assert!(if let Dispatched::Delivered(EventTopic::Admin) = router.dispatch(EngineEvent::SessionEstablished(CONNECTION)) { true } else { false });
+    assert_eq!(router.dropped_count(EventTopic::Orders),0);
+    assert!(admin_rx.try_recv().is_ok());
+
+    //Topics can drop events when full instead.
+    router.drop_when_full(EventTopic::Orders,true);
+    assert!(if let Dispatched::Dropped(EventTopic::Orders) = router.dispatch(message_received(new_fixt_message!(ExecutionReport))) { true } else { false });
+    assert_eq!(router.dropped_count(EventTopic::Orders),1);
+    assert_eq!(router.dropped_count(EventTopic::Admin),0);
+    assert!(orders_rx.try_recv().is_ok());
+
+    //Nothing is dropped by default, including MarketData where losing an incremental refresh
+    //would corrupt the consumer's book.
+    let _market_data_rx = router.bounded(EventTopic::MarketData,0);
+    assert!(if let Dispatched::Full(EventTopic::MarketData,_) = router.dispatch(message_received(new_fixt_message!(MarketDataIncrementalRefresh))) { true } else { false });
+    router.drop_when_full(EventTopic::MarketData,true);
+    assert!(if let Dispatched::Dropped(EventTopic::MarketData) = router.dispatch(message_received(new_fixt_message!(MarketDataSnapshotFullRefresh))) { true } else { false });
+
+    //Dropping a receiver stops its topic from being routed.
+    drop(orders_rx);
+    assert!(if let Dispatched::Unrouted(EventTopic::Orders,_) = router.dispatch(message_received(new_fixt_message!(ExecutionReport))) { true } else { false });
+    assert!(if let Dispatched::Unrouted(EventTopic::Orders,_) = router.dispatch(message_received(new_fixt_message!(ExecutionReport))) { true } else { false });
+}
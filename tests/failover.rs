@@ -0,0 +1,152 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use std::env;
+use std::fs::{self,File};
+use std::io::{self,Write};
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
+use std::sync::{Arc,Barrier};
+use std::thread;
+use std::time::Duration;
+
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::failover::{FileLease,SessionCoordinator};
+use fix_rs::fixt::session_id::SessionID;
+
+const LOCK_EX: c_int = 2;
+const LOCK_NB: c_int = 4;
+
+extern "C" {
+    fn flock(fd: c_int,operation: c_int) -> c_int;
+}
+
+fn test_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&directory);
+
+    directory
+}
+
+#[test]
+fn test_file_lease() {
+    let directory = test_directory("fix_rs_test_file_lease");
+    let duration = Duration::from_secs(60);
+    let primary = FileLease::new(&directory,"primary",duration);
+    let standby = FileLease::new(&directory,"standby",duration);
+    assert_eq!(primary.renew_interval(),Duration::from_secs(20));
+
+    //Only one process can hold the lease at a time.
+    assert!(primary.try_acquire().unwrap());
+    assert_eq!(primary.epoch(),Some(1));
+    assert!(primary.is_active());
+    assert!(!standby.try_acquire().unwrap());
+    assert_eq!(standby.epoch(),None);
+    assert!(!standby.is_active());
+    assert!(!standby.renew().unwrap());
+    assert!(primary.renew().unwrap());
+    assert!(primary.try_acquire().unwrap());
+    assert_eq!(primary.epoch(),Some(1));
+
+    //Releasing lets the standby take over right away with a new epoch. The old primary can't
+    //renew its way back in.
+    primary.release().unwrap();
+    assert_eq!(primary.epoch(),None);
+    assert!(!primary.is_active());
+    assert!(standby.try_acquire().unwrap());
+    assert_eq!(standby.epoch(),Some(2));
+    assert!(!primary.renew().unwrap());
+    assert!(!primary.try_acquire().unwrap());
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_file_lease_expires() {
+    let root_directory = test_directory("fix_rs_test_file_lease_expires");
+    let session_id = SessionID::new(FIXVersion::FIXT_1_1,b"TX",b"TEST");
+    let duration = Duration::from_millis(100);
+    let primary = FileLease::for_session(&root_directory,&session_id,"primary",duration);
+    let standby = FileLease::for_session(&root_directory,&session_id,"standby",duration);
+
+    //A primary that stops renewing, like one that crashed or hung, loses the lease once it
+    //expires. If it comes back, it finds out it's no longer active.
+    assert!(primary.try_acquire().unwrap());
+    assert!(!standby.try_acquire().unwrap());
+    thread::sleep(duration * 2);
+    assert!(!primary.is_active());
+    assert!(standby.try_acquire().unwrap());
+    assert_eq!(standby.epoch(),Some(2));
+    assert!(!primary.renew().unwrap());
+    assert!(root_directory.join(session_id.file_name()).join("lease").exists());
+
+    let _ = fs::remove_dir_all(&root_directory);
+}
+
+#[test]
+fn test_file_lease_busy() {
+    let directory = test_directory("fix_rs_test_file_lease_busy");
+    let duration = Duration::from_secs(60);
+    let primary = FileLease::new(&directory,"primary",duration);
+    assert!(primary.try_acquire().unwrap());
+
+    //While another process is in the middle of changing the lease, calls return right away
+    //instead of waiting so the engine thread is never blocked.
+    let lock_file = File::create(directory.join("lease.lock")).unwrap();
+    assert_eq!(unsafe { flock(lock_file.as_raw_fd(),LOCK_EX | LOCK_NB) },0);
+    assert_eq!(primary.renew().unwrap_err().kind(),io::ErrorKind::WouldBlock);
+    assert_eq!(primary.try_acquire().unwrap_err().kind(),io::ErrorKind::WouldBlock);
+    assert!(primary.is_active());
+
+    //Closing the file releases the lock even though the file is still there.
+    drop(lock_file);
+    assert!(directory.join("lease.lock").exists());
+    assert!(primary.renew().unwrap());
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_file_lease_stale_lock_race() {
+    let directory = test_directory("fix_rs_test_file_lease_stale_lock_race");
+    let duration = Duration::from_secs(60);
+
+    //Leave a lock file behind like a process that died while changing the lease.
+    fs::create_dir_all(&directory).unwrap();
+    write!(File::create(directory.join("lease.lock")).unwrap(),"crashed").unwrap();
+
+    //Several standbys race to take over. Exactly one of them may end up active.
+    let contender_count = 4;
+    let barrier = Arc::new(Barrier::new(contender_count));
+    let mut threads = Vec::new();
+    for i in 0..contender_count {
+        let lease = FileLease::new(&directory,format!("standby{}",i),duration);
+        let barrier = barrier.clone();
+        threads.push(thread::spawn(move || {
+            barrier.wait();
+            loop {
+                match lease.try_acquire() {
+                    Ok(acquired) => return (acquired,lease.epoch()),
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => thread::yield_now(),
+                    Err(e) => panic!("{}",e),
+                }
+            }
+        }));
+    }
+    let results: Vec<(bool,Option<u64>)> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    assert_eq!(results.iter().filter(|&&(acquired,_)| acquired).count(),1);
+    assert!(results.iter().all(|&(acquired,epoch)| if acquired { epoch == Some(1) } else { epoch.is_none() }));
+
+    let _ = fs::remove_dir_all(&directory);
+}
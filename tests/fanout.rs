@@ -0,0 +1,101 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use fix_rs::dictionary::field_types::other::{MDEntryType,MDUpdateAction};
+use fix_rs::dictionary::fields::MDIncGrp;
+use fix_rs::dictionary::messages::{Heartbeat,MarketDataIncrementalRefresh,MarketDataSnapshotFullRefresh};
+use fix_rs::fixt::fanout::{Fanout,FanoutFilter};
+use fix_rs::fixt::message::FIXTMessage;
+
+fn snapshot(symbol: &[u8]) -> MarketDataSnapshotFullRefresh {
+    let mut message = new_fixt_message!(MarketDataSnapshotFullRefresh);
+    message.symbol = symbol.to_vec();
+
+    message
+}
+
+fn incremental_refresh(symbols: &[&[u8]]) -> MarketDataIncrementalRefresh {
+    let mut message = new_fixt_message!(MarketDataIncrementalRefresh);
+    for symbol in symbols {
+        let mut group = MDIncGrp::new();
+        group.md_update_action = MDUpdateAction::Change;
+        group.md_entry_type = MDEntryType::Bid;
+        group.symbol = symbol.to_vec();
+        message.no_md_entries.push(Box::new(group));
+    }
+
+    message
+}
+
+#[test]
+fn test_fanout_filters() {
+    let fanout = Fanout::new();
+    let everything_rx = fanout.subscribe(FanoutFilter::new());
+    let snapshot_rx = fanout.subscribe(FanoutFilter::new().msg_type(MarketDataSnapshotFullRefresh::msg_type()));
+    let aaa_rx = fanout.subscribe(FanoutFilter::new().symbol(b"AAA"));
+    let bbb_snapshot_rx = fanout.subscribe(FanoutFilter::new().msg_type(MarketDataSnapshotFullRefresh::msg_type()).symbol(b"BBB"));
+
+    assert_eq!(fanout.dispatch(Box::new(snapshot(b"AAA"))),3);
+    assert_eq!(fanout.dispatch(Box::new(snapshot(b"BBB"))),3);
+    assert_eq!(fanout.dispatch(Box::new(new_fixt_message!(Heartbeat))),1);
+
+    //Symbols inside repeating groups count too.
+    assert_eq!(fanout.dispatch(Box::new(incremental_refresh(&[b"CCC",b"AAA"]))),2);
+    assert_eq!(fanout.dispatch(Box::new(incremental_refresh(&[b"BBB"]))),1);
+
+    assert_eq!(everything_rx.try_iter().map(|message| message.msg_type()).collect::<Vec<_>>(),vec![&b"W"[..],b"W",b"0",b"X",b"X"]);
+    assert_eq!(snapshot_rx.try_iter().count(),2);
+    assert_eq!(aaa_rx.try_iter().map(|message| message.msg_type()).collect::<Vec<_>>(),vec![&b"W"[..],b"X"]);
+    let messages: Vec<_> = bbb_snapshot_rx.try_iter().collect();
+    assert_eq!(messages.len(),1);
+    assert_eq!(messages[0].as_any().downcast_ref::<MarketDataSnapshotFullRefresh>().unwrap().symbol,b"BBB");
+}
+
+#[test]
+fn test_fanout_across_threads() {
+    let fanout = Fanout::new();
+
+    //Each strategy thread subscribes using its own handle.
+    let mut threads = Vec::new();
+    for symbol in vec![b"AAA",b"BBB"] {
+        let fanout = fanout.clone();
+        let rx = fanout.subscribe(FanoutFilter::new().symbol(symbol));
+        threads.push(thread::spawn(move || {
+            let message = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            message.as_any().downcast_ref::<MarketDataSnapshotFullRefresh>().unwrap().symbol.clone()
+        }));
+    }
+    assert_eq!(fanout.subscriber_count(),2);
+
+    assert_eq!(fanout.dispatch(Box::new(snapshot(b"AAA"))),1);
+    assert_eq!(fanout.dispatch(Box::new(snapshot(b"BBB"))),1);
+    let symbols: Vec<Vec<u8>> = threads.into_iter().map(|thread| thread.join().unwrap()).collect();
+    assert_eq!(symbols,vec![b"AAA".to_vec(),b"BBB".to_vec()]);
+
+    //Subscribers that went away are removed.
+    assert_eq!(fanout.dispatch(Box::new(snapshot(b"AAA"))),0);
+    assert_eq!(fanout.subscriber_count(),0);
+}
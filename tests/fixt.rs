@@ -35,7 +35,7 @@ mod common;
 use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,SERVER_SENDER_COMP_ID,SERVER_TARGET_COMP_ID,TestStream,new_logon_message,recv_bytes_with_timeout,send_message};
 use fix_rs::dictionary::standard_msg_types;
 use fix_rs::dictionary::field_types::generic::{CharFieldType,NoneFieldType,StringFieldType};
-use fix_rs::dictionary::field_types::other::{BusinessRejectReason,OrdType,SecurityIDSource,SessionRejectReason,Side};
+use fix_rs::dictionary::field_types::other::{BusinessRejectReason,OrdType,SecurityIDSource,SessionRejectReason,SessionStatus,Side};
 use fix_rs::dictionary::fields::{TestReqID,HeartBtInt,EndSeqNo,SideField,OrigSendingTime,NoHops,HopCompID};
 use fix_rs::dictionary::messages::{Logon,Logout,NewOrderSingle,ResendRequest,TestRequest,Heartbeat,SequenceReset,Reject,BusinessMessageReject};
 use fix_rs::field::Field;
@@ -43,7 +43,7 @@ use fix_rs::fix::ParseError;
 use fix_rs::field_tag::{self,FieldTag};
 use fix_rs::fix_version::FIXVersion;
 use fix_rs::fixt;
-use fix_rs::fixt::engine::{Engine,EngineEvent,Connection,ConnectionTerminatedReason,Listener,ResendResponse};
+use fix_rs::fixt::engine::{Engine,EngineEvent,Connection,ConnectionLoggingOnData,ConnectionTerminatedReason,Listener,ResendResponse,SessionStatusReceivedData};
 use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage};
 use fix_rs::message::{self,NOT_REQUIRED,REQUIRED,MessageDetails};
 use fix_rs::message_version::{self,MessageVersion};
@@ -218,7 +218,7 @@ fn test_1S() {
         logon_message_setup_func(&mut logon_message);
         test_client.send_message(logon_message.clone());
 
-        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,logon_message) => {
+        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
             assert_eq!(some_listener,listener);
             assert_eq!(some_connection,connection);
 
@@ -875,8 +875,8 @@ fn test_4B() {
         //Connect and logon.
         let (mut test_server,_client,_) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-        //Sleep until Heartbeat is triggered.
-        thread::sleep(Duration::from_millis(5500));
+        //Advance time until Heartbeat is triggered.
+        test_server.advance_clock(Duration::from_millis(5500));
 
         //Make sure Heartbeat was sent by client.
         let _ = test_server.recv_message::<Heartbeat>();
@@ -888,8 +888,8 @@ fn test_4B() {
         //Connect and logon.
         let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-        //Sleep for half the Heartbeat session.
-        thread::sleep(Duration::from_millis(2500));
+        //Advance time for half the Heartbeat session.
+        test_server.advance_clock(Duration::from_millis(2500));
 
         //Send message to reset Engine's output heartbeat.
         let mut message = new_fixt_message!(TestRequest);
@@ -897,13 +897,13 @@ fn test_4B() {
         client.send_message(connection,message);
         let _ = test_server.recv_message::<TestRequest>();
 
-        //Sleep a little bit and make sure clienent sends a TestRequest because it didn't receive
-        //anything.
-        thread::sleep(Duration::from_millis(1000));
+        //Advance time a little past HeartBeatInt and make sure client sends a TestRequest because
+        //it didn't receive anything.
+        test_server.advance_clock(Duration::from_millis(3000));
         let _ = test_server.recv_message::<TestRequest>();
 
-        //Sleep a little longer than the original heartbeat session.
-        thread::sleep(Duration::from_millis(2000));
+        //Advance time a little longer than the original heartbeat session.
+        test_server.advance_clock(Duration::from_millis(2000));
 
         //Make sure Heartbeat was NOT sent by client.
         assert!(test_server.try_recv_fixt_message(Duration::from_secs(1)).is_none());
@@ -964,8 +964,8 @@ fn test_6B() {
         //Connect and logon.
         let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-        //Sleep until TestRequest is triggered.
-        thread::sleep(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
+        //Advance time until TestRequest is triggered.
+        test_server.advance_clock(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
 
         //Ignore HeartBeat because Engine didn't send anything for HeartBeatInt seconds.
         let message = test_server.recv_message::<Heartbeat>();
@@ -993,8 +993,8 @@ fn test_6B() {
         //Connect and logon.
         let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-        //Sleep until TestRequest is triggered.
-        thread::sleep(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
+        //Advance time until TestRequest is triggered.
+        test_server.advance_clock(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
 
         //Ignore HeartBeat because Engine didn't send anything for HeartBeatInt seconds.
         let _ = test_server.recv_message::<Heartbeat>();
@@ -1003,8 +1003,8 @@ fn test_6B() {
         let message = test_server.recv_message::<TestRequest>();
         assert_eq!(message.msg_seq_num,3);
 
-        //Sleep until disconnect.
-        thread::sleep(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
+        //Advance time until disconnect.
+        test_server.advance_clock(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
 
         //Confirm client notified that it disconnected.
         engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
@@ -1066,8 +1066,8 @@ fn test_8B() {
     //Connect and logon.
     let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
 
-    //Sleep until TestRequest and Heartbeat are triggered.
-    thread::sleep(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
+    //Advance time until TestRequest and Heartbeat are triggered.
+    test_server.advance_clock(Duration::from_millis(6000)); //1.2 * HeartBeatInt as stated.
 
     let _ = test_server.recv_message::<Heartbeat>();
     let _ = test_server.recv_message::<TestRequest>();
@@ -1434,7 +1434,7 @@ fn test_12B() {
 
         let mut message = new_fixt_message!(Logout);
         message.msg_seq_num = 2;
-        message.session_status = b"4".to_vec(); //Session logout complete.
+        message.session_status = Some(SessionStatus::SessionLogoutComplete);
         test_server.send_message(message);
 
         //Give client thread a chance to disconnect.
@@ -1443,7 +1443,12 @@ fn test_12B() {
         //Confirm the client socket disconnected.
         assert!(test_server.is_stream_closed(Duration::from_secs(5)));
 
-        //Confirm client notified that it disconnected.
+        //Confirm client was notified of the SessionStatus and then that it disconnected.
+        engine_poll_event!(client,EngineEvent::SessionStatusReceived(SessionStatusReceivedData { connection: status_connection, session_status, text }) => {
+            assert_eq!(status_connection,connection);
+            assert_eq!(session_status,SessionStatus::SessionLogoutComplete);
+            assert!(text.is_empty());
+        });
         engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
             assert_eq!(terminated_connection,connection);
             assert!(if let ConnectionTerminatedReason::LocalRequested = reason { true } else { false });
@@ -1462,8 +1467,8 @@ fn test_12B() {
         //Make sure socket isn't closed immediatelly.
         assert!(!test_server.is_stream_closed(Duration::from_secs(1)));
 
-        //Wait for Logout to timeout.
-        thread::sleep(Duration::from_millis(9500));
+        //Advance time until Logout times out.
+        test_server.advance_clock(Duration::from_millis(10500));
 
         //Confirm the client socket disconnected.
         assert!(test_server.is_stream_closed(Duration::from_secs(5)));
@@ -1502,10 +1507,8 @@ fn test_13B() {
         let _ = test_server.recv_message::<Logout>();
 
         //Server disconnects and client should acknowledge that the connection has been closed.
-        //Since client isn't sending anymore data in this state, it can take up to 10 seconds to
-        //notice the shutdown.
+        //The socket hanging up is noticed without waiting on any timeout.
         let _ = test_server.stream.shutdown(Shutdown::Both);
-        thread::sleep(Duration::from_secs(6)); //6 seconds + the duration in engine_poll_event!() >= 10 seconds
         engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
             assert_eq!(terminated_connection,connection);
             assert!(if let ConnectionTerminatedReason::RemoteRequested = reason { true } else { false });
@@ -1527,13 +1530,13 @@ fn test_13B() {
         //Engine should respond with a response Logout message.
         let _ = test_server.recv_message::<Logout>();
 
-        //Wait around a little bit and make sure client doesn't disconnect instantly.
-        thread::sleep(Duration::from_secs(5));
+        //Advance time a little bit and make sure client doesn't disconnect instantly.
+        test_server.advance_clock(Duration::from_secs(5));
         assert!(!test_server.is_stream_closed(Duration::from_millis(100)));
 
-        //Wait around a little over the full 10 seconds and make sure client does force a
+        //Advance time a little over the full 10 seconds and make sure client does force a
         //disconnect.
-        thread::sleep(Duration::from_millis(5500));
+        test_server.advance_clock(Duration::from_millis(5500));
         assert!(recv_bytes_with_timeout(&mut test_server.stream,Duration::from_secs(1)).is_none()); //Engine should have stopped sending TestRequests and Heartbeats!
         assert!(test_server.is_stream_closed(Duration::from_secs(1)));
         engine_poll_event!(client,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
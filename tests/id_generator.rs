@@ -0,0 +1,67 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use std::collections::HashSet;
+
+use fix_rs::dictionary::field_types::other::MDEntryType;
+use fix_rs::fixt::market_data::MarketDataSubscriptions;
+use fix_rs::id_generator::{IdGenerator,MonotonicIdGenerator,PrefixIdGenerator,TimestampIdGenerator};
+
+#[test]
+fn test_monotonic_id_generator() {
+    let mut generator = MonotonicIdGenerator::new();
+    assert_eq!(generator.next_id(),b"1");
+    assert_eq!(generator.next_id(),b"2");
+    assert_eq!(generator.last_counter(),2);
+
+    //Resuming from a saved counter continues where the previous generator left off.
+    let mut generator = MonotonicIdGenerator::resume(generator.last_counter());
+    assert_eq!(generator.next_id(),b"3");
+}
+
+#[test]
+fn test_prefix_id_generator() {
+    let mut generator = PrefixIdGenerator::new(b"ORD");
+    assert_eq!(generator.next_id(),b"ORD1");
+    assert_eq!(generator.next_id(),b"ORD2");
+
+    let mut generator = PrefixIdGenerator::resume(b"ORD",generator.last_counter());
+    assert_eq!(generator.next_id(),b"ORD3");
+}
+
+#[test]
+fn test_timestamp_id_generator() {
+    //IDs generated faster than the clock resolution must still be unique and increasing.
+    let mut generator = TimestampIdGenerator::new(b"Q");
+    let mut ids = HashSet::new();
+    let mut last_counter = 0;
+    for _ in 0..10000 {
+        let id = generator.next_id();
+        assert_eq!(id[0],b'Q');
+        assert!(generator.last_counter() > last_counter);
+        last_counter = generator.last_counter();
+        assert!(ids.insert(id));
+    }
+
+    //A counter saved from the "future" is never reused even though the clock is behind it.
+    let mut generator = TimestampIdGenerator::resume(b"Q",u64::max_value() - 1);
+    assert_eq!(generator.next_id(),format!("Q{}",u64::max_value()).into_bytes());
+}
+
+#[test]
+fn test_market_data_custom_id_generator() {
+    let mut subscriptions = MarketDataSubscriptions::with_id_generator(Box::new(PrefixIdGenerator::resume(b"MD",41)));
+    let request = subscriptions.snapshot(&[b"AAA"],&[MDEntryType::Bid],1);
+    assert_eq!(request.md_req_id,b"MD42");
+    assert_eq!(subscriptions.id_generator().last_counter(),42);
+}
@@ -0,0 +1,99 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::{round_trip,serialize_message};
+use fix_rs::dictionary::field_types::other::{AdvSide,AdvTransType,IOIQltyInd,IOIQualifier,IOITransType,RoutingType,Side};
+use fix_rs::dictionary::fields::{IOIQualGrp,RoutingGrp};
+use fix_rs::dictionary::messages::{Advertisement,IOI};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_ioi() {
+    let mut message = new_fixt_message!(IOI);
+    message.ioi_id = b"IOI1".to_vec();
+    message.ioi_trans_type = IOITransType::New;
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.ioi_qty = b"L".to_vec();
+    message.price = b"10.5".to_vec();
+    message.ioi_qlty_ind = Some(IOIQltyInd::High);
+    message.ioi_natural_flag = b"Y".to_vec();
+    for ioi_qualifier in vec![IOIQualifier::AllOrNone,IOIQualifier::AtTheClose] {
+        let mut group = IOIQualGrp::new();
+        group.ioi_qualifier = ioi_qualifier;
+        message.no_ioi_qualifiers.push(Box::new(group));
+    }
+
+    //Target the IOI at specific firms only.
+    for (routing_type,routing_id) in vec![(RoutingType::TargetFirm,&b"FIRM1"[..]),(RoutingType::BlockFirm,&b"FIRM2"[..])] {
+        let mut group = RoutingGrp::new();
+        group.routing_type = routing_type;
+        group.routing_id = routing_id.to_vec();
+        message.no_routing_ids.push(Box::new(group));
+    }
+
+    let bytes = serialize_message(&message,FIXVersion::FIX_4_2,MessageVersion::FIX42);
+    let text = String::from_utf8_lossy(&bytes[..]).into_owned();
+    assert!(text.contains("\x01199=2\x01104=A\x01104=C\x01"));
+    assert!(text.contains("\x01215=2\x01216=1\x01217=FIRM1\x01216=3\x01217=FIRM2\x01"));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_2,MessageVersion::FIX42);
+    assert_eq!(parsed_message.ioi_id,b"IOI1");
+    assert_eq!(parsed_message.ioi_qty,b"L");
+    assert_eq!(parsed_message.ioi_qlty_ind,Some(IOIQltyInd::High));
+    assert_eq!(parsed_message.no_ioi_qualifiers.len(),2);
+    assert_eq!(parsed_message.no_ioi_qualifiers[1].ioi_qualifier,IOIQualifier::AtTheClose);
+    assert_eq!(parsed_message.no_routing_ids.len(),2);
+    assert_eq!(parsed_message.no_routing_ids[1].routing_type,RoutingType::BlockFirm);
+    assert_eq!(parsed_message.no_routing_ids[1].routing_id,b"FIRM2");
+
+    //Qualifier and routing groups don't exist before FIX 4.2.
+    let bytes = serialize_message(&message,FIXVersion::FIX_4_1,MessageVersion::FIX41);
+    let text = String::from_utf8_lossy(&bytes[..]).into_owned();
+    assert!(!text.contains("\x01199="));
+    assert!(!text.contains("\x01215="));
+}
+
+#[test]
+fn test_advertisement() {
+    let mut message = new_fixt_message!(Advertisement);
+    message.adv_id = b"ADV2".to_vec();
+    message.adv_trans_type = AdvTransType::Replace;
+    message.adv_ref_id = b"ADV1".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.adv_side = AdvSide::Cross;
+    message.quantity = b"5000".to_vec();
+    message.price = b"10.25".to_vec();
+    message.last_mkt = b"XNYS".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.adv_id,b"ADV2");
+    assert_eq!(parsed_message.adv_trans_type,AdvTransType::Replace);
+    assert_eq!(parsed_message.adv_ref_id,b"ADV1");
+    assert_eq!(parsed_message.adv_side,AdvSide::Cross);
+    assert_eq!(parsed_message.quantity,b"5000");
+    assert_eq!(parsed_message.last_mkt,b"XNYS");
+}
@@ -0,0 +1,129 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::MAX_MESSAGE_SIZE;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::dictionary::field_types::other::{ExecType,OrdStatus,OrdType,Side};
+use fix_rs::dictionary::fields::{ClOrdID,MsgSeqNum,OrigClOrdID,Price,Text};
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,NewOrderSingle,OrderCancelReplaceRequest,OrderCancelRequest};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::journal_export::{JournalColumn,JournalExportSettings,JournalExporter};
+use fix_rs::fixt::message_store::{MemoryMessageStore,MessageStore};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+define_dictionary!(
+    ExecutionReport,
+    NewOrderSingle,
+    OrderCancelReplaceRequest,
+    OrderCancelRequest,
+);
+
+fn store_outbound<T: Message>(message_store: &mut MemoryMessageStore,msg_seq_num: u64,message: &T) {
+    let mut bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut bytes);
+    message_store.store_outbound(msg_seq_num,bytes.bytes()).unwrap();
+}
+
+#[test]
+fn test_export_follows_cl_ord_id_chains() {
+    //Order A is replaced by B and then canceled by C. The Heartbeat in between isn't an order
+    //event and is skipped.
+    let mut message_store = MemoryMessageStore::new();
+    let mut message = new_fixt_message!(FROM_CLIENT NewOrderSingle);
+    message.msg_seq_num = 2;
+    message.cl_ord_id = b"A".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.transact_time = message.sending_time;
+    message.order_qty = b"100".to_vec();
+    message.ord_type = OrdType::Limit;
+    message.price = b"10".to_vec();
+    store_outbound(&mut message_store,2,&message);
+
+    let mut message = new_fixt_message!(FROM_CLIENT Heartbeat);
+    message.msg_seq_num = 3;
+    store_outbound(&mut message_store,3,&message);
+
+    let mut message = new_fixt_message!(FROM_CLIENT OrderCancelReplaceRequest);
+    message.msg_seq_num = 4;
+    message.orig_cl_ord_id = b"A".to_vec();
+    message.cl_ord_id = b"B".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.transact_time = message.sending_time;
+    message.order_qty = b"100".to_vec();
+    message.ord_type = OrdType::Limit;
+    message.price = b"11".to_vec();
+    store_outbound(&mut message_store,4,&message);
+
+    let mut message = new_fixt_message!(FROM_CLIENT OrderCancelRequest);
+    message.msg_seq_num = 5;
+    message.orig_cl_ord_id = b"B".to_vec();
+    message.cl_ord_id = b"C".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.transact_time = message.sending_time;
+    message.text = b"done, \"for now\"".to_vec();
+    store_outbound(&mut message_store,5,&message);
+    message_store.set_next_outbound_msg_seq_num(6).unwrap();
+
+    let mut settings = JournalExportSettings::new();
+    settings.columns = vec![
+        JournalColumn::field::<MsgSeqNum>(),
+        JournalColumn::EventType(String::from("Event")),
+        JournalColumn::field::<ClOrdID>(),
+        JournalColumn::field::<OrigClOrdID>(),
+        JournalColumn::RootClOrdID(String::from("RootClOrdID")),
+        JournalColumn::field::<Price>(),
+        JournalColumn::field::<Text>(),
+    ];
+    let mut exporter = JournalExporter::new(build_dictionary(),MAX_MESSAGE_SIZE,settings);
+    let mut output = Vec::new();
+    assert_eq!(exporter.export_store(&mut message_store,&mut output).unwrap(),3);
+
+    //Received messages can be added afterwards and are tied to the same chain.
+    let mut message = new_fixt_message!(ExecutionReport);
+    message.msg_seq_num = 7;
+    message.order_id = b"VENUE1".to_vec();
+    message.cl_ord_id = b"C".to_vec();
+    message.orig_cl_ord_id = b"B".to_vec();
+    message.exec_id = b"E1".to_vec();
+    message.exec_type = ExecType::Canceled;
+    message.ord_status = OrdStatus::Canceled;
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.cum_qty = b"0".to_vec();
+    message.leaves_qty = b"0".to_vec();
+    assert!(exporter.export_message(&message,&mut output).unwrap());
+    assert!(!exporter.export_message(&new_fixt_message!(Heartbeat),&mut output).unwrap());
+
+    //Values containing the delimiter or quotes are quoted.
+    assert_eq!(String::from_utf8(output).unwrap(),
+               "MsgSeqNum,Event,ClOrdID,OrigClOrdID,RootClOrdID,Price,Text\n\
+                2,NEW,A,,A,10,\n\
+                4,REPLACE,B,A,A,11,\n\
+                5,CANCEL,C,B,A,,\"done, \"\"for now\"\"\"\n\
+                7,EXECUTION,C,B,A,,\n");
+}
@@ -0,0 +1,155 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::collections::HashMap;
+
+#[macro_use]
+mod common;
+use common::MAX_MESSAGE_SIZE;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::dictionary::messages::{Heartbeat,TestRequest};
+use fix_rs::fix::{ParseError,Parser};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
+use fix_rs::lazy_message::{LazyMessageArena,prescan_lazy_messages};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn new_parser() -> Parser {
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(Heartbeat::msg_type(),Heartbeat::new().builder());
+    message_dictionary.insert(TestRequest::msg_type(),TestRequest::new().builder());
+
+    Parser::new(message_dictionary,MAX_MESSAGE_SIZE)
+}
+
+#[test]
+fn test_lazy_messages_are_decoded_on_first_access() {
+    let mut bytes = ByteBuffer::new();
+    for msg_seq_num in 2..5 {
+        let mut message = new_fixt_message!(Heartbeat);
+        message.msg_seq_num = msg_seq_num;
+        message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+    }
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 5;
+    message.test_req_id = b"test_id".to_vec();
+    message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+
+    //Leave an incomplete message at the end. It must be left for Parser::parse().
+    let mut message_bytes = bytes.bytes().to_vec();
+    let complete_length = message_bytes.len();
+    message_bytes.extend_from_slice(b"8=FIX.4.4\x019=5");
+
+    let mut parser = new_parser();
+    let (bytes_used,mut messages) = prescan_lazy_messages(&parser,&message_bytes[..]);
+    assert_eq!(bytes_used,complete_length);
+    assert_eq!(messages.len(),4);
+
+    //Only the header is known until the message is accessed.
+    for (index,message) in messages.iter().enumerate() {
+        assert_eq!(message.msg_seq_num(),Some(index as u64 + 2));
+        assert!(!message.is_decoded());
+    }
+    assert_eq!(messages[0].msg_type(),Heartbeat::msg_type());
+    assert_eq!(messages[3].msg_type(),TestRequest::msg_type());
+    assert_eq!(messages[3].sender_comp_id(),b"TX");
+    assert_eq!(messages[3].target_comp_id(),b"TEST");
+
+    //Skip the Heartbeats and only decode the TestRequest.
+    let mut test_request = messages.pop().unwrap();
+    assert!(messages.iter().all(|message| message.msg_type() == Heartbeat::msg_type() && !message.is_decoded()));
+    {
+        let decoded_message = test_request.get::<TestRequest>(&mut parser).unwrap().unwrap();
+        assert_eq!(decoded_message.msg_seq_num,5);
+        assert_eq!(decoded_message.test_req_id,b"test_id");
+    }
+    assert!(test_request.is_decoded());
+    assert!(test_request.get::<Heartbeat>(&mut parser).unwrap().is_none());
+    assert!(parser.messages.is_empty());
+
+    let decoded_message = test_request.into_message(&mut parser).unwrap();
+    assert_eq!(decoded_message.msg_type(),TestRequest::msg_type());
+}
+
+#[test]
+fn test_lazy_message_arena_reuses_released_batches() {
+    let mut bytes = ByteBuffer::new();
+    for msg_seq_num in 2..4 {
+        let mut message = new_fixt_message!(TestRequest);
+        message.msg_seq_num = msg_seq_num;
+        message.test_req_id = b"test_id".to_vec();
+        message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+    }
+    let message_bytes = bytes.bytes().to_vec();
+
+    let mut parser = new_parser();
+    let mut arena = LazyMessageArena::new(4096);
+
+    //Every message in a batch shares the same chunk.
+    let (bytes_used,mut first_batch) = arena.prescan_messages(&parser,&message_bytes[..]);
+    assert_eq!(bytes_used,message_bytes.len());
+    assert_eq!(first_batch.len(),2);
+    let first_chunk_start = first_batch[0].bytes().as_ptr();
+    assert_eq!(first_batch[1].bytes().as_ptr() as usize,first_chunk_start as usize + first_batch[0].bytes().len());
+    assert_eq!(first_batch[1].get::<TestRequest>(&mut parser).unwrap().unwrap().msg_seq_num,3);
+    assert!(!arena.is_released());
+
+    //A batch that's still held onto isn't overwritten by the next one.
+    let (_,second_batch) = arena.prescan_messages(&parser,&message_bytes[..]);
+    assert!(second_batch[0].bytes().as_ptr() != first_chunk_start);
+    assert_eq!(first_batch[0].msg_seq_num(),Some(2));
+    assert_eq!(first_batch[0].bytes(),second_batch[0].bytes());
+
+    //Once released, the chunk is reused.
+    let second_chunk_start = second_batch[0].bytes().as_ptr();
+    drop(second_batch);
+    assert!(arena.is_released());
+    let (_,third_batch) = arena.prescan_messages(&parser,&message_bytes[..]);
+    assert_eq!(third_batch[0].bytes().as_ptr(),second_chunk_start);
+    assert_eq!(third_batch[1].msg_seq_num(),Some(3));
+}
+
+#[test]
+fn test_lazy_message_decode_with_busy_parser() {
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 2;
+    message.test_req_id = b"test_id".to_vec();
+    let mut bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+    let message_bytes = bytes.bytes().to_vec();
+
+    let mut parser = new_parser();
+    let (_,mut messages) = prescan_lazy_messages(&parser,&message_bytes[..]);
+    let mut message = messages.remove(0);
+
+    //A parser part way through another message is refused instead of being disturbed.
+    let (_,result) = parser.parse(&message_bytes[..message_bytes.len() / 2]);
+    assert!(result.is_ok());
+    assert!(!parser.is_idle());
+    assert!(if let Err(&ParseError::ParserNotIdle) = message.get::<TestRequest>(&mut parser) { true } else { false });
+    assert!(!parser.is_idle());
+
+    //The message can still be decoded once the parser is idle again.
+    parser.reset_parser();
+    assert_eq!(message.get::<TestRequest>(&mut parser).unwrap().unwrap().test_req_id,b"test_id");
+}
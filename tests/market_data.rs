@@ -0,0 +1,274 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use chrono::offset::utc::UTC;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,MAX_MESSAGE_SIZE,TestStream,new_logon_message};
+use fix_rs::dictionary::field_types::other::{MDEntryType,MDReqRejReason,MDUpdateType,SubscriptionRequestType};
+use fix_rs::dictionary::fields::MDFullGrp;
+use fix_rs::dictionary::messages::{Heartbeat,Logon,MarketDataIncrementalRefresh,MarketDataRequest,MarketDataRequestReject,MarketDataSnapshotFullRefresh};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::{Connection,ConnectionBuilder,EngineBuilder,EngineEvent};
+use fix_rs::fixt::market_data::{MarketDataSubscriptions,StaleMarketDataFilter,SubscriptionStatus};
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::middleware::{InboundMiddleware,MiddlewareAction};
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_market_data_request_builder() {
+    let mut subscriptions = MarketDataSubscriptions::new(b"MD");
+
+    //Each request gets its own MDReqID.
+    let snapshot = subscriptions.snapshot(&[b"AAA"],&[MDEntryType::Bid,MDEntryType::Offer],1);
+    assert_eq!(snapshot.md_req_id,b"MD1");
+    assert_eq!(snapshot.subscription_request_type,SubscriptionRequestType::Snapshot);
+    assert_eq!(snapshot.market_depth,1);
+    assert!(snapshot.md_update_type.is_none());
+    assert_eq!(snapshot.no_md_entry_types.len(),2);
+    assert_eq!(snapshot.no_md_entry_types[0].md_entry_type,MDEntryType::Bid);
+    assert_eq!(snapshot.no_md_entry_types[1].md_entry_type,MDEntryType::Offer);
+    assert_eq!(snapshot.no_related_sym.len(),1);
+    assert_eq!(snapshot.no_related_sym[0].symbol,b"AAA");
+
+    let subscribe = subscriptions.subscribe(&[b"BBB",b"CCC"],&[MDEntryType::Trade],0,MDUpdateType::IncrementalRefresh);
+    assert_eq!(subscribe.md_req_id,b"MD2");
+    assert_eq!(subscribe.subscription_request_type,SubscriptionRequestType::SnapshotPlusUpdates);
+    assert_eq!(subscribe.md_update_type,Some(MDUpdateType::IncrementalRefresh));
+    assert_eq!(subscribe.no_related_sym.len(),2);
+    assert_eq!(subscribe.no_related_sym[1].symbol,b"CCC");
+
+    assert_eq!(subscriptions.get(b"MD1").unwrap().status,SubscriptionStatus::Pending);
+    assert_eq!(subscriptions.get(b"MD2").unwrap().status,SubscriptionStatus::Pending);
+    assert!(subscriptions.get(b"MD3").is_none());
+
+    //Snapshot-only requests can't be unsubscribed.
+    assert!(subscriptions.unsubscribe(b"MD1").is_none());
+    assert!(subscriptions.unsubscribe(b"MD3").is_none());
+
+    //Unsubscribing reuses the original MDReqID and parameters.
+    let unsubscribe = subscriptions.unsubscribe(b"MD2").unwrap();
+    assert_eq!(unsubscribe.md_req_id,b"MD2");
+    assert_eq!(unsubscribe.subscription_request_type,SubscriptionRequestType::DisablePreviousSnapshotPlusUpdateRequest);
+    assert_eq!(unsubscribe.no_related_sym.len(),2);
+    assert_eq!(subscriptions.get(b"MD2").unwrap().status,SubscriptionStatus::Unsubscribed);
+    assert!(subscriptions.unsubscribe(b"MD2").is_none());
+}
+
+#[test]
+fn test_market_data_resubscribe() {
+    let mut subscriptions = MarketDataSubscriptions::new(b"MD");
+    let _ = subscriptions.snapshot(&[b"AAA"],&[MDEntryType::Bid],1);
+    let _ = subscriptions.subscribe(&[b"BBB"],&[MDEntryType::Trade],0,MDUpdateType::IncrementalRefresh);
+    let _ = subscriptions.subscribe(&[b"CCC"],&[MDEntryType::Trade],0,MDUpdateType::FullRefresh);
+    let _ = subscriptions.subscribe(&[b"DDD"],&[MDEntryType::Trade],0,MDUpdateType::FullRefresh);
+    let _ = subscriptions.unsubscribe(b"MD4").unwrap();
+
+    //Only subscriptions that could still be receiving updates are renewed, each with a new MDReqID.
+    let requests = subscriptions.resubscribe();
+    assert_eq!(requests.len(),2);
+    assert_eq!(requests[0].md_req_id,b"MD5");
+    assert_eq!(requests[0].subscription_request_type,SubscriptionRequestType::SnapshotPlusUpdates);
+    assert_eq!(requests[0].md_update_type,Some(MDUpdateType::IncrementalRefresh));
+    assert_eq!(requests[0].no_related_sym[0].symbol,b"BBB");
+    assert_eq!(requests[1].md_req_id,b"MD6");
+    assert_eq!(requests[1].no_related_sym[0].symbol,b"CCC");
+
+    assert!(subscriptions.get(b"MD2").is_none());
+    assert!(subscriptions.get(b"MD3").is_none());
+    assert_eq!(subscriptions.get(b"MD5").unwrap().status,SubscriptionStatus::Pending);
+    assert_eq!(subscriptions.get(b"MD6").unwrap().status,SubscriptionStatus::Pending);
+    assert_eq!(subscriptions.get(b"MD1").unwrap().status,SubscriptionStatus::Pending);
+    assert_eq!(subscriptions.get(b"MD4").unwrap().status,SubscriptionStatus::Unsubscribed);
+}
+
+#[test]
+fn test_market_data_response_correlation() {
+    let mut subscriptions = MarketDataSubscriptions::new(b"");
+    subscriptions.snapshot(&[b"AAA"],&[MDEntryType::Bid],1);
+    subscriptions.subscribe(&[b"BBB"],&[MDEntryType::Bid],1,MDUpdateType::FullRefresh);
+    subscriptions.subscribe(&[b"CCC"],&[MDEntryType::Bid],1,MDUpdateType::IncrementalRefresh);
+    subscriptions.subscribe(&[b"DDD"],&[MDEntryType::Bid],1,MDUpdateType::IncrementalRefresh);
+
+    //Snapshot completes a snapshot-only request.
+    let mut message = MarketDataSnapshotFullRefresh::new();
+    message.md_req_id = b"1".to_vec();
+    assert_eq!(subscriptions.on_message(&message).unwrap().status,SubscriptionStatus::Completed);
+
+    //Snapshot or incremental refresh activates a subscription.
+    message.md_req_id = b"2".to_vec();
+    assert_eq!(subscriptions.on_message(&message).unwrap().status,SubscriptionStatus::Active);
+    let mut message = MarketDataIncrementalRefresh::new();
+    message.md_req_id = b"3".to_vec();
+    assert_eq!(subscriptions.on_message(&message).unwrap().status,SubscriptionStatus::Active);
+
+    //Reject is matched with its reason and text.
+    let mut message = MarketDataRequestReject::new();
+    message.md_req_id = b"4".to_vec();
+    message.md_req_rej_reason = Some(MDReqRejReason::UnknownSymbol);
+    message.text = b"Unknown symbol".to_vec();
+    assert_eq!(subscriptions.on_message(&message).unwrap().status,SubscriptionStatus::Rejected(Some(MDReqRejReason::UnknownSymbol),b"Unknown symbol".to_vec()));
+
+    //Rejected subscriptions, unsubscribed subscriptions, unknown MDReqIDs, and unrelated messages
+    //are ignored.
+    let mut message = MarketDataIncrementalRefresh::new();
+    message.md_req_id = b"4".to_vec();
+    assert!(subscriptions.on_message(&message).is_none());
+    subscriptions.unsubscribe(b"3").unwrap();
+    message.md_req_id = b"3".to_vec();
+    assert!(subscriptions.on_message(&message).is_none());
+    message.md_req_id = b"5".to_vec();
+    assert!(subscriptions.on_message(&message).is_none());
+    assert!(subscriptions.on_message(&Heartbeat::new()).is_none());
+}
+
+#[test]
+fn test_market_data_subscription_round_trip() {
+    define_dictionary!(
+        Logon,
+        MarketDataIncrementalRefresh,
+        MarketDataRequest,
+        MarketDataRequestReject,
+        MarketDataSnapshotFullRefresh,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Send a subscription and make sure it arrives intact.
+    let mut subscriptions = MarketDataSubscriptions::new(b"MD");
+    let request = subscriptions.subscribe(&[b"AAA"],&[MDEntryType::Bid,MDEntryType::Offer],1,MDUpdateType::IncrementalRefresh);
+    client.send_message(connection,request);
+    let message = test_server.recv_message::<MarketDataRequest>();
+    assert_eq!(message.md_req_id,b"MD1");
+    assert_eq!(message.subscription_request_type,SubscriptionRequestType::SnapshotPlusUpdates);
+    assert_eq!(message.no_md_entry_types.len(),2);
+    assert_eq!(message.no_related_sym.len(),1);
+    assert_eq!(message.no_related_sym[0].symbol,b"AAA");
+
+    //Respond with a snapshot and make sure it's matched to the subscription.
+    let mut message = new_fixt_message!(MarketDataSnapshotFullRefresh);
+    message.msg_seq_num = 2;
+    message.md_req_id = b"MD1".to_vec();
+    message.symbol = b"AAA".to_vec();
+    let mut md_entry = MDFullGrp::new();
+    md_entry.md_entry_type = MDEntryType::Bid;
+    md_entry.md_entry_px = b"10.5".to_vec();
+    md_entry.md_entry_size = b"100".to_vec();
+    message.no_md_entries.push(Box::new(md_entry));
+    test_server.send_message(message);
+    let message = engine_poll_message!(client,connection,MarketDataSnapshotFullRefresh);
+    let subscription = subscriptions.on_message(&message).unwrap();
+    assert_eq!(subscription.md_req_id,b"MD1");
+    assert_eq!(subscription.status,SubscriptionStatus::Active);
+
+    //Unsubscribe.
+    let request = subscriptions.unsubscribe(b"MD1").unwrap();
+    client.send_message(connection,request);
+    let message = test_server.recv_message::<MarketDataRequest>();
+    assert_eq!(message.md_req_id,b"MD1");
+    assert_eq!(message.subscription_request_type,SubscriptionRequestType::DisablePreviousSnapshotPlusUpdateRequest);
+}
+
+#[test]
+fn test_stale_market_data_filter() {
+    let mut filter = StaleMarketDataFilter::new(Duration::from_secs(5));
+    let dropped_count = filter.dropped_count();
+    let connection = Connection { id: 3, generation: 0 };
+    let now = UTC::now();
+    let is_dropped = |filter: &mut StaleMarketDataFilter,message: Box<FIXTMessage + Send>| {
+        if let MiddlewareAction::Drop = filter.on_message_at(connection,None,now,message) { true } else { false }
+    };
+
+    //Fresh market data is kept.
+    let mut snapshot = new_fixt_message!(MarketDataSnapshotFullRefresh);
+    snapshot.sending_time = now - chrono::Duration::seconds(1);
+    assert!(!is_dropped(&mut filter,Box::new(snapshot.clone())));
+
+    //Age is measured against the time given by the engine's Clock, not the system clock.
+    snapshot.sending_time = now - chrono::Duration::seconds(10);
+    let result = filter.on_message_at(connection,None,now - chrono::Duration::seconds(8),Box::new(snapshot.clone()));
+    assert!(if let MiddlewareAction::Continue(_) = result { true } else { false });
+
+    //Old snapshots and incremental refreshes are dropped and counted.
+    assert!(is_dropped(&mut filter,Box::new(snapshot)));
+    let mut incremental_refresh = new_fixt_message!(MarketDataIncrementalRefresh);
+    incremental_refresh.sending_time = now - chrono::Duration::seconds(10);
+    assert!(is_dropped(&mut filter,Box::new(incremental_refresh)));
+    assert_eq!(dropped_count.load(Ordering::Relaxed),2);
+
+    //Anything else is kept no matter how old.
+    let mut reject = new_fixt_message!(MarketDataRequestReject);
+    reject.sending_time = now - chrono::Duration::seconds(10);
+    assert!(!is_dropped(&mut filter,Box::new(reject)));
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.sending_time = now - chrono::Duration::seconds(10);
+    assert!(!is_dropped(&mut filter,Box::new(heartbeat)));
+    assert_eq!(dropped_count.load(Ordering::Relaxed),2);
+}
+
+#[test]
+fn test_market_data_conflation() {
+    define_dictionary!(
+        Logon,
+        MarketDataSnapshotFullRefresh,
+    );
+
+    //Connect and logon.
+    let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE).conflate_market_data(true);
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_builders(engine_builder,connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let _ = test_server.recv_message::<Logon>();
+    test_server.send_message(new_logon_message());
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    //Send several snapshots without polling so they back up.
+    for (msg_seq_num,symbol) in vec![b"AAA",b"AAA",b"BBB",b"AAA"].into_iter().enumerate() {
+        let mut message = new_fixt_message!(MarketDataSnapshotFullRefresh);
+        message.msg_seq_num = msg_seq_num as u64 + 2;
+        message.md_req_id = b"MD1".to_vec();
+        message.symbol = symbol.to_vec();
+        let mut md_entry = MDFullGrp::new();
+        md_entry.md_entry_type = MDEntryType::Bid;
+        md_entry.md_entry_px = b"10.5".to_vec();
+        md_entry.md_entry_size = b"100".to_vec();
+        message.no_md_entries.push(Box::new(md_entry));
+        test_server.send_message(message);
+    }
+    thread::sleep(Duration::from_millis(500));
+
+    //Only the latest snapshot for each symbol is left.
+    let message = engine_poll_message!(client,connection,MarketDataSnapshotFullRefresh);
+    assert_eq!(message.symbol,b"BBB");
+    assert_eq!(message.msg_seq_num,4);
+    let message = engine_poll_message!(client,connection,MarketDataSnapshotFullRefresh);
+    assert_eq!(message.symbol,b"AAA");
+    assert_eq!(message.msg_seq_num,5);
+    assert_eq!(client.conflated_count(),2);
+    assert!(client.poll(Duration::from_millis(100)).is_none());
+}
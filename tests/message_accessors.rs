@@ -0,0 +1,57 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,MarketDataIncrementalRefresh,MarketDataSnapshotFullRefresh,NewOrderSingle};
+use fix_rs::fixt::message::FIXTMessage;
+
+#[test]
+fn test_hot_field_accessors() {
+    let mut new_order_single = new_fixt_message!(NewOrderSingle);
+    new_order_single.msg_seq_num = 7;
+    new_order_single.cl_ord_id = b"ORDER1".to_vec();
+    new_order_single.symbol = b"AAA".to_vec();
+    let mut execution_report = new_fixt_message!(ExecutionReport);
+    execution_report.symbol = b"BBB".to_vec();
+    let mut snapshot = new_fixt_message!(MarketDataSnapshotFullRefresh);
+    snapshot.symbol = b"CCC".to_vec();
+
+    //Everything is available through the trait object without downcasting.
+    let messages: Vec<Box<FIXTMessage + Send>> = vec![
+        Box::new(new_order_single),
+        Box::new(execution_report),
+        Box::new(snapshot),
+        Box::new(new_fixt_message!(MarketDataIncrementalRefresh)),
+        Box::new(new_fixt_message!(Heartbeat)),
+    ];
+    let accessed: Vec<(&[u8],Option<&[u8]>,Option<&[u8]>)> = messages.iter()
+        .map(|message| (message.msg_type(),message.symbol_bytes(),message.cl_ord_id_bytes()))
+        .collect();
+    assert_eq!(accessed,vec![
+        (&b"D"[..],Some(&b"AAA"[..]),Some(&b"ORDER1"[..])),
+        (&b"8"[..],Some(&b"BBB"[..]),None), //ClOrdID is optional and empty.
+        (&b"W"[..],Some(&b"CCC"[..]),None),
+        (&b"X"[..],None,None), //Symbols are only inside the repeating group.
+        (&b"0"[..],None,None),
+    ]);
+    assert_eq!(messages[0].msg_seq_num(),7);
+}
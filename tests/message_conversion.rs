@@ -0,0 +1,110 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::new_logon_message;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::dictionary::field_types::other::{ExecType,OrdStatus,Side};
+use fix_rs::dictionary::fields::{DefaultApplVerID,ExecType as ExecTypeField,OrdStatus as OrdStatusField,Username};
+use fix_rs::dictionary::messages::{ExecutionReport,Logon};
+use fix_rs::field::Field;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_conversion::convert_message;
+use fix_rs::message_version::MessageVersion;
+
+fn execution_report(exec_type: ExecType,ord_status: OrdStatus,cum_qty: &[u8],leaves_qty: &[u8]) -> ExecutionReport {
+    let mut message = new_fixt_message!(ExecutionReport);
+    message.order_id = b"VENUE1".to_vec();
+    message.cl_ord_id = b"1".to_vec();
+    message.exec_id = b"E1".to_vec();
+    message.exec_type = exec_type;
+    message.ord_status = ord_status;
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.cum_qty = cum_qty.to_vec();
+    message.leaves_qty = leaves_qty.to_vec();
+
+    message
+}
+
+#[test]
+fn test_downgrade_drops_unsupported_fields() {
+    let mut logon = new_logon_message();
+    logon.username = b"user".to_vec();
+
+    let converted = convert_message(&logon,MessageVersion::FIX50SP2,MessageVersion::FIX42);
+    assert_eq!(converted.fix_version,FIXVersion::FIX_4_2);
+    assert!(converted.dropped_fields.contains(&Username::tag()));
+    assert!(converted.dropped_fields.contains(&DefaultApplVerID::tag()));
+    assert!(converted.mapped_fields.is_empty());
+
+    let mut bytes = ByteBuffer::new();
+    converted.read(&mut bytes);
+    let message = String::from_utf8_lossy(bytes.bytes()).into_owned();
+    assert!(message.starts_with("8=FIX.4.2\x01"));
+    assert!(!message.contains("\x01553="));
+    assert!(!message.contains("\x011137="));
+
+    //The original message is left untouched.
+    assert_eq!(logon.username,b"user".to_vec());
+    assert!(converted.message.as_any().downcast_ref::<Logon>().is_some());
+}
+
+#[test]
+fn test_exec_type_mapping() {
+    //Fill becomes Trade when upgrading.
+    let message = execution_report(ExecType::Fill,OrdStatus::Filled,b"100",b"0");
+    let converted = convert_message(&message,MessageVersion::FIX42,MessageVersion::FIX50SP2);
+    assert_eq!(converted.fix_version,FIXVersion::FIXT_1_1);
+    assert_eq!(converted.mapped_fields,vec![ExecTypeField::tag()]);
+    let converted_message = converted.message.as_any().downcast_ref::<ExecutionReport>().unwrap();
+    assert_eq!(converted_message.exec_type,ExecType::Trade);
+    assert_eq!(message.exec_type,ExecType::Fill);
+
+    //Trade becomes PartialFill or Fill depending on LeavesQty when downgrading.
+    let message = execution_report(ExecType::Trade,OrdStatus::PartiallyFilled,b"40",b"60");
+    let converted = convert_message(&message,MessageVersion::FIX50SP2,MessageVersion::FIX42);
+    let converted_message = converted.message.as_any().downcast_ref::<ExecutionReport>().unwrap();
+    assert_eq!(converted_message.exec_type,ExecType::PartialFill);
+
+    let message = execution_report(ExecType::Trade,OrdStatus::Filled,b"100",b"0");
+    let converted = convert_message(&message,MessageVersion::FIX50SP2,MessageVersion::FIX42);
+    let converted_message = converted.message.as_any().downcast_ref::<ExecutionReport>().unwrap();
+    assert_eq!(converted_message.exec_type,ExecType::Fill);
+
+    //Converting to the same family of versions leaves values alone.
+    let message = execution_report(ExecType::Trade,OrdStatus::Filled,b"100",b"0");
+    let converted = convert_message(&message,MessageVersion::FIX50SP2,MessageVersion::FIX44);
+    assert!(converted.mapped_fields.is_empty());
+}
+
+#[test]
+fn test_ord_status_replaced_mapping() {
+    let message = execution_report(ExecType::Replaced,OrdStatus::Replaced,b"40",b"60");
+    let converted = convert_message(&message,MessageVersion::FIX42,MessageVersion::FIX44);
+    assert_eq!(converted.mapped_fields,vec![OrdStatusField::tag()]);
+    let converted_message = converted.message.as_any().downcast_ref::<ExecutionReport>().unwrap();
+    assert_eq!(converted_message.exec_type,ExecType::Replaced);
+    assert_eq!(converted_message.ord_status,OrdStatus::PartiallyFilled);
+}
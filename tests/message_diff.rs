@@ -0,0 +1,114 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::{new_logon_message,new_order_single};
+use fix_rs::dictionary::field_types::other::MsgDirection;
+use fix_rs::dictionary::fields::{MsgDirection as MsgDirectionField,MsgTypeGrp,NoMsgTypeGrp,Price,RefMsgType,SendingTime};
+use fix_rs::field::Field;
+use fix_rs::message_diff::{FieldDiff,diff};
+
+fn new_msg_type_grp(ref_msg_type: &[u8],msg_direction: MsgDirection) -> Box<MsgTypeGrp> {
+    let mut group = MsgTypeGrp::new();
+    group.ref_msg_type = ref_msg_type.to_vec();
+    group.msg_direction = msg_direction;
+
+    Box::new(group)
+}
+
+#[test]
+fn test_diff_identical_messages() {
+    let message = new_order_single(b"1");
+    assert!(diff(&message,&message.clone()).is_empty());
+}
+
+#[test]
+fn test_diff_only_sending_time() {
+    let message = new_order_single(b"1");
+    let mut other_message = message.clone();
+    other_message.sending_time = message.sending_time + chrono::Duration::seconds(1);
+
+    let diffs = diff(&message,&other_message);
+    assert_eq!(diffs.len(),1);
+    assert_eq!(diffs[0].tag,SendingTime::tag());
+    assert_eq!(diffs[0].name,"sending_time");
+    assert!(diffs[0].groups.is_empty());
+    assert!(diffs[0].left.is_some());
+    assert!(diffs[0].right.is_some());
+    assert!(diffs[0].left != diffs[0].right);
+
+    //Missing fields are reported on the side they are missing from.
+    other_message.sending_time = message.sending_time;
+    other_message.price.clear();
+    let diffs = diff(&message,&other_message);
+    assert_eq!(diffs,vec![FieldDiff {
+        groups: Vec::new(),
+        tag: Price::tag(),
+        name: "price",
+        left: Some(b"9.5".to_vec()),
+        right: None,
+    }]);
+    assert_eq!(diffs[0].to_string(),"price(44): 9.5 != <missing>");
+}
+
+#[test]
+fn test_diff_repeating_groups() {
+    let mut message = new_logon_message();
+    message.no_msg_types.push(new_msg_type_grp(b"D",MsgDirection::Send));
+    let mut other_message = message.clone();
+    other_message.no_msg_types[0].ref_msg_type = b"F".to_vec();
+    other_message.no_msg_types.push(new_msg_type_grp(b"8",MsgDirection::Receive));
+
+    let diffs = diff(&message,&other_message);
+    assert_eq!(diffs,vec![
+        FieldDiff {
+            groups: Vec::new(),
+            tag: NoMsgTypeGrp::tag(),
+            name: "no_msg_types",
+            left: Some(b"1".to_vec()),
+            right: Some(b"2".to_vec()),
+        },
+        FieldDiff {
+            groups: vec![(NoMsgTypeGrp::tag(),0)],
+            tag: RefMsgType::tag(),
+            name: "ref_msg_type",
+            left: Some(b"D".to_vec()),
+            right: Some(b"F".to_vec()),
+        },
+        FieldDiff {
+            groups: vec![(NoMsgTypeGrp::tag(),1)],
+            tag: RefMsgType::tag(),
+            name: "ref_msg_type",
+            left: None,
+            right: Some(b"8".to_vec()),
+        },
+        FieldDiff {
+            groups: vec![(NoMsgTypeGrp::tag(),1)],
+            tag: MsgDirectionField::tag(),
+            name: "msg_direction",
+            left: None,
+            right: Some(b"R".to_vec()),
+        },
+    ]);
+    assert_eq!(diffs[1].to_string(),"384[0].ref_msg_type(372): D != F");
+}
@@ -0,0 +1,91 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::{new_logon_message,new_order_single};
+use fix_rs::dictionary::field_types::other::MsgDirection;
+use fix_rs::dictionary::fields::{MsgTypeGrp,NoMsgTypeGrp,Price,SenderCompID,SendingTime,TargetCompID};
+use fix_rs::field::Field;
+use fix_rs::message_hash::{MessageHasher,hash};
+
+#[test]
+fn test_hash_ignores_volatile_fields() {
+    let message = new_order_single(b"1");
+
+    //A resend of the same message only changes the volatile fields.
+    let mut resent_message = message.clone();
+    resent_message.msg_seq_num = 15;
+    resent_message.poss_dup_flag = true;
+    resent_message.orig_sending_time = message.sending_time;
+    resent_message.sending_time = message.sending_time + chrono::Duration::seconds(5);
+    assert_eq!(hash(&message),hash(&resent_message));
+
+    //Any other change must change the hash.
+    let mut changed_message = message.clone();
+    changed_message.price = b"9.6".to_vec();
+    assert!(hash(&message) != hash(&changed_message));
+
+    //The hash must be repeatable.
+    assert_eq!(hash(&message),hash(&message.clone()));
+}
+
+#[test]
+fn test_hash_excluded_tags() {
+    let message = new_order_single(b"1");
+    let mut changed_message = message.clone();
+    changed_message.price = b"9.6".to_vec();
+    changed_message.sending_time = message.sending_time + chrono::Duration::seconds(5);
+
+    let hasher = MessageHasher::new().exclude_tag(Price::tag());
+    assert_eq!(hasher.hash(&message),hasher.hash(&changed_message));
+
+    let hasher = hasher.include_tag(SendingTime::tag());
+    assert!(hasher.hash(&message) != hasher.hash(&changed_message));
+
+    //The same order seen from the other side of a session, or on a different session, only
+    //matches once the CompIDs are excluded.
+    let mut other_session_message = message.clone();
+    other_session_message.sender_comp_id = b"DROPCOPY".to_vec();
+    other_session_message.target_comp_id = b"CLIENT".to_vec();
+    assert!(hash(&message) != hash(&other_session_message));
+    let hasher = MessageHasher::new().exclude_tag(SenderCompID::tag()).exclude_tag(TargetCompID::tag());
+    assert_eq!(hasher.hash(&message),hasher.hash(&other_session_message));
+}
+
+#[test]
+fn test_hash_excluded_repeating_group() {
+    let mut message = new_logon_message();
+    let mut other_message = message.clone();
+    let mut group = MsgTypeGrp::new();
+    group.ref_msg_type = b"D".to_vec();
+    group.msg_direction = MsgDirection::Send;
+    other_message.no_msg_types.push(Box::new(group));
+    assert!(hash(&message) != hash(&other_message));
+
+    //Excluding the count tag leaves out every field inside the group too.
+    let hasher = MessageHasher::new().exclude_tag(NoMsgTypeGrp::tag());
+    assert_eq!(hasher.hash(&message),hasher.hash(&other_message));
+
+    message.heart_bt_int += 1;
+    assert!(hasher.hash(&message) != hasher.hash(&other_message));
+}
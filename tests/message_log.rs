@@ -0,0 +1,138 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::new_logon_message;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message_log::{MessageLogFormat,MessageLogSettings,format_message};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn contains(haystack: &[u8],needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|window| window == needle)
+}
+
+#[test]
+fn test_binary_values_are_encoded() {
+    let mut message = new_logon_message();
+    message.raw_data = b"\x01\x02AB".to_vec();
+
+    let mut settings = MessageLogSettings::new();
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(entry.starts_with(b"8=FIXT.1.1|35=A|"));
+    assert!(contains(&entry,b"|96=base64:AQJBQg==|"));
+    assert!(!entry.contains(&b'\x01'));
+
+    //Values containing the separator must be encoded so fields can be split apart again.
+    message.raw_data = b"A|B".to_vec();
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(contains(&entry,b"|96=base64:QXxC|"));
+
+    settings.soh_replacement = b'^';
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(entry.starts_with(b"8=FIXT.1.1^35=A^"));
+    assert!(contains(&entry,b"^96=A|B^"));
+}
+
+#[test]
+fn test_truncation() {
+    let mut message = new_logon_message();
+    message.raw_data = vec![0xFF;3000];
+
+    let settings = MessageLogSettings {
+        format: MessageLogFormat::Text,
+        max_length: None,
+        soh_replacement: b'|',
+    };
+    let full_entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(contains(&full_entry,&[b"|96=base64:".to_vec(),vec![b'/';4000],b"|".to_vec()].concat()));
+
+    //Cut off in the middle of a field with an indicator saying how much was left out.
+    let settings = MessageLogSettings {
+        format: MessageLogFormat::Text,
+        max_length: Some(20),
+        soh_replacement: b'|',
+    };
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    let expected_entry = [&full_entry[..20],format!("...[truncated {} bytes]",full_entry.len() - 20).as_bytes()].concat();
+    assert_eq!(String::from_utf8_lossy(&entry),String::from_utf8_lossy(&expected_entry));
+
+    //Cut off near the end.
+    let max_length = full_entry.len() - 100;
+    let settings = MessageLogSettings {
+        format: MessageLogFormat::Text,
+        max_length: Some(max_length),
+        soh_replacement: b'|',
+    };
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    let expected_entry = [&full_entry[..max_length],&b"...[truncated 100 bytes]"[..]].concat();
+    assert_eq!(String::from_utf8_lossy(&entry),String::from_utf8_lossy(&expected_entry));
+}
+
+#[test]
+fn test_json_lines_format() {
+    let mut message = new_logon_message();
+    message.raw_data = b"say \"hi\"\\".to_vec();
+
+    let mut settings = MessageLogSettings::new();
+    settings.format = MessageLogFormat::JsonLines;
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(entry.starts_with(b"{\"fields\":[[8,\"FIXT.1.1\"],[35,\"A\"],"));
+    assert!(contains(&entry,b"[96,\"say \\\"hi\\\"\\\\\"]"));
+    assert!(entry.ends_with(b"\"]]}"));
+
+    //Binary values are encoded the same as Text. The separator doesn't matter here.
+    message.raw_data = b"\x01\x02AB".to_vec();
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(contains(&entry,b"[96,\"base64:AQJBQg==\"]"));
+    message.raw_data = b"A|B".to_vec();
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert!(contains(&entry,b"[96,\"A|B\"]"));
+
+    //Truncated entries are still complete JSON objects.
+    settings.max_length = Some(40);
+    let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    let entry = String::from_utf8(entry).unwrap();
+    assert!(entry.starts_with("{\"fields\":[[8,\"FIXT.1.1\"],[35,\"A\"]"));
+    assert!(entry.contains("]],\"truncated\":"));
+    assert!(entry.ends_with("}"));
+}
+
+#[test]
+fn test_raw_formats_are_not_modified() {
+    let mut message = new_logon_message();
+    message.raw_data = vec![0x01;3000];
+
+    let mut expected_bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut expected_bytes);
+
+    for format in vec![MessageLogFormat::RawFIX,MessageLogFormat::LengthPrefixed] {
+        let settings = MessageLogSettings {
+            format: format,
+            max_length: Some(20),
+            soh_replacement: b'|',
+        };
+        let entry = format_message(&settings,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+        assert_eq!(entry,expected_bytes.bytes());
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate fix_rs;
+
+use fix_rs::dictionary::fields::{EmailThreadID,HeartBtInt,NoMsgTypeGrp,RefMsgType,RelatedSym,SecurityIDSource,Username};
+use fix_rs::dictionary::messages::{Email,ExecutionReport,Logon};
+use fix_rs::field::Field;
+use fix_rs::message::MessageDetails;
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_message_metadata() {
+    let metadata = Logon::message_metadata();
+    assert_eq!(metadata.name,"Logon");
+    assert_eq!(metadata.msg_type,b"A");
+
+    let heart_bt_int = metadata.field(HeartBtInt::tag()).unwrap();
+    assert_eq!(heart_bt_int.name,"heart_bt_int");
+    assert!(heart_bt_int.required);
+    assert_eq!(heart_bt_int.minimum_version,MessageVersion::FIX40);
+    assert_eq!(heart_bt_int.maximum_version,MessageVersion::FIX50SP2);
+
+    let username = metadata.field(Username::tag()).unwrap();
+    assert!(!username.required);
+    assert!(!username.supports(MessageVersion::FIX42));
+    assert!(username.supports(MessageVersion::FIX43));
+    assert!(metadata.fields_for_version(MessageVersion::FIX42).iter().all(|field| field.tag != Username::tag()));
+
+    //Fields that were removed in a later version.
+    let metadata = Email::message_metadata();
+    let related_sym = metadata.field(RelatedSym::tag()).unwrap();
+    assert_eq!(related_sym.maximum_version,MessageVersion::FIX41);
+    assert!(!related_sym.supports(MessageVersion::FIX42));
+    assert!(metadata.field(EmailThreadID::tag()).unwrap().required);
+
+    //Conditionally required fields.
+    let metadata = ExecutionReport::message_metadata();
+    let security_id_source = metadata.field(SecurityIDSource::tag()).unwrap();
+    assert!(!security_id_source.required);
+    assert!(security_id_source.conditionally_required);
+}
+
+#[test]
+fn test_message_metadata_table() {
+    let table = Email::message_metadata().to_string();
+    let mut lines = table.lines();
+    assert_eq!(lines.next().unwrap(),"| Field | Tag | Required | Versions |");
+    assert!(table.contains("| email_thread_id | 164 | Yes | FIX41+ |"));
+    assert!(table.contains("| related_sym | 46 | No | FIX40-FIX41 |"));
+}
+
+#[test]
+fn test_message_metadata_reflection() {
+    let metadata = Logon::metadata();
+    assert_eq!(metadata,Logon::message_metadata());
+
+    let heart_bt_int = metadata.field(HeartBtInt::tag()).unwrap();
+    assert_eq!(heart_bt_int.field_name,"HeartBtInt");
+    assert_eq!(heart_bt_int.field_type,"IntFieldType");
+    assert!(heart_bt_int.group.is_none());
+
+    //Repeating groups describe the fields of each entry.
+    let no_msg_types = metadata.field(NoMsgTypeGrp::tag()).unwrap();
+    assert_eq!(no_msg_types.field_name,"NoMsgTypeGrp");
+    let group = no_msg_types.group.as_ref().unwrap();
+    assert_eq!(group.name,"MsgTypeGrp");
+    assert!(group.msg_type.is_empty());
+    assert_eq!(group.fields[0].tag,RefMsgType::tag());
+    assert!(group.fields[0].required);
+    assert_eq!(group.fields[0].minimum_version,MessageVersion::FIX42);
+}
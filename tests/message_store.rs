@@ -0,0 +1,371 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use std::env;
+use std::fs::{self,OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use fix_rs::fixt::message_store::{FileMessageStore,MemoryMessageStore,MessageStore};
+#[cfg(feature = "store-compression")]
+use fix_rs::fixt::message_store::ZstdCompression;
+#[cfg(feature = "store-encryption")]
+use fix_rs::fixt::store_encryption::{KeyProvider,StaticKeyProvider,StoreEncryption};
+
+fn test_directory(name: &str) -> PathBuf {
+    let directory = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&directory);
+
+    directory
+}
+
+#[test]
+fn test_memory_message_store() {
+    let mut message_store = MemoryMessageStore::new();
+    assert_eq!(message_store.next_inbound_msg_seq_num(),1);
+    assert_eq!(message_store.next_outbound_msg_seq_num(),1);
+
+    message_store.store_outbound(1,b"first").unwrap();
+    message_store.store_outbound(3,b"third").unwrap();
+    message_store.set_next_outbound_msg_seq_num(4).unwrap();
+    assert_eq!(message_store.next_outbound_msg_seq_num(),4);
+    assert_eq!(message_store.outbound_messages(1..4).unwrap(),vec![(1,b"first".to_vec()),(3,b"third".to_vec())]);
+    assert!(message_store.outbound_messages(2..3).unwrap().is_empty());
+}
+
+#[test]
+fn test_file_message_store_reopen() {
+    let directory = test_directory("fix_rs_test_file_message_store_reopen");
+
+    //Everything is kept when reopened, including messages containing the record separator.
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        assert_eq!(message_store.next_inbound_msg_seq_num(),1);
+        assert_eq!(message_store.next_outbound_msg_seq_num(),1);
+
+        message_store.store_outbound(1,b"8=FIXT.1.1\x01").unwrap();
+        message_store.store_outbound(2,b"two\nlines").unwrap();
+        message_store.store_outbound(3,b"").unwrap();
+        message_store.set_next_inbound_msg_seq_num(7).unwrap();
+        message_store.set_next_outbound_msg_seq_num(4).unwrap();
+    }
+
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert_eq!(message_store.next_inbound_msg_seq_num(),7);
+    assert_eq!(message_store.next_outbound_msg_seq_num(),4);
+    assert_eq!(message_store.outbound_messages(1..4).unwrap(),vec![
+        (1,b"8=FIXT.1.1\x01".to_vec()),
+        (2,b"two\nlines".to_vec()),
+        (3,b"".to_vec()),
+    ]);
+
+    //Storing the same MsgSeqNum again replaces it.
+    message_store.store_outbound(2,b"replaced").unwrap();
+    assert_eq!(message_store.outbound_messages(2..3).unwrap(),vec![(2,b"replaced".to_vec())]);
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert_eq!(message_store.outbound_messages(2..3).unwrap(),vec![(2,b"replaced".to_vec())]);
+
+    //Reset forgets everything.
+    message_store.reset().unwrap();
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert_eq!(message_store.next_inbound_msg_seq_num(),1);
+    assert_eq!(message_store.next_outbound_msg_seq_num(),1);
+    assert!(message_store.outbound_messages(1..4).unwrap().is_empty());
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_file_message_store_partial_record() {
+    let directory = test_directory("fix_rs_test_file_message_store_partial_record");
+
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        message_store.store_outbound(1,b"first").unwrap();
+    }
+
+    //Simulate a crash in the middle of writing a message.
+    {
+        let mut file = OpenOptions::new().append(true).open(directory.join("messages")).unwrap();
+        file.write_all(b"2 100\nsecond").unwrap();
+    }
+
+    //The partial message is discarded and new messages are stored after the last complete one.
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        assert_eq!(message_store.outbound_messages(1..3).unwrap(),vec![(1,b"first".to_vec())]);
+        message_store.store_outbound(2,b"second").unwrap();
+    }
+
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert_eq!(message_store.outbound_messages(1..3).unwrap(),vec![(1,b"first".to_vec()),(2,b"second".to_vec())]);
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_message_store_order_acks() {
+    let mut message_store = MemoryMessageStore::new();
+    assert!(!message_store.is_order_acknowledged(b"1").unwrap());
+    message_store.store_order_ack(b"1").unwrap();
+    message_store.store_order_ack(b"1").unwrap();
+    assert!(message_store.is_order_acknowledged(b"1").unwrap());
+    assert!(!message_store.is_order_acknowledged(b"2").unwrap());
+
+    //Acknowledgements survive a restart, including after a partially written record.
+    let directory = test_directory("fix_rs_test_message_store_order_acks");
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        message_store.store_order_ack(b"1").unwrap();
+        message_store.store_order_ack(b"two\nlines").unwrap();
+        message_store.store_order_ack(b"1").unwrap();
+    }
+    {
+        let mut file = OpenOptions::new().append(true).open(directory.join("orderacks")).unwrap();
+        file.write_all(b"5\nthr").unwrap();
+    }
+
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        assert!(message_store.is_order_acknowledged(b"1").unwrap());
+        assert!(message_store.is_order_acknowledged(b"two\nlines").unwrap());
+        assert!(!message_store.is_order_acknowledged(b"thr").unwrap());
+        message_store.store_order_ack(b"three").unwrap();
+    }
+
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert!(message_store.is_order_acknowledged(b"three").unwrap());
+
+    //Reset forgets them too.
+    message_store.reset().unwrap();
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert!(!message_store.is_order_acknowledged(b"1").unwrap());
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn test_message_store_orders_sent() {
+    let mut message_store = MemoryMessageStore::new();
+    message_store.store_order_sent(b"1").unwrap();
+    message_store.store_order_sent(b"1").unwrap();
+    assert!(message_store.is_order_sent(b"1").unwrap());
+    assert!(!message_store.is_order_sent(b"2").unwrap());
+    assert!(!message_store.is_order_acknowledged(b"1").unwrap());
+
+    //Sent ClOrdIDs are kept separately from acknowledged ones and survive a restart.
+    let directory = test_directory("fix_rs_test_message_store_orders_sent");
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        message_store.store_order_sent(b"1").unwrap();
+        message_store.store_order_sent(b"2").unwrap();
+        message_store.store_order_ack(b"2").unwrap();
+    }
+
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert!(message_store.is_order_sent(b"1").unwrap());
+    assert!(!message_store.is_order_acknowledged(b"1").unwrap());
+    assert!(message_store.is_order_sent(b"2").unwrap());
+    assert!(message_store.is_order_acknowledged(b"2").unwrap());
+
+    message_store.reset().unwrap();
+    let mut message_store = FileMessageStore::open(&directory).unwrap();
+    assert!(!message_store.is_order_sent(b"1").unwrap());
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[cfg(feature = "store-compression")]
+#[test]
+fn test_file_message_store_compression() {
+    fn message(msg_seq_num: u64) -> Vec<u8> {
+        format!("8=FIX.4.2\x019=67\x0135=D\x0149=TX\x0156=TEST\x0134={}\x0111=ORDER{}\x0155=ABC\x0154=1\x0138=100\x0140=1\x0110=000\x01",msg_seq_num,msg_seq_num).into_bytes()
+    }
+
+    let directory = test_directory("fix_rs_test_file_message_store_compression");
+
+    //Messages written before compression is turned on stay readable alongside compressed ones.
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        for msg_seq_num in 1..51 {
+            message_store.store_outbound(msg_seq_num,&message(msg_seq_num)).unwrap();
+        }
+    }
+    let dictionary = {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        let dictionary = message_store.train_dictionary(4096).unwrap();
+        assert!(!dictionary.is_empty());
+
+        dictionary
+    };
+    {
+        let message_store = FileMessageStore::open(&directory).unwrap();
+        let mut message_store = message_store.compression(ZstdCompression::new().level(5).dictionary(dictionary.clone()));
+        for msg_seq_num in 51..71 {
+            message_store.store_outbound(msg_seq_num,&message(msg_seq_num)).unwrap();
+        }
+        message_store.store_outbound(70,b"replaced").unwrap();
+    }
+
+    let message_store = FileMessageStore::open(&directory).unwrap();
+    let mut message_store = message_store.compression(ZstdCompression::new().dictionary(dictionary));
+    let messages = message_store.outbound_messages(1..100).unwrap();
+    assert_eq!(messages.len(),70);
+    for &(msg_seq_num,ref bytes) in &messages[..69] {
+        assert_eq!(*bytes,message(msg_seq_num));
+    }
+    assert_eq!(messages[69],(70,b"replaced".to_vec()));
+    let _ = fs::remove_dir_all(&directory);
+
+    //Compression works without a dictionary too.
+    let directory = test_directory("fix_rs_test_file_message_store_compression_no_dictionary");
+    {
+        let message_store = FileMessageStore::open(&directory).unwrap();
+        let mut message_store = message_store.compression(ZstdCompression::new());
+        message_store.store_outbound(1,&message(1)).unwrap();
+    }
+    let message_store = FileMessageStore::open(&directory).unwrap();
+    let mut message_store = message_store.compression(ZstdCompression::new());
+    assert_eq!(message_store.outbound_messages(1..2).unwrap(),vec![(1,message(1))]);
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[cfg(feature = "store-encryption")]
+#[test]
+fn test_file_message_store_encryption() {
+    fn read_file(path: PathBuf) -> Vec<u8> {
+        use std::io::Read;
+
+        let mut contents = Vec::new();
+        fs::File::open(path).unwrap().read_to_end(&mut contents).unwrap();
+        contents
+    }
+
+    fn contains(haystack: &[u8],needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|window| window == needle)
+    }
+
+    let directory = test_directory("fix_rs_test_file_message_store_encryption");
+    let key = vec![7;32];
+
+    //Records written before encryption was turned on can only be read when migrating explicitly.
+    {
+        let mut message_store = FileMessageStore::open(&directory).unwrap();
+        message_store.store_outbound(1,b"plain message").unwrap();
+        message_store.store_order_ack(b"PLAIN").unwrap();
+    }
+    assert!(FileMessageStore::open_encrypted(&directory,StoreEncryption::new(StaticKeyProvider::new(key.clone()))).is_err());
+    {
+        let mut message_store = FileMessageStore::open_encrypted(&directory,StoreEncryption::new(StaticKeyProvider::new(key.clone())).allow_unencrypted_records()).unwrap();
+        message_store.store_outbound(2,b"secret message").unwrap();
+        message_store.store_order_ack(b"SECRET").unwrap();
+        message_store.store_order_sent(b"SECRET").unwrap();
+        message_store.set_next_inbound_msg_seq_num(5).unwrap();
+        message_store.set_next_outbound_msg_seq_num(3).unwrap();
+    }
+
+    //Nothing written while encrypted is readable from the files.
+    assert!(!contains(&read_file(directory.join("messages")),b"secret message"));
+    assert!(!contains(&read_file(directory.join("orderacks")),b"SECRET"));
+    assert!(!contains(&read_file(directory.join("orderssent")),b"SECRET"));
+    assert!(read_file(directory.join("seqnums")).starts_with(b"E\n"));
+
+    {
+        let mut message_store = FileMessageStore::open_encrypted(&directory,StoreEncryption::new(StaticKeyProvider::new(key.clone())).allow_unencrypted_records()).unwrap();
+        assert_eq!(message_store.next_inbound_msg_seq_num(),5);
+        assert_eq!(message_store.next_outbound_msg_seq_num(),3);
+        assert_eq!(message_store.outbound_messages(1..3).unwrap(),vec![(1,b"plain message".to_vec()),(2,b"secret message".to_vec())]);
+        assert!(message_store.is_order_acknowledged(b"PLAIN").unwrap());
+        assert!(message_store.is_order_acknowledged(b"SECRET").unwrap());
+        assert!(message_store.is_order_sent(b"SECRET").unwrap());
+    }
+
+    //A missing or wrong key is an error instead of garbage.
+    assert!(FileMessageStore::open(&directory).is_err());
+    assert!(FileMessageStore::open_encrypted(&directory,StoreEncryption::new(StaticKeyProvider::new(vec![8;32]))).is_err());
+
+    //Keys can come from a callback, such as one that asks a KMS, and be rotated as long as the old
+    //ones can still be looked up.
+    struct RotatedKeyProvider;
+    impl KeyProvider for RotatedKeyProvider {
+        fn current_key_id(&self) -> u32 {
+            1
+        }
+
+        fn key(&self,key_id: u32) -> Result<Vec<u8>,std::io::Error> {
+            Ok(vec![7 + key_id as u8;32])
+        }
+    }
+    {
+        let mut message_store = FileMessageStore::open_encrypted(&directory,StoreEncryption::new(RotatedKeyProvider).allow_unencrypted_records()).unwrap();
+        message_store.store_outbound(3,b"rotated message").unwrap();
+    }
+    {
+        let mut message_store = FileMessageStore::open_encrypted(&directory,StoreEncryption::from_fn(|key_id| {
+            match key_id {
+                0 => Ok(vec![7;32]),
+                1 => Ok(vec![8;32]),
+                _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound,"Unknown key")),
+            }
+        }).allow_unencrypted_records()).unwrap();
+        assert_eq!(message_store.outbound_messages(2..4).unwrap(),vec![(2,b"secret message".to_vec()),(3,b"rotated message".to_vec())]);
+    }
+
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[cfg(feature = "store-encryption")]
+#[test]
+fn test_file_message_store_encryption_rejects_unencrypted_records() {
+    fn append_file(path: PathBuf,bytes: &[u8]) {
+        use std::io::Write;
+
+        fs::OpenOptions::new().append(true).open(path).unwrap().write_all(bytes).unwrap();
+    }
+
+    fn new_encryption() -> StoreEncryption {
+        StoreEncryption::new(StaticKeyProvider::new(vec![7;32]))
+    }
+
+    //Unencrypted records added to any file of an encrypted store, such as a message slipped in to
+    //be sent with the next resend, are refused instead of trusted.
+    for &(file_name,record) in &[("messages",&b"2 5\nHELLO\n"[..]),("orderacks",&b"5\nFORGE\n"[..]),("orderssent",&b"5\nFORGE\n"[..])] {
+        let directory = test_directory("fix_rs_test_file_message_store_encryption_rejects_unencrypted_records");
+        {
+            let mut message_store = FileMessageStore::open_encrypted(&directory,new_encryption()).unwrap();
+            message_store.store_outbound(1,b"secret message").unwrap();
+            message_store.store_order_ack(b"SECRET").unwrap();
+            message_store.store_order_sent(b"SECRET").unwrap();
+            message_store.set_next_outbound_msg_seq_num(2).unwrap();
+        }
+        assert!(FileMessageStore::open_encrypted(&directory,new_encryption()).is_ok());
+
+        append_file(directory.join(file_name),record);
+        assert!(FileMessageStore::open_encrypted(&directory,new_encryption()).is_err());
+        assert!(FileMessageStore::open_encrypted(&directory,new_encryption().allow_unencrypted_records()).is_ok());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    //Same for a seqnums file replaced with an unencrypted one.
+    let directory = test_directory("fix_rs_test_file_message_store_encryption_rejects_unencrypted_records");
+    {
+        let mut message_store = FileMessageStore::open_encrypted(&directory,new_encryption()).unwrap();
+        message_store.set_next_outbound_msg_seq_num(2).unwrap();
+    }
+    fs::File::create(directory.join("seqnums")).unwrap();
+    append_file(directory.join("seqnums"),b"1 1\n");
+    assert!(FileMessageStore::open_encrypted(&directory,new_encryption()).is_err());
+
+    let _ = fs::remove_dir_all(&directory);
+}
@@ -0,0 +1,158 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{TestStream,new_order_single,serialize_message};
+use fix_rs::dictionary::fields::{ClOrdID,OrderQty,Price,Text};
+use fix_rs::dictionary::messages::{Logon,NewOrderSingle};
+use fix_rs::field::Field;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::EngineEvent;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::message_template::{MessageTemplate,MessageTemplateError};
+use fix_rs::message_version::MessageVersion;
+
+fn assert_body_length_and_checksum(bytes: &[u8]) {
+    let body_length_begin = bytes.iter().position(|byte| *byte == b'\x01').unwrap() + 3; //9=
+    let body_begin = body_length_begin + bytes[body_length_begin..].iter().position(|byte| *byte == b'\x01').unwrap() + 1;
+    let checksum_begin = bytes.len() - 7; //10=XXX<SOH>
+
+    let body_length = String::from_utf8(bytes[body_length_begin..body_begin - 1].to_vec()).unwrap();
+    assert_eq!(body_length,(checksum_begin - body_begin).to_string());
+
+    let mut checksum: u8 = 0;
+    for byte in &bytes[..checksum_begin] {
+        checksum = checksum.wrapping_add(*byte);
+    }
+    assert_eq!(&bytes[checksum_begin..],format!("10={:03}\x01",checksum).as_bytes());
+}
+
+#[test]
+fn test_template_matches_serialized_message() {
+    let mut message = new_order_single(b"1");
+    let mut template = MessageTemplate::new(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&[ClOrdID::tag(),Price::tag(),OrderQty::tag()]).unwrap();
+    assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+
+    //Patch fields with values that are longer, shorter, and the same length. The result should
+    //always be identical to serializing the message from scratch.
+    template.patch(ClOrdID::tag(),b"123456").unwrap();
+    message.cl_ord_id = b"123456".to_vec();
+    assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+
+    template.patch_field::<Price>(&b"10".to_vec()).unwrap();
+    message.price = b"10".to_vec();
+    assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+
+    template.patch(OrderQty::tag(),b"200").unwrap();
+    message.order_qty = b"200".to_vec();
+    assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+
+    //Header fields managed by the Engine can always be patched.
+    template.setup_fixt_session_header(Some(1000),b"SENDER".to_vec(),b"TARGET".to_vec());
+    message.msg_seq_num = 1000;
+    message.sender_comp_id = b"SENDER".to_vec();
+    message.target_comp_id = b"TARGET".to_vec();
+    message.sending_time = template.sending_time();
+    assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+}
+
+#[test]
+fn test_template_errors() {
+    let message = new_order_single(b"1");
+
+    //Tags that were not included in the message can't be patched.
+    let result = MessageTemplate::new(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&[Text::tag()]);
+    assert!(if let Err(MessageTemplateError::MissingTag(tag)) = result { tag == Text::tag() } else { false });
+
+    //Only tags marked as variable can be patched.
+    let mut template = MessageTemplate::new(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&[ClOrdID::tag()]).unwrap();
+    let result = template.patch(Price::tag(),b"10");
+    assert!(if let Err(MessageTemplateError::NotVariableTag(tag)) = result { tag == Price::tag() } else { false });
+}
+
+#[test]
+fn test_send_template() {
+    define_dictionary!(
+        Logon,
+        NewOrderSingle,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Send the same template twice with a different ClOrdID each time.
+    let mut template = MessageTemplate::new(&new_order_single(b"1"),FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&[ClOrdID::tag()]).unwrap();
+    for cl_ord_id in &[b"A".to_vec(),b"ABCDEFGHIJ".to_vec()] {
+        template.patch(ClOrdID::tag(),&cl_ord_id[..]).unwrap();
+        client.send_message(connection,template.clone());
+    }
+
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,2);
+    assert_eq!(message.cl_ord_id,b"A");
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.msg_seq_num,3);
+    assert_eq!(message.cl_ord_id,b"ABCDEFGHIJ");
+}
+
+#[test]
+fn test_template_digit_width_changes() {
+    let mut message = new_order_single(b"1");
+    let mut template = MessageTemplate::new(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&[ClOrdID::tag()]).unwrap();
+
+    //Grow ClOrdID one byte at a time so BodyLength crosses from three to four digits and then
+    //shrink it back again. BodyLength and CheckSum are only adjusted by the bytes that changed so
+    //every step must still match serializing the message from scratch.
+    let mut cl_ord_id = Vec::new();
+    for _ in 0..1000 {
+        cl_ord_id.push(b'A');
+        template.patch(ClOrdID::tag(),&cl_ord_id[..]).unwrap();
+        message.cl_ord_id = cl_ord_id.clone();
+        assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+    }
+    assert!(template.bytes().len() > 1000);
+    while !cl_ord_id.is_empty() {
+        cl_ord_id.pop();
+        if cl_ord_id.is_empty() {
+            break;
+        }
+        template.patch(ClOrdID::tag(),&cl_ord_id[..]).unwrap();
+        message.cl_ord_id = cl_ord_id.clone();
+        assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+    }
+
+    //MsgSeqNum changes width as it crosses 9->10, 99->100, etc.
+    for msg_seq_num in &[1,9,10,99,100,999,1000,99999,100000,9,1] {
+        template.setup_fixt_session_header(Some(*msg_seq_num),message.sender_comp_id.clone(),message.target_comp_id.clone());
+        message.msg_seq_num = *msg_seq_num;
+        message.sending_time = template.sending_time();
+        assert_eq!(template.bytes(),&serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2)[..]);
+    }
+
+    //Inserting PossDupFlag and OrigSendingTime changes BodyLength after the fact too. The fields
+    //aren't inserted in the same order as a fresh serialization so count everything by hand.
+    template.set_orig_sending_time(message.sending_time);
+    template.set_is_poss_dup(true);
+    assert_body_length_and_checksum(template.bytes());
+}
@@ -0,0 +1,132 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::io::Write;
+
+#[macro_use]
+mod common;
+use common::new_logon_message;
+use fix_rs::constant::{TAG_END,VALUE_END};
+use fix_rs::dictionary::field_types::other::MsgDirection;
+use fix_rs::dictionary::fields::MsgTypeGrp;
+use fix_rs::field_tag::FieldTag;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::message::{Message,MessageVisitor};
+use fix_rs::message_version::MessageVersion;
+
+//Rebuilds the serialized body from the visited fields while keeping track of the group nesting.
+struct RecordingVisitor {
+    body: Vec<u8>,
+    events: Vec<String>,
+}
+
+impl RecordingVisitor {
+    fn new() -> RecordingVisitor {
+        RecordingVisitor {
+            body: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    fn write_field(&mut self,tag: FieldTag,value: &[u8]) {
+        self.body.write(&tag.to_bytes()[..]).unwrap();
+        self.body.push(TAG_END);
+        self.body.write(value).unwrap();
+        self.body.push(VALUE_END);
+    }
+}
+
+impl MessageVisitor for RecordingVisitor {
+    fn visit_field(&mut self,tag: FieldTag,name: &'static str,value: &[u8]) {
+        self.write_field(tag,value);
+        self.events.push(format!("{}({})={}",name,tag,String::from_utf8_lossy(value)));
+    }
+
+    fn begin_group(&mut self,tag: FieldTag,name: &'static str,count: usize) {
+        self.write_field(tag,count.to_string().as_bytes());
+        self.events.push(format!("begin {}({}) x{}",name,tag,count));
+    }
+
+    fn begin_group_entry(&mut self,index: usize) {
+        self.events.push(format!("begin entry {}",index));
+    }
+
+    fn end_group_entry(&mut self,index: usize) {
+        self.events.push(format!("end entry {}",index));
+    }
+
+    fn end_group(&mut self,tag: FieldTag,name: &'static str) {
+        self.events.push(format!("end {}({})",name,tag));
+    }
+}
+
+fn new_msg_type_grp(ref_msg_type: &[u8],msg_direction: MsgDirection) -> Box<MsgTypeGrp> {
+    let mut group = MsgTypeGrp::new();
+    group.ref_msg_type = ref_msg_type.to_vec();
+    group.msg_direction = msg_direction;
+
+    Box::new(group)
+}
+
+#[test]
+fn test_visit_matches_serialized_body() {
+    let mut message = new_logon_message();
+    message.username = b"user".to_vec();
+    message.no_msg_types.push(new_msg_type_grp(b"D",MsgDirection::Send));
+    message.no_msg_types.push(new_msg_type_grp(b"8",MsgDirection::Receive));
+
+    for message_version in MessageVersion::all() {
+        let mut body = Vec::new();
+        message.read_body(FIXVersion::FIXT_1_1,message_version,&mut body);
+
+        let mut visitor = RecordingVisitor::new();
+        message.visit(FIXVersion::FIXT_1_1,message_version,&mut visitor);
+        assert_eq!(visitor.body,body);
+    }
+}
+
+#[test]
+fn test_visit_repeating_group_nesting() {
+    let mut message = new_logon_message();
+    message.no_msg_types.push(new_msg_type_grp(b"D",MsgDirection::Send));
+    message.no_msg_types.push(new_msg_type_grp(b"8",MsgDirection::Receive));
+
+    let mut visitor = RecordingVisitor::new();
+    message.visit(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut visitor);
+
+    let group_begin = visitor.events.iter().position(|event| event.starts_with("begin no_msg_types(384)")).unwrap();
+    assert_eq!(visitor.events[group_begin..group_begin + 9].to_vec(),vec![
+        String::from("begin no_msg_types(384) x2"),
+        String::from("begin entry 0"),
+        String::from("ref_msg_type(372)=D"),
+        String::from("msg_direction(385)=S"),
+        String::from("end entry 0"),
+        String::from("begin entry 1"),
+        String::from("ref_msg_type(372)=8"),
+        String::from("msg_direction(385)=R"),
+        String::from("end entry 1"),
+    ]);
+    assert_eq!(visitor.events[group_begin + 9],"end no_msg_types(384)");
+
+    //Empty optional fields are never visited, just like they are never serialized.
+    assert!(visitor.events.iter().all(|event| !event.starts_with("username(")));
+    assert!(visitor.events.iter().any(|event| event == "heart_bt_int(108)=5"));
+}
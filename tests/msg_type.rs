@@ -0,0 +1,59 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use std::collections::HashSet;
+
+use fix_rs::dictionary::{administrative_msg_types,standard_msg_types};
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,Logon,MarketDataRequest,NewOrderSingle,PartyRiskLimitsRequest,SecurityList};
+use fix_rs::dictionary::msg_type::MsgType;
+
+#[test]
+fn test_bytes_round_trip() {
+    //Every MsgType has its own bytes and can be found from them.
+    let mut seen_bytes = HashSet::new();
+    for msg_type in MsgType::all() {
+        assert!(seen_bytes.insert(msg_type.as_bytes()));
+        assert_eq!(MsgType::from_bytes(msg_type.as_bytes()),Some(*msg_type));
+    }
+    assert_eq!(MsgType::all().len(),118);
+
+    assert_eq!(MsgType::from_bytes(b"CE"),Some(MsgType::StreamAssignmentReportAck));
+    assert_eq!(MsgType::from_bytes(b"U1"),None);
+    assert_eq!(MsgType::NewOrderSingle.to_string(),"D");
+}
+
+#[test]
+fn test_matches_messages() {
+    assert_eq!(MsgType::Heartbeat.as_bytes(),Heartbeat::msg_type());
+    assert_eq!(MsgType::Logon.as_bytes(),Logon::msg_type());
+    assert_eq!(MsgType::ExecutionReport.as_bytes(),ExecutionReport::msg_type());
+    assert_eq!(MsgType::MarketDataRequest.as_bytes(),MarketDataRequest::msg_type());
+    assert_eq!(MsgType::NewOrderSingle.as_bytes(),NewOrderSingle::msg_type());
+    assert_eq!(MsgType::SecurityList.as_bytes(),SecurityList::msg_type());
+
+    //Newer MsgTypes aren't part of the standard list.
+    assert_eq!(MsgType::from_bytes(PartyRiskLimitsRequest::msg_type()),None);
+}
+
+#[test]
+fn test_msg_type_lists() {
+    let standard_msg_types = standard_msg_types();
+    assert_eq!(standard_msg_types.len(),MsgType::all().len());
+    assert!(standard_msg_types.contains(&b"CD"[..]));
+    assert!(standard_msg_types.contains(&b"CE"[..]));
+
+    for msg_type in administrative_msg_types() {
+        assert!(MsgType::from_bytes(msg_type).unwrap().is_administrative());
+    }
+    assert_eq!(MsgType::all().iter().filter(|msg_type| msg_type.is_administrative()).count(),administrative_msg_types().len());
+}
@@ -0,0 +1,138 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::{round_trip,serialize_message};
+use fix_rs::dictionary::field_types::other::{ExecType,MDEntryType,OrdStatus,OrdType,Side};
+use fix_rs::dictionary::fields::{InstrmtLegExecGrp,InstrumentLeg,MDFullGrp};
+use fix_rs::dictionary::messages::{ExecutionReport,MarketDataSnapshotFullRefresh,NewOrderMultileg};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::multileg::{MultilegOrderBuilder,butterfly,new_leg,spread};
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_spread_order() {
+    //Selling the spread sells the buy leg and buys the sell leg.
+    let mut message = spread(b"1",b"ESH8-ESM8",Side::Sell,b"10",b"ESH8",b"ESM8")
+        .limit_price(b"-2.5")
+        .build();
+    assert_eq!(message.ord_type,OrdType::Limit);
+    assert_eq!(message.price,b"-2.5");
+    assert_eq!(message.no_legs.len(),2);
+    assert_eq!(message.no_legs[0].leg_symbol,b"ESH8");
+    assert_eq!(message.no_legs[0].leg_side,Some(Side::Sell));
+    assert_eq!(message.no_legs[1].leg_symbol,b"ESM8");
+    assert_eq!(message.no_legs[1].leg_side,Some(Side::Buy));
+
+    message.setup_fixt_session_header(Some(1),b"CLIENT".to_vec(),b"SERVER".to_vec());
+    let bytes = serialize_message(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    let text = String::from_utf8_lossy(&bytes[..]).into_owned();
+    assert!(text.contains("\x01555=2\x01600=ESH8\x01623=1\x01624=2\x01600=ESM8\x01623=1\x01624=1\x01"));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.symbol,b"ESH8-ESM8");
+    assert_eq!(parsed_message.no_legs.len(),2);
+    assert_eq!(parsed_message.no_legs[0].leg_symbol,b"ESH8");
+    assert_eq!(parsed_message.no_legs[0].leg_side,Some(Side::Sell));
+
+    //Legs don't exist before FIX 4.4.
+    let bytes = serialize_message(&message,FIXVersion::FIX_4_3,MessageVersion::FIX43);
+    let text = String::from_utf8_lossy(&bytes[..]).into_owned();
+    assert!(!text.contains("\x01555="));
+}
+
+#[test]
+fn test_strategy_order() {
+    let message = butterfly(b"1",b"FLY",Side::Buy,b"5",b"C100",b"C105",b"C110").build();
+    let legs: Vec<(&[u8],Option<Side>,&[u8])> = message.no_legs.iter()
+        .map(|leg| (&leg.leg_symbol[..],leg.leg_side.clone(),&leg.leg_ratio_qty[..]))
+        .collect();
+    assert_eq!(legs,vec![
+        (&b"C100"[..],Some(Side::Buy),&b"1"[..]),
+        (&b"C105"[..],Some(Side::Sell),&b"2"[..]),
+        (&b"C110"[..],Some(Side::Buy),&b"1"[..]),
+    ]);
+    assert_eq!(message.ord_type,OrdType::Market);
+
+    //Legs with more detail can be added directly.
+    let mut leg = new_leg(b"OPT",Side::Buy,b"1");
+    leg.leg_strike_price = b"100".to_vec();
+    leg.leg_qty = b"5".to_vec();
+    let message = MultilegOrderBuilder::new(b"2",b"CUSTOM",Side::Sell,b"5")
+        .account(b"ACCOUNT")
+        .add_leg(leg)
+        .build();
+    assert_eq!(message.account,b"ACCOUNT");
+    assert_eq!(message.no_legs[0].leg_side,Some(Side::Sell));
+    assert_eq!(message.no_legs[0].leg_strike_price,b"100");
+}
+
+#[test]
+fn test_execution_report_legs() {
+    let mut message = new_fixt_message!(ExecutionReport);
+    message.order_id = b"VENUE1".to_vec();
+    message.exec_id = b"E1".to_vec();
+    message.exec_type = ExecType::Trade;
+    message.ord_status = OrdStatus::PartiallyFilled;
+    message.symbol = b"ESH8-ESM8".to_vec();
+    message.side = Side::Buy;
+    message.cum_qty = b"1".to_vec();
+    message.leaves_qty = b"9".to_vec();
+    message.multi_leg_reporting_type = b'3';
+    for (leg_symbol,leg_side,leg_last_px) in vec![(&b"ESH8"[..],Side::Buy,&b"2700.25"[..]),(&b"ESM8"[..],Side::Sell,&b"2702.75"[..])] {
+        let mut leg = InstrmtLegExecGrp::new();
+        leg.leg_symbol = leg_symbol.to_vec();
+        leg.leg_side = Some(leg_side);
+        leg.leg_last_px = leg_last_px.to_vec();
+        message.no_legs.push(Box::new(leg));
+    }
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.multi_leg_reporting_type,b'3');
+    assert_eq!(parsed_message.no_legs.len(),2);
+    assert_eq!(parsed_message.no_legs[1].leg_symbol,b"ESM8");
+    assert_eq!(parsed_message.no_legs[1].leg_side,Some(Side::Sell));
+    assert_eq!(parsed_message.no_legs[1].leg_last_px,b"2702.75");
+}
+
+#[test]
+fn test_market_data_legs() {
+    let mut message = new_fixt_message!(MarketDataSnapshotFullRefresh);
+    message.md_req_id = b"1".to_vec();
+    message.symbol = b"ESH8-ESM8".to_vec();
+    for leg_symbol in &[&b"ESH8"[..],&b"ESM8"[..]] {
+        let mut leg = InstrumentLeg::new();
+        leg.leg_symbol = leg_symbol.to_vec();
+        message.no_legs.push(Box::new(leg));
+    }
+    let mut md_entry = MDFullGrp::new();
+    md_entry.md_entry_type = MDEntryType::Bid;
+    md_entry.md_entry_px = b"-2.5".to_vec();
+    message.no_md_entries.push(Box::new(md_entry));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.no_legs.len(),2);
+    assert_eq!(parsed_message.no_legs[0].leg_symbol,b"ESH8");
+    assert_eq!(parsed_message.no_md_entries[0].md_entry_px,b"-2.5");
+}
@@ -0,0 +1,167 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::collections::HashMap;
+
+#[macro_use]
+mod common;
+use common::MAX_MESSAGE_SIZE;
+use fix_rs::byte_buffer::ByteBuffer;
+use fix_rs::dictionary::field_types::other::{BidType,BusinessRejectReason,ExecType,ListExecInstType,ListOrderStatus,ListStatusType,OrdStatus,OrdType,Side};
+use fix_rs::dictionary::fields::OrdListStatGrp;
+use fix_rs::dictionary::messages::{BusinessMessageReject,ExecutionReport,ListStatus,NewOrderList};
+use fix_rs::fix::Parser;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
+use fix_rs::fixt::order_list::{OrderListTracker,PendingListRequest,list_cancel_request,list_execute,new_list_order,new_order_list};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn build_new_order_list() -> NewOrderList {
+    let mut message = new_order_list(b"LIST1",BidType::NoBiddingProcess,Some(ListExecInstType::WaitForExecuteInstruction),vec![
+        new_list_order(b"1",b"AAA",Side::Buy,b"100",OrdType::Market),
+        new_list_order(b"2",b"BBB",Side::Sell,b"200",OrdType::Market),
+    ]);
+    message.setup_fixt_session_header(Some(1),b"CLIENT".to_vec(),b"SERVER".to_vec());
+
+    message
+}
+
+fn list_status(list_order_status: ListOrderStatus,orders: Vec<(&str,OrdStatus,&str,&str)>) -> ListStatus {
+    let mut message = new_fixt_message!(ListStatus);
+    message.list_id = b"LIST1".to_vec();
+    message.list_status_type = ListStatusType::Response;
+    message.no_rpts = 1;
+    message.list_order_status = list_order_status;
+    message.rpt_seq = 1;
+    message.tot_no_orders = orders.len() as i64;
+    for (cl_ord_id,ord_status,cum_qty,leaves_qty) in orders {
+        let mut order = OrdListStatGrp::new();
+        order.cl_ord_id = cl_ord_id.as_bytes().to_vec();
+        order.ord_status = ord_status;
+        order.cum_qty = cum_qty.as_bytes().to_vec();
+        order.leaves_qty = leaves_qty.as_bytes().to_vec();
+        order.cxl_qty = b"0".to_vec();
+        order.avg_px = b"0".to_vec();
+        message.no_orders.push(Box::new(order));
+    }
+
+    message
+}
+
+#[test]
+fn test_new_order_list_serialization() {
+    let message = build_new_order_list();
+    assert_eq!(message.tot_no_orders,2);
+    assert_eq!(message.no_orders[0].list_seq_no,1);
+    assert_eq!(message.no_orders[1].list_seq_no,2);
+
+    let mut bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIX_4_4,MessageVersion::FIX44,&mut bytes);
+    let text = String::from_utf8_lossy(bytes.bytes()).into_owned();
+    assert!(text.contains("\x0166=LIST1\x01394=3\x01433=2\x0168=2\x0173=2\x0111=1\x0167=1\x01"));
+
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(NewOrderList::msg_type(),NewOrderList::new().builder());
+    let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+    let (_,result) = parser.parse(bytes.bytes());
+    assert!(result.is_ok());
+    let parsed_message = parser.messages[0].as_any().downcast_ref::<NewOrderList>().unwrap();
+    assert_eq!(parsed_message.list_id,b"LIST1");
+    assert_eq!(parsed_message.no_orders.len(),2);
+    assert_eq!(parsed_message.no_orders[1].cl_ord_id,b"2");
+    assert_eq!(parsed_message.no_orders[1].symbol,b"BBB");
+    assert_eq!(parsed_message.no_orders[1].side,Side::Sell);
+}
+
+#[test]
+fn test_list_execution() {
+    let mut tracker = OrderListTracker::new();
+    tracker.on_message_sent(&build_new_order_list());
+    {
+        let list = tracker.get(b"LIST1").unwrap();
+        assert!(list.is_fully_submitted());
+        assert_eq!(list.list_order_status,None);
+        assert_eq!(list.orders.len(),2);
+    }
+    assert!(tracker.get_by_cl_ord_id(b"2").is_some());
+
+    //Counterparty accepts the list and waits for ListExecute.
+    {
+        let message = list_status(ListOrderStatus::ReceivedForExecution,vec![("1",OrdStatus::New,"0","100"),("2",OrdStatus::New,"0","200")]);
+        let list = tracker.on_message(&message).unwrap();
+        assert_eq!(list.list_order_status,Some(ListOrderStatus::ReceivedForExecution));
+        assert!(!list.is_executing());
+    }
+
+    //ListExecute stays pending until the list is reported as executing.
+    tracker.on_message_sent(&list_execute(b"LIST1"));
+    assert_eq!(tracker.get(b"LIST1").unwrap().pending_request,Some(PendingListRequest::Execute));
+    {
+        let message = list_status(ListOrderStatus::Executing,vec![("1",OrdStatus::New,"0","100"),("2",OrdStatus::New,"0","200")]);
+        let list = tracker.on_message(&message).unwrap();
+        assert!(list.is_executing());
+        assert_eq!(list.pending_request,None);
+    }
+
+    //Orders in the list are updated by ExecutionReports.
+    {
+        let mut message = new_fixt_message!(ExecutionReport);
+        message.order_id = b"VENUE1".to_vec();
+        message.cl_ord_id = b"1".to_vec();
+        message.exec_id = b"E1".to_vec();
+        message.exec_type = ExecType::Trade;
+        message.ord_status = OrdStatus::Filled;
+        message.symbol = b"AAA".to_vec();
+        message.side = Side::Buy;
+        message.cum_qty = b"100".to_vec();
+        message.leaves_qty = b"0".to_vec();
+        let list = tracker.on_message(&message).unwrap();
+        assert!(list.get(b"1").unwrap().is_done());
+        assert!(!list.all_orders_done());
+    }
+
+    //A rejected ListCancelRequest clears the pending request.
+    tracker.on_message_sent(&list_cancel_request(b"LIST1"));
+    assert_eq!(tracker.get(b"LIST1").unwrap().pending_request,Some(PendingListRequest::Cancel));
+    {
+        let mut message = new_fixt_message!(BusinessMessageReject);
+        message.ref_msg_type = b"K".to_vec();
+        message.business_reject_ref_id = b"LIST1".to_vec();
+        message.business_reject_reason = BusinessRejectReason::Other;
+        let list = tracker.on_message(&message).unwrap();
+        assert_eq!(list.pending_request,None);
+        assert!(list.rejection.is_some());
+    }
+
+    //ListStatus finishes the list.
+    {
+        let message = list_status(ListOrderStatus::AllDone,vec![("1",OrdStatus::Filled,"100","0"),("2",OrdStatus::Filled,"200","0")]);
+        let list = tracker.on_message(&message).unwrap();
+        assert!(list.is_done());
+        assert!(list.all_orders_done());
+        assert_eq!(list.get(b"2").unwrap().cum_qty,b"200");
+    }
+
+    assert!(tracker.remove(b"LIST1").is_some());
+    assert!(tracker.get_by_cl_ord_id(b"1").is_none());
+}
@@ -0,0 +1,376 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{TestStream,new_order_single};
+use fix_rs::dictionary::field_types::other::{BusinessRejectReason,CxlRejReason,CxlRejResponseTo,ExecType,OrdStatus,OrdType,SessionRejectReason,Side};
+use fix_rs::dictionary::messages::{BusinessMessageReject,ExecutionReport,Heartbeat,Logon,NewOrderSingle,OrderCancelReject,OrderCancelReplaceRequest,OrderCancelRequest,Reject};
+use fix_rs::fixt::engine::EngineEvent;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::order_tracker::{OrderTracker,PendingRequest};
+use fix_rs::fixt::positions::Positions;
+use fix_rs::fixt::reject_reason::{RejectReason,Rejection};
+
+fn order_cancel_replace_request(orig_cl_ord_id: &[u8],cl_ord_id: &[u8],order_qty: &[u8],price: &[u8]) -> OrderCancelReplaceRequest {
+    let mut message = new_fixt_message!(FROM_CLIENT OrderCancelReplaceRequest);
+    message.orig_cl_ord_id = orig_cl_ord_id.to_vec();
+    message.cl_ord_id = cl_ord_id.to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.transact_time = message.sending_time;
+    message.order_qty = order_qty.to_vec();
+    message.ord_type = OrdType::Limit;
+    message.price = price.to_vec();
+
+    message
+}
+
+fn execution_report(cl_ord_id: &[u8],orig_cl_ord_id: &[u8],exec_id: &[u8],exec_type: ExecType,ord_status: OrdStatus,cum_qty: &[u8],leaves_qty: &[u8]) -> ExecutionReport {
+    let mut message = new_fixt_message!(ExecutionReport);
+    message.order_id = b"VENUE1".to_vec();
+    message.cl_ord_id = cl_ord_id.to_vec();
+    message.orig_cl_ord_id = orig_cl_ord_id.to_vec();
+    message.exec_id = exec_id.to_vec();
+    message.exec_type = exec_type;
+    message.ord_status = ord_status;
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.cum_qty = cum_qty.to_vec();
+    message.leaves_qty = leaves_qty.to_vec();
+
+    message
+}
+
+#[test]
+fn test_cancel_replace_chain() {
+    let mut tracker = OrderTracker::new();
+
+    tracker.on_message_sent(&new_order_single(b"A"));
+    assert!(tracker.get(b"A").unwrap().ord_status.is_none());
+    tracker.on_message(&execution_report(b"A",b"",b"E1",ExecType::New,OrdStatus::New,b"0",b"100")).unwrap();
+    assert_eq!(tracker.get(b"A").unwrap().ord_status,Some(OrdStatus::New));
+    assert_eq!(tracker.get_by_order_id(b"VENUE1").unwrap().cl_ord_id(),b"A");
+
+    //Replace A with B. Both ClOrdIDs lead to the same order while the replace is pending.
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"B",b"200",b"11"));
+    {
+        let order = tracker.get(b"B").unwrap();
+        assert_eq!(order.cl_ord_id(),b"A");
+        assert!(order.is_pending_replace());
+        assert_eq!(order.pending_request,Some(PendingRequest::Replace(b"B".to_vec(),b"200".to_vec(),b"11".to_vec())));
+    }
+    tracker.on_message(&execution_report(b"B",b"A",b"E2",ExecType::PendingReplace,OrdStatus::PendingReplace,b"0",b"100")).unwrap();
+    assert!(tracker.get(b"A").unwrap().is_pending_replace());
+
+    //Venue fills part of the order using the old ClOrdID before confirming the replace.
+    {
+        let order = tracker.on_message(&execution_report(b"A",b"",b"E3",ExecType::Trade,OrdStatus::PartiallyFilled,b"40",b"60")).unwrap();
+        assert_eq!(order.cum_qty,b"40");
+        assert_eq!(order.leaves_qty,b"60");
+        assert_eq!(order.cl_ord_id(),b"A");
+        assert!(order.is_pending_replace());
+    }
+
+    //Replace is confirmed and B becomes the live ClOrdID.
+    {
+        let order = tracker.on_message(&execution_report(b"B",b"A",b"E4",ExecType::Replaced,OrdStatus::PartiallyFilled,b"40",b"160")).unwrap();
+        assert_eq!(order.cl_ord_ids,vec![b"A".to_vec(),b"B".to_vec()]);
+        assert_eq!(order.orig_cl_ord_id(),b"A");
+        assert_eq!(order.cl_ord_id(),b"B");
+        assert_eq!(order.order_qty,b"200");
+        assert_eq!(order.price,b"11");
+        assert_eq!(order.leaves_qty,b"160");
+        assert!(order.pending_request.is_none());
+    }
+    assert_eq!(tracker.iter().count(),1);
+
+    //A second replace is rejected and the order stays as is.
+    tracker.on_message_sent(&order_cancel_replace_request(b"B",b"C",b"300",b"12"));
+    let mut reject = new_fixt_message!(OrderCancelReject);
+    reject.order_id = b"VENUE1".to_vec();
+    reject.cl_ord_id = b"C".to_vec();
+    reject.orig_cl_ord_id = b"B".to_vec();
+    reject.ord_status = OrdStatus::PartiallyFilled;
+    reject.cxl_rej_response_to = CxlRejResponseTo::OrderCancelReplaceRequest;
+    reject.cxl_rej_reason = Some(CxlRejReason::TooLateToCancel);
+    {
+        let order = tracker.on_message(&reject).unwrap();
+        assert_eq!(order.cl_ord_id(),b"B");
+        assert_eq!(order.order_qty,b"200");
+        assert!(order.pending_request.is_none());
+    }
+
+    //Cancel B.
+    let mut cancel = new_fixt_message!(FROM_CLIENT OrderCancelRequest);
+    cancel.orig_cl_ord_id = b"B".to_vec();
+    cancel.cl_ord_id = b"D".to_vec();
+    cancel.symbol = b"TEST".to_vec();
+    cancel.side = Side::Buy;
+    cancel.transact_time = cancel.sending_time;
+    tracker.on_message_sent(&cancel);
+    assert!(tracker.get(b"B").unwrap().is_pending_cancel());
+    {
+        let order = tracker.on_message(&execution_report(b"D",b"B",b"E5",ExecType::Canceled,OrdStatus::Canceled,b"40",b"0")).unwrap();
+        assert_eq!(order.cl_ord_id(),b"D");
+        assert!(order.is_done());
+        assert!(order.pending_request.is_none());
+    }
+
+    //Unrelated messages and unknown orders are ignored.
+    assert!(tracker.on_message(&Heartbeat::new()).is_none());
+    let mut report = execution_report(b"X",b"",b"E6",ExecType::New,OrdStatus::New,b"0",b"100");
+    report.order_id = b"VENUE2".to_vec();
+    assert!(tracker.on_message(&report).is_none());
+
+    //Removing the order forgets every ClOrdID in the chain.
+    assert!(tracker.remove(b"B").is_some());
+    assert!(tracker.get(b"A").is_none());
+    assert!(tracker.get(b"C").is_none());
+    assert!(tracker.get(b"D").is_none());
+    assert!(tracker.get_by_order_id(b"VENUE1").is_none());
+}
+
+#[test]
+fn test_rejected_requests_are_forgotten() {
+    let mut tracker = OrderTracker::new();
+    tracker.on_message_sent(&new_order_single(b"A"));
+
+    //A rejected replace no longer leads to the order.
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"B",b"200",b"11"));
+    let mut reject = new_fixt_message!(OrderCancelReject);
+    reject.order_id = b"VENUE1".to_vec();
+    reject.cl_ord_id = b"B".to_vec();
+    reject.orig_cl_ord_id = b"A".to_vec();
+    reject.ord_status = OrdStatus::New;
+    reject.cxl_rej_response_to = CxlRejResponseTo::OrderCancelReplaceRequest;
+    reject.cxl_rej_reason = Some(CxlRejReason::Other);
+    assert!(tracker.on_message(&reject).unwrap().pending_request.is_none());
+    assert!(tracker.get(b"B").is_none());
+    assert_eq!(tracker.get(b"A").unwrap().cl_ord_id(),b"A");
+
+    //Same for a replace rejected with a BusinessMessageReject.
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"C",b"200",b"11"));
+    let mut reject = new_fixt_message!(BusinessMessageReject);
+    reject.ref_msg_type = b"G".to_vec();
+    reject.business_reject_ref_id = b"C".to_vec();
+    reject.business_reject_reason = BusinessRejectReason::Other;
+    assert!(tracker.on_message(&reject).unwrap().pending_request.is_none());
+    assert!(tracker.get(b"C").is_none());
+
+    //And for a request that is superseded by another before being answered.
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"D",b"200",b"11"));
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"E",b"300",b"12"));
+    assert!(tracker.get(b"D").is_none());
+    assert!(tracker.get(b"E").unwrap().is_pending_replace());
+
+    //Removing the order afterwards leaves nothing behind.
+    assert!(tracker.remove(b"A").is_some());
+    assert!(tracker.get(b"A").is_none());
+    assert!(tracker.get(b"E").is_none());
+    assert_eq!(tracker.iter().count(),0);
+}
+
+#[test]
+fn test_fill_completes_order_with_pending_replace() {
+    let mut tracker = OrderTracker::new();
+    tracker.on_message_sent(&new_order_single(b"A"));
+    tracker.on_message_sent(&order_cancel_replace_request(b"A",b"B",b"200",b"11"));
+
+    //Order is completely filled on the old ClOrdID so there is nothing left to replace.
+    let order = tracker.on_message(&execution_report(b"A",b"",b"E1",ExecType::Trade,OrdStatus::Filled,b"100",b"0")).unwrap();
+    assert!(order.is_done());
+    assert!(order.pending_request.is_none());
+    assert_eq!(order.cl_ord_id(),b"A");
+    assert_eq!(order.order_qty,b"100");
+}
+
+#[test]
+fn test_exec_id_dedup() {
+    let mut tracker = OrderTracker::new();
+    tracker.on_message_sent(&new_order_single(b"A"));
+
+    //Duplicates are passed through until deduplication is enabled.
+    let report = execution_report(b"A",b"",b"E1",ExecType::Trade,OrdStatus::PartiallyFilled,b"10",b"90");
+    assert!(tracker.on_message(&report).is_some());
+    assert!(tracker.on_message(&report).is_some());
+    assert!(!tracker.has_seen_exec_id(b"E1"));
+
+    tracker.set_exec_id_dedup_window(2);
+    assert!(tracker.on_message(&report).is_some());
+    assert!(tracker.has_seen_exec_id(b"E1"));
+    assert!(tracker.on_message(&report).is_none());
+
+    //Only the most recent ExecIDs are remembered.
+    tracker.on_message(&execution_report(b"A",b"",b"E2",ExecType::Trade,OrdStatus::PartiallyFilled,b"20",b"80")).unwrap();
+    tracker.on_message(&execution_report(b"A",b"",b"E3",ExecType::Trade,OrdStatus::PartiallyFilled,b"30",b"70")).unwrap();
+    assert!(!tracker.has_seen_exec_id(b"E1"));
+    assert!(tracker.has_seen_exec_id(b"E2"));
+    assert!(tracker.has_seen_exec_id(b"E3"));
+    assert!(tracker.on_message(&execution_report(b"A",b"",b"E3",ExecType::Trade,OrdStatus::PartiallyFilled,b"40",b"60")).is_none());
+    assert_eq!(tracker.get(b"A").unwrap().cum_qty,b"30");
+
+    //Shrinking the window forgets the oldest ExecIDs and disabling it forgets them all.
+    tracker.set_exec_id_dedup_window(1);
+    assert!(!tracker.has_seen_exec_id(b"E2"));
+    assert!(tracker.has_seen_exec_id(b"E3"));
+    tracker.set_exec_id_dedup_window(None);
+    assert!(!tracker.has_seen_exec_id(b"E3"));
+}
+
+#[test]
+fn test_positions() {
+    let mut tracker = OrderTracker::new();
+    tracker.set_exec_id_dedup_window(100);
+    let mut positions = Positions::new();
+    tracker.on_message_sent(&new_order_single(b"A"));
+    let mut sell = new_order_single(b"B");
+    sell.side = Side::Sell;
+    tracker.on_message_sent(&sell);
+
+    let mut reports = Vec::new();
+    let mut report = execution_report(b"A",b"",b"E1",ExecType::Trade,OrdStatus::PartiallyFilled,b"60",b"40");
+    report.last_qty = b"60".to_vec();
+    reports.push(report.clone());
+    reports.push(report); //Duplicate fill.
+    let mut report = execution_report(b"B",b"",b"E2",ExecType::Trade,OrdStatus::Filled,b"100",b"0");
+    report.order_id = b"VENUE2".to_vec();
+    report.side = Side::Sell;
+    report.last_qty = b"100".to_vec();
+    reports.push(report);
+    reports.push(execution_report(b"A",b"",b"E3",ExecType::New,OrdStatus::New,b"60",b"40")); //Not a fill.
+
+    for report in &reports {
+        if tracker.on_message(report).is_some() {
+            positions.on_message(report);
+        }
+    }
+
+    //Bought 60 and then sold 100 so 60 was realized and the position is now short 40.
+    let position = positions.get(b"TEST").unwrap().clone();
+    assert_eq!(position.bought_qty,60.0);
+    assert_eq!(position.sold_qty,100.0);
+    assert_eq!(position.net_qty,-40.0);
+    assert_eq!(position.realized_qty,60.0);
+    assert_eq!(position.fill_count,2);
+
+    //Snapshots aren't affected by later fills.
+    let snapshot = positions.snapshot();
+    positions.on_fill(b"TEST",&Side::Buy,40.0).unwrap();
+    assert_eq!(snapshot.get(&b"TEST".to_vec()).unwrap().net_qty,-40.0);
+    let position = positions.get(b"TEST").unwrap();
+    assert_eq!(position.net_qty,0.0);
+    assert_eq!(position.realized_qty,100.0);
+    assert!(positions.get(b"OTHER").is_none());
+}
+
+#[test]
+fn test_reject_reasons() {
+    //Session level rejects keep the raw code even for reserved values.
+    let mut reject = new_fixt_message!(Reject);
+    reject.ref_seq_num = 5;
+    reject.session_reject_reason = Some(SessionRejectReason::Reserved100Plus(150));
+    let rejection = Rejection::from_message(&reject).unwrap();
+    assert_eq!(rejection.reason,RejectReason::Session(Some(SessionRejectReason::Reserved100Plus(150))));
+    assert_eq!(rejection.raw_code,b"150");
+    assert_eq!(rejection.ref_seq_num,Some(5));
+
+    reject.session_reject_reason = None;
+    let rejection = Rejection::from_message(&reject).unwrap();
+    assert_eq!(rejection.reason,RejectReason::Session(None));
+    assert!(rejection.raw_code.is_empty());
+
+    assert!(Rejection::from_message(&Heartbeat::new()).is_none());
+
+    //BusinessMessageReject of a NewOrderSingle is attached to the order.
+    let mut tracker = OrderTracker::new();
+    tracker.on_message_sent(&new_order_single(b"A"));
+    let mut reject = new_fixt_message!(BusinessMessageReject);
+    reject.ref_msg_type = b"D".to_vec();
+    reject.business_reject_ref_id = b"A".to_vec();
+    reject.business_reject_reason = BusinessRejectReason::UnknownSecurity;
+    reject.text = b"Unknown symbol".to_vec();
+    {
+        let order = tracker.on_message(&reject).unwrap();
+        assert_eq!(order.ord_status,Some(OrdStatus::Rejected));
+        let rejection = order.rejection.as_ref().unwrap();
+        assert_eq!(rejection.reason,RejectReason::Business(BusinessRejectReason::UnknownSecurity));
+        assert_eq!(rejection.raw_code,b"2");
+        assert_eq!(rejection.ref_id,b"A");
+        assert_eq!(rejection.text,b"Unknown symbol");
+    }
+
+    //OrderCancelReject is attached to the order and clears the pending request.
+    tracker.on_message_sent(&new_order_single(b"B"));
+    tracker.on_message_sent(&order_cancel_replace_request(b"B",b"C",b"200",b"11"));
+    let mut reject = new_fixt_message!(OrderCancelReject);
+    reject.order_id = b"VENUE1".to_vec();
+    reject.cl_ord_id = b"C".to_vec();
+    reject.orig_cl_ord_id = b"B".to_vec();
+    reject.ord_status = OrdStatus::New;
+    reject.cxl_rej_response_to = CxlRejResponseTo::OrderCancelReplaceRequest;
+    reject.cxl_rej_reason = Some(CxlRejReason::Other);
+    let order = tracker.on_message(&reject).unwrap();
+    assert!(order.pending_request.is_none());
+    let rejection = order.rejection.as_ref().unwrap();
+    assert_eq!(rejection.reason,RejectReason::OrderCancel(Some(CxlRejReason::Other)));
+    assert_eq!(rejection.raw_code,b"99");
+    assert_eq!(rejection.ref_id,b"C");
+}
+
+#[test]
+fn test_order_messages_round_trip() {
+    define_dictionary!(
+        ExecutionReport,
+        Logon,
+        NewOrderSingle,
+        OrderCancelReject,
+        OrderCancelReplaceRequest,
+        OrderCancelRequest,
+    );
+
+    //Connect and logon.
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_and_logon(build_dictionary());
+    let mut tracker = OrderTracker::new();
+
+    let message = new_order_single(b"A");
+    tracker.on_message_sent(&message);
+    client.send_message(connection,message);
+    let message = test_server.recv_message::<NewOrderSingle>();
+    assert_eq!(message.cl_ord_id,b"A");
+
+    let message = order_cancel_replace_request(b"A",b"B",b"200",b"11");
+    tracker.on_message_sent(&message);
+    client.send_message(connection,message);
+    let message = test_server.recv_message::<OrderCancelReplaceRequest>();
+    assert_eq!(message.orig_cl_ord_id,b"A");
+    assert_eq!(message.cl_ord_id,b"B");
+
+    let mut message = execution_report(b"B",b"A",b"E1",ExecType::Replaced,OrdStatus::New,b"0",b"200");
+    message.msg_seq_num = 2;
+    test_server.send_message(message);
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    let order = tracker.on_message(&message).unwrap();
+    assert_eq!(order.cl_ord_id(),b"B");
+    assert_eq!(order.order_qty,b"200");
+}
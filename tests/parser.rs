@@ -19,23 +19,25 @@ extern crate fix_rs;
 extern crate fix_rs_macros;
 
 use chrono::offset::utc::UTC;
-use chrono::TimeZone;
+use chrono::{TimeZone,Timelike};
 use std::any::Any;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::thread;
 
 use fix_rs::byte_buffer::ByteBuffer;
-use fix_rs::dictionary::field_types::generic::RepeatingGroupFieldType;
+use fix_rs::dictionary::field_types::generic::{RepeatingGroupFieldType,TimestampPrecision,UTCTimestampFieldType};
 use fix_rs::dictionary::field_types::other::{EncryptMethod,RateSource,RateSourceType};
 use fix_rs::dictionary::fields::{EncryptMethod as EncryptMethodField,HeartBtInt,MsgSeqNum,SendingTime,SenderCompID,TargetCompID,NoMsgTypeGrp,RawData,RawDataLength,Symbol,TestReqID,Text,OrigSendingTime,ClOrdID,AllocAccount,RateSource as RateSourceField,RateSourceType as RateSourceTypeField,ReferencePage as ReferencePageField};
 use fix_rs::dictionary::messages::Heartbeat;
 use fix_rs::field::Field;
 use fix_rs::field_tag::{self,FieldTag};
 use fix_rs::field_type::FieldType;
-use fix_rs::fix::{Parser,ParseError};
+use fix_rs::fix::{Parser,ParseError,ValidationProfile,ValidationSettings};
 use fix_rs::fix_version::FIXVersion;
 use fix_rs::fixt;
 use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
-use fix_rs::message::{self,MessageDetails,REQUIRED,NOT_REQUIRED};
+use fix_rs::message::{self,Message,MessageDetails,REQUIRED,NOT_REQUIRED};
 use fix_rs::message_version::{self,MessageVersion};
 
 const PARSE_MESSAGE_BY_STREAM: bool = true;
@@ -59,6 +61,10 @@ impl FIXTMessage for LogonTest {
         Box::new(Self::new())
     }
 
+    fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+        Box::new(self.clone())
+    }
+
     fn msg_type(&self) -> &'static [u8] {
         <LogonTest as MessageDetails>::msg_type()
     }
@@ -103,12 +109,13 @@ impl FIXTMessage for LogonTest {
     }
 }
 
-fn parse_message_with_ver<T: FIXTMessage + FIXTMessageBuildable + MessageDetails + Default + Any + Clone + PartialEq + Send>(fix_version: FIXVersion,message_version: MessageVersion,message: &[u8]) -> Result<T,ParseError> {
+fn parse_message_with_settings<T: FIXTMessage + FIXTMessageBuildable + MessageDetails + Default + Any + Clone + PartialEq + Send>(fix_version: FIXVersion,message_version: MessageVersion,validation_settings: ValidationSettings,message: &[u8]) -> Result<T,ParseError> {
     let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
     let builder: Box<BuildFIXTMessage + Send> = <T as Default>::default().builder();
     message_dictionary.insert(<T as MessageDetails>::msg_type(),builder);
 
     let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+    parser.set_validation_settings(validation_settings);
 
     let message_bytes = Vec::from(message);
     if PARSE_MESSAGE_BY_STREAM {
@@ -160,6 +167,10 @@ fn parse_message_with_ver<T: FIXTMessage + FIXTMessageBuildable + MessageDetails
     Ok(casted_message)
 }
 
+fn parse_message_with_ver<T: FIXTMessage + FIXTMessageBuildable + MessageDetails + Default + Any + Clone + PartialEq + Send>(fix_version: FIXVersion,message_version: MessageVersion,message: &[u8]) -> Result<T,ParseError> {
+    parse_message_with_settings::<T>(fix_version,message_version,ValidationSettings::default(),message)
+}
+
 fn parse_message<T: FIXTMessage + FIXTMessageBuildable + MessageDetails + Default + Any + Clone + PartialEq + Send>(message: &[u8]) -> Result<T,ParseError> {
     parse_message_with_ver::<T>(FIXVersion::FIX_4_2,MessageVersion::FIX42,message)
 }
@@ -432,6 +443,41 @@ fn duplicate_tag_test() {
     }
 }
 
+#[test]
+fn validation_profile_test() {
+    fn parse_with_profile(profile: ValidationProfile,message: &[u8]) -> Result<LogonTest,ParseError> {
+        parse_message_with_settings::<LogonTest>(FIXVersion::FIX_4_2,MessageVersion::FIX42,profile.settings(),message)
+    }
+
+    //Duplicate tags are only rejected by the strict profile.
+    let duplicate_tag_message = b"8=FIX.4.2\x019=70\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x0198=0\x01108=30\x0110=040\x01";
+    assert!(parse_with_profile(ValidationProfile::Strict,duplicate_tag_message).is_err());
+    let message = parse_with_profile(ValidationProfile::Interop,duplicate_tag_message).unwrap();
+    assert_eq!(message.encrypt_method,EncryptMethod::None);
+    assert_eq!(message.heart_bt_int,30);
+
+    //Checksums are still validated by the interop profile but not the permissive one.
+    let incorrect_checksum_tag_message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=000\x01";
+    match parse_with_profile(ValidationProfile::Interop,incorrect_checksum_tag_message).err().unwrap() {
+        fix_rs::fix::ParseError::ChecksumDoesNotMatch(calculated_checksum,stated_checksum) => {
+            assert_eq!(calculated_checksum,73);
+            assert_eq!(stated_checksum,0);
+        },
+        _ => assert!(false),
+    }
+    let message = parse_with_profile(ValidationProfile::Permissive,incorrect_checksum_tag_message).unwrap();
+    assert_eq!(message.heart_bt_int,30);
+
+    //Out of range values fall back to the field's default value when permissive.
+    let out_of_range_message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=9\x01108=30\x0110=082\x01";
+    match parse_with_profile(ValidationProfile::Interop,out_of_range_message).err().unwrap() {
+        fix_rs::fix::ParseError::OutOfRangeTag(tag) => assert_eq!(tag,FieldTag(98)),
+        _ => assert!(false),
+    }
+    let message = parse_with_profile(ValidationProfile::Permissive,out_of_range_message).unwrap();
+    assert_eq!(message.encrypt_method,EncryptMethod::None);
+}
+
 #[test]
 fn length_tag_test() {
     define_message!(LengthTagTestMessage: b"L" => {
@@ -444,6 +490,10 @@ fn length_tag_test() {
             Box::new(Self::new())
         }
 
+        fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+            Box::new(self.clone())
+        }
+
         fn msg_type(&self) -> &'static [u8] {
             b"L"
         }
@@ -558,6 +608,10 @@ fn repeating_groups_test() {
             Box::new(Self::new())
         }
 
+        fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+            Box::new(self.clone())
+        }
+
         fn msg_type(&self) -> &'static [u8] {
             b"L"
         }
@@ -756,6 +810,10 @@ fn nested_repeating_groups_test() {
             Box::new(Self::new())
         }
 
+        fn clone_into_box(&self) -> Box<FIXTMessage + Send> {
+            Box::new(self.clone())
+        }
+
         fn msg_type(&self) -> &'static [u8] {
             b"L"
         }
@@ -911,6 +969,43 @@ fn stream_test() {
     assert_eq!(casted_message.heart_bt_int,30);
 }
 
+#[test]
+fn shared_dictionary_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    //Parsers sharing a dictionary, even across threads, don't interfere with each other.
+    let message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01";
+    let message_dictionary = Arc::new(build_dictionary());
+    let mut threads = Vec::new();
+    for _ in 0..4 {
+        let message_dictionary = message_dictionary.clone();
+        threads.push(thread::spawn(move || {
+            let mut parser = Parser::with_shared_dictionary(message_dictionary,MAX_MESSAGE_SIZE);
+            for _ in 0..100 {
+                let (bytes_read,result) = parser.parse(message);
+                assert!(result.is_ok());
+                assert_eq!(bytes_read,message.len());
+            }
+
+            parser.messages.len()
+        }));
+    }
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(),100);
+    }
+
+    //Unknown tags are still detected using the shared dictionary.
+    let mut parser = Parser::with_shared_dictionary(message_dictionary,MAX_MESSAGE_SIZE);
+    let message = b"8=FIX.4.2\x019=73\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0199999=1\x0110=212\x01";
+    let (_,result) = parser.parse(message);
+    match result {
+        Err(ParseError::UnknownTag(tag)) => assert_eq!(tag,FieldTag(99999)),
+        _ => assert!(false),
+    }
+}
+
 #[test]
 fn equal_character_in_text_test() {
     let message = b"8=FIX.4.2\x019=37\x0135=L\x0134=177\x0198=0\x01108=30\x0158=some=text\x0110=176\x01";
@@ -933,3 +1028,283 @@ fn no_value_after_tag_test() {
     }
 }
 
+#[test]
+fn utc_timestamp_precision_test() {
+    //Inbound timestamps are accepted with any number of fractional second digits.
+    let mut timestamp = UTCTimestampFieldType::new_empty();
+    let valid_timestamps: [(&[u8],u32); 5] = [
+        (b"20090107-18:15:16",0),
+        (b"20090107-18:15:16.1",100_000_000),
+        (b"20090107-18:15:16.123",123_000_000),
+        (b"20090107-18:15:16.123456",123_456_000),
+        (b"20090107-18:15:16.123456789",123_456_789),
+    ];
+    for &(bytes,nanoseconds) in &valid_timestamps {
+        assert!(UTCTimestampFieldType::set_value(&mut timestamp,bytes).is_ok());
+        assert_eq!(timestamp,UTC.ymd(2009,1,7).and_hms_nano(18,15,16,nanoseconds));
+    }
+    assert!(UTCTimestampFieldType::set_value(&mut timestamp,b"20090107-18:15:16.").is_err());
+    assert!(UTCTimestampFieldType::set_value(&mut timestamp,b"20090107-18:15:16.1234567890").is_err());
+
+    //Outbound timestamps use the precision in effect on the current thread. Milliseconds by
+    //default.
+    let timestamp = UTC.ymd(2009,1,7).and_hms_nano(18,15,16,123_456_789);
+    let serialize = || {
+        let mut bytes = Vec::new();
+        UTCTimestampFieldType::read(&timestamp,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&mut bytes);
+        bytes
+    };
+    assert_eq!(serialize(),b"20090107-18:15:16.123".to_vec());
+    assert_eq!(UTCTimestampFieldType::with_precision(TimestampPrecision::Seconds,&serialize),b"20090107-18:15:16".to_vec());
+    assert_eq!(UTCTimestampFieldType::with_precision(TimestampPrecision::Microseconds,&serialize),b"20090107-18:15:16.123456".to_vec());
+    assert_eq!(UTCTimestampFieldType::with_precision(TimestampPrecision::Nanoseconds,&serialize),b"20090107-18:15:16.123456789".to_vec());
+    assert_eq!(UTCTimestampFieldType::precision(),TimestampPrecision::Milliseconds);
+
+    //New timestamps don't carry more precision than will be serialized.
+    let now = UTCTimestampFieldType::with_precision(TimestampPrecision::Seconds,UTCTimestampFieldType::new_now);
+    assert_eq!(now.nanosecond(),0);
+}
+
+
+#[test]
+fn to_fix_string_test() {
+    let mut message = Heartbeat::new();
+    message.msg_seq_num = 177;
+    message.sender_comp_id = b"SERVER".to_vec();
+    message.target_comp_id = b"CLIENT".to_vec();
+    message.sending_time = UTC.ymd(2009,1,7).and_hms(18,15,16);
+
+    //Should be identical to serializing except SOH characters are visible.
+    let mut message_bytes = ByteBuffer::new();
+    message.read(FIXVersion::FIX_4_2,MessageVersion::FIX42,&mut message_bytes);
+    let expected_str = String::from_utf8(message_bytes.bytes().to_vec()).unwrap().replace("\x01","|");
+    assert_eq!(message.to_fix_string(FIXVersion::FIX_4_2,MessageVersion::FIX42),expected_str);
+    assert!(!expected_str.contains("\x01"));
+
+    //Display uses the newest versions for messages that were not parsed.
+    assert_eq!(format!("{}",message),message.to_fix_string(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2));
+
+    //Display uses the versions a message was received with when it was parsed.
+    let parsed_message = parse_message::<Heartbeat>(message_bytes.bytes()).unwrap();
+    assert_eq!(format!("{}",parsed_message),expected_str);
+    assert_eq!(format!("{}",&parsed_message as &FIXTMessage),expected_str);
+}
+
+#[test]
+fn prescan_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    //Header values are found without parsing the message or changing the parser's state.
+    let parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    let message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01";
+    let mut message_and_more = message.to_vec();
+    message_and_more.extend_from_slice(b"8=FIX.4.2\x019=");
+    let prescanned_message = parser.prescan(&message_and_more[..]).unwrap();
+    assert_eq!(prescanned_message.length,message.len());
+    assert_eq!(prescanned_message.msg_type,b"L");
+    assert_eq!(prescanned_message.msg_seq_num,Some(177));
+    assert_eq!(prescanned_message.poss_dup,false);
+    assert_eq!(prescanned_message.sender_comp_id,b"SERVER");
+    assert_eq!(prescanned_message.target_comp_id,b"CLIENT");
+    assert!(parser.is_idle());
+
+    //Length tags are honored so a value containing SOH and "34=" isn't mistaken for MsgSeqNum.
+    let message = b"8=FIX.4.2\x019=40\x0135=L\x0143=Y\x0195=13\x0196=This\x0134=99\x01te\x0134=177\x0110=116\x01";
+    let prescanned_message = parser.prescan(message).unwrap();
+    assert_eq!(prescanned_message.msg_seq_num,Some(177));
+    assert_eq!(prescanned_message.poss_dup,true);
+
+    //Incomplete messages and messages with the wrong CheckSum are left to parse().
+    assert!(parser.prescan(&message[..message.len() - 1]).is_none());
+    let incorrect_checksum_message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=000\x01";
+    assert!(parser.prescan(incorrect_checksum_message).is_none());
+    assert!(parser.prescan(b"garbage8=FIX.4.2\x01").is_none());
+}
+
+#[test]
+fn reset_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    //Leave a parsed message that wasn't taken and a partial message in the parser.
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    parser.set_default_message_version(MessageVersion::FIX42);
+    let message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01";
+    let mut message_and_more = message.to_vec();
+    message_and_more.extend_from_slice(b"8=FIX.4.2\x019=65\x0135=L\x0149=SER");
+    let (bytes_read,result) = parser.parse(&message_and_more[..]);
+    assert!(result.is_ok());
+    assert_eq!(bytes_read,message_and_more.len());
+    assert_eq!(parser.messages.len(),1);
+    assert!(!parser.is_idle());
+
+    //Everything left over is thrown away so the next message is parsed from scratch.
+    parser.reset();
+    assert!(parser.is_idle());
+    assert!(parser.messages.is_empty());
+    let (bytes_read,result) = parser.parse(message);
+    assert!(result.is_ok());
+    assert_eq!(bytes_read,message.len());
+    assert_eq!(parser.messages.len(),1);
+    assert_eq!(parser.messages[0].as_any().downcast_ref::<LogonTest>().unwrap().msg_seq_num,177);
+
+    //More parsers can be constructed from the same dictionary without copying it.
+    let message_dictionary = parser.shared_dictionary();
+    let mut other_parser = Parser::with_shared_dictionary(message_dictionary.clone(),MAX_MESSAGE_SIZE);
+    assert!(Arc::ptr_eq(&message_dictionary,&other_parser.shared_dictionary()));
+    let (_,result) = other_parser.parse(message);
+    assert!(result.is_ok());
+    assert_eq!(other_parser.messages.len(),1);
+}
+
+#[test]
+fn error_context_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    //Context describes where the error happened and which message it happened in.
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    parser.set_keep_discarded_bytes(true);
+    let message = b"GARBAGE8=FIX.4.2\x019=70\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x0198=0\x01108=30\x0110=073\x01";
+    let (_,result) = parser.parse(&message[..]);
+    match result.err().unwrap() {
+        ParseError::DuplicateTag(tag) => assert_eq!(tag,FieldTag(98)),
+        _ => assert!(false),
+    }
+    let context = parser.last_error_context().unwrap().clone();
+    let duplicate_end = message.windows(9).position(|window| window == b"98=0\x0198=0").unwrap() + 9;
+    assert_eq!(context.offset,duplicate_end);
+    assert_eq!(context.tag,Some(FieldTag(98)));
+    assert_eq!(context.msg_type,Some(b"L".to_vec()));
+    assert_eq!(context.msg_seq_num,Some(177));
+    assert_eq!(&message[context.excerpt_start..context.excerpt_start + context.excerpt.len()],&context.excerpt[..]);
+    assert!(context.excerpt_start <= context.offset);
+    assert!(context.excerpt.len() <= 64);
+
+    //The excerpt is rendered on a single line as both ASCII and hex.
+    let description = format!("{}",context);
+    assert!(description.starts_with(&format!("offset={} tag=98 msg_type=L msg_seq_num=177 ",duplicate_end)));
+    assert!(description.contains("|98=0|98=0|108=30|"));
+    assert!(description.contains("39383d300139383d3001"));
+    assert!(!description.contains('\n'));
+
+    //Same context is kept with the discarded bytes.
+    let discarded = parser.discarded.last().unwrap();
+    assert_eq!(discarded.reason,format!("{} {}",ParseError::DuplicateTag(FieldTag(98)),context));
+
+    //Nothing is known about an error that happens before the header is read.
+    let (_,result) = parser.parse(b"8=FIX.4.2\x019=XX\x01");
+    assert!(result.is_err());
+    let context = parser.last_error_context().unwrap();
+    assert_eq!(context.tag,Some(FieldTag(9)));
+    assert_eq!(context.msg_type,None);
+    assert_eq!(context.msg_seq_num,None);
+
+    //Cleared once parsing succeeds again.
+    let (_,result) = parser.parse(b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01");
+    assert!(result.is_ok());
+    assert!(parser.last_error_context().is_none());
+}
+
+#[test]
+fn filler_bytes_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    let message = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01";
+    let mut bytes = b"\r\n".to_vec();
+    bytes.extend_from_slice(message);
+    bytes.extend_from_slice(b"\r\n  ");
+    bytes.extend_from_slice(message);
+    bytes.extend_from_slice(b"XX\n");
+    bytes.extend_from_slice(message);
+
+    //Without filler bytes, everything between messages is garbage.
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    parser.set_keep_discarded_bytes(true);
+    let (bytes_read,result) = parser.parse(&bytes[..]);
+    assert!(result.is_ok());
+    assert_eq!(bytes_read,bytes.len());
+    assert_eq!(parser.messages.len(),3);
+    assert_eq!(parser.discarded.len(),3);
+    assert_eq!(parser.filler_byte_count(),0);
+
+    //Filler is skipped and counted instead. Filler after other garbage is still garbage.
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    parser.set_keep_discarded_bytes(true);
+    parser.set_filler_bytes(b"\r\n ");
+    let (bytes_read,result) = parser.parse(&bytes[..]);
+    assert!(result.is_ok());
+    assert_eq!(bytes_read,bytes.len());
+    assert_eq!(parser.messages.len(),3);
+    assert_eq!(parser.discarded.len(),1);
+    assert_eq!(parser.discarded[0].bytes,b"XX\n");
+    assert_eq!(parser.filler_byte_count(),6);
+
+    //Filler split across calls is still skipped.
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    parser.set_filler_bytes(b"\r\n");
+    let (_,result) = parser.parse(b"\r");
+    assert!(result.is_ok());
+    let (_,result) = parser.parse(b"\n");
+    assert!(result.is_ok());
+    let (_,result) = parser.parse(message);
+    assert!(result.is_ok());
+    assert_eq!(parser.messages.len(),1);
+    assert_eq!(parser.filler_byte_count(),2);
+
+    //Count starts over when the parser is reset.
+    parser.reset();
+    assert_eq!(parser.filler_byte_count(),0);
+    assert_eq!(parser.filler_bytes(),b"\r\n");
+}
+
+#[test]
+fn recycle_message_test() {
+    define_dictionary!(
+        LogonTest,
+    );
+
+    let message_with_text = b"8=FIX.4.2\x019=74\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0158=Hello\x0110=232\x01";
+    let message_without_text = b"8=FIX.4.2\x019=65\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x01108=30\x0110=073\x01";
+
+    let mut parser = Parser::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    let (_,result) = parser.parse(message_with_text);
+    assert!(result.is_ok());
+    let message = parser.messages.remove(0);
+    assert_eq!(message.as_any().downcast_ref::<LogonTest>().unwrap().text,b"Hello".to_vec());
+
+    //A recycled message is reused for the next message of the same type. Nothing from the
+    //previous message is left behind.
+    let message_ptr = &*message as *const _ as *const u8;
+    parser.recycle_message(message);
+    let (_,result) = parser.parse(message_without_text);
+    assert!(result.is_ok());
+    let message = parser.messages.remove(0);
+    assert_eq!(&*message as *const _ as *const u8,message_ptr);
+    {
+        let message = message.as_any().downcast_ref::<LogonTest>().unwrap();
+        assert!(message.text.is_empty());
+        assert_eq!(message.heart_bt_int,30);
+        assert_eq!(message.msg_seq_num,177);
+        assert_eq!(message.sender_comp_id,b"SERVER".to_vec());
+        assert_eq!(message.meta.as_ref().unwrap().body_length,65);
+    }
+
+    //Messages with a type the parser doesn't know about are ignored.
+    parser.recycle_message(Box::new(Heartbeat::new()));
+
+    //Required fields are still checked for reused messages.
+    parser.recycle_message(message);
+    let missing_heart_bt_int = b"8=FIX.4.2\x019=58\x0135=L\x0149=SERVER\x0156=CLIENT\x0134=177\x0152=20090107-18:15:16\x0198=0\x0110=017\x01";
+    let (_,result) = parser.parse(missing_heart_bt_int);
+    match result {
+        Err(ParseError::MissingRequiredTag(tag,_)) => assert_eq!(tag,HeartBtInt::tag()),
+        _ => assert!(false),
+    }
+}
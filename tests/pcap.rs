@@ -0,0 +1,221 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "pcap")]
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use chrono::Timelike;
+use std::net::SocketAddr;
+
+#[macro_use]
+mod common;
+use common::{MAX_MESSAGE_SIZE,new_logon_message,serialize_message};
+use fix_rs::dictionary::messages::{Heartbeat,Logon};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_version::MessageVersion;
+use fix_rs::pcap::PcapDecoder;
+
+const CLIENT_ADDRESS: ([u8;4],u16) = ([10,0,0,1],50000);
+const SERVER_ADDRESS: ([u8;4],u16) = ([10,0,0,2],9880);
+const OTHER_ADDRESS: ([u8;4],u16) = ([10,0,0,3],443);
+const TCP_FLAG_SYN: u8 = 0x02;
+const TCP_FLAG_ACK: u8 = 0x10;
+
+fn socket_addr(address: ([u8;4],u16)) -> SocketAddr {
+    format!("{}.{}.{}.{}:{}",address.0[0],address.0[1],address.0[2],address.0[3],address.1).parse().unwrap()
+}
+
+fn ethernet_packet(source: ([u8;4],u16),destination: ([u8;4],u16),seq: u32,flags: u8,payload: &[u8]) -> Vec<u8> {
+    //Destination and source MAC addresses followed by the IPv4 EtherType.
+    let mut packet = vec![0;12];
+    packet.extend_from_slice(&[0x08,0x00]);
+
+    //IPv4 header without options and with Don't Fragment set.
+    let total_len = 20 + 20 + payload.len();
+    packet.extend_from_slice(&[0x45,0,(total_len >> 8) as u8,total_len as u8,0,0,0x40,0,64,6,0,0]);
+    packet.extend_from_slice(&source.0);
+    packet.extend_from_slice(&destination.0);
+
+    //TCP header without options.
+    packet.extend_from_slice(&[(source.1 >> 8) as u8,source.1 as u8,(destination.1 >> 8) as u8,destination.1 as u8]);
+    packet.extend_from_slice(&[(seq >> 24) as u8,(seq >> 16) as u8,(seq >> 8) as u8,seq as u8]);
+    packet.extend_from_slice(&[0,0,0,0,0x50,flags,0xFF,0xFF,0,0,0,0]);
+    packet.extend_from_slice(payload);
+
+    packet
+}
+
+fn push_u16_le(bytes: &mut Vec<u8>,value: u16) {
+    bytes.extend_from_slice(&[value as u8,(value >> 8) as u8]);
+}
+
+fn push_u32_le(bytes: &mut Vec<u8>,value: u32) {
+    bytes.extend_from_slice(&[value as u8,(value >> 8) as u8,(value >> 16) as u8,(value >> 24) as u8]);
+}
+
+fn pcap_file(packets: &[(u32,u32,Vec<u8>)]) -> Vec<u8> {
+    //Little endian with microsecond timestamps and Ethernet link type.
+    let mut bytes = Vec::new();
+    push_u32_le(&mut bytes,0xA1B2C3D4);
+    push_u16_le(&mut bytes,2);
+    push_u16_le(&mut bytes,4);
+    push_u32_le(&mut bytes,0);
+    push_u32_le(&mut bytes,0);
+    push_u32_le(&mut bytes,65535);
+    push_u32_le(&mut bytes,1);
+
+    for &(seconds,microseconds,ref packet) in packets {
+        push_u32_le(&mut bytes,seconds);
+        push_u32_le(&mut bytes,microseconds);
+        push_u32_le(&mut bytes,packet.len() as u32);
+        push_u32_le(&mut bytes,packet.len() as u32);
+        bytes.extend_from_slice(packet);
+    }
+
+    bytes
+}
+
+fn pcapng_file(packets: &[(u64,Vec<u8>)]) -> Vec<u8> {
+    //Little endian with a single Ethernet interface using nanosecond timestamps.
+    let mut bytes = Vec::new();
+
+    //Section header block.
+    push_u32_le(&mut bytes,0x0A0D0D0A);
+    push_u32_le(&mut bytes,28);
+    push_u32_le(&mut bytes,0x1A2B3C4D);
+    push_u16_le(&mut bytes,1);
+    push_u16_le(&mut bytes,0);
+    bytes.extend_from_slice(&[0xFF;8]); //Unknown section length.
+    push_u32_le(&mut bytes,28);
+
+    //Interface description block with if_tsresol = 9.
+    push_u32_le(&mut bytes,1);
+    push_u32_le(&mut bytes,32);
+    push_u16_le(&mut bytes,1);
+    push_u16_le(&mut bytes,0);
+    push_u32_le(&mut bytes,65535);
+    push_u16_le(&mut bytes,9);
+    push_u16_le(&mut bytes,1);
+    bytes.extend_from_slice(&[9,0,0,0]);
+    push_u16_le(&mut bytes,0);
+    push_u16_le(&mut bytes,0);
+    push_u32_le(&mut bytes,32);
+
+    //Enhanced packet blocks.
+    for &(nanoseconds,ref packet) in packets {
+        let padded_len = (packet.len() + 3) & !3;
+        let block_len = 32 + padded_len as u32;
+        push_u32_le(&mut bytes,6);
+        push_u32_le(&mut bytes,block_len);
+        push_u32_le(&mut bytes,0);
+        push_u32_le(&mut bytes,(nanoseconds >> 32) as u32);
+        push_u32_le(&mut bytes,nanoseconds as u32);
+        push_u32_le(&mut bytes,packet.len() as u32);
+        push_u32_le(&mut bytes,packet.len() as u32);
+        bytes.extend_from_slice(packet);
+        bytes.extend_from_slice(&vec![0;padded_len - packet.len()]);
+        push_u32_le(&mut bytes,block_len);
+    }
+
+    bytes
+}
+
+#[test]
+fn test_pcap_reassembly() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+    );
+
+    //The client's Logon is split in two, the second half arrives first, and the first half is
+    //retransmitted. The server's stream is already in progress when the capture starts.
+    let logon_bytes = serialize_message(&new_logon_message(),FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    let (first_half,second_half) = logon_bytes.split_at(logon_bytes.len() / 2);
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 7;
+    let heartbeat_bytes = serialize_message(&heartbeat,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+
+    let packets = vec![
+        (1483228800,0,ethernet_packet(CLIENT_ADDRESS,SERVER_ADDRESS,999,TCP_FLAG_SYN,b"")),
+        (1483228800,100,ethernet_packet(CLIENT_ADDRESS,SERVER_ADDRESS,1000 + first_half.len() as u32,TCP_FLAG_ACK,second_half)),
+        (1483228800,200,ethernet_packet(CLIENT_ADDRESS,SERVER_ADDRESS,1000,TCP_FLAG_ACK,first_half)),
+        (1483228800,300,ethernet_packet(CLIENT_ADDRESS,SERVER_ADDRESS,1000,TCP_FLAG_ACK,first_half)),
+        (1483228801,500,ethernet_packet(SERVER_ADDRESS,CLIENT_ADDRESS,5000,TCP_FLAG_ACK,&heartbeat_bytes)),
+    ];
+    let file = pcap_file(&packets);
+
+    let mut decoder = PcapDecoder::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    let messages = decoder.decode(&mut &file[..]).unwrap();
+    assert_eq!(messages.len(),2);
+
+    assert_eq!(messages[0].source,socket_addr(CLIENT_ADDRESS));
+    assert_eq!(messages[0].destination,socket_addr(SERVER_ADDRESS));
+    assert_eq!(messages[0].timestamp.timestamp(),1483228800);
+    assert_eq!(messages[0].timestamp.nanosecond(),200000);
+    let message = messages[0].message.as_ref().unwrap();
+    let message = message.as_any().downcast_ref::<Logon>().expect("Not expected message type");
+    assert_eq!(message.heart_bt_int,5);
+
+    assert_eq!(messages[1].source,socket_addr(SERVER_ADDRESS));
+    assert_eq!(messages[1].destination,socket_addr(CLIENT_ADDRESS));
+    assert_eq!(messages[1].timestamp.timestamp(),1483228801);
+    let message = messages[1].message.as_ref().unwrap();
+    let message = message.as_any().downcast_ref::<Heartbeat>().expect("Not expected message type");
+    assert_eq!(message.msg_seq_num,7);
+}
+
+#[test]
+fn test_pcapng_port_filter() {
+    define_dictionary!(
+        Heartbeat,
+        Logon,
+    );
+
+    //Only the stream on the FIX port should be decoded even though the other stream also
+    //contains a valid message.
+    let mut heartbeat = new_fixt_message!(Heartbeat);
+    heartbeat.msg_seq_num = 3;
+    let heartbeat_bytes = serialize_message(&heartbeat,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+
+    let packets = vec![
+        (1483228800000000001,ethernet_packet(OTHER_ADDRESS,CLIENT_ADDRESS,1,TCP_FLAG_ACK,&heartbeat_bytes)),
+        (1483228800123456789,ethernet_packet(SERVER_ADDRESS,CLIENT_ADDRESS,1,TCP_FLAG_ACK,&heartbeat_bytes)),
+    ];
+    let file = pcapng_file(&packets);
+
+    let mut decoder = PcapDecoder::new(build_dictionary(),MAX_MESSAGE_SIZE).port(SERVER_ADDRESS.1);
+    let messages = decoder.decode(&mut &file[..]).unwrap();
+    assert_eq!(messages.len(),1);
+    assert_eq!(messages[0].source,socket_addr(SERVER_ADDRESS));
+    assert_eq!(messages[0].timestamp.timestamp(),1483228800);
+    assert_eq!(messages[0].timestamp.nanosecond(),123456789);
+    assert!(messages[0].message.as_ref().unwrap().as_any().downcast_ref::<Heartbeat>().is_some());
+}
+
+#[test]
+fn test_unknown_format() {
+    define_dictionary!(
+        Heartbeat,
+    );
+
+    let mut decoder = PcapDecoder::new(build_dictionary(),MAX_MESSAGE_SIZE);
+    assert!(decoder.decode(&mut &b"8=FIXT.1.1\x019=5\x0135=0\x0110=000\x01"[..]).is_err());
+}
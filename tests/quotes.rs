@@ -0,0 +1,191 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::round_trip;
+use fix_rs::dictionary::field_types::other::{CrossPrioritization,CrossType,OrdType,QuoteRequestRejectReason,QuoteRespType,QuoteStatus,QuoteType,Side};
+use fix_rs::dictionary::fields::{QuotReqGrp,SideCrossOrdCxlGrp,SideCrossOrdModGrp};
+use fix_rs::dictionary::messages::{CrossOrderCancelReplaceRequest,CrossOrderCancelRequest,NewOrderCross,Quote,QuoteRequest,QuoteRequestReject,QuoteResponse,QuoteStatusReport};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_version::MessageVersion;
+
+fn cross_side(side: Side,cl_ord_id: &[u8],orig_cl_ord_id: &[u8]) -> SideCrossOrdModGrp {
+    let mut group = SideCrossOrdModGrp::new();
+    group.side = side;
+    group.cl_ord_id = cl_ord_id.to_vec();
+    group.orig_cl_ord_id = orig_cl_ord_id.to_vec();
+    group.order_qty = b"5000".to_vec();
+
+    group
+}
+
+fn quote_request_group(side: Option<Side>) -> QuotReqGrp {
+    let mut group = QuotReqGrp::new();
+    group.symbol = b"TEST".to_vec();
+    group.side = side;
+    group.order_qty = b"5000".to_vec();
+
+    group
+}
+
+#[test]
+fn test_cross_orders() {
+    let mut message = new_fixt_message!(NewOrderCross);
+    message.cross_id = b"CROSS1".to_vec();
+    message.cross_type = CrossType::CrossAONBothSidesMustBeFullyExecuted;
+    message.cross_prioritization = CrossPrioritization::None;
+    message.no_sides.push(Box::new(cross_side(Side::Buy,b"BUY1",b"")));
+    message.no_sides.push(Box::new(cross_side(Side::Sell,b"SELL1",b"")));
+    message.symbol = b"TEST".to_vec();
+    message.transact_time = message.sending_time;
+    message.ord_type = OrdType::Limit;
+    message.price = b"10.25".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.cross_id,b"CROSS1");
+    assert_eq!(parsed_message.cross_type,CrossType::CrossAONBothSidesMustBeFullyExecuted);
+    assert_eq!(parsed_message.cross_prioritization,CrossPrioritization::None);
+    assert_eq!(parsed_message.no_sides.len(),2);
+    assert_eq!(parsed_message.no_sides[0].side,Side::Buy);
+    assert_eq!(parsed_message.no_sides[1].side,Side::Sell);
+    assert_eq!(parsed_message.no_sides[1].cl_ord_id,b"SELL1");
+    assert_eq!(parsed_message.no_sides[1].order_qty,b"5000");
+    assert_eq!(parsed_message.price,b"10.25");
+
+    let mut message = new_fixt_message!(CrossOrderCancelReplaceRequest);
+    message.cross_id = b"CROSS2".to_vec();
+    message.orig_cross_id = b"CROSS1".to_vec();
+    message.cross_type = CrossType::CrossAONBothSidesMustBeFullyExecuted;
+    message.cross_prioritization = CrossPrioritization::BuySideIsPrioritized;
+    message.no_sides.push(Box::new(cross_side(Side::Buy,b"BUY2",b"BUY1")));
+    message.no_sides.push(Box::new(cross_side(Side::Sell,b"SELL2",b"SELL1")));
+    message.symbol = b"TEST".to_vec();
+    message.transact_time = message.sending_time;
+    message.ord_type = OrdType::Limit;
+    message.price = b"10.30".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.orig_cross_id,b"CROSS1");
+    assert_eq!(parsed_message.cross_prioritization,CrossPrioritization::BuySideIsPrioritized);
+    assert_eq!(parsed_message.no_sides[0].orig_cl_ord_id,b"BUY1");
+    assert_eq!(parsed_message.no_sides[1].orig_cl_ord_id,b"SELL1");
+
+    let mut message = new_fixt_message!(CrossOrderCancelRequest);
+    message.cross_id = b"CROSS3".to_vec();
+    message.orig_cross_id = b"CROSS2".to_vec();
+    message.cross_type = CrossType::CrossAONBothSidesMustBeFullyExecuted;
+    message.cross_prioritization = CrossPrioritization::None;
+    for (side,cl_ord_id,orig_cl_ord_id) in vec![(Side::Buy,&b"BUY3"[..],&b"BUY2"[..]),(Side::Sell,&b"SELL3"[..],&b"SELL2"[..])] {
+        let mut group = SideCrossOrdCxlGrp::new();
+        group.side = side;
+        group.cl_ord_id = cl_ord_id.to_vec();
+        group.orig_cl_ord_id = orig_cl_ord_id.to_vec();
+        message.no_sides.push(Box::new(group));
+    }
+    message.symbol = b"TEST".to_vec();
+    message.transact_time = message.sending_time;
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.cross_id,b"CROSS3");
+    assert_eq!(parsed_message.no_sides.len(),2);
+    assert_eq!(parsed_message.no_sides[1].cl_ord_id,b"SELL3");
+    assert_eq!(parsed_message.no_sides[1].orig_cl_ord_id,b"SELL2");
+}
+
+#[test]
+fn test_quote_negotiation() {
+    //Request a two-sided quote.
+    let mut message = new_fixt_message!(QuoteRequest);
+    message.quote_req_id = b"RFQ1".to_vec();
+    message.no_related_sym.push(Box::new(quote_request_group(None)));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.quote_req_id,b"RFQ1");
+    assert_eq!(parsed_message.no_related_sym.len(),1);
+    assert_eq!(parsed_message.no_related_sym[0].symbol,b"TEST");
+    assert_eq!(parsed_message.no_related_sym[0].side,None);
+    assert_eq!(parsed_message.no_related_sym[0].order_qty,b"5000");
+
+    //Dealer responds with a tradeable quote.
+    let mut message = new_fixt_message!(Quote);
+    message.quote_req_id = b"RFQ1".to_vec();
+    message.quote_id = b"Q1".to_vec();
+    message.quote_type = Some(QuoteType::Tradeable);
+    message.symbol = b"TEST".to_vec();
+    message.bid_px = b"10.20".to_vec();
+    message.offer_px = b"10.30".to_vec();
+    message.bid_size = b"5000".to_vec();
+    message.offer_size = b"5000".to_vec();
+    message.valid_until_time = message.sending_time;
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.quote_id,b"Q1");
+    assert_eq!(parsed_message.quote_type,Some(QuoteType::Tradeable));
+    assert_eq!(parsed_message.side,None);
+    assert_eq!(parsed_message.bid_px,b"10.20");
+    assert_eq!(parsed_message.offer_px,b"10.30");
+    assert_eq!(parsed_message.offer_size,b"5000");
+
+    //Client counters on one side.
+    let mut message = new_fixt_message!(QuoteResponse);
+    message.quote_resp_id = b"QR1".to_vec();
+    message.quote_id = b"Q1".to_vec();
+    message.quote_resp_type = QuoteRespType::Counter;
+    message.symbol = b"TEST".to_vec();
+    message.side = Some(Side::Buy);
+    message.order_qty = b"5000".to_vec();
+    message.offer_px = b"10.25".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.quote_resp_id,b"QR1");
+    assert_eq!(parsed_message.quote_resp_type,QuoteRespType::Counter);
+    assert_eq!(parsed_message.side,Some(Side::Buy));
+    assert_eq!(parsed_message.offer_px,b"10.25");
+
+    //Dealer reports the counter was accepted.
+    let mut message = new_fixt_message!(QuoteStatusReport);
+    message.quote_id = b"Q1".to_vec();
+    message.quote_resp_id = b"QR1".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.quote_status = Some(QuoteStatus::Accepted);
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.quote_resp_id,b"QR1");
+    assert_eq!(parsed_message.quote_status,Some(QuoteStatus::Accepted));
+}
+
+#[test]
+fn test_quote_request_reject() {
+    let mut message = new_fixt_message!(QuoteRequestReject);
+    message.quote_req_id = b"RFQ2".to_vec();
+    message.quote_request_reject_reason = QuoteRequestRejectReason::InsufficientCredit;
+    message.no_related_sym.push(Box::new(quote_request_group(Some(Side::Sell))));
+    message.text = b"Credit limit reached".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.quote_request_reject_reason,QuoteRequestRejectReason::InsufficientCredit);
+    assert_eq!(parsed_message.no_related_sym.len(),1);
+    assert_eq!(parsed_message.no_related_sym[0].side,Some(Side::Sell));
+    assert_eq!(parsed_message.text,b"Credit limit reached");
+}
@@ -0,0 +1,69 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use fix_rs::fixt::rate_anomaly::{RateAnomalyDetector,RateAnomalyKind,RateAnomalySettings};
+
+fn record(detector: &mut RateAnomalyDetector,msg_type: &[u8],count: usize) {
+    for _ in 0..count {
+        detector.record(msg_type);
+    }
+}
+
+#[test]
+fn test_rate_anomaly_detector() {
+    let mut settings = RateAnomalySettings::new();
+    settings.baseline_windows = 3;
+    settings.min_count = 5;
+    let mut detector = RateAnomalyDetector::new(settings);
+
+    //Nothing is reported while the baseline is built.
+    for _ in 0..3 {
+        record(&mut detector,b"8",10);
+        record(&mut detector,b"W",100);
+        record(&mut detector,b"0",1);
+        assert!(detector.end_window().is_empty());
+    }
+
+    //An ExecutionReport storm and a market data drought in the same window are reported
+    //separately. A quiet MsgType that goes silent isn't a drought.
+    record(&mut detector,b"8",500);
+    record(&mut detector,b"W",5);
+    let anomalies = detector.end_window();
+    assert_eq!(anomalies.len(),2);
+    assert_eq!(anomalies[0].msg_type,b"8");
+    assert_eq!(anomalies[0].kind,RateAnomalyKind::Storm);
+    assert_eq!(anomalies[0].count,500);
+    assert_eq!(anomalies[0].baseline,10.0);
+    assert_eq!(anomalies[1].msg_type,b"W");
+    assert_eq!(anomalies[1].kind,RateAnomalyKind::Drought);
+    assert_eq!(anomalies[1].baseline,100.0);
+
+    //An ongoing drought isn't reported again. Each MsgType reports when it's back to normal.
+    record(&mut detector,b"8",200);
+    let anomalies = detector.end_window();
+    assert_eq!(anomalies.len(),1);
+    assert_eq!(anomalies[0].msg_type,b"8");
+    assert_eq!(anomalies[0].kind,RateAnomalyKind::Normal);
+
+    record(&mut detector,b"8",200);
+    record(&mut detector,b"W",100);
+    let anomalies = detector.end_window();
+    assert_eq!(anomalies.len(),1);
+    assert_eq!(anomalies[0].msg_type,b"W");
+    assert_eq!(anomalies[0].kind,RateAnomalyKind::Normal);
+
+    //Clearing starts the baseline over.
+    detector.clear();
+    record(&mut detector,b"8",10000);
+    assert!(detector.end_window().is_empty());
+}
@@ -0,0 +1,146 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::new_order_single;
+use fix_rs::dictionary::field_types::other::{ExecType,OrdStatus,Side};
+use fix_rs::dictionary::fields::{CumQty,Text};
+use fix_rs::dictionary::messages::ExecutionReport;
+use fix_rs::field::Field;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::reconcile::{ReconcileBreak,Reconciler};
+use fix_rs::message_diff::FieldDiff;
+use fix_rs::message_hash::MessageHasher;
+
+fn execution_report(cl_ord_id: &[u8],exec_id: &[u8],cum_qty: &[u8],leaves_qty: &[u8]) -> ExecutionReport {
+    let mut message = new_fixt_message!(ExecutionReport);
+    message.order_id = b"VENUE1".to_vec();
+    message.cl_ord_id = cl_ord_id.to_vec();
+    message.exec_id = exec_id.to_vec();
+    message.exec_type = ExecType::Trade;
+    message.ord_status = OrdStatus::PartiallyFilled;
+    message.symbol = b"TEST".to_vec();
+    message.side = Side::Buy;
+    message.cum_qty = cum_qty.to_vec();
+    message.leaves_qty = leaves_qty.to_vec();
+
+    message
+}
+
+fn drop_copy_of(message: &ExecutionReport) -> ExecutionReport {
+    //Same execution as it would arrive on a drop-copy session.
+    let mut message = message.clone();
+    message.sender_comp_id = b"DROPCOPY".to_vec();
+    message.target_comp_id = b"RISK".to_vec();
+    message.msg_seq_num += 100;
+
+    message
+}
+
+#[test]
+fn test_reconcile_matches() {
+    let mut reconciler = Reconciler::new();
+    reconciler.on_trading_message_sent(&new_order_single(b"A"));
+
+    //Trading session first.
+    let fill = execution_report(b"A",b"E1",b"40",b"60");
+    assert!(reconciler.on_trading_message(&fill).is_none());
+    assert_eq!(reconciler.unmatched_count(),1);
+    assert!(reconciler.on_drop_copy_message(&drop_copy_of(&fill)).is_none());
+    assert_eq!(reconciler.matched_count(),1);
+    assert_eq!(reconciler.unmatched_count(),0);
+    assert_eq!(reconciler.orders().get(b"A").unwrap().cum_qty,b"40");
+
+    //Drop-copy session first.
+    let fill = execution_report(b"A",b"E2",b"100",b"0");
+    assert!(reconciler.on_drop_copy_message(&drop_copy_of(&fill)).is_none());
+    assert!(reconciler.on_trading_message(&fill).is_none());
+    assert_eq!(reconciler.matched_count(),2);
+
+    //Duplicates from either session are ignored.
+    let mut duplicate_fill = drop_copy_of(&fill);
+    duplicate_fill.poss_dup_flag = true;
+    assert!(reconciler.on_drop_copy_message(&duplicate_fill).is_none());
+    assert!(reconciler.on_trading_message(&fill).is_none());
+    assert_eq!(reconciler.matched_count(),2);
+    assert!(reconciler.finish().is_empty());
+}
+
+#[test]
+fn test_reconcile_mismatch() {
+    let mut reconciler = Reconciler::new();
+
+    let fill = execution_report(b"A",b"E1",b"40",b"60");
+    let mut drop_copy_fill = drop_copy_of(&fill);
+    drop_copy_fill.cum_qty = b"50".to_vec();
+    assert!(reconciler.on_trading_message(&fill).is_none());
+    assert_eq!(reconciler.on_drop_copy_message(&drop_copy_fill),Some(ReconcileBreak::Mismatch(b"E1".to_vec(),vec![FieldDiff {
+        groups: Vec::new(),
+        tag: CumQty::tag(),
+        name: "cum_qty",
+        left: Some(b"40".to_vec()),
+        right: Some(b"50".to_vec()),
+    }])));
+    assert_eq!(reconciler.matched_count(),0);
+    assert_eq!(reconciler.unmatched_count(),0);
+
+    //Fields excluded by the hasher are never a break.
+    let mut reconciler = Reconciler::new();
+    reconciler.set_hasher(MessageHasher::new().exclude_tag(Text::tag()));
+    let fill = execution_report(b"A",b"E1",b"40",b"60");
+    let mut drop_copy_fill = drop_copy_of(&fill);
+    drop_copy_fill.text = b"Drop copy".to_vec();
+    assert!(reconciler.on_trading_message(&fill).is_none());
+    match reconciler.on_drop_copy_message(&drop_copy_fill) {
+        Some(ReconcileBreak::Mismatch(exec_id,differences)) => {
+            //CompIDs are no longer excluded once the default hasher is replaced.
+            assert_eq!(exec_id,b"E1");
+            assert!(differences.iter().all(|difference| difference.tag != Text::tag()));
+            assert!(!differences.is_empty());
+        },
+        _ => panic!("Expected mismatch"),
+    }
+}
+
+#[test]
+fn test_reconcile_missing() {
+    let mut reconciler = Reconciler::new();
+
+    assert!(reconciler.on_trading_message(&execution_report(b"A",b"E1",b"40",b"60")).is_none());
+    assert!(reconciler.on_drop_copy_message(&drop_copy_of(&execution_report(b"B",b"E2",b"10",b"90"))).is_none());
+
+    //Nothing is reported until the executions are old enough.
+    assert!(reconciler.expire(Duration::from_secs(60)).is_empty());
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(reconciler.expire(Duration::from_millis(25)),vec![
+        ReconcileBreak::MissingFromDropCopy(b"E1".to_vec(),b"A".to_vec()),
+        ReconcileBreak::MissingFromTrading(b"E2".to_vec(),b"B".to_vec()),
+    ]);
+    assert_eq!(reconciler.unmatched_count(),0);
+
+    //A late copy after expiring is reported as missing again instead of being matched.
+    assert!(reconciler.on_drop_copy_message(&drop_copy_of(&execution_report(b"A",b"E1",b"40",b"60"))).is_none());
+    assert_eq!(reconciler.finish(),vec![ReconcileBreak::MissingFromTrading(b"E1".to_vec(),b"A".to_vec())]);
+}
@@ -0,0 +1,220 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::thread;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,MAX_MESSAGE_SIZE,TestStream,new_logon_message,round_trip};
+use fix_rs::dictionary::field_types::other::{ExecType,OrdStatus,SecurityIDSource,SecurityRequestResult,SecurityRequestType,SecurityResponseType,SecurityType,Side};
+use fix_rs::dictionary::fields::Instrument;
+use fix_rs::dictionary::messages::{ExecutionReport,Heartbeat,Logon,SecurityDefinition,SecurityDefinitionRequest,SecurityList};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::{ConnectionBuilder,EngineBuilder,EngineEvent};
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::reference_data::{InstrumentKey,ReferenceDataCache};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn security_definition(symbol: &[u8],security_id: &[u8],min_price_increment: &[u8]) -> SecurityDefinition {
+    let mut message = new_fixt_message!(SecurityDefinition);
+    message.security_req_id = b"SECDEF1".to_vec();
+    message.security_response_id = b"RESP1".to_vec();
+    message.security_response_type = Some(SecurityResponseType::AcceptSecurityProposalAsIs);
+    message.symbol = symbol.to_vec();
+    if !security_id.is_empty() {
+        message.security_id = security_id.to_vec();
+        message.security_id_source = Some(SecurityIDSource::ISINNumber);
+    }
+    message.security_type = Some(SecurityType::Future);
+    message.contract_multiplier = b"50".to_vec();
+    message.min_price_increment = min_price_increment.to_vec();
+
+    message
+}
+
+#[test]
+fn test_reference_data_messages() {
+    let mut message = new_fixt_message!(SecurityDefinitionRequest);
+    message.security_req_id = b"SECDEF1".to_vec();
+    message.security_request_type = SecurityRequestType::RequestSecurityIdentityForTheSpecificationsProvided;
+    message.symbol = b"ES".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(parsed_message.security_req_id,b"SECDEF1");
+    assert_eq!(parsed_message.security_request_type,SecurityRequestType::RequestSecurityIdentityForTheSpecificationsProvided);
+    assert_eq!(parsed_message.symbol,b"ES");
+
+    let message = security_definition(b"ES",b"US1234567890",b"0.25");
+    let parsed_message = round_trip(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(parsed_message.security_response_type,Some(SecurityResponseType::AcceptSecurityProposalAsIs));
+    assert_eq!(parsed_message.security_id,b"US1234567890");
+    assert_eq!(parsed_message.security_id_source,Some(SecurityIDSource::ISINNumber));
+    assert_eq!(parsed_message.security_type,Some(SecurityType::Future));
+    assert_eq!(parsed_message.contract_multiplier,b"50");
+    assert_eq!(parsed_message.min_price_increment,b"0.25");
+}
+
+#[test]
+fn test_reference_data_cache() {
+    let cache = ReferenceDataCache::new(Duration::from_secs(60));
+    assert!(cache.is_empty());
+
+    //SecurityDefinition adds a single instrument that can be found by Symbol or SecurityID.
+    assert_eq!(cache.on_message(&security_definition(b"ES",b"US1234567890",b"0.25")),1);
+    let reference = cache.get_by_symbol(b"ES").unwrap();
+    assert_eq!(reference.contract_multiplier,b"50");
+    assert_eq!(reference.min_price_increment,b"0.25");
+    assert_eq!(reference.security_id_source,Some(SecurityIDSource::ISINNumber));
+    assert_eq!(cache.get(&InstrumentKey::with_security_id(b"",b"US1234567890")).unwrap().symbol,b"ES");
+    assert!(cache.get(&InstrumentKey::with_security_id(b"ES",b"UNKNOWN")).is_none());
+    assert!(cache.get_by_symbol(b"NQ").is_none());
+
+    //Clones share the same entries so other components see what was fetched.
+    let shared_cache = cache.clone();
+    let handle = thread::spawn(move || {
+        shared_cache.get_by_symbol(b"ES").map(|reference| reference.min_price_increment.clone())
+    });
+    assert_eq!(handle.join().unwrap(),Some(b"0.25".to_vec()));
+
+    //A newer definition replaces the old one.
+    assert_eq!(cache.on_message(&security_definition(b"ES",b"US1234567890",b"0.5")),1);
+    assert_eq!(cache.len(),1);
+    assert_eq!(cache.get_by_symbol(b"ES").unwrap().min_price_increment,b"0.5");
+
+    //SecurityList adds every instrument in the list.
+    let mut message = new_fixt_message!(SecurityList);
+    message.security_response_id = b"RESP2".to_vec();
+    message.security_request_result = Some(SecurityRequestResult::ValidRequest);
+    for &(symbol,min_price_increment) in &[(&b"NQ"[..],&b"0.25"[..]),(&b"YM"[..],&b"1"[..])] {
+        let mut instrument = Instrument::new();
+        instrument.symbol = symbol.to_vec();
+        instrument.min_price_increment = min_price_increment.to_vec();
+        message.no_related_sym.push(Box::new(instrument));
+    }
+    assert_eq!(cache.on_message(&message),2);
+    assert_eq!(cache.len(),3);
+    assert_eq!(cache.get_by_symbol(b"YM").unwrap().min_price_increment,b"1");
+
+    //Failed requests and unrelated messages are ignored.
+    message.security_request_result = Some(SecurityRequestResult::InstrumentDataTemporarilyUnavailable);
+    message.no_related_sym[0].symbol = b"RTY".to_vec();
+    assert_eq!(cache.on_message(&message),0);
+    assert!(!cache.is_cached(&InstrumentKey::new(b"RTY")));
+    assert_eq!(cache.on_message(&new_fixt_message!(Heartbeat)),0);
+
+    //Removing by SecurityID removes the instrument completely.
+    assert!(cache.remove(&InstrumentKey::with_security_id(b"",b"US1234567890")));
+    assert!(cache.get_by_symbol(b"ES").is_none());
+    assert!(!cache.remove(&InstrumentKey::new(b"ES")));
+    assert_eq!(cache.len(),2);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_reference_data_cache_expiry() {
+    let cache = ReferenceDataCache::new(Duration::from_millis(50));
+    cache.on_message(&security_definition(b"ES",b"US1234567890",b"0.25"));
+    cache.on_message(&security_definition(b"NQ",b"",b"0.25"));
+    assert!(cache.is_cached(&InstrumentKey::new(b"ES")));
+    assert_eq!(cache.len(),2);
+
+    //Expired entries are never returned.
+    thread::sleep(Duration::from_millis(100));
+    assert!(cache.get_by_symbol(b"ES").is_none());
+    assert!(cache.get(&InstrumentKey::with_security_id(b"ES",b"US1234567890")).is_none());
+    assert_eq!(cache.len(),1);
+    assert_eq!(cache.remove_expired(),1);
+    assert!(cache.is_empty());
+
+    //Refreshing an entry restarts its expiry.
+    cache.on_message(&security_definition(b"ES",b"US1234567890",b"0.25"));
+    thread::sleep(Duration::from_millis(30));
+    cache.on_message(&security_definition(b"ES",b"US1234567890",b"0.25"));
+    thread::sleep(Duration::from_millis(30));
+    assert!(cache.is_cached(&InstrumentKey::new(b"ES")));
+}
+
+#[test]
+fn test_engine_attaches_instrument() {
+    define_dictionary!(
+        ExecutionReport,
+        Logon,
+        SecurityDefinition,
+    );
+
+    //Connect and logon.
+    let cache = ReferenceDataCache::new(Duration::from_secs(60));
+    let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE).reference_data_cache(cache.clone());
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+    let (mut test_server,mut client,connection) = TestStream::setup_test_server_with_builders(engine_builder,connection_builder,build_dictionary());
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    let _ = test_server.recv_message::<Logon>();
+    test_server.send_message(new_logon_message());
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+
+    let execution_report = |msg_seq_num: u64,symbol: &[u8]| {
+        let mut message = new_fixt_message!(ExecutionReport);
+        message.msg_seq_num = msg_seq_num;
+        message.order_id = b"VENUE1".to_vec();
+        message.exec_id = b"E1".to_vec();
+        message.exec_type = ExecType::New;
+        message.ord_status = OrdStatus::New;
+        message.symbol = symbol.to_vec();
+        message.side = Side::Buy;
+        message.cum_qty = b"0".to_vec();
+        message.leaves_qty = b"10".to_vec();
+
+        message
+    };
+
+    //Messages for instruments that aren't cached are delivered as usual.
+    test_server.send_message(execution_report(2,b"ES"));
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    assert_eq!(message.symbol,b"ES");
+
+    //Reference data is cached as it's received.
+    let mut message = security_definition(b"ES",b"US1234567890",b"0.25");
+    message.msg_seq_num = 3;
+    test_server.send_message(message);
+    let message = engine_poll_message!(client,connection,SecurityDefinition);
+    assert_eq!(message.symbol,b"ES");
+    assert!(cache.is_cached(&InstrumentKey::new(b"ES")));
+
+    //Later messages for the same instrument carry the cached reference data.
+    test_server.send_message(execution_report(4,b"ES"));
+    engine_poll_event!(client,EngineEvent::MessageReceivedWithInstrument(data) => {
+        assert_eq!(data.connection,connection);
+        assert_eq!(data.message.as_any().downcast_ref::<ExecutionReport>().unwrap().symbol,b"ES");
+        assert_eq!(data.instrument.min_price_increment,b"0.25");
+        assert_eq!(data.instrument.contract_multiplier,b"50");
+    });
+
+    //Other instruments are still delivered as usual.
+    test_server.send_message(execution_report(5,b"NQ"));
+    let message = engine_poll_message!(client,connection,ExecutionReport);
+    assert_eq!(message.symbol,b"NQ");
+}
@@ -0,0 +1,78 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use chrono::offset::utc::UTC;
+use std::time::Duration;
+
+#[macro_use]
+mod common;
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,MAX_MESSAGE_SIZE,new_logon_message};
+use fix_rs::clock::SimulatedClock;
+use fix_rs::dictionary::messages::{Heartbeat,Logon,TestRequest};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::{ConnectionBuilder,EngineBuilder,EngineEvent};
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::replay::{ReplaySpeed,SessionReplay};
+use fix_rs::message_version::MessageVersion;
+
+define_dictionary!(
+    Heartbeat,
+    Logon,
+    TestRequest,
+);
+
+#[test]
+fn test_replay_drives_engine() {
+    //Record a Logon response followed by a Heartbeat a second later and a TestRequest a second
+    //after that.
+    let start = UTC::now();
+    let mut replay = SessionReplay::new();
+    replay.add_message(start,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&new_logon_message());
+    let mut message = new_fixt_message!(Heartbeat);
+    message.msg_seq_num = 2;
+    replay.add_message(start + chrono::Duration::seconds(1),FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    let mut message = new_fixt_message!(TestRequest);
+    message.msg_seq_num = 3;
+    message.test_req_id = b"test_id".to_vec();
+    replay.add_message(start + chrono::Duration::seconds(2),FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,&message);
+    assert_eq!(replay.len(),3);
+
+    //Replay as fast as possible while the engine's clock follows the recording.
+    let clock = SimulatedClock::new();
+    let handle = replay.speed(ReplaySpeed::Unpaced).clock(clock.clone()).start("127.0.0.1:0".parse().unwrap()).unwrap();
+    let mut client = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE).clock(clock.clone()).build().unwrap();
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID);
+    let connection = client.add_connection_with_builder(connection_builder,handle.local_addr()).unwrap();
+    engine_poll_event!(client,EngineEvent::ConnectionSucceeded(_) => {});
+
+    //The recorded messages are handled exactly like they would be from a live counterparty.
+    client.send_message_box_with_message_version(connection,MessageVersion::FIX50SP2,Box::new(new_logon_message()));
+    engine_poll_event!(client,EngineEvent::SessionEstablished(_) => {});
+    let _ = engine_poll_message!(client,connection,Logon);
+    let message = engine_poll_message!(client,connection,Heartbeat);
+    assert_eq!(message.msg_seq_num,2);
+    let message = engine_poll_message!(client,connection,TestRequest);
+    assert_eq!(message.test_req_id,b"test_id");
+
+    assert_eq!(handle.join().unwrap(),3);
+    assert_eq!(clock.elapsed(),Duration::from_secs(2));
+}
@@ -0,0 +1,110 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::round_trip;
+use fix_rs::dictionary::field_types::other::{PartyIDSource,PartyRole,RiskLimitRequestResult,RiskLimitRequestStatus,RiskLimitRequestType,RiskLimitType};
+use fix_rs::dictionary::fields::{PartyDetailGrp,PartyRiskLimitsGrp,RiskLimitTypesGrp,RiskLimitsGrp};
+use fix_rs::dictionary::messages::{PartyRiskLimitsReport,PartyRiskLimitsRequest,PartyRiskLimitsUpdateReport};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message_version::MessageVersion;
+
+fn party_detail(party_detail_id: &[u8]) -> PartyDetailGrp {
+    let mut group = PartyDetailGrp::new();
+    group.party_detail_id = party_detail_id.to_vec();
+    group.party_detail_id_source = PartyIDSource::ProprietaryOrCustomCode;
+    group.party_detail_role = Some(PartyRole::ExecutingFirm);
+
+    group
+}
+
+fn risk_limit_type(risk_limit_type: RiskLimitType,risk_limit_amount: &[u8],risk_limit_utilization_amount: &[u8]) -> RiskLimitTypesGrp {
+    let mut group = RiskLimitTypesGrp::new();
+    group.risk_limit_type = risk_limit_type;
+    group.risk_limit_amount = risk_limit_amount.to_vec();
+    group.risk_limit_currency = b"USD".to_vec();
+    group.risk_limit_utilization_amount = risk_limit_utilization_amount.to_vec();
+
+    group
+}
+
+#[test]
+fn test_party_risk_limits_request() {
+    let mut message = new_fixt_message!(PartyRiskLimitsRequest);
+    message.risk_limit_request_id = b"RLR1".to_vec();
+    message.risk_limit_request_type = Some(RiskLimitRequestType::DefinitionsAndUtilization);
+    message.no_party_details.push(Box::new(party_detail(b"FIRM1")));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(parsed_message.risk_limit_request_id,b"RLR1");
+    assert_eq!(parsed_message.risk_limit_request_type,Some(RiskLimitRequestType::DefinitionsAndUtilization));
+    assert_eq!(parsed_message.no_party_details.len(),1);
+    assert_eq!(parsed_message.no_party_details[0].party_detail_id,b"FIRM1");
+    assert_eq!(parsed_message.no_party_details[0].party_detail_id_source,PartyIDSource::ProprietaryOrCustomCode);
+    assert_eq!(parsed_message.no_party_details[0].party_detail_role,Some(PartyRole::ExecutingFirm));
+}
+
+#[test]
+fn test_party_risk_limits_report() {
+    let mut risk_limits = RiskLimitsGrp::new();
+    risk_limits.no_risk_limit_types.push(Box::new(risk_limit_type(RiskLimitType::GrossLimit,b"1000000",b"250000")));
+    risk_limits.no_risk_limit_types.push(Box::new(risk_limit_type(RiskLimitType::MaxNotionalOrderSize,b"50000",b"")));
+    risk_limits.risk_limit_id = b"LIMIT1".to_vec();
+    let mut party_risk_limits = PartyRiskLimitsGrp::new();
+    party_risk_limits.no_party_details.push(Box::new(party_detail(b"FIRM1")));
+    party_risk_limits.no_risk_limits.push(Box::new(risk_limits));
+
+    let mut message = new_fixt_message!(PartyRiskLimitsReport);
+    message.risk_limit_report_id = b"RPT1".to_vec();
+    message.risk_limit_request_id = b"RLR1".to_vec();
+    message.risk_limit_request_result = Some(RiskLimitRequestResult::Successful);
+    message.risk_limit_request_status = Some(RiskLimitRequestStatus::Accepted);
+    message.no_party_risk_limits.push(Box::new(party_risk_limits.clone()));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(parsed_message.risk_limit_report_id,b"RPT1");
+    assert_eq!(parsed_message.risk_limit_request_result,Some(RiskLimitRequestResult::Successful));
+    assert_eq!(parsed_message.risk_limit_request_status,Some(RiskLimitRequestStatus::Accepted));
+    assert_eq!(parsed_message.no_party_risk_limits.len(),1);
+    let parsed_party_risk_limits = &parsed_message.no_party_risk_limits[0];
+    assert_eq!(parsed_party_risk_limits.no_party_details[0].party_detail_id,b"FIRM1");
+    assert_eq!(parsed_party_risk_limits.no_risk_limits.len(),1);
+    assert_eq!(parsed_party_risk_limits.no_risk_limits[0].risk_limit_id,b"LIMIT1");
+    let parsed_risk_limit_types = &parsed_party_risk_limits.no_risk_limits[0].no_risk_limit_types;
+    assert_eq!(parsed_risk_limit_types.len(),2);
+    assert_eq!(parsed_risk_limit_types[0].risk_limit_type,RiskLimitType::GrossLimit);
+    assert_eq!(parsed_risk_limit_types[0].risk_limit_amount,b"1000000");
+    assert_eq!(parsed_risk_limit_types[0].risk_limit_utilization_amount,b"250000");
+    assert_eq!(parsed_risk_limit_types[1].risk_limit_type,RiskLimitType::MaxNotionalOrderSize);
+    assert!(parsed_risk_limit_types[1].risk_limit_utilization_amount.is_empty());
+
+    //Unsolicited updates use the same repeating groups.
+    let mut message = new_fixt_message!(PartyRiskLimitsUpdateReport);
+    message.risk_limit_report_id = b"RPT2".to_vec();
+    message.no_party_risk_limits.push(Box::new(party_risk_limits));
+
+    let parsed_message = round_trip(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(parsed_message.risk_limit_report_id,b"RPT2");
+    assert_eq!(parsed_message.no_party_risk_limits[0].no_risk_limits[0].no_risk_limit_types.len(),2);
+}
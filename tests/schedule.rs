@@ -0,0 +1,109 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate chrono;
+#[cfg(feature = "timezones")]
+extern crate chrono_tz;
+extern crate fix_rs;
+
+use chrono::{NaiveDate,NaiveTime,TimeZone};
+use chrono::naive::datetime::NaiveDateTime;
+use chrono::offset::utc::UTC;
+
+use fix_rs::fixt::schedule::{FixedTimeZone,SessionSchedule,SessionTimeZone};
+
+//US Eastern time for 2017 only so the tests don't depend on a time zone database.
+#[derive(Clone)]
+struct TestEasternTimeZone;
+
+impl SessionTimeZone for TestEasternTimeZone {
+    fn utc_offset_at(&self,utc: &NaiveDateTime) -> i32 {
+        let dst_begin = NaiveDate::from_ymd(2017,3,12).and_hms(7,0,0); //02:00 EST
+        let dst_end = NaiveDate::from_ymd(2017,11,5).and_hms(6,0,0); //02:00 EDT
+
+        if *utc >= dst_begin && *utc < dst_end {
+            -4 * 60 * 60
+        }
+        else {
+            -5 * 60 * 60
+        }
+    }
+}
+
+fn check_spring_forward<T: SessionTimeZone + Send + 'static>(time_zone: T) {
+    //Session opens at 02:30 which doesn't exist on 2017-03-12.
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(2,30,0),NaiveTime::from_hms(16,0,0),time_zone);
+
+    //Day before uses EST.
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,3,11).and_hms(12,0,0)),Some((UTC.ymd(2017,3,11).and_hms(7,30,0),UTC.ymd(2017,3,11).and_hms(21,0,0))));
+
+    //Start is pushed forward to 03:30 EDT and the end uses EDT.
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,3,12).and_hms(12,0,0)),Some((UTC.ymd(2017,3,12).and_hms(7,30,0),UTC.ymd(2017,3,12).and_hms(20,0,0))));
+    assert!(!schedule.is_active(&UTC.ymd(2017,3,12).and_hms(7,29,59)));
+    assert!(schedule.is_active(&UTC.ymd(2017,3,12).and_hms(7,30,0)));
+    assert!(!schedule.is_active(&UTC.ymd(2017,3,12).and_hms(20,0,0)));
+
+    //Day after uses EDT for both.
+    assert_eq!(schedule.next_start(&UTC.ymd(2017,3,12).and_hms(12,0,0)),UTC.ymd(2017,3,13).and_hms(6,30,0));
+}
+
+fn check_fall_back<T: SessionTimeZone + Clone + Send + 'static>(time_zone: T) {
+    //Session opens at 01:30 which happens twice on 2017-11-05. The first occurrence (EDT) is used.
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(1,30,0),NaiveTime::from_hms(16,0,0),time_zone.clone());
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,11,5).and_hms(12,0,0)),Some((UTC.ymd(2017,11,5).and_hms(5,30,0),UTC.ymd(2017,11,5).and_hms(21,0,0))));
+    assert!(schedule.is_active(&UTC.ymd(2017,11,5).and_hms(6,30,0))); //Second 01:30.
+
+    //Overnight session that spans the change is an hour longer than usual.
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(17,0,0),NaiveTime::from_hms(16,0,0),time_zone);
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,11,5).and_hms(12,0,0)),Some((UTC.ymd(2017,11,4).and_hms(21,0,0),UTC.ymd(2017,11,5).and_hms(21,0,0))));
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,11,6).and_hms(12,0,0)),Some((UTC.ymd(2017,11,5).and_hms(22,0,0),UTC.ymd(2017,11,6).and_hms(21,0,0))));
+
+    //Closed between 16:00 and 17:00 local.
+    assert!(!schedule.is_active(&UTC.ymd(2017,11,5).and_hms(21,30,0)));
+    assert_eq!(schedule.next_start(&UTC.ymd(2017,11,5).and_hms(21,30,0)),UTC.ymd(2017,11,5).and_hms(22,0,0));
+}
+
+#[test]
+fn test_fixed_time_zone_schedule() {
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(8,0,0),NaiveTime::from_hms(17,0,0),FixedTimeZone::utc());
+    assert!(!schedule.is_active(&UTC.ymd(2017,6,1).and_hms(7,59,59)));
+    assert!(schedule.is_active(&UTC.ymd(2017,6,1).and_hms(8,0,0)));
+    assert!(schedule.is_active(&UTC.ymd(2017,6,1).and_hms(16,59,59)));
+    assert!(!schedule.is_active(&UTC.ymd(2017,6,1).and_hms(17,0,0)));
+    assert_eq!(schedule.next_start(&UTC.ymd(2017,6,1).and_hms(12,0,0)),UTC.ymd(2017,6,2).and_hms(8,0,0));
+
+    //Overnight session in a fixed UTC+9 time zone.
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(20,0,0),NaiveTime::from_hms(6,0,0),FixedTimeZone { utc_offset: 9 * 60 * 60 });
+    assert_eq!(schedule.session_window(&UTC.ymd(2017,6,1).and_hms(18,0,0)),Some((UTC.ymd(2017,6,1).and_hms(11,0,0),UTC.ymd(2017,6,1).and_hms(21,0,0))));
+    assert!(!schedule.is_active(&UTC.ymd(2017,6,1).and_hms(22,0,0)));
+
+    //A leap second still belongs to the session it happens in.
+    let leap_second = UTC.ymd(2016,12,31).and_hms_milli(23,59,59,1_500);
+    let schedule = SessionSchedule::new(NaiveTime::from_hms(17,0,0),NaiveTime::from_hms(1,0,0),FixedTimeZone::utc());
+    assert_eq!(schedule.session_window(&leap_second),Some((UTC.ymd(2016,12,31).and_hms(17,0,0),UTC.ymd(2017,1,1).and_hms(1,0,0))));
+}
+
+#[test]
+fn test_spring_forward_schedule() {
+    check_spring_forward(TestEasternTimeZone);
+}
+
+#[test]
+fn test_fall_back_schedule() {
+    check_fall_back(TestEasternTimeZone);
+}
+
+#[cfg(feature = "timezones")]
+#[test]
+fn test_chrono_tz_schedule() {
+    check_spring_forward(chrono_tz::America::New_York);
+    check_fall_back(chrono_tz::America::New_York);
+}
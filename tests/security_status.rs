@@ -0,0 +1,147 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+#[macro_use]
+mod common;
+use common::round_trip;
+use fix_rs::dictionary::field_types::other::{HaltReason,SecurityTradingStatus,SubscriptionRequestType};
+use fix_rs::dictionary::messages::{Heartbeat,SecurityStatus,SecurityStatusRequest};
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::Connection;
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::middleware::{InboundMiddleware,MiddlewareAction};
+use fix_rs::fixt::security_status::{TradingHaltEvent,TradingHaltMonitor};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn security_status(symbol: &[u8],security_trading_status: SecurityTradingStatus) -> SecurityStatus {
+    let mut message = new_fixt_message!(SecurityStatus);
+    message.symbol = symbol.to_vec();
+    message.security_trading_status = Some(security_trading_status);
+
+    message
+}
+
+#[test]
+fn test_security_status_messages() {
+    let mut message = new_fixt_message!(SecurityStatusRequest);
+    message.security_status_req_id = b"SSR1".to_vec();
+    message.symbol = b"TEST".to_vec();
+    message.subscription_request_type = SubscriptionRequestType::SnapshotPlusUpdates;
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.security_status_req_id,b"SSR1");
+    assert_eq!(parsed_message.symbol,b"TEST");
+    assert_eq!(parsed_message.subscription_request_type,SubscriptionRequestType::SnapshotPlusUpdates);
+
+    let mut message = security_status(b"TEST",SecurityTradingStatus::TradingHalt);
+    message.security_status_req_id = b"SSR1".to_vec();
+    message.halt_reason = Some(HaltReason::NewsPending);
+    message.unsolicited_indicator = true;
+    message.high_px = b"10.5".to_vec();
+    message.low_px = b"9.75".to_vec();
+
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.security_status_req_id,b"SSR1");
+    assert_eq!(parsed_message.security_trading_status,Some(SecurityTradingStatus::TradingHalt));
+    assert_eq!(parsed_message.halt_reason,Some(HaltReason::NewsPending));
+    assert!(parsed_message.unsolicited_indicator);
+    assert_eq!(parsed_message.high_px,b"10.5");
+    assert_eq!(parsed_message.low_px,b"9.75");
+
+    //Two character SecurityTradingStatus values survive the round trip.
+    let message = security_status(b"TEST",SecurityTradingStatus::ReadyToTrade);
+    let parsed_message = round_trip(&message,FIXVersion::FIX_4_4,MessageVersion::FIX44);
+    assert_eq!(parsed_message.security_trading_status,Some(SecurityTradingStatus::ReadyToTrade));
+}
+
+#[test]
+fn test_trading_halt_monitor() {
+    let mut monitor = TradingHaltMonitor::new();
+    let events = monitor.events();
+    let connection = Connection { id: 3, generation: 0 };
+    let other_connection = Connection { id: 4, generation: 0 };
+
+    //A halt is reported with its reason.
+    let mut message = security_status(b"TEST",SecurityTradingStatus::TradingHalt);
+    message.halt_reason = Some(HaltReason::OrderImbalance);
+    let event = monitor.on_security_status(connection,&message).unwrap();
+    assert_eq!(event,TradingHaltEvent::Halted {
+        connection: connection,
+        symbol: b"TEST".to_vec(),
+        halt_reason: Some(HaltReason::OrderImbalance),
+        text: Vec::new(),
+    });
+    assert_eq!(events.try_recv().unwrap(),event);
+    assert!(monitor.is_halted(connection,b"TEST"));
+    assert!(!monitor.is_halted(other_connection,b"TEST"));
+    assert!(!monitor.is_halted(connection,b"OTHER"));
+
+    //Repeated halts and unrelated statuses don't change anything.
+    assert!(monitor.on_security_status(connection,&message).is_none());
+    assert!(monitor.on_security_status(connection,&security_status(b"TEST",SecurityTradingStatus::PriceIndication)).is_none());
+    assert!(events.try_recv().is_err());
+    assert!(monitor.is_halted(connection,b"TEST"));
+
+    //Resuming trading is reported once.
+    let event = monitor.on_security_status(connection,&security_status(b"TEST",SecurityTradingStatus::Resume)).unwrap();
+    assert_eq!(event,TradingHaltEvent::Resumed {
+        connection: connection,
+        symbol: b"TEST".to_vec(),
+        security_trading_status: SecurityTradingStatus::Resume,
+    });
+    assert_eq!(events.try_recv().unwrap(),event);
+    assert!(!monitor.is_halted(connection,b"TEST"));
+    assert!(monitor.on_security_status(connection,&security_status(b"TEST",SecurityTradingStatus::ReadyToTrade)).is_none());
+
+    //Securities that were never halted don't produce a resume event.
+    assert!(monitor.on_security_status(connection,&security_status(b"OTHER",SecurityTradingStatus::ReadyToTrade)).is_none());
+
+    //Halt state is forgotten when the connection is cleared.
+    monitor.on_security_status(connection,&security_status(b"TEST",SecurityTradingStatus::TradingHalt)).unwrap();
+    monitor.on_security_status(other_connection,&security_status(b"TEST",SecurityTradingStatus::TradingHalt)).unwrap();
+    monitor.clear_connection(connection);
+    assert!(!monitor.is_halted(connection,b"TEST"));
+    assert!(monitor.is_halted(other_connection,b"TEST"));
+}
+
+#[test]
+fn test_trading_halt_monitor_middleware() {
+    let mut monitor = TradingHaltMonitor::new();
+    let events = monitor.events();
+    let connection = Connection { id: 3, generation: 0 };
+
+    //Every message is passed through while SecurityStatus messages update the halt state.
+    let action = monitor.on_message(connection,Box::new(security_status(b"TEST",SecurityTradingStatus::TradingHalt)));
+    assert!(if let MiddlewareAction::Continue(message) = action { message.as_any().is::<SecurityStatus>() } else { false });
+    let action = monitor.on_message(connection,Box::new(new_fixt_message!(Heartbeat)));
+    assert!(if let MiddlewareAction::Continue(message) = action { message.as_any().is::<Heartbeat>() } else { false });
+
+    assert!(monitor.is_halted(connection,b"TEST"));
+    assert!(if let Ok(TradingHaltEvent::Halted { .. }) = events.try_recv() { true } else { false });
+    assert!(events.try_recv().is_err());
+
+    //Dropped receivers are forgotten.
+    drop(events);
+    monitor.on_message(connection,Box::new(security_status(b"TEST",SecurityTradingStatus::Resume)));
+    assert!(!monitor.is_halted(connection,b"TEST"));
+}
@@ -0,0 +1,115 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::collections::HashMap;
+
+#[macro_use]
+mod common;
+use common::{MAX_MESSAGE_SIZE,new_logon_message,new_order_single,serialize_message};
+use fix_rs::dictionary::field_types::other::MsgDirection;
+use fix_rs::dictionary::fields::MsgTypeGrp;
+use fix_rs::dictionary::messages::Logon;
+use fix_rs::fix::Parser;
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::message::{BuildFIXTMessage,FIXTMessage,FIXTMessageBuildable};
+use fix_rs::message::Message;
+use fix_rs::message_version::MessageVersion;
+
+fn position(bytes: &[u8],needle: &[u8]) -> Option<usize> {
+    bytes.windows(needle.len()).position(|window| window == needle)
+}
+
+fn find(bytes: &[u8],needle: &[u8]) -> usize {
+    position(bytes,needle).unwrap()
+}
+
+fn msg_type_grp(ref_msg_type: &[u8],msg_direction: MsgDirection) -> Box<MsgTypeGrp> {
+    let mut group = MsgTypeGrp::new();
+    group.ref_msg_type = ref_msg_type.to_vec();
+    group.msg_direction = msg_direction;
+
+    Box::new(group)
+}
+
+#[test]
+fn test_serialization_is_repeatable() {
+    let mut message = new_logon_message();
+    message.no_msg_types.push(msg_type_grp(b"D",MsgDirection::Send));
+    message.no_msg_types.push(msg_type_grp(b"8",MsgDirection::Receive));
+    message.no_msg_types.push(msg_type_grp(b"F",MsgDirection::Send));
+
+    //The same message, or a copy of it, always serializes to exactly the same bytes.
+    let bytes = serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert_eq!(serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2),bytes);
+    assert_eq!(serialize_message(&message.clone(),FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2),bytes);
+
+    //Parsing the bytes and serializing the result again doesn't change them either.
+    let mut message_dictionary: HashMap<&'static [u8],Box<BuildFIXTMessage + Send>> = HashMap::new();
+    message_dictionary.insert(Logon::msg_type(),Logon::new().builder());
+    let mut parser = Parser::new(message_dictionary,MAX_MESSAGE_SIZE);
+    parser.set_default_message_version(MessageVersion::FIX50SP2);
+    let (_,result) = parser.parse(&bytes[..]);
+    assert!(result.is_ok());
+    let parsed_message = parser.messages[0].as_any().downcast_ref::<Logon>().unwrap();
+    assert_eq!(serialize_message(parsed_message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2),bytes);
+}
+
+#[test]
+fn test_serialization_follows_field_definition_order() {
+    //Optional fields are always written in the order they are defined by the message, not the
+    //order they were set in.
+    let mut message = new_order_single(b"1");
+    message.price = b"9.5".to_vec();
+    message.account = b"ACCOUNT".to_vec();
+    message.text = b"text".to_vec();
+
+    let mut other_message = new_order_single(b"1");
+    other_message.text = b"text".to_vec();
+    other_message.account = b"ACCOUNT".to_vec();
+    other_message.price = b"9.5".to_vec();
+    assert_eq!(serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2),serialize_message(&other_message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2));
+
+    let bytes = serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert!(find(&bytes,b"\x011=ACCOUNT\x01") < find(&bytes,b"\x0144=9.5\x01"));
+    assert!(find(&bytes,b"\x0144=9.5\x01") < find(&bytes,b"\x0158=text\x01"));
+
+    //Fields left empty are never written.
+    let mut message = new_order_single(b"1");
+    message.price.clear();
+    let bytes = serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert!(position(&bytes,b"\x0144=").is_none());
+}
+
+#[test]
+fn test_serialization_keeps_repeating_group_order() {
+    //Repeating groups are written in the order they are stored in.
+    let mut message = new_logon_message();
+    message.no_msg_types.push(msg_type_grp(b"D",MsgDirection::Send));
+    message.no_msg_types.push(msg_type_grp(b"F",MsgDirection::Send));
+    let mut reversed_message = new_logon_message();
+    reversed_message.no_msg_types.push(msg_type_grp(b"F",MsgDirection::Send));
+    reversed_message.no_msg_types.push(msg_type_grp(b"D",MsgDirection::Send));
+
+    let bytes = serialize_message(&message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2);
+    assert!(bytes != serialize_message(&reversed_message,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2));
+    assert!(find(&bytes,b"\x01372=D\x01") < find(&bytes,b"\x01372=F\x01"));
+}
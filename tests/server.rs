@@ -20,12 +20,13 @@ extern crate mio;
 
 use mio::tcp::Shutdown;
 use std::io::Write;
+use std::net::{self,IpAddr,Ipv4Addr};
 use std::thread;
 use std::time::Duration;
 
 #[macro_use]
 mod common;
-use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,TestStream,new_logon_message};
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,MAX_MESSAGE_SIZE,SERVER_SENDER_COMP_ID,TestStream,new_logon_message,new_test_address};
 use fix_rs::dictionary::field_types::other::{MsgDirection,SessionRejectReason};
 use fix_rs::dictionary::fields::{MsgTypeGrp,Text};
 use fix_rs::dictionary::messages::{Heartbeat,Logon,Logout,Reject,TestRequest};
@@ -34,8 +35,9 @@ use fix_rs::field_tag::{self,FieldTag};
 use fix_rs::fix::ParseError;
 use fix_rs::fix_version::FIXVersion;
 use fix_rs::fixt;
-use fix_rs::fixt::engine::{EngineEvent,ConnectionTerminatedReason};
+use fix_rs::fixt::engine::{ConnectionAttemptLimit,ConnectionRefusedData,ConnectionRefusedReason,Engine,EngineBuilder,EngineEvent,ConnectionLoggingOnData,ConnectionTerminatedReason,HeartBtIntRange,ListenerSettings};
 use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::session_provisioner::{LogonRequest,ProvisioningDecision,SessionProvisioner,SessionSettings};
 use fix_rs::fixt::tests::{AUTO_DISCONNECT_AFTER_NO_LOGON_RECEIVED_SECONDS};
 use fix_rs::message::{self,REQUIRED};
 use fix_rs::message_version::{self,MessageVersion};
@@ -83,7 +85,7 @@ fn test_logon_all_fix_versions() {
         logon_message.default_appl_ver_id = fix_version.max_message_version();
         test_client.send_message_with_ver(fix_version,logon_message.default_appl_ver_id,logon_message);
 
-        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,logon_message) => {
+        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
             assert_eq!(some_listener,listener);
             assert_eq!(some_connection,connection);
             assert_eq!(logon_message.msg_seq_num,1);
@@ -111,6 +113,48 @@ fn test_approve_already_approved_connection_does_nothing() {
     assert!(test_client.try_recv_fixt_message(Duration::from_secs(1)).is_none());
 }
 
+#[test]
+fn test_duplicate_session_rejected() {
+    //Only one connection may be logged on with the same SenderCompID and TargetCompID at a time.
+    //Approving a second one sends a Logout instead.
+
+    define_dictionary!(
+        Logon,
+        Logout,
+    );
+
+    let (mut test_clients,mut engine,listener,connections) = TestStream::setup_test_clients_with_ver(2,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,build_dictionary());
+    for (test_client,connection) in test_clients.iter_mut().zip(connections.iter()) {
+        let mut logon_message = new_logon_message();
+        logon_message.sender_comp_id = CLIENT_SENDER_COMP_ID.to_vec();
+        logon_message.target_comp_id = CLIENT_TARGET_COMP_ID.to_vec();
+        test_client.send_message(logon_message);
+
+        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
+            assert_eq!(some_listener,listener);
+            assert_eq!(some_connection,*connection);
+
+            let mut response_message = new_fixt_message!(Logon);
+            response_message.encrypt_method = logon_message.encrypt_method.clone();
+            response_message.heart_bt_int = logon_message.heart_bt_int;
+            response_message.default_appl_ver_id = logon_message.default_appl_ver_id;
+            engine.approve_new_connection(*connection,Box::new(response_message),None);
+        });
+    }
+
+    let _ = test_clients[0].recv_message::<Logon>();
+    let message = test_clients[1].recv_message::<Logout>();
+    assert_eq!(message.text,b"Session is already logged on");
+    engine_poll_event!(engine,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+        assert_eq!(terminated_connection,connections[1]);
+        assert!(if let ConnectionTerminatedReason::LogonDuplicateSessionError = reason { true } else { false });
+    });
+    assert!(test_clients[1].is_stream_closed(Duration::from_secs(5)));
+
+    //The original session is unaffected.
+    assert!(!test_clients[0].is_stream_closed(Duration::from_millis(100)));
+}
+
 #[test]
 fn test_default_appl_ver_id() {
     define_fixt_message!(TestMessage: b"9999" => {
@@ -268,7 +312,7 @@ fn test_message_type_default_application_version() {
 
     test_client.send_message_with_ver(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,logon_message);
 
-    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,logon_message) => {
+    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
         assert_eq!(some_listener,listener);
         assert_eq!(some_connection,connection);
 
@@ -329,7 +373,7 @@ fn test_block_read_while_approving_logon() {
     logon_message.target_comp_id = CLIENT_TARGET_COMP_ID.to_vec();
     test_client.send_message(logon_message.clone());
 
-    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,_) => {
+    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: _, address: _ }) => {
         assert_eq!(some_listener,listener);
         assert_eq!(some_connection,connection);
     });
@@ -369,8 +413,8 @@ fn test_auto_disconnect_after_no_logon() {
 
     let (test_client,mut engine,_,connection) = TestStream::setup_test_client(build_dictionary());
 
-    //Wait for auto-disconnect.
-    thread::sleep(Duration::from_secs(AUTO_DISCONNECT_AFTER_NO_LOGON_RECEIVED_SECONDS));
+    //Advance time until auto-disconnect.
+    test_client.advance_clock(Duration::from_secs(AUTO_DISCONNECT_AFTER_NO_LOGON_RECEIVED_SECONDS));
 
     //Confirm connection was terminated.
     engine_poll_event!(engine,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
@@ -414,7 +458,7 @@ fn test_connection_terminated_while_approving_logon() {
     logon_message.target_comp_id = CLIENT_TARGET_COMP_ID.to_vec();
     test_client.send_message(logon_message.clone());
 
-    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,_) => {
+    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: _, address: _ }) => {
         assert_eq!(some_listener,listener);
         assert_eq!(some_connection,connection);
     });
@@ -460,6 +504,30 @@ fn test_heart_bt_int() {
         });
     }
 
+    //Make sure logging in with a heart_bt_int the listener doesn't allow is rejected.
+    for heart_bt_int in vec![0,5,61] {
+        let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE);
+        let listener_settings = ListenerSettings {
+            heart_bt_int_range: Some(HeartBtIntRange { min: 10, max: 60 }),
+            ..Default::default()
+        };
+        let (mut test_clients,mut engine,_,connections) = TestStream::setup_test_clients_with_listener_settings(1,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,engine_builder,listener_settings,build_dictionary());
+
+        let mut logon_message = new_logon_message();
+        logon_message.sender_comp_id = CLIENT_SENDER_COMP_ID.to_vec();
+        logon_message.target_comp_id = CLIENT_TARGET_COMP_ID.to_vec();
+        logon_message.heart_bt_int = heart_bt_int;
+        test_clients[0].send_message(logon_message);
+
+        let message = test_clients[0].recv_message::<Logout>();
+        assert_eq!(message.text,b"HeartBtInt must be between 10 and 60".to_vec());
+
+        engine_poll_event!(engine,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+            assert_eq!(terminated_connection,connections[0]);
+            assert!(if let ConnectionTerminatedReason::LogonHeartBtIntOutOfRangeError = reason { true } else { false });
+        });
+    }
+
     //Make sure requested heart_bt_int is respected.
     for heart_bt_int in vec![3,7] {
         let (mut test_client,mut engine,listener,connection) = TestStream::setup_test_client(build_dictionary());
@@ -470,7 +538,7 @@ fn test_heart_bt_int() {
         logon_message.heart_bt_int = heart_bt_int;
         test_client.send_message(logon_message);
 
-        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(some_listener,some_connection,logon_message) => {
+        engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: logon_message, address: _ }) => {
             assert_eq!(some_listener,listener);
             assert_eq!(some_connection,connection);
             assert_eq!(logon_message.msg_seq_num,1);
@@ -484,15 +552,140 @@ fn test_heart_bt_int() {
 
         let _ = test_client.recv_message::<Logon>();
 
-        //Wait a moment and make sure no Heartbeat messages have been sent.
-        thread::sleep(Duration::from_millis(heart_bt_int as u64 * 1000 / 2));
+        //Advance time a moment and make sure no Heartbeat messages have been sent.
+        test_client.advance_clock(Duration::from_millis(heart_bt_int as u64 * 1000 / 2));
         assert!(test_client.try_recv_fixt_message(Duration::from_secs(1)).is_none());
 
-        //Wait another moment and make sure a Heartbeat message has been sent.
-        thread::sleep(Duration::from_millis(heart_bt_int as u64 * 1000 / 2 + 200));
+        //Advance time another moment and make sure a Heartbeat message has been sent. Go far
+        //enough past HeartBtInt that the TestRequest is due too.
+        test_client.advance_clock(Duration::from_millis(heart_bt_int as u64 * 1000 / 2 + 500));
         let _ = test_client.recv_message::<Heartbeat>();
 
         //Make sure a TestRequest is also sent after the Heartbeat.
         let _ = test_client.recv_message::<TestRequest>();
     }
 }
+
+#[test]
+fn test_session_provisioner() {
+    define_dictionary!(
+        Logon,
+        Logout,
+    );
+
+    //Accept known usernames and reject everyone else. Logons without a username are left to the
+    //application.
+    struct UsernameProvisioner;
+    impl SessionProvisioner for UsernameProvisioner {
+        fn on_logon(&mut self,request: &LogonRequest) -> ProvisioningDecision {
+            if request.message.username.is_empty() {
+                ProvisioningDecision::Defer
+            }
+            else if request.message.username == b"known" {
+                ProvisioningDecision::Accept(SessionSettings::new(request.message))
+            }
+            else {
+                ProvisioningDecision::Reject(Some(b"Unknown username".to_vec()))
+            }
+        }
+    }
+
+    let engine_builder = EngineBuilder::new(build_dictionary(),MAX_MESSAGE_SIZE).session_provisioner(UsernameProvisioner);
+    let (mut test_clients,mut engine,listener,connections) = TestStream::setup_test_clients_with_builder(3,FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,engine_builder,build_dictionary());
+
+    fn send_logon(test_client: &mut TestStream,username: &[u8]) {
+        let mut logon_message = new_logon_message();
+        logon_message.sender_comp_id = CLIENT_SENDER_COMP_ID.to_vec();
+        logon_message.target_comp_id = CLIENT_TARGET_COMP_ID.to_vec();
+        logon_message.username = username.to_vec();
+        test_client.send_message(logon_message);
+    }
+
+    //Known username is approved without the application seeing EngineEvent::ConnectionLoggingOn.
+    send_logon(&mut test_clients[0],b"known");
+    assert!(engine.poll(Duration::from_millis(500)).is_none());
+    let message = test_clients[0].recv_message::<Logon>();
+    assert_eq!(message.msg_seq_num,1);
+
+    //Unknown username is rejected with a Logout explaining why.
+    send_logon(&mut test_clients[1],b"unknown");
+    engine_poll_event!(engine,EngineEvent::ConnectionTerminated(terminated_connection,reason) => {
+        assert_eq!(terminated_connection,connections[1]);
+        assert!(if let ConnectionTerminatedReason::LogonRejectedError = reason { true } else { false });
+    });
+    let message = test_clients[1].recv_message::<Logout>();
+    assert_eq!(message.text,b"Unknown username".to_vec());
+
+    //Deferred Logon is delivered to the application as usual.
+    send_logon(&mut test_clients[2],b"");
+    engine_poll_event!(engine,EngineEvent::ConnectionLoggingOn(ConnectionLoggingOnData { listener: some_listener, connection: some_connection, message: _, address }) => {
+        assert_eq!(some_listener,listener);
+        assert_eq!(some_connection,connections[2]);
+        assert_eq!(address.ip(),IpAddr::V4(Ipv4Addr::new(127,0,0,1)));
+    });
+}
+
+#[test]
+fn test_listener_settings() {
+    define_dictionary!(
+        Logon,
+    );
+
+    fn add_listener(engine: &mut Engine,settings: ListenerSettings) -> net::SocketAddr {
+        let addr = new_test_address();
+        let _ = engine.add_listener_with_settings(SERVER_SENDER_COMP_ID,addr,settings).unwrap().unwrap();
+
+        addr
+    }
+
+    fn connect_and_expect_accepted(engine: &mut Engine,addr: net::SocketAddr) -> net::TcpStream {
+        let stream = net::TcpStream::connect(addr).unwrap();
+        engine_poll_event!(engine,EngineEvent::ConnectionAccepted(_) => {});
+
+        stream
+    }
+
+    fn connect_and_expect_refused(engine: &mut Engine,addr: net::SocketAddr,expected_reason: ConnectionRefusedReason) {
+        let _stream = net::TcpStream::connect(addr).unwrap();
+        engine_poll_event!(engine,EngineEvent::ConnectionRefused(ConnectionRefusedData { listener: _, address, reason }) => {
+            assert_eq!(address.ip(),IpAddr::V4(Ipv4Addr::new(127,0,0,1)));
+            assert_eq!(reason,expected_reason);
+        });
+    }
+
+    let mut engine = Engine::new(build_dictionary(),MAX_MESSAGE_SIZE).unwrap();
+
+    //Denied address.
+    let addr = add_listener(&mut engine,ListenerSettings {
+        denied_addresses: vec![IpAddr::V4(Ipv4Addr::new(127,0,0,1))],
+        ..Default::default()
+    });
+    connect_and_expect_refused(&mut engine,addr,ConnectionRefusedReason::AddressNotAllowed);
+
+    //Address missing from allow list.
+    let addr = add_listener(&mut engine,ListenerSettings {
+        allowed_addresses: vec![IpAddr::V4(Ipv4Addr::new(10,0,0,1))],
+        ..Default::default()
+    });
+    connect_and_expect_refused(&mut engine,addr,ConnectionRefusedReason::AddressNotAllowed);
+
+    //Too many attempts from the same address.
+    let addr = add_listener(&mut engine,ListenerSettings {
+        connection_attempt_limit: Some(ConnectionAttemptLimit {
+            max_attempts: 2,
+            interval: Duration::from_secs(60),
+        }),
+        ..Default::default()
+    });
+    let _first_stream = connect_and_expect_accepted(&mut engine,addr);
+    let _second_stream = connect_and_expect_accepted(&mut engine,addr);
+    connect_and_expect_refused(&mut engine,addr,ConnectionRefusedReason::TooManyAttempts);
+
+    //Too many connections waiting to logon.
+    let addr = add_listener(&mut engine,ListenerSettings {
+        max_unauthenticated_connections: Some(1),
+        ..Default::default()
+    });
+    let _unauthenticated_stream = connect_and_expect_accepted(&mut engine,addr);
+    connect_and_expect_refused(&mut engine,addr,ConnectionRefusedReason::TooManyUnauthenticatedConnections);
+}
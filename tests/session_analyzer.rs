@@ -0,0 +1,109 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use chrono::{Duration,TimeZone};
+use chrono::datetime::DateTime;
+use chrono::offset::utc::UTC;
+
+#[macro_use]
+mod common;
+use common::{CLIENT_SENDER_COMP_ID,CLIENT_TARGET_COMP_ID,new_logon_message};
+use fix_rs::dictionary::messages::{Heartbeat,Logon,ResendRequest,SequenceReset};
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::fixt::session_analyzer::SessionAnalyzer;
+
+fn at(milliseconds: i64) -> DateTime<UTC> {
+    UTC.ymd(2017,1,1).and_hms(0,0,0) + Duration::milliseconds(milliseconds)
+}
+
+macro_rules! add_message {
+    ( $analyzer:ident, $milliseconds:expr, $message:ident ) => {{
+        //Every message takes 2ms to arrive.
+        $message.sending_time = at($milliseconds - 2);
+        $analyzer.add_message(at($milliseconds),&$message);
+    }};
+}
+
+#[test]
+fn test_session_report() {
+    let mut analyzer = SessionAnalyzer::new();
+
+    //Client logs on and later asks for a resend after noticing a gap.
+    let mut client_logon = new_fixt_message!(FROM_CLIENT Logon);
+    client_logon.heart_bt_int = 5;
+    add_message!(analyzer,0,client_logon);
+
+    //Server responds and heartbeats regularly until it goes quiet and skips MsgSeqNums 4 and 5.
+    let mut server_logon = new_logon_message();
+    add_message!(analyzer,1,server_logon);
+    for &(msg_seq_num,milliseconds) in &[(2,5001),(3,10001),(6,20001)] {
+        let mut heartbeat = new_fixt_message!(Heartbeat);
+        heartbeat.msg_seq_num = msg_seq_num;
+        add_message!(analyzer,milliseconds,heartbeat);
+    }
+
+    let mut resend_request = new_fixt_message!(FROM_CLIENT ResendRequest);
+    resend_request.msg_seq_num = 2;
+    resend_request.begin_seq_no = 4;
+    resend_request.end_seq_no = 0;
+    add_message!(analyzer,20002,resend_request);
+
+    //Only MsgSeqNum 4 is gap filled so 5 stays missing.
+    let mut sequence_reset = new_fixt_message!(SequenceReset);
+    sequence_reset.msg_seq_num = 4;
+    sequence_reset.gap_fill_flag = true;
+    sequence_reset.new_seq_no = 5;
+    sequence_reset.set_is_poss_dup(true);
+    add_message!(analyzer,20003,sequence_reset);
+
+    let reports = analyzer.report();
+    assert_eq!(reports.len(),2);
+
+    let client_report = &reports[0];
+    assert_eq!(client_report.sender_comp_id,CLIENT_SENDER_COMP_ID);
+    assert_eq!(client_report.target_comp_id,CLIENT_TARGET_COMP_ID);
+    assert_eq!(client_report.message_count,2);
+    assert_eq!(client_report.resend_requests,1);
+    assert!(client_report.gaps.is_empty());
+    assert_eq!(client_report.missed_heartbeats,1);
+
+    let server_report = &reports[1];
+    assert_eq!(server_report.sender_comp_id,CLIENT_TARGET_COMP_ID);
+    assert_eq!(server_report.message_count,5);
+    assert_eq!(server_report.gaps,vec![5..6]);
+    assert_eq!(server_report.gap_fills,1);
+    assert_eq!(server_report.poss_dup_messages,1);
+    assert_eq!(server_report.resend_requests,0);
+    assert_eq!(server_report.heart_bt_int,Some(5));
+    assert_eq!(server_report.missed_heartbeats,1);
+
+    let heartbeat_intervals = server_report.heartbeat_intervals.unwrap();
+    assert_eq!(heartbeat_intervals.count,2);
+    assert_eq!(heartbeat_intervals.min,Duration::milliseconds(5000));
+    assert_eq!(heartbeat_intervals.p50,Duration::milliseconds(5000));
+    assert_eq!(heartbeat_intervals.max,Duration::milliseconds(10000));
+
+    let transit_latency = server_report.transit_latency.unwrap();
+    assert_eq!(transit_latency.count,5);
+    assert_eq!(transit_latency.min,Duration::milliseconds(2));
+    assert_eq!(transit_latency.max,Duration::milliseconds(2));
+}
@@ -0,0 +1,83 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate fix_rs;
+
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+
+use fix_rs::fix_version::FIXVersion;
+use fix_rs::fixt::engine::ConnectionBuilder;
+use fix_rs::fixt::message_store::{FileMessageStore,MessageStore};
+use fix_rs::fixt::session_id::SessionID;
+use fix_rs::message_version::MessageVersion;
+
+#[test]
+fn test_display() {
+    let session_id = SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET");
+    assert_eq!(session_id.to_string(),"FIXT.1.1:SENDER->TARGET");
+
+    let session_id = SessionID::new(FIXVersion::FIX_4_2,b"SENDER",b"TARGET").session_qualifier(b"ORDERS");
+    assert_eq!(session_id.to_string(),"FIX.4.2:SENDER->TARGET:ORDERS");
+}
+
+#[test]
+fn test_equality() {
+    //Every part of the SessionID matters, including the session qualifier.
+    let session_ids = vec![
+        SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET"),
+        SessionID::new(FIXVersion::FIX_4_4,b"SENDER",b"TARGET"),
+        SessionID::new(FIXVersion::FIXT_1_1,b"OTHER",b"TARGET"),
+        SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"OTHER"),
+        SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET").session_qualifier(b"A"),
+        SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET").session_qualifier(b"B"),
+    ];
+    let unique: HashSet<SessionID> = session_ids.iter().cloned().collect();
+    assert_eq!(unique.len(),session_ids.len());
+
+    //ConnectionBuilder uses the same SessionID every time it's built the same way.
+    let connection_builder = ConnectionBuilder::new(FIXVersion::FIXT_1_1,MessageVersion::FIX50SP2,b"SENDER",b"TARGET")
+        .session_qualifier(b"A");
+    assert_eq!(connection_builder.session_id(),session_ids[4]);
+}
+
+#[test]
+fn test_file_name() {
+    let session_id = SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET");
+    assert_eq!(session_id.file_name(),"FIXT.1.1_SENDER_TARGET");
+
+    //Anything that isn't safe in a file name or could be confused with a separator is escaped.
+    let session_id = SessionID::new(FIXVersion::FIXT_1_1,b"A/B",b"C_D").session_qualifier(b"E F%");
+    assert_eq!(session_id.file_name(),"FIXT.1.1_A%2FB_C%5FD_E%20F%25");
+    assert!(SessionID::new(FIXVersion::FIXT_1_1,b"A_B",b"C").file_name() != SessionID::new(FIXVersion::FIXT_1_1,b"A",b"B_C").file_name());
+}
+
+#[test]
+fn test_file_message_store_per_session() {
+    let root_directory = env::temp_dir().join("fix_rs_test_file_message_store_per_session");
+    let _ = fs::remove_dir_all(&root_directory);
+
+    //Sessions sharing a root directory are kept apart.
+    let first_session_id = SessionID::new(FIXVersion::FIXT_1_1,b"SENDER",b"TARGET");
+    let second_session_id = first_session_id.clone().session_qualifier(b"SECOND");
+    {
+        let mut message_store = FileMessageStore::open_session(&root_directory,&first_session_id).unwrap();
+        message_store.set_next_outbound_msg_seq_num(10).unwrap();
+        let mut message_store = FileMessageStore::open_session(&root_directory,&second_session_id).unwrap();
+        message_store.set_next_outbound_msg_seq_num(20).unwrap();
+    }
+
+    assert_eq!(FileMessageStore::open_session(&root_directory,&first_session_id).unwrap().next_outbound_msg_seq_num(),10);
+    assert_eq!(FileMessageStore::open_session(&root_directory,&second_session_id).unwrap().next_outbound_msg_seq_num(),20);
+
+    let _ = fs::remove_dir_all(&root_directory);
+}
@@ -0,0 +1,208 @@
+// Copyright 2017 James Bendig. See the COPYRIGHT file at the top-level
+// directory of this distribution.
+//
+// Licensed under:
+//   the MIT license
+//     <LICENSE-MIT or https://opensource.org/licenses/MIT>
+//   or the Apache License, Version 2.0
+//     <LICENSE-APACHE or https://www.apache.org/licenses/LICENSE-2.0>,
+// at your option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(attr_literals)]
+#![feature(const_fn)]
+
+extern crate chrono;
+#[macro_use]
+extern crate fix_rs;
+#[macro_use]
+extern crate fix_rs_macros;
+extern crate mio;
+extern crate phf;
+
+use std::io::Write;
+
+#[macro_use]
+mod common;
+use common::{MAX_MESSAGE_SIZE,TestStream};
+use fix_rs::dictionary::field_types::generic::{NoneFieldType,StringFieldType};
+use fix_rs::dictionary::field_types::other::SessionRejectReason;
+use fix_rs::dictionary::fields::{EndSeqNo,HeartBtInt,TestReqID};
+use fix_rs::dictionary::messages::{Heartbeat,Logon,Logout,Reject,ResendRequest,SequenceReset,TestRequest};
+use fix_rs::field::Field;
+use fix_rs::field_tag;
+use fix_rs::fixt;
+use fix_rs::fixt::capabilities::{SESSION_REJECT_CAPABILITIES,can_emit_session_reject_reason};
+use fix_rs::fixt::message::FIXTMessage;
+use fix_rs::message::{self,NOT_REQUIRED,REQUIRED};
+use fix_rs::message_version;
+
+define_dictionary!(
+    Heartbeat,
+    Logon,
+    Logout,
+    Reject,
+    ResendRequest,
+    SequenceReset,
+    TestRequest,
+);
+
+define_fields!(
+    BeginSeqNoString: StringFieldType = 7,
+    TestReqIDEmpty: NoneFieldType = 112,
+    UndefinedField: StringFieldType = 9999999,
+);
+
+define_fixt_message!(TestRequestWithUndefinedField: b"1" => {
+    REQUIRED, test_req_id: TestReqID [FIX40..],
+    REQUIRED, undefined: UndefinedField [FIX40..],
+});
+
+define_fixt_message!(TestRequestWithNotRequiredField: b"1" => {
+    NOT_REQUIRED, test_req_id: TestReqID [FIX40..],
+});
+
+define_fixt_message!(TestRequestWithWrongField: b"1" => {
+    REQUIRED, test_req_id: TestReqID [FIX40..],
+    REQUIRED, heart_bt_int: HeartBtInt [FIX40..],
+});
+
+define_fixt_message!(TestRequestWithEmptyField: b"1" => {
+    REQUIRED, test_req_id: TestReqIDEmpty [FIX40..],
+});
+
+define_fixt_message!(TestRequestWithDuplicateField: b"1" => {
+    REQUIRED, test_req_id_1: TestReqID [FIX40..],
+    REQUIRED, test_req_id_2: TestReqID [FIX40..],
+});
+
+define_fixt_message!(ResendRequestWithStringBeginSeqNo: b"2" => {
+    REQUIRED, begin_seq_no: BeginSeqNoString [FIX40..],
+    REQUIRED, end_seq_no: EndSeqNo [FIX40..],
+});
+
+define_fixt_message!(MessageWithInvalidMsgType: b"99999" => {
+    NOT_REQUIRED, test_req_id: TestReqID [FIX40..],
+});
+
+fn expect_session_reject<F: Fn(&mut TestStream)>(session_reject_reason: SessionRejectReason,send_func: F) {
+    //Connect and logon.
+    let (mut test_server,_client,_) = TestStream::setup_test_server_and_logon(build_dictionary());
+
+    //Send the malformed message and make sure the engine rejects it for the expected reason.
+    send_func(&mut test_server);
+    let message = test_server.recv_message::<Reject>();
+    assert_eq!(message.session_reject_reason,Some(session_reject_reason));
+}
+
+//Generates a test for each SessionRejectReason that sends the engine a malformed message and
+//expects a Reject with exactly that reason. Also generates tested_session_reject_reasons() so the
+//list can be compared against SESSION_REJECT_CAPABILITIES.
+macro_rules! session_reject_tests {
+    ( $( $test_name:ident: $reason:ident => $send_func:expr, )* ) => {
+        $(
+            #[test]
+            fn $test_name() {
+                expect_session_reject(SessionRejectReason::$reason,$send_func);
+            }
+        )*
+
+        fn tested_session_reject_reasons() -> Vec<SessionRejectReason> {
+            vec![ $( SessionRejectReason::$reason, )* ]
+        }
+    };
+}
+
+session_reject_tests!(
+    test_invalid_tag_number: InvalidTagNumber => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequestWithUndefinedField);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"test_id".to_vec();
+        message.undefined = b"undefined".to_vec();
+        test_server.send_message(message);
+    },
+    test_required_tag_missing: RequiredTagMissing => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequestWithNotRequiredField);
+        message.msg_seq_num = 2;
+        test_server.send_message(message);
+    },
+    test_tag_not_defined_for_this_message_type: TagNotDefinedForThisMessageType => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequestWithWrongField);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"test_id".to_vec();
+        message.heart_bt_int = 5;
+        test_server.send_message(message);
+    },
+    test_tag_specified_without_a_value: TagSpecifiedWithoutAValue => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequestWithEmptyField);
+        message.msg_seq_num = 2;
+        test_server.send_message(message);
+    },
+    test_value_is_incorrect_for_this_tag: ValueIsIncorrectForThisTag => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(ResendRequest);
+        message.msg_seq_num = 2;
+        message.begin_seq_no = 2;
+        message.end_seq_no = 1;
+        test_server.send_message(message);
+    },
+    test_incorrect_data_format_for_value: IncorrectDataFormatForValue => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(ResendRequestWithStringBeginSeqNo);
+        message.msg_seq_num = 2;
+        message.begin_seq_no = b"-1".to_vec();
+        message.end_seq_no = 0;
+        test_server.send_message(message);
+    },
+    test_comp_id_problem: CompIDProblem => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequest);
+        message.msg_seq_num = 2;
+        message.test_req_id = b"test_id".to_vec();
+        message.sender_comp_id = b"unknown".to_vec();
+        test_server.send_message(message);
+    },
+    test_sending_time_accuracy_problem: SendingTimeAccuracyProblem => |test_server: &mut TestStream| {
+        test_server.send_poss_dup_message_with_future_orig_sending_time(2,new_fixt_message!(TestRequest),chrono::Duration::seconds(1));
+    },
+    test_invalid_msg_type: InvalidMsgType => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(MessageWithInvalidMsgType);
+        message.msg_seq_num = 2;
+        test_server.send_message(message);
+    },
+    test_tag_appears_more_than_once: TagAppearsMoreThanOnce => |test_server: &mut TestStream| {
+        let mut message = new_fixt_message!(TestRequestWithDuplicateField);
+        message.msg_seq_num = 2;
+        message.test_req_id_1 = b"test_id_1".to_vec();
+        message.test_req_id_2 = b"test_id_2".to_vec();
+        test_server.send_message(message);
+    },
+    test_tag_specified_out_of_required_order: TagSpecifiedOutOfRequiredOrder => |test_server: &mut TestStream| {
+        //BeginStr is not the first tag.
+        let message_bytes = b"49=TEST\x018=FIX.4.2\x019=38\x0135=1\x0156=TX\x0134=1\x0152=20090107-18:15:16\x01112=1\x0110=204\x01";
+        test_server.stream.write_all(message_bytes).unwrap();
+    },
+    test_incorrect_num_in_group_count_for_repeating_group: IncorrectNumInGroupCountForRepeatingGroup => |test_server: &mut TestStream| {
+        //Claim two NoHops groups but have zero.
+        let message_bytes = b"8=FIX.4.3\x019=999\x0135=1\x0149=TEST\x0156=TX\x0134=1\x0152=20090107-18:15:16\x01627=2\x01112=1\x0110=204\x01";
+        test_server.stream.write_all(message_bytes).unwrap();
+    },
+    test_other: Other => |test_server: &mut TestStream| {
+        //Message is larger than the engine's maximum message size.
+        let mut message = new_fixt_message!(TestRequest);
+        message.msg_seq_num = 2;
+        message.test_req_id = vec![b'A';MAX_MESSAGE_SIZE as usize + 1];
+        test_server.send_message(message);
+    },
+);
+
+#[test]
+fn test_session_reject_capabilities_are_tested() {
+    //Every SessionRejectReason the engine claims it can emit must have a test above and every
+    //tested reason must be claimed.
+    let tested_reasons = tested_session_reject_reasons();
+    for capability in SESSION_REJECT_CAPABILITIES {
+        assert!(tested_reasons.contains(&capability.reason),"No test for {:?}",capability.reason);
+    }
+    for reason in &tested_reasons {
+        assert!(can_emit_session_reject_reason(reason),"{:?} is tested but not listed as a capability",reason);
+    }
+    assert_eq!(tested_reasons.len(),SESSION_REJECT_CAPABILITIES.len());
+}